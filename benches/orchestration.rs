@@ -0,0 +1,67 @@
+//! Orchestration hot path benchmarks
+//!
+//! Polls fleets of mock adapters through the coordinator and analyzes
+//! opportunities over large optimizer histories. Run with the mock adapters
+//! enabled:
+//!
+//! ```bash
+//! cargo bench --features test-util --bench orchestration
+//! ```
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use depin_orcha::orchestration::coordinator::ProtocolCoordinator;
+use depin_orcha::orchestration::optimizer::{EarningsOptimizer, OptimizerConfig};
+use depin_orcha::protocols::mock::{EarningsCurve, MockAdapter};
+use depin_orcha::protocols::{ResourceKind, ResourceProfile};
+
+/// Resource each benchmark protocol competes for, spread over three pools
+fn profile(index: usize) -> ResourceProfile {
+    let kind = [ResourceKind::Cpu, ResourceKind::Bandwidth, ResourceKind::Storage][index % 3];
+    ResourceProfile::new(kind, 0.0, 100.0)
+}
+
+/// Coordinator over `count` mock adapters with varied earnings
+fn coordinator(count: usize) -> ProtocolCoordinator {
+    let mut coordinator = ProtocolCoordinator::new(10).with_host_cpu_cores(16.0);
+    for index in 0..count {
+        let name = format!("protocol{}", index);
+        let curve = EarningsCurve::Constant(1.0 + (index % 7) as f64);
+        let adapter = MockAdapter::new(&name, curve, 100.0 / count as f64).with_profile(profile(index));
+        coordinator.register_adapter(name, Box::new(adapter));
+    }
+    coordinator
+}
+
+fn bench_poll_all(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("poll_all");
+
+    for count in [10, 50, 200] {
+        let coordinator = coordinator(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &coordinator, |b, coordinator| {
+            b.iter(|| runtime.block_on(coordinator.poll_all()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_analyze_opportunities(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let coordinator = coordinator(20);
+    let mut group = c.benchmark_group("analyze_opportunities");
+
+    for history in [100, 1000] {
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        for _ in 0..history {
+            optimizer.update_metrics(runtime.block_on(coordinator.poll_all()).unwrap());
+        }
+        let metrics = runtime.block_on(coordinator.poll_all()).unwrap();
+
+        group.bench_with_input(BenchmarkId::from_parameter(history), &metrics, |b, metrics| {
+            b.iter(|| optimizer.analyze_opportunities(black_box(metrics)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_poll_all, bench_analyze_opportunities);
+criterion_main!(benches);
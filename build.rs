@@ -0,0 +1,12 @@
+/// Build script
+///
+/// Compiles the remote adapter protobuf contract when the `grpc-adapters`
+/// feature is enabled (requires `protoc` on the build host).
+fn main() {
+    #[cfg(feature = "grpc-adapters")]
+    {
+        println!("cargo:rerun-if-changed=proto/protocol_adapter.proto");
+        tonic_build::compile_protos("proto/protocol_adapter.proto")
+            .expect("Failed to compile proto/protocol_adapter.proto");
+    }
+}
@@ -10,8 +10,8 @@ from datetime import datetime
 import json
 import sys
 
-def create_admin_key(db_path="depin_orcha.db", name="Admin Bootstrap Key", rate_limit=1000):
-    """Create an admin API key and insert into database"""
+def create_admin_key(db_path="depin_orcha.db", name="Admin Bootstrap Key"):
+    """Create an admin API key for the default account and insert into database"""
 
     # Generate API key
     key_uuid = uuid.uuid4().hex
@@ -36,10 +36,10 @@ def create_admin_key(db_path="depin_orcha.db", name="Admin Bootstrap Key", rate_
         # Insert the key
         cursor.execute("""
             INSERT INTO api_keys
-                (key_hash, name, description, created_at, is_active, rate_limit_per_minute, permissions)
+                (key_hash, name, description, created_at, is_active, permissions)
             VALUES
-                (?, ?, ?, ?, 1, ?, ?)
-        """, (key_hash, name, "Bootstrap admin key", now, rate_limit, permissions))
+                (?, ?, ?, ?, 1, ?)
+        """, (key_hash, name, "Bootstrap admin key", now, permissions))
 
         conn.commit()
         key_id = cursor.lastrowid
@@ -51,7 +51,7 @@ def create_admin_key(db_path="depin_orcha.db", name="Admin Bootstrap Key", rate_
         print(f"Key ID: {key_id}")
         print(f"API Key: {api_key}")
         print(f"Name: {name}")
-        print(f"Rate Limit: {rate_limit} requests/minute")
+        print("Rate Limit: admin tier (see [rate_limits] in the config file)")
         print(f"Permissions: {', '.join(json.loads(permissions))}")
         print()
         print("Usage: Add this header to your API requests:")
@@ -75,8 +75,7 @@ if __name__ == "__main__":
     parser = argparse.ArgumentParser(description='Create admin API key for DePIN-Orcha')
     parser.add_argument('--db', default='depin_orcha.db', help='Database file path')
     parser.add_argument('--name', default='Admin Bootstrap Key', help='Key name')
-    parser.add_argument('--rate-limit', type=int, default=1000, help='Rate limit per minute')
 
     args = parser.parse_args()
 
-    create_admin_key(args.db, args.name, args.rate_limit)
+    create_admin_key(args.db, args.name)
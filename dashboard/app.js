@@ -0,0 +1,309 @@
+// DePIN-Orcha dashboard: reads the REST API once, then follows the WebSocket.
+"use strict";
+
+const API = "/api/v1";
+const COLORS = ["#4fb3ff", "#4caf50", "#f5a623", "#b36bff", "#ff5c5c", "#2ed3c6", "#e0e060", "#ff8ad8"];
+const MAX_FEED_ITEMS = 50;
+const MAX_CHART_POINTS = 24 * 12;
+
+const state = {
+  token: sessionStorage.getItem("orcha.token"),
+  earnings: [],
+  socket: null,
+  reconnectDelay: 1000,
+};
+
+const $ = (id) => document.getElementById(id);
+
+// ============================================================================
+// API
+// ============================================================================
+
+async function api(path, options = {}) {
+  const response = await fetch(API + path, {
+    ...options,
+    headers: {
+      "Content-Type": "application/json",
+      ...(state.token ? { Authorization: "Bearer " + state.token } : {}),
+    },
+  });
+  if (response.status === 401 && state.token) {
+    // Bearer tokens are short-lived; the session cookie renews them
+    if (!options.retried && (await renewToken())) {
+      return api(path, { ...options, retried: true });
+    }
+    signOut();
+    throw new Error("Session expired");
+  }
+  const body = await response.json();
+  if (!response.ok) {
+    throw new Error(body.detail || body.title || response.statusText);
+  }
+  return body.data !== undefined ? body.data : body;
+}
+
+async function signIn(username, password) {
+  const response = await fetch("session", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ username, password }),
+  });
+  if (!response.ok) {
+    const body = await response.json().catch(() => ({}));
+    throw new Error(body.detail || body.title || response.statusText);
+  }
+  if (!(await renewToken())) {
+    throw new Error("Sign-in failed");
+  }
+}
+
+// Trade the session cookie for a bearer token; false without a live session
+async function renewToken() {
+  const response = await fetch("session/token", { method: "POST" });
+  if (!response.ok) return false;
+  const token = await response.json();
+  state.token = token.access_token;
+  sessionStorage.setItem("orcha.token", state.token);
+  return true;
+}
+
+function signOut() {
+  if (state.token) {
+    fetch("session", { method: "DELETE" }).catch(() => {});
+  }
+  state.token = null;
+  sessionStorage.removeItem("orcha.token");
+  if (state.socket) {
+    state.socket.onclose = null;
+    state.socket.close();
+    state.socket = null;
+  }
+  setLive(false);
+  $("panels").hidden = true;
+  $("sign-out").hidden = true;
+  $("login").hidden = false;
+}
+
+// ============================================================================
+// RENDERING
+// ============================================================================
+
+function el(tag, attrs = {}, text) {
+  const node = attrs.svg
+    ? document.createElementNS("http://www.w3.org/2000/svg", tag)
+    : document.createElement(tag);
+  for (const [name, value] of Object.entries(attrs)) {
+    if (name !== "svg") node.setAttribute(name, value);
+  }
+  if (text !== undefined) node.textContent = text;
+  return node;
+}
+
+const time = (timestamp) => new Date(timestamp).toLocaleString();
+const usd = (value) => "$" + value.toFixed(2);
+
+function renderEarnings() {
+  const chart = $("earnings-chart");
+  chart.replaceChildren();
+  const points = state.earnings;
+  if (points.length === 0) {
+    chart.append(el("text", { svg: true, x: 10, y: 100 }, "No earnings recorded yet"));
+    return;
+  }
+
+  const max = Math.max(...points.map((p) => p.total_earnings), 0.01);
+  const step = points.length > 1 ? 600 / (points.length - 1) : 0;
+  const coords = points
+    .map((p, i) => `${(i * step).toFixed(1)},${(195 - (p.total_earnings / max) * 180).toFixed(1)}`)
+    .join(" ");
+  chart.append(el("polyline", { svg: true, points: coords }));
+  chart.append(el("text", { svg: true, x: 4, y: 12 }, usd(max)));
+
+  $("total-earnings").textContent = usd(points[points.length - 1].total_earnings) + "/h";
+}
+
+function renderAllocation(allocation) {
+  const pie = $("allocation-pie");
+  const legend = $("allocation-legend");
+  pie.replaceChildren();
+  legend.replaceChildren();
+
+  const entries = Object.entries(allocation).filter(([, percent]) => percent > 0);
+  entries.sort((a, b) => b[1] - a[1]);
+  const total = entries.reduce((sum, [, percent]) => sum + percent, 0);
+
+  let angle = -Math.PI / 2;
+  entries.forEach(([protocol, percent], i) => {
+    const color = COLORS[i % COLORS.length];
+    const sweep = (percent / total) * Math.PI * 2;
+    if (entries.length === 1) {
+      pie.append(el("circle", { svg: true, r: 100, fill: color }));
+    } else {
+      const [x1, y1] = [100 * Math.cos(angle), 100 * Math.sin(angle)];
+      angle += sweep;
+      const [x2, y2] = [100 * Math.cos(angle), 100 * Math.sin(angle)];
+      const large = sweep > Math.PI ? 1 : 0;
+      pie.append(el("path", { svg: true, d: `M0,0 L${x1},${y1} A100,100 0 ${large} 1 ${x2},${y2} Z`, fill: color }));
+    }
+
+    const item = el("li");
+    const swatch = el("span", { class: "swatch" });
+    swatch.style.background = color;
+    item.append(swatch);
+    item.append(document.createTextNode(`${protocol} ${percent.toFixed(1)}%`));
+    legend.append(item);
+  });
+}
+
+function alertItem(alert) {
+  const level = alert.severity >= 0.8 ? "critical" : alert.severity >= 0.5 ? "warning" : "";
+  const item = el("li", { class: level });
+  item.append(el("span", { class: "time" }, time(alert.timestamp)));
+  item.append(document.createTextNode(`${alert.alert_type}: ${alert.message}`));
+  return item;
+}
+
+function renderAlerts(alerts) {
+  const feed = $("alerts");
+  feed.replaceChildren(...alerts.slice(0, MAX_FEED_ITEMS).map(alertItem));
+  if (alerts.length === 0) feed.append(el("li", { class: "empty" }, "No alerts"));
+}
+
+function prependAlert(alert) {
+  const feed = $("alerts");
+  feed.querySelector(".empty")?.remove();
+  feed.prepend(alertItem(alert));
+  while (feed.children.length > MAX_FEED_ITEMS) feed.lastChild.remove();
+}
+
+function reallocationRow(change) {
+  const row = el("tr");
+  row.append(el("td", {}, time(change.timestamp)));
+  row.append(el("td", {}, change.protocol));
+  row.append(el("td", {}, change.old_allocation.toFixed(1) + "%"));
+  row.append(el("td", {}, change.new_allocation.toFixed(1) + "%"));
+  row.append(el("td", { class: change.earnings_impact >= 0 ? "positive" : "negative" }, usd(change.earnings_impact)));
+  return row;
+}
+
+function renderReallocations(changes) {
+  const sorted = [...changes].sort((a, b) => new Date(b.timestamp) - new Date(a.timestamp));
+  $("reallocations").replaceChildren(...sorted.slice(0, MAX_FEED_ITEMS).map(reallocationRow));
+}
+
+function renderUptime(uptime, connected) {
+  const percent = (value) => (value === null || value === undefined ? "–" : value.toFixed(1) + "%");
+  const rows = Object.keys(connected)
+    .sort()
+    .map((protocol) => {
+      const row = el("tr");
+      const periods = uptime[protocol] || {};
+      row.append(el("td", { class: connected[protocol] ? "positive" : "negative" }, protocol));
+      for (const period of ["last_24h", "last_7d", "last_30d"]) {
+        row.append(el("td", {}, percent(periods[period])));
+      }
+      return row;
+    });
+  $("uptime").replaceChildren(...rows);
+}
+
+function setLive(online) {
+  const status = $("live-status");
+  status.className = online ? "online" : "offline";
+  status.textContent = online ? "live" : "offline";
+}
+
+// ============================================================================
+// DATA FLOW
+// ============================================================================
+
+async function refreshAllocation() {
+  const dashboard = await api("/dashboard");
+  renderAllocation(dashboard.current_allocation);
+  renderUptime(dashboard.uptime, dashboard.connection_status);
+}
+
+async function load() {
+  $("login").hidden = true;
+  $("panels").hidden = false;
+  $("sign-out").hidden = false;
+
+  const [history, alerts, reallocations] = await Promise.all([
+    api("/metrics/history?hours=24&interval=5m"),
+    api(`/alerts?limit=${MAX_FEED_ITEMS}`),
+    api(`/reallocation/history?limit=${MAX_FEED_ITEMS}`),
+    refreshAllocation().catch(() => renderAllocation({})),
+  ]);
+  state.earnings = history.metrics;
+  renderEarnings();
+  renderAlerts(alerts.alerts);
+  renderReallocations(reallocations.changes);
+  connect();
+}
+
+function connect() {
+  const scheme = location.protocol === "https:" ? "wss:" : "ws:";
+  // Browsers cannot set headers on a WebSocket; the token goes as a subprotocol
+  const socket = new WebSocket(`${scheme}//${location.host}/ws`, ["bearer", state.token]);
+  state.socket = socket;
+  let opened = false;
+
+  socket.onopen = () => {
+    opened = true;
+    state.reconnectDelay = 1000;
+    setLive(true);
+    for (const topic of ["metrics", "alerts", "reallocations"]) {
+      socket.send(JSON.stringify({ type: "Subscribe", topic }));
+    }
+  };
+
+  socket.onmessage = (event) => {
+    const message = JSON.parse(event.data);
+    switch (message.type) {
+      case "MetricsUpdate":
+        state.earnings.push(message.metrics);
+        if (state.earnings.length > MAX_CHART_POINTS) state.earnings.shift();
+        renderEarnings();
+        refreshAllocation().catch(() => {});
+        break;
+      case "AlertNotification":
+        prependAlert(message.alert);
+        break;
+      case "ReallocationNotification":
+        for (const change of message.changes) {
+          $("reallocations").prepend(reallocationRow(change));
+        }
+        refreshAllocation().catch(() => {});
+        break;
+    }
+  };
+
+  socket.onclose = () => {
+    setLive(false);
+    // A refused handshake usually means the bearer token expired
+    const reconnect = opened
+      ? connect
+      : () => renewToken().then((ok) => (ok ? connect() : signOut()), () => connect());
+    setTimeout(reconnect, state.reconnectDelay);
+    state.reconnectDelay = Math.min(state.reconnectDelay * 2, 30000);
+  };
+}
+
+$("login-form").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  $("login-error").textContent = "";
+  try {
+    await signIn($("username").value, $("password").value);
+    $("password").value = "";
+    await load();
+  } catch (e) {
+    $("login-error").textContent = e.message;
+  }
+});
+
+$("sign-out").addEventListener("click", signOut);
+
+// A session from an earlier visit signs straight in
+(state.token ? Promise.resolve(true) : renewToken())
+  .then((signedIn) => (signedIn ? load() : Promise.reject()))
+  .catch(signOut);
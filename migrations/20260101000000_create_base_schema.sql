@@ -0,0 +1,46 @@
+-- Create Base Schema
+-- The tables the application created at startup before the schema moved to
+-- migrations. IF NOT EXISTS keeps this a no-op on databases that already
+-- have them.
+
+CREATE TABLE IF NOT EXISTS metrics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp DATETIME NOT NULL,
+    total_earnings_per_hour REAL NOT NULL,
+    cpu_percent REAL,
+    memory_percent REAL,
+    bandwidth_percent REAL,
+    storage_percent REAL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS protocol_metrics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    metrics_id INTEGER NOT NULL,
+    protocol_name TEXT NOT NULL,
+    earnings_per_hour REAL NOT NULL,
+    allocation_percent REAL NOT NULL,
+    connected BOOLEAN DEFAULT true,
+    FOREIGN KEY(metrics_id) REFERENCES metrics(id)
+);
+
+CREATE TABLE IF NOT EXISTS reallocations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp DATETIME NOT NULL,
+    protocol_name TEXT NOT NULL,
+    old_allocation REAL NOT NULL,
+    new_allocation REAL NOT NULL,
+    earnings_impact REAL,
+    reason TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp DATETIME NOT NULL,
+    alert_type TEXT NOT NULL,
+    severity REAL NOT NULL,
+    message TEXT NOT NULL,
+    acknowledged BOOLEAN DEFAULT false,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
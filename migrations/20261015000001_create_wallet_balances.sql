@@ -0,0 +1,18 @@
+-- Create Wallet Balances Table
+-- Stores polled on-chain balances of tracked payout addresses
+
+CREATE TABLE IF NOT EXISTS wallet_balances (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    label TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    address TEXT NOT NULL,
+    protocol_name TEXT,
+    symbol TEXT NOT NULL,
+    balance REAL NOT NULL,
+    balance_usd REAL,
+    polled_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_balances_address ON wallet_balances(chain, address, symbol, polled_at);
+CREATE INDEX IF NOT EXISTS idx_wallet_balances_polled ON wallet_balances(polled_at);
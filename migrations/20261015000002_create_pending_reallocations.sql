@@ -0,0 +1,16 @@
+-- Create Pending Reallocations Table
+-- Stores proposed allocation plans awaiting operator confirmation
+
+CREATE TABLE IF NOT EXISTS pending_reallocations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at TIMESTAMP NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    plan_json TEXT NOT NULL,
+    estimated_improvement REAL NOT NULL,
+    net_benefit REAL NOT NULL,
+    confidence REAL NOT NULL,
+    decided_at TIMESTAMP,
+    note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_reallocations_status ON pending_reallocations(status, created_at);
@@ -0,0 +1,10 @@
+-- Add Alert Fingerprints
+-- Deduplicates repeating alerts and tracks when their condition clears
+
+ALTER TABLE alerts ADD COLUMN fingerprint TEXT;
+ALTER TABLE alerts ADD COLUMN occurrences INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE alerts ADD COLUMN last_seen_at DATETIME;
+ALTER TABLE alerts ADD COLUMN resolved BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE alerts ADD COLUMN resolved_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_alerts_fingerprint ON alerts(fingerprint, resolved);
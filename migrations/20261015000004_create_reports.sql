@@ -0,0 +1,13 @@
+-- Create Reports Table
+-- Stores generated performance reports for later download
+
+CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    generated_at TIMESTAMP NOT NULL,
+    period_start TIMESTAMP NOT NULL,
+    period_end TIMESTAMP NOT NULL,
+    total_earnings REAL NOT NULL,
+    report_json TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reports_generated_at ON reports(generated_at);
@@ -0,0 +1,11 @@
+-- Create Protocol Credentials Table
+-- Stores adapter credentials encrypted with the vault master key (AES-256-GCM)
+
+CREATE TABLE IF NOT EXISTS protocol_credentials (
+    protocol TEXT PRIMARY KEY,
+    fields TEXT NOT NULL,
+    nonce BLOB NOT NULL,
+    ciphertext BLOB NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    rotated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,46 @@
+-- Create Accounts Table
+-- Accounts own API keys, protocol credentials, and everything the orchestrator
+-- records for them. Existing rows belong to the default account (id 1).
+
+CREATE TABLE IF NOT EXISTS accounts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    is_active BOOLEAN NOT NULL DEFAULT 1
+);
+
+INSERT OR IGNORE INTO accounts (id, name) VALUES (1, 'default');
+
+ALTER TABLE api_keys ADD COLUMN account_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE metrics ADD COLUMN account_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE reallocations ADD COLUMN account_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE pending_reallocations ADD COLUMN account_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE alerts ADD COLUMN account_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE reports ADD COLUMN account_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE wallet_balances ADD COLUMN account_id INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_account ON api_keys(account_id);
+CREATE INDEX IF NOT EXISTS idx_metrics_account_timestamp ON metrics(account_id, timestamp);
+CREATE INDEX IF NOT EXISTS idx_reallocations_account_timestamp ON reallocations(account_id, timestamp);
+CREATE INDEX IF NOT EXISTS idx_pending_reallocations_account ON pending_reallocations(account_id, status);
+CREATE INDEX IF NOT EXISTS idx_alerts_account_fingerprint ON alerts(account_id, fingerprint, resolved);
+CREATE INDEX IF NOT EXISTS idx_reports_account_generated_at ON reports(account_id, generated_at);
+CREATE INDEX IF NOT EXISTS idx_wallet_balances_account ON wallet_balances(account_id, chain, address, symbol, polled_at);
+
+-- Credentials are keyed by account and protocol
+CREATE TABLE protocol_credentials_new (
+    account_id INTEGER NOT NULL DEFAULT 1,
+    protocol TEXT NOT NULL,
+    fields TEXT NOT NULL,
+    nonce BLOB NOT NULL,
+    ciphertext BLOB NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    rotated_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (account_id, protocol)
+);
+
+INSERT INTO protocol_credentials_new (account_id, protocol, fields, nonce, ciphertext, created_at, rotated_at)
+SELECT 1, protocol, fields, nonce, ciphertext, created_at, rotated_at FROM protocol_credentials;
+
+DROP TABLE protocol_credentials;
+ALTER TABLE protocol_credentials_new RENAME TO protocol_credentials;
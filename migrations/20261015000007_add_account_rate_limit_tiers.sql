@@ -0,0 +1,10 @@
+-- Rate Limit Tiers
+-- Request budgets are set per account tier in the `[rate_limits]` config
+-- section instead of per API key. The default (operator) account gets the
+-- admin tier; other accounts start on the free tier.
+
+ALTER TABLE accounts ADD COLUMN tier TEXT NOT NULL DEFAULT 'free';
+
+UPDATE accounts SET tier = 'admin' WHERE id = 1;
+
+ALTER TABLE api_keys DROP COLUMN rate_limit_per_minute;
@@ -0,0 +1,18 @@
+-- Create Idempotency Keys Table
+-- Remembers the response to each mutating request sent with an
+-- `Idempotency-Key` header, so a retried request is answered from here
+-- instead of being executed again. Rows without a status are in flight.
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL DEFAULT 1,
+    idempotency_key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    status_code INTEGER,
+    content_type TEXT,
+    response_body BLOB,
+    UNIQUE (account_id, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys(created_at);
@@ -0,0 +1,13 @@
+-- Add Alert Protocols
+-- Records which protocol an alert concerns so alerts can be filtered by
+-- protocol, and indexes the columns alert and reallocation history pages on.
+
+ALTER TABLE alerts ADD COLUMN protocol TEXT;
+
+-- Protocol alerts carry the protocol name after the fingerprint's type
+UPDATE alerts SET protocol = substr(fingerprint, length('protocol_degraded:') + 1)
+WHERE fingerprint LIKE 'protocol_degraded:%';
+
+CREATE INDEX IF NOT EXISTS idx_alerts_account_timestamp ON alerts(account_id, timestamp);
+CREATE INDEX IF NOT EXISTS idx_alerts_account_protocol ON alerts(account_id, protocol, timestamp);
+CREATE INDEX IF NOT EXISTS idx_reallocations_account_protocol ON reallocations(account_id, protocol_name, timestamp);
@@ -0,0 +1,11 @@
+-- Add Query Indexes
+-- Indexes for the lookups that otherwise scan whole tables: retention and
+-- archival by timestamp across accounts, the metrics/protocol_metrics join,
+-- and unacknowledged alerts. rate_limit_log(api_key_id, window_start) is
+-- already covered by idx_rate_limit_key_window.
+
+CREATE INDEX IF NOT EXISTS idx_metrics_timestamp ON metrics(timestamp);
+CREATE INDEX IF NOT EXISTS idx_protocol_metrics_metrics ON protocol_metrics(metrics_id, protocol_name);
+CREATE INDEX IF NOT EXISTS idx_alerts_acknowledged ON alerts(acknowledged, created_at);
+CREATE INDEX IF NOT EXISTS idx_reallocations_timestamp ON reallocations(timestamp);
+CREATE INDEX IF NOT EXISTS idx_rate_limit_key_window ON rate_limit_log(api_key_id, window_start);
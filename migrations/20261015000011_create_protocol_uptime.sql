@@ -0,0 +1,16 @@
+-- Create Protocol Uptime Table
+-- One row per stretch of time a protocol was observed connected or
+-- disconnected. ended_at is the last poll that saw the state, so time the
+-- orchestrator was not running counts as untracked rather than as up or down.
+
+CREATE TABLE IF NOT EXISTS protocol_uptime (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL DEFAULT 1,
+    protocol_name TEXT NOT NULL,
+    connected BOOLEAN NOT NULL,
+    started_at TIMESTAMP NOT NULL,
+    ended_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_protocol_uptime_account_protocol ON protocol_uptime(account_id, protocol_name);
+CREATE INDEX IF NOT EXISTS idx_protocol_uptime_account_ended ON protocol_uptime(account_id, ended_at);
@@ -0,0 +1,15 @@
+-- Create Protocol Metric Values Table
+-- Protocol-specific metrics an adapter reports alongside earnings (e.g. Storj
+-- satellite audit, suspension, and online scores), stored with the metrics
+-- snapshot they were polled in and expired with it.
+
+CREATE TABLE IF NOT EXISTS protocol_metric_values (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    metrics_id INTEGER NOT NULL,
+    protocol_name TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value REAL NOT NULL,
+    FOREIGN KEY(metrics_id) REFERENCES metrics(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_protocol_metric_values_metrics ON protocol_metric_values(metrics_id, protocol_name);
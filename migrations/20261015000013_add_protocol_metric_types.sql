@@ -0,0 +1,7 @@
+-- Add Protocol Metric Types
+-- Stores each protocol-specific metric value with the unit and aggregation
+-- its adapter declares, so stored values can be labeled and rolled up
+-- without consulting the adapter.
+
+ALTER TABLE protocol_metric_values ADD COLUMN unit TEXT NOT NULL DEFAULT '';
+ALTER TABLE protocol_metric_values ADD COLUMN aggregation TEXT NOT NULL DEFAULT 'last';
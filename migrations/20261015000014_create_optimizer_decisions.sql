@@ -0,0 +1,20 @@
+-- Create Optimizer Decisions Table
+-- Records why the optimizer proposed, executed, or declined each plan: the
+-- protocol scores it worked from, every candidate move with its rejection
+-- reason, and the plan it settled on. Reallocations reference the decision
+-- that made them.
+
+CREATE TABLE IF NOT EXISTS optimizer_decisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMP NOT NULL,
+    outcome TEXT NOT NULL,
+    reason TEXT,
+    pending_reallocation_id INTEGER,
+    decision_json TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_optimizer_decisions_account ON optimizer_decisions(account_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_optimizer_decisions_pending ON optimizer_decisions(pending_reallocation_id);
+
+ALTER TABLE reallocations ADD COLUMN decision_id INTEGER;
@@ -0,0 +1,10 @@
+-- Add Reallocation Outcomes
+-- Records the earnings change observed after each executed reallocation and
+-- how far it missed the predicted improvement, so the optimizer can discount
+-- confidence in moves it has mispredicted before.
+
+ALTER TABLE reallocations ADD COLUMN realized_impact REAL;
+ALTER TABLE reallocations ADD COLUMN impact_error REAL;
+ALTER TABLE reallocations ADD COLUMN evaluated_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_reallocations_evaluated ON reallocations(account_id, evaluated_at);
@@ -0,0 +1,21 @@
+-- Create Request Log Table
+-- Access log written by the API when `[access_log] sink = "database"`: one
+-- row per request with its outcome, latency, and the API key that made it.
+-- Headers, query strings, and bodies are redacted before they are stored.
+
+CREATE TABLE IF NOT EXISTS request_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TIMESTAMP NOT NULL,
+    request_id TEXT,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    query TEXT,
+    status INTEGER NOT NULL,
+    latency_ms REAL NOT NULL,
+    api_key_id INTEGER,
+    account_id INTEGER,
+    headers_json TEXT NOT NULL,
+    body TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_request_log_timestamp ON request_log(timestamp);
@@ -0,0 +1,7 @@
+-- Add API Key Rotation
+-- Rotating a key replaces its hash in place. The previous hash is kept, and
+-- still accepted, until previous_key_expires_at so clients can switch to the
+-- new secret without downtime.
+
+ALTER TABLE api_keys ADD COLUMN previous_key_hash TEXT;
+ALTER TABLE api_keys ADD COLUMN previous_key_expires_at TIMESTAMP;
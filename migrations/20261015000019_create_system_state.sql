@@ -0,0 +1,9 @@
+-- Create System State Table
+-- Key/value markers for one-time setup steps, e.g. `initialized_at` once the
+-- bootstrap admin key has been generated.
+
+CREATE TABLE IF NOT EXISTS system_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
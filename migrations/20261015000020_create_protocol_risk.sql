@@ -0,0 +1,17 @@
+-- Create Protocol Risk Table
+-- Volatility of each protocol's hourly earnings over the trailing risk
+-- window, refreshed by the optimization task. One row per protocol, replaced
+-- on every refresh.
+
+CREATE TABLE IF NOT EXISTS protocol_risk (
+    account_id INTEGER NOT NULL DEFAULT 1,
+    protocol_name TEXT NOT NULL,
+    computed_at TIMESTAMP NOT NULL,
+    window_hours INTEGER NOT NULL,
+    samples INTEGER NOT NULL,
+    mean_earnings REAL NOT NULL,
+    std_dev REAL NOT NULL,
+    max_drawdown_percent REAL NOT NULL,
+    risk_score REAL NOT NULL,
+    PRIMARY KEY (account_id, protocol_name)
+);
@@ -0,0 +1,17 @@
+-- Create Notification Rules Table
+-- Routes alerts to notification channels by alert type and severity range.
+-- The first matching rule, lowest priority first, decides an alert's
+-- channels; `channels` is a JSON array of channel names.
+
+CREATE TABLE IF NOT EXISTS notification_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL DEFAULT 1,
+    alert_type TEXT,
+    min_severity REAL NOT NULL DEFAULT 0,
+    max_severity REAL NOT NULL DEFAULT 100,
+    channels TEXT NOT NULL DEFAULT '[]',
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_rules_account ON notification_rules(account_id, priority);
@@ -0,0 +1,14 @@
+-- Create Network Probes Table
+-- Node-level network quality measured by the probe task: the fastest round
+-- trip to the latency endpoint and download/upload throughput in Mbps.
+
+CREATE TABLE IF NOT EXISTS network_probes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL DEFAULT 1,
+    latency_ms REAL NOT NULL,
+    download_mbps REAL NOT NULL,
+    upload_mbps REAL NOT NULL,
+    measured_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_network_probes_measured ON network_probes(account_id, measured_at);
@@ -0,0 +1,22 @@
+-- Create Protocol Instances Table
+-- Several nodes of one protocol (e.g. two Storj nodes) run as separate
+-- instances. Per-protocol rows elsewhere (protocol_metrics, reallocations,
+-- alerts, ...) store the instance id in their protocol_name column; this table
+-- maps each instance id to its protocol type.
+
+CREATE TABLE IF NOT EXISTS protocol_instances (
+    account_id INTEGER NOT NULL DEFAULT 1,
+    instance_id TEXT NOT NULL,
+    protocol_type TEXT NOT NULL,
+    first_seen_at TIMESTAMP NOT NULL,
+    last_seen_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (account_id, instance_id)
+);
+
+-- Instances recorded so far are assumed to be of the type they are named
+-- after until the orchestrator registers them again
+INSERT OR IGNORE INTO protocol_instances (account_id, instance_id, protocol_type, first_seen_at, last_seen_at)
+SELECT m.account_id, pm.protocol_name, pm.protocol_name, MIN(m.timestamp), MAX(m.timestamp)
+FROM protocol_metrics pm
+JOIN metrics m ON m.id = pm.metrics_id
+GROUP BY m.account_id, pm.protocol_name;
@@ -0,0 +1,5 @@
+-- Add API Key Rotation Time
+-- Bearer tokens issued for a key before its secret was rotated are accepted
+-- only until previous_key_expires_at, like the previous secret itself.
+
+ALTER TABLE api_keys ADD COLUMN rotated_at TIMESTAMP;
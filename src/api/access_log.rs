@@ -0,0 +1,477 @@
+/// API Access Log
+///
+/// Records every `/api/v1` request (method, path, status, latency, API key
+/// and request ID) to the `request_log` table or a JSON-lines file, as
+/// chosen with `[access_log] sink`.
+///
+/// Nothing secret reaches the log. Credential headers (`Authorization`,
+/// `X-API-Key`, cookies) are replaced with `[REDACTED]`. So are query and
+/// JSON body fields named like secrets (`api_key`, `auth_token`,
+/// `private_key`, `password`, ...) and values that look like private keys,
+/// bearer tokens or email addresses. Bodies sent to the credential vault
+/// are redacted field by field, and bodies that are neither JSON nor a form
+/// are not recorded.
+use actix_web::{
+    body::MessageBody,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web, Error, HttpMessage,
+};
+use chrono::Utc;
+use futures_util::{future::LocalBoxFuture, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use super::middleware::{ApiKeyInfo, RequestId};
+use crate::db::models::RequestLogRecord;
+use crate::db::queries;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers that carry credentials
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "proxy-authorization", "x-api-key", "cookie", "set-cookie"];
+
+/// Field name fragments that mark a value as secret
+const SENSITIVE_FIELDS: &[&str] = &[
+    "key", "token", "secret", "password", "passphrase", "mnemonic", "seed", "credential", "authorization",
+    "signature", "email",
+];
+
+/// Path prefix whose request bodies hold protocol credentials
+const CREDENTIALS_PATH: &str = "/api/v1/admin/credentials";
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Where access log entries are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogSink {
+    /// The `request_log` table
+    #[default]
+    Database,
+    /// One JSON object per line, appended to `path`
+    File,
+}
+
+/// Access log settings (`[access_log]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    pub sink: AccessLogSink,
+    /// File written by the `file` sink
+    pub path: PathBuf,
+    /// Record (redacted) request bodies
+    pub log_bodies: bool,
+    /// Recorded bodies are cut to this many bytes after redaction
+    pub max_body_bytes: usize,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sink: AccessLogSink::Database,
+            path: PathBuf::from("logs/access.log"),
+            log_bodies: false,
+            max_body_bytes: 4096,
+        }
+    }
+}
+
+impl AccessLogConfig {
+    /// Validate the file path and body limit
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sink == AccessLogSink::File && self.path.as_os_str().is_empty() {
+            return Err("path must be set for the file sink".to_string());
+        }
+        if self.log_bodies && self.max_body_bytes == 0 {
+            return Err("max_body_bytes must be at least 1 when log_bodies is set".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// REDACTION
+// ============================================================================
+
+/// Whether a header, query parameter or JSON field named `name` holds a secret
+fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_FIELDS.iter().any(|fragment| name.contains(fragment))
+}
+
+/// Whether `value` looks like a secret whatever it is called: a 32-byte hex
+/// private key, a JWT, or an email address
+fn is_sensitive_value(value: &str) -> bool {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    let private_key = hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit());
+    let jwt = value.starts_with("eyJ") && value.split('.').count() == 3;
+    let email = !value.contains(char::is_whitespace)
+        && value.split_once('@').is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'));
+    private_key || jwt || email
+}
+
+/// Request headers as a JSON object, with credential headers redacted
+pub fn redact_headers<'a>(headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> Value {
+    let mut redacted = Map::new();
+    for (name, value) in headers {
+        let value = if SENSITIVE_HEADERS.contains(&name) || is_sensitive_value(value) {
+            REDACTED
+        } else {
+            value
+        };
+        redacted.insert(name.to_string(), Value::String(value.to_string()));
+    }
+    Value::Object(redacted)
+}
+
+/// Query string (or form body) with secret parameters redacted
+pub fn redact_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value)) if is_sensitive_name(name) || is_sensitive_value(value) => {
+                format!("{}={}", name, REDACTED)
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Redact secret fields and values of a JSON document in place
+///
+/// With `all_fields` every string and number is treated as secret, for
+/// bodies (like stored credentials) whose field names say nothing.
+pub fn redact_json(value: &mut Value, all_fields: bool) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                let secret = all_fields || is_sensitive_name(name);
+                if secret && !field.is_object() && !field.is_array() {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field, secret);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_json(item, all_fields)),
+        Value::String(s) if all_fields || is_sensitive_value(s) => *s = REDACTED.to_string(),
+        Value::Number(_) if all_fields => *value = Value::String(REDACTED.to_string()),
+        _ => {}
+    }
+}
+
+/// Redacted request body to record, cut to `max_bytes`
+///
+/// JSON and form bodies are redacted; other bodies are only described.
+pub fn redact_body(path: &str, content_type: Option<&str>, body: &[u8], max_bytes: usize) -> Option<String> {
+    if body.is_empty() {
+        return None;
+    }
+    let content_type = content_type.unwrap_or_default();
+    let redacted = if content_type.starts_with("application/x-www-form-urlencoded") {
+        redact_query(&String::from_utf8_lossy(body))
+    } else {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(mut json) => {
+                redact_json(&mut json, path.starts_with(CREDENTIALS_PATH));
+                json.to_string()
+            }
+            Err(_) => return Some(format!("[{} byte body not recorded]", body.len())),
+        }
+    };
+    Some(truncate(redacted, max_bytes))
+}
+
+/// Cut `text` to at most `max_bytes`, on a character boundary
+fn truncate(mut text: String, max_bytes: usize) -> String {
+    if text.len() > max_bytes {
+        let mut end = max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push('…');
+    }
+    text
+}
+
+// ============================================================================
+// SINKS
+// ============================================================================
+
+enum Sink {
+    Database(SqlitePool),
+    File(Mutex<std::fs::File>),
+}
+
+/// Access log writer, registered as app data to enable the middleware
+pub struct AccessLog {
+    config: AccessLogConfig,
+    sink: Sink,
+}
+
+impl AccessLog {
+    /// Create the writer for `config`, opening (and creating) the log file
+    /// for the file sink
+    pub fn new(config: AccessLogConfig, db: SqlitePool) -> std::io::Result<Self> {
+        let sink = match config.sink {
+            AccessLogSink::Database => Sink::Database(db),
+            AccessLogSink::File => {
+                if let Some(parent) = config.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let file = std::fs::OpenOptions::new().create(true).append(true).open(&config.path)?;
+                Sink::File(Mutex::new(file))
+            }
+        };
+        Ok(Self { config, sink })
+    }
+
+    /// Access log settings
+    pub fn config(&self) -> &AccessLogConfig {
+        &self.config
+    }
+
+    /// Write one entry; failures are logged, never returned to the client
+    pub async fn write(&self, record: &RequestLogRecord) {
+        let result = match &self.sink {
+            Sink::Database(db) => queries::store_request_log(db, record).await.map(|_| ()).map_err(|e| e.to_string()),
+            Sink::File(file) => {
+                let line = serde_json::to_string(record).unwrap_or_default();
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                writeln!(file, "{}", line).map_err(|e| e.to_string())
+            }
+        };
+        if let Err(e) = result {
+            log::warn!("⚠️  Failed to write access log entry: {}", e);
+        }
+    }
+}
+
+// ============================================================================
+// MIDDLEWARE
+// ============================================================================
+
+/// Access log middleware
+///
+/// Does nothing unless an [`AccessLog`] is registered as app data. Must run
+/// inside `RequestIdMiddleware` to record the request ID; the API key is
+/// read after the inner services, so it may wrap `AuthMiddleware`.
+pub struct AccessLogMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLogMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AccessLogMiddlewareService<S>;
+    type Future = std::future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        std::future::ready(Ok(AccessLogMiddlewareService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct AccessLogMiddlewareService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLogMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let Some(access_log) = req.app_data::<web::Data<AccessLog>>().map(|log| log.clone().into_inner()) else {
+            return Box::pin(service.call(req));
+        };
+
+        Box::pin(async move {
+            let started = Instant::now();
+            let timestamp = Utc::now().to_rfc3339();
+            let method = req.method().to_string();
+            let path = req.path().to_string();
+            let query = Some(req.query_string()).filter(|q| !q.is_empty()).map(redact_query);
+            let headers = redact_headers(
+                req.headers().iter().filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+            );
+            let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+
+            let mut body = None;
+            if access_log.config().log_bodies {
+                // Record the body, then hand it back to the request
+                let mut payload = req.take_payload();
+                let mut bytes = web::BytesMut::new();
+                while let Some(chunk) = payload.next().await {
+                    bytes.extend_from_slice(&chunk?);
+                }
+                let bytes = bytes.freeze();
+                let content_type = req.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+                body = redact_body(&path, content_type, &bytes, access_log.config().max_body_bytes);
+                req.set_payload(Payload::from(bytes));
+            }
+
+            let result = service.call(req).await;
+            // Errors are raised before a handler runs, without a key to record
+            let (status, key_info) = match &result {
+                Ok(res) => (
+                    res.status(),
+                    res.request().extensions().get::<ApiKeyInfo>().map(|info| (info.principal, info.account_id)),
+                ),
+                Err(e) => (e.as_response_error().status_code(), None),
+            };
+
+            let record = RequestLogRecord {
+                id: None,
+                timestamp,
+                request_id,
+                method,
+                path,
+                query,
+                status: status.as_u16() as i64,
+                latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+                api_key_id: key_info.and_then(|(principal, _)| principal.api_key_id()),
+                account_id: key_info.map(|(_, account_id)| account_id),
+                headers_json: headers.to_string(),
+                body,
+            };
+            access_log.write(&record).await;
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::middleware::RequestIdMiddleware;
+    use actix_web::{test::TestRequest, App, HttpRequest, HttpResponse};
+    use serde_json::json;
+
+    #[test]
+    fn test_redaction() {
+        let headers = redact_headers([
+            ("authorization", "Bearer abc"),
+            ("x-api-key", "orcha_secret"),
+            ("user-agent", "curl/8.0"),
+        ]);
+        assert_eq!(headers, json!({ "authorization": REDACTED, "x-api-key": REDACTED, "user-agent": "curl/8.0" }));
+
+        assert_eq!(redact_query("token=abc&hours=24"), format!("token={}&hours=24", REDACTED));
+
+        let private_key = format!("0x{}", "ab".repeat(32));
+        let mut body = json!({
+            "name": "rig",
+            "auth_token": "abc",
+            "wallet": { "address": "0x00000000000000000000000000000000000000aa", "note": private_key },
+            "contacts": ["ops@example.com"],
+            "credentials": { "user": "alice" },
+            "amount": 5
+        });
+        redact_json(&mut body, false);
+        assert_eq!(
+            body,
+            json!({
+                "name": "rig",
+                "auth_token": REDACTED,
+                "wallet": { "address": "0x00000000000000000000000000000000000000aa", "note": REDACTED },
+                "contacts": [REDACTED],
+                "credentials": { "user": REDACTED },
+                "amount": 5
+            })
+        );
+
+        // Every stored credential field is secret, whatever it is called
+        let credentials = redact_body(
+            "/api/v1/admin/credentials/grass",
+            Some("application/json"),
+            br#"{"user":"alice","pin":1234}"#,
+            4096,
+        );
+        assert_eq!(credentials, Some(json!({ "user": REDACTED, "pin": REDACTED }).to_string()));
+
+        assert_eq!(
+            redact_body("/api/v1/reports", Some("text/plain"), b"hello", 4096),
+            Some("[5 byte body not recorded]".to_string())
+        );
+        assert_eq!(redact_body("/api/v1/reports", None, br#"{"note":"abcdef"}"#, 8), Some("{\"note\":…".to_string()));
+        assert_eq!(redact_body("/api/v1/reports", None, b"", 8), None);
+    }
+
+    #[actix_web::test]
+    async fn test_requests_are_logged() {
+        let pool = crate::db::test_pool().await;
+
+        let config = AccessLogConfig {
+            enabled: true,
+            log_bodies: true,
+            ..Default::default()
+        };
+        let access_log = web::Data::new(AccessLog::new(config, pool.clone()).unwrap());
+        let app = actix_web::test::init_service(
+            App::new().app_data(access_log).service(
+                web::scope("/api/v1")
+                    .wrap(AccessLogMiddleware)
+                    .wrap(RequestIdMiddleware)
+                    .route(
+                        "/keys",
+                        web::post().to(|req: HttpRequest, body: web::Bytes| async move {
+                            req.extensions_mut().insert(ApiKeyInfo::for_account(7));
+                            HttpResponse::Created().body(body)
+                        }),
+                    ),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/api/v1/keys?api_key=orcha_secret&verbose=1")
+            .insert_header(("X-API-Key", "orcha_secret"))
+            .insert_header(("X-Request-Id", "req-1"))
+            .set_json(json!({ "name": "rig", "password": "hunter2" }))
+            .to_request();
+        let res = actix_web::test::call_service(&app, req).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::CREATED);
+        // The handler still received the original body
+        let echoed: Value = actix_web::test::read_body_json(res).await;
+        assert_eq!(echoed["password"], "hunter2");
+
+        let entries = queries::get_recent_request_log(&pool, 10).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!((entry.method.as_str(), entry.path.as_str(), entry.status), ("POST", "/api/v1/keys", 201));
+        assert_eq!(entry.request_id.as_deref(), Some("req-1"));
+        assert_eq!(entry.query.as_deref(), Some("api_key=[REDACTED]&verbose=1"));
+        assert_eq!((entry.api_key_id, entry.account_id), (Some(1), Some(7)));
+        assert!(!entry.headers_json.contains("orcha_secret"));
+        assert_eq!(entry.body.as_deref(), Some(r#"{"name":"rig","password":"[REDACTED]"}"#));
+    }
+}
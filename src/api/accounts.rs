@@ -0,0 +1,256 @@
+/// Account Handlers
+///
+/// Every API key belongs to an account, and recorded metrics, reallocations,
+/// alerts, reports, wallet balances, and credentials are scoped to it. The
+/// account's tier selects its request budgets. The default account (ID 1) is
+/// the operator: only its keys can manage accounts and take database backups.
+use actix_web::{http::StatusCode, web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::auth::{get_api_key_info, insert_api_key};
+use super::middleware::{AccountId, ApiKeyInfo, RateLimitTier};
+use super::models::SuccessResponse;
+use super::problem::ApiError;
+use crate::db::models::AccountRecord;
+use crate::db::queries;
+
+// ============================================================================
+// REQUEST / RESPONSE TYPES
+// ============================================================================
+
+/// Request to create an account
+#[derive(Debug, Deserialize)]
+pub struct CreateAccountRequest {
+    pub name: String,
+    /// Name of the account's first API key (default: "admin")
+    pub key_name: Option<String>,
+    /// Rate limit tier (default: free)
+    #[serde(default)]
+    pub tier: RateLimitTier,
+}
+
+/// Request to change an account's status or tier
+#[derive(Debug, Deserialize)]
+pub struct UpdateAccountRequest {
+    pub is_active: Option<bool>,
+    pub tier: Option<RateLimitTier>,
+}
+
+/// A new account with its first API key, shown only once
+#[derive(Debug, Serialize)]
+pub struct CreateAccountResponse {
+    pub account: AccountRecord,
+    pub api_key: String,
+    pub key: ApiKeyInfo,
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// GET /api/v1/account - The caller's account
+pub async fn get_account(db: web::Data<SqlitePool>, account: AccountId) -> Result<HttpResponse> {
+    let record = queries::get_account(db.get_ref(), account.0)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found(format!("Account {} not found", account.0)))?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(record)))
+}
+
+/// GET /api/v1/admin/accounts - List every account (operator only)
+pub async fn list_accounts(db: web::Data<SqlitePool>, account: AccountId) -> Result<HttpResponse> {
+    require_operator(account)?;
+    let accounts = queries::list_accounts(db.get_ref())
+        .await
+        .map_err(ApiError::from)?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(accounts)))
+}
+
+/// POST /api/v1/admin/accounts - Create an account and its first API key
+/// (operator only)
+pub async fn create_account(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Json<CreateAccountRequest>,
+) -> Result<HttpResponse> {
+    require_operator(account)?;
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("INVALID_NAME", "Account name must not be empty").into());
+    }
+
+    let existing = queries::list_accounts(db.get_ref())
+        .await
+        .map_err(ApiError::from)?;
+    if existing.iter().any(|a| a.name == name) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "ACCOUNT_EXISTS",
+            format!("Account '{}' already exists", name),
+        )
+        .into());
+    }
+
+    let account_id = queries::create_account(db.get_ref(), name, req.tier.as_str())
+        .await
+        .map_err(ApiError::from)?;
+    let permissions = vec!["read".to_string(), "write".to_string()];
+    let (api_key, key_id) = insert_api_key(
+        db.get_ref(),
+        account_id,
+        req.key_name.as_deref().unwrap_or("admin"),
+        None,
+        None,
+        &permissions,
+    )
+    .await
+    .map_err(|e| ApiError::internal("DATABASE_ERROR", e))?;
+
+    let key = get_api_key_info(db.get_ref(), account_id, key_id).await?;
+    let account = queries::get_account(db.get_ref(), account_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found(format!("Account {} not found", account_id)))?;
+
+    Ok(HttpResponse::Created().json(SuccessResponse::new(CreateAccountResponse {
+        account,
+        api_key,
+        key,
+    })))
+}
+
+/// PUT /api/v1/admin/accounts/{id} - Activate, deactivate, or change the
+/// tier of an account (operator only)
+///
+/// Keys of a deactivated account are rejected; its data is kept. Bearer
+/// tokens keep the tier they were issued with until they expire.
+pub async fn update_account(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    path: web::Path<i64>,
+    req: web::Json<UpdateAccountRequest>,
+) -> Result<HttpResponse> {
+    require_operator(account)?;
+    let id = path.into_inner();
+    if req.is_active.is_none() && req.tier.is_none() {
+        return Err(ApiError::bad_request("NO_UPDATES", "No fields to update").into());
+    }
+    if AccountId(id).is_default() && req.is_active == Some(false) {
+        return Err(ApiError::bad_request(
+            "DEFAULT_ACCOUNT",
+            "The default account cannot be deactivated",
+        )
+        .into());
+    }
+
+    if queries::get_account(db.get_ref(), id)
+        .await
+        .map_err(ApiError::from)?
+        .is_none()
+    {
+        return Err(ApiError::not_found(format!("Account {} not found", id)).into());
+    }
+    if let Some(is_active) = req.is_active {
+        queries::set_account_active(db.get_ref(), id, is_active)
+            .await
+            .map_err(ApiError::from)?;
+    }
+    if let Some(tier) = req.tier {
+        queries::set_account_tier(db.get_ref(), id, tier.as_str())
+            .await
+            .map_err(ApiError::from)?;
+    }
+
+    let record = queries::get_account(db.get_ref(), id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found(format!("Account {} not found", id)))?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(record)))
+}
+
+/// Reject callers outside the default (operator) account with 403
+pub fn require_operator(account: AccountId) -> Result<(), ApiError> {
+    if account.is_default() {
+        Ok(())
+    } else {
+        Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "OPERATOR_ONLY",
+            "Only keys of the default account can do this",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App, HttpMessage};
+
+    fn as_account<R: HttpMessage>(req: R, id: i64) -> R {
+        req.extensions_mut().insert(ApiKeyInfo::for_account(id));
+        req
+    }
+
+    #[actix_web::test]
+    async fn test_account_lifecycle() {
+        let pool = crate::db::test_pool().await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .route("/account", web::get().to(get_account))
+                .route("/admin/accounts", web::get().to(list_accounts))
+                .route("/admin/accounts", web::post().to(create_account))
+                .route("/admin/accounts/{id}", web::put().to(update_account)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/admin/accounts")
+            .set_json(serde_json::json!({ "name": "acme", "tier": "pro" }))
+            .to_request();
+        let resp = test::call_service(&app, as_account(req, 1)).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let id = body["data"]["account"]["id"].as_i64().unwrap();
+        assert_eq!(body["data"]["key"]["account_id"], id);
+        assert_eq!(body["data"]["account"]["tier"], "pro");
+        assert_eq!(body["data"]["key"]["tier"], "pro");
+        assert!(body["data"]["api_key"].as_str().unwrap().starts_with("dpn_"));
+
+        // Names are unique
+        let req = test::TestRequest::post()
+            .uri("/admin/accounts")
+            .set_json(serde_json::json!({ "name": "acme" }))
+            .to_request();
+        let resp = test::call_service(&app, as_account(req, 1)).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        // Tenants see their own account but cannot manage accounts
+        let req = test::TestRequest::get().uri("/account").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, as_account(req, id)).await;
+        assert_eq!(body["data"]["name"], "acme");
+        let req = test::TestRequest::get().uri("/admin/accounts").to_request();
+        let resp = test::call_service(&app, as_account(req, id)).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let req = test::TestRequest::put()
+            .uri(&format!("/admin/accounts/{}", id))
+            .set_json(serde_json::json!({ "is_active": false, "tier": "free" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, as_account(req, 1)).await;
+        assert_eq!(body["data"]["is_active"], false);
+        assert_eq!(body["data"]["tier"], "free");
+
+        let req = test::TestRequest::put()
+            .uri("/admin/accounts/1")
+            .set_json(serde_json::json!({ "is_active": false }))
+            .to_request();
+        let resp = test::call_service(&app, as_account(req, 1)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = test::TestRequest::get().uri("/admin/accounts").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, as_account(req, 1)).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    }
+}
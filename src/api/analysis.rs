@@ -0,0 +1,72 @@
+/// What-if Analysis
+///
+/// Runs stress scenarios against the current metrics and allocation without
+/// touching either, so operators can see how exposed the allocation is to a
+/// protocol's rate collapsing, an outage or a token price crash, and how the
+/// optimizer would respond.
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::export::ExportConfig;
+use super::models::SuccessResponse;
+use super::problem::ApiError;
+use super::OrchestratorState;
+use crate::orchestration::stress::{run_scenario, StressResult, StressScenario};
+
+// ============================================================================
+// MODELS
+// ============================================================================
+
+/// Stress test request; omit `scenarios` to run the default set
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StressTestRequest {
+    #[serde(default)]
+    pub scenarios: Vec<StressScenario>,
+}
+
+/// Stress test response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressTestResponse {
+    pub current_allocation: HashMap<String, f64>,
+    pub baseline_earnings_per_hour: f64,
+    pub scenarios: Vec<StressResult>,
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// POST /api/v1/analysis/stress - Simulate shocks against the current allocation
+pub async fn stress_test(
+    state: OrchestratorState,
+    export: Option<web::Data<ExportConfig>>,
+    req: Option<web::Json<StressTestRequest>>,
+) -> ActixResult<HttpResponse> {
+    let metrics = state.coordinator.get_current_metrics().await?.ok_or_else(ApiError::no_data)?;
+    let metrics = state.reallocation.config().constrained_metrics(&metrics);
+
+    let export = export.map(|c| c.get_ref().clone()).unwrap_or_default();
+    let tokens: HashMap<String, String> =
+        export.tokens.into_iter().map(|(protocol, token)| (protocol, token.symbol)).collect();
+
+    let mut scenarios = req.map(|r| r.into_inner().scenarios).unwrap_or_default();
+    if scenarios.is_empty() {
+        scenarios = StressScenario::defaults(&metrics, &tokens);
+    }
+
+    let optimizer = state.optimizer.snapshot();
+    let results = scenarios
+        .iter()
+        .map(|scenario| run_scenario(scenario, &metrics, &optimizer, &state.reallocation, &tokens))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ApiError::bad_request("INVALID_SCENARIO", e))?;
+
+    let response = StressTestResponse {
+        current_allocation: metrics.allocation_by_protocol.clone(),
+        baseline_earnings_per_hour: metrics.total_earnings_per_hour,
+        scenarios: results,
+    };
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
+}
@@ -1,14 +1,16 @@
 /// API Key Management Handlers
 ///
 /// Endpoints for creating, listing, and managing API keys
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpRequest, HttpResponse, Result};
 use bcrypt;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use crate::api::middleware::ApiKeyInfo;
+use crate::api::middleware::{AccountId, ApiKeyInfo, AuthError, AuthMiddleware, JwtConfig, Principal};
+use crate::api::problem::ApiError;
+use crate::config::Config;
 
 // ============================================================================
 // REQUEST/RESPONSE MODELS
@@ -19,7 +21,6 @@ pub struct CreateApiKeyRequest {
     pub name: String,
     pub description: Option<String>,
     pub expires_in_days: Option<i64>,
-    pub rate_limit_per_minute: Option<i32>,
     pub permissions: Option<Vec<String>>,
 }
 
@@ -39,86 +40,116 @@ pub struct UpdateApiKeyRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub is_active: Option<bool>,
-    pub rate_limit_per_minute: Option<i32>,
     pub permissions: Option<Vec<String>>,
 }
 
+/// Key rotation request; the grace period defaults to `server.key_rotation_grace_secs`
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateApiKeyRequest {
+    pub grace_period_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateApiKeyResponse {
+    pub api_key: String,
+    pub info: ApiKeyInfo,
+    /// When the previous secret stops working (`None` when it was revoked immediately)
+    pub previous_key_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Token exchange request; the key may also be sent as `X-API-Key`
+#[derive(Debug, Default, Deserialize)]
+pub struct TokenRequest {
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+    pub expires_at: DateTime<Utc>,
+}
+
 // ============================================================================
 // HANDLERS
 // ============================================================================
 
-/// Create a new API key
-pub async fn create_api_key(
+/// Exchange an API key for a short-lived bearer token
+pub async fn issue_token(
+    http_req: HttpRequest,
     db: web::Data<SqlitePool>,
-    req: web::Json<CreateApiKeyRequest>,
+    jwt: web::Data<JwtConfig>,
+    req: Option<web::Json<TokenRequest>>,
 ) -> Result<HttpResponse> {
-    // Generate new API key
-    let api_key = format!("dpn_{}", Uuid::new_v4().to_string().replace("-", ""));
-
-    // Hash the API key for storage
-    let key_hash = bcrypt::hash(&api_key, bcrypt::DEFAULT_COST)
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
-
-    // Calculate expiration
-    let expires_at = req
-        .expires_in_days
-        .map(|days| (Utc::now() + Duration::days(days)).to_rfc3339());
+    let api_key = req
+        .and_then(|r| r.into_inner().api_key)
+        .or_else(|| {
+            http_req
+                .headers()
+                .get("X-API-Key")
+                .and_then(|h| h.to_str().ok())
+                .map(str::to_string)
+        })
+        .ok_or(AuthError::MissingApiKey)?;
 
-    // Serialize permissions
-    let permissions = serde_json::to_string(&req.permissions.clone().unwrap_or_default())
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+    let key_info = AuthMiddleware::validate_api_key(db.get_ref(), &api_key).await?;
+    let (access_token, claims) = jwt.issue(&key_info)?;
 
-    let rate_limit = req.rate_limit_per_minute.unwrap_or(60);
-    let now = Utc::now().to_rfc3339();
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: jwt.ttl.as_secs(),
+        expires_at: DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now),
+    }))
+}
 
-    // Insert into database
-    let result = sqlx::query!(
-        r#"
-        INSERT INTO api_keys
-            (key_hash, name, description, created_at, expires_at, is_active, rate_limit_per_minute, permissions)
-        VALUES (?, ?, ?, ?, ?, 1, ?, ?)
-        "#,
-        key_hash,
-        req.name,
-        req.description,
-        now,
-        expires_at,
-        rate_limit,
-        permissions
+/// Create a new API key for the caller's account
+pub async fn create_api_key(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Json<CreateApiKeyRequest>,
+) -> Result<HttpResponse> {
+    let (api_key, key_id) = insert_api_key(
+        db.get_ref(),
+        account.0,
+        &req.name,
+        req.description.as_deref(),
+        req.expires_in_days,
+        &req.permissions.clone().unwrap_or_default(),
     )
-    .execute(db.get_ref())
     .await
-    .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
-
-    let key_id = result.last_insert_rowid();
+    .map_err(|e| ApiError::internal("DATABASE_ERROR", e))?;
 
     // Fetch the created key info
-    let info = get_api_key_info(db.get_ref(), key_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+    let info = get_api_key_info(db.get_ref(), account.0, key_id).await?;
 
     Ok(HttpResponse::Created().json(CreateApiKeyResponse { api_key, info }))
 }
 
-/// List all API keys (excluding key hashes)
-pub async fn list_api_keys(db: web::Data<SqlitePool>) -> Result<HttpResponse> {
+/// List the API keys of the caller's account (excluding key hashes)
+pub async fn list_api_keys(db: web::Data<SqlitePool>, account: AccountId) -> Result<HttpResponse> {
     let records = sqlx::query!(
         r#"
         SELECT
-            id, name, description, created_at, expires_at, last_used_at,
-            is_active, rate_limit_per_minute, permissions
-        FROM api_keys
-        ORDER BY created_at DESC
-        "#
+            k.id AS "id!", k.account_id, k.name, k.description, k.created_at, k.expires_at,
+            k.last_used_at, k.is_active, k.permissions, a.tier
+        FROM api_keys k
+        JOIN accounts a ON a.id = k.account_id
+        WHERE k.account_id = ?
+        ORDER BY k.created_at DESC
+        "#,
+        account.0
     )
     .fetch_all(db.get_ref())
     .await
-    .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+    .map_err(ApiError::from)?;
 
     let keys: Vec<ApiKeyInfo> = records
         .into_iter()
         .map(|r| ApiKeyInfo {
-            id: r.id,
+            principal: Principal::ApiKey(r.id),
+            account_id: r.account_id,
             name: r.name,
             description: r.description,
             created_at: DateTime::from_naive_utc_and_offset(r.created_at, Utc),
@@ -129,7 +160,7 @@ pub async fn list_api_keys(db: web::Data<SqlitePool>) -> Result<HttpResponse> {
                 .last_used_at
                 .map(|l| DateTime::from_naive_utc_and_offset(l, Utc)),
             is_active: r.is_active,
-            rate_limit_per_minute: r.rate_limit_per_minute as i32,
+            tier: r.tier.parse().unwrap_or_default(),
             permissions: serde_json::from_str(&r.permissions).unwrap_or_default(),
         })
         .collect();
@@ -140,11 +171,10 @@ pub async fn list_api_keys(db: web::Data<SqlitePool>) -> Result<HttpResponse> {
 /// Get API key by ID
 pub async fn get_api_key(
     db: web::Data<SqlitePool>,
+    account: AccountId,
     key_id: web::Path<i64>,
 ) -> Result<HttpResponse> {
-    let info = get_api_key_info(db.get_ref(), *key_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorNotFound(e))?;
+    let info = get_api_key_info(db.get_ref(), account.0, *key_id).await?;
 
     Ok(HttpResponse::Ok().json(info))
 }
@@ -152,6 +182,7 @@ pub async fn get_api_key(
 /// Update API key
 pub async fn update_api_key(
     db: web::Data<SqlitePool>,
+    account: AccountId,
     key_id: web::Path<i64>,
     req: web::Json<UpdateApiKeyRequest>,
 ) -> Result<HttpResponse> {
@@ -174,27 +205,20 @@ pub async fn update_api_key(
         params.push(if is_active { "1" } else { "0" }.to_string());
     }
 
-    if let Some(rate_limit) = req.rate_limit_per_minute {
-        updates.push("rate_limit_per_minute = ?");
-        params.push(rate_limit.to_string());
-    }
-
     if let Some(permissions) = &req.permissions {
         updates.push("permissions = ?");
         let perms_json = serde_json::to_string(permissions)
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+            .map_err(|e| ApiError::internal("INTERNAL_ERROR", e.to_string()))?;
         params.push(perms_json);
     }
 
     if updates.is_empty() {
-        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "NO_UPDATES",
-            "message": "No fields to update"
-        })));
+        return Err(ApiError::bad_request("NO_UPDATES", "No fields to update").into());
     }
 
     // Execute update (simplified version - in production use proper parameterized query)
     let key_id_value = *key_id;
+    get_api_key_info(db.get_ref(), account.0, key_id_value).await?;
     sqlx::query(&format!(
         "UPDATE api_keys SET {} WHERE id = ?",
         updates.join(", ")
@@ -202,11 +226,9 @@ pub async fn update_api_key(
     .bind(key_id_value)
     .execute(db.get_ref())
     .await
-    .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+    .map_err(ApiError::from)?;
 
-    let info = get_api_key_info(db.get_ref(), key_id_value)
-        .await
-        .map_err(|e| actix_web::error::ErrorNotFound(e))?;
+    let info = get_api_key_info(db.get_ref(), account.0, key_id_value).await?;
 
     Ok(HttpResponse::Ok().json(info))
 }
@@ -214,12 +236,21 @@ pub async fn update_api_key(
 /// Delete (revoke) API key
 pub async fn delete_api_key(
     db: web::Data<SqlitePool>,
+    account: AccountId,
     key_id: web::Path<i64>,
 ) -> Result<HttpResponse> {
-    sqlx::query!("DELETE FROM api_keys WHERE id = ?", *key_id)
-        .execute(db.get_ref())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+    let result = sqlx::query!(
+        "DELETE FROM api_keys WHERE id = ? AND account_id = ?",
+        *key_id,
+        account.0
+    )
+    .execute(db.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(format!("API key {} not found", key_id)).into());
+    }
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "success": true,
@@ -227,28 +258,155 @@ pub async fn delete_api_key(
     })))
 }
 
+/// Rotate an API key in place
+///
+/// POST /api/v1/admin/keys/{id}/rotate
+///
+/// Generates a new secret for the key, keeping its name, permissions and
+/// tier. The new secret is only returned here; the previous one keeps working
+/// until the grace period ends so clients can switch over without downtime.
+pub async fn rotate_api_key(
+    db: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    account: AccountId,
+    key_id: web::Path<i64>,
+    req: Option<web::Json<RotateApiKeyRequest>>,
+) -> Result<HttpResponse> {
+    let grace_secs = req
+        .and_then(|r| r.into_inner().grace_period_secs)
+        .unwrap_or(config.server.key_rotation_grace_secs);
+
+    let (api_key, previous_key_expires_at) =
+        rotate_api_key_secret(db.get_ref(), account.0, *key_id, Duration::seconds(grace_secs as i64)).await?;
+    let info = get_api_key_info(db.get_ref(), account.0, *key_id).await?;
+
+    Ok(HttpResponse::Ok().json(RotateApiKeyResponse {
+        api_key,
+        info,
+        previous_key_expires_at,
+    }))
+}
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
 
-async fn get_api_key_info(db: &SqlitePool, key_id: i64) -> Result<ApiKeyInfo, String> {
+/// Replace the secret of an account's API key
+///
+/// Returns the new plaintext key and, for a non-zero grace period, when the
+/// previous secret stops being accepted.
+pub async fn rotate_api_key_secret(
+    db: &SqlitePool,
+    account_id: i64,
+    key_id: i64,
+    grace: Duration,
+) -> Result<(String, Option<DateTime<Utc>>), ApiError> {
+    let api_key = format!("dpn_{}", Uuid::new_v4().to_string().replace("-", ""));
+    let key_hash =
+        bcrypt::hash(&api_key, bcrypt::DEFAULT_COST).map_err(|e| ApiError::internal("INTERNAL_ERROR", e.to_string()))?;
+    let now = Utc::now();
+    let previous_key_expires_at = (grace > Duration::zero()).then(|| now + grace);
+    let previous_expiry = previous_key_expires_at.map(|at| at.to_rfc3339());
+    let rotated_at = now.to_rfc3339();
+
+    // The previous hash is dropped outright when there is no grace period
+    let result = sqlx::query!(
+        r#"
+        UPDATE api_keys
+        SET previous_key_hash = CASE WHEN ? IS NULL THEN NULL ELSE key_hash END,
+            previous_key_expires_at = ?,
+            key_hash = ?,
+            rotated_at = ?
+        WHERE id = ? AND account_id = ?
+        "#,
+        previous_expiry,
+        previous_expiry,
+        key_hash,
+        rotated_at,
+        key_id,
+        account_id
+    )
+    .execute(db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(format!("API key {} not found", key_id)));
+    }
+
+    Ok((api_key, previous_key_expires_at))
+}
+
+/// Generate, hash, and store a new API key
+///
+/// Returns the plaintext key (only available at creation time) and its row ID.
+pub async fn insert_api_key(
+    db: &SqlitePool,
+    account_id: i64,
+    name: &str,
+    description: Option<&str>,
+    expires_in_days: Option<i64>,
+    permissions: &[String],
+) -> Result<(String, i64), String> {
+    // Generate new API key
+    let api_key = format!("dpn_{}", Uuid::new_v4().to_string().replace("-", ""));
+
+    // Hash the API key for storage
+    let key_hash = bcrypt::hash(&api_key, bcrypt::DEFAULT_COST).map_err(|e| e.to_string())?;
+
+    // Calculate expiration
+    let expires_at = expires_in_days.map(|days| (Utc::now() + Duration::days(days)).to_rfc3339());
+
+    // Serialize permissions
+    let permissions = serde_json::to_string(permissions).map_err(|e| e.to_string())?;
+    let now = Utc::now().to_rfc3339();
+
+    // Insert into database
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO api_keys
+            (account_id, key_hash, name, description, created_at, expires_at, is_active, permissions)
+        VALUES (?, ?, ?, ?, ?, ?, 1, ?)
+        "#,
+        account_id,
+        key_hash,
+        name,
+        description,
+        now,
+        expires_at,
+        permissions
+    )
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok((api_key, result.last_insert_rowid()))
+}
+
+/// Get one API key of an account
+pub async fn get_api_key_info(
+    db: &SqlitePool,
+    account_id: i64,
+    key_id: i64,
+) -> Result<ApiKeyInfo, ApiError> {
     let record = sqlx::query!(
         r#"
         SELECT
-            id, name, description, created_at, expires_at, last_used_at,
-            is_active, rate_limit_per_minute, permissions
-        FROM api_keys
-        WHERE id = ?
+            k.id, k.account_id, k.name, k.description, k.created_at, k.expires_at,
+            k.last_used_at, k.is_active, k.permissions, a.tier
+        FROM api_keys k
+        JOIN accounts a ON a.id = k.account_id
+        WHERE k.id = ? AND k.account_id = ?
         "#,
-        key_id
+        key_id,
+        account_id
     )
     .fetch_optional(db)
-    .await
-    .map_err(|e| e.to_string())?
-    .ok_or_else(|| "API key not found".to_string())?;
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("API key {} not found", key_id)))?;
 
     Ok(ApiKeyInfo {
-        id: record.id,
+        principal: Principal::ApiKey(record.id),
+        account_id: record.account_id,
         name: record.name,
         description: record.description,
         created_at: DateTime::from_naive_utc_and_offset(record.created_at, Utc),
@@ -259,7 +417,74 @@ async fn get_api_key_info(db: &SqlitePool, key_id: i64) -> Result<ApiKeyInfo, St
             .last_used_at
             .map(|l| DateTime::from_naive_utc_and_offset(l, Utc)),
         is_active: record.is_active,
-        rate_limit_per_minute: record.rate_limit_per_minute as i32,
+        tier: record.tier.parse().unwrap_or_default(),
         permissions: serde_json::from_str(&record.permissions).unwrap_or_default(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rotate_api_key_secret() {
+        let db = crate::db::test_pool().await;
+        let permissions = vec!["admin".to_string()];
+        let (old_key, key_id) = insert_api_key(&db, 1, "ci", None, None, &permissions).await.unwrap();
+
+        // Within the grace period both secrets resolve to the same key
+        let (new_key, expires_at) = rotate_api_key_secret(&db, 1, key_id, Duration::hours(1)).await.unwrap();
+        assert!(expires_at.is_some());
+        assert_ne!(new_key, old_key);
+        let info = AuthMiddleware::validate_api_key(&db, &new_key).await.unwrap();
+        assert_eq!((info.principal, info.name.as_str()), (Principal::ApiKey(key_id), "ci"));
+        assert_eq!(info.permissions, permissions);
+        assert_eq!(
+            AuthMiddleware::validate_api_key(&db, &old_key).await.unwrap().principal,
+            Principal::ApiKey(key_id)
+        );
+
+        // Without one, only the newest secret works
+        let (newest_key, expires_at) = rotate_api_key_secret(&db, 1, key_id, Duration::zero()).await.unwrap();
+        assert_eq!(expires_at, None);
+        assert!(AuthMiddleware::validate_api_key(&db, &newest_key).await.is_ok());
+        assert!(AuthMiddleware::validate_api_key(&db, &new_key).await.is_err());
+        assert!(AuthMiddleware::validate_api_key(&db, &old_key).await.is_err());
+
+        // Keys of other accounts are not found
+        assert!(rotate_api_key_secret(&db, 2, key_id, Duration::zero()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bearer_tokens_end_with_their_key() {
+        let db = crate::db::test_pool().await;
+        let jwt = JwtConfig::new("test-secret", std::time::Duration::from_secs(60));
+        let (api_key, key_id) = insert_api_key(&db, 1, "ci", None, None, &[]).await.unwrap();
+        let info = AuthMiddleware::validate_api_key(&db, &api_key).await.unwrap();
+        let (_, mut claims) = jwt.issue(&info).unwrap();
+        assert!(AuthMiddleware::validate_claims(&db, &claims).await.is_ok());
+
+        // Tokens from before a rotation last as long as the previous secret
+        claims.iat -= 10;
+        rotate_api_key_secret(&db, 1, key_id, Duration::hours(1)).await.unwrap();
+        assert!(AuthMiddleware::validate_claims(&db, &claims).await.is_ok());
+        rotate_api_key_secret(&db, 1, key_id, Duration::zero()).await.unwrap();
+        assert!(matches!(
+            AuthMiddleware::validate_claims(&db, &claims).await,
+            Err(AuthError::InvalidToken)
+        ));
+
+        // Tokens of deactivated and deleted keys are refused
+        let (_, claims) = jwt.issue(&info).unwrap();
+        sqlx::query("UPDATE api_keys SET is_active = 0 WHERE id = ?").bind(key_id).execute(&db).await.unwrap();
+        assert!(matches!(
+            AuthMiddleware::validate_claims(&db, &claims).await,
+            Err(AuthError::InactiveApiKey)
+        ));
+        sqlx::query("DELETE FROM api_keys WHERE id = ?").bind(key_id).execute(&db).await.unwrap();
+        assert!(matches!(
+            AuthMiddleware::validate_claims(&db, &claims).await,
+            Err(AuthError::InvalidToken)
+        ));
+    }
+}
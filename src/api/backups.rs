@@ -0,0 +1,41 @@
+/// Database Backup Handlers
+///
+/// List local backups and take one on demand. Backups hold every account's
+/// data, so only the default (operator) account can use these endpoints.
+/// Restoring replaces the database file, so it is only offered offline
+/// through `depin-orcha backup restore`.
+use actix_web::{web, HttpResponse, Result};
+use sqlx::SqlitePool;
+
+use super::accounts::require_operator;
+use super::middleware::AccountId;
+use super::models::SuccessResponse;
+use super::problem::ApiError;
+use crate::backup::{self, BackupError};
+use crate::config::Config;
+
+/// GET /api/v1/admin/backups - List local backups, newest first
+pub async fn list_backups(config: web::Data<Config>, account: AccountId) -> Result<HttpResponse> {
+    require_operator(account)?;
+    let backups = backup::list_backups(&config.backup.directory)
+        .await
+        .map_err(backup_error)?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(backups)))
+}
+
+/// POST /api/v1/admin/backups - Back up the database now
+pub async fn create_backup(
+    config: web::Data<Config>,
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+) -> Result<HttpResponse> {
+    require_operator(account)?;
+    let backup = backup::create_backup(db.get_ref(), &config.backup)
+        .await
+        .map_err(backup_error)?;
+    Ok(HttpResponse::Created().json(SuccessResponse::new(backup)))
+}
+
+fn backup_error(e: BackupError) -> ApiError {
+    ApiError::internal("BACKUP_FAILED", e.to_string())
+}
@@ -0,0 +1,138 @@
+/// Protocol Credential Handlers
+///
+/// Manage the encrypted adapter credentials held in the secrets vault.
+/// Values are write-only: listing shows field names and rotation times, never
+/// the secrets themselves. Stored credentials are applied when adapters are
+/// instantiated at startup.
+use actix_web::{http::StatusCode, web, HttpResponse, Result};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::middleware::AccountId;
+use super::models::SuccessResponse;
+use super::problem::problem_response;
+use crate::db::models::ProtocolCredentialRecord;
+use crate::db::queries::{
+    delete_protocol_credentials, get_protocol_credentials, list_protocol_credentials,
+};
+use crate::secrets::{Credentials, SecretsError, SecretsVault};
+
+// ============================================================================
+// RESPONSE TYPES
+// ============================================================================
+
+/// Stored credential summary (no secret values)
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialSummaryDto {
+    pub protocol: String,
+    pub fields: Vec<String>,
+    pub created_at: String,
+    pub rotated_at: String,
+}
+
+impl From<&ProtocolCredentialRecord> for CredentialSummaryDto {
+    fn from(record: &ProtocolCredentialRecord) -> Self {
+        Self {
+            protocol: record.protocol.clone(),
+            fields: record.field_names(),
+            created_at: record.created_at.clone(),
+            rotated_at: record.rotated_at.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// GET /api/v1/admin/credentials - List protocols with stored credentials
+pub async fn list_credentials(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+) -> Result<HttpResponse> {
+    match list_protocol_credentials(db.get_ref(), account.0).await {
+        Ok(records) => {
+            let credentials: Vec<CredentialSummaryDto> =
+                records.iter().map(CredentialSummaryDto::from).collect();
+            Ok(HttpResponse::Ok().json(SuccessResponse::new(credentials)))
+        }
+        Err(e) => Ok(vault_error(e.into())),
+    }
+}
+
+/// PUT /api/v1/admin/credentials/{protocol} - Store or rotate credentials
+///
+/// Replaces every stored field for the protocol with the request body, a
+/// flat object of adapter config fields to values.
+pub async fn put_credentials(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    vault: Option<web::Data<SecretsVault>>,
+    path: web::Path<String>,
+    req: web::Json<Credentials>,
+) -> Result<HttpResponse> {
+    let Some(vault) = vault else {
+        return Ok(vault_disabled());
+    };
+    let protocol = path.into_inner();
+    let credentials = req.into_inner();
+
+    if credentials.is_empty() || credentials.values().any(|v| v.is_empty()) {
+        return Ok(problem_response(
+            StatusCode::BAD_REQUEST,
+            "INVALID_CREDENTIALS",
+            "Credentials must contain at least one non-empty field",
+        ));
+    }
+
+    if let Err(e) = vault.store(db.get_ref(), account.0, &protocol, &credentials).await {
+        return Ok(vault_error(e));
+    }
+
+    match get_protocol_credentials(db.get_ref(), account.0, &protocol).await {
+        Ok(Some(record)) => Ok(HttpResponse::Ok().json(SuccessResponse::new(
+            CredentialSummaryDto::from(&record),
+        ))),
+        Ok(None) => Ok(not_found(&protocol)),
+        Err(e) => Ok(vault_error(e.into())),
+    }
+}
+
+/// DELETE /api/v1/admin/credentials/{protocol} - Remove stored credentials
+pub async fn delete_credentials(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let protocol = path.into_inner();
+
+    match delete_protocol_credentials(db.get_ref(), account.0, &protocol).await {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(not_found(&protocol)),
+        Err(e) => Ok(vault_error(e.into())),
+    }
+}
+
+fn not_found(protocol: &str) -> HttpResponse {
+    problem_response(
+        StatusCode::NOT_FOUND,
+        "NOT_FOUND",
+        format!("No credentials stored for {}", protocol),
+    )
+}
+
+fn vault_disabled() -> HttpResponse {
+    problem_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "VAULT_DISABLED",
+        format!(
+            "Set {} or {} to store credentials",
+            crate::secrets::MASTER_KEY_ENV,
+            crate::secrets::MASTER_KEY_FILE_ENV
+        ),
+    )
+}
+
+fn vault_error(e: SecretsError) -> HttpResponse {
+    problem_response(StatusCode::INTERNAL_SERVER_ERROR, "VAULT_ERROR", e.to_string())
+}
@@ -0,0 +1,150 @@
+/// Display Currency
+///
+/// Earnings are computed and stored in USD. Responses that show money
+/// (current metrics, the dashboard and reports) are converted here, at the
+/// API layer, into the currency chosen with `[display] currency`, using the
+/// exchange rates configured next to it.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Currency amounts can be displayed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    #[default]
+    Usd,
+    Eur,
+    Gbp,
+    Btc,
+}
+
+impl Currency {
+    /// ISO 4217 style code (`USD`, `BTC`)
+    pub fn code(self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+            Currency::Btc => "BTC",
+        }
+    }
+
+    /// Decimal places for an amount usually shown with `fiat_decimals` in USD
+    fn decimals(self, fiat_decimals: usize) -> usize {
+        match self {
+            Currency::Btc => 8,
+            _ => fiat_decimals,
+        }
+    }
+}
+
+/// Display settings (`[display]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Currency earnings are shown in
+    pub currency: Currency,
+    /// Units of each currency per USD
+    pub rates: HashMap<Currency, f64>,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            currency: Currency::Usd,
+            rates: HashMap::from([(Currency::Eur, 0.92), (Currency::Gbp, 0.79), (Currency::Btc, 0.000016)]),
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// Check the rates and that the display currency has one
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some((currency, rate)) = self.rates.iter().find(|(_, rate)| !rate.is_finite() || **rate <= 0.0) {
+            return Err(format!("rates.{} must be positive, got {}", currency.code(), rate));
+        }
+        if self.currency != Currency::Usd && !self.rates.contains_key(&self.currency) {
+            return Err(format!("currency {} has no entry in rates", self.currency.code()));
+        }
+        Ok(())
+    }
+
+    /// Units of the display currency per USD
+    pub fn rate(&self) -> f64 {
+        match self.currency {
+            Currency::Usd => 1.0,
+            currency => self.rates.get(&currency).copied().unwrap_or(1.0),
+        }
+    }
+
+    /// Convert a USD amount into the display currency
+    pub fn convert(&self, usd: f64) -> f64 {
+        usd * self.rate()
+    }
+
+    /// Convert USD amounts by protocol into the display currency
+    pub fn convert_map(&self, usd: &HashMap<String, f64>) -> HashMap<String, f64> {
+        usd.iter()
+            .map(|(protocol, amount)| (protocol.clone(), self.convert(*amount)))
+            .collect()
+    }
+
+    /// Format a USD amount in the display currency: `$1.25`, `1.15 EUR`
+    ///
+    /// `decimals` applies to fiat currencies; BTC always shows satoshis.
+    pub fn format(&self, usd: f64, decimals: usize) -> String {
+        let decimals = self.currency.decimals(decimals);
+        match self.currency {
+            Currency::Usd => format!("${:.*}", decimals, usd),
+            currency => format!("{:.*} {}", decimals, self.convert(usd), currency.code()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_and_formatting() {
+        let usd = DisplayConfig::default();
+        assert_eq!(usd.convert(10.0), 10.0);
+        assert_eq!(usd.format(1.5, 2), "$1.50");
+
+        let eur = DisplayConfig {
+            currency: Currency::Eur,
+            ..DisplayConfig::default()
+        };
+        assert!((eur.convert(10.0) - 9.2).abs() < 1e-9);
+        assert_eq!(eur.format(10.0, 2), "9.20 EUR");
+        let converted = eur.convert_map(&HashMap::from([("storj".to_string(), 1.0)]));
+        assert!((converted["storj"] - 0.92).abs() < 1e-9);
+
+        let btc = DisplayConfig {
+            currency: Currency::Btc,
+            ..DisplayConfig::default()
+        };
+        assert_eq!(btc.format(100.0, 2), "0.00160000 BTC");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(DisplayConfig::default().validate().is_ok());
+
+        let missing = DisplayConfig {
+            currency: Currency::Gbp,
+            rates: HashMap::new(),
+        };
+        assert!(missing.validate().unwrap_err().contains("GBP"));
+
+        let negative = DisplayConfig {
+            rates: HashMap::from([(Currency::Eur, -1.0)]),
+            ..DisplayConfig::default()
+        };
+        assert!(negative.validate().is_err());
+    }
+}
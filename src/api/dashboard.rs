@@ -0,0 +1,172 @@
+/// Dashboard Snapshot Cache
+///
+/// The dashboard view is built once per poll by the scheduler's polling task
+/// and served from here, so `GET /api/v1/dashboard` reads a shared snapshot
+/// instead of re-running the optimizer analysis on every request.
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::currency::{Currency, DisplayConfig};
+use super::models::DashboardResponse;
+use crate::orchestration::{AggregatedMetrics, ProtocolUptime};
+use crate::EarningsOptimizer;
+
+/// Seconds until the next scheduled reallocation shown on the dashboard
+const NEXT_REALLOCATION_SECS: u64 = 3600;
+
+/// Latest dashboard view, replaced wholesale on every refresh
+#[derive(Debug, Default)]
+pub struct DashboardCache {
+    current: RwLock<Option<Arc<DashboardResponse>>>,
+}
+
+impl DashboardCache {
+    /// Create an empty snapshot
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Latest dashboard view, if one has been built
+    pub fn get(&self) -> Option<Arc<DashboardResponse>> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Rebuild the view from `metrics` and `uptime` and publish it
+    ///
+    /// Readers keep getting the previous view until the new one is swapped in.
+    pub fn refresh(
+        &self,
+        metrics: &AggregatedMetrics,
+        optimizer: &EarningsOptimizer,
+        uptime: &HashMap<String, ProtocolUptime>,
+    ) -> Arc<DashboardResponse> {
+        let response = Arc::new(build_dashboard(metrics, optimizer, uptime));
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Some(response.clone());
+        response
+    }
+}
+
+/// Dashboard view of `metrics` with the optimizer's target allocation
+pub fn build_dashboard(
+    metrics: &AggregatedMetrics,
+    optimizer: &EarningsOptimizer,
+    uptime: &HashMap<String, ProtocolUptime>,
+) -> DashboardResponse {
+    DashboardResponse {
+        timestamp: Utc::now(),
+        currency: Currency::Usd,
+        total_earnings_per_hour: metrics.total_earnings_per_hour,
+        earnings_by_protocol: metrics.earnings_by_protocol.clone(),
+        current_allocation: metrics.allocation_by_protocol.clone(),
+        optimal_allocation: match optimizer.calculate_optimal_allocation(metrics) {
+            Ok(plan) => plan.allocation,
+            Err(_) => metrics.allocation_by_protocol.clone(),
+        },
+        next_reallocation_in: Some(NEXT_REALLOCATION_SECS),
+        connection_status: metrics.connection_status.clone(),
+        uptime: uptime.clone(),
+        alerts_count: 0, // Would fetch from monitor
+    }
+}
+
+/// Cached USD `view` with its earnings in the display currency
+///
+/// A USD display shares the cached view as-is.
+pub fn in_currency(view: Arc<DashboardResponse>, display: &DisplayConfig) -> Arc<DashboardResponse> {
+    if display.currency == Currency::Usd {
+        return view;
+    }
+    Arc::new(DashboardResponse {
+        currency: display.currency,
+        total_earnings_per_hour: display.convert(view.total_earnings_per_hour),
+        earnings_by_protocol: display.convert_map(&view.earnings_by_protocol),
+        ..(*view).clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::ResourceUtilization;
+    use crate::OptimizerConfig;
+    use std::collections::HashMap;
+
+    fn create_test_metrics(storj_rate: f64) -> AggregatedMetrics {
+        let mut earnings = HashMap::new();
+        earnings.insert("streamr".to_string(), 3.0);
+        earnings.insert("storj".to_string(), storj_rate);
+
+        let mut allocation = HashMap::new();
+        allocation.insert("streamr".to_string(), 50.0);
+        allocation.insert("storj".to_string(), 50.0);
+
+        let mut status = HashMap::new();
+        status.insert("streamr".to_string(), true);
+        status.insert("storj".to_string(), true);
+
+        AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: 3.0 + storj_rate,
+            earnings_by_protocol: earnings,
+            allocation_by_protocol: allocation,
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 50.0,
+                memory_percent: 60.0,
+                bandwidth_percent: 40.0,
+                storage_percent: 30.0,
+            },
+            connection_status: status,
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        }
+    }
+
+    #[test]
+    fn test_refresh_publishes_view() {
+        let cache = DashboardCache::new();
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        assert!(cache.get().is_none());
+
+        let uptime = HashMap::from([(
+            "storj".to_string(),
+            ProtocolUptime { last_24h: Some(99.0), last_7d: Some(98.0), last_30d: None },
+        )]);
+        let built = cache.refresh(&create_test_metrics(4.0), &optimizer, &uptime);
+        let cached = cache.get().unwrap();
+        assert!(Arc::ptr_eq(&built, &cached));
+        assert_eq!(cached.total_earnings_per_hour, 7.0);
+        assert_eq!(cached.current_allocation.len(), 2);
+        assert_eq!(cached.uptime["storj"].last_24h, Some(99.0));
+
+        cache.refresh(&create_test_metrics(6.0), &optimizer, &HashMap::new());
+        assert_eq!(cache.get().unwrap().total_earnings_per_hour, 9.0);
+        // Earlier readers keep the view they were handed
+        assert_eq!(cached.total_earnings_per_hour, 7.0);
+    }
+
+    #[test]
+    fn test_view_in_display_currency() {
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let view = Arc::new(build_dashboard(&create_test_metrics(4.0), &optimizer, &HashMap::new()));
+
+        let usd = in_currency(view.clone(), &DisplayConfig::default());
+        assert!(Arc::ptr_eq(&usd, &view));
+
+        let display = DisplayConfig {
+            currency: Currency::Gbp,
+            rates: HashMap::from([(Currency::Gbp, 0.5)]),
+        };
+        let gbp = in_currency(view, &display);
+        assert_eq!(gbp.currency, Currency::Gbp);
+        assert_eq!(gbp.total_earnings_per_hour, 3.5);
+        assert_eq!(gbp.earnings_by_protocol["storj"], 2.0);
+        assert_eq!(gbp.current_allocation["storj"], 50.0);
+    }
+}
@@ -0,0 +1,216 @@
+/// Metrics Snapshot Diff
+///
+/// Compares the metrics snapshots recorded closest to (at or before) two
+/// points in time: each protocol's earnings rate, allocation and connection
+/// status, and the total earnings rate. Powers "what changed since
+/// yesterday" views on the dashboard.
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::middleware::AccountId;
+use super::models::SuccessResponse;
+use super::problem::ApiError;
+use crate::db::models::ProtocolSnapshotRecord;
+use crate::db::queries;
+
+/// Default lookback when `from` is omitted
+const DEFAULT_DIFF_HOURS: i64 = 24;
+
+// ============================================================================
+// MODELS
+// ============================================================================
+
+/// Metrics diff query parameters (RFC 3339 timestamps)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetricsDiffQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Change of a value between the two snapshots; a side is `null` when the
+/// protocol was not in that snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    pub from: Option<f64>,
+    pub to: Option<f64>,
+    /// `to - from`, when both are known
+    pub change: Option<f64>,
+    /// Change relative to `from`, when both are known and `from` is not 0
+    pub change_percent: Option<f64>,
+}
+
+impl ValueChange {
+    fn new(from: Option<f64>, to: Option<f64>) -> Self {
+        let change = from.zip(to).map(|(from, to)| to - from);
+        let change_percent = from.zip(change).filter(|(from, _)| *from != 0.0).map(|(from, c)| c / from.abs() * 100.0);
+        Self { from, to, change, change_percent }
+    }
+}
+
+/// Connection status in the two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionChange {
+    pub from: Option<bool>,
+    pub to: Option<bool>,
+    pub changed: bool,
+}
+
+/// What changed for one protocol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolDiff {
+    pub earnings_per_hour: ValueChange,
+    pub allocation_percent: ValueChange,
+    pub connected: ConnectionChange,
+}
+
+/// Metrics diff response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsDiffResponse {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// When the snapshots compared were recorded
+    pub from_snapshot_at: String,
+    pub to_snapshot_at: String,
+    pub total_earnings_per_hour: ValueChange,
+    pub protocols: BTreeMap<String, ProtocolDiff>,
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// GET /api/v1/metrics/diff - Change per protocol between two points in time
+pub async fn get_metrics_diff(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    query: web::Query<MetricsDiffQuery>,
+) -> ActixResult<HttpResponse> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::hours(DEFAULT_DIFF_HOURS));
+    if from >= to {
+        return Err(ApiError::bad_request(
+            "INVALID_RANGE",
+            format!("`from` ({}) must be before `to` ({})", from, to),
+        )
+        .into());
+    }
+
+    let before = queries::get_protocol_snapshot_at(db.get_ref(), account.0, from)
+        .await
+        .map_err(ApiError::from)?;
+    let after = queries::get_protocol_snapshot_at(db.get_ref(), account.0, to)
+        .await
+        .map_err(ApiError::from)?;
+    let response = diff_snapshots(&before, &after, from, to)
+        .ok_or_else(|| ApiError::not_found(format!("No metrics recorded at or before {}", from.to_rfc3339())))?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
+}
+
+// ============================================================================
+// DIFF
+// ============================================================================
+
+/// Diff two snapshots' protocol rows; `None` when either snapshot is missing
+pub fn diff_snapshots(
+    before: &[ProtocolSnapshotRecord],
+    after: &[ProtocolSnapshotRecord],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Option<MetricsDiffResponse> {
+    let (first_before, first_after) = (before.first()?, after.first()?);
+    let by_protocol = |rows: &'_ [ProtocolSnapshotRecord]| -> BTreeMap<String, ProtocolSnapshotRecord> {
+        rows.iter().map(|r| (r.protocol_name.clone(), r.clone())).collect()
+    };
+    let (before_rows, after_rows) = (by_protocol(before), by_protocol(after));
+
+    let names: BTreeSet<&String> = before_rows.keys().chain(after_rows.keys()).collect();
+    let protocols = names
+        .into_iter()
+        .map(|name| {
+            let (old, new) = (before_rows.get(name), after_rows.get(name));
+            let connected = |row: Option<&ProtocolSnapshotRecord>| row.map(|r| r.connected.unwrap_or(false));
+            let diff = ProtocolDiff {
+                earnings_per_hour: ValueChange::new(old.map(|r| r.earnings_per_hour), new.map(|r| r.earnings_per_hour)),
+                allocation_percent: ValueChange::new(
+                    old.map(|r| r.allocation_percent),
+                    new.map(|r| r.allocation_percent),
+                ),
+                connected: ConnectionChange {
+                    from: connected(old),
+                    to: connected(new),
+                    changed: connected(old) != connected(new),
+                },
+            };
+            (name.clone(), diff)
+        })
+        .collect();
+
+    Some(MetricsDiffResponse {
+        from,
+        to,
+        from_snapshot_at: first_before.timestamp.clone(),
+        to_snapshot_at: first_after.timestamp.clone(),
+        total_earnings_per_hour: ValueChange::new(
+            Some(first_before.total_earnings_per_hour),
+            Some(first_after.total_earnings_per_hour),
+        ),
+        protocols,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn at(hour: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z").unwrap().with_timezone(&Utc) + Duration::hours(hour)
+    }
+
+    #[test]
+    fn test_value_change() {
+        assert_eq!(
+            ValueChange::new(Some(2.0), Some(3.0)),
+            ValueChange { from: Some(2.0), to: Some(3.0), change: Some(1.0), change_percent: Some(50.0) }
+        );
+        assert_eq!(ValueChange::new(Some(0.0), Some(3.0)).change_percent, None);
+        assert_eq!(ValueChange::new(None, Some(3.0)).change, None);
+    }
+
+    #[tokio::test]
+    async fn test_diff_between_stored_snapshots() {
+        let pool = crate::db::test_pool().await;
+        let store = |hour: i64, earnings: &[(&str, f64)], allocation: &[(&str, f64)]| {
+            let pool = pool.clone();
+            let earnings: HashMap<String, f64> = earnings.iter().map(|(p, v)| (p.to_string(), *v)).collect();
+            let allocation: HashMap<String, f64> = allocation.iter().map(|(p, v)| (p.to_string(), *v)).collect();
+            async move { queries::store_historical_metrics(&pool, 1, at(hour), &earnings, &allocation).await.unwrap() }
+        };
+        store(0, &[("storj", 2.0), ("golem", 1.0)], &[("storj", 60.0), ("golem", 40.0)]).await;
+        store(10, &[("storj", 3.0), ("grass", 0.5)], &[("storj", 70.0), ("grass", 30.0)]).await;
+
+        let before = queries::get_protocol_snapshot_at(&pool, 1, at(5)).await.unwrap();
+        let after = queries::get_protocol_snapshot_at(&pool, 1, at(12)).await.unwrap();
+        let diff = diff_snapshots(&before, &after, at(5), at(12)).unwrap();
+
+        assert_eq!(diff.from_snapshot_at, at(0).to_rfc3339());
+        assert_eq!(diff.to_snapshot_at, at(10).to_rfc3339());
+        assert_eq!(diff.total_earnings_per_hour.change, Some(0.5));
+        let storj = &diff.protocols["storj"];
+        assert_eq!((storj.earnings_per_hour.change, storj.allocation_percent.change), (Some(1.0), Some(10.0)));
+        assert_eq!(storj.earnings_per_hour.change_percent, Some(50.0));
+        // golem dropped out and grass joined
+        assert_eq!(diff.protocols["golem"].earnings_per_hour.to, None);
+        assert_eq!(diff.protocols["grass"].allocation_percent.from, None);
+        assert!(diff.protocols["grass"].connected.changed);
+
+        // Nothing was recorded before the first snapshot
+        let empty = queries::get_protocol_snapshot_at(&pool, 1, at(-1)).await.unwrap();
+        assert!(diff_snapshots(&empty, &after, at(-1), at(12)).is_none());
+        assert!(queries::get_protocol_snapshot_at(&pool, 2, at(12)).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,665 @@
+/// Export Handlers
+///
+/// Dumps per-protocol earnings at daily granularity for tax and accounting
+/// software. Daily amounts are estimated from the average hourly earnings
+/// rate recorded for each day; native token amounts are derived from the
+/// configured token prices. Days follow the `[reporting]` time zone.
+///
+/// Raw metrics are exported separately as NDJSON or CSV, streamed from a
+/// database cursor in chunks so month-long ranges never sit in memory.
+use actix_web::{
+    http::{header, StatusCode},
+    web, HttpResponse, Result,
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::middleware::AccountId;
+use super::models::SuccessResponse;
+use super::problem::problem_response;
+use crate::db::models::{DailyEarningsRecord, MetricsExportRow};
+use crate::db::queries::{get_daily_protocol_earnings, stream_metrics_export};
+use crate::timezone::ReportingTimezone;
+
+/// Default export window when `from` is omitted
+const DEFAULT_EXPORT_DAYS: i64 = 30;
+
+/// Bytes buffered before a metrics export chunk is sent
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Chunks queued ahead of a slow client before reading from the cursor pauses
+const EXPORT_CHANNEL_CHUNKS: usize = 4;
+
+/// Metrics export CSV header
+const METRICS_CSV_HEADER: &str = "timestamp,protocol,earnings_per_hour,allocation_percent,connected,\
+total_earnings_per_hour,cpu_percent,memory_percent,bandwidth_percent,storage_percent\n";
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Native token paid out by a protocol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenPrice {
+    pub symbol: String,
+    pub price_usd: f64,
+}
+
+/// Export configuration: native token per protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    pub tokens: HashMap<String, TokenPrice>,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        let tokens = [
+            ("streamr", "DATA", 0.03),
+            ("storj", "STORJ", 0.5),
+            ("golem", "GLM", 0.3),
+            ("grass", "GRASS", 1.5),
+            ("filecoin", "FIL", 5.0),
+            ("akash", "AKT", 3.0),
+            ("theta", "TFUEL", 0.05),
+            ("ionet", "IO", 2.0),
+            ("hivemapper", "HONEY", 0.03),
+            ("weatherxm", "WXM", 0.8),
+        ]
+        .into_iter()
+        .map(|(protocol, symbol, price_usd)| {
+            (
+                protocol.to_string(),
+                TokenPrice {
+                    symbol: symbol.to_string(),
+                    price_usd,
+                },
+            )
+        })
+        .collect();
+
+        Self { tokens }
+    }
+}
+
+impl ExportConfig {
+    /// Build from the config file
+    ///
+    /// Prices set on protocol sections (`fil_price_usd = 5.0`) override the
+    /// defaults, and `[export.tokens]` entries override both.
+    pub fn from_table(config: &toml::Table) -> Self {
+        let mut export = Self::default();
+
+        let protocols = config.get("protocols").and_then(|p| p.as_table());
+        for (protocol, section) in protocols.into_iter().flatten() {
+            let Some(section) = section.as_table() else {
+                continue;
+            };
+            for (key, value) in section {
+                let (Some(symbol), Some(price_usd)) =
+                    (key.strip_suffix("_price_usd"), value.as_float())
+                else {
+                    continue;
+                };
+                export.tokens.insert(
+                    protocol.clone(),
+                    TokenPrice {
+                        symbol: symbol.to_uppercase(),
+                        price_usd,
+                    },
+                );
+            }
+        }
+
+        let overrides = config
+            .get("export")
+            .and_then(|e| e.get("tokens"))
+            .cloned()
+            .and_then(|t| t.try_into::<HashMap<String, TokenPrice>>().ok());
+        export.tokens.extend(overrides.unwrap_or_default());
+
+        export
+    }
+}
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+/// Export file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+    Ofx,
+}
+
+/// Export query parameters (`from`/`to` are inclusive `YYYY-MM-DD` dates)
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Metrics export format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsExportFormat {
+    /// One JSON object per line
+    #[default]
+    Ndjson,
+    Csv,
+}
+
+impl MetricsExportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Ndjson => "ndjson",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// Metrics export query parameters (`start`/`end` are inclusive RFC 3339 timestamps)
+#[derive(Debug, Deserialize)]
+pub struct MetricsExportQuery {
+    #[serde(default)]
+    pub format: MetricsExportFormat,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// One protocol's earnings for one day
+#[derive(Debug, Clone, Serialize)]
+pub struct EarningsRow {
+    pub date: NaiveDate,
+    pub protocol: String,
+    pub token_symbol: Option<String>,
+    pub token_amount: Option<f64>,
+    pub token_price_usd: Option<f64>,
+    pub amount_usd: f64,
+    pub samples: i64,
+}
+
+/// JSON export body
+#[derive(Debug, Serialize)]
+pub struct EarningsExport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total_usd: f64,
+    pub rows: Vec<EarningsRow>,
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// GET /api/v1/export/earnings - Export daily earnings as CSV, JSON, or OFX
+pub async fn export_earnings(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    config: Option<web::Data<ExportConfig>>,
+    tz: Option<web::Data<ReportingTimezone>>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse> {
+    let tz = tz.map(|tz| *tz.get_ref()).unwrap_or_default();
+    let to = query.to.unwrap_or_else(|| tz.date_of(Utc::now()));
+    let from = query
+        .from
+        .unwrap_or_else(|| to - Duration::days(DEFAULT_EXPORT_DAYS - 1));
+
+    if from > to {
+        return Ok(problem_response(
+            StatusCode::BAD_REQUEST,
+            "INVALID_RANGE",
+            format!("`from` ({}) is after `to` ({})", from, to),
+        ));
+    }
+
+    let records = match get_daily_protocol_earnings(db.get_ref(), account.0, from, to, tz).await {
+        Ok(records) => records,
+        Err(e) => {
+            return Ok(problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DATABASE_ERROR",
+                format!("Failed to load earnings: {}", e),
+            ));
+        }
+    };
+
+    let default_config = ExportConfig::default();
+    let config = config.as_ref().map_or(&default_config, |c| c.get_ref());
+    let rows = build_rows(records, config);
+    let filename = format!("depin-orcha-earnings-{}-{}", from, to);
+
+    Ok(match query.format {
+        ExportFormat::Json => {
+            let total_usd = rows.iter().map(|r| r.amount_usd).sum();
+            HttpResponse::Ok().json(SuccessResponse::new(EarningsExport {
+                from,
+                to,
+                total_usd,
+                rows,
+            }))
+        }
+        ExportFormat::Csv => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(attachment(&filename, "csv"))
+            .body(to_csv(&rows)),
+        ExportFormat::Ofx => HttpResponse::Ok()
+            .content_type("application/x-ofx")
+            .insert_header(attachment(&filename, "ofx"))
+            .body(to_ofx(&rows, from, to)),
+    })
+}
+
+/// GET /api/v1/metrics/export - Stream raw metrics as NDJSON or CSV
+///
+/// The body is sent with chunked transfer encoding while rows are read. A
+/// database error after the first chunk aborts the connection, so clients
+/// should treat a truncated body as a failed export.
+pub async fn export_metrics(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    query: web::Query<MetricsExportQuery>,
+) -> Result<HttpResponse> {
+    let end = query.end.unwrap_or_else(Utc::now);
+    let start = query
+        .start
+        .unwrap_or_else(|| end - Duration::days(DEFAULT_EXPORT_DAYS));
+
+    if start > end {
+        return Ok(problem_response(
+            StatusCode::BAD_REQUEST,
+            "INVALID_RANGE",
+            format!("`start` ({}) is after `end` ({})", start.to_rfc3339(), end.to_rfc3339()),
+        ));
+    }
+
+    let format = query.format;
+    let (mut tx, rx) = mpsc::channel::<Result<web::Bytes, sqlx::Error>>(EXPORT_CHANNEL_CHUNKS);
+    let pool = db.get_ref().clone();
+    tokio::spawn(async move {
+        let mut rows = stream_metrics_export(&pool, account.0, start, end);
+        let mut chunk = match format {
+            MetricsExportFormat::Ndjson => String::new(),
+            MetricsExportFormat::Csv => METRICS_CSV_HEADER.to_string(),
+        };
+
+        while let Some(row) = rows.next().await {
+            match row {
+                Ok(row) => write_metrics_row(&mut chunk, format, &row),
+                Err(e) => {
+                    log::error!("❌ Metrics export failed: {}", e);
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            }
+            // Stop reading once the client has gone away
+            if chunk.len() >= EXPORT_CHUNK_BYTES
+                && tx.send(Ok(std::mem::take(&mut chunk).into())).await.is_err()
+            {
+                return;
+            }
+        }
+        if !chunk.is_empty() {
+            let _ = tx.send(Ok(chunk.into())).await;
+        }
+    });
+
+    let filename = format!(
+        "depin-orcha-metrics-{}-{}",
+        start.format("%Y%m%dT%H%M%SZ"),
+        end.format("%Y%m%dT%H%M%SZ")
+    );
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(attachment(&filename, format.extension()))
+        .streaming(rx))
+}
+
+/// `Content-Disposition` header for a downloaded file
+pub(super) fn attachment(filename: &str, extension: &str) -> (header::HeaderName, String) {
+    (
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}.{}\"", filename, extension),
+    )
+}
+
+// ============================================================================
+// FORMATTING
+// ============================================================================
+
+/// Convert daily averages into earnings rows
+pub fn build_rows(records: Vec<DailyEarningsRecord>, config: &ExportConfig) -> Vec<EarningsRow> {
+    records
+        .into_iter()
+        .filter_map(|record| {
+            let date = NaiveDate::parse_from_str(&record.day, "%Y-%m-%d").ok()?;
+            let amount_usd = record.avg_earnings_per_hour * record.hours;
+            // A price set for the instance wins over the price of its protocol type
+            let token = config
+                .tokens
+                .get(&record.protocol_name)
+                .or_else(|| config.tokens.get(&record.protocol_type));
+
+            Some(EarningsRow {
+                date,
+                token_symbol: token.map(|t| t.symbol.clone()),
+                token_amount: token
+                    .filter(|t| t.price_usd > 0.0)
+                    .map(|t| amount_usd / t.price_usd),
+                token_price_usd: token.map(|t| t.price_usd),
+                protocol: record.protocol_name,
+                amount_usd,
+                samples: record.samples,
+            })
+        })
+        .collect()
+}
+
+/// Render rows as CSV
+pub fn to_csv(rows: &[EarningsRow]) -> String {
+    let mut out =
+        String::from("date,protocol,token_symbol,token_amount,token_price_usd,amount_usd,samples\n");
+
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{:.6},{}",
+            row.date,
+            csv_field(&row.protocol),
+            csv_field(row.token_symbol.as_deref().unwrap_or("")),
+            row.token_amount.map(|a| format!("{:.8}", a)).unwrap_or_default(),
+            row.token_price_usd.map(|p| p.to_string()).unwrap_or_default(),
+            row.amount_usd,
+            row.samples
+        );
+    }
+
+    out
+}
+
+/// Quote a CSV field when it contains separators or quotes
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Append one metrics export row in `format`
+pub fn write_metrics_row(out: &mut String, format: MetricsExportFormat, row: &MetricsExportRow) {
+    match format {
+        MetricsExportFormat::Ndjson => {
+            // Serializing plain strings and numbers cannot fail
+            out.push_str(&serde_json::to_string(row).unwrap_or_default());
+            out.push('\n');
+        }
+        MetricsExportFormat::Csv => {
+            let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&row.timestamp),
+                csv_field(row.protocol_name.as_deref().unwrap_or("")),
+                optional(row.earnings_per_hour),
+                optional(row.allocation_percent),
+                row.connected.map(|c| c.to_string()).unwrap_or_default(),
+                row.total_earnings_per_hour,
+                optional(row.cpu_percent),
+                optional(row.memory_percent),
+                optional(row.bandwidth_percent),
+                optional(row.storage_percent),
+            );
+        }
+    }
+}
+
+/// Render rows as an OFX 1.0.2 bank statement with one credit per row
+pub fn to_ofx(rows: &[EarningsRow], from: NaiveDate, to: NaiveDate) -> String {
+    let now = Utc::now().format("%Y%m%d%H%M%S");
+    let total: f64 = rows.iter().map(|r| r.amount_usd).sum();
+
+    let mut out = String::from(
+        "OFXHEADER:100\nDATA:OFXSGML\nVERSION:102\nSECURITY:NONE\nENCODING:USASCII\n\
+         CHARSET:1252\nCOMPRESSION:NONE\nOLDFILEUID:NONE\nNEWFILEUID:NONE\n\n",
+    );
+    let _ = write!(
+        out,
+        "<OFX>\n<SIGNONMSGSRSV1><SONRS>\n<STATUS><CODE>0<SEVERITY>INFO</STATUS>\n\
+         <DTSERVER>{now}\n<LANGUAGE>ENG\n</SONRS></SIGNONMSGSRSV1>\n\
+         <BANKMSGSRSV1><STMTTRNRS>\n<TRNUID>1\n<STATUS><CODE>0<SEVERITY>INFO</STATUS>\n\
+         <STMTRS>\n<CURDEF>USD\n\
+         <BANKACCTFROM><BANKID>DEPIN-ORCHA<ACCTID>EARNINGS<ACCTTYPE>CHECKING</BANKACCTFROM>\n\
+         <BANKTRANLIST>\n<DTSTART>{}\n<DTEND>{}\n",
+        from.format("%Y%m%d"),
+        to.format("%Y%m%d"),
+    );
+
+    for row in rows {
+        let memo = match (&row.token_amount, &row.token_symbol) {
+            (Some(amount), Some(symbol)) => format!("{:.8} {}", amount, symbol),
+            _ => "Estimated earnings".to_string(),
+        };
+        let _ = write!(
+            out,
+            "<STMTTRN>\n<TRNTYPE>CREDIT\n<DTPOSTED>{}\n<TRNAMT>{:.2}\n<FITID>{}-{}\n\
+             <NAME>{}\n<MEMO>{}\n</STMTTRN>\n",
+            row.date.format("%Y%m%d"),
+            row.amount_usd,
+            row.date.format("%Y%m%d"),
+            row.protocol,
+            ofx_text(&row.protocol),
+            ofx_text(&memo),
+        );
+    }
+
+    let _ = write!(
+        out,
+        "</BANKTRANLIST>\n<LEDGERBAL><BALAMT>{:.2}<DTASOF>{now}</LEDGERBAL>\n\
+         </STMTRS>\n</STMTTRNRS></BANKMSGSRSV1>\n</OFX>\n",
+        total,
+    );
+
+    out
+}
+
+/// Escape SGML markup characters in OFX text fields
+fn ofx_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(day: &str, protocol: &str, rate: f64) -> DailyEarningsRecord {
+        DailyEarningsRecord {
+            day: day.to_string(),
+            hours: 24.0,
+            protocol_name: protocol.to_string(),
+            protocol_type: protocol.to_string(),
+            avg_earnings_per_hour: rate,
+            samples: 288,
+        }
+    }
+
+    #[test]
+    fn test_build_rows_converts_to_native_tokens() {
+        let rows = build_rows(
+            vec![
+                record("2026-10-01", "filecoin", 0.5),
+                record("2026-10-01", "custom", 1.0),
+                record("not-a-date", "storj", 1.0),
+            ],
+            &ExportConfig::default(),
+        );
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].amount_usd, 12.0);
+        assert_eq!(rows[0].token_symbol.as_deref(), Some("FIL"));
+        assert!((rows[0].token_amount.unwrap() - 2.4).abs() < 1e-9);
+        assert_eq!(rows[1].token_symbol, None);
+        assert_eq!(rows[1].token_amount, None);
+
+        // A second Filecoin node is priced as Filecoin
+        let backup = DailyEarningsRecord {
+            protocol_type: "filecoin".to_string(),
+            ..record("2026-10-01", "filecoin_backup", 0.5)
+        };
+        let rows = build_rows(vec![backup], &ExportConfig::default());
+        assert_eq!(rows[0].protocol, "filecoin_backup");
+        assert_eq!(rows[0].token_symbol.as_deref(), Some("FIL"));
+    }
+
+    #[test]
+    fn test_config_reads_protocol_prices() {
+        let table: toml::Table = toml::from_str(
+            r#"
+            [protocols.filecoin]
+            fil_price_usd = 4.0
+
+            [export.tokens.custom]
+            symbol = "CUST"
+            price_usd = 2.0
+            "#,
+        )
+        .unwrap();
+
+        let config = ExportConfig::from_table(&table);
+        assert_eq!(config.tokens["filecoin"].price_usd, 4.0);
+        assert_eq!(config.tokens["custom"].symbol, "CUST");
+        assert_eq!(config.tokens["storj"].symbol, "STORJ");
+    }
+
+    #[test]
+    fn test_csv_output() {
+        let rows = build_rows(
+            vec![record("2026-10-01", "storj", 0.25), record("2026-10-02", "a,b", 0.0)],
+            &ExportConfig::default(),
+        );
+        let csv = to_csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("date,protocol"));
+        assert_eq!(lines[1], "2026-10-01,storj,STORJ,12.00000000,0.5,6.000000,288");
+        assert!(lines[2].starts_with("2026-10-02,\"a,b\",,,"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_export_streams_account_rows() {
+        let pool = crate::db::test_pool().await;
+        let start = Utc::now() - Duration::hours(2);
+        for (account, minutes) in [(1, 0), (1, 30), (2, 30)] {
+            let id = crate::db::queries::store_metrics(
+                &pool,
+                account,
+                start + Duration::minutes(minutes),
+                1.5,
+                40.0,
+                30.0,
+                20.0,
+                10.0,
+            )
+            .await
+            .unwrap();
+            if minutes == 0 {
+                for protocol in ["storj", "grass"] {
+                    crate::db::queries::store_protocol_metrics(&pool, id, protocol.to_string(), 0.75, 50.0, true)
+                        .await
+                        .unwrap();
+                }
+            }
+        }
+
+        let export = |format| {
+            export_metrics(
+                web::Data::new(pool.clone()),
+                AccountId(1),
+                web::Query(MetricsExportQuery {
+                    format,
+                    start: Some(start),
+                    end: None,
+                }),
+            )
+        };
+
+        let response = export(MetricsExportFormat::Csv).await.unwrap();
+        assert_eq!(response.headers().get(header::CONTENT_TYPE).unwrap(), "text/csv; charset=utf-8");
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        let csv = std::str::from_utf8(&body).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], METRICS_CSV_HEADER.trim_end());
+        assert!(lines[1].contains(",grass,0.75,50,true,1.5,40,30,20,10"));
+        assert!(lines[2].contains(",storj,"));
+        assert!(lines[3].ends_with(",,,,,1.5,40,30,20,10"));
+
+        let response = export(MetricsExportFormat::Ndjson).await.unwrap();
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        let rows: Vec<serde_json::Value> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0]["protocol_name"], "grass");
+        assert_eq!(rows[2]["protocol_name"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_export_rejects_inverted_range() {
+        let pool = crate::db::test_pool().await;
+        let now = Utc::now();
+        let response = export_metrics(
+            web::Data::new(pool),
+            AccountId(1),
+            web::Query(MetricsExportQuery {
+                format: MetricsExportFormat::Ndjson,
+                start: Some(now),
+                end: Some(now - Duration::hours(1)),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_ofx_output() {
+        let from = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 10, 2).unwrap();
+        let rows = build_rows(
+            vec![record("2026-10-01", "storj", 0.25), record("2026-10-02", "golem", 0.5)],
+            &ExportConfig::default(),
+        );
+        let ofx = to_ofx(&rows, from, to);
+
+        assert!(ofx.starts_with("OFXHEADER:100"));
+        assert_eq!(ofx.matches("<STMTTRN>").count(), 2);
+        assert!(ofx.contains("<DTSTART>20261001"));
+        assert!(ofx.contains("<FITID>20261001-storj"));
+        assert!(ofx.contains("<TRNAMT>6.00"));
+        assert!(ofx.contains("<BALAMT>18.00"));
+    }
+}
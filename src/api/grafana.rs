@@ -0,0 +1,407 @@
+/// Grafana JSON Datasource Handlers
+///
+/// Implements the simple-JSON datasource protocol over the metrics and
+/// reallocation tables, so an existing Grafana install can chart earnings and
+/// mark reallocations as annotations. Point a JSON datasource at
+/// `/api/v1/grafana` and send an API key in the `X-API-Key` header.
+///
+/// Targets are `total_earnings`, the resource utilization columns
+/// (`cpu_percent`, ...), and `earnings:{protocol}` / `allocation:{protocol}`
+/// for every protocol with recorded metrics.
+use actix_web::{web, HttpResponse, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+
+use super::middleware::AccountId;
+use super::problem::ApiError;
+use crate::db::models::{MetricsRecord, ProtocolMetricsRecord, ReallocationRecord};
+use crate::db::queries;
+
+/// Series built from the `metrics` table
+const SYSTEM_TARGETS: &[&str] = &[
+    "total_earnings",
+    "cpu_percent",
+    "memory_percent",
+    "bandwidth_percent",
+    "storage_percent",
+];
+
+// ============================================================================
+// PROTOCOL TYPES
+// ============================================================================
+
+/// Body of `POST /search`
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchRequest {
+    /// Text typed into the query editor
+    #[serde(default)]
+    pub target: String,
+}
+
+/// Time range of a query
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TimeRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// Body of `POST /query`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    pub range: TimeRange,
+    /// Width of one point on the panel
+    #[serde(default)]
+    pub interval_ms: Option<i64>,
+    #[serde(default)]
+    pub max_data_points: Option<i64>,
+    pub targets: Vec<QueryTarget>,
+}
+
+/// One series requested by a panel
+#[derive(Debug, Deserialize)]
+pub struct QueryTarget {
+    pub target: String,
+    #[serde(default)]
+    pub hide: bool,
+}
+
+/// Points as `[value, unix_ms]` pairs, oldest first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimeSeries {
+    pub target: String,
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+/// Body of `POST /annotations`
+#[derive(Debug, Deserialize)]
+pub struct AnnotationRequest {
+    pub range: TimeRange,
+    /// Annotation definition; echoed back and its `query` filters by protocol
+    pub annotation: serde_json::Value,
+}
+
+/// A reallocation marked on the time axis
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotation {
+    pub annotation: serde_json::Value,
+    /// Unix milliseconds
+    pub time: i64,
+    pub title: String,
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+/// A queryable series
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    System(&'static str),
+    Earnings(String),
+    Allocation(String),
+}
+
+impl Target {
+    fn parse(name: &str) -> Option<Self> {
+        if let Some(column) = SYSTEM_TARGETS.iter().find(|t| **t == name) {
+            return Some(Target::System(column));
+        }
+        match name.split_once(':') {
+            Some(("earnings", protocol)) if !protocol.is_empty() => {
+                Some(Target::Earnings(protocol.to_string()))
+            }
+            Some(("allocation", protocol)) if !protocol.is_empty() => {
+                Some(Target::Allocation(protocol.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// GET /api/v1/grafana - Connection test used by "Save & test"
+pub async fn test_connection() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+/// POST /api/v1/grafana/search - Targets matching the typed text
+pub async fn search(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: Option<web::Json<SearchRequest>>,
+) -> Result<HttpResponse> {
+    let filter = req.map(|r| r.into_inner().target).unwrap_or_default();
+    let protocols = queries::get_protocol_names(db.get_ref(), account.0)
+        .await
+        .map_err(ApiError::from)?;
+
+    let targets: Vec<String> = SYSTEM_TARGETS
+        .iter()
+        .map(|t| t.to_string())
+        .chain(protocols.iter().map(|p| format!("earnings:{}", p)))
+        .chain(protocols.iter().map(|p| format!("allocation:{}", p)))
+        .filter(|t| t.contains(filter.as_str()))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(targets))
+}
+
+/// POST /api/v1/grafana/query - Time series for the requested targets
+pub async fn query(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Json<QueryRequest>,
+) -> Result<HttpResponse> {
+    let series = query_series(db.get_ref(), account.0, &req).await?;
+    Ok(HttpResponse::Ok().json(series))
+}
+
+/// POST /api/v1/grafana/annotations - Reallocations within the range
+pub async fn annotations(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Json<AnnotationRequest>,
+) -> Result<HttpResponse> {
+    let records = queries::get_reallocations_by_range(db.get_ref(), account.0, req.range.from, req.range.to)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(HttpResponse::Ok().json(build_annotations(records, &req.annotation)))
+}
+
+// ============================================================================
+// SERIES
+// ============================================================================
+
+/// Load and downsample every visible target of `req` for an account
+pub async fn query_series(
+    pool: &SqlitePool,
+    account_id: i64,
+    req: &QueryRequest,
+) -> Result<Vec<TimeSeries>, ApiError> {
+    let targets = req
+        .targets
+        .iter()
+        .filter(|t| !t.hide)
+        .map(|t| {
+            Target::parse(&t.target)
+                .map(|parsed| (t.target.clone(), parsed))
+                .ok_or_else(|| ApiError::bad_request("UNKNOWN_TARGET", format!("Unknown target '{}'", t.target)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let TimeRange { from, to } = req.range;
+    let metrics = queries::get_metrics_by_range(pool, account_id, from, to).await?;
+    let protocol_rows = if targets.iter().any(|(_, t)| !matches!(t, Target::System(_))) {
+        queries::get_protocol_metrics_by_range(pool, account_id, from, to).await?
+    } else {
+        Vec::new()
+    };
+
+    // Wide enough that no series exceeds the panel's point budget
+    let span_ms = (to - from).num_milliseconds().max(0);
+    let bucket_ms = req
+        .interval_ms
+        .unwrap_or(0)
+        .max(span_ms / req.max_data_points.filter(|n| *n > 0).unwrap_or(i64::MAX));
+
+    Ok(targets
+        .into_iter()
+        .map(|(name, target)| TimeSeries {
+            target: name,
+            datapoints: downsample(points(&target, &metrics, &protocol_rows), bucket_ms),
+        })
+        .collect())
+}
+
+/// Raw `(unix_ms, value)` points of `target`, oldest first
+fn points(target: &Target, metrics: &[MetricsRecord], protocol_rows: &[ProtocolMetricsRecord]) -> Vec<(i64, f64)> {
+    let times: HashMap<i64, i64> = metrics
+        .iter()
+        .filter_map(|m| Some((m.id?, timestamp_ms(&m.timestamp)?)))
+        .collect();
+
+    let mut points: Vec<(i64, f64)> = match target {
+        Target::System(column) => metrics
+            .iter()
+            .filter_map(|m| {
+                let value = match *column {
+                    "total_earnings" => Some(m.total_earnings_per_hour),
+                    "cpu_percent" => m.cpu_percent,
+                    "memory_percent" => m.memory_percent,
+                    "bandwidth_percent" => m.bandwidth_percent,
+                    _ => m.storage_percent,
+                };
+                Some((timestamp_ms(&m.timestamp)?, value?))
+            })
+            .collect(),
+        Target::Earnings(protocol) | Target::Allocation(protocol) => protocol_rows
+            .iter()
+            .filter(|row| &row.protocol_name == protocol)
+            .filter_map(|row| {
+                let value = match target {
+                    Target::Earnings(_) => row.earnings_per_hour,
+                    _ => row.allocation_percent,
+                };
+                Some((*times.get(&row.metrics_id)?, value))
+            })
+            .collect(),
+    };
+
+    points.sort_by_key(|(time, _)| *time);
+    points
+}
+
+/// Average `points` into `bucket_ms`-wide buckets as `[value, unix_ms]` pairs
+fn downsample(points: Vec<(i64, f64)>, bucket_ms: i64) -> Vec<(f64, i64)> {
+    if bucket_ms <= 0 {
+        return points.into_iter().map(|(time, value)| (value, time)).collect();
+    }
+
+    let mut buckets: BTreeMap<i64, (f64, u32)> = BTreeMap::new();
+    for (time, value) in points {
+        let (sum, count) = buckets.entry(time - time.rem_euclid(bucket_ms)).or_default();
+        *sum += value;
+        *count += 1;
+    }
+    buckets
+        .into_iter()
+        .map(|(time, (sum, count))| (sum / count as f64, time))
+        .collect()
+}
+
+fn timestamp_ms(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.timestamp_millis())
+}
+
+// ============================================================================
+// ANNOTATIONS
+// ============================================================================
+
+/// One annotation per reallocation, grouping the rows written together
+///
+/// A non-empty `query` on the annotation keeps only reallocations that
+/// touched that protocol.
+fn build_annotations(records: Vec<ReallocationRecord>, annotation: &serde_json::Value) -> Vec<Annotation> {
+    let protocol = annotation
+        .get("query")
+        .and_then(|q| q.as_str())
+        .map(str::trim)
+        .filter(|q| !q.is_empty());
+
+    let mut events: BTreeMap<String, Vec<ReallocationRecord>> = BTreeMap::new();
+    for record in records {
+        events.entry(record.timestamp.clone()).or_default().push(record);
+    }
+
+    events
+        .into_iter()
+        .filter(|(_, changes)| protocol.is_none_or(|p| changes.iter().any(|c| c.protocol_name == p)))
+        .filter_map(|(timestamp, changes)| {
+            let mut text = changes
+                .iter()
+                .map(|c| format!("{}: {:.1}% → {:.1}%", c.protocol_name, c.old_allocation, c.new_allocation))
+                .collect::<Vec<_>>()
+                .join(", ");
+            if let Some(reason) = changes.iter().find_map(|c| c.reason.as_deref()) {
+                text = format!("{} ({})", text, reason);
+            }
+
+            Some(Annotation {
+                annotation: annotation.clone(),
+                time: timestamp_ms(&timestamp)?,
+                title: "Reallocation".to_string(),
+                text,
+                tags: std::iter::once("reallocation".to_string())
+                    .chain(changes.iter().map(|c| c.protocol_name.clone()))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_target_parsing_and_downsampling() {
+        assert_eq!(Target::parse("cpu_percent"), Some(Target::System("cpu_percent")));
+        assert_eq!(Target::parse("earnings:storj"), Some(Target::Earnings("storj".to_string())));
+        assert_eq!(Target::parse("allocation:"), None);
+        assert_eq!(Target::parse("uptime"), None);
+
+        let points = vec![(0, 1.0), (30_000, 3.0), (60_000, 5.0)];
+        assert_eq!(downsample(points.clone(), 60_000), vec![(2.0, 0), (5.0, 60_000)]);
+        assert_eq!(downsample(points, 0).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_query_series_and_annotations() {
+        let pool = crate::db::test_pool().await;
+
+        let now = Utc::now();
+        for (minutes_ago, earnings) in [(20, 1.0), (10, 2.0)] {
+            queries::store_historical_metrics(
+                &pool,
+                1,
+                now - Duration::minutes(minutes_ago),
+                &HashMap::from([("storj".to_string(), earnings)]),
+                &HashMap::from([("storj".to_string(), 60.0)]),
+            )
+            .await
+            .unwrap();
+        }
+        for protocol in ["storj", "golem"] {
+            let at = now - Duration::minutes(5);
+            queries::store_reallocation(&pool, 1, at, protocol.to_string(), 50.0, 60.0, None, None, None)
+                .await
+                .unwrap();
+        }
+
+        let range = TimeRange {
+            from: now - Duration::hours(1),
+            to: now,
+        };
+        let target = |name: &str| QueryTarget {
+            target: name.to_string(),
+            hide: false,
+        };
+        let req = QueryRequest {
+            range,
+            interval_ms: None,
+            max_data_points: None,
+            targets: vec![target("total_earnings"), target("allocation:storj")],
+        };
+        let series = query_series(&pool, 1, &req).await.unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].datapoints.iter().map(|p| p.0).collect::<Vec<_>>(), vec![1.0, 2.0]);
+        assert!(series[0].datapoints[0].1 < series[0].datapoints[1].1);
+        assert_eq!(series[1].datapoints.len(), 2);
+        assert_eq!(series[1].datapoints[0].0, 60.0);
+        assert!(query_series(&pool, 2, &req).await.unwrap().iter().all(|s| s.datapoints.is_empty()));
+
+        let unknown = QueryRequest {
+            range,
+            interval_ms: None,
+            max_data_points: None,
+            targets: vec![target("nope")],
+        };
+        assert!(query_series(&pool, 1, &unknown).await.is_err());
+
+        let records = queries::get_reallocations_by_range(&pool, 1, range.from, range.to).await.unwrap();
+        let all = build_annotations(records.clone(), &serde_json::json!({ "name": "reallocations" }));
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].tags, vec!["reallocation", "storj", "golem"]);
+        assert!(build_annotations(records, &serde_json::json!({ "query": "grass" })).is_empty());
+    }
+}
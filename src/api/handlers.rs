@@ -1,14 +1,26 @@
 /// API Endpoint Handlers
 ///
 /// HTTP request handlers for all API endpoints.
-
 use actix_web::{web, HttpResponse, Result as ActixResult};
-use crate::orchestration::OrchestrationError;
-use chrono::Utc;
+use crate::orchestration::ml_optimizer::PlanSource;
+use crate::config::Config;
+use crate::containers::{ContainerAction, ContainerError};
+use crate::services::{ServiceAction, ServiceError};
+use crate::OptimizerConfig;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
 use std::collections::HashMap;
 
+use super::currency::DisplayConfig;
+use super::dashboard;
 use super::models::*;
-use super::AppState;
+use super::problem::ApiError;
+use crate::db::models::{
+    AlertFilter, DecisionOutcome, OptimizerDecisionRecord, PendingReallocationRecord, PendingStatus, ReallocationFilter,
+};
+use crate::db::queries;
+use super::middleware::AccountId;
+use super::{AppState, OrchestratorState};
 
 // ============================================================================
 // METRICS ENDPOINTS
@@ -16,56 +28,102 @@ use super::AppState;
 
 /// GET /api/v1/metrics - Get current metrics
 pub async fn get_metrics(
-    state: web::Data<AppState>,
+    state: OrchestratorState,
+    display: Option<web::Data<DisplayConfig>>,
 ) -> ActixResult<HttpResponse> {
-    match state.coordinator.get_current_metrics().await {
-        Ok(Some(metrics)) => {
+    let display = display.map(|d| d.into_inner()).unwrap_or_default();
+    match state.coordinator.get_current_metrics().await? {
+        Some(metrics) => {
             let response = MetricsResponse {
                 timestamp: metrics.timestamp,
-                total_earnings_per_hour: metrics.total_earnings_per_hour,
-                earnings_by_protocol: metrics.earnings_by_protocol,
-                allocation_by_protocol: metrics.allocation_by_protocol,
-                connection_status: metrics.connection_status,
+                currency: display.currency,
+                total_earnings_per_hour: display.convert(metrics.total_earnings_per_hour),
+                earnings_by_protocol: display.convert_map(&metrics.earnings_by_protocol),
+                allocation_by_protocol: metrics.allocation_by_protocol.clone(),
+                connection_status: metrics.connection_status.clone(),
                 resource_utilization: ResourceUtilizationDto {
                     cpu_percent: metrics.resource_utilization.cpu_percent,
                     memory_percent: metrics.resource_utilization.memory_percent,
                     bandwidth_percent: metrics.resource_utilization.bandwidth_percent,
                     storage_percent: metrics.resource_utilization.storage_percent,
                 },
+                stale_seconds: state.coordinator.stale_protocols(),
             };
 
             Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
         }
-        Ok(None) => {
-            let error = ErrorResponse::new(
-                "NO_DATA".to_string(),
-                "No metrics available yet".to_string(),
-            );
-            Ok(HttpResponse::NotFound().json(error))
-        }
-        Err(_) => {
-            let error = ErrorResponse::new(
-                "INTERNAL_ERROR".to_string(),
-                "Failed to fetch metrics".to_string(),
-            );
-            Ok(HttpResponse::InternalServerError().json(error))
-        }
+        None => Err(ApiError::no_data().into()),
     }
 }
 
 /// GET /api/v1/metrics/history - Get metrics history
+///
+/// With `interval` set, stored metrics are aggregated into time buckets by
+/// the database; otherwise the latest in-memory snapshots are returned.
 pub async fn get_metrics_history(
     state: web::Data<AppState>,
+    account: AccountId,
+    db: web::Data<SqlitePool>,
     req: web::Query<MetricsHistoryRequest>,
 ) -> ActixResult<HttpResponse> {
     let hours = req.hours.unwrap_or(24);
     let limit = req.limit.unwrap_or(1000);
 
+    if let Some(interval) = req.interval {
+        let aggregation = req.agg.unwrap_or_default();
+        let start = Utc::now() - chrono::Duration::hours(hours);
+
+        let buckets =
+            queries::get_bucketed_metrics(db.get_ref(), account.0, start, interval, aggregation).await;
+        let protocol_buckets = queries::get_bucketed_protocol_metrics(
+            db.get_ref(),
+            account.0,
+            start,
+            interval,
+            aggregation,
+        )
+        .await;
+        let buckets = buckets.map_err(ApiError::from)?;
+        let protocol_buckets = protocol_buckets.map_err(ApiError::from)?;
+
+        let mut earnings_by_bucket: HashMap<i64, HashMap<String, f64>> = HashMap::new();
+        for record in protocol_buckets {
+            earnings_by_bucket
+                .entry(record.bucket)
+                .or_default()
+                .insert(record.protocol_name, record.earnings_per_hour);
+        }
+
+        let total_count = buckets.len();
+        let mut snapshots: Vec<MetricsSnapshot> = buckets
+            .into_iter()
+            .rev()
+            .take(limit)
+            .filter_map(|b| {
+                Some(MetricsSnapshot {
+                    timestamp: DateTime::from_timestamp(b.bucket, 0)?,
+                    total_earnings: b.total_earnings_per_hour,
+                    earnings_by_protocol: earnings_by_bucket.remove(&b.bucket).unwrap_or_default(),
+                })
+            })
+            .collect();
+        snapshots.reverse();
+
+        let response = MetricsHistoryResponse {
+            metrics: snapshots,
+            total_count,
+            interval: Some(interval),
+            aggregation: Some(aggregation),
+        };
+        return Ok(HttpResponse::Ok().json(SuccessResponse::new(response)));
+    }
+
+    state.check_owner(account)?;
     let history = state.coordinator.get_metrics_history().await;
     let history_len = history.len();
 
     let snapshots: Vec<MetricsSnapshot> = history
-        .into_iter()
+        .iter()
         .rev()
         .take(limit)
         .map(|m| MetricsSnapshot {
@@ -78,6 +136,8 @@ pub async fn get_metrics_history(
     let response = MetricsHistoryResponse {
         metrics: snapshots,
         total_count: history_len,
+        interval: None,
+        aggregation: None,
     };
 
     Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
@@ -89,94 +149,302 @@ pub async fn get_metrics_history(
 
 /// GET /api/v1/opportunities - Get optimization opportunities
 pub async fn get_opportunities(
-    state: web::Data<AppState>,
+    state: OrchestratorState,
     req: web::Query<OpportunitiesRequest>,
 ) -> ActixResult<HttpResponse> {
-    match state.coordinator.get_current_metrics().await {
-        Ok(Some(metrics)) => {
-            let optimizer = state.optimizer.lock().await;
-            match optimizer.analyze_opportunities(&metrics) {
-                Ok(opportunities) => {
-                    let limit = req.limit.unwrap_or(10);
-                    let opps: Vec<OpportunityDto> = opportunities
-                        .into_iter()
-                        .take(limit)
-                        .map(|o| OpportunityDto {
-                            from_protocol: o.from_protocol,
-                            to_protocol: o.to_protocol,
-                            current_rate: o.current_rate,
-                            projected_rate: o.projected_rate,
-                            earnings_improvement: o.earnings_improvement,
-                            confidence: o.confidence,
-                        })
-                        .collect();
-
-                    let best_improvement = opps.first().map(|o| o.earnings_improvement);
-                    let confidence = opps.first().map(|o| o.confidence).unwrap_or(0.0);
-
-                    let response = OpportunitiesResponse {
-                        opportunities: opps,
-                        best_improvement,
-                        confidence,
-                    };
-
-                    Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
-                }
-                Err(_) => {
-                    let error = ErrorResponse::new(
-                        "ANALYSIS_ERROR".to_string(),
-                        "Failed to analyze opportunities".to_string(),
-                    );
-                    Ok(HttpResponse::InternalServerError().json(error))
-                }
-            }
-        }
-        _ => {
-            let error = ErrorResponse::new(
-                "NO_DATA".to_string(),
-                "No metrics available".to_string(),
-            );
-            Ok(HttpResponse::NotFound().json(error))
-        }
-    }
+    let metrics = state.coordinator.get_current_metrics().await?.ok_or_else(ApiError::no_data)?;
+    let metrics = state.reallocation.config().constrained_metrics(&metrics);
+    let opportunities = state
+        .optimizer
+        .analyze_opportunities(&metrics)
+        .map_err(|e| ApiError::internal("ANALYSIS_ERROR", e.to_string()))?;
+
+    let limit = req.limit.unwrap_or(10);
+    let opps: Vec<OpportunityDto> = opportunities
+        .into_iter()
+        .take(limit)
+        .map(|o| OpportunityDto {
+            from_protocol: o.from_protocol,
+            to_protocol: o.to_protocol,
+            current_rate: o.current_rate,
+            projected_rate: o.projected_rate,
+            earnings_improvement: o.earnings_improvement,
+            confidence: o.confidence,
+            risk_score: o.risk_score,
+        })
+        .collect();
+
+    let best_improvement = opps.first().map(|o| o.earnings_improvement);
+    let confidence = opps.first().map(|o| o.confidence).unwrap_or(0.0);
+
+    let response = OpportunitiesResponse {
+        opportunities: opps,
+        best_improvement,
+        confidence,
+    };
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
 }
 
 /// GET /api/v1/allocation - Get optimal allocation
 pub async fn get_optimal_allocation(
-    state: web::Data<AppState>,
+    state: OrchestratorState,
 ) -> ActixResult<HttpResponse> {
-    match state.coordinator.get_current_metrics().await {
-        Ok(Some(metrics)) => {
-            let optimizer = state.optimizer.lock().await;
-            match optimizer.calculate_optimal_allocation(&metrics) {
-                Ok(plan) => {
-                    let response = AllocationResponse {
-                        current_allocation: metrics.allocation_by_protocol,
-                        optimal_allocation: plan.allocation,
-                        estimated_improvement: plan.estimated_improvement,
-                        net_benefit: plan.net_benefit,
-                        roi_percent: plan.roi_percent,
-                    };
-
-                    Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
-                }
-                Err(_) => {
-                    let error = ErrorResponse::new(
-                        "CALCULATION_ERROR".to_string(),
-                        "Failed to calculate allocation".to_string(),
-                    );
-                    Ok(HttpResponse::InternalServerError().json(error))
-                }
-            }
+    let metrics = state.coordinator.get_current_metrics().await?.ok_or_else(ApiError::no_data)?;
+    let metrics = state.reallocation.config().constrained_metrics(&metrics);
+
+    let plan = state
+        .optimizer
+        .calculate_optimal_allocation(&metrics)
+        .map_err(|e| ApiError::internal("CALCULATION_ERROR", e.to_string()))?;
+    let (mut plan, source) = match &state.ml_optimizer {
+        Some(ml) => {
+            let history = state.coordinator.get_metrics_history().await;
+            let history: Vec<_> = history.iter().collect();
+            let blended = ml.refine(plan, &metrics, &history).await;
+            (blended.plan, blended.source)
         }
-        _ => {
-            let error = ErrorResponse::new(
-                "NO_DATA".to_string(),
-                "No metrics available".to_string(),
-            );
-            Ok(HttpResponse::NotFound().json(error))
+        None => (plan, PlanSource::Heuristic),
+    };
+    state.reallocation.price_plan(&mut plan, &metrics);
+
+    let response = AllocationResponse {
+        current_allocation: metrics.allocation_by_protocol.clone(),
+        optimal_allocation: plan.allocation,
+        estimated_improvement: plan.estimated_improvement,
+        net_benefit: plan.net_benefit,
+        roi_percent: plan.roi_percent,
+        source,
+    };
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
+}
+
+/// GET /api/v1/efficiency - Get earnings per CPU core, GB stored and Mbps shared
+pub async fn get_efficiency(
+    state: OrchestratorState,
+) -> ActixResult<HttpResponse> {
+    let metrics = state.coordinator.get_current_metrics().await?.ok_or_else(ApiError::no_data)?;
+
+    let mut protocols: Vec<ProtocolEfficiencyDto> = metrics
+        .efficiency_by_protocol
+        .iter()
+        .map(|(protocol, efficiency)| ProtocolEfficiencyDto {
+            protocol: protocol.clone(),
+            earnings_per_hour: metrics.earnings_by_protocol.get(protocol).copied().unwrap_or(0.0),
+            usd_per_cpu_core_hour: efficiency.usd_per_cpu_core_hour,
+            usd_per_gb_hour: efficiency.usd_per_gb_hour,
+            usd_per_mbps_hour: efficiency.usd_per_mbps_hour,
+        })
+        .collect();
+    protocols.sort_by(|a, b| a.protocol.cmp(&b.protocol));
+
+    let most_efficient = MostEfficientDto {
+        cpu: most_efficient(&protocols, |p| p.usd_per_cpu_core_hour),
+        storage: most_efficient(&protocols, |p| p.usd_per_gb_hour),
+        bandwidth: most_efficient(&protocols, |p| p.usd_per_mbps_hour),
+    };
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(EfficiencyResponse {
+        timestamp: metrics.timestamp,
+        protocols,
+        most_efficient,
+    })))
+}
+
+/// Protocol with the highest rate for one resource, if any reports it
+fn most_efficient(
+    protocols: &[ProtocolEfficiencyDto],
+    rate: impl Fn(&ProtocolEfficiencyDto) -> Option<f64>,
+) -> Option<String> {
+    protocols
+        .iter()
+        .filter_map(|p| rate(p).map(|r| (p, r)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(p, _)| p.protocol.clone())
+}
+
+/// GET /api/v1/config - Get the effective (file + environment) configuration
+pub async fn get_config(config: web::Data<Config>) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(config.get_ref())))
+}
+
+/// GET /api/v1/config/optimizer - Get optimizer configuration
+pub async fn get_optimizer_config(
+    state: OrchestratorState,
+) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(optimizer_config_dto(&state.optimizer.config()))))
+}
+
+/// PUT /api/v1/config/optimizer - Update optimizer configuration (e.g. strategy)
+pub async fn update_optimizer_config(
+    state: OrchestratorState,
+    req: web::Json<UpdateOptimizerConfigRequest>,
+) -> ActixResult<HttpResponse> {
+    let req = req.into_inner();
+    let optimizer = &state.optimizer;
+
+    let mut config = OptimizerConfig::clone(&optimizer.config());
+    if let Some(v) = req.min_improvement_threshold {
+        config.min_improvement_threshold = v;
+    }
+    if let Some(v) = req.min_improvement_percent {
+        config.min_improvement_percent = v;
+    }
+    if let Some(v) = req.max_allocation_change {
+        config.max_allocation_change = v;
+    }
+    if let Some(v) = req.analysis_window_hours {
+        config.analysis_window_hours = v;
+    }
+    if let Some(v) = req.strategy {
+        config.strategy = v;
+    }
+    if let Some(v) = req.risk_aversion {
+        config.risk_aversion = v;
+    }
+    if let Some(v) = req.max_protocol_share {
+        config.max_protocol_share = v;
+    }
+    if let Some(v) = req.demand_weight {
+        config.demand_weight = v;
+    }
+    if let Some(v) = req.yield_weight {
+        config.yield_weight = v;
+    }
+    if let Some(v) = req.prediction_error_weight {
+        config.prediction_error_weight = v;
+    }
+    if let Some(v) = req.max_bandwidth_percent {
+        config.max_bandwidth_percent = v;
+    }
+
+    optimizer
+        .set_config(config)
+        .map_err(|e| ApiError::bad_request("INVALID_CONFIG", e.to_string()))?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(optimizer_config_dto(&optimizer.config()))))
+}
+
+/// GET /api/v1/config/scheduler - Get the live scheduler and reallocation configuration
+pub async fn get_scheduler_config(state: OrchestratorState) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(scheduler_config_dto(&state))))
+}
+
+/// PUT /api/v1/config/scheduler - Update scheduler and reallocation configuration
+///
+/// Running scheduler tasks pick up the new values on their next tick; interval
+/// changes apply immediately. Nothing is changed unless every value is valid.
+pub async fn update_scheduler_config(
+    state: OrchestratorState,
+    req: web::Json<UpdateSchedulerConfigRequest>,
+) -> ActixResult<HttpResponse> {
+    let req = req.into_inner();
+
+    let mut config = state.scheduler_config.borrow().clone();
+    if let Some(v) = req.poll_interval {
+        config.poll_interval = v;
+    }
+    if let Some(v) = req.optimization_interval {
+        config.optimization_interval = v;
+    }
+    if let Some(v) = req.metrics_retention_days {
+        config.metrics_retention_days = v;
+    }
+    if let Some(v) = req.alert_processing_interval {
+        config.alert_processing_interval = v;
+    }
+    if let Some(v) = req.min_reallocation_threshold {
+        config.min_reallocation_threshold = v;
+    }
+    if let Some(v) = req.cpu_alert_threshold {
+        config.cpu_alert_threshold = v;
+    }
+    if let Some(v) = req.memory_alert_threshold {
+        config.memory_alert_threshold = v;
+    }
+    if let Some(v) = req.disk_free_alert_percent {
+        config.disk_free_alert_percent = v;
+    }
+    if let Some(v) = req.alert_cooldown_secs {
+        config.alert_cooldown_secs = v;
+    }
+    if let Some(v) = req.metrics_flush_interval {
+        config.metrics_flush_interval = v;
+    }
+    if let Some(v) = req.canary_window_hours {
+        config.canary_window_hours = v;
+    }
+
+    let mut reallocation = state.reallocation.config();
+    if let Some(r) = req.reallocation {
+        if let Some(v) = r.min_hold_duration_seconds {
+            reallocation.min_hold_duration = chrono::Duration::seconds(v as i64);
+        }
+        if let Some(v) = r.max_per_hour {
+            reallocation.max_per_hour = v;
+        }
+        if let Some(v) = r.auto_rollback {
+            reallocation.auto_rollback = v;
+        }
+        if let Some(v) = r.require_confirmation {
+            reallocation.require_confirmation = v;
+        }
+        if let Some(v) = r.confirmation_timeout_hours {
+            reallocation.confirmation_timeout = chrono::Duration::hours(v);
+        }
+        if let Some(v) = r.constraints {
+            reallocation.constraints = v;
         }
     }
+
+    let validation = config
+        .validate()
+        .and_then(|()| reallocation.validate().map_err(|e| e.to_string()));
+    validation.map_err(|e| ApiError::bad_request("INVALID_CONFIG", e))?;
+
+    // Validated above, so neither update can fail
+    let _ = state.reallocation.set_config(reallocation);
+    state.scheduler_config.send_replace(config);
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(scheduler_config_dto(&state))))
+}
+
+/// GET /api/v1/scheduler/status - Liveness of each background task
+pub async fn get_scheduler_status(state: OrchestratorState) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(state.scheduler_status.snapshot())))
+}
+
+/// Current scheduler configuration and reallocation settings as a DTO
+fn scheduler_config_dto(state: &AppState) -> SchedulerConfigDto {
+    let reallocation = state.reallocation.config();
+    SchedulerConfigDto {
+        scheduler: state.scheduler_config.borrow().clone(),
+        reallocation: ReallocationConfigDto {
+            min_hold_duration_seconds: reallocation.min_hold_duration.num_seconds().max(0) as u64,
+            max_per_hour: reallocation.max_per_hour,
+            auto_rollback: reallocation.auto_rollback,
+            require_confirmation: reallocation.require_confirmation,
+            confirmation_timeout_hours: reallocation.confirmation_timeout.num_hours(),
+            constraints: reallocation.constraints,
+        },
+    }
+}
+
+/// Convert optimizer configuration to its DTO
+fn optimizer_config_dto(config: &OptimizerConfig) -> OptimizerConfigDto {
+    OptimizerConfigDto {
+        min_improvement_threshold: config.min_improvement_threshold,
+        min_improvement_percent: config.min_improvement_percent,
+        max_allocation_change: config.max_allocation_change,
+        analysis_window_hours: config.analysis_window_hours,
+        strategy: config.strategy,
+        risk_aversion: config.risk_aversion,
+        max_protocol_share: config.max_protocol_share,
+        demand_weight: config.demand_weight,
+        yield_weight: config.yield_weight,
+        prediction_error_weight: config.prediction_error_weight,
+        max_bandwidth_percent: config.max_bandwidth_percent,
+    }
 }
 
 // ============================================================================
@@ -184,58 +452,406 @@ pub async fn get_optimal_allocation(
 // ============================================================================
 
 /// POST /api/v1/reallocate - Execute reallocation
+///
+/// The allocation is priced and executed by the reallocation engine like a
+/// scheduled plan. With `require_confirmation` set it is queued for approval
+/// instead, and a staged rollout continues in the background; both answer
+/// `202 Accepted`.
 pub async fn execute_reallocation(
-    state: web::Data<AppState>,
+    state: OrchestratorState,
+    db: web::Data<SqlitePool>,
     req: web::Json<ReallocateRequest>,
 ) -> ActixResult<HttpResponse> {
+    let req = req.into_inner();
+
     // Validate allocation
     let total: f64 = req.allocation.values().sum();
     if (total - 100.0).abs() > 1.0 {
-        let error = ErrorResponse::new(
-            "INVALID_ALLOCATION".to_string(),
+        return Err(ApiError::bad_request(
+            "INVALID_ALLOCATION",
             format!("Total allocation {} != 100%", total),
-        );
-        return Ok(HttpResponse::BadRequest().json(error));
+        )
+        .into());
     }
 
+    // Reject targets that break a floor, cap or pin
+    let metrics = state.coordinator.get_current_metrics().await?.ok_or_else(ApiError::no_data)?;
+    state
+        .reallocation
+        .config()
+        .check_allocation(&req.allocation, &metrics.allocation_by_protocol, state.coordinator.protocol_types())
+        .map_err(|e| ApiError::bad_request("CONSTRAINT_VIOLATION", e.to_string()))?;
+
     // Check if can reallocate
-    if let Err(_) = state.reallocation.can_reallocate().await {
-        let error = ErrorResponse::new(
-            "CANNOT_REALLOCATE".to_string(),
-            "Reallocation not currently allowed (rate limit or hold duration)".to_string(),
-        );
-        return Ok(HttpResponse::TooManyRequests().json(error));
+    if let Err(e) = state.reallocation.can_reallocate().await {
+        return Err(ApiError::new(
+            actix_web::http::StatusCode::TOO_MANY_REQUESTS,
+            "CANNOT_REALLOCATE",
+            format!("Reallocation not currently allowed: {}", e),
+        )
+        .into());
+    }
+
+    let estimated_improvement = state
+        .optimizer
+        .estimate_earnings_improvement(&req.allocation, &metrics.earnings_by_protocol)
+        .max(0.0);
+    let mut plan = crate::AllocationPlan {
+        allocation: req.allocation,
+        estimated_improvement,
+        estimated_cost: 0.0,
+        net_benefit: estimated_improvement,
+        roi_percent: 100.0,
+        confidence: 1.0,
+        created_at: Utc::now(),
+    };
+    state.reallocation.price_plan(&mut plan, &metrics);
+
+    let account_id = state.account_id();
+    if state.reallocation.requires_confirmation() {
+        let record = PendingReallocationRecord::new(&plan)
+            .map_err(|e| ApiError::internal("REALLOCATION_FAILED", e.to_string()))?;
+        let id = queries::store_pending_reallocation(db.get_ref(), account_id, &record)
+            .await
+            .map_err(ApiError::from)?;
+        log::info!("📝 Reallocation #{} awaiting approval: {}", id, req.reason);
+        let response = ReallocateResponse {
+            success: true,
+            message: format!("Reallocation {} is awaiting approval", id),
+            changes: vec![],
+            pending_id: Some(id),
+        };
+        return Ok(HttpResponse::Accepted().json(SuccessResponse::new(response)));
     }
 
-    // Note: Actual execution would require protocol adapters
-    // This is a simplified response for demonstration
+    // A staged rollout takes several step intervals; its changes are
+    // recorded and published once it finishes
+    if state.reallocation.is_staged() {
+        let (coordinator, reallocation) = (state.coordinator.clone(), state.reallocation.clone());
+        let (pool, events) = (db.get_ref().clone(), state.events.clone());
+        tokio::spawn(async move {
+            let executed =
+                crate::scheduler::execute_plan(&coordinator, &reallocation, &pool, &events, &plan, &req.reason, None);
+            if let Err(e) = executed.await {
+                log::error!("❌ Reallocation failed: {}", e);
+            }
+        });
+        let response = ReallocateResponse {
+            success: true,
+            message: "Staged reallocation started".to_string(),
+            changes: vec![],
+            pending_id: None,
+        };
+        return Ok(HttpResponse::Accepted().json(SuccessResponse::new(response)));
+    }
+
+    let changes = crate::scheduler::execute_plan(
+        &state.coordinator,
+        &state.reallocation,
+        db.get_ref(),
+        &state.events,
+        &plan,
+        &req.reason,
+        None,
+    )
+    .await
+    .map_err(|e| ApiError::internal("REALLOCATION_FAILED", e.to_string()))?;
+
     let response = ReallocateResponse {
         success: true,
         message: "Reallocation executed successfully".to_string(),
-        changes: vec![],
+        changes: changes
+            .into_iter()
+            .map(|change| AllocationChangeDto {
+                timestamp: change.timestamp,
+                protocol: change.protocol,
+                old_allocation: change.old_allocation,
+                new_allocation: change.new_allocation,
+                earnings_impact: change.earnings_impact,
+                realized_impact: None,
+                decision_id: None,
+            })
+            .collect(),
+        pending_id: None,
     };
-
     Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
 }
 
 /// GET /api/v1/reallocation/history - Get reallocation history
+///
+/// Recorded changes newest first, filtered by protocol and time range and
+/// paged by `cursor` or `offset`.
 pub async fn get_reallocation_history(
-    state: web::Data<AppState>,
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Query<ReallocationHistoryRequest>,
 ) -> ActixResult<HttpResponse> {
-    let history = state.reallocation.get_reallocation_history().await;
+    let req = req.into_inner();
+    let page = req.page().map_err(|e| ApiError::bad_request("INVALID_PAGE", e))?;
+    let filter = ReallocationFilter {
+        protocol: req.protocol,
+        start: req.start,
+        end: req.end,
+    };
 
-    let changes: Vec<AllocationChangeDto> = history
-        .into_iter()
-        .map(|c| AllocationChangeDto {
-            timestamp: c.timestamp,
-            protocol: c.protocol,
-            old_allocation: c.old_allocation,
-            new_allocation: c.new_allocation,
-            earnings_impact: c.earnings_impact,
-        })
-        .collect();
+    let records = queries::list_reallocations(db.get_ref(), account.0, &filter, page)
+        .await
+        .map_err(ApiError::from)?;
+    let total_count = queries::count_reallocations(db.get_ref(), account.0, &filter)
+        .await
+        .map_err(ApiError::from)?;
+
+    let next_cursor = page.next_cursor(records.len(), records.last().and_then(|r| r.id));
+    let response = ReallocationHistoryResponse {
+        changes: records.into_iter().map(AllocationChangeDto::from).collect(),
+        total_count: total_count as usize,
+        next_cursor,
+    };
 
-    Ok(HttpResponse::Ok().json(SuccessResponse::new(changes)))
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
+}
+
+/// GET /api/v1/reallocations/pending - List plans awaiting confirmation
+pub async fn get_pending_reallocations(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+) -> ActixResult<HttpResponse> {
+    let records = queries::get_pending_reallocations(db.get_ref(), account.0)
+        .await
+        .map_err(ApiError::from)?;
+    let pending: Vec<PendingReallocationDto> = records.iter().map(pending_reallocation_dto).collect();
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(pending)))
+}
+
+/// POST /api/v1/reallocations/{id}/approve - Approve and execute a pending plan
+pub async fn approve_reallocation(
+    state: OrchestratorState,
+    db: web::Data<SqlitePool>,
+    id: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    let id = id.into_inner();
+    let account_id = state.account_id();
+    let record = load_pending_reallocation(db.get_ref(), account_id, id).await?;
+
+    let proposed_at = DateTime::parse_from_rfc3339(&record.created_at)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+    if state.reallocation.is_confirmation_expired(proposed_at) {
+        let _ = queries::transition_pending_reallocation(
+            db.get_ref(),
+            account_id,
+            id,
+            PendingStatus::Pending,
+            PendingStatus::Expired,
+            Some("Confirmation window elapsed"),
+        )
+        .await;
+        settle_decision(db.get_ref(), account_id, id, DecisionOutcome::Rejected, Some("Confirmation window elapsed"))
+            .await;
+        return Err(ApiError::new(
+            actix_web::http::StatusCode::GONE,
+            "PLAN_EXPIRED",
+            format!("Reallocation {} is too old to approve", id),
+        )
+        .into());
+    }
+
+    let approved = queries::transition_pending_reallocation(
+        db.get_ref(),
+        account_id,
+        id,
+        PendingStatus::Pending,
+        PendingStatus::Approved,
+        None,
+    )
+    .await
+    .map_err(ApiError::from)?;
+    if !approved {
+        return Err(already_decided(id).into());
+    }
+
+    // A staged rollout takes several step intervals; the plan stays Approved
+    // until it finishes in the background
+    if state.reallocation.is_staged() {
+        let (coordinator, reallocation) = (state.coordinator.clone(), state.reallocation.clone());
+        let (pool, events) = (db.get_ref().clone(), state.events.clone());
+        // The outcome is recorded on the pending plan
+        tokio::spawn(async move {
+            let _ = run_approved(&coordinator, &reallocation, &pool, &events, id, &record).await;
+        });
+        return decided_reallocation(db.get_ref(), account_id, id).await;
+    }
+
+    run_approved(&state.coordinator, &state.reallocation, db.get_ref(), &state.events, id, &record)
+        .await
+        .map_err(|message| ApiError::internal("REALLOCATION_FAILED", message))?;
+    decided_reallocation(db.get_ref(), account_id, id).await
+}
+
+/// Execute an approved plan and record it as Executed or Failed
+async fn run_approved(
+    coordinator: &crate::ProtocolCoordinator,
+    reallocation: &crate::ReallocationEngine,
+    db: &SqlitePool,
+    events: &crate::events::EventBus,
+    id: i64,
+    record: &PendingReallocationRecord,
+) -> Result<(), String> {
+    let decision_id = queries::get_optimizer_decision_for_pending(db, coordinator.account_id(), id)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|d| d.id);
+    let outcome = match record.plan() {
+        Ok(plan) => {
+            let reason = "Approved reallocation";
+            crate::scheduler::execute_plan(coordinator, reallocation, db, events, &plan, reason, decision_id)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => Err(format!("Stored plan is invalid: {}", e)),
+    };
+
+    let (status, note) = match &outcome {
+        Ok(()) => (PendingStatus::Executed, None),
+        Err(message) => (PendingStatus::Failed, Some(message.as_str())),
+    };
+    let decision = if note.is_none() { DecisionOutcome::Executed } else { DecisionOutcome::Failed };
+    settle_decision(db, coordinator.account_id(), id, decision, note).await;
+    queries::transition_pending_reallocation(
+        db,
+        coordinator.account_id(),
+        id,
+        PendingStatus::Approved,
+        status,
+        note,
+    )
+        .await
+        .map_err(|e| e.to_string())?;
+    outcome
+}
+
+/// POST /api/v1/reallocations/{id}/reject - Reject a pending plan
+pub async fn reject_reallocation(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    id: web::Path<i64>,
+    req: Option<web::Json<RejectReallocationRequest>>,
+) -> ActixResult<HttpResponse> {
+    let id = id.into_inner();
+    load_pending_reallocation(db.get_ref(), account.0, id).await?;
+
+    let reason = req.and_then(|r| r.into_inner().reason);
+    let rejected = queries::transition_pending_reallocation(
+        db.get_ref(),
+        account.0,
+        id,
+        PendingStatus::Pending,
+        PendingStatus::Rejected,
+        reason.as_deref(),
+    )
+    .await
+    .map_err(ApiError::from)?;
+    if !rejected {
+        return Err(already_decided(id).into());
+    }
+    let note = reason.as_deref().unwrap_or("Rejected by operator");
+    settle_decision(db.get_ref(), account.0, id, DecisionOutcome::Rejected, Some(note)).await;
+    decided_reallocation(db.get_ref(), account.0, id).await
+}
+
+/// Record what became of the optimizer decision that queued pending plan `id`
+async fn settle_decision(db: &SqlitePool, account_id: i64, id: i64, outcome: DecisionOutcome, reason: Option<&str>) {
+    let decision = match queries::get_optimizer_decision_for_pending(db, account_id, id).await {
+        Ok(Some(decision)) => decision,
+        Ok(None) => return,
+        Err(e) => {
+            log::error!("❌ Failed to load optimizer decision for reallocation {}: {}", id, e);
+            return;
+        }
+    };
+    if let Some(decision_id) = decision.id {
+        if let Err(e) = queries::set_optimizer_decision_outcome(db, account_id, decision_id, outcome, reason).await {
+            log::error!("❌ Failed to update optimizer decision #{}: {}", decision_id, e);
+        }
+    }
+}
+
+/// Load a reallocation that is still pending
+async fn load_pending_reallocation(
+    db: &SqlitePool,
+    account_id: i64,
+    id: i64,
+) -> Result<PendingReallocationRecord, ApiError> {
+    match queries::get_pending_reallocation(db, account_id, id).await? {
+        Some(record) if record.is_pending() => Ok(record),
+        Some(_) => Err(already_decided(id)),
+        None => Err(not_found(id)),
+    }
+}
+
+/// Response with the reallocation's state after a decision
+async fn decided_reallocation(db: &SqlitePool, account_id: i64, id: i64) -> ActixResult<HttpResponse> {
+    let record = queries::get_pending_reallocation(db, account_id, id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| not_found(id))?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(pending_reallocation_dto(&record))))
+}
+
+/// Convert pending reallocation record to its DTO
+fn pending_reallocation_dto(record: &PendingReallocationRecord) -> PendingReallocationDto {
+    PendingReallocationDto {
+        id: record.id.unwrap_or_default(),
+        created_at: record.created_at.clone(),
+        status: record.status.clone(),
+        allocation: record.plan().map(|p| p.allocation).unwrap_or_default(),
+        estimated_improvement: record.estimated_improvement,
+        net_benefit: record.net_benefit,
+        confidence: record.confidence,
+        decided_at: record.decided_at.clone(),
+        note: record.note.clone(),
+    }
+}
+
+/// 404 for an unknown reallocation
+fn not_found(id: i64) -> ApiError {
+    ApiError::not_found(format!("Reallocation {} not found", id))
+}
+
+/// 409 for a reallocation that was already decided
+fn already_decided(id: i64) -> ApiError {
+    ApiError::new(
+        actix_web::http::StatusCode::CONFLICT,
+        "ALREADY_DECIDED",
+        format!("Reallocation {} is no longer pending", id),
+    )
+}
+
+/// GET /api/v1/decisions/{id} - Why the optimizer proposed, executed, or declined a plan
+pub async fn get_optimizer_decision(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    id: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    let id = id.into_inner();
+    let record: OptimizerDecisionRecord = queries::get_optimizer_decision(db.get_ref(), account.0, id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found(format!("Decision {} not found", id)))?;
+    let explanation = record
+        .explanation()
+        .map_err(|e| ApiError::internal("INVALID_DECISION", format!("Stored decision is invalid: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(OptimizerDecisionDto {
+        id,
+        created_at: record.created_at,
+        outcome: record.outcome,
+        reason: record.reason,
+        pending_reallocation_id: record.pending_reallocation_id,
+        explanation,
+    })))
 }
 
 // ============================================================================
@@ -243,40 +859,29 @@ pub async fn get_reallocation_history(
 // ============================================================================
 
 /// GET /api/v1/dashboard - Get dashboard data
+///
+/// Served from the snapshot the polling task keeps current; `refresh=true`
+/// (or a missing snapshot) rebuilds it from the latest metrics.
 pub async fn get_dashboard(
-    state: web::Data<AppState>,
+    state: OrchestratorState,
+    req: web::Query<DashboardRequest>,
+    display: Option<web::Data<DisplayConfig>>,
 ) -> ActixResult<HttpResponse> {
-    match state.coordinator.get_current_metrics().await {
-        Ok(Some(metrics)) => {
-            let optimizer = state.optimizer.lock().await;
-            let opportunities = optimizer
-                .analyze_opportunities(&metrics)
-                .unwrap_or_default();
-
-            let response = DashboardResponse {
-                timestamp: Utc::now(),
-                total_earnings_per_hour: metrics.total_earnings_per_hour,
-                earnings_by_protocol: metrics.earnings_by_protocol.clone(),
-                current_allocation: metrics.allocation_by_protocol.clone(),
-                optimal_allocation: if let Ok(plan) = optimizer.calculate_optimal_allocation(&metrics) {
-                    plan.allocation
-                } else {
-                    metrics.allocation_by_protocol.clone()
-                },
-                next_reallocation_in: Some(3600), // 1 hour
-                connection_status: metrics.connection_status.clone(),
-                alerts_count: 0, // Would fetch from monitor
-            };
-
-            Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
+    let display = display.map(|d| d.into_inner()).unwrap_or_default();
+    if !req.refresh {
+        if let Some(snapshot) = state.dashboard.get() {
+            let snapshot = dashboard::in_currency(snapshot, &display);
+            return Ok(HttpResponse::Ok().json(SuccessResponse::new(&*snapshot)));
         }
-        _ => {
-            let error = ErrorResponse::new(
-                "NO_DATA".to_string(),
-                "No metrics available".to_string(),
-            );
-            Ok(HttpResponse::NotFound().json(error))
+    }
+
+    match state.coordinator.get_current_metrics().await? {
+        Some(metrics) => {
+            let snapshot = state.dashboard.refresh(&metrics, &state.optimizer, &state.coordinator.uptime());
+            let snapshot = dashboard::in_currency(snapshot, &display);
+            Ok(HttpResponse::Ok().json(SuccessResponse::new(&*snapshot)))
         }
+        None => Err(ApiError::no_data().into()),
     }
 }
 
@@ -284,55 +889,297 @@ pub async fn get_dashboard(
 // ALERT ENDPOINTS
 // ============================================================================
 
+/// Stored alerts at or above this severity count as critical
+const CRITICAL_ALERT_SEVERITY: f64 = 75.0;
+
 /// GET /api/v1/alerts - Get alerts
+///
+/// Stored alerts newest first, filtered by severity, acknowledgement,
+/// protocol, and time range and paged by `cursor` or `offset`.
 pub async fn get_alerts(
-    state: web::Data<AppState>,
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Query<AlertsRequest>,
 ) -> ActixResult<HttpResponse> {
-    let alerts = state.monitor.get_alert_history().await;
-
-    let alert_dtos: Vec<AlertDto> = alerts
-        .into_iter()
-        .map(|a| AlertDto {
-            timestamp: a.timestamp,
-            alert_type: format!("{:?}", a.alert_type),
-            severity: a.severity,
-            message: a.message,
-            acknowledged: a.acknowledged,
-        })
-        .collect();
+    let req = req.into_inner();
+    let page = req.page().map_err(|e| ApiError::bad_request("INVALID_PAGE", e))?;
+    let filter = AlertFilter {
+        min_severity: req.min_severity,
+        acknowledged: req.acknowledged,
+        protocol: req.protocol,
+        start: req.start,
+        end: req.end,
+    };
 
-    let total_count = alert_dtos.len();
+    let records = queries::list_alerts(db.get_ref(), account.0, &filter, page)
+        .await
+        .map_err(ApiError::from)?;
+    let (total_count, critical_count) =
+        queries::count_alerts(db.get_ref(), account.0, &filter, CRITICAL_ALERT_SEVERITY)
+            .await
+            .map_err(ApiError::from)?;
 
+    let next_cursor = page.next_cursor(records.len(), records.last().and_then(|r| r.id));
     let response = AlertsResponse {
-        alerts: alert_dtos,
-        total_count,
-        critical_count: 0,
+        alerts: records.into_iter().map(AlertDto::from).collect(),
+        total_count: total_count as usize,
+        critical_count: critical_count as u32,
+        next_cursor,
     };
 
     Ok(HttpResponse::Ok().json(SuccessResponse::new(response)))
 }
 
-/// POST /api/v1/alerts/acknowledge - Acknowledge alert
+/// POST /api/v1/alerts/acknowledge - Acknowledge alert by timestamp
+///
+/// Kept for existing clients; `POST /api/v1/alerts/{id}/acknowledge` is
+/// preferred since several alerts can share a timestamp.
 pub async fn acknowledge_alert(
-    state: web::Data<AppState>,
+    state: OrchestratorState,
+    db: web::Data<SqlitePool>,
+    account: AccountId,
     req: web::Json<AcknowledgeAlertRequest>,
 ) -> ActixResult<HttpResponse> {
-    match state.monitor.acknowledge_alert(req.timestamp).await {
-        Ok(_) => {
-            Ok(HttpResponse::Ok().json(SuccessResponse::new(
-                serde_json::json!({"acknowledged": true})
-            )))
+    let ids = queries::acknowledge_alert(db.get_ref(), account.0, req.timestamp)
+        .await
+        .map_err(ApiError::from)?;
+    let in_memory = state.monitor.acknowledge_alert(req.timestamp).await.is_ok();
+    state.monitor.acknowledge_alerts(&ids).await;
+
+    if ids.is_empty() && !in_memory {
+        return Err(ApiError::not_found("Alert not found").into());
+    }
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(
+        serde_json::json!({"acknowledged": true})
+    )))
+}
+
+/// POST /api/v1/alerts/{id}/acknowledge - Acknowledge one alert by id
+pub async fn acknowledge_alert_by_id(
+    state: OrchestratorState,
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    path: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    let id = path.into_inner();
+    let found = queries::acknowledge_alert_by_id(db.get_ref(), account.0, id)
+        .await
+        .map_err(ApiError::from)?;
+    if !found {
+        return Err(ApiError::not_found(format!("Alert {} not found", id)).into());
+    }
+    state.monitor.acknowledge_alerts(&[id]).await;
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(
+        serde_json::json!({"id": id, "acknowledged": true})
+    )))
+}
+
+/// POST /api/v1/alerts/acknowledge-all - Acknowledge every open alert
+///
+/// With `severity_below`, only alerts less severe than that are acknowledged.
+pub async fn acknowledge_all_alerts(
+    state: OrchestratorState,
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Query<AcknowledgeAllAlertsRequest>,
+) -> ActixResult<HttpResponse> {
+    let ids = queries::acknowledge_alerts(db.get_ref(), account.0, req.severity_below)
+        .await
+        .map_err(ApiError::from)?;
+    state.monitor.acknowledge_alerts(&ids).await;
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(
+        serde_json::json!({"acknowledged_count": ids.len()})
+    )))
+}
+
+// ============================================================================
+// WALLET ENDPOINTS
+// ============================================================================
+
+/// GET /api/v1/wallets - Latest balances of tracked payout addresses
+pub async fn get_wallets(db: web::Data<SqlitePool>, account: AccountId) -> ActixResult<HttpResponse> {
+    match queries::get_latest_wallet_balances(db.get_ref(), account.0).await {
+        Ok(records) => {
+            let total_usd = records.iter().filter_map(|r| r.balance_usd).sum();
+            let wallets = records
+                .into_iter()
+                .map(|r| WalletBalanceDto {
+                    label: r.label,
+                    chain: r.chain,
+                    address: r.address,
+                    protocol: r.protocol_name,
+                    symbol: r.symbol,
+                    balance: r.balance,
+                    balance_usd: r.balance_usd,
+                    polled_at: r.polled_at,
+                })
+                .collect();
+
+            Ok(HttpResponse::Ok().json(SuccessResponse::new(WalletsResponse {
+                wallets,
+                total_usd,
+            })))
+        }
+        Err(e) => Err(ApiError::from(e).into()),
+    }
+}
+
+// ============================================================================
+// NETWORK ENDPOINTS
+// ============================================================================
+
+/// GET /api/v1/network/probes - Measured latency and throughput history
+pub async fn get_network_probes(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Query<NetworkProbesRequest>,
+) -> ActixResult<HttpResponse> {
+    let since = Utc::now() - chrono::Duration::hours(req.hours.unwrap_or(168));
+    let records = queries::get_network_probes(db.get_ref(), account.0, since)
+        .await
+        .map_err(ApiError::from)?;
+    let probes = records
+        .into_iter()
+        .map(|r| NetworkProbeDto {
+            latency_ms: r.latency_ms,
+            download_mbps: r.download_mbps,
+            upload_mbps: r.upload_mbps,
+            measured_at: r.measured_at,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(NetworkProbesResponse { probes })))
+}
+
+/// GET /api/v1/network/public-ips - Latest public IP check by protocol
+pub async fn get_public_ips(state: OrchestratorState) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(state.coordinator.public_ips())))
+}
+
+// ============================================================================
+// PROTOCOL LIFECYCLE ENDPOINTS
+// ============================================================================
+
+/// POST /api/v1/protocols/{name}/connect - Connect a protocol adapter
+pub async fn connect_protocol(
+    state: OrchestratorState,
+    name: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let name = registered_protocol(&state, name.into_inner())?;
+    let health = state.coordinator.connect_protocol(&name).await?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(health)))
+}
+
+/// POST /api/v1/protocols/{name}/disconnect - Disconnect a protocol adapter
+pub async fn disconnect_protocol(
+    state: OrchestratorState,
+    name: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let name = registered_protocol(&state, name.into_inner())?;
+    let health = state.coordinator.disconnect_protocol(&name).await?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(health)))
+}
+
+/// POST /api/v1/protocols/{name}/container/{action} - Start, stop, or restart a protocol's container
+pub async fn control_protocol_container(
+    state: OrchestratorState,
+    path: web::Path<(String, String)>,
+) -> ActixResult<HttpResponse> {
+    let (name, action) = path.into_inner();
+    let name = registered_protocol(&state, name)?;
+    let action: ContainerAction = action
+        .parse()
+        .map_err(|e: String| ApiError::bad_request("INVALID_ACTION", e))?;
+    let Some(containers) = state.containers.as_ref().filter(|c| c.manages(&name)) else {
+        return Err(ApiError::not_found(format!("Protocol {} does not run in a managed container", name)).into());
+    };
+    containers.run(&name, action).await.map_err(container_error)?;
+    let health = containers.health(&name).await.map_err(container_error)?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(health)))
+}
+
+fn container_error(e: ContainerError) -> ApiError {
+    match e {
+        ContainerError::Disabled => {
+            ApiError::new(actix_web::http::StatusCode::NOT_IMPLEMENTED, "UNSUPPORTED", e.to_string())
         }
-        Err(_) => {
-            let error = ErrorResponse::new(
-                "NOT_FOUND".to_string(),
-                "Alert not found".to_string(),
-            );
-            Ok(HttpResponse::NotFound().json(error))
+        ContainerError::NotManaged(_) => ApiError::not_found(e.to_string()),
+        #[cfg(feature = "docker")]
+        ContainerError::Docker(_) => {
+            ApiError::new(actix_web::http::StatusCode::BAD_GATEWAY, "DOCKER_ERROR", e.to_string())
         }
     }
 }
 
+/// POST /api/v1/protocols/{name}/service/{action} - Start, stop, or restart a protocol's systemd unit
+pub async fn control_protocol_service(
+    state: OrchestratorState,
+    account: AccountId,
+    path: web::Path<(String, String)>,
+) -> ActixResult<HttpResponse> {
+    super::accounts::require_operator(account)?;
+    let (name, action) = path.into_inner();
+    let name = registered_protocol(&state, name)?;
+    let action: ServiceAction = action
+        .parse()
+        .map_err(|e: String| ApiError::bad_request("INVALID_ACTION", e))?;
+    let Some(services) = state.services.as_ref().filter(|s| s.manages(&name)) else {
+        return Err(ApiError::not_found(format!("Protocol {} does not run as a managed systemd unit", name)).into());
+    };
+    let outcome = services.run(&name, action).await.map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(outcome)))
+}
+
+fn service_error(e: ServiceError) -> ApiError {
+    use actix_web::http::StatusCode;
+    match e {
+        ServiceError::NotManaged(_) => ApiError::not_found(e.to_string()),
+        ServiceError::PermissionDenied { .. } => {
+            ApiError::new(StatusCode::FORBIDDEN, "SERVICE_FORBIDDEN", e.to_string())
+        }
+        ServiceError::CommandFailed { .. } | ServiceError::Io(_) => ApiError::internal("SERVICE_ERROR", e.to_string()),
+    }
+}
+
+/// GET /api/v1/protocols/{name}/metrics/schema - Protocol-specific metrics the adapter reports
+pub async fn get_protocol_metric_schema(
+    state: OrchestratorState,
+    name: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let protocol = registered_protocol(&state, name.into_inner())?;
+    let metrics = state.coordinator.metric_schemas().get(&protocol).cloned().unwrap_or_default();
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(MetricSchemaResponse { protocol, metrics })))
+}
+
+/// GET /api/v1/protocols/{name}/risk - Volatility of a protocol's hourly earnings
+///
+/// Refreshed hourly by the optimization task from the trailing week of
+/// earnings; 404 until the protocol has two hours of earnings on record.
+pub async fn get_protocol_risk(
+    state: OrchestratorState,
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    name: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let protocol = registered_protocol(&state, name.into_inner())?;
+    let record = queries::get_protocol_risk(db.get_ref(), account.0, &protocol)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found(format!("No earnings risk computed for {} yet", protocol)))?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(ProtocolRiskDto::from(record))))
+}
+
+/// `name` if it is a registered protocol, otherwise a 404
+fn registered_protocol(state: &AppState, name: String) -> Result<String, ApiError> {
+    if state.coordinator.adapters().contains_key(&name) {
+        Ok(name)
+    } else {
+        Err(ApiError::not_found(format!("Protocol {} is not registered", name)))
+    }
+}
+
 // ============================================================================
 // HEALTH & STATUS ENDPOINTS
 // ============================================================================
@@ -353,14 +1200,32 @@ pub async fn get_status(
     Ok(HttpResponse::Ok().json(SuccessResponse::new(
         serde_json::json!({
             "protocols": protocols,
+            "protocol_types": state.coordinator.protocol_types(),
             "timestamp": Utc::now(),
         })
     )))
 }
 
+/// GET /api/v1/admin/websocket - `/ws` session counters (default account only)
+pub async fn get_websocket_stats(state: web::Data<AppState>, account: AccountId) -> ActixResult<HttpResponse> {
+    super::accounts::require_operator(account)?;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(state.ws_hub.stats())))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::middleware::ApiKeyInfo;
+    use crate::protocols::mock::{EarningsCurve, MockAdapter, MockHandle};
+    use crate::protocols::ProtocolAdapter;
+    use crate::{
+        EarningsOptimizer, MonitorConfig, OptimizerConfig, ProtocolCoordinator, ReallocationConfig,
+        ReallocationEngine, RealtimeMonitor,
+    };
+    use actix_web::dev::Service as _;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::{App, HttpMessage};
+    use std::sync::Arc;
 
     #[test]
     fn test_error_response() {
@@ -371,4 +1236,160 @@ mod tests {
         assert_eq!(error.error, "TEST");
         assert_eq!(error.message, "Test message");
     }
+
+    /// State over two polled mocks at 50% each; returns grass' handle
+    async fn reallocate_state(config: ReallocationConfig) -> (AppState, MockHandle) {
+        let mut coordinator = ProtocolCoordinator::new(10);
+        let mut grass = MockAdapter::new("grass", EarningsCurve::Constant(2.0), 50.0);
+        grass.connect().await.unwrap();
+        let handle = grass.handle();
+        coordinator.register_adapter("grass".to_string(), Box::new(grass));
+        let mut streamr = MockAdapter::new("streamr", EarningsCurve::Constant(1.0), 50.0);
+        streamr.connect().await.unwrap();
+        coordinator.register_adapter("streamr".to_string(), Box::new(streamr));
+        coordinator.poll_all().await.unwrap();
+
+        let state = AppState::new(
+            Arc::new(coordinator),
+            Arc::new(EarningsOptimizer::new(OptimizerConfig::default())),
+            Arc::new(ReallocationEngine::new(config)),
+            Arc::new(RealtimeMonitor::new(MonitorConfig::default())),
+        );
+        (state, handle)
+    }
+
+    fn reallocate_request() -> TestRequest {
+        TestRequest::post().uri("/reallocate").set_json(serde_json::json!({
+            "allocation": { "grass": 70.0, "streamr": 30.0 },
+            "reason": "Manual rebalance",
+        }))
+    }
+
+    #[actix_web::test]
+    async fn test_reallocate_executes_through_the_engine() {
+        let pool = crate::db::test_pool().await;
+        let (state, grass) = reallocate_state(ReallocationConfig::default()).await;
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .app_data(web::Data::new(pool.clone()))
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(ApiKeyInfo::for_account(1));
+                    srv.call(req)
+                })
+                .route("/reallocate", web::post().to(execute_reallocation)),
+        )
+        .await;
+
+        let res = call_service(&app, reallocate_request().to_request()).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::OK);
+        let body: serde_json::Value = read_body_json(res).await;
+        assert_eq!(body["data"]["changes"].as_array().unwrap().len(), 2);
+        assert_eq!(grass.applied(), vec![70.0]);
+
+        let recorded = queries::count_reallocations(&pool, 1, &ReallocationFilter::default()).await.unwrap();
+        assert_eq!(recorded, 2);
+    }
+
+    #[actix_web::test]
+    async fn test_reallocate_waits_for_approval_when_confirmation_is_required() {
+        let pool = crate::db::test_pool().await;
+        let config = ReallocationConfig { require_confirmation: true, ..Default::default() };
+        let (state, grass) = reallocate_state(config).await;
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .app_data(web::Data::new(pool.clone()))
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(ApiKeyInfo::for_account(1));
+                    srv.call(req)
+                })
+                .route("/reallocate", web::post().to(execute_reallocation)),
+        )
+        .await;
+
+        let res = call_service(&app, reallocate_request().to_request()).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::ACCEPTED);
+        let body: serde_json::Value = read_body_json(res).await;
+        assert!(body["data"]["changes"].as_array().unwrap().is_empty());
+        assert!(grass.applied().is_empty());
+
+        let pending = queries::get_pending_reallocations(&pool, 1).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(body["data"]["pending_id"], pending[0].id.unwrap());
+        assert_eq!(pending[0].plan().unwrap().allocation["grass"], 70.0);
+    }
+
+    #[actix_web::test]
+    async fn test_replayed_idempotency_key_does_not_reallocate_again() {
+        use super::super::middleware::{IdempotencyMiddleware, IDEMPOTENCY_KEY_HEADER};
+
+        let pool = crate::db::test_pool().await;
+        let (state, grass) = reallocate_state(ReallocationConfig::default()).await;
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .app_data(web::Data::new(pool.clone()))
+                .wrap(IdempotencyMiddleware::new(Arc::new(pool.clone())))
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(ApiKeyInfo::for_account(1));
+                    srv.call(req)
+                })
+                .route("/reallocate", web::post().to(execute_reallocation)),
+        )
+        .await;
+        let request = || reallocate_request().insert_header((IDEMPOTENCY_KEY_HEADER, "rebalance-1")).to_request();
+
+        let res = call_service(&app, request()).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::OK);
+        let first: serde_json::Value = read_body_json(res).await;
+
+        // The retry gets the stored response; the mocks see one allocation
+        let res = call_service(&app, request()).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::OK);
+        let replayed: serde_json::Value = read_body_json(res).await;
+        assert_eq!(replayed, first);
+        assert_eq!(grass.applied(), vec![70.0]);
+        let recorded = queries::count_reallocations(&pool, 1, &ReallocationFilter::default()).await.unwrap();
+        assert_eq!(recorded, 2);
+    }
+
+    #[actix_web::test]
+    async fn test_public_ips_are_only_shown_to_the_owning_account() {
+        let (state, _) = reallocate_state(ReallocationConfig::default()).await;
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .route("/status", web::get().to(get_status))
+                .route("/public-ips", web::get().to(get_public_ips))
+                .route("/websocket", web::get().to(get_websocket_stats))
+                .wrap_fn(|req, srv| {
+                    let account = req.headers().get("x-account").map_or(1, |v| v.to_str().unwrap().parse().unwrap());
+                    req.extensions_mut().insert(ApiKeyInfo::for_account(account));
+                    srv.call(req)
+                }),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::get().uri("/status").to_request()).await;
+        let status: serde_json::Value = read_body_json(res).await;
+        assert!(status["data"].get("protocols").is_some());
+        assert!(status["data"].get("public_ips").is_none());
+        assert!(status["data"].get("network_probe").is_none());
+        assert!(status["data"].get("websocket").is_none());
+
+        let res = call_service(&app, TestRequest::get().uri("/public-ips").to_request()).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::OK);
+        let req = TestRequest::get().uri("/public-ips").insert_header(("x-account", "2")).to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+        // Session counters span every account, so only the operator sees them
+        let res = call_service(&app, TestRequest::get().uri("/websocket").to_request()).await;
+        let stats: serde_json::Value = read_body_json(res).await;
+        assert_eq!(stats["data"]["active"], 0);
+        let req = TestRequest::get().uri("/websocket").insert_header(("x-account", "2")).to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::FORBIDDEN);
+    }
 }
@@ -1,25 +1,40 @@
 /// Request Middleware
 ///
 /// Middleware for request tracking, authentication, rate limiting, and validation.
+use actix_cors::Cors;
 use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    error::ResponseError,
-    http::StatusCode,
-    Error, HttpMessage, HttpResponse,
+    body::{self, BoxBody, MessageBody},
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::{InternalError, ResponseError},
+    http::{
+        header::{self, HeaderName, HeaderValue},
+        Method, StatusCode,
+    },
+    middleware::DefaultHeaders,
+    web, Error, FromRequest, HttpMessage, HttpRequest, HttpResponse,
 };
 use bcrypt;
-use chrono::{DateTime, Utc};
-use futures_util::future::LocalBoxFuture;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::{future::LocalBoxFuture, StreamExt};
+use jsonwebtoken::{decode, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::fmt;
 use std::{
     rc::Rc,
-    sync::Arc,
-    time::{Duration, SystemTime},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
+use tracing::Instrument;
 use uuid::Uuid;
 
+use super::problem::{problem_response, ApiError, ProblemDetails, REQUEST_ID};
+use crate::config::ConfigError;
+use crate::db::{models::IdempotencyRecord, queries};
+use crate::shutdown::ShutdownToken;
+
 // ============================================================================
 // ERROR TYPES
 // ============================================================================
@@ -30,6 +45,13 @@ pub enum AuthError {
     InvalidApiKey,
     ExpiredApiKey,
     InactiveApiKey,
+    InactiveAccount,
+    InvalidToken,
+    ExpiredToken,
+    InvalidCredentials,
+    InvalidSession,
+    /// Too many failed logins; retry after this many seconds
+    TooManyLoginAttempts(u64),
     DatabaseError(String),
 }
 
@@ -40,6 +62,14 @@ impl fmt::Display for AuthError {
             AuthError::InvalidApiKey => write!(f, "Invalid API key"),
             AuthError::ExpiredApiKey => write!(f, "API key has expired"),
             AuthError::InactiveApiKey => write!(f, "API key is inactive"),
+            AuthError::InactiveAccount => write!(f, "Account is inactive"),
+            AuthError::InvalidToken => write!(f, "Invalid bearer token"),
+            AuthError::ExpiredToken => write!(f, "Bearer token has expired"),
+            AuthError::InvalidCredentials => write!(f, "Invalid username or password"),
+            AuthError::InvalidSession => write!(f, "Dashboard session is missing or has expired"),
+            AuthError::TooManyLoginAttempts(retry_after) => {
+                write!(f, "Too many failed logins. Retry after {} seconds", retry_after)
+            }
             AuthError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
         }
     }
@@ -52,36 +82,86 @@ impl ResponseError for AuthError {
             AuthError::InvalidApiKey => StatusCode::UNAUTHORIZED,
             AuthError::ExpiredApiKey => StatusCode::UNAUTHORIZED,
             AuthError::InactiveApiKey => StatusCode::FORBIDDEN,
+            AuthError::InactiveAccount => StatusCode::FORBIDDEN,
+            AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthError::ExpiredToken => StatusCode::UNAUTHORIZED,
+            AuthError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            AuthError::InvalidSession => StatusCode::UNAUTHORIZED,
+            AuthError::TooManyLoginAttempts(_) => StatusCode::TOO_MANY_REQUESTS,
             AuthError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code()).json(serde_json::json!({
-            "error": "AUTHENTICATION_ERROR",
-            "message": self.to_string(),
-            "timestamp": Utc::now().to_rfc3339(),
-        }))
+        let code = match self {
+            AuthError::MissingApiKey => "MISSING_API_KEY",
+            AuthError::InvalidApiKey => "INVALID_API_KEY",
+            AuthError::ExpiredApiKey => "EXPIRED_API_KEY",
+            AuthError::InactiveApiKey => "INACTIVE_API_KEY",
+            AuthError::InactiveAccount => "INACTIVE_ACCOUNT",
+            AuthError::InvalidToken => "INVALID_TOKEN",
+            AuthError::ExpiredToken => "EXPIRED_TOKEN",
+            AuthError::InvalidCredentials => "INVALID_CREDENTIALS",
+            AuthError::InvalidSession => "INVALID_SESSION",
+            AuthError::TooManyLoginAttempts(_) => "TOO_MANY_LOGIN_ATTEMPTS",
+            AuthError::DatabaseError(_) => "DATABASE_ERROR",
+        };
+        if let AuthError::TooManyLoginAttempts(retry_after) = self {
+            let mut builder = HttpResponse::build(self.status_code());
+            builder.insert_header(("Retry-After", retry_after.to_string()));
+            return ProblemDetails::new(self.status_code(), code, self.to_string()).respond(builder);
+        }
+        problem_response(self.status_code(), code, self.to_string())
     }
 }
 
 #[derive(Debug)]
 pub enum RateLimitError {
-    Exceeded { retry_after: u64 },
-    DatabaseError(String),
+    /// The per-minute budget is used up
+    Exceeded {
+        retry_after: u64,
+        status: RateLimitStatus,
+    },
+    /// The daily budget is used up until the next UTC day
+    DailyQuotaExceeded {
+        retry_after: u64,
+        status: RateLimitStatus,
+    },
+}
+
+impl RateLimitError {
+    fn retry_after(&self) -> u64 {
+        match self {
+            RateLimitError::Exceeded { retry_after, .. }
+            | RateLimitError::DailyQuotaExceeded { retry_after, .. } => *retry_after,
+        }
+    }
+
+    fn status(&self) -> &RateLimitStatus {
+        match self {
+            RateLimitError::Exceeded { status, .. }
+            | RateLimitError::DailyQuotaExceeded { status, .. } => status,
+        }
+    }
 }
 
 impl fmt::Display for RateLimitError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            RateLimitError::Exceeded { retry_after } => {
+            RateLimitError::Exceeded { retry_after, .. } => {
                 write!(
                     f,
                     "Rate limit exceeded. Retry after {} seconds",
                     retry_after
                 )
             }
-            RateLimitError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            RateLimitError::DailyQuotaExceeded { retry_after, .. } => {
+                write!(
+                    f,
+                    "Daily request quota exceeded. Retry after {} seconds",
+                    retry_after
+                )
+            }
         }
     }
 }
@@ -94,23 +174,25 @@ impl ResponseError for RateLimitError {
     fn error_response(&self) -> HttpResponse {
         let mut builder = HttpResponse::build(self.status_code());
 
-        if let RateLimitError::Exceeded { retry_after } = self {
-            builder.insert_header(("Retry-After", retry_after.to_string()));
-            builder.insert_header((
-                "X-RateLimit-Reset",
-                (SystemTime::now() + Duration::from_secs(*retry_after))
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs()
-                    .to_string(),
-            ));
+        let retry_after = self.retry_after();
+        builder.insert_header(("Retry-After", retry_after.to_string()));
+        builder.insert_header((
+            "X-RateLimit-Reset",
+            (SystemTime::now() + Duration::from_secs(retry_after))
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+                .to_string(),
+        ));
+        for header in self.status().headers() {
+            builder.insert_header(header);
         }
 
-        builder.json(serde_json::json!({
-            "error": "RATE_LIMIT_EXCEEDED",
-            "message": self.to_string(),
-            "timestamp": Utc::now().to_rfc3339(),
-        }))
+        let code = match self {
+            RateLimitError::Exceeded { .. } => "RATE_LIMIT_EXCEEDED",
+            RateLimitError::DailyQuotaExceeded { .. } => "DAILY_QUOTA_EXCEEDED",
+        };
+        ProblemDetails::new(self.status_code(), code, self.to_string()).respond(builder)
     }
 }
 
@@ -118,25 +200,213 @@ impl ResponseError for RateLimitError {
 // API KEY MODELS
 // ============================================================================
 
+/// Who a request is authenticated as
+///
+/// Serialized as `{"kind": "api_key", "id": 7}`; in bearer tokens it is the
+/// `sub` claim, `key:7` or `user:3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum Principal {
+    /// An API key, by key ID
+    ApiKey(i64),
+    /// A dashboard user signed in with a password, by user ID
+    DashboardUser(i64),
+}
+
+impl Principal {
+    /// ID of the API key, when authenticated with one
+    pub fn api_key_id(&self) -> Option<i64> {
+        match self {
+            Principal::ApiKey(id) => Some(*id),
+            Principal::DashboardUser(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Principal::ApiKey(id) => write!(f, "key:{}", id),
+            Principal::DashboardUser(id) => write!(f, "user:{}", id),
+        }
+    }
+}
+
+impl std::str::FromStr for Principal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, id) = s.split_once(':').ok_or_else(|| format!("invalid principal '{}'", s))?;
+        let id = id.parse().map_err(|_| format!("invalid principal '{}'", s))?;
+        match kind {
+            "key" => Ok(Principal::ApiKey(id)),
+            "user" => Ok(Principal::DashboardUser(id)),
+            _ => Err(format!("invalid principal '{}'", s)),
+        }
+    }
+}
+
+/// `Principal` as a string, for the `sub` claim
+mod principal_subject {
+    use super::Principal;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(principal: &Principal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(principal)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Principal, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKeyInfo {
-    pub id: i64,
+    /// API key or dashboard user the request is authenticated as
+    #[serde(flatten)]
+    pub principal: Principal,
+    /// Account the key belongs to; requests made with it are scoped to it
+    pub account_id: i64,
     pub name: String,
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
     pub last_used_at: Option<DateTime<Utc>>,
     pub is_active: bool,
-    pub rate_limit_per_minute: i32,
+    /// Rate limit tier of the key's account
+    pub tier: RateLimitTier,
     pub permissions: Vec<String>,
 }
 
+#[cfg(test)]
+impl ApiKeyInfo {
+    /// Active read/write key of `account_id`, for handler tests
+    pub(crate) fn for_account(account_id: i64) -> Self {
+        Self {
+            principal: Principal::ApiKey(1),
+            account_id,
+            name: "test".to_string(),
+            description: None,
+            created_at: Utc::now(),
+            expires_at: None,
+            last_used_at: None,
+            is_active: true,
+            tier: RateLimitTier::Free,
+            permissions: vec!["read".to_string(), "write".to_string()],
+        }
+    }
+}
+
+/// Account of the authenticated caller
+///
+/// Extracted from the key info `AuthMiddleware` attaches to the request;
+/// handlers pass it to every account-scoped query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountId(pub i64);
+
+impl AccountId {
+    /// Whether this is the default (operator) account
+    pub fn is_default(&self) -> bool {
+        self.0 == crate::db::DEFAULT_ACCOUNT_ID
+    }
+}
+
+impl FromRequest for AccountId {
+    type Error = Error;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        std::future::ready(
+            req.extensions()
+                .get::<ApiKeyInfo>()
+                .map(|key| AccountId(key.account_id))
+                .ok_or_else(|| AuthError::MissingApiKey.into()),
+        )
+    }
+}
+
+// ============================================================================
+// CORS & SECURITY HEADERS
+// ============================================================================
+
+/// Parse a comma-separated origin list; `*` allows any origin
+pub fn parse_allowed_origins(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/'))
+        .filter(|origin| !origin.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// CORS policy for origins listed in `CORS_ALLOWED_ORIGINS`
+///
+/// Without the variable no cross-origin requests are allowed.
+pub fn cors_from_env() -> Cors {
+    let origins = parse_allowed_origins(&std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default());
+
+    let mut cors = Cors::default()
+        .allowed_methods(vec![
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allowed_headers(vec![
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            header::HeaderName::from_static("x-api-key"),
+            header::HeaderName::from_static(REQUEST_ID_HEADER),
+        ])
+        .expose_headers(vec![
+            header::RETRY_AFTER,
+            header::HeaderName::from_static("x-ratelimit-reset"),
+            header::HeaderName::from_static(REQUEST_ID_HEADER),
+        ])
+        .max_age(3600);
+
+    if origins.iter().any(|origin| origin == "*") {
+        cors = cors.allow_any_origin();
+    } else {
+        for origin in &origins {
+            cors = cors.allowed_origin(origin);
+        }
+    }
+    cors
+}
+
+/// Standard security headers added to every API response
+pub fn security_headers() -> DefaultHeaders {
+    DefaultHeaders::new()
+        .add((header::STRICT_TRANSPORT_SECURITY, "max-age=31536000; includeSubDomains"))
+        .add((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .add((header::X_FRAME_OPTIONS, "DENY"))
+        .add((header::REFERRER_POLICY, "no-referrer"))
+}
+
 // ============================================================================
 // REQUEST ID MIDDLEWARE
 // ============================================================================
 
+/// Header carrying the request ID, inbound and on every response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest inbound request ID that is accepted
+const MAX_REQUEST_ID_LEN: usize = 128;
+
 /// Request ID Middleware
 /// Attaches a unique request ID to each request
+///
+/// A well-formed inbound `X-Request-Id` is reused so a caller's trace
+/// continues through this service; otherwise a UUID is generated. The ID
+/// is echoed in the `X-Request-Id` response header and recorded on a
+/// `request` tracing span that wraps the rest of the request, so every log
+/// line for it can be correlated.
+///
+/// Errors from inner services are rendered while the ID is still in scope,
+/// so their problem details carry it.
 pub struct RequestIdMiddleware;
 
 impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
@@ -175,59 +445,253 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let request_id = Uuid::new_v4().to_string();
+        let request_id = inbound_request_id(&req).unwrap_or_else(|| Uuid::new_v4().to_string());
         let service = self.service.clone();
 
-        req.extensions_mut().insert(RequestId(request_id));
-
-        Box::pin(async move {
-            let res = service.call(req).await?;
-            Ok(res)
-        })
+        req.extensions_mut().insert(RequestId(request_id.clone()));
+        let span = tracing::info_span!(
+            "request",
+            request_id = %request_id,
+            method = %req.method(),
+            path = %req.path(),
+        );
+        let header_value = header::HeaderValue::from_str(&request_id).ok();
+
+        Box::pin(
+            REQUEST_ID
+                .scope(request_id, async move {
+                    let mut res = match service.call(req).await {
+                        Ok(res) => res,
+                        Err(e) => {
+                            // The request belongs to the failed service, so hand the
+                            // rendered response back as the error
+                            let mut response = e.error_response();
+                            if let Some(value) = header_value {
+                                response
+                                    .headers_mut()
+                                    .insert(header::HeaderName::from_static(REQUEST_ID_HEADER), value);
+                            }
+                            tracing::debug!(status = response.status().as_u16(), "request failed");
+                            return Err(InternalError::from_response(e, response).into());
+                        }
+                    };
+                    if let Some(value) = header_value {
+                        res.headers_mut()
+                            .insert(header::HeaderName::from_static(REQUEST_ID_HEADER), value);
+                    }
+                    tracing::debug!(status = res.status().as_u16(), "request completed");
+                    Ok(res)
+                })
+                .instrument(span),
+        )
     }
 }
 
+/// Inbound request ID, if present and safe to log and echo back
+fn inbound_request_id(req: &ServiceRequest) -> Option<String> {
+    let id = req.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?.trim();
+    let valid = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    valid.then(|| id.to_string())
+}
+
 /// Request ID wrapper
 #[derive(Clone, Debug)]
 pub struct RequestId(pub String);
 
+// ============================================================================
+// JWT TOKENS
+// ============================================================================
+
+/// Issuer claim of tokens minted by this server
+const JWT_ISSUER: &str = "depin-orcha";
+
+/// Signing configuration for short-lived bearer tokens
+///
+/// Tokens are exchanged for an API key at `POST /api/v1/auth/token` so browser
+/// dashboards never hold the long-lived key. They carry the key's identity,
+/// permissions, and rate limit; `AuthMiddleware` still checks the key (or
+/// dashboard user) against the database, so revoking or rotating a key also
+/// ends the tokens issued for it.
+#[derive(Clone)]
+pub struct JwtConfig {
+    secret: Vec<u8>,
+    /// Lifetime of issued tokens
+    pub ttl: Duration,
+}
+
+impl JwtConfig {
+    /// Create config signing with `secret`
+    pub fn new(secret: impl Into<Vec<u8>>, ttl: Duration) -> Self {
+        Self {
+            secret: secret.into(),
+            ttl,
+        }
+    }
+
+    /// Load from `JWT_SECRET` and `JWT_EXPIRATION_SECONDS` (default 900)
+    ///
+    /// Fails when `JWT_SECRET` is unset or empty.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let ttl = std::env::var("JWT_EXPIRATION_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(900);
+
+        let secret = std::env::var("JWT_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty())
+            .ok_or_else(|| ConfigError::Invalid(vec!["JWT_SECRET must be set to sign bearer tokens".to_string()]))?;
+
+        Ok(Self::new(secret, Duration::from_secs(ttl)))
+    }
+
+    /// Issue a token for an authenticated API key
+    pub fn issue(&self, key_info: &ApiKeyInfo) -> Result<(String, Claims), AuthError> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: key_info.principal,
+            account_id: key_info.account_id,
+            name: key_info.name.clone(),
+            permissions: key_info.permissions.clone(),
+            tier: key_info.tier,
+            iss: JWT_ISSUER.to_string(),
+            iat: now,
+            exp: now + self.ttl.as_secs() as i64,
+        };
+
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(&self.secret),
+        )
+        .map_err(|_| AuthError::InvalidToken)?;
+
+        Ok((token, claims))
+    }
+
+    /// Validate a token's signature, issuer, and expiry
+    pub fn validate(&self, token: &str) -> Result<Claims, AuthError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+        validation.set_issuer(&[JWT_ISSUER]);
+
+        decode::<Claims>(token, &DecodingKey::from_secret(&self.secret), &validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => AuthError::ExpiredToken,
+                _ => AuthError::InvalidToken,
+            })
+    }
+}
+
+impl fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("secret", &"<redacted>")
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+/// Claims carried by issued bearer tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// API key or dashboard user the token was issued for
+    #[serde(with = "principal_subject")]
+    pub sub: Principal,
+    /// Account of that key
+    #[serde(default = "default_account_id")]
+    pub account_id: i64,
+    pub name: String,
+    pub permissions: Vec<String>,
+    /// Rate limit tier of the key's account when the token was issued
+    #[serde(default)]
+    pub tier: RateLimitTier,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+fn default_account_id() -> i64 {
+    crate::db::DEFAULT_ACCOUNT_ID
+}
+
+impl Claims {
+    /// Key info attached to requests authenticated with this token
+    pub fn key_info(&self) -> ApiKeyInfo {
+        ApiKeyInfo {
+            principal: self.sub,
+            account_id: self.account_id,
+            name: self.name.clone(),
+            description: None,
+            created_at: DateTime::from_timestamp(self.iat, 0).unwrap_or_else(Utc::now),
+            expires_at: DateTime::from_timestamp(self.exp, 0),
+            last_used_at: None,
+            is_active: true,
+            tier: self.tier,
+            permissions: self.permissions.clone(),
+        }
+    }
+}
+
 // ============================================================================
 // AUTHENTICATION MIDDLEWARE
 // ============================================================================
 
-/// Authentication middleware for API key validation
+/// Authentication middleware for API key and bearer token validation
 pub struct AuthMiddleware {
     pub db: Arc<SqlitePool>,
+    pub jwt: Arc<JwtConfig>,
     pub skip_auth_paths: Vec<String>,
 }
 
 impl AuthMiddleware {
-    pub fn new(db: Arc<SqlitePool>) -> Self {
+    pub fn new(db: Arc<SqlitePool>, jwt: Arc<JwtConfig>) -> Self {
         Self {
             db,
-            skip_auth_paths: vec!["/api/v1/health".to_string(), "/ws".to_string()],
+            jwt,
+            skip_auth_paths: vec![
+                "/api/v1/health".to_string(),
+                "/api/v1/auth/token".to_string(),
+            ],
         }
     }
 
-    async fn validate_api_key(db: &SqlitePool, api_key: &str) -> Result<ApiKeyInfo, AuthError> {
+    pub(crate) async fn validate_api_key(db: &SqlitePool, api_key: &str) -> Result<ApiKeyInfo, AuthError> {
         // Fetch all active keys and verify with bcrypt
         let records = sqlx::query!(
             r#"
             SELECT
-                id, key_hash, name, description, created_at, expires_at, last_used_at,
-                is_active, rate_limit_per_minute, permissions
-            FROM api_keys
-            WHERE is_active = 1
+                k.id, k.account_id, k.key_hash, k.name, k.description, k.created_at,
+                k.expires_at, k.last_used_at, k.is_active, k.permissions,
+                k.previous_key_hash, k.previous_key_expires_at,
+                a.is_active AS account_active, a.tier
+            FROM api_keys k
+            JOIN accounts a ON a.id = k.account_id
+            WHERE k.is_active = 1
             "#
         )
         .fetch_all(db)
         .await
         .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
-        // Find matching key by verifying with bcrypt
+        // Find matching key by verifying with bcrypt; a rotated key's previous
+        // secret matches until its grace period ends
+        let now = Utc::now();
         let mut matching_record = None;
         for record in records {
-            if bcrypt::verify(api_key, &record.key_hash).unwrap_or(false) {
+            let previous_hash = record.previous_key_hash.as_deref().filter(|_| {
+                record
+                    .previous_key_expires_at
+                    .is_some_and(|expires| DateTime::<Utc>::from_naive_utc_and_offset(expires, Utc) > now)
+            });
+            if bcrypt::verify(api_key, &record.key_hash).unwrap_or(false)
+                || previous_hash.is_some_and(|hash| bcrypt::verify(api_key, hash).unwrap_or(false))
+            {
                 matching_record = Some(record);
                 break;
             }
@@ -240,6 +704,10 @@ impl AuthMiddleware {
             return Err(AuthError::InactiveApiKey);
         }
 
+        if !record.account_active {
+            return Err(AuthError::InactiveAccount);
+        }
+
         // Check if key has expired
         if let Some(expires_at) = record.expires_at {
             let expires: DateTime<Utc> = DateTime::from_naive_utc_and_offset(expires_at, Utc);
@@ -264,7 +732,8 @@ impl AuthMiddleware {
             serde_json::from_str(&record.permissions).unwrap_or_default();
 
         Ok(ApiKeyInfo {
-            id: record.id.unwrap_or(0),
+            principal: Principal::ApiKey(record.id.unwrap_or(0)),
+            account_id: record.account_id,
             name: record.name,
             description: record.description,
             created_at: DateTime::from_naive_utc_and_offset(record.created_at, Utc),
@@ -275,10 +744,77 @@ impl AuthMiddleware {
                 .last_used_at
                 .map(|l| DateTime::from_naive_utc_and_offset(l, Utc)),
             is_active: record.is_active,
-            rate_limit_per_minute: record.rate_limit_per_minute as i32,
+            tier: record.tier.parse().unwrap_or_default(),
             permissions,
         })
     }
+
+    /// Check that the API key or dashboard user a bearer token was issued
+    /// for may still use it
+    ///
+    /// Tokens of deleted, deactivated or expired keys, and of inactive users
+    /// or accounts, are refused. A token issued before its key's secret was
+    /// rotated lasts only as long as the previous secret does.
+    pub(crate) async fn validate_claims(db: &SqlitePool, claims: &Claims) -> Result<ApiKeyInfo, AuthError> {
+        let now = Utc::now();
+        match claims.sub {
+            Principal::ApiKey(key_id) => {
+                let record = sqlx::query!(
+                    r#"
+                    SELECT
+                        k.is_active, k.expires_at, k.rotated_at, k.previous_key_expires_at,
+                        a.is_active AS account_active
+                    FROM api_keys k
+                    JOIN accounts a ON a.id = k.account_id
+                    WHERE k.id = ? AND k.account_id = ?
+                    "#,
+                    key_id,
+                    claims.account_id
+                )
+                .fetch_optional(db)
+                .await
+                .map_err(|e| AuthError::DatabaseError(e.to_string()))?
+                .ok_or(AuthError::InvalidToken)?;
+
+                if !record.is_active {
+                    return Err(AuthError::InactiveApiKey);
+                }
+                if !record.account_active {
+                    return Err(AuthError::InactiveAccount);
+                }
+                let utc = |at| DateTime::<Utc>::from_naive_utc_and_offset(at, Utc);
+                if record.expires_at.is_some_and(|expires| utc(expires) <= now) {
+                    return Err(AuthError::ExpiredApiKey);
+                }
+                let rotated_since_issued = record.rotated_at.is_some_and(|at| utc(at).timestamp() > claims.iat);
+                let previous_expired = record.previous_key_expires_at.is_none_or(|expires| utc(expires) <= now);
+                if rotated_since_issued && previous_expired {
+                    return Err(AuthError::InvalidToken);
+                }
+            }
+            Principal::DashboardUser(user_id) => {
+                let record = sqlx::query!(
+                    r#"
+                    SELECT u.is_active AND a.is_active AS "active!: bool"
+                    FROM dashboard_users u
+                    JOIN accounts a ON a.id = u.account_id
+                    WHERE u.id = ? AND u.account_id = ?
+                    "#,
+                    user_id,
+                    claims.account_id
+                )
+                .fetch_optional(db)
+                .await
+                .map_err(|e| AuthError::DatabaseError(e.to_string()))?
+                .ok_or(AuthError::InvalidToken)?;
+
+                if !record.active {
+                    return Err(AuthError::InactiveAccount);
+                }
+            }
+        }
+        Ok(claims.key_info())
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for AuthMiddleware
@@ -297,6 +833,7 @@ where
         std::future::ready(Ok(AuthMiddlewareService {
             service: Rc::new(service),
             db: self.db.clone(),
+            jwt: self.jwt.clone(),
             skip_auth_paths: self.skip_auth_paths.clone(),
         }))
     }
@@ -305,6 +842,7 @@ where
 pub struct AuthMiddlewareService<S> {
     service: Rc<S>,
     db: Arc<SqlitePool>,
+    jwt: Arc<JwtConfig>,
     skip_auth_paths: Vec<String>,
 }
 
@@ -333,18 +871,34 @@ where
         }
 
         let db = self.db.clone();
+        let jwt = self.jwt.clone();
         let service = self.service.clone();
 
         Box::pin(async move {
-            // Extract API key from header
-            let api_key = req
+            let bearer = req
                 .headers()
-                .get("X-API-Key")
+                .get("Authorization")
                 .and_then(|h| h.to_str().ok())
-                .ok_or(AuthError::MissingApiKey)?;
-
-            // Validate API key
-            let key_info = AuthMiddleware::validate_api_key(&db, api_key).await?;
+                .and_then(|h| h.strip_prefix("Bearer "));
+
+            let key_info = match bearer {
+                // Validate bearer token
+                Some(token) => {
+                    let claims = jwt.validate(token.trim())?;
+                    AuthMiddleware::validate_claims(&db, &claims).await?
+                }
+                None => {
+                    // Extract API key from header
+                    let api_key = req
+                        .headers()
+                        .get("X-API-Key")
+                        .and_then(|h| h.to_str().ok())
+                        .ok_or(AuthError::MissingApiKey)?;
+
+                    // Validate API key
+                    AuthMiddleware::validate_api_key(&db, api_key).await?
+                }
+            };
 
             // Store key info in request extensions
             req.extensions_mut().insert(key_info);
@@ -360,69 +914,359 @@ where
 // RATE LIMITING MIDDLEWARE
 // ============================================================================
 
-/// Rate limiting middleware
-pub struct RateLimitMiddleware {
-    pub db: Arc<SqlitePool>,
-    pub window_duration: Duration,
+/// Rate limit tier of an account; its budgets are set in `[rate_limits]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitTier {
+    #[default]
+    Free,
+    Pro,
+    Admin,
 }
 
-impl RateLimitMiddleware {
-    pub fn new(db: Arc<SqlitePool>) -> Self {
+impl RateLimitTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitTier::Free => "free",
+            RateLimitTier::Pro => "pro",
+            RateLimitTier::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for RateLimitTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RateLimitTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "free" => Ok(RateLimitTier::Free),
+            "pro" => Ok(RateLimitTier::Pro),
+            "admin" => Ok(RateLimitTier::Admin),
+            other => Err(format!("Unknown rate limit tier '{}' (expected free, pro, or admin)", other)),
+        }
+    }
+}
+
+/// Request budgets of one tier
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TierLimits {
+    /// Sustained requests per minute
+    pub per_minute: u32,
+    /// Requests that can be made back to back before the per-minute rate applies
+    pub burst: u32,
+    /// Requests per UTC day; unlimited when unset
+    pub per_day: Option<u64>,
+}
+
+impl TierLimits {
+    fn validate(&self) -> Result<(), String> {
+        if self.per_minute == 0 {
+            return Err("per_minute must be at least 1".to_string());
+        }
+        if self.burst == 0 {
+            return Err("burst must be at least 1".to_string());
+        }
+        if self.per_day == Some(0) {
+            return Err("per_day must be at least 1 (omit it for no daily limit)".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Rate limit tiers (`[rate_limits]` section)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub free: TierLimits,
+    pub pro: TierLimits,
+    pub admin: TierLimits,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
         Self {
-            db,
-            window_duration: Duration::from_secs(60), // 1 minute window
+            free: TierLimits {
+                per_minute: 60,
+                burst: 30,
+                per_day: Some(10_000),
+            },
+            pro: TierLimits {
+                per_minute: 600,
+                burst: 120,
+                per_day: Some(250_000),
+            },
+            admin: TierLimits {
+                per_minute: 6000,
+                burst: 1000,
+                per_day: None,
+            },
         }
     }
+}
 
-    async fn check_rate_limit(
-        db: &SqlitePool,
-        api_key_id: i64,
-        endpoint: &str,
-        limit: i32,
-        window_duration: Duration,
-    ) -> Result<(), RateLimitError> {
-        let window_start = Utc::now() - chrono::Duration::from_std(window_duration).unwrap();
-        let window_start_naive = window_start.naive_utc();
-
-        // Count requests in current window
-        let count = sqlx::query!(
-            r#"
-            SELECT COALESCE(SUM(request_count), 0) as count
-            FROM rate_limit_log
-            WHERE api_key_id = ? AND window_start >= ?
-            "#,
-            api_key_id,
-            window_start_naive
-        )
-        .fetch_one(db)
-        .await
-        .map_err(|e| RateLimitError::DatabaseError(e.to_string()))?;
+impl RateLimitConfig {
+    /// Budgets of `tier`
+    pub fn limits(&self, tier: RateLimitTier) -> &TierLimits {
+        match tier {
+            RateLimitTier::Free => &self.free,
+            RateLimitTier::Pro => &self.pro,
+            RateLimitTier::Admin => &self.admin,
+        }
+    }
+
+    /// Check that every tier is usable
+    pub fn validate(&self) -> Result<(), String> {
+        for tier in [RateLimitTier::Free, RateLimitTier::Pro, RateLimitTier::Admin] {
+            self.limits(tier)
+                .validate()
+                .map_err(|e| format!("{}.{}", tier, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Budget left after a request, reported in `X-RateLimit-*` headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Sustained requests per minute of the caller's tier
+    pub limit: u32,
+    /// Requests that can be made right now
+    pub remaining: u32,
+    /// Requests per UTC day, if the tier has a daily budget
+    pub daily_limit: Option<u64>,
+    pub daily_remaining: Option<u64>,
+}
+
+impl RateLimitStatus {
+    /// `X-RateLimit-Limit`/`Remaining`, plus the `-Day` pair for daily budgets
+    pub fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = vec![
+            (HeaderName::from_static("x-ratelimit-limit"), HeaderValue::from(self.limit)),
+            (HeaderName::from_static("x-ratelimit-remaining"), HeaderValue::from(self.remaining)),
+        ];
+        if let (Some(limit), Some(remaining)) = (self.daily_limit, self.daily_remaining) {
+            headers.push((HeaderName::from_static("x-ratelimit-limit-day"), HeaderValue::from(limit)));
+            headers.push((
+                HeaderName::from_static("x-ratelimit-remaining-day"),
+                HeaderValue::from(remaining),
+            ));
+        }
+        headers
+    }
+}
+
+/// Token bucket and daily count for one account
+#[derive(Debug, Clone)]
+struct AccountBudget {
+    tokens: f64,
+    last_refill: Instant,
+    /// Time for an empty bucket to refill completely
+    refill_time: Duration,
+    day: NaiveDate,
+    used_today: u64,
+}
+
+/// In-memory rate limiter with per-account budgets set by tier
+///
+/// Each account has a token bucket holding up to its tier's `burst`, refilled
+/// at `per_minute`, and a count of requests made this UTC day. All keys of an
+/// account share its budgets. Request counts are accumulated in memory per key
+/// and endpoint and periodically written to `rate_limit_log` for
+/// observability, so the request path never touches the database.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    budgets: Mutex<HashMap<i64, AccountBudget>>,
+    pending_counts: Mutex<HashMap<(i64, String), i64>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter enforcing the tier budgets in `config`
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            budgets: Mutex::new(HashMap::new()),
+            pending_counts: Mutex::new(HashMap::new()),
+        }
+    }
 
-        let current_count = count.count;
+    /// Tier budgets being enforced
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
 
-        if current_count >= limit {
-            return Err(RateLimitError::Exceeded {
-                retry_after: window_duration.as_secs(),
+    /// Count the request against the key's account, or report when to retry
+    pub fn check(&self, key: &ApiKeyInfo, endpoint: &str) -> Result<RateLimitStatus, RateLimitError> {
+        self.check_at(key, endpoint, Instant::now(), Utc::now())
+    }
+
+    fn check_at(
+        &self,
+        key: &ApiKeyInfo,
+        endpoint: &str,
+        now: Instant,
+        wall_clock: DateTime<Utc>,
+    ) -> Result<RateLimitStatus, RateLimitError> {
+        let limits = self.config.limits(key.tier);
+        let capacity = f64::from(limits.burst);
+        let refill_per_sec = f64::from(limits.per_minute) / 60.0;
+        let today = wall_clock.date_naive();
+
+        let status = {
+            let mut budgets = self.budgets.lock().unwrap_or_else(|e| e.into_inner());
+            let budget = budgets.entry(key.account_id).or_insert(AccountBudget {
+                tokens: capacity,
+                last_refill: now,
+                refill_time: Duration::ZERO,
+                day: today,
+                used_today: 0,
             });
+
+            let elapsed = now.saturating_duration_since(budget.last_refill).as_secs_f64();
+            budget.tokens = (budget.tokens + elapsed * refill_per_sec).min(capacity);
+            budget.last_refill = now;
+            budget.refill_time = Duration::from_secs_f64(capacity / refill_per_sec);
+            if budget.day != today {
+                budget.day = today;
+                budget.used_today = 0;
+            }
+
+            let status = |budget: &AccountBudget| RateLimitStatus {
+                limit: limits.per_minute,
+                remaining: budget.tokens.max(0.0).floor() as u32,
+                daily_limit: limits.per_day,
+                daily_remaining: limits.per_day.map(|d| d.saturating_sub(budget.used_today)),
+            };
+
+            if limits.per_day.is_some_and(|d| budget.used_today >= d) {
+                let midnight = today
+                    .succ_opt()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|t| t.and_utc())
+                    .unwrap_or(wall_clock);
+                return Err(RateLimitError::DailyQuotaExceeded {
+                    retry_after: (midnight - wall_clock).num_seconds().max(1) as u64,
+                    status: status(budget),
+                });
+            }
+            if budget.tokens < 1.0 {
+                let retry_after = ((1.0 - budget.tokens) / refill_per_sec).ceil() as u64;
+                return Err(RateLimitError::Exceeded {
+                    retry_after: retry_after.max(1),
+                    status: status(budget),
+                });
+            }
+            budget.tokens -= 1.0;
+            budget.used_today += 1;
+            status(budget)
+        };
+
+        // Dashboard users have no API key row to log against
+        if let Some(api_key_id) = key.principal.api_key_id() {
+            *self
+                .pending_counts
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry((api_key_id, endpoint.to_string()))
+                .or_insert(0) += 1;
         }
 
-        // Record this request
-        let now = Utc::now().naive_utc();
-        let _ = sqlx::query!(
-            r#"
-            INSERT INTO rate_limit_log (api_key_id, endpoint, request_count, window_start)
-            VALUES (?, ?, 1, ?)
-            ON CONFLICT(api_key_id, endpoint, window_start) DO UPDATE SET
-                request_count = request_count + 1
-            "#,
-            api_key_id,
-            endpoint,
-            now
-        )
-        .execute(db)
-        .await;
+        Ok(status)
+    }
 
-        Ok(())
+    /// Write accumulated request counts to `rate_limit_log` and evict idle budgets
+    pub async fn persist(&self, db: &SqlitePool) -> Result<usize, sqlx::Error> {
+        let counts: Vec<((i64, String), i64)> = self
+            .pending_counts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain()
+            .collect();
+
+        {
+            // A refilled bucket from an earlier day is the same as a new one
+            let now = Instant::now();
+            let today = Utc::now().date_naive();
+            self.budgets
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .retain(|_, budget| {
+                    budget.day == today
+                        || now.saturating_duration_since(budget.last_refill) < budget.refill_time
+                });
+        }
+
+        if counts.is_empty() {
+            return Ok(0);
+        }
+
+        let window_start = Utc::now().naive_utc();
+        let mut tx = db.begin().await?;
+        for ((api_key_id, endpoint), count) in &counts {
+            sqlx::query(
+                "INSERT INTO rate_limit_log (api_key_id, endpoint, request_count, window_start) VALUES (?, ?, ?, ?)",
+            )
+            .bind(api_key_id)
+            .bind(endpoint)
+            .bind(count)
+            .bind(window_start)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(counts.len())
+    }
+
+    /// Persist counts every `interval` and prune log rows older than a day
+    ///
+    /// Pending counts are flushed one last time on `shutdown`.
+    pub fn start_persistence(
+        self: Arc<Self>,
+        db: Arc<SqlitePool>,
+        interval: Duration,
+        shutdown: ShutdownToken,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut last_cleanup = Instant::now();
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.cancelled() => {
+                        if let Err(e) = self.persist(&db).await {
+                            log::warn!("⚠️  Failed to flush rate limit counts: {}", e);
+                        }
+                        break;
+                    }
+                }
+                if let Err(e) = self.persist(&db).await {
+                    log::warn!("⚠️  Failed to persist rate limit counts: {}", e);
+                }
+                if last_cleanup.elapsed() >= Duration::from_secs(3600) {
+                    RateLimitMiddleware::cleanup_old_logs(&db, Duration::from_secs(86400)).await;
+                    last_cleanup = Instant::now();
+                }
+            }
+        })
+    }
+}
+
+/// Rate limiting middleware
+pub struct RateLimitMiddleware {
+    pub limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitMiddleware {
+    /// Enforce limits with a limiter shared across workers
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
     }
 
     async fn cleanup_old_logs(db: &SqlitePool, retention: Duration) {
@@ -451,29 +1295,16 @@ where
     type Future = std::future::Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        // Spawn cleanup task
-        let db = self.db.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(3600)); // Cleanup hourly
-            loop {
-                interval.tick().await;
-                RateLimitMiddleware::cleanup_old_logs(&db, Duration::from_secs(86400)).await;
-                // Keep 24 hours
-            }
-        });
-
         std::future::ready(Ok(RateLimitMiddlewareService {
             service: Rc::new(service),
-            db: self.db.clone(),
-            window_duration: self.window_duration,
+            limiter: self.limiter.clone(),
         }))
     }
 }
 
 pub struct RateLimitMiddlewareService<S> {
     service: Rc<S>,
-    db: Arc<SqlitePool>,
-    window_duration: Duration,
+    limiter: Arc<RateLimiter>,
 }
 
 impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
@@ -489,34 +1320,239 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let db = self.db.clone();
-        let window_duration = self.window_duration;
+        let limiter = self.limiter.clone();
         let service = self.service.clone();
         let endpoint = req.path().to_string();
 
         Box::pin(async move {
             // Get API key info from extensions (set by AuthMiddleware)
             let key_info = req.extensions().get::<ApiKeyInfo>().cloned();
+            let status = match key_info {
+                Some(key_info) => Some(limiter.check(&key_info, &endpoint)?),
+                None => None,
+            };
+
+            // Continue with request, reporting the remaining budget
+            let mut res = service.call(req).await?;
+            if let Some(status) = status {
+                for (name, value) in status.headers() {
+                    res.headers_mut().insert(name, value);
+                }
+            }
+            Ok(res)
+        })
+    }
+}
 
-            if let Some(key_info) = key_info {
-                // Check rate limit
-                RateLimitMiddleware::check_rate_limit(
-                    &db,
-                    key_info.id,
-                    &endpoint,
-                    key_info.rate_limit_per_minute,
-                    window_duration,
-                )
-                .await?;
+// ============================================================================
+// IDEMPOTENCY MIDDLEWARE
+// ============================================================================
+
+/// Header a client sets to make a mutating request safe to retry
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header set on responses replayed for a repeated key
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted idempotency key
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Largest request body that is buffered to fingerprint a request
+const MAX_IDEMPOTENT_BODY: usize = 1024 * 1024;
+
+/// Idempotency middleware
+///
+/// A POST, PUT, PATCH, or DELETE carrying an `Idempotency-Key` header is
+/// executed once per account and key: its response is stored in the
+/// `idempotency_keys` table and replayed, with `Idempotent-Replayed: true`,
+/// when the key is sent again. Reusing a key for a different request is
+/// rejected with 422, and retrying while the first request is still running
+/// with 409. Server errors are not stored, so the request can be retried.
+///
+/// Must run inside `AuthMiddleware`, which identifies the account.
+pub struct IdempotencyMiddleware {
+    db: Arc<SqlitePool>,
+}
+
+impl IdempotencyMiddleware {
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self { db }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IdempotencyMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = IdempotencyMiddlewareService<S>;
+    type Future = std::future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        std::future::ready(Ok(IdempotencyMiddlewareService {
+            service: Rc::new(service),
+            db: self.db.clone(),
+        }))
+    }
+}
+
+pub struct IdempotencyMiddlewareService<S> {
+    service: Rc<S>,
+    db: Arc<SqlitePool>,
+}
+
+impl<S, B> Service<ServiceRequest> for IdempotencyMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let db = self.db.clone();
+
+        let mutating = matches!(*req.method(), Method::POST | Method::PUT | Method::PATCH | Method::DELETE);
+        let key = req
+            .headers()
+            .get(IDEMPOTENCY_KEY_HEADER)
+            .map(|value| value.to_str().map(|s| s.trim().to_string()));
+        let account_id = req.extensions().get::<ApiKeyInfo>().map(|info| info.account_id);
+        let (key, account_id) = match (mutating, key, account_id) {
+            (true, Some(key), Some(account_id)) => (key, account_id),
+            _ => return Box::pin(async move { Ok(service.call(req).await?.map_into_boxed_body()) }),
+        };
+
+        Box::pin(async move {
+            let key = key
+                .ok()
+                .filter(|k| !k.is_empty() && k.len() <= MAX_IDEMPOTENCY_KEY_LEN)
+                .ok_or_else(|| {
+                    ApiError::bad_request(
+                        "INVALID_IDEMPOTENCY_KEY",
+                        format!("Idempotency-Key must be 1-{} visible ASCII characters", MAX_IDEMPOTENCY_KEY_LEN),
+                    )
+                })?;
+
+            // Fingerprint the request, then hand the buffered body back to it
+            let mut payload = req.take_payload();
+            let mut body = web::BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                body.extend_from_slice(&chunk?);
+                if body.len() > MAX_IDEMPOTENT_BODY {
+                    return Err(ApiError::new(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "PAYLOAD_TOO_LARGE",
+                        "Request body is too large",
+                    )
+                    .into());
+                }
+            }
+            let body = body.freeze();
+            let request_hash = request_fingerprint(req.method(), &req.uri().to_string(), &body);
+            req.set_payload(Payload::from(body));
+
+            if !queries::claim_idempotency_key(&db, account_id, &key, &request_hash)
+                .await
+                .map_err(ApiError::from)?
+            {
+                let record = queries::get_idempotency_record(&db, account_id, &key)
+                    .await
+                    .map_err(ApiError::from)?;
+                let response = match record {
+                    Some(record) if record.request_hash != request_hash => {
+                        return Err(ApiError::new(
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            "IDEMPOTENCY_KEY_REUSED",
+                            "Idempotency-Key was already used for a different request",
+                        )
+                        .into());
+                    }
+                    Some(record) => replayed_response(record)?,
+                    // Released by a failed request since the claim was attempted
+                    None => return Err(idempotency_in_progress().into()),
+                };
+                return Ok(req.into_response(response));
             }
 
-            // Continue with request
-            let res = service.call(req).await?;
-            Ok(res)
+            let res = match service.call(req).await {
+                Ok(res) => res,
+                Err(e) => {
+                    let _ = queries::release_idempotency_key(&db, account_id, &key).await;
+                    return Err(e);
+                }
+            };
+            if res.status().is_server_error() {
+                let _ = queries::release_idempotency_key(&db, account_id, &key).await;
+                return Ok(res.map_into_boxed_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let (res, body) = res.into_parts();
+            let body = match body::to_bytes(body).await {
+                Ok(body) => body,
+                Err(_) => {
+                    let _ = queries::release_idempotency_key(&db, account_id, &key).await;
+                    return Err(ApiError::internal("RESPONSE_ERROR", "Failed to read response body").into());
+                }
+            };
+            let content_type = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok());
+            queries::complete_idempotency_key(&db, account_id, &key, res.status().as_u16(), content_type, &body)
+                .await
+                .map_err(ApiError::from)?;
+
+            Ok(ServiceResponse::new(req, res.set_body(BoxBody::new(body))))
         })
     }
 }
 
+/// 409 for a key whose first request has not finished
+fn idempotency_in_progress() -> ApiError {
+    ApiError::new(
+        StatusCode::CONFLICT,
+        "IDEMPOTENCY_IN_PROGRESS",
+        "A request with this Idempotency-Key is in progress",
+    )
+}
+
+/// Hash of what makes two requests the same: method, path and query, and body
+fn request_fingerprint(method: &Method, uri: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b"\n");
+    hasher.update(uri);
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// Response stored for a key, or 409 while the first request is running
+fn replayed_response(record: IdempotencyRecord) -> Result<HttpResponse, ApiError> {
+    let status = record
+        .status_code
+        .and_then(|code| StatusCode::from_u16(code as u16).ok())
+        .ok_or_else(idempotency_in_progress)?;
+
+    let mut response = HttpResponse::build(status);
+    response.insert_header((HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER), "true"));
+    if let Some(content_type) = record.content_type {
+        response.insert_header((header::CONTENT_TYPE, content_type));
+    }
+    Ok(response.body(record.response_body.unwrap_or_default()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -532,4 +1568,336 @@ mod tests {
         let uuid = Uuid::new_v4().to_string();
         assert_eq!(uuid.len(), 36); // UUID v4 string length
     }
+
+    fn key_info() -> ApiKeyInfo {
+        ApiKeyInfo {
+            principal: Principal::ApiKey(7),
+            account_id: 3,
+            name: "dashboard".to_string(),
+            description: None,
+            created_at: Utc::now(),
+            expires_at: None,
+            last_used_at: None,
+            is_active: true,
+            tier: RateLimitTier::Pro,
+            permissions: vec!["read".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_jwt_roundtrip() {
+        let jwt = JwtConfig::new("test-secret", Duration::from_secs(60));
+        let (token, issued) = jwt.issue(&key_info()).unwrap();
+
+        let claims = jwt.validate(&token).unwrap();
+        assert_eq!(claims.sub, Principal::ApiKey(7));
+        assert_eq!(claims.exp, issued.exp);
+
+        let info = claims.key_info();
+        assert_eq!(info.tier, RateLimitTier::Pro);
+        assert_eq!(info.account_id, 3);
+        assert_eq!(info.permissions, vec!["read".to_string()]);
+    }
+
+    #[test]
+    fn test_jwt_rejects_wrong_secret_and_expired_tokens() {
+        let jwt = JwtConfig::new("test-secret", Duration::from_secs(60));
+        let (token, _) = jwt.issue(&key_info()).unwrap();
+
+        let other = JwtConfig::new("other-secret", Duration::from_secs(60));
+        assert!(matches!(other.validate(&token), Err(AuthError::InvalidToken)));
+        assert!(matches!(jwt.validate("not-a-token"), Err(AuthError::InvalidToken)));
+
+        let expired = JwtConfig::new("test-secret", Duration::from_secs(0));
+        let (token, _) = expired.issue(&key_info()).unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+        assert!(matches!(expired.validate(&token), Err(AuthError::ExpiredToken)));
+    }
+
+    fn limiter(per_minute: u32, burst: u32, per_day: Option<u64>) -> RateLimiter {
+        let limits = TierLimits {
+            per_minute,
+            burst,
+            per_day,
+        };
+        RateLimiter::new(RateLimitConfig {
+            free: limits,
+            ..RateLimitConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_token_bucket_limits_and_refills() {
+        let limiter = limiter(3, 3, None);
+        let key = ApiKeyInfo::for_account(1);
+        let (start, now) = (Instant::now(), Utc::now());
+
+        for remaining in [2, 1, 0] {
+            let status = limiter.check_at(&key, "/api/v1/metrics", start, now).unwrap();
+            assert_eq!(status.remaining, remaining);
+            assert_eq!(status.limit, 3);
+        }
+        match limiter.check_at(&key, "/api/v1/metrics", start, now) {
+            Err(RateLimitError::Exceeded { retry_after, status }) => {
+                assert_eq!(retry_after, 20);
+                assert_eq!(status.remaining, 0);
+            }
+            other => panic!("expected rate limit, got {:?}", other),
+        }
+
+        // Every key and endpoint of an account shares its budget
+        let other_key = ApiKeyInfo {
+            principal: Principal::ApiKey(2),
+            ..key.clone()
+        };
+        assert!(limiter.check_at(&other_key, "/api/v1/alerts", start, now).is_err());
+        limiter
+            .check_at(&ApiKeyInfo::for_account(2), "/api/v1/metrics", start, now)
+            .unwrap();
+
+        // One token refills every 20 seconds
+        limiter
+            .check_at(&key, "/api/v1/metrics", start + Duration::from_secs(20), now)
+            .unwrap();
+
+        // Higher tiers get larger budgets
+        let admin = ApiKeyInfo {
+            tier: RateLimitTier::Admin,
+            ..ApiKeyInfo::for_account(3)
+        };
+        let status = limiter.check_at(&admin, "/api/v1/metrics", start, now).unwrap();
+        assert_eq!(status.limit, RateLimitConfig::default().admin.per_minute);
+        assert_eq!(status.daily_limit, None);
+    }
+
+    #[test]
+    fn test_daily_quota_resets_at_midnight() {
+        let limiter = limiter(60, 10, Some(2));
+        let key = ApiKeyInfo::for_account(1);
+        let start = Instant::now();
+        let evening = DateTime::parse_from_rfc3339("2026-10-15T23:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        limiter.check_at(&key, "/api/v1/metrics", start, evening).unwrap();
+        let status = limiter.check_at(&key, "/api/v1/metrics", start, evening).unwrap();
+        assert_eq!(status.daily_remaining, Some(0));
+        match limiter.check_at(&key, "/api/v1/metrics", start, evening) {
+            Err(RateLimitError::DailyQuotaExceeded { retry_after, .. }) => {
+                assert_eq!(retry_after, 3600)
+            }
+            other => panic!("expected daily quota, got {:?}", other),
+        }
+
+        let status = limiter
+            .check_at(&key, "/api/v1/metrics", start, evening + chrono::Duration::hours(1))
+            .unwrap();
+        assert_eq!(status.daily_remaining, Some(1));
+        let names: Vec<_> = status.headers().into_iter().map(|(name, _)| name).collect();
+        assert!(names.contains(&HeaderName::from_static("x-ratelimit-remaining-day")));
+    }
+
+    #[test]
+    fn test_rate_limit_config_parsing() {
+        let config: RateLimitConfig = toml::from_str(
+            r#"
+            [pro]
+            per_minute = 100
+            burst = 20
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.pro.per_day, None);
+        assert_eq!(config.free, RateLimitConfig::default().free);
+        assert!(config.validate().is_ok());
+
+        let invalid = RateLimitConfig {
+            free: TierLimits {
+                burst: 0,
+                ..config.free
+            },
+            ..config
+        };
+        assert_eq!(invalid.validate().unwrap_err(), "free.burst must be at least 1");
+        assert_eq!("pro".parse::<RateLimitTier>().unwrap(), RateLimitTier::Pro);
+        assert!("gold".parse::<RateLimitTier>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_counts_are_persisted() {
+        let pool = crate::db::test_pool().await;
+        sqlx::query("INSERT INTO api_keys (id, key_hash, name) VALUES (1, 'hash', 'test')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let key = ApiKeyInfo::for_account(1);
+        limiter.check(&key, "/api/v1/metrics").unwrap();
+        limiter.check(&key, "/api/v1/metrics").unwrap();
+        limiter.check(&key, "/api/v1/alerts").unwrap();
+
+        assert_eq!(limiter.persist(&pool).await.unwrap(), 2);
+        let (total,): (i64,) = sqlx::query_as("SELECT SUM(request_count) FROM rate_limit_log")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(total, 3);
+
+        // Counts are drained once written
+        assert_eq!(limiter.persist(&pool).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_parse_allowed_origins() {
+        assert_eq!(
+            parse_allowed_origins(" https://dash.example.com/, http://localhost:3000 ,"),
+            vec!["https://dash.example.com", "http://localhost:3000"]
+        );
+        assert!(parse_allowed_origins("").is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_cors_preflight_and_security_headers() {
+        use actix_web::{test, web, App};
+
+        std::env::set_var("CORS_ALLOWED_ORIGINS", "https://dash.example.com");
+        let app = test::init_service(
+            App::new()
+                .wrap(security_headers())
+                .wrap(cors_from_env())
+                .route("/ping", web::get().to(|| async { HttpResponse::Ok().finish() })),
+        )
+        .await;
+        std::env::remove_var("CORS_ALLOWED_ORIGINS");
+
+        let preflight = test::TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/ping")
+            .insert_header((header::ORIGIN, "https://dash.example.com"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "GET"))
+            .to_request();
+        let res = test::call_service(&app, preflight).await;
+        assert_eq!(
+            res.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://dash.example.com"
+        );
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/ping").to_request()).await;
+        assert_eq!(res.headers().get(header::X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+        assert_eq!(res.headers().get(header::X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert!(res.headers().contains_key(header::STRICT_TRANSPORT_SECURITY));
+    }
+
+    #[actix_web::test]
+    async fn test_errors_render_as_problem_details_with_request_id() {
+        use crate::api::problem::{ApiError, ProblemDetails, PROBLEM_JSON};
+        use actix_web::{test, web, App};
+
+        let app = test::init_service(App::new().wrap(RequestIdMiddleware).route(
+            "/missing",
+            web::get().to(|| async {
+                Err::<HttpResponse, _>(ApiError::not_found("Nothing here"))
+            }),
+        ))
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/missing").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), PROBLEM_JSON);
+        let header_id = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+
+        let problem: ProblemDetails = test::read_body_json(res).await;
+        assert_eq!(problem.code, "NOT_FOUND");
+        assert_eq!(problem.detail, "Nothing here");
+        assert_eq!(problem.request_id.as_deref(), Some(header_id.as_str()));
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_header_is_echoed_or_generated() {
+        use actix_web::{test, web, App};
+
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestIdMiddleware)
+                .route("/ping", web::get().to(|| async { HttpResponse::Ok().finish() })),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/ping")
+            .insert_header((REQUEST_ID_HEADER, "upstream-trace-42"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "upstream-trace-42");
+
+        // Malformed inbound IDs are replaced rather than echoed
+        let req = test::TestRequest::get()
+            .uri("/ping")
+            .insert_header((REQUEST_ID_HEADER, "bad id <script>"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let generated = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap();
+        assert!(Uuid::parse_str(generated).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_idempotency_key_replays_the_first_response() {
+        use actix_web::{dev::Service as _, test, web, App};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let pool = crate::db::test_pool().await;
+        let executions = Arc::new(AtomicUsize::new(0));
+
+        let counter = executions.clone();
+        let app = test::init_service(
+            App::new()
+                .wrap(IdempotencyMiddleware::new(Arc::new(pool)))
+                .wrap_fn(|req, srv| {
+                    req.extensions_mut().insert(ApiKeyInfo::for_account(1));
+                    srv.call(req)
+                })
+                .route(
+                    "/reallocate",
+                    web::post().to(move |body: web::Bytes| {
+                        let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                        async move { HttpResponse::Created().json(serde_json::json!({ "run": n, "len": body.len() })) }
+                    }),
+                ),
+        )
+        .await;
+        let post = |key: Option<&str>, body: &'static str| {
+            let req = test::TestRequest::post().uri("/reallocate").set_payload(body);
+            match key {
+                Some(key) => req.insert_header((IDEMPOTENCY_KEY_HEADER, key)),
+                None => req,
+            }
+            .to_request()
+        };
+
+        let res = test::call_service(&app, post(Some("retry-1"), "{\"a\":1}")).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert!(res.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        let first: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(first, serde_json::json!({ "run": 1, "len": 7 }));
+
+        // A retry is answered from the table without running the handler
+        let res = test::call_service(&app, post(Some("retry-1"), "{\"a\":1}")).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers().get(IDEMPOTENT_REPLAYED_HEADER).unwrap(), "true");
+        assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "application/json");
+        let replayed: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(replayed, first);
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+
+        // The same key with a different body is rejected
+        let err = test::try_call_service(&app, post(Some("retry-1"), "{\"a\":2}")).await.unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        // Other keys, and requests without one, execute
+        test::call_service(&app, post(Some("retry-2"), "{\"a\":1}")).await;
+        test::call_service(&app, post(None, "{\"a\":1}")).await;
+        test::call_service(&app, post(None, "{\"a\":1}")).await;
+        assert_eq!(executions.load(Ordering::SeqCst), 4);
+    }
 }
@@ -1,4 +1,14 @@
+pub mod access_log;
+pub mod accounts;
+pub mod analysis;
 pub mod auth;
+pub mod backups;
+pub mod credentials;
+pub mod currency;
+pub mod dashboard;
+pub mod diff;
+pub mod export;
+pub mod grafana;
 /// API Module - HTTP REST & WebSocket Server
 ///
 /// Provides RESTful endpoints for orchestration operations and real-time
@@ -6,12 +16,25 @@ pub mod auth;
 pub mod handlers;
 pub mod middleware;
 pub mod models;
+pub mod notifications;
+pub mod problem;
+pub mod reports;
 pub mod routes;
+pub mod sessions;
+pub mod timeline;
+pub mod ui;
 pub mod websocket;
 
-use crate::{EarningsOptimizer, ProtocolCoordinator, ReallocationEngine, RealtimeMonitor};
-use actix_web::{middleware::Logger, web, App, HttpServer};
+use crate::containers::ContainerManager;
+use crate::events::EventBus;
+use crate::scheduler::{SchedulerConfig, SchedulerStatus};
+use crate::services::ServiceManager;
+use crate::shutdown::ShutdownToken;
+use crate::{EarningsOptimizer, MlOptimizer, ProtocolCoordinator, ReallocationEngine, RealtimeMonitor};
+use actix_web::{dev::Payload, middleware::Logger, web, App, FromRequest, HttpRequest, HttpServer};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tokio::sync::watch;
 use tracing::info;
 
 // ============================================================================
@@ -22,16 +45,34 @@ use tracing::info;
 #[derive(Clone)]
 pub struct AppState {
     pub coordinator: Arc<ProtocolCoordinator>,
-    pub optimizer: Arc<tokio::sync::Mutex<EarningsOptimizer>>,
+    pub optimizer: Arc<EarningsOptimizer>,
     pub reallocation: Arc<ReallocationEngine>,
     pub monitor: Arc<RealtimeMonitor>,
+    /// Dashboard view, rebuilt by the polling task after every poll
+    pub dashboard: Arc<dashboard::DashboardCache>,
+    /// Refines heuristic plans with ML recommendations when configured
+    pub ml_optimizer: Option<Arc<MlOptimizer>>,
+    /// Docker containers protocol nodes run in, when managed
+    pub containers: Option<Arc<ContainerManager>>,
+    /// systemd units protocol daemons run as, when managed
+    pub services: Option<Arc<ServiceManager>>,
+    /// Fans out real-time updates to WebSocket sessions
+    pub ws_hub: Arc<websocket::WsHub>,
+    /// Metrics, alerts and reallocations published by the orchestrator
+    pub events: Arc<EventBus>,
+    /// Live scheduler configuration, read by the background tasks each tick
+    pub scheduler_config: Arc<watch::Sender<SchedulerConfig>>,
+    /// Liveness of the background tasks, kept by the scheduler watchdog
+    pub scheduler_status: Arc<SchedulerStatus>,
+    /// Closes WebSocket sessions on shutdown
+    pub shutdown: ShutdownToken,
 }
 
 impl AppState {
     /// Create new application state
     pub fn new(
         coordinator: Arc<ProtocolCoordinator>,
-        optimizer: Arc<tokio::sync::Mutex<EarningsOptimizer>>,
+        optimizer: Arc<EarningsOptimizer>,
         reallocation: Arc<ReallocationEngine>,
         monitor: Arc<RealtimeMonitor>,
     ) -> Self {
@@ -40,21 +81,107 @@ impl AppState {
             optimizer,
             reallocation,
             monitor,
+            dashboard: Arc::new(dashboard::DashboardCache::new()),
+            ml_optimizer: None,
+            containers: None,
+            services: None,
+            ws_hub: Arc::new(websocket::WsHub::default()),
+            events: Arc::new(EventBus::default()),
+            scheduler_config: Arc::new(watch::channel(SchedulerConfig::default()).0),
+            scheduler_status: Arc::new(SchedulerStatus::new()),
+            shutdown: ShutdownToken::never(),
         }
     }
+
+    /// Attach an ML optimizer
+    pub fn with_ml_optimizer(mut self, ml_optimizer: Arc<MlOptimizer>) -> Self {
+        self.ml_optimizer = Some(ml_optimizer);
+        self
+    }
+
+    /// Manage protocol containers through `containers`
+    pub fn with_containers(mut self, containers: Arc<ContainerManager>) -> Self {
+        self.containers = Some(containers);
+        self
+    }
+
+    /// Manage protocol systemd units through `services`
+    pub fn with_services(mut self, services: Arc<ServiceManager>) -> Self {
+        self.services = Some(services);
+        self
+    }
+
+    /// Account that owns the orchestrator behind this state
+    pub fn account_id(&self) -> i64 {
+        self.coordinator.account_id()
+    }
+
+    /// `404 NO_ORCHESTRATOR` unless `account` owns the orchestrator
+    pub fn check_owner(&self, account: middleware::AccountId) -> Result<(), problem::ApiError> {
+        if account.0 == self.account_id() {
+            Ok(())
+        } else {
+            Err(problem::ApiError::new(
+                actix_web::http::StatusCode::NOT_FOUND,
+                "NO_ORCHESTRATOR",
+                "No orchestrator is running for this account",
+            ))
+        }
+    }
+}
+
+/// Application state, extracted only for the account that owns the orchestrator
+///
+/// The live coordinator, optimizer, and scheduler belong to a single account;
+/// callers from other accounts get `404 NO_ORCHESTRATOR` instead of seeing
+/// that account's protocols.
+pub struct OrchestratorState(web::Data<AppState>);
+
+impl std::ops::Deref for OrchestratorState {
+    type Target = web::Data<AppState>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequest for OrchestratorState {
+    type Error = actix_web::Error;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let mut extract = || -> Result<Self, Self::Error> {
+            let state = web::Data::<AppState>::from_request(req, payload).into_inner()?;
+            let account = middleware::AccountId::from_request(req, payload).into_inner()?;
+            state.check_owner(account)?;
+            Ok(Self(state))
+        };
+        std::future::ready(extract())
+    }
 }
 
 // ============================================================================
 // SERVER SETUP
 // ============================================================================
 
-/// API Server Configuration
-#[derive(Debug, Clone)]
+/// API Server Configuration (`[server]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ApiConfig {
     pub host: String,
     pub port: u16,
     pub workers: usize,
     pub request_timeout: u64,
+    /// Seconds to wait for background tasks and connections on shutdown
+    pub shutdown_timeout: u64,
+    /// Account the orchestrator runs for; only its keys see the live state
+    pub account_id: i64,
+    /// Seconds a rotated API key's previous secret keeps working
+    pub key_rotation_grace_secs: u64,
+    /// Seconds a dashboard login stays valid
+    pub session_ttl_secs: u64,
+    /// Mark dashboard session cookies `Secure` (HTTPS only, or localhost)
+    pub secure_cookies: bool,
 }
 
 impl Default for ApiConfig {
@@ -64,6 +191,11 @@ impl Default for ApiConfig {
             port: 8080,
             workers: 4,
             request_timeout: 30,
+            shutdown_timeout: 30,
+            account_id: crate::db::DEFAULT_ACCOUNT_ID,
+            key_rotation_grace_secs: 86400,
+            session_ttl_secs: 43200,
+            secure_cookies: true,
         }
     }
 }
@@ -113,6 +245,11 @@ mod tests {
             port: 3000,
             workers: 8,
             request_timeout: 60,
+            shutdown_timeout: 10,
+            account_id: 2,
+            key_rotation_grace_secs: 0,
+            session_ttl_secs: 3600,
+            secure_cookies: false,
         };
         assert_eq!(config.port, 3000);
     }
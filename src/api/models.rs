@@ -2,6 +2,14 @@ use chrono::{DateTime, Utc};
 /// API Request/Response Models
 ///
 /// Data structures for HTTP requests and responses.
+use crate::api::currency::Currency;
+use crate::db::models::{AlertRecord, MetricsAggregation, MetricsInterval, Page, ReallocationRecord};
+use crate::orchestration::ml_optimizer::PlanSource;
+use crate::orchestration::optimizer::{DecisionExplanation, Strategy};
+use crate::orchestration::reallocation::ProtocolConstraint;
+use crate::orchestration::ProtocolUptime;
+use crate::protocols::MetricSchema;
+use crate::scheduler::SchedulerConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -13,11 +21,16 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsResponse {
     pub timestamp: DateTime<Utc>,
+    /// Currency of the earnings amounts
+    pub currency: Currency,
     pub total_earnings_per_hour: f64,
     pub earnings_by_protocol: HashMap<String, f64>,
     pub allocation_by_protocol: HashMap<String, f64>,
     pub connection_status: HashMap<String, bool>,
     pub resource_utilization: ResourceUtilizationDto,
+    /// Protocols whose data came from the response cache, with its age in seconds
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub stale_seconds: HashMap<String, u64>,
 }
 
 /// Resource utilization DTO
@@ -57,6 +70,8 @@ pub struct OpportunityDto {
     pub projected_rate: f64,
     pub earnings_improvement: f64,
     pub confidence: f64,
+    /// Earnings volatility of `to_protocol` (0-1, lower is steadier)
+    pub risk_score: f64,
 }
 
 // ============================================================================
@@ -71,6 +86,83 @@ pub struct AllocationResponse {
     pub estimated_improvement: f64,
     pub net_benefit: f64,
     pub roi_percent: f64,
+    /// `heuristic` or `ml_blended`
+    pub source: PlanSource,
+}
+
+// ============================================================================
+// EFFICIENCY ENDPOINTS
+// ============================================================================
+
+/// Earnings per resource unit for one protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolEfficiencyDto {
+    pub protocol: String,
+    pub earnings_per_hour: f64,
+    pub usd_per_cpu_core_hour: Option<f64>,
+    pub usd_per_gb_hour: Option<f64>,
+    pub usd_per_mbps_hour: Option<f64>,
+}
+
+/// Protocol earning the most per unit of each resource
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MostEfficientDto {
+    pub cpu: Option<String>,
+    pub storage: Option<String>,
+    pub bandwidth: Option<String>,
+}
+
+/// Get efficiency metrics response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EfficiencyResponse {
+    pub timestamp: DateTime<Utc>,
+    pub protocols: Vec<ProtocolEfficiencyDto>,
+    pub most_efficient: MostEfficientDto,
+}
+
+// ============================================================================
+// PROTOCOL ENDPOINTS
+// ============================================================================
+
+/// Protocol-specific metrics a protocol's adapter reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricSchemaResponse {
+    pub protocol: String,
+    pub metrics: Vec<MetricSchema>,
+}
+
+/// Volatility of a protocol's hourly earnings over the trailing window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolRiskDto {
+    pub protocol: String,
+    pub computed_at: DateTime<Utc>,
+    /// Trailing hours the figures cover
+    pub window_hours: i64,
+    /// Hours with earnings in the window
+    pub samples: i64,
+    /// Mean hourly earnings (USD/hour)
+    pub mean_earnings: f64,
+    /// Standard deviation of hourly earnings (USD/hour)
+    pub std_dev: f64,
+    /// Largest fall from a running peak, as a percentage of that peak
+    pub max_drawdown_percent: f64,
+    /// 0 (steady) to 1 (volatile)
+    pub risk_score: f64,
+}
+
+impl From<crate::db::models::ProtocolRiskRecord> for ProtocolRiskDto {
+    fn from(record: crate::db::models::ProtocolRiskRecord) -> Self {
+        Self {
+            protocol: record.protocol_name,
+            computed_at: parse_timestamp(&record.computed_at),
+            window_hours: record.window_hours,
+            samples: record.samples,
+            mean_earnings: record.mean_earnings,
+            std_dev: record.std_dev,
+            max_drawdown_percent: record.max_drawdown_percent,
+            risk_score: record.risk_score,
+        }
+    }
 }
 
 /// Execute reallocation request
@@ -86,6 +178,9 @@ pub struct ReallocateResponse {
     pub success: bool,
     pub message: String,
     pub changes: Vec<AllocationChangeDto>,
+    /// Pending reallocation to approve when `require_confirmation` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_id: Option<i64>,
 }
 
 /// Allocation change DTO
@@ -96,22 +191,118 @@ pub struct AllocationChangeDto {
     pub old_allocation: f64,
     pub new_allocation: f64,
     pub earnings_impact: f64,
+    /// Earnings change observed over the canary window after the change;
+    /// absent until it has been evaluated
+    pub realized_impact: Option<f64>,
+    /// Optimizer decision behind the change (`GET /api/v1/decisions/{id}`)
+    pub decision_id: Option<i64>,
+}
+
+impl From<ReallocationRecord> for AllocationChangeDto {
+    fn from(record: ReallocationRecord) -> Self {
+        Self {
+            timestamp: parse_timestamp(&record.timestamp),
+            protocol: record.protocol_name,
+            old_allocation: record.old_allocation,
+            new_allocation: record.new_allocation,
+            earnings_impact: record.earnings_impact.unwrap_or(0.0),
+            realized_impact: record.realized_impact,
+            decision_id: record.decision_id,
+        }
+    }
+}
+
+/// Get reallocation history request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReallocationHistoryRequest {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<i64>,
+    pub protocol: Option<String>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl ReallocationHistoryRequest {
+    /// Database page, or a message describing the invalid parameter
+    pub fn page(&self) -> Result<Page, String> {
+        page(self.limit, self.offset, self.cursor)
+    }
+}
+
+/// Get reallocation history response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReallocationHistoryResponse {
+    /// Newest first
+    pub changes: Vec<AllocationChangeDto>,
+    /// Changes matching the filters across all pages
+    pub total_count: usize,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<i64>,
+}
+
+/// Reallocation plan awaiting confirmation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingReallocationDto {
+    pub id: i64,
+    pub created_at: String,
+    pub status: String,
+    pub allocation: HashMap<String, f64>,
+    pub estimated_improvement: f64,
+    pub net_benefit: f64,
+    pub confidence: f64,
+    pub decided_at: Option<String>,
+    pub note: Option<String>,
+}
+
+/// Reject pending reallocation request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RejectReallocationRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Why the optimizer proposed, executed, or declined a plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizerDecisionDto {
+    pub id: i64,
+    pub created_at: String,
+    /// rejected, pending_approval, executing, executed or failed
+    pub outcome: String,
+    /// Why the plan was not executed, or why execution failed
+    pub reason: Option<String>,
+    pub pending_reallocation_id: Option<i64>,
+    #[serde(flatten)]
+    pub explanation: DecisionExplanation,
 }
 
 // ============================================================================
 // DASHBOARD ENDPOINTS
 // ============================================================================
 
+/// Dashboard query parameters
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DashboardRequest {
+    /// Rebuild the snapshot from the latest metrics instead of serving the cached one
+    #[serde(default)]
+    pub refresh: bool,
+}
+
 /// Dashboard snapshot response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DashboardResponse {
     pub timestamp: DateTime<Utc>,
+    /// Currency of the earnings amounts
+    pub currency: Currency,
     pub total_earnings_per_hour: f64,
     pub earnings_by_protocol: HashMap<String, f64>,
     pub current_allocation: HashMap<String, f64>,
     pub optimal_allocation: HashMap<String, f64>,
     pub next_reallocation_in: Option<u64>,
     pub connection_status: HashMap<String, bool>,
+    /// Rolling uptime percentages by protocol
+    pub uptime: HashMap<String, ProtocolUptime>,
     pub alerts_count: u32,
 }
 
@@ -119,22 +310,91 @@ pub struct DashboardResponse {
 // ALERT ENDPOINTS
 // ============================================================================
 
+/// Get alerts request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertsRequest {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<i64>,
+    /// Only alerts at or above this severity
+    pub min_severity: Option<f64>,
+    pub acknowledged: Option<bool>,
+    pub protocol: Option<String>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl AlertsRequest {
+    /// Database page, or a message describing the invalid parameter
+    pub fn page(&self) -> Result<Page, String> {
+        page(self.limit, self.offset, self.cursor)
+    }
+}
+
 /// Get alerts response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertsResponse {
+    /// Newest first
     pub alerts: Vec<AlertDto>,
+    /// Alerts matching the filters across all pages
     pub total_count: usize,
+    /// Matching alerts with critical severity
     pub critical_count: u32,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<i64>,
 }
 
 /// Alert DTO
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertDto {
+    /// Stored alert id; absent for alerts that were not persisted
+    pub id: Option<i64>,
     pub timestamp: DateTime<Utc>,
     pub alert_type: String,
     pub severity: f64,
     pub message: String,
+    pub protocol: Option<String>,
     pub acknowledged: bool,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl From<crate::orchestration::Alert> for AlertDto {
+    fn from(alert: crate::orchestration::Alert) -> Self {
+        use crate::orchestration::AlertType;
+
+        let protocol = match &alert.alert_type {
+            AlertType::ProtocolDisconnected { protocol } | AlertType::EarningsAnomaly { protocol, .. } => {
+                Some(protocol.clone())
+            }
+            _ => None,
+        };
+        Self {
+            id: alert.id,
+            timestamp: alert.timestamp,
+            alert_type: format!("{:?}", alert.alert_type),
+            severity: alert.severity,
+            message: alert.message,
+            protocol,
+            acknowledged: alert.acknowledged,
+            resolved_at: alert.resolved_at,
+        }
+    }
+}
+
+impl From<AlertRecord> for AlertDto {
+    fn from(record: AlertRecord) -> Self {
+        Self {
+            id: record.id,
+            timestamp: parse_timestamp(&record.timestamp),
+            alert_type: record.alert_type,
+            severity: record.severity,
+            message: record.message,
+            protocol: record.protocol,
+            acknowledged: record.acknowledged.unwrap_or(false),
+            resolved_at: record.resolved_at.as_deref().map(parse_timestamp),
+        }
+    }
 }
 
 /// Acknowledge alert request
@@ -143,6 +403,13 @@ pub struct AcknowledgeAlertRequest {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Bulk acknowledge query parameters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AcknowledgeAllAlertsRequest {
+    /// Only acknowledge alerts with a severity below this value
+    pub severity_below: Option<f64>,
+}
+
 // ============================================================================
 // HISTORY & REPORTING
 // ============================================================================
@@ -152,6 +419,10 @@ pub struct AcknowledgeAlertRequest {
 pub struct MetricsHistoryRequest {
     pub hours: Option<i64>,
     pub limit: Option<usize>,
+    /// Bucket width; raw snapshots are returned when omitted
+    pub interval: Option<MetricsInterval>,
+    /// Aggregate within each bucket (default `avg`)
+    pub agg: Option<MetricsAggregation>,
 }
 
 /// Get metrics history response
@@ -159,6 +430,8 @@ pub struct MetricsHistoryRequest {
 pub struct MetricsHistoryResponse {
     pub metrics: Vec<MetricsSnapshot>,
     pub total_count: usize,
+    pub interval: Option<MetricsInterval>,
+    pub aggregation: Option<MetricsAggregation>,
 }
 
 /// Metrics snapshot
@@ -208,6 +481,29 @@ pub struct OptimizerConfigDto {
     pub min_improvement_percent: f64,
     pub max_allocation_change: f64,
     pub analysis_window_hours: u32,
+    pub strategy: Strategy,
+    pub risk_aversion: f64,
+    pub max_protocol_share: f64,
+    pub demand_weight: f64,
+    pub yield_weight: f64,
+    pub prediction_error_weight: f64,
+    pub max_bandwidth_percent: f64,
+}
+
+/// Update optimizer configuration request (omitted fields are unchanged)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateOptimizerConfigRequest {
+    pub min_improvement_threshold: Option<f64>,
+    pub min_improvement_percent: Option<f64>,
+    pub max_allocation_change: Option<f64>,
+    pub analysis_window_hours: Option<u32>,
+    pub strategy: Option<Strategy>,
+    pub risk_aversion: Option<f64>,
+    pub max_protocol_share: Option<f64>,
+    pub demand_weight: Option<f64>,
+    pub yield_weight: Option<f64>,
+    pub prediction_error_weight: Option<f64>,
+    pub max_bandwidth_percent: Option<f64>,
 }
 
 /// Reallocation configuration DTO
@@ -216,6 +512,47 @@ pub struct ReallocationConfigDto {
     pub min_hold_duration_seconds: u64,
     pub max_per_hour: u32,
     pub auto_rollback: bool,
+    pub require_confirmation: bool,
+    pub confirmation_timeout_hours: i64,
+    pub constraints: HashMap<String, ProtocolConstraint>,
+}
+
+/// Update reallocation configuration request (omitted fields are unchanged)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateReallocationConfigRequest {
+    pub min_hold_duration_seconds: Option<u64>,
+    pub max_per_hour: Option<u32>,
+    pub auto_rollback: Option<bool>,
+    pub require_confirmation: Option<bool>,
+    pub confirmation_timeout_hours: Option<i64>,
+    /// Replaces every protocol constraint; send `{}` to clear them
+    pub constraints: Option<HashMap<String, ProtocolConstraint>>,
+}
+
+/// Scheduler configuration DTO, including the reallocation engine settings
+/// the optimization task executes with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfigDto {
+    #[serde(flatten)]
+    pub scheduler: SchedulerConfig,
+    pub reallocation: ReallocationConfigDto,
+}
+
+/// Update scheduler configuration request (omitted fields are unchanged)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateSchedulerConfigRequest {
+    pub poll_interval: Option<u64>,
+    pub optimization_interval: Option<u64>,
+    pub metrics_retention_days: Option<i64>,
+    pub alert_processing_interval: Option<u64>,
+    pub min_reallocation_threshold: Option<f64>,
+    pub cpu_alert_threshold: Option<f64>,
+    pub memory_alert_threshold: Option<f64>,
+    pub disk_free_alert_percent: Option<f64>,
+    pub alert_cooldown_secs: Option<u64>,
+    pub metrics_flush_interval: Option<u64>,
+    pub canary_window_hours: Option<u64>,
+    pub reallocation: Option<UpdateReallocationConfigRequest>,
 }
 
 /// Monitor configuration DTO
@@ -227,6 +564,93 @@ pub struct MonitorConfigDto {
     pub max_alerts: usize,
 }
 
+// ============================================================================
+// WALLET ENDPOINTS
+// ============================================================================
+
+/// Wallet balances response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletsResponse {
+    pub wallets: Vec<WalletBalanceDto>,
+    pub total_usd: f64,
+}
+
+/// Wallet balance DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletBalanceDto {
+    pub label: String,
+    pub chain: String,
+    pub address: String,
+    pub protocol: Option<String>,
+    pub symbol: String,
+    pub balance: f64,
+    pub balance_usd: Option<f64>,
+    pub polled_at: String,
+}
+
+// ============================================================================
+// NETWORK PROBES
+// ============================================================================
+
+/// Network probe history request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkProbesRequest {
+    /// Hours of history to return (default 168)
+    pub hours: Option<i64>,
+}
+
+/// Network probe history response, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkProbesResponse {
+    pub probes: Vec<NetworkProbeDto>,
+}
+
+/// Network probe DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkProbeDto {
+    pub latency_ms: f64,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub measured_at: String,
+}
+
+// ============================================================================
+// PAGINATION
+// ============================================================================
+
+/// Rows per history page when `limit` is omitted
+pub const DEFAULT_PAGE_LIMIT: i64 = 100;
+
+/// Largest accepted history page `limit`
+pub const MAX_PAGE_LIMIT: i64 = 1000;
+
+/// Database page for history listing parameters
+///
+/// `cursor` is the `next_cursor` of the previous page; `offset` skips rows
+/// after it.
+fn page(limit: Option<i64>, offset: Option<i64>, cursor: Option<i64>) -> Result<Page, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+        return Err(format!("limit must be between 1 and {}", MAX_PAGE_LIMIT));
+    }
+    let offset = offset.unwrap_or(0);
+    if offset < 0 {
+        return Err("offset must not be negative".to_string());
+    }
+    Ok(Page {
+        limit,
+        offset,
+        before_id: cursor,
+    })
+}
+
+/// Parse a stored RFC 3339 timestamp, falling back to the epoch
+fn parse_timestamp(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
 // ============================================================================
 // ERROR RESPONSES
 // ============================================================================
@@ -277,16 +701,61 @@ impl<T: Serialize> SuccessResponse<T> {
 // WEBSOCKET MESSAGES
 // ============================================================================
 
+/// Per-topic payload filter supplied with a subscription
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TopicFilter {
+    /// Only include these protocols (`metrics`, `reallocations`)
+    #[serde(default)]
+    pub protocols: Option<Vec<String>>,
+    /// Only include alerts at or above this severity (`alerts`)
+    #[serde(default)]
+    pub min_severity: Option<f64>,
+}
+
+/// Per-protocol update published on the `protocol:{name}` topic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolUpdateDto {
+    pub timestamp: DateTime<Utc>,
+    pub protocol: String,
+    pub earnings_per_hour: f64,
+    pub allocation_percent: f64,
+    pub connected: bool,
+}
+
+/// Encoding of the frames a WebSocket session receives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WsEncoding {
+    /// JSON text frames
+    #[default]
+    Json,
+    /// MessagePack binary frames, with the same fields as the JSON messages
+    Msgpack,
+}
+
 /// WebSocket message types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum WsMessage {
-    /// Subscribe to metrics updates
+    /// Subscribe to a topic (`metrics`, `alerts`, `reallocations`, `protocol:{name}`)
+    ///
+    /// `protocol` alone is shorthand for the `protocol:{name}` topic.
+    /// `encoding` switches the frames of the whole session after the ack.
     Subscribe {
+        #[serde(default)]
+        topic: Option<String>,
+        #[serde(default)]
         protocol: Option<String>,
+        #[serde(default)]
+        filter: TopicFilter,
+        #[serde(default)]
+        encoding: Option<WsEncoding>,
     },
-    /// Unsubscribe from updates
+    /// Unsubscribe from a topic
     Unsubscribe {
+        #[serde(default)]
+        topic: Option<String>,
+        #[serde(default)]
         protocol: Option<String>,
     },
     /// Metrics update
@@ -301,6 +770,23 @@ pub enum WsMessage {
     ReallocationNotification {
         changes: Vec<AllocationChangeDto>,
     },
+    /// Per-protocol update
+    ProtocolUpdate {
+        update: ProtocolUpdateDto,
+    },
+    /// Subscription change accepted
+    Ack {
+        action: String,
+        topic: String,
+        /// Encoding of the frames that follow, when the request changed it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encoding: Option<WsEncoding>,
+    },
+    /// Invalid request from the client
+    Error {
+        code: String,
+        message: String,
+    },
     /// Ping/Pong for keep-alive
     Ping,
     Pong,
@@ -314,6 +800,7 @@ mod tests {
     fn test_metrics_response_creation() {
         let response = MetricsResponse {
             timestamp: Utc::now(),
+            currency: Currency::Usd,
             total_earnings_per_hour: 10.5,
             earnings_by_protocol: HashMap::new(),
             allocation_by_protocol: HashMap::new(),
@@ -324,6 +811,7 @@ mod tests {
                 bandwidth_percent: 40.0,
                 storage_percent: 30.0,
             },
+            stale_seconds: HashMap::new(),
         };
         assert_eq!(response.total_earnings_per_hour, 10.5);
     }
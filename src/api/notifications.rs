@@ -0,0 +1,257 @@
+/// Notification Handlers
+///
+/// Lets operators check their Discord and Slack webhook setup by sending a
+/// sample alert through the configured message template, and manage the
+/// rules routing alerts to channels. The notifier reads the rules for every
+/// alert, so changes apply to the next one.
+use actix_web::{http::StatusCode, web, HttpResponse, Result};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use super::middleware::AccountId;
+use super::models::SuccessResponse;
+use super::problem::{problem_response, ApiError};
+use crate::db::queries::{self, get_latest_metrics};
+use crate::notifications::routing::NotificationRule;
+use crate::notifications::{Notification, NotificationDispatcher};
+
+// ============================================================================
+// REQUEST TYPES
+// ============================================================================
+
+/// Alert routing rule to create or replace
+#[derive(Debug, Deserialize)]
+pub struct NotificationRuleRequest {
+    /// Alert type to match; omit to match every type
+    pub alert_type: Option<String>,
+    #[serde(default)]
+    pub min_severity: f64,
+    #[serde(default = "default_max_severity")]
+    pub max_severity: f64,
+    /// Channels matching alerts go to; empty mutes them
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub priority: i64,
+}
+
+fn default_max_severity() -> f64 {
+    100.0
+}
+
+impl NotificationRuleRequest {
+    /// The rule this request describes, checked
+    fn into_rule(self) -> Result<NotificationRule, ApiError> {
+        let rule = NotificationRule {
+            id: None,
+            alert_type: self.alert_type.map(|t| t.trim().to_string()),
+            min_severity: self.min_severity,
+            max_severity: self.max_severity,
+            channels: self.channels,
+            priority: self.priority,
+        };
+        rule.validate().map_err(|e| ApiError::bad_request("INVALID_RULE", e))?;
+        Ok(rule)
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// POST /api/v1/notifications/test - Send a test notification to every channel
+///
+/// Responds with the delivery result for each channel; a channel that
+/// rejects the message does not fail the request.
+pub async fn test_notification(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    dispatcher: Option<web::Data<NotificationDispatcher>>,
+) -> Result<HttpResponse> {
+    let Some(dispatcher) = dispatcher.filter(|d| !d.is_empty()) else {
+        return Ok(problem_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "NOTIFICATIONS_DISABLED",
+            "Set discord_webhook_url or slack_webhook_url in [notifications] to send notifications",
+        ));
+    };
+
+    let earnings = get_latest_metrics(db.get_ref(), account.0)
+        .await
+        .ok()
+        .flatten()
+        .map(|m| m.total_earnings_per_hour);
+    let notification = Notification {
+        alert_type: "TEST_NOTIFICATION".to_string(),
+        protocol: None,
+        severity: 0.0,
+        message: "Test notification from DePIN-Orcha".to_string(),
+        earnings,
+        earnings_delta: earnings.map(|_| 0.0),
+    };
+
+    let results = dispatcher.send(&notification).await;
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(results)))
+}
+
+/// GET /api/v1/notifications/rules - List alert routing rules in evaluation order
+pub async fn list_rules(db: web::Data<SqlitePool>, account: AccountId) -> Result<HttpResponse> {
+    let records = queries::list_notification_rules(db.get_ref(), account.0)
+        .await
+        .map_err(ApiError::from)?;
+    let rules: Vec<NotificationRule> = records.iter().map(|r| r.rule()).collect();
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(rules)))
+}
+
+/// POST /api/v1/notifications/rules - Add an alert routing rule
+pub async fn create_rule(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    req: web::Json<NotificationRuleRequest>,
+) -> Result<HttpResponse> {
+    let rule = req.into_inner().into_rule()?;
+    let id = queries::create_notification_rule(db.get_ref(), account.0, &rule)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(HttpResponse::Created().json(SuccessResponse::new(NotificationRule { id: Some(id), ..rule })))
+}
+
+/// PUT /api/v1/notifications/rules/{id} - Replace an alert routing rule
+pub async fn update_rule(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    path: web::Path<i64>,
+    req: web::Json<NotificationRuleRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let rule = req.into_inner().into_rule()?;
+    if !queries::update_notification_rule(db.get_ref(), account.0, id, &rule)
+        .await
+        .map_err(ApiError::from)?
+    {
+        return Err(rule_not_found(id).into());
+    }
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(NotificationRule { id: Some(id), ..rule })))
+}
+
+/// DELETE /api/v1/notifications/rules/{id} - Remove an alert routing rule
+pub async fn delete_rule(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    if !queries::delete_notification_rule(db.get_ref(), account.0, id)
+        .await
+        .map_err(ApiError::from)?
+    {
+        return Err(rule_not_found(id).into());
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn rule_not_found(id: i64) -> ApiError {
+    ApiError::not_found(format!("Notification rule {} not found", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::middleware::ApiKeyInfo;
+    use crate::notifications::NotificationsConfig;
+    use actix_web::{test, App, HttpMessage};
+
+    #[actix_web::test]
+    async fn test_notification_disabled() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(crate::db::test_pool().await))
+                .app_data(web::Data::new(NotificationDispatcher::new(
+                    NotificationsConfig::default(),
+                )))
+                .route("/notifications/test", web::post().to(test_notification)),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/notifications/test").to_request();
+        req.extensions_mut().insert(ApiKeyInfo::for_account(1));
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[actix_web::test]
+    async fn test_notification_sent() {
+        let mut server = mockito::Server::new_async().await;
+        let slack = server
+            .mock("POST", "/slack")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({ "text": "TEST_NOTIFICATION" }),
+            ))
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let dispatcher = NotificationDispatcher::new(NotificationsConfig {
+            slack_webhook_url: Some(format!("{}/slack", server.url())),
+            template: "{alert_type}".to_string(),
+            ..Default::default()
+        });
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(crate::db::test_pool().await))
+                .app_data(web::Data::new(dispatcher))
+                .route("/notifications/test", web::post().to(test_notification)),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/notifications/test").to_request();
+        req.extensions_mut().insert(ApiKeyInfo::for_account(1));
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        slack.assert_async().await;
+        assert_eq!(body["data"][0]["channel"], "slack");
+        assert_eq!(body["data"][0]["delivered"], true);
+    }
+
+    #[actix_web::test]
+    async fn test_rule_lifecycle() {
+        let pool = crate::db::test_pool().await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .route("/rules", web::get().to(list_rules))
+                .route("/rules", web::post().to(create_rule))
+                .route("/rules/{id}", web::put().to(update_rule))
+                .route("/rules/{id}", web::delete().to(delete_rule)),
+        )
+        .await;
+        let as_operator = |req: test::TestRequest| {
+            let req = req.to_request();
+            req.extensions_mut().insert(ApiKeyInfo::for_account(1));
+            req
+        };
+
+        let bad = serde_json::json!({ "channels": ["email"] });
+        let resp = test::call_service(&app, as_operator(test::TestRequest::post().uri("/rules").set_json(bad))).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let rule = serde_json::json!({ "alert_type": "DEVICE_OFFLINE", "min_severity": 80, "channels": ["telegram"] });
+        let resp = test::call_service(&app, as_operator(test::TestRequest::post().uri("/rules").set_json(rule))).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let id = body["data"]["id"].as_i64().unwrap();
+        assert_eq!(body["data"]["max_severity"], 100.0);
+
+        let update = serde_json::json!({ "alert_type": "DEVICE_OFFLINE", "channels": ["telegram", "webhook"] });
+        let uri = format!("/rules/{}", id);
+        let req = test::TestRequest::put().uri(&uri).set_json(update);
+        assert_eq!(test::call_service(&app, as_operator(req)).await.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            test::call_and_read_body_json(&app, as_operator(test::TestRequest::get().uri("/rules"))).await;
+        assert_eq!(body["data"][0]["channels"], serde_json::json!(["telegram", "webhook"]));
+
+        let req = test::TestRequest::delete().uri(&uri);
+        assert_eq!(test::call_service(&app, as_operator(req)).await.status(), StatusCode::NO_CONTENT);
+        let req = test::TestRequest::delete().uri(&uri);
+        assert_eq!(test::call_service(&app, as_operator(req)).await.status(), StatusCode::NOT_FOUND);
+    }
+}
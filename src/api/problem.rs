@@ -0,0 +1,248 @@
+/// Problem Details Error Responses
+///
+/// Every API error is rendered as an RFC 7807 `application/problem+json`
+/// body carrying a stable machine-readable `code` and the ID of the request
+/// that failed. `ApiError` is the handler-level error; orchestration and
+/// protocol errors map onto it through their `ResponseError` impls.
+use actix_web::{
+    error::ResponseError,
+    http::{header, StatusCode},
+    HttpResponse, HttpResponseBuilder,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::orchestration::OrchestrationError;
+use crate::protocols::ProtocolError;
+
+/// Content type of problem detail bodies
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+tokio::task_local! {
+    /// ID of the request being handled, set by `RequestIdMiddleware`
+    pub(crate) static REQUEST_ID: String;
+}
+
+/// ID of the request being handled on this task, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+// ============================================================================
+// PROBLEM DETAILS
+// ============================================================================
+
+/// RFC 7807 problem details body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Stable machine-readable error code
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ProblemDetails {
+    /// Problem for `status` with the current request ID attached
+    pub fn new(status: StatusCode, code: &str, detail: impl Into<String>) -> Self {
+        Self {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: detail.into(),
+            code: code.to_string(),
+            request_id: current_request_id(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Render into `builder`, whose status must match
+    pub fn respond(self, mut builder: HttpResponseBuilder) -> HttpResponse {
+        builder
+            .insert_header((header::CONTENT_TYPE, PROBLEM_JSON))
+            .body(serde_json::to_string(&self).unwrap_or_default())
+    }
+}
+
+/// Problem details response
+pub fn problem_response(status: StatusCode, code: &str, detail: impl Into<String>) -> HttpResponse {
+    ProblemDetails::new(status, code, detail).respond(HttpResponse::build(status))
+}
+
+// ============================================================================
+// API ERROR
+// ============================================================================
+
+/// Error returned by API handlers
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub detail: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            detail: detail.into(),
+        }
+    }
+
+    /// 400 with a specific code
+    pub fn bad_request(code: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, detail)
+    }
+
+    /// 404 `NOT_FOUND`
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NOT_FOUND", detail)
+    }
+
+    /// 404 `NO_DATA`, for endpoints waiting on the first poll
+    pub fn no_data() -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NO_DATA", "No metrics available yet")
+    }
+
+    /// 500 with a specific code
+    pub fn internal(code: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, code, detail)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.detail)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        problem_response(self.status, self.code, self.detail.clone())
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::internal("DATABASE_ERROR", e.to_string())
+    }
+}
+
+impl From<&ProtocolError> for ApiError {
+    fn from(e: &ProtocolError) -> Self {
+        let (status, code) = match e {
+            ProtocolError::ConnectionError(_) | ProtocolError::NetworkError(_) => {
+                (StatusCode::BAD_GATEWAY, "PROTOCOL_UNAVAILABLE")
+            }
+            ProtocolError::TimeoutError(_) => (StatusCode::GATEWAY_TIMEOUT, "PROTOCOL_TIMEOUT"),
+            ProtocolError::AuthenticationError(_) => (StatusCode::BAD_GATEWAY, "PROTOCOL_AUTH_FAILED"),
+            ProtocolError::ApiError(_) | ProtocolError::ParseError(_) | ProtocolError::DataError(_) => {
+                (StatusCode::BAD_GATEWAY, "PROTOCOL_ERROR")
+            }
+            ProtocolError::AllocationError(_) | ProtocolError::ResourceError(_) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "ALLOCATION_ERROR")
+            }
+            ProtocolError::ConfigurationError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "PROTOCOL_MISCONFIGURED")
+            }
+            ProtocolError::UnsupportedError(_) => (StatusCode::NOT_IMPLEMENTED, "UNSUPPORTED"),
+        };
+        Self::new(status, code, e.to_string())
+    }
+}
+
+impl From<&OrchestrationError> for ApiError {
+    fn from(e: &OrchestrationError) -> Self {
+        let (status, code) = match e {
+            OrchestrationError::ProtocolError(inner) => return Self::from(inner),
+            OrchestrationError::ReallocationError(_) => (StatusCode::CONFLICT, "CANNOT_REALLOCATE"),
+            OrchestrationError::ConfigurationError(_) => {
+                (StatusCode::BAD_REQUEST, "INVALID_CONFIG")
+            }
+            OrchestrationError::DataError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DATA_ERROR"),
+            OrchestrationError::CalculationError(_) | OrchestrationError::OptimizationError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "CALCULATION_ERROR")
+            }
+            OrchestrationError::MonitoringError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "MONITORING_ERROR")
+            }
+            OrchestrationError::CoordinationError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "COORDINATION_ERROR")
+            }
+        };
+        Self::new(status, code, e.to_string())
+    }
+}
+
+impl ResponseError for ProtocolError {
+    fn status_code(&self) -> StatusCode {
+        ApiError::from(self).status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        ApiError::from(self).error_response()
+    }
+}
+
+impl ResponseError for OrchestrationError {
+    fn status_code(&self) -> StatusCode {
+        ApiError::from(self).status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        ApiError::from(self).error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::MessageBody;
+
+    fn body_of(response: HttpResponse) -> ProblemDetails {
+        let bytes = response.into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_problem_includes_request_id_in_scope() {
+        let response = REQUEST_ID
+            .scope("req-1".to_string(), async {
+                ApiError::not_found("Reallocation 7 not found").error_response()
+            })
+            .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+        let problem = body_of(response);
+        assert_eq!(problem.code, "NOT_FOUND");
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.request_id.as_deref(), Some("req-1"));
+    }
+
+    #[test]
+    fn test_orchestration_errors_map_to_status_and_code() {
+        let e = OrchestrationError::ConfigurationError("bad".to_string());
+        assert_eq!(e.status_code(), StatusCode::BAD_REQUEST);
+
+        let e = OrchestrationError::ProtocolError(ProtocolError::TimeoutError("slow".to_string()));
+        let problem = body_of(e.error_response());
+        assert_eq!(problem.status, 504);
+        assert_eq!(problem.code, "PROTOCOL_TIMEOUT");
+        assert!(problem.request_id.is_none());
+    }
+}
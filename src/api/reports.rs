@@ -0,0 +1,686 @@
+/// Performance Report Handlers
+///
+/// Generates performance reports from the realtime monitor, stores them, and
+/// renders stored reports as self-contained HTML (inline SVG charts) or PDF
+/// documents for sharing with stakeholders. Reports are stored in USD and
+/// shown in the display currency, with times in the reporting time zone.
+use actix_web::{http::StatusCode, web, HttpResponse, Result};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::fmt::Write;
+
+use super::currency::{Currency, DisplayConfig};
+use super::export::attachment;
+use super::middleware::AccountId;
+use super::models::SuccessResponse;
+use super::problem::problem_response;
+use super::OrchestratorState;
+use crate::db::models::ReportRecord;
+use crate::db::queries::{get_report, get_reports, store_report};
+use crate::orchestration::PerformanceReport;
+use crate::timezone::ReportingTimezone;
+
+/// Default report period when none is requested
+const DEFAULT_REPORT_HOURS: i64 = 24;
+
+/// Number of stored reports listed
+const REPORT_LIST_LIMIT: i64 = 50;
+
+/// Allocation changes shown in a PDF before the timeline is truncated
+const PDF_TIMELINE_ROWS: usize = 20;
+
+// ============================================================================
+// REQUEST / RESPONSE TYPES
+// ============================================================================
+
+/// Rendered report format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Html,
+    Pdf,
+}
+
+/// Query parameters for report downloads
+#[derive(Debug, Deserialize)]
+pub struct DownloadQuery {
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// Request to generate a report over the trailing `hours`
+#[derive(Debug, Default, Deserialize)]
+pub struct GenerateReportRequest {
+    pub hours: Option<i64>,
+}
+
+/// Stored report summary
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSummaryDto {
+    pub id: i64,
+    pub generated_at: String,
+    pub period_start: String,
+    pub period_end: String,
+    /// Currency of `total_earnings`
+    pub currency: Currency,
+    pub total_earnings: f64,
+}
+
+impl ReportSummaryDto {
+    /// Summary of a stored report with its total in the display currency
+    pub fn new(record: &ReportRecord, display: &DisplayConfig) -> Self {
+        Self {
+            id: record.id.unwrap_or_default(),
+            generated_at: record.generated_at.clone(),
+            period_start: record.period_start.clone(),
+            period_end: record.period_end.clone(),
+            currency: display.currency,
+            total_earnings: display.convert(record.total_earnings),
+        }
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// POST /api/v1/reports - Generate and store a report for the trailing period
+pub async fn generate_report(
+    state: OrchestratorState,
+    db: web::Data<SqlitePool>,
+    display: Option<web::Data<DisplayConfig>>,
+    req: Option<web::Json<GenerateReportRequest>>,
+) -> Result<HttpResponse> {
+    let display = display.map(|d| d.into_inner()).unwrap_or_default();
+    let hours = req
+        .and_then(|r| r.hours)
+        .unwrap_or(DEFAULT_REPORT_HOURS);
+    if hours <= 0 {
+        return Ok(problem_response(
+            StatusCode::BAD_REQUEST,
+            "INVALID_PERIOD",
+            format!("`hours` must be positive, got {}", hours),
+        ));
+    }
+
+    let period_end = Utc::now();
+    let report = match state
+        .monitor
+        .generate_report(period_end - Duration::hours(hours), period_end)
+        .await
+    {
+        Ok(report) => report,
+        Err(e) => {
+            return Ok(problem_response(StatusCode::NOT_FOUND, "NO_DATA", e.to_string()));
+        }
+    };
+
+    let record = match ReportRecord::new(&report) {
+        Ok(record) => record,
+        Err(e) => {
+            return Ok(problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "SERIALIZATION_ERROR",
+                format!("Failed to serialize report: {}", e),
+            ));
+        }
+    };
+
+    let id = match store_report(db.get_ref(), state.account_id(), &record).await {
+        Ok(id) => id,
+        Err(e) => return Ok(database_error(e)),
+    };
+
+    match get_report(db.get_ref(), state.account_id(), id).await {
+        Ok(Some(record)) => {
+            Ok(HttpResponse::Created().json(SuccessResponse::new(ReportSummaryDto::new(&record, &display))))
+        }
+        Ok(None) => Ok(not_found(id)),
+        Err(e) => Ok(database_error(e)),
+    }
+}
+
+/// GET /api/v1/reports - List stored reports
+pub async fn list_reports(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    display: Option<web::Data<DisplayConfig>>,
+) -> Result<HttpResponse> {
+    let display = display.map(|d| d.into_inner()).unwrap_or_default();
+    match get_reports(db.get_ref(), account.0, REPORT_LIST_LIMIT).await {
+        Ok(records) => {
+            let reports: Vec<ReportSummaryDto> =
+                records.iter().map(|r| ReportSummaryDto::new(r, &display)).collect();
+            Ok(HttpResponse::Ok().json(SuccessResponse::new(reports)))
+        }
+        Err(e) => Ok(database_error(e)),
+    }
+}
+
+/// GET /api/v1/reports/{id}/download - Render a stored report as HTML or PDF
+pub async fn download_report(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    path: web::Path<i64>,
+    query: web::Query<DownloadQuery>,
+    display: Option<web::Data<DisplayConfig>>,
+    tz: Option<web::Data<ReportingTimezone>>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let display = display.map(|d| d.into_inner()).unwrap_or_default();
+    let tz = tz.map(|tz| *tz.get_ref()).unwrap_or_default();
+
+    let record = match get_report(db.get_ref(), account.0, id).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Ok(not_found(id)),
+        Err(e) => return Ok(database_error(e)),
+    };
+
+    let report = match record.report() {
+        Ok(report) => report,
+        Err(e) => {
+            return Ok(problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INVALID_REPORT",
+                format!("Stored report {} is unreadable: {}", id, e),
+            ));
+        }
+    };
+
+    let filename = format!("depin-orcha-report-{}", id);
+    Ok(match query.format {
+        ReportFormat::Html => HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .insert_header(attachment(&filename, "html"))
+            .body(render_html(id, &report, &display, tz)),
+        ReportFormat::Pdf => HttpResponse::Ok()
+            .content_type("application/pdf")
+            .insert_header(attachment(&filename, "pdf"))
+            .body(render_pdf(id, &report, &display, tz)),
+    })
+}
+
+fn not_found(id: i64) -> HttpResponse {
+    problem_response(StatusCode::NOT_FOUND, "NOT_FOUND", format!("Report {} not found", id))
+}
+
+fn database_error(e: sqlx::Error) -> HttpResponse {
+    problem_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "DATABASE_ERROR",
+        format!("Failed to access reports: {}", e),
+    )
+}
+
+// ============================================================================
+// RENDERING
+// ============================================================================
+
+/// Protocol earnings sorted from highest to lowest
+fn sorted_earnings(report: &PerformanceReport) -> Vec<(&str, f64)> {
+    let mut earnings: Vec<(&str, f64)> = report
+        .earnings_by_protocol
+        .iter()
+        .map(|(protocol, amount)| (protocol.as_str(), *amount))
+        .collect();
+    earnings.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+    earnings
+}
+
+/// Render a report as a standalone HTML document
+pub fn render_html(id: i64, report: &PerformanceReport, display: &DisplayConfig, tz: ReportingTimezone) -> String {
+    let mut html = String::new();
+    let earnings = sorted_earnings(report);
+
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>DePIN Orcha Performance Report #{id}</title>
+<style>
+body {{ font-family: -apple-system, Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 960px; color: #222; }}
+h1 {{ margin-bottom: 0; }}
+.period {{ color: #666; margin-top: 0.25em; }}
+.summary {{ display: flex; gap: 1em; flex-wrap: wrap; }}
+.card {{ border: 1px solid #ddd; border-radius: 6px; padding: 0.75em 1em; min-width: 150px; }}
+.card .value {{ font-size: 1.4em; font-weight: bold; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ border-bottom: 1px solid #eee; padding: 0.4em; text-align: left; }}
+td.num {{ text-align: right; }}
+.up {{ color: #1a7f37; }}
+.down {{ color: #cf222e; }}
+</style>
+</head>
+<body>
+<h1>Performance Report #{id}</h1>
+<p class="period">{start} &ndash; {end}</p>
+<div class="summary">
+<div class="card"><div>Total earnings</div><div class="value">{total}</div></div>
+<div class="card"><div>Average per hour</div><div class="value">{hourly}</div></div>
+<div class="card"><div>Improvement</div><div class="value">{improvement}</div></div>
+<div class="card"><div>Optimizations</div><div class="value">{optimizations}</div></div>
+<div class="card"><div>Uptime</div><div class="value">{uptime:.1}%</div></div>
+</div>
+"#,
+        id = id,
+        start = tz.format(&report.period_start),
+        end = tz.format(&report.period_end),
+        total = display.format(report.total_earnings, 2),
+        hourly = display.format(report.average_hourly_earnings, 4),
+        improvement = display.format(report.total_improvement, 4),
+        optimizations = report.successful_optimizations,
+        uptime = report.uptime_percent,
+    );
+
+    html.push_str("<h2>Earnings by Protocol</h2>\n");
+    if earnings.is_empty() {
+        html.push_str("<p>No protocol earnings recorded.</p>\n");
+    } else {
+        html.push_str(&earnings_chart_svg(&earnings, display));
+    }
+
+    let attribution = &report.attribution;
+    html.push_str("<h2>Earnings Attribution</h2>\n");
+    let _ = writeln!(
+        html,
+        "<p>Earnings moved from {}/hr to {}/hr: {} from market rates, {} from reallocations.</p>",
+        display.format(attribution.start_earnings_per_hour, 4),
+        display.format(attribution.end_earnings_per_hour, 4),
+        display.format(attribution.market_effect, 4),
+        display.format(attribution.allocation_effect, 4),
+    );
+    if !attribution.by_protocol.is_empty() {
+        html.push_str("<table>\n<tr><th>Protocol</th><th>Market</th><th>Reallocations</th></tr>\n");
+        let mut protocols: Vec<_> = attribution.by_protocol.iter().collect();
+        protocols.sort_by(|a, b| a.0.cmp(b.0));
+        for (protocol, effects) in protocols {
+            let _ = writeln!(
+                html,
+                r#"<tr><td>{}</td><td class="num">{}</td><td class="num">{}</td></tr>"#,
+                html_text(protocol),
+                display.format(effects.market_effect, 4),
+                display.format(effects.allocation_effect, 4),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("<h2>Allocation Changes</h2>\n");
+    if report.allocation_changes.is_empty() {
+        html.push_str("<p>No allocation changes during this period.</p>\n");
+    } else {
+        html.push_str(&timeline_svg(report, tz));
+        html.push_str(
+            "<table>\n<tr><th>Time</th><th>Protocol</th><th>Allocation</th><th>Impact</th><th>Reason</th></tr>\n",
+        );
+        let mut changes: Vec<_> = report.allocation_changes.iter().collect();
+        changes.sort_by_key(|c| c.timestamp);
+        for change in changes {
+            let class = if change.new_allocation >= change.old_allocation { "up" } else { "down" };
+            let _ = writeln!(
+                html,
+                r#"<tr><td>{}</td><td>{}</td><td class="{}">{:.1}% &rarr; {:.1}%</td><td class="num">{}</td><td>{}</td></tr>"#,
+                tz.format(&change.timestamp),
+                html_text(&change.protocol),
+                class,
+                change.old_allocation,
+                change.new_allocation,
+                display.format(change.earnings_impact, 4),
+                html_text(&change.reason),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = write!(
+        html,
+        "<p class=\"period\">Generated by DePIN Orcha on {}</p>\n</body>\n</html>\n",
+        tz.format(&Utc::now())
+    );
+    html
+}
+
+/// Horizontal bar chart of earnings per protocol
+fn earnings_chart_svg(earnings: &[(&str, f64)], display: &DisplayConfig) -> String {
+    const BAR_HEIGHT: usize = 24;
+    const LABEL_WIDTH: f64 = 140.0;
+    const CHART_WIDTH: f64 = 640.0;
+
+    let max = earnings.iter().map(|(_, v)| *v).fold(0.0_f64, f64::max);
+    let height = earnings.len() * BAR_HEIGHT + 8;
+    let mut svg = format!(
+        "<svg class=\"chart\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        LABEL_WIDTH + CHART_WIDTH + 100.0,
+        height
+    );
+
+    for (i, (protocol, amount)) in earnings.iter().enumerate() {
+        let y = i * BAR_HEIGHT + 4;
+        let width = if max > 0.0 { amount.max(0.0) / max * CHART_WIDTH } else { 0.0 };
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{}\" font-size=\"13\">{}</text><rect x=\"{}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"#4c8bf5\"/><text x=\"{:.1}\" y=\"{}\" font-size=\"12\">{}</text>",
+            y + 16,
+            html_text(protocol),
+            LABEL_WIDTH,
+            y + 2,
+            width,
+            BAR_HEIGHT - 6,
+            LABEL_WIDTH + width + 6.0,
+            y + 16,
+            display.format(*amount, 2),
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Allocation changes plotted along the report period
+fn timeline_svg(report: &PerformanceReport, tz: ReportingTimezone) -> String {
+    const WIDTH: f64 = 880.0;
+    const MARGIN: f64 = 20.0;
+    const AXIS_Y: f64 = 40.0;
+
+    let span = (report.period_end - report.period_start).num_seconds().max(1) as f64;
+    let mut svg = format!(
+        "<svg class=\"timeline\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"80\">\n<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#999\"/>\n",
+        WIDTH,
+        MARGIN,
+        AXIS_Y,
+        WIDTH - MARGIN,
+        AXIS_Y
+    );
+
+    for change in &report.allocation_changes {
+        let offset = (change.timestamp - report.period_start).num_seconds() as f64 / span;
+        let x = MARGIN + offset.clamp(0.0, 1.0) * (WIDTH - 2.0 * MARGIN);
+        let color = if change.new_allocation >= change.old_allocation { "#1a7f37" } else { "#cf222e" };
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{}\" r=\"5\" fill=\"{}\"><title>{} {}: {:.1}% &#8594; {:.1}%</title></circle>",
+            x,
+            AXIS_Y,
+            color,
+            tz.format(&change.timestamp),
+            html_text(&change.protocol),
+            change.old_allocation,
+            change.new_allocation,
+        );
+    }
+
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"70\" font-size=\"11\">{}</text><text x=\"{}\" y=\"70\" font-size=\"11\" text-anchor=\"end\">{}</text>\n</svg>\n",
+        MARGIN,
+        tz.format(&report.period_start),
+        WIDTH - MARGIN,
+        tz.format(&report.period_end),
+    );
+    svg
+}
+
+fn html_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render a report as a single-page PDF document
+///
+/// Uses the standard Helvetica font so no font embedding is required.
+pub fn render_pdf(id: i64, report: &PerformanceReport, display: &DisplayConfig, tz: ReportingTimezone) -> Vec<u8> {
+    let mut content = PdfContent::default();
+    let mut y = 790.0;
+
+    content.text(50.0, y, 20.0, &format!("Performance Report #{}", id));
+    y -= 20.0;
+    content.text(
+        50.0,
+        y,
+        10.0,
+        &format!(
+            "{} - {}",
+            tz.format(&report.period_start),
+            tz.format(&report.period_end)
+        ),
+    );
+
+    y -= 30.0;
+    for line in [
+        format!("Total earnings: {}", display.format(report.total_earnings, 2)),
+        format!("Average per hour: {}", display.format(report.average_hourly_earnings, 4)),
+        format!("Improvement from reallocations: {}", display.format(report.total_improvement, 4)),
+        format!("Change from market rates: {}", display.format(report.attribution.market_effect, 4)),
+        format!("Optimizations: {}", report.successful_optimizations),
+        format!("Uptime: {:.1}%", report.uptime_percent),
+    ] {
+        content.text(50.0, y, 11.0, &line);
+        y -= 15.0;
+    }
+
+    y -= 15.0;
+    content.text(50.0, y, 14.0, "Earnings by Protocol");
+    y -= 20.0;
+    let earnings = sorted_earnings(report);
+    let max = earnings.iter().map(|(_, v)| *v).fold(0.0_f64, f64::max);
+    for (protocol, amount) in &earnings {
+        let width = if max > 0.0 { amount.max(0.0) / max * 300.0 } else { 0.0 };
+        content.text(50.0, y, 10.0, protocol);
+        content.rect(160.0, y - 2.0, width, 12.0);
+        content.text(166.0 + width, y, 10.0, &display.format(*amount, 2));
+        y -= 18.0;
+    }
+
+    y -= 15.0;
+    content.text(50.0, y, 14.0, "Allocation Changes");
+    y -= 20.0;
+    let mut changes: Vec<_> = report.allocation_changes.iter().collect();
+    changes.sort_by_key(|c| c.timestamp);
+    if changes.is_empty() {
+        content.text(50.0, y, 10.0, "No allocation changes during this period.");
+    }
+    for change in changes.iter().take(PDF_TIMELINE_ROWS) {
+        content.text(
+            50.0,
+            y,
+            9.0,
+            &format!(
+                "{}  {}  {:.1}% -> {:.1}%  {}  {}",
+                tz.format(&change.timestamp),
+                change.protocol,
+                change.old_allocation,
+                change.new_allocation,
+                display.format(change.earnings_impact, 4),
+                change.reason,
+            ),
+        );
+        y -= 13.0;
+    }
+    if changes.len() > PDF_TIMELINE_ROWS {
+        content.text(
+            50.0,
+            y,
+            9.0,
+            &format!("... and {} more changes", changes.len() - PDF_TIMELINE_ROWS),
+        );
+    }
+
+    content.finish()
+}
+
+/// Page content stream of a minimal one-page PDF
+#[derive(Default)]
+struct PdfContent {
+    stream: String,
+}
+
+impl PdfContent {
+    fn text(&mut self, x: f64, y: f64, size: f64, text: &str) {
+        let _ = writeln!(
+            self.stream,
+            "BT /F1 {} Tf {:.1} {:.1} Td ({}) Tj ET",
+            size,
+            x,
+            y,
+            pdf_text(text)
+        );
+    }
+
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let _ = writeln!(
+            self.stream,
+            "0.3 0.55 0.96 rg {:.1} {:.1} {:.1} {:.1} re f 0 g",
+            x, y, width, height
+        );
+    }
+
+    /// Assemble the document with its cross-reference table
+    fn finish(self) -> Vec<u8> {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                self.stream.len(),
+                self.stream
+            ),
+        ];
+
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+        }
+
+        let xref = pdf.len();
+        let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        pdf.into_bytes()
+    }
+}
+
+/// Escape a string for a PDF literal; non-ASCII characters become `?`
+fn pdf_text(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{}", c),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::{AllocationChange, EarningsAttribution, ProtocolAttribution};
+
+    fn report() -> PerformanceReport {
+        let period_end = Utc::now();
+        PerformanceReport {
+            period_start: period_end - Duration::hours(24),
+            period_end,
+            total_earnings: 30.0,
+            average_hourly_earnings: 1.25,
+            earnings_by_protocol: [("storj".to_string(), 20.0), ("grass".to_string(), 10.0)]
+                .into_iter()
+                .collect(),
+            allocation_changes: vec![AllocationChange {
+                timestamp: period_end - Duration::hours(6),
+                protocol: "grass".to_string(),
+                old_allocation: 20.0,
+                new_allocation: 35.0,
+                reason: "Higher <yield>".to_string(),
+                earnings_impact: 0.5,
+            }],
+            total_improvement: 0.5,
+            successful_optimizations: 1,
+            uptime_percent: 99.0,
+            attribution: EarningsAttribution {
+                start_earnings_per_hour: 1.0,
+                end_earnings_per_hour: 1.25,
+                market_effect: -0.25,
+                allocation_effect: 0.5,
+                by_protocol: [(
+                    "grass".to_string(),
+                    ProtocolAttribution { market_effect: -0.25, allocation_effect: 0.5 },
+                )]
+                .into_iter()
+                .collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = render_html(7, &report(), &DisplayConfig::default(), ReportingTimezone::Utc);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Performance Report #7"));
+        assert!(html.contains("$30.00"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        // Highest earner is charted first
+        assert!(html.find(">storj<").unwrap() < html.find(">grass<").unwrap());
+        assert!(html.contains("Higher &lt;yield&gt;"));
+        assert!(html.contains("from reallocations"));
+
+        let eur = DisplayConfig {
+            currency: Currency::Eur,
+            ..DisplayConfig::default()
+        };
+        let html = render_html(7, &report(), &eur, ReportingTimezone::Utc);
+        assert!(html.contains("27.60 EUR"));
+        assert!(!html.contains("$30.00"));
+
+        // Times are shown in the reporting time zone
+        assert!(html.contains(" UTC"));
+        let html = render_html(7, &report(), &DisplayConfig::default(), "+09:00".parse().unwrap());
+        assert!(html.contains(" +09:00") && !html.contains(" UTC"));
+    }
+
+    #[test]
+    fn test_render_pdf() {
+        let pdf = render_pdf(7, &report(), &DisplayConfig::default(), ReportingTimezone::Utc);
+        let pdf = String::from_utf8(pdf).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("(Performance Report #7) Tj"));
+        assert!(pdf.contains("re f"));
+
+        // startxref points at the cross-reference table
+        let startxref: usize = pdf
+            .lines()
+            .rev()
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[startxref..].starts_with("xref"));
+    }
+
+    #[test]
+    fn test_pdf_text_escaping() {
+        assert_eq!(pdf_text("a(b)\\c"), "a\\(b\\)\\\\c");
+        assert_eq!(pdf_text("café"), "caf?");
+    }
+}
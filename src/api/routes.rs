@@ -5,24 +5,41 @@ use actix_web::web;
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
-use super::{auth, handlers, middleware};
+use super::{
+    access_log, accounts, analysis, auth, backups, credentials, diff, export, grafana, handlers, middleware,
+    notifications, reports, timeline, ui,
+};
+use super::middleware::{JwtConfig, RateLimiter};
 
 /// Configure all API routes with authentication and rate limiting
-pub fn configure_routes(cfg: &mut web::ServiceConfig, db_pool: Arc<SqlitePool>) {
+pub fn configure_routes(
+    cfg: &mut web::ServiceConfig,
+    db_pool: Arc<SqlitePool>,
+    jwt: Arc<JwtConfig>,
+    rate_limiter: Arc<RateLimiter>,
+) {
     log::info!("Configuring API routes...");
 
     // Single unified /api/v1 scope with nested sub-scopes for different middleware layers
     cfg.service(
         web::scope("/api/v1")
+            // Inside the request ID scope; logs only when an AccessLog is registered
+            .wrap(access_log::AccessLogMiddleware)
             .wrap(middleware::RequestIdMiddleware)
+            .wrap(middleware::security_headers())
+            // Outermost so preflight requests are answered before authentication
+            .wrap(middleware::cors_from_env())
             // Public routes (no authentication required)
             .route("/health", web::get().to(handlers::health_check))
             .route("/status", web::get().to(handlers::get_status))
+            // Exchange an API key for a short-lived bearer token
+            .route("/auth/token", web::post().to(auth::issue_token))
             // Protected endpoints sub-scope (authentication required)
             .service(
                 web::scope("")
-                    .wrap(middleware::RateLimitMiddleware::new(db_pool.clone()))
-                    .wrap(middleware::AuthMiddleware::new(db_pool.clone()))
+                    .wrap(middleware::IdempotencyMiddleware::new(db_pool.clone()))
+                    .wrap(middleware::RateLimitMiddleware::new(rate_limiter))
+                    .wrap(middleware::AuthMiddleware::new(db_pool.clone(), jwt))
                     // Metrics endpoints
                     .route("/metrics", web::get().to(handlers::get_metrics))
                     .route("/metrics/current", web::get().to(handlers::get_metrics))
@@ -30,12 +47,41 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig, db_pool: Arc<SqlitePool>)
                         "/metrics/history",
                         web::get().to(handlers::get_metrics_history),
                     )
+                    .route("/metrics/diff", web::get().to(diff::get_metrics_diff))
+                    .route("/metrics/export", web::get().to(export::export_metrics))
                     // Optimization endpoints
                     .route("/opportunities", web::get().to(handlers::get_opportunities))
                     .route(
                         "/allocation",
                         web::get().to(handlers::get_optimal_allocation),
                     )
+                    .route(
+                        "/allocation/history",
+                        web::get().to(timeline::get_allocation_history),
+                    )
+                    .route("/efficiency", web::get().to(handlers::get_efficiency))
+                    .route("/analysis/stress", web::post().to(analysis::stress_test))
+                    .route("/config", web::get().to(handlers::get_config))
+                    .route(
+                        "/config/optimizer",
+                        web::get().to(handlers::get_optimizer_config),
+                    )
+                    .route(
+                        "/config/optimizer",
+                        web::put().to(handlers::update_optimizer_config),
+                    )
+                    .route(
+                        "/config/scheduler",
+                        web::get().to(handlers::get_scheduler_config),
+                    )
+                    .route(
+                        "/config/scheduler",
+                        web::put().to(handlers::update_scheduler_config),
+                    )
+                    .route(
+                        "/scheduler/status",
+                        web::get().to(handlers::get_scheduler_status),
+                    )
                     // Reallocation endpoints
                     .route(
                         "/reallocate",
@@ -45,6 +91,47 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig, db_pool: Arc<SqlitePool>)
                         "/reallocation/history",
                         web::get().to(handlers::get_reallocation_history),
                     )
+                    .route(
+                        "/reallocations/pending",
+                        web::get().to(handlers::get_pending_reallocations),
+                    )
+                    .route(
+                        "/reallocations/{id}/approve",
+                        web::post().to(handlers::approve_reallocation),
+                    )
+                    .route(
+                        "/reallocations/{id}/reject",
+                        web::post().to(handlers::reject_reallocation),
+                    )
+                    .route(
+                        "/decisions/{id}",
+                        web::get().to(handlers::get_optimizer_decision),
+                    )
+                    // Protocol lifecycle endpoints
+                    .route(
+                        "/protocols/{name}/connect",
+                        web::post().to(handlers::connect_protocol),
+                    )
+                    .route(
+                        "/protocols/{name}/disconnect",
+                        web::post().to(handlers::disconnect_protocol),
+                    )
+                    .route(
+                        "/protocols/{name}/container/{action}",
+                        web::post().to(handlers::control_protocol_container),
+                    )
+                    .route(
+                        "/protocols/{name}/service/{action}",
+                        web::post().to(handlers::control_protocol_service),
+                    )
+                    .route(
+                        "/protocols/{name}/metrics/schema",
+                        web::get().to(handlers::get_protocol_metric_schema),
+                    )
+                    .route(
+                        "/protocols/{name}/risk",
+                        web::get().to(handlers::get_protocol_risk),
+                    )
                     // Dashboard endpoints
                     .route("/dashboard", web::get().to(handlers::get_dashboard))
                     // Alert endpoints
@@ -53,27 +140,104 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig, db_pool: Arc<SqlitePool>)
                         "/alerts/acknowledge",
                         web::post().to(handlers::acknowledge_alert),
                     )
+                    .route(
+                        "/alerts/acknowledge-all",
+                        web::post().to(handlers::acknowledge_all_alerts),
+                    )
+                    .route(
+                        "/alerts/{id}/acknowledge",
+                        web::post().to(handlers::acknowledge_alert_by_id),
+                    )
+                    // Wallet endpoints
+                    .route("/wallets", web::get().to(handlers::get_wallets))
+                    // Network endpoints
+                    .route("/network/probes", web::get().to(handlers::get_network_probes))
+                    .route("/network/public-ips", web::get().to(handlers::get_public_ips))
+                    // Export endpoints
+                    .route(
+                        "/export/earnings",
+                        web::get().to(export::export_earnings),
+                    )
+                    // Grafana JSON datasource
+                    .route("/grafana", web::get().to(grafana::test_connection))
+                    .route("/grafana/search", web::post().to(grafana::search))
+                    .route("/grafana/query", web::post().to(grafana::query))
+                    .route(
+                        "/grafana/annotations",
+                        web::post().to(grafana::annotations),
+                    )
+                    // Notification endpoints
+                    .route(
+                        "/notifications/test",
+                        web::post().to(notifications::test_notification),
+                    )
+                    .route("/notifications/rules", web::get().to(notifications::list_rules))
+                    .route("/notifications/rules", web::post().to(notifications::create_rule))
+                    .route("/notifications/rules/{id}", web::put().to(notifications::update_rule))
+                    .route("/notifications/rules/{id}", web::delete().to(notifications::delete_rule))
+                    // Report endpoints
+                    .route("/reports", web::get().to(reports::list_reports))
+                    .route("/reports", web::post().to(reports::generate_report))
+                    .route(
+                        "/reports/{id}/download",
+                        web::get().to(reports::download_report),
+                    )
+                    // Account endpoints
+                    .route("/account", web::get().to(accounts::get_account))
                     // Admin routes (flattened into main protected scope)
+                    // Accounts (default account only)
+                    .route("/admin/accounts", web::get().to(accounts::list_accounts))
+                    .route("/admin/accounts", web::post().to(accounts::create_account))
+                    .route(
+                        "/admin/accounts/{id}",
+                        web::put().to(accounts::update_account),
+                    )
                     // API key management
                     .route("/admin/keys", web::post().to(auth::create_api_key))
                     .route("/admin/keys", web::get().to(auth::list_api_keys))
                     .route("/admin/keys/{id}", web::get().to(auth::get_api_key))
                     .route("/admin/keys/{id}", web::put().to(auth::update_api_key))
-                    .route("/admin/keys/{id}", web::delete().to(auth::delete_api_key)),
+                    .route("/admin/keys/{id}", web::delete().to(auth::delete_api_key))
+                    .route("/admin/keys/{id}/rotate", web::post().to(auth::rotate_api_key))
+                    // Protocol credential vault
+                    .route(
+                        "/admin/credentials",
+                        web::get().to(credentials::list_credentials),
+                    )
+                    .route(
+                        "/admin/credentials/{protocol}",
+                        web::put().to(credentials::put_credentials),
+                    )
+                    .route(
+                        "/admin/credentials/{protocol}",
+                        web::delete().to(credentials::delete_credentials),
+                    )
+                    // WebSocket session counters
+                    .route("/admin/websocket", web::get().to(handlers::get_websocket_stats))
+                    // Database backups
+                    .route("/admin/backups", web::get().to(backups::list_backups))
+                    .route("/admin/backups", web::post().to(backups::create_backup)),
             ),
     );
 
+    // Embedded web dashboard
+    cfg.configure(ui::configure);
+
     log::info!("All API routes configured successfully");
-    log::info!("   Public: /api/v1/health, /api/v1/status");
+    log::info!("   Public: /api/v1/health, /api/v1/status, /api/v1/auth/token");
     log::info!("   Protected: /api/v1/metrics, /api/v1/allocation, etc.");
-    log::info!("   Admin: /api/v1/admin/keys");
+    log::info!("   Admin: /api/v1/admin/accounts, /api/v1/admin/keys, /api/v1/admin/credentials, /api/v1/admin/backups, /api/v1/admin/websocket");
+    log::info!("   Dashboard: /dashboard/");
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_routes_configuration() {
-        // Verify route configuration is defined
-        assert!(true);
+        // Verify route configuration is defined with the expected signature
+        let _: fn(&mut web::ServiceConfig, Arc<SqlitePool>, Arc<JwtConfig>, Arc<RateLimiter>) =
+            configure_routes;
     }
 }
@@ -0,0 +1,451 @@
+/// Dashboard Sessions
+///
+/// Password logins for the embedded dashboard, so operators don't paste raw
+/// API keys into the browser. Users (argon2-hashed passwords) are created with
+/// `depin-orcha users create`. Logging in sets an `HttpOnly`, `SameSite=Strict`
+/// session cookie scoped to `/dashboard`; the page trades it for the same
+/// short-lived bearer token an API key would get, so the REST API and its
+/// authentication are unchanged. Repeated failed logins for a username, or
+/// from a client address, are refused for a while (see [`LoginThrottle`]).
+use actix_web::{
+    cookie::{time, Cookie, SameSite},
+    web, HttpRequest, HttpResponse, Result,
+};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use uuid::Uuid;
+
+use crate::api::auth::TokenResponse;
+use crate::api::middleware::{ApiKeyInfo, AuthError, JwtConfig, Principal};
+use crate::api::problem::ApiError;
+use crate::config::Config;
+
+/// Session cookie name
+pub const SESSION_COOKIE: &str = "orcha_session";
+
+/// Path the session cookie is sent to
+const SESSION_COOKIE_PATH: &str = "/dashboard";
+
+/// Failed logins allowed per username, and per client address, in a window
+const MAX_FAILED_LOGINS: u32 = 5;
+
+/// Window failed logins are counted over
+const FAILED_LOGIN_WINDOW: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+// ============================================================================
+// LOGIN THROTTLING
+// ============================================================================
+
+/// Failed logins by username and by client address
+///
+/// Once either reaches `MAX_FAILED_LOGINS` within `FAILED_LOGIN_WINDOW`,
+/// further logins for it are refused, right password or not, until the
+/// window ends. A successful login clears the username's count.
+#[derive(Debug, Default)]
+pub struct LoginThrottle {
+    failures: Mutex<HashMap<String, (u32, Instant)>>,
+}
+
+impl LoginThrottle {
+    /// Refuse the login if any of `keys` is locked out
+    fn check(&self, keys: &[String], now: Instant) -> Result<(), AuthError> {
+        let failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        let retry_after = keys
+            .iter()
+            .filter_map(|key| failures.get(key))
+            .filter(|(count, _)| *count >= MAX_FAILED_LOGINS)
+            .map(|(_, since)| FAILED_LOGIN_WINDOW.saturating_sub(now.saturating_duration_since(*since)))
+            .filter(|remaining| !remaining.is_zero())
+            .max();
+        match retry_after {
+            Some(remaining) => Err(AuthError::TooManyLoginAttempts(remaining.as_secs().max(1))),
+            None => Ok(()),
+        }
+    }
+
+    /// Count a failed login against each of `keys`
+    fn record_failure(&self, keys: &[String], now: Instant) {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        failures.retain(|_, (_, since)| now.saturating_duration_since(*since) < FAILED_LOGIN_WINDOW);
+        for key in keys {
+            failures.entry(key.clone()).or_insert((0, now)).0 += 1;
+        }
+    }
+
+    /// Forget the failed logins of `keys`
+    fn clear(&self, keys: &[String]) {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        for key in keys {
+            failures.remove(key);
+        }
+    }
+}
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub username: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// POST /dashboard/session - Log in with a username and password
+pub async fn login(
+    http_req: HttpRequest,
+    db: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    throttle: web::Data<LoginThrottle>,
+    req: web::Json<LoginRequest>,
+) -> Result<HttpResponse> {
+    let client = http_req.peer_addr().map(|addr| addr.ip().to_string()).unwrap_or_default();
+    let throttle_keys = [format!("user:{}", req.username), format!("ip:{}", client)];
+    throttle.check(&throttle_keys, Instant::now())?;
+
+    let user = sqlx::query!(
+        r#"
+        SELECT u.id AS "id!", u.password_hash, u.is_active AND a.is_active AS "active!: bool"
+        FROM dashboard_users u
+        JOIN accounts a ON a.id = u.account_id
+        WHERE u.username = ?
+        "#,
+        req.username
+    )
+    .fetch_optional(db.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    // Unknown users and wrong passwords are indistinguishable, in timing too
+    let password_hash = match &user {
+        Some(user) => user.password_hash.as_str(),
+        None => dummy_password_hash(),
+    };
+    let verified = verify_password(&req.password, password_hash);
+    let Some(user) = user.filter(|_| verified) else {
+        throttle.record_failure(&throttle_keys, Instant::now());
+        return Err(AuthError::InvalidCredentials.into());
+    };
+    // The client address keeps its count, so one known password cannot reset it
+    throttle.clear(&throttle_keys[..1]);
+    if !user.active {
+        return Err(AuthError::InactiveAccount.into());
+    }
+
+    let ttl = Duration::seconds(config.server.session_ttl_secs as i64);
+    let (token, expires_at) = create_session(db.get_ref(), user.id, ttl).await?;
+
+    let cookie = session_cookie(token, &config)
+        .max_age(time::Duration::seconds(ttl.num_seconds()))
+        .finish();
+    Ok(HttpResponse::Ok().cookie(cookie).json(LoginResponse {
+        username: req.username.clone(),
+        expires_at,
+    }))
+}
+
+/// DELETE /dashboard/session - Log out, ending the session
+pub async fn logout(
+    http_req: HttpRequest,
+    db: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if let Some(cookie) = http_req.cookie(SESSION_COOKIE) {
+        let token_hash = hash_token(cookie.value());
+        sqlx::query!("DELETE FROM dashboard_sessions WHERE token_hash = ?", token_hash)
+            .execute(db.get_ref())
+            .await
+            .map_err(ApiError::from)?;
+    }
+
+    let mut cookie = session_cookie(String::new(), &config).finish();
+    cookie.make_removal();
+    Ok(HttpResponse::NoContent().cookie(cookie).finish())
+}
+
+/// POST /dashboard/session/token - Exchange the session cookie for a bearer token
+pub async fn issue_session_token(
+    http_req: HttpRequest,
+    db: web::Data<SqlitePool>,
+    jwt: web::Data<JwtConfig>,
+) -> Result<HttpResponse> {
+    let cookie = http_req.cookie(SESSION_COOKIE).ok_or(AuthError::InvalidSession)?;
+    let key_info = validate_session(db.get_ref(), cookie.value()).await?;
+    let (access_token, claims) = jwt.issue(&key_info)?;
+
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: jwt.ttl.as_secs(),
+        expires_at: DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now),
+    }))
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Hash a password with argon2id and a random salt
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Check a password against a stored argon2 hash
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+/// Hash checked for unknown usernames, so they take as long to refuse as
+/// wrong passwords
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_password(&Uuid::new_v4().to_string()).unwrap_or_default())
+}
+
+/// Create a dashboard user, returning its row ID
+pub async fn insert_dashboard_user(
+    db: &SqlitePool,
+    account_id: i64,
+    username: &str,
+    password: &str,
+    permissions: &[String],
+) -> Result<i64, String> {
+    if username.trim().is_empty() {
+        return Err("username must not be empty".to_string());
+    }
+    if password.len() < 8 {
+        return Err("password must be at least 8 characters".to_string());
+    }
+
+    let password_hash = hash_password(password)?;
+    let permissions = serde_json::to_string(permissions).map_err(|e| e.to_string())?;
+    let now = Utc::now().to_rfc3339();
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO dashboard_users (account_id, username, password_hash, permissions, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+        account_id,
+        username,
+        password_hash,
+        permissions,
+        now
+    )
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Start a session for a user, returning the cookie token and its expiry
+///
+/// Expired sessions are pruned on the way.
+async fn create_session(
+    db: &SqlitePool,
+    user_id: i64,
+    ttl: Duration,
+) -> Result<(String, DateTime<Utc>), ApiError> {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let token_hash = hash_token(&token);
+    let now = Utc::now();
+    let expires_at = now + ttl;
+    let (now_str, expires_str) = (now.to_rfc3339(), expires_at.to_rfc3339());
+
+    let mut tx = db.begin().await?;
+    sqlx::query!("DELETE FROM dashboard_sessions WHERE expires_at <= ?", now_str)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO dashboard_sessions (user_id, token_hash, created_at, expires_at) VALUES (?, ?, ?, ?)",
+        user_id,
+        token_hash,
+        now_str,
+        expires_str
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!("UPDATE dashboard_users SET last_login_at = ? WHERE id = ?", now_str, user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok((token, expires_at))
+}
+
+/// Resolve a session token to the identity its bearer tokens carry
+pub async fn validate_session(db: &SqlitePool, token: &str) -> Result<ApiKeyInfo, AuthError> {
+    let token_hash = hash_token(token);
+    let now = Utc::now().to_rfc3339();
+    let record = sqlx::query!(
+        r#"
+        SELECT
+            u.id AS "id!", u.account_id, u.username, u.permissions, u.created_at, u.last_login_at,
+            s.expires_at, u.is_active AND a.is_active AS "active!: bool", a.tier
+        FROM dashboard_sessions s
+        JOIN dashboard_users u ON u.id = s.user_id
+        JOIN accounts a ON a.id = u.account_id
+        WHERE s.token_hash = ? AND s.expires_at > ?
+        "#,
+        token_hash,
+        now
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AuthError::DatabaseError(e.to_string()))?
+    .ok_or(AuthError::InvalidSession)?;
+
+    if !record.active {
+        return Err(AuthError::InactiveAccount);
+    }
+
+    Ok(ApiKeyInfo {
+        principal: Principal::DashboardUser(record.id),
+        account_id: record.account_id,
+        name: record.username,
+        description: Some("Dashboard session".to_string()),
+        created_at: DateTime::from_naive_utc_and_offset(record.created_at, Utc),
+        expires_at: Some(DateTime::from_naive_utc_and_offset(record.expires_at, Utc)),
+        last_used_at: record
+            .last_login_at
+            .map(|l| DateTime::from_naive_utc_and_offset(l, Utc)),
+        is_active: true,
+        tier: record.tier.parse().unwrap_or_default(),
+        permissions: serde_json::from_str(&record.permissions).unwrap_or_default(),
+    })
+}
+
+/// Session cookie with the security attributes set
+fn session_cookie<'c>(token: String, config: &Config) -> actix_web::cookie::CookieBuilder<'c> {
+    Cookie::build(SESSION_COOKIE, token)
+        .path(SESSION_COOKIE_PATH)
+        .http_only(true)
+        .secure(config.server.secure_cookies)
+        .same_site(SameSite::Strict)
+}
+
+/// Stored form of a session token
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test::TestRequest, App};
+
+    #[test]
+    fn test_password_hashing() {
+        let hash = hash_password("correct horse").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+    }
+
+    #[test]
+    fn test_login_throttle() {
+        let throttle = LoginThrottle::default();
+        let start = Instant::now();
+        let keys = ["user:ops".to_string(), "ip:10.0.0.1".to_string()];
+        for _ in 0..MAX_FAILED_LOGINS {
+            assert!(throttle.check(&keys, start).is_ok());
+            throttle.record_failure(&keys, start);
+        }
+        assert!(matches!(throttle.check(&keys, start), Err(AuthError::TooManyLoginAttempts(900))));
+
+        // The address is locked out for other usernames too, until the window ends
+        let other_user = ["user:admin".to_string(), "ip:10.0.0.1".to_string()];
+        assert!(throttle.check(&other_user, start).is_err());
+        assert!(throttle.check(&other_user, start + FAILED_LOGIN_WINDOW).is_ok());
+
+        throttle.clear(&keys);
+        assert!(throttle.check(&keys, start).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_login_flow() {
+        let pool = crate::db::test_pool().await;
+        let permissions = vec!["read".to_string()];
+        let user_id = insert_dashboard_user(&pool, 1, "ops", "hunter2hunter2", &permissions).await.unwrap();
+        assert!(insert_dashboard_user(&pool, 1, "ops", "hunter2hunter2", &[]).await.is_err());
+        assert!(insert_dashboard_user(&pool, 1, "short", "pw", &[]).await.is_err());
+
+        let jwt = JwtConfig::new("test-secret", std::time::Duration::from_secs(60));
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .app_data(web::Data::new(Config::default()))
+                .app_data(web::Data::new(jwt.clone()))
+                .app_data(web::Data::new(LoginThrottle::default()))
+                .route("/dashboard/session", web::post().to(login))
+                .route("/dashboard/session", web::delete().to(logout))
+                .route("/dashboard/session/token", web::post().to(issue_session_token)),
+        )
+        .await;
+        let login_as = |password: &str| {
+            TestRequest::post()
+                .uri("/dashboard/session")
+                .set_json(serde_json::json!({ "username": "ops", "password": password }))
+                .to_request()
+        };
+
+        let resp = actix_web::test::call_service(&app, login_as("wrong password")).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = actix_web::test::call_service(&app, login_as("hunter2hunter2")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let cookie = resp.response().cookies().find(|c| c.name() == SESSION_COOKIE).unwrap().into_owned();
+        assert_eq!(cookie.path(), Some("/dashboard"));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+
+        // The session trades for a bearer token carrying the user's identity
+        let exchange = || TestRequest::post().uri("/dashboard/session/token").cookie(cookie.clone());
+        let resp = actix_web::test::call_service(&app, exchange().to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        let claims = jwt.validate(body["access_token"].as_str().unwrap()).unwrap();
+        assert_eq!((claims.sub, claims.account_id), (Principal::DashboardUser(user_id), 1));
+        assert_eq!(claims.name, "ops");
+        assert_eq!(claims.permissions, permissions);
+
+        let anonymous = TestRequest::post().uri("/dashboard/session/token").to_request();
+        let resp = actix_web::test::call_service(&app, anonymous).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // Logging out ends the session
+        let logout = TestRequest::delete().uri("/dashboard/session").cookie(cookie.clone()).to_request();
+        let resp = actix_web::test::call_service(&app, logout).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let resp = actix_web::test::call_service(&app, exchange().to_request()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
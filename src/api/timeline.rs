@@ -0,0 +1,287 @@
+/// Allocation Timeline
+///
+/// Reconstructs each protocol's allocation over time as a step function from
+/// the allocation recorded with every metrics snapshot and from executed
+/// reallocations. Each step carries the average earnings observed while it
+/// held, so the dashboard can overlay the timeline on the earnings chart and
+/// show whether a reallocation paid off.
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+use super::middleware::AccountId;
+use super::models::SuccessResponse;
+use super::problem::ApiError;
+use crate::db::models::{AllocationSampleRecord, ReallocationRecord};
+use crate::db::queries;
+
+/// Default timeline window when `from` is omitted
+const DEFAULT_TIMELINE_DAYS: i64 = 7;
+
+/// Allocation changes smaller than this (percentage points) are noise
+const ALLOCATION_EPSILON: f64 = 1e-6;
+
+// ============================================================================
+// MODELS
+// ============================================================================
+
+/// Allocation history query parameters (RFC 3339 timestamps)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AllocationHistoryQuery {
+    pub protocol: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Stretch of time a protocol held one allocation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AllocationStep {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub allocation_percent: f64,
+    /// Reallocation that started this step; absent when the change was only
+    /// observed in metrics snapshots
+    pub reallocation_id: Option<i64>,
+    /// Average earnings of the snapshots recorded during this step
+    pub avg_earnings_per_hour: Option<f64>,
+}
+
+/// Allocation history response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationHistoryResponse {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Steps per protocol, oldest first
+    pub protocols: BTreeMap<String, Vec<AllocationStep>>,
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// GET /api/v1/allocation/history - Allocation over time as a step function
+pub async fn get_allocation_history(
+    db: web::Data<SqlitePool>,
+    account: AccountId,
+    query: web::Query<AllocationHistoryQuery>,
+) -> ActixResult<HttpResponse> {
+    let query = query.into_inner();
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(DEFAULT_TIMELINE_DAYS));
+    if from >= to {
+        return Err(ApiError::bad_request(
+            "INVALID_RANGE",
+            format!("`from` ({}) must be before `to` ({})", from, to),
+        )
+        .into());
+    }
+
+    let protocol = query.protocol.as_deref();
+    let samples = queries::get_allocation_samples(db.get_ref(), account.0, protocol, from, to)
+        .await
+        .map_err(ApiError::from)?;
+    let reallocations: Vec<_> = queries::get_reallocations_by_range(db.get_ref(), account.0, from, to)
+        .await
+        .map_err(ApiError::from)?
+        .into_iter()
+        .filter(|r| protocol.is_none_or(|p| r.protocol_name == p))
+        .collect();
+
+    let protocols = build_timelines(&samples, &reallocations, from, to);
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(AllocationHistoryResponse { from, to, protocols })))
+}
+
+// ============================================================================
+// RECONSTRUCTION
+// ============================================================================
+
+/// A point at which a protocol's allocation was known
+struct AllocationEvent {
+    at: DateTime<Utc>,
+    allocation_percent: f64,
+    /// Earnings recorded with a snapshot
+    earnings_per_hour: Option<f64>,
+    reallocation_id: Option<i64>,
+}
+
+/// Build each protocol's steps over `[from, to]`
+///
+/// A sample from before `from` sets the allocation the window opens with;
+/// without one, the window opens with the `old_allocation` of a reallocation
+/// preceding the first sample, or else at the first sample.
+pub fn build_timelines(
+    samples: &[AllocationSampleRecord],
+    reallocations: &[ReallocationRecord],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> BTreeMap<String, Vec<AllocationStep>> {
+    let mut events: BTreeMap<String, Vec<AllocationEvent>> = BTreeMap::new();
+    for sample in samples {
+        let Some(at) = parse_timestamp(&sample.timestamp) else {
+            continue;
+        };
+        events.entry(sample.protocol_name.clone()).or_default().push(AllocationEvent {
+            at: at.max(from),
+            allocation_percent: sample.allocation_percent,
+            // Earnings from before the window say nothing about it
+            earnings_per_hour: (at >= from).then_some(sample.earnings_per_hour),
+            reallocation_id: None,
+        });
+    }
+    for reallocation in reallocations {
+        let Some(at) = parse_timestamp(&reallocation.timestamp) else {
+            continue;
+        };
+        let protocol = events.entry(reallocation.protocol_name.clone()).or_default();
+        if protocol.first().is_none_or(|first| at < first.at) {
+            protocol.insert(
+                0,
+                AllocationEvent {
+                    at: from,
+                    allocation_percent: reallocation.old_allocation,
+                    earnings_per_hour: None,
+                    reallocation_id: None,
+                },
+            );
+        }
+        protocol.push(AllocationEvent {
+            at,
+            allocation_percent: reallocation.new_allocation,
+            earnings_per_hour: None,
+            reallocation_id: reallocation.id,
+        });
+    }
+
+    events
+        .into_iter()
+        .map(|(protocol, mut events)| {
+            // Stable, so a reallocation follows a snapshot taken at the same instant
+            events.sort_by_key(|event| event.at);
+            (protocol, steps(&events, to))
+        })
+        .collect()
+}
+
+/// Collapse time-ordered events into steps, closing the last at `to`
+fn steps(events: &[AllocationEvent], to: DateTime<Utc>) -> Vec<AllocationStep> {
+    let mut steps: Vec<AllocationStep> = Vec::new();
+    // Earnings total and snapshot count of the open step
+    let mut earnings = (0.0, 0);
+
+    for event in events {
+        let changed = steps
+            .last()
+            .is_none_or(|step| (step.allocation_percent - event.allocation_percent).abs() > ALLOCATION_EPSILON);
+        if changed {
+            if let Some(step) = steps.last_mut() {
+                step.end = event.at;
+                step.avg_earnings_per_hour = average(earnings);
+            }
+            earnings = (0.0, 0);
+            steps.push(AllocationStep {
+                start: event.at,
+                end: to,
+                allocation_percent: event.allocation_percent,
+                reallocation_id: event.reallocation_id,
+                avg_earnings_per_hour: None,
+            });
+        }
+        if let Some(value) = event.earnings_per_hour {
+            earnings = (earnings.0 + value, earnings.1 + 1);
+        }
+    }
+    if let Some(step) = steps.last_mut() {
+        step.avg_earnings_per_hour = average(earnings);
+    }
+    steps
+}
+
+fn average((total, count): (f64, usize)) -> Option<f64> {
+    (count > 0).then(|| total / count as f64)
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn at(hour: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z").unwrap().with_timezone(&Utc) + Duration::hours(hour)
+    }
+
+    fn sample(hour: i64, protocol: &str, allocation: f64, earnings: f64) -> AllocationSampleRecord {
+        AllocationSampleRecord {
+            timestamp: at(hour).to_rfc3339(),
+            protocol_name: protocol.to_string(),
+            earnings_per_hour: earnings,
+            allocation_percent: allocation,
+        }
+    }
+
+    fn reallocation(id: i64, hour: i64, protocol: &str, old: f64, new: f64) -> ReallocationRecord {
+        ReallocationRecord {
+            id: Some(id),
+            ..ReallocationRecord::new(at(hour), protocol.to_string(), old, new, None, None)
+        }
+    }
+
+    #[test]
+    fn test_timeline_steps_at_reallocations_and_observed_changes() {
+        let samples = [
+            sample(-2, "storj", 30.0, 9.0),
+            sample(1, "storj", 30.0, 1.0),
+            sample(2, "storj", 30.0, 2.0),
+            sample(4, "storj", 50.0, 4.0),
+            sample(5, "storj", 50.0, 5.0),
+            sample(7, "storj", 40.0, 3.0),
+        ];
+        let reallocations = [reallocation(11, 3, "storj", 30.0, 50.0)];
+
+        let timeline = build_timelines(&samples, &reallocations, at(0), at(8));
+        let storj = &timeline["storj"];
+        let spans: Vec<_> = storj.iter().map(|s| (s.start, s.end, s.allocation_percent)).collect();
+        assert_eq!(spans, [(at(0), at(3), 30.0), (at(3), at(7), 50.0), (at(7), at(8), 40.0)]);
+        assert_eq!(storj[1].reallocation_id, Some(11));
+        assert_eq!(storj[2].reallocation_id, None);
+        // The sample from before the window only sets the opening allocation
+        assert_eq!(storj[0].avg_earnings_per_hour, Some(1.5));
+        assert_eq!(storj[1].avg_earnings_per_hour, Some(4.5));
+    }
+
+    #[test]
+    fn test_timeline_opens_with_old_allocation_of_first_reallocation() {
+        let reallocations = [reallocation(1, 2, "golem", 10.0, 25.0)];
+        let timeline = build_timelines(&[], &reallocations, at(0), at(4));
+        let spans: Vec<_> = timeline["golem"].iter().map(|s| (s.start, s.allocation_percent)).collect();
+        assert_eq!(spans, [(at(0), 10.0), (at(2), 25.0)]);
+        assert!(timeline["golem"].iter().all(|s| s.avg_earnings_per_hour.is_none()));
+
+        // Without earlier data the timeline starts at the first sample
+        let timeline = build_timelines(&[sample(3, "grass", 20.0, 1.0)], &[], at(0), at(4));
+        assert_eq!(timeline["grass"][0].start, at(3));
+    }
+
+    #[tokio::test]
+    async fn test_allocation_samples_include_latest_before_window() {
+        let pool = crate::db::test_pool().await;
+        for (hour, allocation) in [(-3, 10.0), (-1, 20.0), (1, 30.0)] {
+            let storj = HashMap::from([("storj".to_string(), allocation)]);
+            queries::store_historical_metrics(&pool, 1, at(hour), &storj, &storj).await.unwrap();
+            let both = HashMap::from([("storj".to_string(), allocation), ("grass".to_string(), 5.0)]);
+            queries::store_historical_metrics(&pool, 2, at(hour), &both, &both).await.unwrap();
+        }
+
+        let samples = queries::get_allocation_samples(&pool, 1, Some("storj"), at(0), at(2)).await.unwrap();
+        let allocations: Vec<_> = samples.iter().map(|s| s.allocation_percent).collect();
+        assert_eq!(allocations, [20.0, 30.0]);
+        let samples = queries::get_allocation_samples(&pool, 2, None, at(0), at(2)).await.unwrap();
+        assert_eq!(samples.len(), 4);
+        assert!(queries::get_allocation_samples(&pool, 1, Some("grass"), at(0), at(2)).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+/// Embedded Web Dashboard
+///
+/// The static dashboard in `dashboard/` is compiled into the binary and served
+/// at `/dashboard/`, so operators don't need a separate frontend deployment.
+/// Operators log in with a dashboard user (see `sessions`); the page trades
+/// the session for a bearer token, reads the REST API and follows live
+/// updates over `/ws`.
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+use rust_embed::RustEmbed;
+
+use super::sessions;
+
+/// Files under `dashboard/`, embedded at build time
+#[derive(RustEmbed)]
+#[folder = "dashboard/"]
+struct DashboardAssets;
+
+/// Scripts, styles and API calls only from this server
+const CONTENT_SECURITY_POLICY: &str =
+    "default-src 'self'; connect-src 'self' ws: wss:; img-src 'self' data:; frame-ancestors 'none'";
+
+/// Register the dashboard routes
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/dashboard", web::get().to(redirect_to_dashboard))
+        // Registered before the asset catch-all, which would answer 405
+        .route("/dashboard/session", web::post().to(sessions::login))
+        .route("/dashboard/session", web::delete().to(sessions::logout))
+        .route("/dashboard/session/token", web::post().to(sessions::issue_session_token))
+        .route("/dashboard/{path:.*}", web::get().to(serve_dashboard));
+}
+
+/// GET /dashboard - Redirect so relative asset URLs resolve under `/dashboard/`
+async fn redirect_to_dashboard() -> HttpResponse {
+    HttpResponse::PermanentRedirect()
+        .insert_header((header::LOCATION, "/dashboard/"))
+        .finish()
+}
+
+/// GET /dashboard/{path} - Serve an embedded dashboard file
+async fn serve_dashboard(req: HttpRequest, path: web::Path<String>) -> HttpResponse {
+    let path = match path.as_str() {
+        "" => "index.html",
+        path => path,
+    };
+    let Some(file) = DashboardAssets::get(path) else {
+        return HttpResponse::NotFound().finish();
+    };
+
+    let etag = format!("\"{}\"", hex::encode(file.metadata.sha256_hash()));
+    let unchanged = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value == etag);
+
+    let mut response = if unchanged {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header((header::ETAG, etag))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .insert_header((header::CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"));
+
+    if unchanged {
+        response.finish()
+    } else {
+        response
+            .content_type(file.metadata.mimetype())
+            .body(file.data.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, App};
+
+    #[actix_web::test]
+    async fn test_dashboard_assets_are_served() {
+        let app = test::init_service(App::new().configure(configure)).await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/dashboard").to_request()).await;
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers().get(header::LOCATION).unwrap(), "/dashboard/");
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/dashboard/").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::CONTENT_TYPE).unwrap().to_str().unwrap().starts_with("text/html"));
+        let etag = res.headers().get(header::ETAG).unwrap().clone();
+        let body = test::read_body(res).await;
+        assert!(std::str::from_utf8(&body).unwrap().contains("app.js"));
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/dashboard/app.js").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::CONTENT_TYPE).unwrap().to_str().unwrap().contains("javascript"));
+
+        // Revalidation with the current ETag skips the body
+        let req = test::TestRequest::get()
+            .uri("/dashboard/")
+            .insert_header((header::IF_NONE_MATCH, etag))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_MODIFIED);
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/dashboard/missing.js").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,52 +1,851 @@
 /// WebSocket Real-Time Updates
 ///
 /// Handles WebSocket connections for real-time dashboard updates.
-
+///
+/// Clients subscribe to topics (`metrics`, `alerts`, `reallocations`,
+/// `protocol:{name}`) with optional per-topic filters. The shared [`WsHub`]
+/// subscribes to the orchestrator's event bus and fans updates out to every
+/// session; sessions never poll the orchestrator themselves. Frames are JSON
+/// until a subscription asks for `"encoding": "msgpack"`.
+///
+/// Sessions are capped globally and per principal (API key or dashboard
+/// user). Each session queues its updates in an outbox that a
+/// writer task drains to the client; a session whose outbox holds
+/// `max_pending_updates` is a slow consumer: its queue is coalesced,
+/// dropped, or the session is closed, so one stalled dashboard tab cannot
+/// hold updates in memory for everyone.
+use actix_web::http::{header, StatusCode};
 use actix_web::{web, HttpRequest, HttpResponse};
 use actix_ws::Message;
-use futures::{SinkExt, StreamExt};
-use std::sync::Arc;
-use tokio::sync::broadcast;
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, Notify};
 use serde_json::json;
 use chrono::Utc;
 
+use crate::events::{EventSubscriber, OrchestratorEvent};
 use crate::orchestration::AggregatedMetrics;
-use super::models::{WsMessage, MetricsSnapshot};
+use crate::shutdown::ShutdownToken;
+use super::middleware::{ApiKeyInfo, AuthError, AuthMiddleware, JwtConfig};
+use super::models::{
+    AlertDto, AllocationChangeDto, MetricsSnapshot, ProtocolUpdateDto, TopicFilter, WsEncoding, WsMessage,
+};
+use super::problem::problem_response;
 use super::AppState;
 
+/// Events buffered per subscriber before slow sessions start dropping updates
+const HUB_CAPACITY: usize = 256;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// What happens to a slow consumer's queued updates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlowConsumerPolicy {
+    /// Keep every alert and reallocation, but only the newest metrics
+    /// snapshot and the newest update of each protocol
+    #[default]
+    Coalesce,
+    /// Discard the queued updates and resume with the next one
+    Drop,
+    /// Close the session with a policy violation
+    Disconnect,
+}
+
+/// WebSocket limits (`[websocket]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WsConfig {
+    /// Sessions open at once across all clients
+    pub max_connections: usize,
+    /// Sessions open at once per principal (API key or dashboard user)
+    pub max_connections_per_key: usize,
+    /// Updates queued for a session's client before it is a slow consumer
+    pub max_pending_updates: usize,
+    pub slow_consumer: SlowConsumerPolicy,
+    /// A send not accepted within this many milliseconds is abandoned and
+    /// its update dropped
+    pub send_timeout_ms: u64,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 500,
+            max_connections_per_key: 10,
+            max_pending_updates: 64,
+            slow_consumer: SlowConsumerPolicy::Coalesce,
+            send_timeout_ms: 5000,
+        }
+    }
+}
+
+impl WsConfig {
+    /// Validate the limits
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_connections == 0 {
+            return Err("max_connections must be at least 1".to_string());
+        }
+        if self.max_connections_per_key == 0 {
+            return Err("max_connections_per_key must be at least 1".to_string());
+        }
+        if self.max_connections_per_key > self.max_connections {
+            return Err(format!(
+                "max_connections_per_key ({}) exceeds max_connections ({})",
+                self.max_connections_per_key, self.max_connections
+            ));
+        }
+        if self.max_pending_updates == 0 {
+            return Err("max_pending_updates must be at least 1".to_string());
+        }
+        if self.send_timeout_ms == 0 {
+            return Err("send_timeout_ms must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// TOPICS
+// ============================================================================
+
+/// Subscription topic
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Topic {
+    Metrics,
+    Alerts,
+    Reallocations,
+    Protocol(String),
+}
+
+impl Topic {
+    /// Parse a topic name such as `alerts` or `protocol:storj`
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "metrics" => Ok(Topic::Metrics),
+            "alerts" => Ok(Topic::Alerts),
+            "reallocations" => Ok(Topic::Reallocations),
+            _ => match name.strip_prefix("protocol:") {
+                Some(protocol) if !protocol.is_empty() => Ok(Topic::Protocol(protocol.to_string())),
+                _ => Err(format!(
+                    "Unknown topic '{}'; expected metrics, alerts, reallocations or protocol:{{name}}",
+                    name
+                )),
+            },
+        }
+    }
+
+    /// Resolve the topic of a subscribe/unsubscribe request
+    fn from_request(topic: Option<String>, protocol: Option<String>) -> Result<Self, String> {
+        match (topic, protocol) {
+            (Some(topic), _) => Topic::parse(&topic),
+            (None, Some(protocol)) => Topic::parse(&format!("protocol:{}", protocol)),
+            (None, None) => Err("Missing `topic`".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topic::Metrics => write!(f, "metrics"),
+            Topic::Alerts => write!(f, "alerts"),
+            Topic::Reallocations => write!(f, "reallocations"),
+            Topic::Protocol(protocol) => write!(f, "protocol:{}", protocol),
+        }
+    }
+}
+
+impl TopicFilter {
+    fn allows_protocol(&self, protocol: &str) -> bool {
+        self.protocols
+            .as_ref()
+            .is_none_or(|protocols| protocols.iter().any(|p| p == protocol))
+    }
+}
+
+// ============================================================================
+// BROADCAST HUB
+// ============================================================================
+
+/// Update published to all WebSocket sessions
+///
+/// Metrics events share the coordinator's snapshot, so fanning one out to
+/// every session copies a pointer rather than the metrics.
+#[derive(Debug, Clone)]
+pub enum HubEvent {
+    Metrics(Arc<AggregatedMetrics>),
+    Alert(AlertDto),
+    Reallocation(Vec<AllocationChangeDto>),
+    Protocol(ProtocolUpdateDto),
+}
+
+impl HubEvent {
+    /// Topic the event is published on
+    pub fn topic(&self) -> Topic {
+        match self {
+            HubEvent::Metrics(_) => Topic::Metrics,
+            HubEvent::Alert(_) => Topic::Alerts,
+            HubEvent::Reallocation(_) => Topic::Reallocations,
+            HubEvent::Protocol(update) => Topic::Protocol(update.protocol.clone()),
+        }
+    }
+
+    /// Message for a session subscribed with `filter`, or None if filtered out
+    pub fn filtered(&self, filter: &TopicFilter) -> Option<WsMessage> {
+        match self {
+            HubEvent::Metrics(snapshot) => Some(WsMessage::MetricsUpdate {
+                metrics: MetricsSnapshot {
+                    timestamp: snapshot.timestamp,
+                    total_earnings: snapshot.total_earnings_per_hour,
+                    earnings_by_protocol: snapshot
+                        .earnings_by_protocol
+                        .iter()
+                        .filter(|(protocol, _)| filter.allows_protocol(protocol))
+                        .map(|(protocol, earnings)| (protocol.clone(), *earnings))
+                        .collect(),
+                },
+            }),
+            HubEvent::Alert(alert) => {
+                if alert.severity < filter.min_severity.unwrap_or(0.0) {
+                    return None;
+                }
+                Some(WsMessage::AlertNotification {
+                    alert: alert.clone(),
+                })
+            }
+            HubEvent::Reallocation(changes) => {
+                let changes: Vec<_> = changes
+                    .iter()
+                    .filter(|c| filter.allows_protocol(&c.protocol))
+                    .cloned()
+                    .collect();
+                if changes.is_empty() {
+                    return None;
+                }
+                Some(WsMessage::ReallocationNotification { changes })
+            }
+            HubEvent::Protocol(update) => Some(WsMessage::ProtocolUpdate {
+                update: update.clone(),
+            }),
+        }
+    }
+}
+
+/// Collapse a slow consumer's queue to what its client still needs
+///
+/// Alerts and reallocations are all kept; metrics snapshots and protocol
+/// updates supersede earlier ones on the same topic, so only the newest of
+/// each survives. Kept updates stay in publish order.
+fn coalesce(queue: VecDeque<Outgoing>) -> VecDeque<Outgoing> {
+    let supersedes = |item: &Outgoing| {
+        matches!(item.message, WsMessage::MetricsUpdate { .. } | WsMessage::ProtocolUpdate { .. })
+    };
+    let newest: HashMap<Topic, usize> = queue
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| supersedes(item))
+        .map(|(i, item)| (item.topic.clone(), i))
+        .collect();
+    queue
+        .into_iter()
+        .enumerate()
+        .filter(|(i, item)| !supersedes(item) || newest[&item.topic] == *i)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Shared fan-out of updates to WebSocket sessions
+#[derive(Debug, Clone)]
+pub struct WsHub {
+    sender: broadcast::Sender<HubEvent>,
+    config: WsConfig,
+    connections: Arc<Mutex<Connections>>,
+    stats: Arc<WsStats>,
+}
+
+/// Open sessions, in total and per client key
+#[derive(Debug, Default)]
+struct Connections {
+    total: usize,
+    by_key: HashMap<String, usize>,
+}
+
+impl WsHub {
+    /// Create hub buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            config: WsConfig::default(),
+            connections: Arc::new(Mutex::new(Connections::default())),
+            stats: Arc::new(WsStats::default()),
+        }
+    }
+
+    /// Create hub enforcing `config`
+    ///
+    /// The buffer holds at least twice `max_pending_updates`, so a session
+    /// busy applying its slow consumer policy does not lag the channel.
+    pub fn with_config(config: WsConfig) -> Self {
+        let mut hub = Self::new(HUB_CAPACITY.max(config.max_pending_updates * 2));
+        hub.config = config;
+        hub
+    }
+
+    /// Limits the hub enforces
+    pub fn config(&self) -> &WsConfig {
+        &self.config
+    }
+
+    /// Publish an event; returns the number of sessions it reached
+    pub fn publish(&self, event: HubEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Receive all subsequently published events
+    pub fn subscribe(&self) -> broadcast::Receiver<HubEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of connected sessions
+    pub fn session_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Admit a session for client `key` if neither cap is reached
+    ///
+    /// The session counts against the caps until the returned guard drops.
+    pub fn admit(&self, key: String) -> Result<WsConnection, WsRejection> {
+        let mut connections = self.connections.lock().unwrap();
+        if connections.total >= self.config.max_connections {
+            self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(WsRejection::ServerFull);
+        }
+        let open = connections.by_key.get(&key).copied().unwrap_or(0);
+        if open >= self.config.max_connections_per_key {
+            self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(WsRejection::KeyLimit);
+        }
+        connections.by_key.insert(key.clone(), open + 1);
+        connections.total += 1;
+        self.stats.accepted.fetch_add(1, Ordering::Relaxed);
+        Ok(WsConnection { hub: self.clone(), key })
+    }
+
+    /// Connection and delivery counters
+    pub fn stats(&self) -> WsStatsSnapshot {
+        let connections = self.connections.lock().unwrap();
+        WsStatsSnapshot {
+            active: connections.total,
+            clients: connections.by_key.len(),
+            accepted: self.stats.accepted.load(Ordering::Relaxed),
+            rejected: self.stats.rejected.load(Ordering::Relaxed),
+            messages_sent: self.stats.sent.load(Ordering::Relaxed),
+            bytes_sent: self.stats.bytes_sent.load(Ordering::Relaxed),
+            messages_dropped: self.stats.dropped.load(Ordering::Relaxed),
+            messages_coalesced: self.stats.coalesced.load(Ordering::Relaxed),
+            slow_consumers: self.stats.slow_consumers.load(Ordering::Relaxed),
+            slow_disconnects: self.stats.slow_disconnects.load(Ordering::Relaxed),
+        }
+    }
+
+    fn release(&self, key: &str) {
+        let mut connections = self.connections.lock().unwrap();
+        connections.total = connections.total.saturating_sub(1);
+        if let Some(open) = connections.by_key.get_mut(key) {
+            *open -= 1;
+            if *open == 0 {
+                connections.by_key.remove(key);
+            }
+        }
+    }
+}
+
+impl Default for WsHub {
+    fn default() -> Self {
+        Self::new(HUB_CAPACITY)
+    }
+}
+
+/// Why a session was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsRejection {
+    /// `max_connections` sessions are open
+    ServerFull,
+    /// The client already has `max_connections_per_key` sessions open
+    KeyLimit,
+}
+
+impl WsRejection {
+    fn response(self) -> HttpResponse {
+        match self {
+            WsRejection::ServerFull => problem_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "WS_CONNECTION_LIMIT",
+                "Too many WebSocket sessions are open; try again later",
+            ),
+            WsRejection::KeyLimit => problem_response(
+                StatusCode::TOO_MANY_REQUESTS,
+                "WS_KEY_CONNECTION_LIMIT",
+                "This client has too many WebSocket sessions open; close one first",
+            ),
+        }
+    }
+}
+
+/// Admitted session; releases its slot when dropped
+#[derive(Debug)]
+pub struct WsConnection {
+    hub: WsHub,
+    key: String,
+}
+
+impl Drop for WsConnection {
+    fn drop(&mut self) {
+        self.hub.release(&self.key);
+    }
+}
+
+/// Counters kept by the hub
+#[derive(Debug, Default)]
+struct WsStats {
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    dropped: AtomicU64,
+    coalesced: AtomicU64,
+    slow_consumers: AtomicU64,
+    slow_disconnects: AtomicU64,
+}
+
+/// WebSocket connection metrics, as reported by `/api/v1/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct WsStatsSnapshot {
+    /// Sessions open now
+    pub active: usize,
+    /// Distinct client keys with a session open
+    pub clients: usize,
+    /// Sessions admitted since start
+    pub accepted: u64,
+    /// Sessions refused by a connection cap
+    pub rejected: u64,
+    pub messages_sent: u64,
+    /// Payload bytes of the updates sent, in each session's encoding
+    pub bytes_sent: u64,
+    /// Updates lost to lag, send timeouts, or the `drop` policy
+    pub messages_dropped: u64,
+    /// Superseded updates skipped by the `coalesce` policy
+    pub messages_coalesced: u64,
+    /// Times a session's outbox reached `max_pending_updates`
+    pub slow_consumers: u64,
+    /// Sessions closed by the `disconnect` policy
+    pub slow_disconnects: u64,
+}
+
+#[async_trait]
+impl EventSubscriber for WsHub {
+    async fn on_event(&self, event: &OrchestratorEvent) {
+        match event {
+            OrchestratorEvent::MetricsCollected(metrics) => {
+                if self.session_count() == 0 {
+                    return;
+                }
+                for (protocol, earnings) in &metrics.earnings_by_protocol {
+                    self.publish(HubEvent::Protocol(ProtocolUpdateDto {
+                        timestamp: metrics.timestamp,
+                        protocol: protocol.clone(),
+                        earnings_per_hour: *earnings,
+                        allocation_percent: metrics.allocation_by_protocol.get(protocol).copied().unwrap_or(0.0),
+                        connected: metrics.connection_status.get(protocol).copied().unwrap_or(false),
+                    }));
+                }
+                self.publish(HubEvent::Metrics(metrics.clone()));
+            }
+            OrchestratorEvent::AlertRaised(alert) => {
+                let notification = &alert.notification;
+                self.publish(HubEvent::Alert(AlertDto {
+                    id: alert.id,
+                    timestamp: alert.timestamp,
+                    alert_type: notification.alert_type.clone(),
+                    severity: notification.severity,
+                    message: notification.message.clone(),
+                    protocol: notification.protocol.clone(),
+                    acknowledged: false,
+                    resolved_at: None,
+                }));
+            }
+            OrchestratorEvent::ReallocationExecuted(reallocation) => {
+                let changes = reallocation
+                    .changes
+                    .iter()
+                    .map(|change| AllocationChangeDto {
+                        timestamp: change.timestamp,
+                        protocol: change.protocol.clone(),
+                        old_allocation: change.old_allocation,
+                        new_allocation: change.new_allocation,
+                        earnings_impact: change.earnings_impact,
+                        realized_impact: None,
+                        decision_id: reallocation.decision_id,
+                    })
+                    .collect();
+                self.publish(HubEvent::Reallocation(changes));
+            }
+            OrchestratorEvent::ProtocolStateChanged(_) => {}
+        }
+    }
+}
+
+// ============================================================================
+// SESSIONS
+// ============================================================================
+
+/// Subprotocol a browser offers ahead of its bearer token
+///
+/// Browsers cannot set headers on a WebSocket request, so the dashboard
+/// connects with `Sec-WebSocket-Protocol: bearer, <token>`.
+const BEARER_PROTOCOL: &str = "bearer";
+
 /// WebSocket connection handler
+///
+/// Sessions need an `X-API-Key` or a bearer token, in the `Authorization`
+/// header or offered as a subprotocol after `bearer`. The hub carries the
+/// live orchestrator's metrics, alerts and reallocations, so principals of
+/// any other account are refused with `403 NO_ORCHESTRATOR`.
 pub async fn ws_handler(
     req: HttpRequest,
     stream: web::Payload,
     state: web::Data<AppState>,
+    jwt: web::Data<JwtConfig>,
+    db: web::Data<SqlitePool>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let (response, session, msg_stream) = actix_ws::handle(&req, stream)?;
+    let (key_info, subprotocol) = authenticate(&req, jwt.get_ref(), db.get_ref()).await?;
+    if key_info.account_id != state.account_id() {
+        tracing::warn!("WebSocket session refused: {} is not the orchestrator's account", key_info.principal);
+        return Ok(problem_response(
+            StatusCode::FORBIDDEN,
+            "NO_ORCHESTRATOR",
+            "No orchestrator is running for this account",
+        ));
+    }
+
+    let connection = match state.ws_hub.admit(key_info.principal.to_string()) {
+        Ok(connection) => connection,
+        Err(rejection) => {
+            tracing::warn!("WebSocket session refused: {:?}", rejection);
+            return Ok(rejection.response());
+        }
+    };
+    let (mut response, session, msg_stream) = actix_ws::handle(&req, stream)?;
+    if subprotocol {
+        response.headers_mut().insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            header::HeaderValue::from_static(BEARER_PROTOCOL),
+        );
+    }
 
     // Spawn a task to handle the WebSocket session
     actix_web::rt::spawn(handle_ws_session(
         session,
         msg_stream,
-        state.into_inner(),
+        state.ws_hub.subscribe(),
+        connection,
+        state.shutdown.clone(),
     ));
 
     Ok(response)
 }
 
+/// Resolve the API key or dashboard user a session is opened with
+///
+/// Also returns whether the bearer token came as a subprotocol, which the
+/// handshake response must then accept.
+async fn authenticate(req: &HttpRequest, jwt: &JwtConfig, db: &SqlitePool) -> Result<(ApiKeyInfo, bool), AuthError> {
+    let headers = req.headers();
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    let offered = headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| {
+            let mut protocols = h.split(',').map(str::trim);
+            (protocols.next() == Some(BEARER_PROTOCOL)).then(|| protocols.next()).flatten()
+        });
+
+    if let Some(token) = bearer.or(offered) {
+        let claims = jwt.validate(token.trim())?;
+        let key_info = AuthMiddleware::validate_claims(db, &claims).await?;
+        return Ok((key_info, bearer.is_none()));
+    }
+    let api_key = headers
+        .get("X-API-Key")
+        .and_then(|h| h.to_str().ok())
+        .ok_or(AuthError::MissingApiKey)?;
+    Ok((AuthMiddleware::validate_api_key(db, api_key).await?, false))
+}
+
+/// Serialized WebSocket message
+#[derive(Debug, Clone, PartialEq)]
+enum Frame {
+    /// JSON
+    Text(String),
+    /// MessagePack
+    Binary(Vec<u8>),
+}
+
+impl Frame {
+    /// Serialize `msg` for a session using `encoding`
+    fn encode(msg: &WsMessage, encoding: WsEncoding) -> Option<Self> {
+        match encoding {
+            WsEncoding::Json => serde_json::to_string(msg).ok().map(Frame::Text),
+            // Named fields keep MessagePack messages shaped like the JSON ones
+            WsEncoding::Msgpack => rmp_serde::to_vec_named(msg).ok().map(Frame::Binary),
+        }
+    }
+
+    /// Parse a client message; clients may send either encoding
+    fn decode(&self) -> Result<WsMessage, String> {
+        match self {
+            Frame::Text(text) => serde_json::from_str(text).map_err(|e| e.to_string()),
+            Frame::Binary(bytes) => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Frame::Text(text) => text.len(),
+            Frame::Binary(bytes) => bytes.len(),
+        }
+    }
+
+    async fn send(self, session: &mut actix_ws::Session) -> Result<(), actix_ws::Closed> {
+        match self {
+            Frame::Text(text) => session.text(text).await,
+            Frame::Binary(bytes) => session.binary(bytes).await,
+        }
+    }
+}
+
+/// Apply a client message to the session's subscriptions and encoding;
+/// returns the reply
+fn handle_client_message(
+    frame: &Frame,
+    subscriptions: &mut HashMap<Topic, TopicFilter>,
+    encoding: &mut WsEncoding,
+) -> WsMessage {
+    let ws_msg = match frame.decode() {
+        Ok(ws_msg) => ws_msg,
+        Err(message) => {
+            return WsMessage::Error {
+                code: "INVALID_MESSAGE".to_string(),
+                message,
+            };
+        }
+    };
+
+    match ws_msg {
+        WsMessage::Subscribe {
+            topic,
+            protocol,
+            filter,
+            encoding: requested,
+        } => match Topic::from_request(topic, protocol) {
+            Ok(topic) => {
+                let name = topic.to_string();
+                subscriptions.insert(topic, filter);
+                if let Some(requested) = requested {
+                    *encoding = requested;
+                }
+                WsMessage::Ack {
+                    action: "subscribe".to_string(),
+                    topic: name,
+                    encoding: requested,
+                }
+            }
+            Err(message) => WsMessage::Error {
+                code: "INVALID_TOPIC".to_string(),
+                message,
+            },
+        },
+        WsMessage::Unsubscribe { topic, protocol } => match Topic::from_request(topic, protocol) {
+            Ok(topic) => {
+                subscriptions.remove(&topic);
+                WsMessage::Ack {
+                    action: "unsubscribe".to_string(),
+                    topic: topic.to_string(),
+                    encoding: None,
+                }
+            }
+            Err(message) => WsMessage::Error {
+                code: "INVALID_TOPIC".to_string(),
+                message,
+            },
+        },
+        WsMessage::Ping => WsMessage::Pong,
+        _ => WsMessage::Error {
+            code: "UNSUPPORTED_MESSAGE".to_string(),
+            message: "Only Subscribe, Unsubscribe and Ping are accepted".to_string(),
+        },
+    }
+}
+
+/// Update queued for one session's client
+#[derive(Debug, Clone)]
+struct Outgoing {
+    topic: Topic,
+    message: WsMessage,
+    /// Session encoding when the update was queued
+    encoding: WsEncoding,
+}
+
+impl Outgoing {
+    /// The update a session with these subscriptions receives for `event`
+    fn for_session(
+        event: &HubEvent,
+        subscriptions: &HashMap<Topic, TopicFilter>,
+        encoding: WsEncoding,
+    ) -> Option<Self> {
+        let topic = event.topic();
+        let message = subscriptions.get(&topic).and_then(|filter| event.filtered(filter))?;
+        Some(Outgoing { topic, message, encoding })
+    }
+
+    fn frame(&self) -> Option<Frame> {
+        Frame::encode(&self.message, self.encoding)
+    }
+}
+
+/// Updates waiting to be written to one session's client
+///
+/// The session task queues updates here and a writer task drains them, so
+/// the queue length is how far the client is behind.
+#[derive(Debug, Default)]
+struct Outbox {
+    queue: Mutex<VecDeque<Outgoing>>,
+    ready: Notify,
+}
+
+/// Result of queueing an update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backpressure {
+    Queued,
+    /// The queue was full and this many superseded updates were skipped
+    Coalesced(usize),
+    /// The queue was full and this many updates were discarded
+    Dropped(usize),
+    /// The queue was full and the session must close
+    Disconnect,
+}
+
+impl Outbox {
+    /// Queue `item`, applying `config.slow_consumer` when the queue is full
+    fn push(&self, item: Outgoing, config: &WsConfig) -> Backpressure {
+        let mut queue = self.queue.lock().unwrap();
+        let outcome = if queue.len() < config.max_pending_updates {
+            queue.push_back(item);
+            Backpressure::Queued
+        } else {
+            match config.slow_consumer {
+                SlowConsumerPolicy::Coalesce => {
+                    queue.push_back(item);
+                    let queued = queue.len();
+                    *queue = coalesce(std::mem::take(&mut *queue));
+                    Backpressure::Coalesced(queued - queue.len())
+                }
+                SlowConsumerPolicy::Drop => {
+                    let dropped = queue.len();
+                    queue.clear();
+                    queue.push_back(item);
+                    Backpressure::Dropped(dropped)
+                }
+                SlowConsumerPolicy::Disconnect => return Backpressure::Disconnect,
+            }
+        };
+        self.ready.notify_one();
+        outcome
+    }
+
+    fn pop(&self) -> Option<Outgoing> {
+        self.queue.lock().unwrap().pop_front()
+    }
+}
+
+/// Write queued updates to the client until the session closes
+///
+/// A send the client does not take within `send_timeout_ms` is abandoned
+/// and the update counted as dropped.
+async fn drain_outbox(mut session: actix_ws::Session, outbox: Arc<Outbox>, hub: WsHub) {
+    let timeout = Duration::from_millis(hub.config.send_timeout_ms);
+    loop {
+        let Some(item) = outbox.pop() else {
+            outbox.ready.notified().await;
+            continue;
+        };
+        let Some(frame) = item.frame() else {
+            continue;
+        };
+        let size = frame.len() as u64;
+        match tokio::time::timeout(timeout, frame.send(&mut session)).await {
+            Ok(Ok(())) => {
+                hub.stats.sent.fetch_add(1, Ordering::Relaxed);
+                hub.stats.bytes_sent.fetch_add(size, Ordering::Relaxed);
+            }
+            Ok(Err(_)) => return,
+            Err(_) => {
+                hub.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("WebSocket send timed out, dropped {} update", item.topic);
+            }
+        }
+    }
+}
+
+/// Answer a client message in the encoding the session used when it arrived
+async fn reply(
+    session: &mut actix_ws::Session,
+    frame: &Frame,
+    subscriptions: &mut HashMap<Topic, TopicFilter>,
+    encoding: &mut WsEncoding,
+) {
+    let used = *encoding;
+    let reply = handle_client_message(frame, subscriptions, encoding);
+    if let Some(frame) = Frame::encode(&reply, used) {
+        let _ = frame.send(session).await;
+    }
+}
+
 /// Handle a WebSocket session
 async fn handle_ws_session(
     mut session: actix_ws::Session,
     mut msg_stream: actix_ws::MessageStream,
-    state: Arc<AppState>,
+    mut events: broadcast::Receiver<HubEvent>,
+    connection: WsConnection,
+    shutdown: ShutdownToken,
 ) {
-    tracing::info!("WebSocket connection established");
+    tracing::info!("WebSocket connection established ({})", connection.key);
+    let hub = connection.hub.clone();
 
-    let mut subscriptions = std::collections::HashSet::new();
-    subscriptions.insert("metrics".to_string());
+    let mut subscriptions = HashMap::new();
+    subscriptions.insert(Topic::Metrics, TopicFilter::default());
+    let mut encoding = WsEncoding::default();
 
     // Send initial connection message
     let init_msg = json!({
         "type": "connected",
         "message": "Connected to DePIN-Orcha Dashboard",
+        "topics": ["metrics", "alerts", "reallocations", "protocol:{name}"],
+        "encodings": ["json", "msgpack"],
         "timestamp": Utc::now().to_rfc3339(),
     });
 
@@ -55,38 +854,23 @@ async fn handle_ws_session(
         return;
     }
 
-    // Main WebSocket loop
-    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(5));
+    let outbox = Arc::new(Outbox::default());
+    let mut writer = actix_web::rt::spawn(drain_outbox(session.clone(), outbox.clone(), hub.clone()));
 
+    // Main WebSocket loop
     loop {
         tokio::select! {
             // Handle incoming WebSocket messages
             msg = msg_stream.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                            match ws_msg {
-                                WsMessage::Subscribe { protocol } => {
-                                    if let Some(proto) = protocol {
-                                        subscriptions.insert(format!("metrics:{}", proto));
-                                        let response = json!({
-                                            "type": "subscribed",
-                                            "protocol": proto,
-                                        });
-                                        let _ = session.text(response.to_string()).await;
-                                    }
-                                }
-                                WsMessage::Unsubscribe { protocol } => {
-                                    if let Some(proto) = protocol {
-                                        subscriptions.remove(&format!("metrics:{}", proto));
-                                    }
-                                }
-                                WsMessage::Ping => {
-                                    let _ = session.text(json!({"type": "pong"}).to_string()).await;
-                                }
-                                _ => {}
-                            }
-                        }
+                        reply(&mut session, &Frame::Text(text.to_string()), &mut subscriptions, &mut encoding).await;
+                    }
+                    Some(Ok(Message::Binary(bytes))) => {
+                        reply(&mut session, &Frame::Binary(bytes.to_vec()), &mut subscriptions, &mut encoding).await;
+                    }
+                    Some(Ok(Message::Ping(bytes))) => {
+                        let _ = session.pong(&bytes).await;
                     }
                     Some(Ok(Message::Close(_))) => {
                         tracing::info!("WebSocket connection closed");
@@ -100,29 +884,69 @@ async fn handle_ws_session(
                 }
             }
 
-            // Send periodic metrics updates
-            _ = ticker.tick() => {
-                if subscriptions.contains("metrics") {
-                    if let Ok(Some(metrics)) = state.coordinator.get_current_metrics().await {
-                        let snapshot = MetricsSnapshot {
-                            timestamp: metrics.timestamp,
-                            total_earnings: metrics.total_earnings_per_hour,
-                            earnings_by_protocol: metrics.earnings_by_protocol,
-                        };
+            // Queue hub events for subscribed topics
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        hub.stats.dropped.fetch_add(skipped, Ordering::Relaxed);
+                        tracing::warn!("WebSocket session lagging, skipped {} updates", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Some(item) = Outgoing::for_session(&event, &subscriptions, encoding) else {
+                    continue;
+                };
 
-                        let msg = WsMessage::MetricsUpdate {
-                            metrics: snapshot,
+                let backpressure = outbox.push(item, &hub.config);
+                if backpressure != Backpressure::Queued {
+                    hub.stats.slow_consumers.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "Slow WebSocket consumer ({}): {} updates pending, {:?}",
+                        connection.key,
+                        hub.config.max_pending_updates,
+                        hub.config.slow_consumer
+                    );
+                }
+                match backpressure {
+                    Backpressure::Queued => {}
+                    Backpressure::Coalesced(skipped) => {
+                        hub.stats.coalesced.fetch_add(skipped as u64, Ordering::Relaxed);
+                    }
+                    Backpressure::Dropped(dropped) => {
+                        hub.stats.dropped.fetch_add(dropped as u64, Ordering::Relaxed);
+                    }
+                    Backpressure::Disconnect => {
+                        hub.stats.slow_disconnects.fetch_add(1, Ordering::Relaxed);
+                        writer.abort();
+                        let reason = actix_ws::CloseReason {
+                            code: actix_ws::CloseCode::Policy,
+                            description: Some("Too slow to keep up with updates".to_string()),
                         };
-
-                        if let Ok(json) = serde_json::to_string(&msg) {
-                            let _ = session.text(json).await;
-                        }
+                        let _ = session.close(Some(reason)).await;
+                        return;
                     }
                 }
             }
+
+            // The writer stops once the client is gone
+            _ = &mut writer => {
+                tracing::info!("WebSocket client stopped accepting messages");
+                break;
+            }
+
+            // Tell the client the server is going away
+            _ = shutdown.cancelled() => {
+                writer.abort();
+                let _ = session.close(Some(actix_ws::CloseCode::Away.into())).await;
+                tracing::info!("WebSocket session closed for shutdown");
+                return;
+            }
         }
     }
 
+    writer.abort();
     tracing::info!("WebSocket session ended");
 }
 
@@ -137,6 +961,7 @@ pub async fn ws_health() -> Result<HttpResponse, actix_web::Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::orchestration::ResourceUtilization;
 
     #[test]
     fn test_ws_message_serialization() {
@@ -154,4 +979,418 @@ mod tests {
         };
         assert_eq!(snapshot.total_earnings, 10.5);
     }
+
+    #[tokio::test]
+    async fn test_hub_forwards_bus_events() {
+        use crate::events::{ExecutedReallocation, RaisedAlert};
+        use crate::notifications::Notification;
+        use crate::orchestration::AllocationChange;
+
+        let hub = WsHub::default();
+        let mut session = hub.subscribe();
+        let notification = Notification {
+            alert_type: "ProtocolDisconnected".to_string(),
+            protocol: Some("storj".to_string()),
+            severity: 80.0,
+            message: "storj disconnected".to_string(),
+            earnings: None,
+            earnings_delta: None,
+        };
+        hub.on_event(&OrchestratorEvent::AlertRaised(RaisedAlert { id: Some(7), timestamp: Utc::now(), notification }))
+            .await;
+        let change = AllocationChange {
+            timestamp: Utc::now(),
+            protocol: "grass".to_string(),
+            old_allocation: 20.0,
+            new_allocation: 30.0,
+            reason: "optimizer".to_string(),
+            earnings_impact: 0.5,
+        };
+        let reallocation = ExecutedReallocation { changes: vec![change], decision_id: Some(3) };
+        hub.on_event(&OrchestratorEvent::ReallocationExecuted(reallocation)).await;
+
+        match session.recv().await.unwrap() {
+            HubEvent::Alert(alert) => {
+                assert_eq!((alert.id, alert.protocol.as_deref()), (Some(7), Some("storj")));
+                assert!(!alert.acknowledged);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match session.recv().await.unwrap() {
+            HubEvent::Reallocation(changes) => {
+                assert_eq!(changes.len(), 1);
+                assert_eq!((changes[0].new_allocation, changes[0].decision_id), (30.0, Some(3)));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_topic_parsing() {
+        assert_eq!(Topic::parse("alerts").unwrap(), Topic::Alerts);
+        assert_eq!(
+            Topic::parse("protocol:storj").unwrap(),
+            Topic::Protocol("storj".to_string())
+        );
+        assert_eq!(Topic::Protocol("storj".to_string()).to_string(), "protocol:storj");
+        assert!(Topic::parse("protocol:").is_err());
+        assert!(Topic::parse("weather").is_err());
+    }
+
+    fn text(json: &str) -> Frame {
+        Frame::Text(json.to_string())
+    }
+
+    #[test]
+    fn test_subscription_ack_and_error() {
+        let mut subscriptions = HashMap::new();
+        let mut encoding = WsEncoding::Json;
+
+        let reply = handle_client_message(
+            &text(r#"{"type":"Subscribe","topic":"alerts","filter":{"min_severity":0.5}}"#),
+            &mut subscriptions,
+            &mut encoding,
+        );
+        assert!(matches!(reply, WsMessage::Ack { ref topic, encoding: None, .. } if topic == "alerts"));
+        assert_eq!(subscriptions[&Topic::Alerts].min_severity, Some(0.5));
+
+        // Legacy protocol-only subscription
+        handle_client_message(&text(r#"{"type":"Subscribe","protocol":"grass"}"#), &mut subscriptions, &mut encoding);
+        assert!(subscriptions.contains_key(&Topic::Protocol("grass".to_string())));
+
+        let reply =
+            handle_client_message(&text(r#"{"type":"Subscribe","topic":"nope"}"#), &mut subscriptions, &mut encoding);
+        assert!(matches!(reply, WsMessage::Error { ref code, .. } if code == "INVALID_TOPIC"));
+
+        let reply = handle_client_message(&text("not json"), &mut subscriptions, &mut encoding);
+        assert!(matches!(reply, WsMessage::Error { ref code, .. } if code == "INVALID_MESSAGE"));
+
+        handle_client_message(&text(r#"{"type":"Unsubscribe","topic":"alerts"}"#), &mut subscriptions, &mut encoding);
+        assert!(!subscriptions.contains_key(&Topic::Alerts));
+        assert_eq!(encoding, WsEncoding::Json);
+    }
+
+    #[test]
+    fn test_encoding_negotiated_on_subscribe() {
+        let mut subscriptions = HashMap::new();
+        let mut encoding = WsEncoding::Json;
+
+        let reply = handle_client_message(
+            &text(r#"{"type":"Subscribe","topic":"metrics","encoding":"msgpack"}"#),
+            &mut subscriptions,
+            &mut encoding,
+        );
+        assert!(matches!(reply, WsMessage::Ack { encoding: Some(WsEncoding::Msgpack), .. }));
+        assert_eq!(encoding, WsEncoding::Msgpack);
+
+        // A rejected subscription leaves the encoding alone
+        handle_client_message(
+            &text(r#"{"type":"Subscribe","topic":"nope","encoding":"json"}"#),
+            &mut subscriptions,
+            &mut encoding,
+        );
+        assert_eq!(encoding, WsEncoding::Msgpack);
+
+        // Binary frames from the client are MessagePack
+        let ping = Frame::encode(&WsMessage::Ping, WsEncoding::Msgpack).unwrap();
+        assert!(matches!(ping, Frame::Binary(_)));
+        let reply = handle_client_message(&ping, &mut subscriptions, &mut encoding);
+        assert!(matches!(reply, WsMessage::Pong));
+    }
+
+    #[test]
+    fn test_msgpack_frames_are_smaller() {
+        let msg = WsMessage::ProtocolUpdate {
+            update: ProtocolUpdateDto {
+                timestamp: Utc::now(),
+                protocol: "storj".to_string(),
+                earnings_per_hour: 0.123456789,
+                allocation_percent: 33.333333333,
+                connected: true,
+            },
+        };
+        let json = Frame::encode(&msg, WsEncoding::Json).unwrap();
+        let msgpack = Frame::encode(&msg, WsEncoding::Msgpack).unwrap();
+        assert!(msgpack.len() < json.len());
+
+        match msgpack.decode().unwrap() {
+            WsMessage::ProtocolUpdate { update } => {
+                assert_eq!(update.protocol, "storj");
+                assert_eq!(update.earnings_per_hour, 0.123456789);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sessions_get_frames_in_their_encoding() {
+        let event = HubEvent::Protocol(ProtocolUpdateDto {
+            timestamp: Utc::now(),
+            protocol: "grass".to_string(),
+            earnings_per_hour: 0.5,
+            allocation_percent: 25.0,
+            connected: true,
+        });
+        let session = |subscribe: &str| {
+            let mut subscriptions = HashMap::new();
+            let mut encoding = WsEncoding::default();
+            handle_client_message(&text(subscribe), &mut subscriptions, &mut encoding);
+            Outgoing::for_session(&event, &subscriptions, encoding).unwrap()
+        };
+
+        // JSON sessions still get the same text frames
+        let json = session(r#"{"type":"Subscribe","topic":"protocol:grass"}"#);
+        assert_eq!(json.frame(), Some(Frame::Text(serde_json::to_string(&json.message).unwrap())));
+
+        let msgpack = session(r#"{"type":"Subscribe","topic":"protocol:grass","encoding":"msgpack"}"#);
+        let frame = msgpack.frame().unwrap();
+        assert!(matches!(frame, Frame::Binary(_)));
+        match frame.decode().unwrap() {
+            WsMessage::ProtocolUpdate { update } => assert_eq!(update.protocol, "grass"),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_event_filters() {
+        let snapshot = AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: 3.0,
+            earnings_by_protocol: HashMap::from([("storj".to_string(), 1.0), ("grass".to_string(), 2.0)]),
+            allocation_by_protocol: HashMap::new(),
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 0.0,
+                memory_percent: 0.0,
+                bandwidth_percent: 0.0,
+                storage_percent: 0.0,
+            },
+            connection_status: HashMap::new(),
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        };
+        let filter = TopicFilter {
+            protocols: Some(vec!["grass".to_string()]),
+            ..Default::default()
+        };
+        match HubEvent::Metrics(Arc::new(snapshot)).filtered(&filter) {
+            Some(WsMessage::MetricsUpdate { metrics }) => {
+                assert_eq!(metrics.total_earnings, 3.0);
+                assert_eq!(metrics.earnings_by_protocol.len(), 1);
+                assert!(metrics.earnings_by_protocol.contains_key("grass"));
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        let alert = HubEvent::Alert(AlertDto {
+            id: None,
+            timestamp: Utc::now(),
+            alert_type: "LowEarnings".to_string(),
+            severity: 0.4,
+            message: "low".to_string(),
+            protocol: None,
+            acknowledged: false,
+            resolved_at: None,
+        });
+        let severe_only = TopicFilter {
+            min_severity: Some(0.5),
+            ..Default::default()
+        };
+        assert!(alert.filtered(&severe_only).is_none());
+        assert!(alert.filtered(&TopicFilter::default()).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_hub_fans_out_to_all_sessions() {
+        let hub = WsHub::default();
+        let mut first = hub.subscribe();
+        let mut second = hub.subscribe();
+
+        let delivered = hub.publish(HubEvent::Reallocation(vec![]));
+        assert_eq!(delivered, 2);
+        assert_eq!(first.recv().await.unwrap().topic(), Topic::Reallocations);
+        assert_eq!(second.recv().await.unwrap().topic(), Topic::Reallocations);
+    }
+
+    #[actix_web::test]
+    async fn test_sessions_need_credentials_of_the_owning_account() {
+        use crate::api::auth::insert_api_key;
+        use crate::db::queries;
+        use crate::{
+            EarningsOptimizer, MonitorConfig, OptimizerConfig, ProtocolCoordinator, ReallocationConfig,
+            ReallocationEngine, RealtimeMonitor,
+        };
+        use actix_web::{test, App};
+
+        let pool = crate::db::test_pool().await;
+        let other_account = queries::create_account(&pool, "other", "free").await.unwrap();
+        let (owner_key, _) = insert_api_key(&pool, 1, "owner", None, None, &[]).await.unwrap();
+        let (other_key, _) = insert_api_key(&pool, other_account, "other", None, None, &[]).await.unwrap();
+        let jwt = JwtConfig::new("test-secret", Duration::from_secs(60));
+        let owner = AuthMiddleware::validate_api_key(&pool, &owner_key).await.unwrap();
+        let (token, _) = jwt.issue(&owner).unwrap();
+
+        let state = AppState::new(
+            Arc::new(ProtocolCoordinator::new(10)),
+            Arc::new(EarningsOptimizer::new(OptimizerConfig::default())),
+            Arc::new(ReallocationEngine::new(ReallocationConfig::default())),
+            Arc::new(RealtimeMonitor::new(MonitorConfig::default())),
+        );
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .app_data(web::Data::new(jwt))
+                .app_data(web::Data::new(pool))
+                .route("/ws", web::get().to(ws_handler)),
+        )
+        .await;
+        let handshake = || {
+            test::TestRequest::get()
+                .uri("/ws")
+                .insert_header((header::UPGRADE, "websocket"))
+                .insert_header((header::CONNECTION, "upgrade"))
+                .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+                .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+        };
+
+        // Anonymous clients and keys of other accounts are refused
+        let res = test::call_service(&app, handshake().to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = test::call_service(&app, handshake().insert_header(("X-API-Key", other_key)).to_request()).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let res = test::call_service(&app, handshake().insert_header(("X-API-Key", "dpn_wrong")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = test::call_service(&app, handshake().insert_header(("X-API-Key", owner_key)).to_request()).await;
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert!(res.headers().get(header::SEC_WEBSOCKET_PROTOCOL).is_none());
+
+        // Browsers offer their bearer token as a subprotocol
+        let offered = format!("{}, {}", BEARER_PROTOCOL, token);
+        let req = handshake().insert_header((header::SEC_WEBSOCKET_PROTOCOL, offered)).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(res.headers().get(header::SEC_WEBSOCKET_PROTOCOL).unwrap(), BEARER_PROTOCOL);
+    }
+
+    #[test]
+    fn test_connection_caps() {
+        let hub = WsHub::with_config(WsConfig {
+            max_connections: 3,
+            max_connections_per_key: 2,
+            ..Default::default()
+        });
+
+        let first = hub.admit("key:1".to_string()).unwrap();
+        let _second = hub.admit("key:1".to_string()).unwrap();
+        assert_eq!(hub.admit("key:1".to_string()).unwrap_err(), WsRejection::KeyLimit);
+        let _other = hub.admit("user:1".to_string()).unwrap();
+        assert_eq!(hub.admit("key:2".to_string()).unwrap_err(), WsRejection::ServerFull);
+
+        let stats = hub.stats();
+        assert_eq!((stats.active, stats.clients, stats.accepted, stats.rejected), (3, 2, 3, 2));
+
+        // Closing a session frees its slot
+        drop(first);
+        let _again = hub.admit("key:1".to_string()).unwrap();
+        assert_eq!(hub.stats().active, 3);
+    }
+
+    fn queued(event: HubEvent) -> Outgoing {
+        let subscriptions = HashMap::from([(event.topic(), TopicFilter::default())]);
+        Outgoing::for_session(&event, &subscriptions, WsEncoding::Json).unwrap()
+    }
+
+    fn protocol_update(protocol: &str, earnings: f64) -> Outgoing {
+        queued(HubEvent::Protocol(ProtocolUpdateDto {
+            timestamp: Utc::now(),
+            protocol: protocol.to_string(),
+            earnings_per_hour: earnings,
+            allocation_percent: 0.0,
+            connected: true,
+        }))
+    }
+
+    #[test]
+    fn test_coalesce_keeps_alerts_and_newest_updates() {
+        let alert = queued(HubEvent::Alert(AlertDto {
+            id: Some(1),
+            timestamp: Utc::now(),
+            alert_type: "LowEarnings".to_string(),
+            severity: 0.4,
+            message: "low".to_string(),
+            protocol: None,
+            acknowledged: false,
+            resolved_at: None,
+        }));
+
+        let kept = coalesce(VecDeque::from([
+            protocol_update("storj", 1.0),
+            alert.clone(),
+            protocol_update("grass", 2.0),
+            protocol_update("storj", 3.0),
+            Outgoing {
+                topic: Topic::Reallocations,
+                message: WsMessage::ReallocationNotification { changes: vec![] },
+                encoding: WsEncoding::Json,
+            },
+            alert,
+        ]));
+        let summary: Vec<_> = kept
+            .iter()
+            .map(|item| match &item.message {
+                WsMessage::ProtocolUpdate { update } => format!("{}={}", update.protocol, update.earnings_per_hour),
+                _ => item.topic.to_string(),
+            })
+            .collect();
+        assert_eq!(summary, ["alerts", "grass=2", "storj=3", "reallocations", "alerts"]);
+    }
+
+    #[test]
+    fn test_slow_consumer_is_measured_on_the_outbox() {
+        let config = |slow_consumer| WsConfig {
+            max_pending_updates: 2,
+            slow_consumer,
+            ..Default::default()
+        };
+
+        // Updates the writer has sent no longer count against the limit
+        let outbox = Outbox::default();
+        let drop = config(SlowConsumerPolicy::Drop);
+        assert_eq!(outbox.push(protocol_update("storj", 1.0), &drop), Backpressure::Queued);
+        assert_eq!(outbox.push(protocol_update("storj", 2.0), &drop), Backpressure::Queued);
+        outbox.pop().unwrap();
+        assert_eq!(outbox.push(protocol_update("storj", 3.0), &drop), Backpressure::Queued);
+        assert_eq!(outbox.push(protocol_update("grass", 4.0), &drop), Backpressure::Dropped(2));
+        assert_eq!(outbox.queue.lock().unwrap().len(), 1);
+
+        let outbox = Outbox::default();
+        let coalesce = config(SlowConsumerPolicy::Coalesce);
+        outbox.push(protocol_update("storj", 1.0), &coalesce);
+        outbox.push(protocol_update("grass", 2.0), &coalesce);
+        assert_eq!(outbox.push(protocol_update("storj", 3.0), &coalesce), Backpressure::Coalesced(1));
+        assert_eq!(outbox.queue.lock().unwrap().len(), 2);
+
+        let outbox = Outbox::default();
+        let disconnect = config(SlowConsumerPolicy::Disconnect);
+        outbox.push(protocol_update("storj", 1.0), &disconnect);
+        outbox.push(protocol_update("storj", 2.0), &disconnect);
+        assert_eq!(outbox.push(protocol_update("storj", 3.0), &disconnect), Backpressure::Disconnect);
+    }
+
+    #[test]
+    fn test_ws_config_validation() {
+        assert!(WsConfig::default().validate().is_ok());
+        let config = WsConfig {
+            max_connections: 5,
+            max_connections_per_key: 10,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        let config: WsConfig = toml::from_str("slow_consumer = \"drop\"").unwrap();
+        assert_eq!(config.slow_consumer, SlowConsumerPolicy::Drop);
+        assert_eq!(config.max_pending_updates, 64);
+    }
 }
@@ -0,0 +1,315 @@
+/// Database Backups
+///
+/// Takes online SQLite backups with `VACUUM INTO` while the orchestrator keeps
+/// running, keeps the newest few in a local directory and optionally uploads
+/// each one to an S3-compatible bucket. Restoring replaces the database file
+/// and must be done with the server stopped (`depin-orcha backup restore`).
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::storage::{S3Config, StorageError};
+
+/// File name prefix of backups
+const BACKUP_PREFIX: &str = "depin_orcha-";
+
+/// Timestamp format embedded in backup file names
+const BACKUP_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Backup settings (`[backup]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Take backups on a schedule
+    pub enabled: bool,
+    /// Hours between scheduled backups
+    pub interval_hours: u64,
+    /// Directory backups are written to
+    pub directory: PathBuf,
+    /// Local backups kept; older ones are deleted after each backup
+    pub keep: usize,
+    /// Bucket every backup is also uploaded to (requires the `s3-storage` feature)
+    pub s3: Option<S3Config>,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            directory: PathBuf::from("backups"),
+            keep: 7,
+            s3: None,
+        }
+    }
+}
+
+impl BackupConfig {
+    /// Validate configuration values
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_hours == 0 {
+            return Err("interval_hours must be at least 1".to_string());
+        }
+        if self.keep == 0 {
+            return Err("keep must be at least 1".to_string());
+        }
+        if let Some(s3) = &self.s3 {
+            s3.check_supported().map_err(|e| format!("s3: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// BACKUPS
+// ============================================================================
+
+/// Backup errors
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Backup I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Backup storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Invalid backup: {0}")]
+    Invalid(String),
+}
+
+/// A backup file in the local backup directory
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Back up the live database, upload it if a bucket is configured, and
+/// prune old local backups
+pub async fn create_backup(pool: &SqlitePool, config: &BackupConfig) -> Result<BackupInfo, BackupError> {
+    tokio::fs::create_dir_all(&config.directory).await?;
+
+    let created_at = Utc::now();
+    let name = format!("{}{}.db", BACKUP_PREFIX, created_at.format(BACKUP_TIME_FORMAT));
+    let path = config.directory.join(&name);
+
+    // A consistent snapshot, taken without blocking writers for long
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().into_owned())
+        .execute(pool)
+        .await?;
+    let size_bytes = tokio::fs::metadata(&path).await?.len();
+
+    if let Some(s3) = &config.s3 {
+        s3.upload(&name, tokio::fs::read(&path).await?).await?;
+    }
+
+    for old in list_backups(&config.directory).await?.iter().skip(config.keep) {
+        tokio::fs::remove_file(config.directory.join(&old.name)).await?;
+    }
+
+    Ok(BackupInfo {
+        name,
+        size_bytes,
+        created_at,
+    })
+}
+
+/// Backups in `directory`, newest first
+pub async fn list_backups(directory: &Path) -> Result<Vec<BackupInfo>, BackupError> {
+    let mut backups = Vec::new();
+    let mut entries = match tokio::fs::read_dir(directory).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(backups),
+        Err(e) => return Err(e.into()),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(created_at) = backup_time(&name) {
+            backups.push(BackupInfo {
+                size_bytes: entry.metadata().await?.len(),
+                name,
+                created_at,
+            });
+        }
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+/// Download backup `name` from the bucket into `directory`
+pub async fn fetch_backup(s3: &S3Config, name: &str, directory: &Path) -> Result<PathBuf, BackupError> {
+    if backup_time(name).is_none() {
+        return Err(BackupError::Invalid(format!("{} is not a backup name", name)));
+    }
+    let contents = s3.download(name).await?;
+    tokio::fs::create_dir_all(directory).await?;
+    let path = directory.join(name);
+    tokio::fs::write(&path, contents).await?;
+    Ok(path)
+}
+
+/// Replace the database at `database_url` with `backup`
+///
+/// The server must be stopped. The backup is integrity-checked first and the
+/// current database is kept as `<file>.pre-restore`. Returns the restored path.
+pub async fn restore_backup(backup: &Path, database_url: &str) -> Result<PathBuf, BackupError> {
+    verify_backup(backup).await?;
+
+    let target = database_path(database_url)?;
+    if tokio::fs::try_exists(&target).await? {
+        let mut kept = target.clone().into_os_string();
+        kept.push(".pre-restore");
+        tokio::fs::rename(&target, &kept).await?;
+    }
+    // Stale WAL files would be replayed over the restored database
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = target.clone().into_os_string();
+        sidecar.push(suffix);
+        if let Err(e) = tokio::fs::remove_file(&sidecar).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(e.into());
+            }
+        }
+    }
+
+    tokio::fs::copy(backup, &target).await?;
+    Ok(target)
+}
+
+/// Check that `backup` is an intact SQLite database
+async fn verify_backup(backup: &Path) -> Result<(), BackupError> {
+    if !tokio::fs::try_exists(backup).await? {
+        return Err(BackupError::Invalid(format!("{} does not exist", backup.display())));
+    }
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::new().filename(backup).read_only(true))
+        .await?;
+    let result: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(&pool)
+        .await?;
+    pool.close().await;
+
+    if result == "ok" {
+        Ok(())
+    } else {
+        Err(BackupError::Invalid(format!("{} failed integrity check: {}", backup.display(), result)))
+    }
+}
+
+/// Database file named by a SQLite URL (`depin_orcha.db`, `sqlite://data/orcha.db?mode=rwc`)
+pub fn database_path(database_url: &str) -> Result<PathBuf, BackupError> {
+    let path = database_url
+        .strip_prefix("sqlite://")
+        .or_else(|| database_url.strip_prefix("sqlite:"))
+        .unwrap_or(database_url);
+    let path = path.split('?').next().unwrap_or_default();
+
+    if path.is_empty() || path == ":memory:" {
+        return Err(BackupError::Invalid(format!("{} is not a database file", database_url)));
+    }
+    Ok(PathBuf::from(path))
+}
+
+/// Creation time encoded in a backup file name
+fn backup_time(name: &str) -> Option<DateTime<Utc>> {
+    let stamp = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(".db")?;
+    NaiveDateTime::parse_from_str(stamp, BACKUP_TIME_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{init_pool, queries, DbConfig};
+
+    #[test]
+    fn test_database_path() {
+        assert_eq!(database_path("depin_orcha.db").unwrap(), PathBuf::from("depin_orcha.db"));
+        assert_eq!(
+            database_path("sqlite://data/orcha.db?mode=rwc").unwrap(),
+            PathBuf::from("data/orcha.db")
+        );
+        assert!(database_path("sqlite::memory:").is_err());
+        assert!(backup_time("depin_orcha-20261015T120000Z.db").is_some());
+        assert!(backup_time("notes.txt").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("orcha-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let database_url = dir.join("orcha.db").to_string_lossy().into_owned();
+        let pool = init_pool(DbConfig {
+            database_url: database_url.clone(),
+            max_connections: 1,
+            min_connections: 1,
+            ..DbConfig::default()
+        })
+        .await
+        .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+        queries::store_metrics(&pool, 1, Utc::now(), 1.5, 0.0, 0.0, 0.0, 0.0).await.unwrap();
+
+        let config = BackupConfig {
+            directory: dir.join("backups"),
+            keep: 1,
+            ..BackupConfig::default()
+        };
+        let backup = create_backup(&pool, &config).await.unwrap();
+        assert!(backup.size_bytes > 0);
+        let listed = list_backups(&config.directory).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, backup.name);
+
+        // Changes after the backup are undone by the restore
+        queries::store_metrics(&pool, 1, Utc::now(), 2.5, 0.0, 0.0, 0.0, 0.0).await.unwrap();
+        pool.close().await;
+
+        let restored = restore_backup(&config.directory.join(&backup.name), &database_url)
+            .await
+            .unwrap();
+        assert_eq!(restored, PathBuf::from(&database_url));
+        assert!(dir.join("orcha.db.pre-restore").exists());
+
+        let pool = init_pool(DbConfig {
+            database_url,
+            max_connections: 1,
+            min_connections: 1,
+            ..DbConfig::default()
+        })
+        .await
+        .unwrap();
+        let history = queries::get_metrics_history(&pool, 1, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].total_earnings_per_hour, 1.5);
+        pool.close().await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_missing_backup() {
+        let missing = std::env::temp_dir().join(format!("orcha-missing-{}.db", uuid::Uuid::new_v4()));
+        assert!(matches!(
+            restore_backup(&missing, "orcha.db").await,
+            Err(BackupError::Invalid(_))
+        ));
+    }
+}
@@ -10,38 +10,60 @@
 //! ```text
 //! main.rs
 //!   ├─> Load Configuration (env vars + .env file)
-//!   ├─> Initialize Database (SQLite pool + schema)
-//!   ├─> Create Orchestration Engine (ProtocolCoordinator)
-//!   ├─> Start HTTP Server (Actix-web with routes)
-//!   ├─> Start WebSocket Server (Real-time streaming)
-//!   ├─> Start Background Schedulers (Optimization, Cleanup)
+//!   ├─> Initialize Database (SQLite pool + migrations)
+//!   ├─> Create Protocol Adapters (config file + credentials vault)
+//!   ├─> Start Orchestrator (depin_orcha::Orchestrator::builder)
+//!   │     ├─> Orchestration Engine (ProtocolCoordinator)
+//!   │     ├─> Background Schedulers (Optimization, Cleanup)
+//!   │     └─> HTTP + WebSocket Server (Actix-web with routes)
 //!   └─> Handle Graceful Shutdown (SIGTERM, SIGINT)
 //! ```
 //!
+//! ## Subcommands
+//! Running without a subcommand (or with `serve`) starts the orchestrator.
+//! `init`, `migrate [--status]`, `status`, `reallocate --plan <file>`,
+//! `protocols list`, `keys create`, `users create`, and
+//! `backup create|list|restore` provide headless control; see
+//! `depin-orcha --help`.
+//!
+//! ## Configuration
+//! Settings are read from `ORCHA_CONFIG` (default: `orcha.toml`, falling back
+//! to `config/default.toml`) and then overridden by the environment variables
+//! below. The effective configuration is served at `GET /api/v1/config`.
+//!
 //! ## Environment Variables
 //! - `API_HOST`: API server host (default: "127.0.0.1")
 //! - `API_PORT`: API server port (default: "8080")
 //! - `API_WORKERS`: Number of worker threads (default: 4)
 //! - `API_REQUEST_TIMEOUT`: Request timeout in seconds (default: 30)
+//! - `API_SHUTDOWN_TIMEOUT`: Seconds to wait for in-flight work on shutdown (default: 30)
+//! - `ORCHA_ACCOUNT_ID`: Account the orchestrator runs for (default: 1)
+//! - `ORCHA_CONFIG`: Config file (default: "orcha.toml", then "config/default.toml")
 //! - `DATABASE_URL`: SQLite database path (default: "depin_orcha.db")
 //! - `DB_MAX_CONNECTIONS`: Max pool connections (default: 10)
 //! - `DB_MIN_CONNECTIONS`: Min pool connections (default: 2)
+//! - `ORCHA_MASTER_KEY` / `ORCHA_MASTER_KEY_FILE`: Credentials vault key (hex, 32 bytes)
 //! - `LOG_LEVEL`: Logging level (default: "info")
 //! - `RUST_LOG`: Rust logging configuration (overrides LOG_LEVEL)
 
-use actix_web::{middleware, web, App, HttpServer};
+use clap::Parser;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::signal;
-use tokio::sync::Mutex;
 
 // Import our modules
-use depin_orcha::api::{routes::configure_routes, middleware::RequestIdMiddleware, websocket, ApiConfig, AppState};
-use depin_orcha::db::{create_schema, init_pool, DbConfig};
-use depin_orcha::{
-    ProtocolCoordinator, EarningsOptimizer, OptimizerConfig,
-    ReallocationEngine, ReallocationConfig,
-    RealtimeMonitor, MonitorConfig,
-};
+use depin_orcha::cli::{Cli, Command};
+use depin_orcha::cgroups::{is_compute, CgroupAdapter, CgroupEnforcer};
+use depin_orcha::containers::{ContainerManager, ContainerizedAdapter};
+use depin_orcha::db::init_pool;
+use depin_orcha::disk_quotas::{DiskQuotaAdapter, DiskQuotaManager};
+use depin_orcha::protocols::http::HttpClientFactory;
+use depin_orcha::protocols::ProtocolAdapter;
+use depin_orcha::services::{ServiceAdapter, ServiceManager};
+use depin_orcha::config::Config;
+use depin_orcha::secrets::{self, SecretsVault};
+use depin_orcha::{AdapterFactory, Orchestrator, ProtocolInstance};
+
 
 /// Main application entry point
 #[actix_web::main]
@@ -49,14 +71,49 @@ async fn main() -> std::io::Result<()> {
     // Load environment variables from .env file (if present)
     dotenv::dotenv().ok();
 
-    // Initialize logging
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    // Initialize logging; `log` records are bridged so they pick up the
+    // request span (and its request ID) they were emitted in
+    init_tracing();
+
+    let cli = Cli::parse();
+    match &cli.command {
+        None | Some(Command::Serve) => run_server().await,
+        Some(command) => depin_orcha::cli::run(&cli, command).await.map_err(|e| {
+            eprintln!("Error: {:#}", e);
+            std::io::Error::other(e.to_string())
+        }),
+    }
+}
+
+/// Install the tracing subscriber (`RUST_LOG` filter, default `info`)
+///
+/// `log` records from actix and the library are forwarded into tracing.
+fn init_tracing() {
+    use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
 
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(env_filter)
+        .init();
+}
+
+/// Run the orchestrator: HTTP/WebSocket server plus background schedulers
+async fn run_server() -> std::io::Result<()> {
     log::info!("🚀 Starting DePIN-Orcha Orchestration System v1.0.0");
     log::info!("📅 Date: {}", chrono::Utc::now().to_rfc3339());
 
-    // Step 1: Load API Configuration
-    let api_config = load_api_config();
+    // Step 1: Load Configuration (config file, then environment overrides)
+    let config = Config::load().map_err(|e| {
+        log::error!("❌ {}", e);
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+    })?;
+    match &config.source {
+        Some(path) => log::info!("✅ Configuration loaded from {}", path.display()),
+        None => log::info!("✅ Configuration loaded from defaults and environment"),
+    }
+    let api_config = config.server.clone();
     log::info!("✅ API Configuration loaded:");
     log::info!("   Host: {}", api_config.host);
     log::info!("   Port: {}", api_config.port);
@@ -64,100 +121,152 @@ async fn main() -> std::io::Result<()> {
     log::info!("   Request Timeout: {}s", api_config.request_timeout);
 
     // Step 2: Load Database Configuration
-    let db_config = load_db_config();
+    let db_config = config.database.clone();
     log::info!("✅ Database Configuration loaded:");
     log::info!("   URL: {}", db_config.database_url);
     log::info!("   Max Connections: {}", db_config.max_connections);
     log::info!("   Min Connections: {}", db_config.min_connections);
 
-    // Step 3: Initialize Database Connection Pool
+    // Step 3: Initialize Database Connection Pool and apply pending migrations
     log::info!("🔧 Initializing database connection pool...");
     let db_pool = init_pool(db_config.clone())
         .await
         .expect("Failed to initialize database pool");
     log::info!("✅ Database pool initialized with {} connections", db_config.max_connections);
 
-    // Step 4: Create Database Schema
-    log::info!("🔧 Creating database schema...");
-    create_schema(&db_pool)
-        .await
-        .expect("Failed to create database schema");
-    log::info!("✅ Database schema created successfully");
-
-    // Step 5: Initialize Protocol Coordinator (Orchestration Engine)
-    log::info!("🔧 Initializing Protocol Coordinator...");
-    // Step 6: Create orchestration components
-    let coordinator = Arc::new(ProtocolCoordinator::new(1000)); // Keep 1000 history entries
-    log::info!("✅ Protocol Coordinator initialized");
-
-    let optimizer_config = OptimizerConfig::default();
-    let optimizer = Arc::new(Mutex::new(EarningsOptimizer::new(optimizer_config)));
-    log::info!("✅ Earnings Optimizer initialized");
-
-    let reallocation_config = ReallocationConfig::default();
-    let reallocation = Arc::new(ReallocationEngine::new(reallocation_config));
-    log::info!("✅ Reallocation Engine initialized");
-
-    let monitor_config = MonitorConfig::default();
-    let monitor = Arc::new(RealtimeMonitor::new(monitor_config));
-    log::info!("✅ Realtime Monitor initialized");
-
-    // Step 7: Create Application State (thread-safe, Arc-wrapped)
-    let app_state = web::Data::new(AppState {
-        coordinator: coordinator.clone(),
-        optimizer: optimizer.clone(),
-        reallocation: reallocation.clone(),
-        monitor: monitor.clone(),
+    // The orchestrator records its data under the configured account
+    match depin_orcha::db::queries::get_account(&db_pool, api_config.account_id).await {
+        Ok(Some(account)) => log::info!("✅ Running for account {} ({})", account.id, account.name),
+        Ok(None) => {
+            let message = format!("Account {} does not exist", api_config.account_id);
+            log::error!("❌ {}", message);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, message));
+        }
+        Err(e) => {
+            log::error!("❌ Failed to load account {}: {}", api_config.account_id, e);
+            return Err(std::io::Error::other(e.to_string()));
+        }
+    }
+
+    // On a fresh database, generate the admin key the protected API needs
+    match depin_orcha::bootstrap::bootstrap(&db_pool, api_config.account_id).await {
+        Ok(Some(key)) => {
+            log::warn!("🔑 No API keys found; generated bootstrap admin key #{} (printed to stdout)", key.id);
+            depin_orcha::bootstrap::print_bootstrap_key(&key);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            log::error!("❌ Failed to bootstrap admin key: {}", e);
+            return Err(std::io::Error::other(e));
+        }
+    }
+
+    // Step 4: Unlock the credentials vault, if a master key is configured
+    let vault = SecretsVault::from_env().map_err(|e| {
+        log::error!("❌ {}", e);
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+    })?;
+    let credentials = match &vault {
+        Some(vault) => match vault.load_all(&db_pool, api_config.account_id).await {
+            Ok(credentials) => {
+                log::info!("🔐 Credentials vault unlocked ({} protocols)", credentials.len());
+                credentials
+            }
+            Err(e) => {
+                log::error!("❌ Failed to load protocol credentials: {}", e);
+                HashMap::new()
+            }
+        },
+        None => {
+            log::info!("🔐 Credentials vault disabled (set {})", secrets::MASTER_KEY_ENV);
+            HashMap::new()
+        }
+    };
+
+    // Step 5: Set up host-level protocol controls
+    let containers = if config.containers.enabled {
+        match ContainerManager::connect(config.containers.clone()) {
+            Ok(manager) => {
+                log::info!("✅ Managing {} protocol containers", config.containers.protocols.len());
+                Some(Arc::new(manager))
+            }
+            Err(e) => {
+                log::error!("❌ Failed to connect to Docker: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let cgroups = config.cgroups.enabled.then(|| {
+        log::info!("✅ Enforcing allocations on {} protocol cgroups", config.cgroups.protocols.len());
+        Arc::new(CgroupEnforcer::new(config.cgroups.clone()))
+    });
+    let disks = config.disk_quotas.enabled.then(|| {
+        log::info!("✅ Enforcing disk quotas of {} storage protocols", config.disk_quotas.protocols.len());
+        Arc::new(DiskQuotaManager::new(config.disk_quotas.clone()))
     });
-    log::info!("✅ Application state created");
-
-    // Step 8: Start Background Schedulers
-    log::info!("🔧 Starting background schedulers...");
-    let scheduler_config = depin_orcha::scheduler::SchedulerConfig::from_env();
-    depin_orcha::scheduler::start_schedulers(
-        coordinator.clone(),
-        db_pool.clone(),
-        scheduler_config,
+    let services = config.services.enabled.then(|| {
+        if config.services.dry_run {
+            log::info!("   systemd units in dry-run mode: commands are logged, not run");
+        }
+        log::info!("✅ Managing {} protocol systemd units", config.services.units.len());
+        Arc::new(ServiceManager::new(config.services.clone()))
+    });
+
+    // Step 6: Create protocol adapters
+    let http = HttpClientFactory::new(config.http.clone()).map_err(|e| {
+        log::error!("❌ {}", e);
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+    })?;
+    if let Some(proxy) = &config.http.proxy {
+        log::info!("   Protocol API requests go through proxy {}", redact_proxy(proxy));
+    }
+    let mut builder = Orchestrator::builder().with_db(db_pool);
+    // Kept alive for the whole run: adapters from plugin libraries borrow its code
+    let (_adapter_factory, adapters) = create_protocol_adapters(
+        &config.raw,
+        &credentials,
+        http,
+        ProcessControl {
+            containers: containers.as_ref(),
+            services: services.as_ref(),
+            cgroups: cgroups.as_ref(),
+            disks: disks.as_ref(),
+        },
     );
-    log::info!("✅ Background schedulers started successfully");
-
-    // Step 8: Build and Start HTTP Server
-    let bind_address = format!("{}:{}", api_config.host, api_config.port);
-    log::info!("🌐 Starting HTTP server at http://{}", bind_address);
-
-    let db_pool_arc = Arc::new(db_pool.clone());
-
-    let server = HttpServer::new(move || {
-        App::new()
-            // Add application state
-            .app_data(app_state.clone())
-            .app_data(web::Data::new(db_pool.clone()))
-            // Add middleware
-            .wrap(middleware::Logger::default())
-            .wrap(middleware::Compress::default())
-            // Configure API routes with database pool
-            .configure(|cfg| configure_routes(cfg, db_pool_arc.clone()))
-            // Add WebSocket endpoint
-            .route("/ws", web::get().to(websocket::ws_handler))
-            // Add health check at root
-            .route("/", web::get().to(|| async { "DePIN-Orcha Orchestration System v1.0.0" }))
-    })
-    .workers(api_config.workers)
-    .bind(&bind_address)?
-    .run();
-
-    log::info!("✅ HTTP server started successfully");
-    log::info!("📡 WebSocket endpoint: ws://{}/ws", bind_address);
-    log::info!("🏥 Health check: http://{}/api/v1/health", bind_address);
-    log::info!("📊 Dashboard: http://{}/api/v1/dashboard", bind_address);
-
-    // Step 9: Handle Graceful Shutdown
-    let server_handle = server.handle();
+    drop(credentials);
+    for instance in adapters {
+        builder = builder.with_instance(instance);
+    }
+    if let Some(containers) = containers {
+        builder = builder.with_containers(containers);
+    }
+    if let Some(services) = services {
+        builder = builder.with_services(services);
+    }
+    if let Some(vault) = vault {
+        builder = builder.with_vault(vault);
+    }
+
+    // Step 7: Start the orchestration engine, background schedulers and HTTP server
+    let orchestrator = builder
+        .with_config(config)
+        .with_http_server()
+        .start()
+        .await
+        .map_err(|e| {
+            log::error!("❌ {}", e);
+            std::io::Error::other(e.to_string())
+        })?;
 
+    // Step 8: Handle Graceful Shutdown
+    let signal_shutdown = orchestrator.shutdown().clone();
     tokio::spawn(async move {
         shutdown_signal().await;
         log::warn!("⚠️  Shutdown signal received, initiating graceful shutdown...");
-        server_handle.stop(true).await;
+        // Stops the server, closes WebSocket sessions and stops background loops
+        signal_shutdown.trigger();
     });
 
     // Run server until stopped
@@ -165,49 +274,97 @@ async fn main() -> std::io::Result<()> {
     log::info!("   Press Ctrl+C to shutdown gracefully");
     log::info!("");
 
-    server.await?;
-
-    log::info!("✅ Server shutdown complete");
+    orchestrator.wait().await?;
     log::info!("👋 Goodbye!");
 
     Ok(())
 }
 
-/// Load API configuration from environment variables
-fn load_api_config() -> ApiConfig {
-    ApiConfig {
-        host: std::env::var("API_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-        port: std::env::var("API_PORT")
-            .unwrap_or_else(|_| "8080".to_string())
-            .parse()
-            .expect("API_PORT must be a valid number"),
-        workers: std::env::var("API_WORKERS")
-            .unwrap_or_else(|_| "4".to_string())
-            .parse()
-            .expect("API_WORKERS must be a valid number"),
-        request_timeout: std::env::var("API_REQUEST_TIMEOUT")
-            .unwrap_or_else(|_| "30".to_string())
-            .parse()
-            .expect("API_REQUEST_TIMEOUT must be a valid number"),
+/// Build protocol adapters from the config file
+///
+/// Vault `credentials` are merged into the protocol sections first and the
+/// adapters send their API requests through clients from `http`; adapters of
+/// protocols with a managed container, systemd unit, cgroup, or disk are
+/// wrapped to drive it. The orchestrator connects them on start.
+fn create_protocol_adapters(
+    config: &toml::Table,
+    credentials: &HashMap<String, secrets::Credentials>,
+    http: HttpClientFactory,
+    control: ProcessControl<'_>,
+) -> (AdapterFactory, Vec<ProtocolInstance>) {
+    let mut factory = AdapterFactory::with_builtin();
+    factory.set_http(http);
+
+    #[cfg(feature = "dynamic-adapters")]
+    for library in config
+        .get("plugins")
+        .and_then(|p| p.get("libraries"))
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|l| l.as_str())
+    {
+        // SAFETY: plugin libraries are operator-configured and must be built
+        // against this version of depin-orcha.
+        if let Err(e) = unsafe { factory.load_library(std::path::Path::new(library)) } {
+            log::error!("❌ Failed to load adapter library: {}", e);
+        }
+    }
+
+    let mut protocols = config
+        .get("protocols")
+        .and_then(|p| p.as_table())
+        .cloned()
+        .unwrap_or_default();
+
+    for field in secrets::plaintext_secret_fields(&protocols) {
+        log::warn!(
+            "⚠️  protocols.{} is stored in plaintext; move it to the vault via /api/v1/admin/credentials",
+            field
+        );
     }
+    secrets::apply_credentials(&mut protocols, credentials);
+
+    let mut created = Vec::new();
+    match factory.create_from_config(&protocols) {
+        Ok(instances) => {
+            for mut instance in instances {
+                let id = instance.id.clone();
+                let mut adapter: Box<dyn ProtocolAdapter> = instance.adapter;
+                if let Some(containers) = control.containers.filter(|c| c.manages(&id)) {
+                    adapter = Box::new(ContainerizedAdapter::new(adapter, containers.clone(), &id));
+                }
+                if let Some(services) = control.services.filter(|s| s.manages(&id)) {
+                    adapter = Box::new(ServiceAdapter::new(adapter, services.clone(), &id));
+                }
+                if let Some(cgroups) = control.cgroups.filter(|c| c.manages(&id)) {
+                    if is_compute(&adapter.resource_profile()) {
+                        adapter = Box::new(CgroupAdapter::new(adapter, cgroups.clone(), &id));
+                    } else {
+                        log::warn!("⚠️  cgroups.protocols.{} ignored: not a compute protocol", id);
+                    }
+                }
+                if let Some(disks) = control.disks.filter(|d| d.manages(&id)) {
+                    adapter = Box::new(DiskQuotaAdapter::new(adapter, disks.clone(), &id));
+                }
+                instance.adapter = adapter;
+                created.push(instance);
+            }
+        }
+        Err(e) => log::error!("❌ Invalid protocol configuration: {}", e),
+    }
+
+    (factory, created)
 }
 
-/// Load database configuration from environment variables
-fn load_db_config() -> DbConfig {
-    DbConfig {
-        database_url: std::env::var("DATABASE_URL").unwrap_or_else(|_| "depin_orcha.db".to_string()),
-        max_connections: std::env::var("DB_MAX_CONNECTIONS")
-            .unwrap_or_else(|_| "10".to_string())
-            .parse()
-            .expect("DB_MAX_CONNECTIONS must be a valid number"),
-        min_connections: std::env::var("DB_MIN_CONNECTIONS")
-            .unwrap_or_else(|_| "2".to_string())
-            .parse()
-            .expect("DB_MIN_CONNECTIONS must be a valid number"),
-        connect_timeout: std::env::var("DB_CONNECT_TIMEOUT")
-            .unwrap_or_else(|_| "5".to_string())
-            .parse()
-            .expect("DB_CONNECT_TIMEOUT must be a valid number"),
+/// `proxy` with any password in its URL masked, for logging
+fn redact_proxy(proxy: &str) -> String {
+    match reqwest::Url::parse(proxy) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some("***"));
+            url.to_string()
+        }
+        _ => proxy.to_string(),
     }
 }
 
@@ -240,57 +397,10 @@ async fn shutdown_signal() {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_api_config_defaults() {
-        // Clear env vars for test
-        std::env::remove_var("API_HOST");
-        std::env::remove_var("API_PORT");
-        std::env::remove_var("API_WORKERS");
-        std::env::remove_var("API_REQUEST_TIMEOUT");
-
-        let config = load_api_config();
-        assert_eq!(config.host, "127.0.0.1");
-        assert_eq!(config.port, 8080);
-        assert_eq!(config.workers, 4);
-        assert_eq!(config.request_timeout, 30);
-    }
-
-    #[test]
-    fn test_db_config_defaults() {
-        // Clear env vars for test
-        std::env::remove_var("DATABASE_URL");
-        std::env::remove_var("DB_MAX_CONNECTIONS");
-        std::env::remove_var("DB_MIN_CONNECTIONS");
-        std::env::remove_var("DB_CONNECT_TIMEOUT");
-
-        let config = load_db_config();
-        assert_eq!(config.database_url, "depin_orcha.db");
-        assert_eq!(config.max_connections, 10);
-        assert_eq!(config.min_connections, 2);
-        assert_eq!(config.connect_timeout, 5);
-    }
-
-    #[test]
-    fn test_api_config_from_env() {
-        std::env::set_var("API_HOST", "0.0.0.0");
-        std::env::set_var("API_PORT", "3000");
-        std::env::set_var("API_WORKERS", "8");
-        std::env::set_var("API_REQUEST_TIMEOUT", "60");
-
-        let config = load_api_config();
-        assert_eq!(config.host, "0.0.0.0");
-        assert_eq!(config.port, 3000);
-        assert_eq!(config.workers, 8);
-        assert_eq!(config.request_timeout, 60);
-
-        // Cleanup
-        std::env::remove_var("API_HOST");
-        std::env::remove_var("API_PORT");
-        std::env::remove_var("API_WORKERS");
-        std::env::remove_var("API_REQUEST_TIMEOUT");
-    }
+/// Host-level controls wrapped around the adapters of the protocols they manage
+struct ProcessControl<'a> {
+    containers: Option<&'a Arc<ContainerManager>>,
+    services: Option<&'a Arc<ServiceManager>>,
+    cgroups: Option<&'a Arc<CgroupEnforcer>>,
+    disks: Option<&'a Arc<DiskQuotaManager>>,
 }
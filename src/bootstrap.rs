@@ -0,0 +1,108 @@
+/// First-Run Bootstrap
+///
+/// The protected API needs an API key, and a fresh database has none. On the
+/// first startup with an empty `api_keys` table (or explicitly via
+/// `depin-orcha init`) an admin key is generated for the orchestrator's
+/// account and printed once. The database is then marked initialized, so
+/// revoking every key later never silently mints a new admin key.
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::api::auth::insert_api_key;
+
+/// `system_state` key recording when the system was initialized
+const INITIALIZED_KEY: &str = "initialized_at";
+
+/// Name of the generated key
+pub const BOOTSTRAP_KEY_NAME: &str = "Bootstrap Admin Key";
+
+/// Permissions of the generated key
+pub const BOOTSTRAP_PERMISSIONS: [&str; 4] = ["read", "write", "admin", "delete"];
+
+/// A freshly generated bootstrap key
+#[derive(Debug, Clone)]
+pub struct BootstrapKey {
+    pub id: i64,
+    /// Plaintext key; only available now
+    pub api_key: String,
+}
+
+/// Generate the bootstrap admin key unless the system is already initialized
+///
+/// Databases that already hold API keys are marked initialized without a new
+/// key. Returns the generated key, if any.
+pub async fn bootstrap(pool: &SqlitePool, account_id: i64) -> Result<Option<BootstrapKey>, String> {
+    let initialized = sqlx::query_scalar!("SELECT value FROM system_state WHERE key = ?", INITIALIZED_KEY)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if initialized.is_some() {
+        return Ok(None);
+    }
+
+    let key_count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!: i64" FROM api_keys"#)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let key = if key_count == 0 {
+        let permissions: Vec<String> = BOOTSTRAP_PERMISSIONS.iter().map(|p| p.to_string()).collect();
+        let (api_key, id) = insert_api_key(pool, account_id, BOOTSTRAP_KEY_NAME, None, None, &permissions).await?;
+        Some(BootstrapKey { id, api_key })
+    } else {
+        None
+    };
+
+    let now = Utc::now().to_rfc3339();
+    sqlx::query!(
+        "INSERT OR IGNORE INTO system_state (key, value, updated_at) VALUES (?, ?, ?)",
+        INITIALIZED_KEY,
+        now,
+        now
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(key)
+}
+
+/// Print a bootstrap key to stdout (never to the logs)
+pub fn print_bootstrap_key(key: &BootstrapKey) {
+    println!("============================================================");
+    println!("Bootstrap admin API key created (#{})", key.id);
+    println!("API Key: {}", key.api_key);
+    println!("Permissions: {}", BOOTSTRAP_PERMISSIONS.join(", "));
+    println!();
+    println!("Send it as `X-API-Key` or exchange it at POST /api/v1/auth/token.");
+    println!("Store this key now; it cannot be shown again.");
+    println!("============================================================");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::middleware::{AuthMiddleware, Principal};
+
+    #[tokio::test]
+    async fn test_bootstrap_runs_once() {
+        let pool = crate::db::test_pool().await;
+        let key = bootstrap(&pool, 1).await.unwrap().unwrap();
+        let info = AuthMiddleware::validate_api_key(&pool, &key.api_key).await.unwrap();
+        assert_eq!((info.principal, info.account_id), (Principal::ApiKey(key.id), 1));
+        assert_eq!(info.permissions, BOOTSTRAP_PERMISSIONS);
+
+        // Revoking every key does not mint a new one
+        sqlx::query("DELETE FROM api_keys").execute(&pool).await.unwrap();
+        assert!(bootstrap(&pool, 1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_existing_keys_skip_bootstrap() {
+        let pool = crate::db::test_pool().await;
+        insert_api_key(&pool, 1, "ops", None, None, &[]).await.unwrap();
+        assert!(bootstrap(&pool, 1).await.unwrap().is_none());
+
+        sqlx::query("DELETE FROM api_keys").execute(&pool).await.unwrap();
+        assert!(bootstrap(&pool, 1).await.unwrap().is_none());
+    }
+}
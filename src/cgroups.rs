@@ -0,0 +1,505 @@
+/// Protocol cgroups
+///
+/// Compute-heavy protocols (Golem, Akash, io.net) are only throttled by their
+/// own configuration unless the host enforces it. Protocols mapped to a cgroup
+/// v2 group in `[cgroups]` have every allocation written to that group's
+/// `cpu.max` and `memory.max`, and optionally pinned to a subset of a
+/// configured CPU set through `cpuset.cpus`. The group is the one the
+/// protocol's process already runs in, e.g. `system.slice/golem.service` for a
+/// systemd unit or `system.slice/docker-<id>.scope` for a container. Health
+/// checks read the limits back and report whether they still match the last
+/// allocation.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::protocols::http::HttpClientFactory;
+use crate::protocols::{
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus, MetricSchema, ProtocolAdapter,
+    ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+
+/// Bytes per GB of memory
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// cgroup v2 value meaning "no limit"
+const UNLIMITED: &str = "max";
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// cgroup settings (`[cgroups]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CgroupConfig {
+    /// Enforce allocations on the groups below
+    pub enabled: bool,
+    /// Mount point of the cgroup v2 hierarchy
+    pub root: PathBuf,
+    /// CPU bandwidth period in microseconds; a core is one period of quota
+    pub period_us: u64,
+    /// Group of each protocol (`[cgroups.protocols.<name>]`)
+    pub protocols: BTreeMap<String, ProtocolCgroup>,
+}
+
+impl Default for CgroupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            root: PathBuf::from("/sys/fs/cgroup"),
+            period_us: 100_000,
+            protocols: BTreeMap::new(),
+        }
+    }
+}
+
+/// A protocol's cgroup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolCgroup {
+    /// Group path relative to the hierarchy root
+    pub path: String,
+    /// CPUs the protocol may be pinned to (`0-3,8`); an allocation of `n`
+    /// cores pins it to the first `n`. Unpinned when unset.
+    #[serde(default)]
+    pub cpus: Option<String>,
+}
+
+impl CgroupConfig {
+    /// Validate the section
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !(1_000..=1_000_000).contains(&self.period_us) {
+            return Err("period_us must be between 1000 and 1000000".to_string());
+        }
+        for (protocol, group) in &self.protocols {
+            let path = Path::new(&group.path);
+            if group.path.trim().is_empty() || path.is_absolute() || group.path.split('/').any(|p| p == "..") {
+                return Err(format!("protocols.{}.path must be a path below the cgroup root", protocol));
+            }
+            if let Some(cpus) = &group.cpus {
+                parse_cpu_list(cpus).map_err(|e| format!("protocols.{}.cpus: {}", protocol, e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Expand a cpuset list (`0-3,8`) into CPU numbers
+pub fn parse_cpu_list(list: &str) -> Result<Vec<u32>, String> {
+    let mut cpus = Vec::new();
+    for part in list.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let parse = |n: &str| n.trim().parse::<u32>().map_err(|_| format!("invalid CPU {:?}", n));
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (parse(first)?, parse(last)?);
+                if first > last {
+                    return Err(format!("invalid CPU range {:?}", part));
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(parse(part)?),
+        }
+    }
+    if cpus.is_empty() {
+        return Err("no CPUs listed".to_string());
+    }
+    Ok(cpus)
+}
+
+/// Format CPU numbers as a cpuset list, collapsing runs into ranges
+fn format_cpu_list(cpus: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == cpu => *last = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// cgroup enforcement errors
+#[derive(Debug, Error)]
+pub enum CgroupError {
+    #[error("Protocol {0} has no managed cgroup")]
+    NotManaged(String),
+
+    #[error("{path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Values written to a group's interface files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CgroupLimits {
+    /// `cpu.max`: `<quota> <period>` or `max <period>`
+    pub cpu_max: String,
+    /// `memory.max`: bytes or `max`
+    pub memory_max: String,
+    /// `cpuset.cpus`, when the protocol is pinned
+    pub cpus: Option<String>,
+}
+
+impl CgroupLimits {
+    /// Limits for `strategy`; unallocated CPU or memory is left unlimited
+    ///
+    /// Pinning takes the first `cpu_cores` of `cpus` (all of them when
+    /// `cpu_cores` is 0 or exceeds the set).
+    pub fn for_strategy(strategy: &AllocationStrategy, period_us: u64, cpus: Option<&[u32]>) -> Self {
+        let quota = match strategy.cpu_cores {
+            0 => UNLIMITED.to_string(),
+            cores => (u64::from(cores) * period_us).to_string(),
+        };
+        let memory_bytes = (strategy.memory_gb * BYTES_PER_GB).round() as u64;
+        let pinned = cpus.map(|cpus| {
+            let cores = strategy.cpu_cores as usize;
+            let take = if cores == 0 { cpus.len() } else { cores.min(cpus.len()) };
+            format_cpu_list(&cpus[..take])
+        });
+        Self {
+            cpu_max: format!("{} {}", quota, period_us),
+            memory_max: if memory_bytes > 0 { memory_bytes.to_string() } else { UNLIMITED.to_string() },
+            cpus: pinned,
+        }
+    }
+}
+
+/// Limits of a protocol's group, as read back from the kernel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnforcementStatus {
+    pub cgroup: String,
+    pub current: CgroupLimits,
+    /// Limits of the last allocation applied, if any
+    pub expected: Option<CgroupLimits>,
+    /// Whether the group still holds the last allocation's limits
+    pub enforced: bool,
+}
+
+impl EnforcementStatus {
+    /// Report enforcement in an adapter's health
+    ///
+    /// Limits that drifted from the last allocation do not make the adapter
+    /// unhealthy; the next allocation writes them again.
+    pub fn apply_to(&self, status: &mut HealthStatus) {
+        if let Ok(value) = serde_json::to_value(self) {
+            status.metrics.insert("cgroup".to_string(), value);
+        }
+    }
+}
+
+// ============================================================================
+// CGROUP ENFORCER
+// ============================================================================
+
+/// Writes allocations to protocol cgroups
+pub struct CgroupEnforcer {
+    config: CgroupConfig,
+    /// Limits last written, by protocol
+    applied: Mutex<HashMap<String, CgroupLimits>>,
+}
+
+impl CgroupEnforcer {
+    /// Enforce allocations on the groups mapped in `config`
+    pub fn new(config: CgroupConfig) -> Self {
+        Self {
+            config,
+            applied: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `protocol` has a managed cgroup
+    pub fn manages(&self, protocol: &str) -> bool {
+        self.config.protocols.contains_key(protocol)
+    }
+
+    fn group(&self, protocol: &str) -> Result<(&ProtocolCgroup, PathBuf), CgroupError> {
+        let group = self
+            .config
+            .protocols
+            .get(protocol)
+            .ok_or_else(|| CgroupError::NotManaged(protocol.to_string()))?;
+        Ok((group, self.config.root.join(&group.path)))
+    }
+
+    /// Write `strategy`'s limits to `protocol`'s group
+    pub async fn apply(&self, protocol: &str, strategy: &AllocationStrategy) -> Result<CgroupLimits, CgroupError> {
+        let (group, dir) = self.group(protocol)?;
+        let cpus = group.cpus.as_deref().and_then(|list| parse_cpu_list(list).ok());
+        let limits = CgroupLimits::for_strategy(strategy, self.config.period_us, cpus.as_deref());
+
+        if let Some(cpus) = &limits.cpus {
+            write(&dir.join("cpuset.cpus"), cpus).await?;
+        }
+        write(&dir.join("cpu.max"), &limits.cpu_max).await?;
+        write(&dir.join("memory.max"), &limits.memory_max).await?;
+        tracing::info!(
+            "cgroup {} ({}): cpu.max={}, memory.max={}, cpuset.cpus={}",
+            group.path,
+            protocol,
+            limits.cpu_max,
+            limits.memory_max,
+            limits.cpus.as_deref().unwrap_or("unpinned")
+        );
+
+        self.applied
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(protocol.to_string(), limits.clone());
+        Ok(limits)
+    }
+
+    /// Limits `protocol`'s group holds, compared with the last allocation's
+    pub async fn status(&self, protocol: &str) -> Result<EnforcementStatus, CgroupError> {
+        let (group, dir) = self.group(protocol)?;
+        let cpus = match group.cpus {
+            Some(_) => Some(read(&dir.join("cpuset.cpus")).await?),
+            None => None,
+        };
+        let current = CgroupLimits {
+            cpu_max: read(&dir.join("cpu.max")).await?,
+            memory_max: read(&dir.join("memory.max")).await?,
+            cpus,
+        };
+        let expected = self.applied.lock().unwrap_or_else(|e| e.into_inner()).get(protocol).cloned();
+        Ok(EnforcementStatus {
+            cgroup: group.path.clone(),
+            enforced: expected.as_ref().is_some_and(|expected| holds(&current, expected)),
+            current,
+            expected,
+        })
+    }
+}
+
+/// Whether `current` holds `expected`, comparing cpusets by CPU
+fn holds(current: &CgroupLimits, expected: &CgroupLimits) -> bool {
+    let cpus = |list: &Option<String>| list.as_deref().map(parse_cpu_list).and_then(Result::ok);
+    expected.cpu_max == current.cpu_max
+        && expected.memory_max == current.memory_max
+        && (expected.cpus.is_none() || cpus(&expected.cpus) == cpus(&current.cpus))
+}
+
+async fn write(path: &Path, value: &str) -> Result<(), CgroupError> {
+    tokio::fs::write(path, value).await.map_err(|source| CgroupError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+async fn read(path: &Path) -> Result<String, CgroupError> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(value) => Ok(value.trim().to_string()),
+        Err(source) => Err(CgroupError::Io {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+/// Whether allocations of a protocol with `profile` are worth enforcing
+pub fn is_compute(profile: &ResourceProfile) -> bool {
+    matches!(profile.primary, ResourceKind::Cpu | ResourceKind::Gpu)
+}
+
+// ============================================================================
+// CGROUP ADAPTER
+// ============================================================================
+
+/// Adapter for a compute protocol whose allocations are enforced by cgroup
+///
+/// Writes each allocation's limits before handing it to the wrapped adapter,
+/// and reports whether they still hold in its health.
+pub struct CgroupAdapter {
+    inner: Box<dyn ProtocolAdapter>,
+    cgroups: Arc<CgroupEnforcer>,
+    /// Protocol instance the adapter is registered as, keying its `cgroups` entry
+    instance: String,
+}
+
+impl CgroupAdapter {
+    /// Wrap `inner`, whose process runs in a group managed by `cgroups`, as
+    /// protocol instance `instance`
+    pub fn new(
+        inner: Box<dyn ProtocolAdapter>,
+        cgroups: Arc<CgroupEnforcer>,
+        instance: impl Into<String>,
+    ) -> Self {
+        Self { inner, cgroups, instance: instance.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProtocolAdapter for CgroupAdapter {
+    fn protocol_name(&self) -> &str {
+        self.inner.protocol_name()
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        self.inner.disconnect().await
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.inner.connection_status()
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.inner.get_current_earnings().await
+    }
+
+    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        self.inner.get_historical_earnings(hours).await
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        self.inner.get_resource_usage().await
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        self.cgroups
+            .apply(&self.instance, &strategy)
+            .await
+            .map_err(|e| ProtocolError::ResourceError(e.to_string()))?;
+        self.inner.apply_allocation(strategy).await
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        self.inner.get_current_allocation().await
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let mut status = self.inner.health_check().await?;
+        match self.cgroups.status(&self.instance).await {
+            Ok(enforcement) => enforcement.apply_to(&mut status),
+            Err(e) => {
+                tracing::warn!("Failed to read cgroup of {}: {}", self.instance, e);
+                status.metrics.insert("cgroup_error".to_string(), e.to_string().into());
+            }
+        }
+        Ok(status)
+    }
+
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        self.inner.check_credentials().await
+    }
+
+    fn configure_http(&mut self, http: &HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.inner.configure_http(http, protocol)
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        self.inner.get_config()
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        self.inner.resource_profile()
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        self.inner.metric_schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(cpu_cores: u32, memory_gb: f64) -> AllocationStrategy {
+        AllocationStrategy {
+            cpu_cores,
+            memory_gb,
+            storage_gb: 0.0,
+            bandwidth_mbps: 0.0,
+            allocation_percent: 50.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        }
+    }
+
+    #[test]
+    fn test_limits_follow_allocation_strategy() {
+        let cpus = parse_cpu_list("4-7, 10").unwrap();
+        assert_eq!(cpus, [4, 5, 6, 7, 10]);
+        let limits = CgroupLimits::for_strategy(&strategy(3, 2.0), 100_000, Some(&cpus));
+        assert_eq!(limits.cpu_max, "300000 100000");
+        assert_eq!(limits.memory_max, "2147483648");
+        assert_eq!(limits.cpus.as_deref(), Some("4-6"));
+
+        let unlimited = CgroupLimits::for_strategy(&strategy(0, 0.0), 100_000, Some(&cpus));
+        assert_eq!(unlimited.cpu_max, "max 100000");
+        assert_eq!(unlimited.memory_max, "max");
+        assert_eq!(unlimited.cpus.as_deref(), Some("4-7,10"));
+        assert_eq!(CgroupLimits::for_strategy(&strategy(2, 1.0), 50_000, None).cpus, None);
+    }
+
+    #[tokio::test]
+    async fn test_enforcement_written_and_read_back() {
+        let root = std::env::temp_dir().join(format!("orcha-cgroup-{}", uuid::Uuid::new_v4()));
+        let dir = root.join("system.slice/golem.service");
+        std::fs::create_dir_all(&dir).unwrap();
+        let config: CgroupConfig = toml::from_str(&format!(
+            "enabled = true\nroot = {:?}\n[protocols.golem]\npath = \"system.slice/golem.service\"\ncpus = \"0-3\"\n",
+            root
+        ))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        let enforcer = CgroupEnforcer::new(config);
+
+        // Nothing has been applied yet
+        for (file, value) in [("cpu.max", "max 100000\n"), ("memory.max", "max\n"), ("cpuset.cpus", "\n")] {
+            std::fs::write(dir.join(file), value).unwrap();
+        }
+        assert!(!enforcer.status("golem").await.unwrap().enforced);
+
+        let limits = enforcer.apply("golem", &strategy(2, 0.5)).await.unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("cpu.max")).unwrap(), "200000 100000");
+        assert_eq!(std::fs::read_to_string(dir.join("cpuset.cpus")).unwrap(), "0-1");
+        let status = enforcer.status("golem").await.unwrap();
+        assert!(status.enforced);
+        assert_eq!(status.expected, Some(limits));
+
+        // Something else reset the memory limit
+        std::fs::write(dir.join("memory.max"), "max\n").unwrap();
+        let status = enforcer.status("golem").await.unwrap();
+        assert!(!status.enforced);
+        let mut health = crate::protocols::basic_health_status(true, ConnectionStatus::Connected, None);
+        status.apply_to(&mut health);
+        assert!(health.is_healthy);
+        assert_eq!(health.metrics["cgroup"]["enforced"], false);
+
+        assert!(matches!(enforcer.status("storj").await, Err(CgroupError::NotManaged(_))));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_cgroup_config_validation() {
+        let config = |group: &str| -> CgroupConfig {
+            toml::from_str(&format!("enabled = true\n[protocols.golem]\n{}\n", group)).unwrap()
+        };
+        assert!(config("path = \"golem.slice\"").validate().is_ok());
+        assert!(config("path = \"/sys/fs/cgroup/golem\"").validate().is_err());
+        assert!(config("path = \"../etc\"").validate().is_err());
+        let error = config("path = \"golem.slice\"\ncpus = \"3-1\"").validate().unwrap_err();
+        assert!(error.contains("protocols.golem.cpus"));
+        assert_eq!(format_cpu_list(&[0, 1, 2, 5, 7, 8]), "0-2,5,7-8");
+    }
+}
@@ -0,0 +1,497 @@
+/// Command-Line Interface
+///
+/// Headless control of a DePIN-Orcha node. Read-only and reallocation commands
+/// talk to the running API; key and user management and backups operate
+/// directly on the database so they also work while the server is down.
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::api::auth::insert_api_key;
+use crate::api::models::ReallocateRequest;
+use crate::api::sessions::insert_dashboard_user;
+use crate::backup;
+use crate::bootstrap;
+use crate::config::Config;
+use crate::db::{self, init_pool, DbConfig};
+
+// ============================================================================
+// ARGUMENT DEFINITIONS
+// ============================================================================
+
+/// DePIN-Orcha command-line arguments
+#[derive(Debug, Parser)]
+#[command(name = "depin-orcha", version, about = "Intelligent Multi-Protocol DePIN Orchestrator")]
+pub struct Cli {
+    /// Base URL of a running DePIN-Orcha API
+    #[arg(long, env = "ORCHA_API_URL", default_value = "http://127.0.0.1:8080", global = true)]
+    pub api_url: String,
+
+    /// API key used for protected endpoints
+    #[arg(long, env = "ORCHA_API_KEY", global = true, hide_env_values = true)]
+    pub api_key: Option<String>,
+
+    /// Subcommand to run (defaults to `serve`)
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Top-level subcommands
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the orchestrator (HTTP server + schedulers)
+    Serve,
+    /// Initialize the configured database and print a bootstrap admin key
+    Init,
+    /// Apply pending database migrations and list their status
+    Migrate(MigrateArgs),
+    /// Show system status from the running API
+    Status,
+    /// Submit a reallocation plan to the running API
+    Reallocate(ReallocateArgs),
+    /// Protocol commands
+    #[command(subcommand)]
+    Protocols(ProtocolsCommand),
+    /// API key management (operates directly on the database)
+    #[command(subcommand)]
+    Keys(KeysCommand),
+    /// Dashboard user management (operates directly on the database)
+    #[command(subcommand)]
+    Users(UsersCommand),
+    /// Database backups (operates directly on the database and `[backup]` settings)
+    #[command(subcommand)]
+    Backup(BackupCommand),
+}
+
+/// Arguments for `migrate`
+#[derive(Debug, Args)]
+pub struct MigrateArgs {
+    /// Only list migrations; apply nothing
+    #[arg(long)]
+    pub status: bool,
+}
+
+/// Arguments for `reallocate`
+#[derive(Debug, Args)]
+pub struct ReallocateArgs {
+    /// Path to a JSON plan: `{"allocation": {"storj": 40.0, ...}, "reason": "..."}`
+    #[arg(long)]
+    pub plan: PathBuf,
+}
+
+/// `protocols` subcommands
+#[derive(Debug, Subcommand)]
+pub enum ProtocolsCommand {
+    /// List registered protocols
+    List,
+}
+
+/// `keys` subcommands
+#[derive(Debug, Subcommand)]
+pub enum KeysCommand {
+    /// Create a new API key and print it once
+    Create(CreateKeyArgs),
+}
+
+/// Arguments for `keys create`
+#[derive(Debug, Args)]
+pub struct CreateKeyArgs {
+    /// Human-readable key name
+    #[arg(long)]
+    pub name: String,
+    /// Optional description
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Expire the key after this many days
+    #[arg(long)]
+    pub expires_in_days: Option<i64>,
+    /// Permission to grant (repeatable)
+    #[arg(long = "permission")]
+    pub permissions: Vec<String>,
+    /// Account the key belongs to
+    #[arg(long = "account", default_value_t = crate::db::DEFAULT_ACCOUNT_ID)]
+    pub account_id: i64,
+    /// SQLite database path
+    #[arg(long, env = "DATABASE_URL", default_value = "depin_orcha.db")]
+    pub database_url: String,
+}
+
+/// `users` subcommands
+#[derive(Debug, Subcommand)]
+pub enum UsersCommand {
+    /// Create a dashboard login
+    Create(CreateUserArgs),
+}
+
+/// Arguments for `users create`
+#[derive(Debug, Args)]
+pub struct CreateUserArgs {
+    /// Login name
+    #[arg(long)]
+    pub username: String,
+    /// Password (at least 8 characters); read from stdin when omitted
+    #[arg(long, env = "ORCHA_USER_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+    /// Permission to grant (repeatable)
+    #[arg(long = "permission")]
+    pub permissions: Vec<String>,
+    /// Account the user belongs to
+    #[arg(long = "account", default_value_t = crate::db::DEFAULT_ACCOUNT_ID)]
+    pub account_id: i64,
+    /// SQLite database path
+    #[arg(long, env = "DATABASE_URL", default_value = "depin_orcha.db")]
+    pub database_url: String,
+}
+
+/// `backup` subcommands
+#[derive(Debug, Subcommand)]
+pub enum BackupCommand {
+    /// Back up the database now
+    Create,
+    /// List local backups, newest first
+    List,
+    /// Replace the database with a backup (stop the server first)
+    Restore(RestoreArgs),
+}
+
+/// Arguments for `backup restore`
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    /// Backup file, or object name in the `[backup.s3]` bucket with `--from-s3`
+    pub backup: String,
+    /// Download the backup from the configured bucket first
+    #[arg(long)]
+    pub from_s3: bool,
+}
+
+// ============================================================================
+// COMMAND EXECUTION
+// ============================================================================
+
+/// Run a non-server subcommand
+pub async fn run(cli: &Cli, command: &Command) -> Result<()> {
+    match command {
+        Command::Serve => Err(anyhow!("`serve` is handled by the binary entry point")),
+        Command::Init => init().await,
+        Command::Migrate(args) => migrate(args).await,
+        Command::Status => {
+            let status = ApiClient::new(cli).get("/api/v1/status").await?;
+            print_json(&status["data"])
+        }
+        Command::Reallocate(args) => {
+            let raw = std::fs::read_to_string(&args.plan)
+                .with_context(|| format!("Failed to read plan {}", args.plan.display()))?;
+            let plan: ReallocateRequest =
+                serde_json::from_str(&raw).context("Plan file is not a valid reallocation request")?;
+            let response = ApiClient::new(cli)
+                .post("/api/v1/reallocate", serde_json::to_value(&plan)?)
+                .await?;
+            print_json(&response["data"])
+        }
+        Command::Protocols(ProtocolsCommand::List) => {
+            let status = ApiClient::new(cli).get("/api/v1/status").await?;
+            let protocols = status["data"]["protocols"].as_array().cloned().unwrap_or_default();
+            if protocols.is_empty() {
+                println!("No protocols registered");
+            }
+            for protocol in protocols {
+                let id = protocol.as_str().unwrap_or_default();
+                match status["data"]["protocol_types"][id].as_str() {
+                    Some(protocol_type) if protocol_type != id => println!("{} ({})", id, protocol_type),
+                    _ => println!("{}", id),
+                }
+            }
+            Ok(())
+        }
+        Command::Keys(KeysCommand::Create(args)) => create_key(args).await,
+        Command::Users(UsersCommand::Create(args)) => create_user(args).await,
+        Command::Backup(command) => run_backup(command).await,
+    }
+}
+
+/// Run a `backup` subcommand against the configured database
+async fn run_backup(command: &BackupCommand) -> Result<()> {
+    let config = Config::load()?;
+    let backup_config = &config.backup;
+
+    match command {
+        BackupCommand::Create => {
+            let pool = init_pool(config.database.clone())
+                .await
+                .context("Failed to open database")?;
+            let info = backup::create_backup(&pool, backup_config).await?;
+            println!(
+                "Wrote {} ({} bytes)",
+                backup_config.directory.join(&info.name).display(),
+                info.size_bytes
+            );
+        }
+        BackupCommand::List => {
+            let backups = backup::list_backups(&backup_config.directory).await?;
+            if backups.is_empty() {
+                println!("No backups in {}", backup_config.directory.display());
+            }
+            for info in backups {
+                println!("{}  {:>12} bytes  {}", info.created_at.to_rfc3339(), info.size_bytes, info.name);
+            }
+        }
+        BackupCommand::Restore(args) => {
+            let path = if args.from_s3 {
+                let s3 = backup_config
+                    .s3
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("No [backup.s3] bucket is configured"))?;
+                backup::fetch_backup(s3, &args.backup, &backup_config.directory).await?
+            } else {
+                PathBuf::from(&args.backup)
+            };
+            let restored = backup::restore_backup(&path, &config.database.database_url).await?;
+            println!("Restored {} from {}", restored.display(), path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Initialize the configured database, generating the bootstrap admin key
+async fn init() -> Result<()> {
+    let config = Config::load()?;
+    let pool = init_pool(config.database.clone())
+        .await
+        .context("Failed to open database")?;
+
+    match bootstrap::bootstrap(&pool, config.server.account_id)
+        .await
+        .map_err(|e| anyhow!("Failed to bootstrap admin key: {}", e))?
+    {
+        Some(key) => bootstrap::print_bootstrap_key(&key),
+        None => println!("Already initialized; create more keys with `depin-orcha keys create`"),
+    }
+    Ok(())
+}
+
+/// Apply pending migrations to the configured database and list every migration
+async fn migrate(args: &MigrateArgs) -> Result<()> {
+    let config = Config::load()?;
+    let pool = db::connect(&config.database).await.context("Failed to open database")?;
+
+    let pending = db::migration_status(&pool)
+        .await
+        .context("Failed to read migration status")?
+        .iter()
+        .filter(|m| m.applied_at.is_none())
+        .count();
+    if !args.status && pending > 0 {
+        db::run_migrations(&pool).await.context("Failed to apply migrations")?;
+        println!("Applied {} migration(s) to {}", pending, config.database.database_url);
+    }
+
+    for migration in db::migration_status(&pool).await.context("Failed to read migration status")? {
+        let state = match migration.applied_at {
+            Some(at) if migration.modified => format!("applied {} (file modified since)", at.to_rfc3339()),
+            Some(at) => format!("applied {}", at.to_rfc3339()),
+            None => "pending".to_string(),
+        };
+        println!("{}  {:<45}  {}", migration.version, migration.description, state);
+    }
+    Ok(())
+}
+
+/// Create an API key directly in the database
+async fn create_key(args: &CreateKeyArgs) -> Result<()> {
+    let pool = init_pool(DbConfig {
+        database_url: args.database_url.clone(),
+        ..DbConfig::default()
+    })
+    .await
+    .context("Failed to open database")?;
+
+    let (api_key, key_id) = insert_api_key(
+        &pool,
+        args.account_id,
+        &args.name,
+        args.description.as_deref(),
+        args.expires_in_days,
+        &args.permissions,
+    )
+    .await
+    .map_err(|e| anyhow!("Failed to create API key: {}", e))?;
+
+    println!("Created API key #{} ({})", key_id, args.name);
+    println!("{}", api_key);
+    println!("Store this key now; it cannot be shown again.");
+    Ok(())
+}
+
+/// Create a dashboard user directly in the database
+async fn create_user(args: &CreateUserArgs) -> Result<()> {
+    let password = match &args.password {
+        Some(password) => password.clone(),
+        None => {
+            eprint!("Password: ");
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).context("Failed to read password")?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+
+    let pool = init_pool(DbConfig {
+        database_url: args.database_url.clone(),
+        ..DbConfig::default()
+    })
+    .await
+    .context("Failed to open database")?;
+
+    let user_id = insert_dashboard_user(&pool, args.account_id, &args.username, &password, &args.permissions)
+        .await
+        .map_err(|e| anyhow!("Failed to create dashboard user: {}", e))?;
+
+    println!("Created dashboard user #{} ({})", user_id, args.username);
+    Ok(())
+}
+
+fn print_json(value: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+// ============================================================================
+// API CLIENT
+// ============================================================================
+
+/// Thin JSON client for the running API
+struct ApiClient {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+impl ApiClient {
+    fn new(cli: &Cli) -> Self {
+        Self {
+            base_url: cli.api_url.trim_end_matches('/').to_string(),
+            api_key: cli.api_key.clone(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        self.send(self.http.get(format!("{}{}", self.base_url, path))).await
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        self.send(self.http.post(format!("{}{}", self.base_url, path)).json(&body))
+            .await
+    }
+
+    async fn send(&self, mut request: reqwest::RequestBuilder) -> Result<Value> {
+        if let Some(key) = &self.api_key {
+            request = request.header("X-API-Key", key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach API at {}", self.base_url))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+
+        if !status.is_success() {
+            let message = body["message"].as_str().unwrap_or("request failed");
+            return Err(anyhow!("API returned {}: {}", status, message));
+        }
+
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_subcommand_defaults_to_serve() {
+        let cli = Cli::try_parse_from(["depin-orcha"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_parse_init() {
+        let cli = Cli::try_parse_from(["depin-orcha", "init"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Init)));
+    }
+
+    #[test]
+    fn test_parse_migrate() {
+        let cli = Cli::try_parse_from(["depin-orcha", "migrate"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Migrate(MigrateArgs { status: false }))));
+        let cli = Cli::try_parse_from(["depin-orcha", "migrate", "--status"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Migrate(MigrateArgs { status: true }))));
+    }
+
+    #[test]
+    fn test_parse_reallocate() {
+        let cli = Cli::try_parse_from(["depin-orcha", "reallocate", "--plan", "plan.json"]).unwrap();
+        match cli.command {
+            Some(Command::Reallocate(args)) => assert_eq!(args.plan, PathBuf::from("plan.json")),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_backup_restore() {
+        let cli = Cli::try_parse_from([
+            "depin-orcha",
+            "backup",
+            "restore",
+            "depin_orcha-20261015T120000Z.db",
+            "--from-s3",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Backup(BackupCommand::Restore(args))) => {
+                assert_eq!(args.backup, "depin_orcha-20261015T120000Z.db");
+                assert!(args.from_s3);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_keys_create() {
+        let cli = Cli::try_parse_from([
+            "depin-orcha",
+            "keys",
+            "create",
+            "--name",
+            "ops",
+            "--permission",
+            "admin",
+            "--permission",
+            "read",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Keys(KeysCommand::Create(args))) => {
+                assert_eq!(args.name, "ops");
+                assert_eq!(args.permissions, vec!["admin", "read"]);
+                assert_eq!(args.account_id, crate::db::DEFAULT_ACCOUNT_ID);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_users_create() {
+        let cli = Cli::try_parse_from(["depin-orcha", "users", "create", "--username", "ops", "--permission", "read"])
+            .unwrap();
+        match cli.command {
+            Some(Command::Users(UsersCommand::Create(args))) => {
+                assert_eq!(args.username, "ops");
+                assert_eq!(args.permissions, vec!["read"]);
+                assert_eq!(args.account_id, crate::db::DEFAULT_ACCOUNT_ID);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+}
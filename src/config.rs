@@ -0,0 +1,538 @@
+/// Application Configuration
+///
+/// Unified configuration loaded from a TOML file (`ORCHA_CONFIG`, else
+/// `orcha.toml`, else `config/default.toml`) and overridden by environment
+/// variables. Typed sections are validated at startup; sections owned by other
+/// modules (`[protocols]`, `[wallets]`, `[export]`, `[plugins]`,
+/// `[notifications]`) are kept as raw TOML and parsed by those modules.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::api::access_log::AccessLogConfig;
+use crate::api::currency::DisplayConfig;
+use crate::api::middleware::RateLimitConfig;
+use crate::api::websocket::WsConfig;
+use crate::api::ApiConfig;
+use crate::backup::BackupConfig;
+use crate::cgroups::CgroupConfig;
+use crate::containers::ContainerConfig;
+use crate::db::DbConfig;
+use crate::disk_quotas::DiskQuotaConfig;
+use crate::ip_intel::IpIntelConfig;
+use crate::network_probe::NetworkProbeConfig;
+use crate::protocols::http::HttpConfig;
+use crate::scheduler::SchedulerConfig;
+use crate::services::ServiceConfig;
+use crate::timezone::ReportingConfig;
+use crate::{
+    CacheConfig, MlOptimizerConfig, MonitorConfig, OptimizerConfig, ProtocolConstraint, ReallocationConfig,
+    ResilienceConfig, RolloutConfig, SwitchingCost,
+};
+
+/// Config file used when `ORCHA_CONFIG` is not set
+pub const DEFAULT_CONFIG_PATH: &str = "orcha.toml";
+
+/// Fallback config file shipped with the repository
+pub const FALLBACK_CONFIG_PATH: &str = "config/default.toml";
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+/// Configuration loading error
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Cannot read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid config file {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error("Environment variable {var}={value:?} is not a valid {expected}")]
+    Env {
+        var: String,
+        value: String,
+        expected: &'static str,
+    },
+
+    #[error("Invalid configuration:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+// ============================================================================
+// SECTIONS
+// ============================================================================
+
+/// Reallocation settings (`[reallocation]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReallocationSettings {
+    pub require_confirmation: bool,
+    pub confirmation_timeout_hours: i64,
+    pub max_per_hour: u32,
+    pub auto_rollback: bool,
+    /// Floors, caps and pins by protocol (`[reallocation.constraints.<name>]`)
+    pub constraints: HashMap<String, ProtocolConstraint>,
+    pub base_cost_usd: f64,
+    pub cost_amortization_hours: f64,
+    /// Migration costs by protocol (`[reallocation.switching_costs.<name>]`)
+    pub switching_costs: HashMap<String, SwitchingCost>,
+    /// Staged rollout (`[reallocation.rollout]`)
+    pub rollout: RolloutConfig,
+}
+
+impl Default for ReallocationSettings {
+    fn default() -> Self {
+        let defaults = ReallocationConfig::default();
+        Self {
+            require_confirmation: defaults.require_confirmation,
+            confirmation_timeout_hours: defaults.confirmation_timeout.num_hours(),
+            max_per_hour: defaults.max_per_hour,
+            auto_rollback: defaults.auto_rollback,
+            constraints: defaults.constraints,
+            base_cost_usd: defaults.base_cost_usd,
+            cost_amortization_hours: defaults.cost_amortization_hours,
+            switching_costs: defaults.switching_costs,
+            rollout: defaults.rollout,
+        }
+    }
+}
+
+/// Alerting settings (`[alerts]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub anomaly_detection_enabled: bool,
+    pub anomaly_z_threshold: f64,
+    pub anomaly_window: usize,
+    pub alert_cooldown_minutes: i64,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        let defaults = MonitorConfig::default();
+        Self {
+            anomaly_detection_enabled: defaults.anomaly_detection_enabled,
+            anomaly_z_threshold: defaults.anomaly_z_threshold,
+            anomaly_window: defaults.anomaly_window,
+            alert_cooldown_minutes: defaults.alert_cooldown.num_minutes(),
+        }
+    }
+}
+
+// ============================================================================
+// CONFIG
+// ============================================================================
+
+/// Complete application configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub server: ApiConfig,
+    pub database: DbConfig,
+    pub scheduler: SchedulerConfig,
+    pub optimizer: OptimizerConfig,
+    pub reallocation: ReallocationSettings,
+    pub alerts: AlertSettings,
+    pub ml_engine: MlOptimizerConfig,
+    pub resilience: ResilienceConfig,
+    pub cache: CacheConfig,
+    pub http: HttpConfig,
+    pub ip_intel: IpIntelConfig,
+    pub network_probes: NetworkProbeConfig,
+    pub backup: BackupConfig,
+    pub containers: ContainerConfig,
+    pub services: ServiceConfig,
+    pub cgroups: CgroupConfig,
+    pub disk_quotas: DiskQuotaConfig,
+    pub rate_limits: RateLimitConfig,
+    pub display: DisplayConfig,
+    pub reporting: ReportingConfig,
+    pub access_log: AccessLogConfig,
+    pub websocket: WsConfig,
+    /// File the configuration was read from, if any
+    #[serde(skip_deserializing)]
+    pub source: Option<PathBuf>,
+    /// Raw file contents for module-owned sections (may contain credentials)
+    #[serde(skip)]
+    pub raw: toml::Table,
+}
+
+impl Config {
+    /// Load from the config file, apply environment overrides, and validate
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match std::env::var("ORCHA_CONFIG") {
+            // An explicitly named file must exist
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) => [DEFAULT_CONFIG_PATH, FALLBACK_CONFIG_PATH]
+                .iter()
+                .map(Path::new)
+                .find(|path| path.exists())
+                .map(Self::from_file)
+                .transpose()?
+                .unwrap_or_default(),
+        };
+
+        config.apply_env(|var| std::env::var(var).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read a config file without environment overrides or validation
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let raw = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut config = Self::from_toml(&raw).map_err(|message| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+        })?;
+        config.source = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Parse TOML text
+    pub fn from_toml(raw: &str) -> Result<Self, String> {
+        let table: toml::Table = toml::from_str(raw).map_err(|e| e.to_string())?;
+        let mut config: Config = table.clone().try_into().map_err(|e| e.to_string())?;
+        config.raw = table;
+        Ok(config)
+    }
+
+    /// Override settings from environment variables looked up with `lookup`
+    pub fn apply_env(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        if let Some(host) = lookup("API_HOST") {
+            self.server.host = host;
+        }
+        env_override(&lookup, "API_PORT", &mut self.server.port)?;
+        env_override(&lookup, "API_WORKERS", &mut self.server.workers)?;
+        env_override(&lookup, "API_REQUEST_TIMEOUT", &mut self.server.request_timeout)?;
+        env_override(&lookup, "API_SHUTDOWN_TIMEOUT", &mut self.server.shutdown_timeout)?;
+        env_override(&lookup, "ORCHA_ACCOUNT_ID", &mut self.server.account_id)?;
+
+        if let Some(url) = lookup("DATABASE_URL") {
+            self.database.database_url = url;
+        }
+        env_override(&lookup, "DB_MAX_CONNECTIONS", &mut self.database.max_connections)?;
+        env_override(&lookup, "DB_MIN_CONNECTIONS", &mut self.database.min_connections)?;
+        env_override(&lookup, "DB_CONNECT_TIMEOUT", &mut self.database.connect_timeout)?;
+
+        let scheduler = &mut self.scheduler;
+        env_override(&lookup, "POLL_INTERVAL", &mut scheduler.poll_interval)?;
+        env_override(&lookup, "OPTIMIZATION_INTERVAL", &mut scheduler.optimization_interval)?;
+        env_override(&lookup, "METRICS_RETENTION_DAYS", &mut scheduler.metrics_retention_days)?;
+        env_override(&lookup, "ALERT_PROCESSING_INTERVAL", &mut scheduler.alert_processing_interval)?;
+        env_override(&lookup, "MIN_REALLOCATION_THRESHOLD", &mut scheduler.min_reallocation_threshold)?;
+        env_override(&lookup, "CPU_ALERT_THRESHOLD", &mut scheduler.cpu_alert_threshold)?;
+        env_override(&lookup, "MEMORY_ALERT_THRESHOLD", &mut scheduler.memory_alert_threshold)?;
+        env_override(&lookup, "ALERT_COOLDOWN_SECS", &mut scheduler.alert_cooldown_secs)?;
+        env_override(&lookup, "METRICS_FLUSH_INTERVAL", &mut scheduler.metrics_flush_interval)?;
+        env_override(&lookup, "METRICS_BATCH_SIZE", &mut scheduler.metrics_batch_size)?;
+
+        Ok(())
+    }
+
+    /// Check every section, reporting all problems at once
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+
+        if self.server.host.trim().is_empty() {
+            errors.push("server.host must not be empty".to_string());
+        }
+        if self.server.port == 0 {
+            errors.push("server.port must be between 1 and 65535".to_string());
+        }
+        if self.server.workers == 0 {
+            errors.push("server.workers must be at least 1".to_string());
+        }
+        if self.server.account_id < 1 {
+            errors.push("server.account_id must be at least 1".to_string());
+        }
+        if self.server.session_ttl_secs == 0 {
+            errors.push("server.session_ttl_secs must be at least 1 second".to_string());
+        }
+
+        if self.database.database_url.trim().is_empty() {
+            errors.push("database.database_url must not be empty".to_string());
+        }
+        if self.database.max_connections == 0 {
+            errors.push("database.max_connections must be at least 1".to_string());
+        }
+        if self.database.min_connections > self.database.max_connections {
+            errors.push(format!(
+                "database.min_connections ({}) exceeds max_connections ({})",
+                self.database.min_connections, self.database.max_connections
+            ));
+        }
+
+        if let Err(e) = self.scheduler.validate() {
+            errors.push(format!("scheduler: {}", e));
+        }
+
+        if let Err(e) = self.optimizer.validate() {
+            errors.push(format!("optimizer: {}", e));
+        }
+
+        if self.reallocation.confirmation_timeout_hours < 1 {
+            errors.push("reallocation.confirmation_timeout_hours must be at least 1".to_string());
+        } else if let Err(e) = self.reallocation_config().validate() {
+            errors.push(format!("reallocation: {}", e));
+        }
+
+        if self.alerts.anomaly_z_threshold <= 0.0 {
+            errors.push("alerts.anomaly_z_threshold must be positive".to_string());
+        }
+        if self.alerts.alert_cooldown_minutes < 0 {
+            errors.push("alerts.alert_cooldown_minutes must not be negative".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.ml_engine.confidence_threshold) {
+            errors.push("ml_engine.confidence_threshold must be between 0.0 and 1.0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.ml_engine.blend_weight) {
+            errors.push("ml_engine.blend_weight must be between 0.0 and 1.0".to_string());
+        }
+
+        if let Err(e) = self.resilience.validate() {
+            errors.push(format!("resilience: {}", e));
+        }
+        if let Err(e) = self.cache.validate() {
+            errors.push(format!("cache: {}", e));
+        }
+        if let Err(e) = self.http.validate() {
+            errors.push(format!("http: {}", e));
+        }
+        if let Err(e) = self.ip_intel.validate() {
+            errors.push(format!("ip_intel: {}", e));
+        }
+        if let Err(e) = self.network_probes.validate() {
+            errors.push(format!("network_probes: {}", e));
+        }
+        if let Err(e) = self.backup.validate() {
+            errors.push(format!("backup: {}", e));
+        }
+        if let Err(e) = self.containers.validate() {
+            errors.push(format!("containers: {}", e));
+        }
+        if let Err(e) = self.services.validate() {
+            errors.push(format!("services: {}", e));
+        }
+        if let Err(e) = self.cgroups.validate() {
+            errors.push(format!("cgroups: {}", e));
+        }
+        if let Err(e) = self.disk_quotas.validate() {
+            errors.push(format!("disk_quotas: {}", e));
+        }
+        if let Err(e) = self.rate_limits.validate() {
+            errors.push(format!("rate_limits: {}", e));
+        }
+        if let Err(e) = self.display.validate() {
+            errors.push(format!("display: {}", e));
+        }
+        if let Err(e) = self.access_log.validate() {
+            errors.push(format!("access_log: {}", e));
+        }
+        if let Err(e) = self.websocket.validate() {
+            errors.push(format!("websocket: {}", e));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+
+    /// Reallocation engine configuration
+    pub fn reallocation_config(&self) -> ReallocationConfig {
+        ReallocationConfig {
+            require_confirmation: self.reallocation.require_confirmation,
+            confirmation_timeout: chrono::Duration::hours(self.reallocation.confirmation_timeout_hours),
+            max_per_hour: self.reallocation.max_per_hour,
+            auto_rollback: self.reallocation.auto_rollback,
+            constraints: self.reallocation.constraints.clone(),
+            base_cost_usd: self.reallocation.base_cost_usd,
+            switching_costs: self.reallocation.switching_costs.clone(),
+            cost_amortization_hours: self.reallocation.cost_amortization_hours,
+            rollout: self.reallocation.rollout.clone(),
+            ..ReallocationConfig::default()
+        }
+    }
+
+    /// Realtime monitor configuration
+    pub fn monitor_config(&self) -> MonitorConfig {
+        MonitorConfig {
+            anomaly_detection_enabled: self.alerts.anomaly_detection_enabled,
+            anomaly_z_threshold: self.alerts.anomaly_z_threshold,
+            anomaly_window: self.alerts.anomaly_window,
+            alert_cooldown: chrono::Duration::minutes(self.alerts.alert_cooldown_minutes),
+            ..MonitorConfig::default()
+        }
+    }
+}
+
+/// Replace `target` with the parsed value of `var`, if set
+fn env_override<T: FromStr>(
+    lookup: &impl Fn(&str) -> Option<String>,
+    var: &str,
+    target: &mut T,
+) -> Result<(), ConfigError> {
+    let Some(value) = lookup(var) else {
+        return Ok(());
+    };
+    *target = value.trim().parse().map_err(|_| ConfigError::Env {
+        var: var.to_string(),
+        value,
+        expected: std::any::type_name::<T>(),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |var| vars.get(var).cloned()
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config = Config::default();
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.workers, 4);
+        assert_eq!(config.database.database_url, "depin_orcha.db");
+        assert_eq!(config.database.connect_timeout, 5);
+        assert_eq!(config.scheduler.optimization_interval, 300);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_shipped_default_file_is_valid() {
+        let config = Config::from_file(Path::new(FALLBACK_CONFIG_PATH)).unwrap();
+        assert!(config.validate().is_ok());
+        assert!(config.raw.contains_key("protocols"));
+    }
+
+    #[test]
+    fn test_file_sections_and_env_overrides() {
+        let mut config = Config::from_toml(
+            r#"
+            [server]
+            port = 9000
+
+            [scheduler]
+            optimization_interval = 600
+
+            [reallocation]
+            require_confirmation = true
+            confirmation_timeout_hours = 2
+
+            [reallocation.constraints.storj]
+            min_percent = 20.0
+
+            [reallocation.switching_costs.storj]
+            penalty_usd = 15.0
+            irreversible = true
+
+            [reallocation.rollout]
+            enabled = true
+            step_percent = 10.0
+
+            [alerts]
+            alert_cooldown_minutes = 5
+
+            [display]
+            currency = "GBP"
+            rates = { GBP = 0.8 }
+
+            [protocols.storj]
+            enabled = true
+            "#,
+        )
+        .unwrap();
+
+        config
+            .apply_env(env(&[
+                ("API_HOST", "0.0.0.0"),
+                ("API_PORT", "3000"),
+                ("ORCHA_ACCOUNT_ID", "4"),
+                ("METRICS_RETENTION_DAYS", "60"),
+                ("MIN_REALLOCATION_THRESHOLD", "10.0"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, 3000);
+        assert_eq!(config.server.account_id, 4);
+        assert_eq!(config.scheduler.optimization_interval, 600);
+        assert_eq!(config.scheduler.metrics_retention_days, 60);
+        assert_eq!(config.scheduler.min_reallocation_threshold, 10.0);
+        assert!(config.reallocation_config().require_confirmation);
+        assert_eq!(config.reallocation_config().confirmation_timeout.num_hours(), 2);
+        assert_eq!(config.reallocation_config().constraints["storj"].min_percent, Some(20.0));
+        assert!(config.reallocation_config().switching_costs["storj"].irreversible);
+        assert!(config.reallocation_config().rollout.enabled);
+        assert_eq!(config.reallocation_config().rollout.step_percent, 10.0);
+        assert_eq!(config.reallocation_config().rollout.step_interval_secs, 900);
+        assert_eq!(config.monitor_config().alert_cooldown.num_minutes(), 5);
+        assert_eq!(config.display.format(10.0, 2), "8.00 GBP");
+        assert!(config.raw.contains_key("protocols"));
+    }
+
+    #[test]
+    fn test_invalid_env_value_is_reported() {
+        let mut config = Config::default();
+        let err = config.apply_env(env(&[("API_PORT", "eighty")])).unwrap_err();
+        assert!(err.to_string().contains("API_PORT=\"eighty\""));
+    }
+
+    #[test]
+    fn test_validation_collects_all_errors() {
+        let mut config = Config::default();
+        config.server.workers = 0;
+        config.database.min_connections = 20;
+        config.ml_engine.blend_weight = 1.5;
+
+        match config.validate() {
+            Err(ConfigError::Invalid(errors)) => assert_eq!(errors.len(), 3),
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_names_file() {
+        let path = std::env::temp_dir().join("depin-orcha-invalid-config.toml");
+        std::fs::write(&path, "[server]\nport = \"not a port\"\n").unwrap();
+
+        let err = Config::from_file(&path).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { .. }));
+        assert!(err.to_string().contains("depin-orcha-invalid-config.toml"));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_serialized_view_omits_raw_sections() {
+        let config = Config::from_toml("[protocols.grass]\napi_key = \"secret\"\n").unwrap();
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(!json.contains("secret"));
+        assert!(json.contains("\"server\""));
+    }
+}
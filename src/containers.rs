@@ -0,0 +1,497 @@
+/// Protocol Containers
+///
+/// Many protocol nodes (Storj, Golem, ...) run as Docker containers on the
+/// orchestrator's host. Protocols mapped to a container in `[containers]`
+/// can be started, stopped, and restarted; every allocation applied to them
+/// also sets the container's CPU and memory cgroup limits, and the
+/// container's state is reported in the adapter's health. The Docker client
+/// is only built with the `docker` feature; without it every call fails with
+/// [`ContainerError::Disabled`] and enabling containers is rejected at
+/// startup.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::protocols::http::HttpClientFactory;
+use crate::protocols::{
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus, MetricSchema, ProtocolAdapter,
+    ProtocolError, ProtocolResult, ResourceMetrics, ResourceProfile,
+};
+
+/// Bytes per GB of memory, as Docker counts them
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Docker CPU quota units per core
+const NANO_CPUS_PER_CORE: i64 = 1_000_000_000;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Container settings (`[containers]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContainerConfig {
+    /// Manage the containers below (requires the `docker` feature)
+    pub enabled: bool,
+    /// Docker daemon address (`unix://...`, `tcp://...`); `DOCKER_HOST` or
+    /// the local socket when unset
+    pub docker_host: Option<String>,
+    /// Seconds Docker waits for a container to stop before killing it
+    pub stop_timeout_secs: u64,
+    /// Container name or ID by protocol (`[containers.protocols]`)
+    pub protocols: BTreeMap<String, String>,
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            docker_host: None,
+            stop_timeout_secs: 30,
+            protocols: BTreeMap::new(),
+        }
+    }
+}
+
+impl ContainerConfig {
+    /// Validate the section against this build
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !cfg!(feature = "docker") {
+            return Err("managing containers requires the docker feature".to_string());
+        }
+        if let Some((protocol, _)) = self.protocols.iter().find(|(_, container)| container.trim().is_empty()) {
+            return Err(format!("protocols.{} must name a container", protocol));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Container management errors
+#[derive(Debug, Error)]
+pub enum ContainerError {
+    #[error("Managing containers requires the docker feature")]
+    Disabled,
+
+    #[error("Protocol {0} does not run in a managed container")]
+    NotManaged(String),
+
+    #[cfg(feature = "docker")]
+    #[error("Docker error: {0}")]
+    Docker(#[from] bollard::errors::Error),
+}
+
+/// Lifecycle operation on a protocol's container
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl FromStr for ContainerAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Self::Start),
+            "stop" => Ok(Self::Stop),
+            "restart" => Ok(Self::Restart),
+            other => Err(format!("Unknown container action {:?} (expected start, stop, or restart)", other)),
+        }
+    }
+}
+
+/// cgroup limits for a container; `None` leaves a limit unchanged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ContainerLimits {
+    /// CPU quota in billionths of a core
+    pub nano_cpus: Option<i64>,
+    /// Memory limit in bytes
+    pub memory_bytes: Option<i64>,
+}
+
+impl From<&AllocationStrategy> for ContainerLimits {
+    fn from(strategy: &AllocationStrategy) -> Self {
+        let memory_bytes = (strategy.memory_gb * BYTES_PER_GB).round() as i64;
+        Self {
+            nano_cpus: (strategy.cpu_cores > 0).then(|| i64::from(strategy.cpu_cores) * NANO_CPUS_PER_CORE),
+            memory_bytes: (memory_bytes > 0).then_some(memory_bytes),
+        }
+    }
+}
+
+/// State of a protocol's container as reported by Docker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerHealth {
+    pub container: String,
+    /// Docker state: created, running, paused, restarting, exited, ...
+    pub state: String,
+    pub running: bool,
+    /// Result of the image's HEALTHCHECK (healthy, unhealthy, starting), if it has one
+    pub health: Option<String>,
+    pub restart_count: i64,
+    pub started_at: Option<String>,
+}
+
+impl ContainerHealth {
+    /// Running and not failing its health check
+    pub fn is_healthy(&self) -> bool {
+        self.running && self.health.as_deref() != Some("unhealthy")
+    }
+
+    /// Report this container in an adapter's health
+    ///
+    /// An unhealthy container makes the adapter unhealthy.
+    pub fn apply_to(&self, status: &mut HealthStatus) {
+        if !self.is_healthy() {
+            status.is_healthy = false;
+            if status.error_message.is_none() {
+                let state = self.health.as_deref().filter(|_| self.running).unwrap_or(&self.state);
+                status.error_message = Some(format!("Container {} is {}", self.container, state));
+            }
+        }
+        if let Ok(value) = serde_json::to_value(self) {
+            status.metrics.insert("container".to_string(), value);
+        }
+    }
+}
+
+// ============================================================================
+// CONTAINER MANAGER
+// ============================================================================
+
+/// Docker client for the containers protocols run in
+pub struct ContainerManager {
+    config: ContainerConfig,
+    #[cfg(feature = "docker")]
+    docker: bollard::Docker,
+}
+
+impl ContainerManager {
+    /// Connect to the Docker daemon named in `config`
+    pub fn connect(config: ContainerConfig) -> Result<Self, ContainerError> {
+        #[cfg(feature = "docker")]
+        {
+            use bollard::{Docker, API_DEFAULT_VERSION};
+
+            const TIMEOUT_SECS: u64 = 120;
+            let docker = match config.docker_host.as_deref() {
+                None => Docker::connect_with_defaults()?,
+                Some(host) if host.starts_with("unix://") => {
+                    Docker::connect_with_socket(host, TIMEOUT_SECS, API_DEFAULT_VERSION)?
+                }
+                Some(host) => Docker::connect_with_http(host, TIMEOUT_SECS, API_DEFAULT_VERSION)?,
+            };
+            Ok(Self { config, docker })
+        }
+        #[cfg(not(feature = "docker"))]
+        {
+            let _ = config;
+            Err(ContainerError::Disabled)
+        }
+    }
+
+    /// Whether `protocol` runs in a managed container
+    pub fn manages(&self, protocol: &str) -> bool {
+        self.config.protocols.contains_key(protocol)
+    }
+
+    /// Container `protocol` runs in
+    pub fn container_for(&self, protocol: &str) -> Result<&str, ContainerError> {
+        self.config
+            .protocols
+            .get(protocol)
+            .map(String::as_str)
+            .ok_or_else(|| ContainerError::NotManaged(protocol.to_string()))
+    }
+
+    /// Start, stop, or restart `protocol`'s container
+    pub async fn run(&self, protocol: &str, action: ContainerAction) -> Result<(), ContainerError> {
+        let container = self.container_for(protocol)?;
+        #[cfg(feature = "docker")]
+        {
+            use bollard::container::{RestartContainerOptions, StartContainerOptions, StopContainerOptions};
+
+            let timeout = self.config.stop_timeout_secs;
+            match action {
+                ContainerAction::Start => {
+                    self.docker
+                        .start_container(container, None::<StartContainerOptions<String>>)
+                        .await?
+                }
+                ContainerAction::Stop => {
+                    let options = StopContainerOptions { t: timeout as i64 };
+                    self.docker.stop_container(container, Some(options)).await?
+                }
+                ContainerAction::Restart => {
+                    let options = RestartContainerOptions { t: timeout as isize };
+                    self.docker.restart_container(container, Some(options)).await?
+                }
+            }
+            tracing::info!("Container {} ({}): {:?}", container, protocol, action);
+            Ok(())
+        }
+        #[cfg(not(feature = "docker"))]
+        {
+            let _ = (container, action);
+            Err(ContainerError::Disabled)
+        }
+    }
+
+    /// Set the CPU and memory limits of `protocol`'s container
+    ///
+    /// Swap is capped at the memory limit, so the container cannot page
+    /// beyond its allocation.
+    pub async fn apply_limits(&self, protocol: &str, limits: ContainerLimits) -> Result<(), ContainerError> {
+        let container = self.container_for(protocol)?;
+        #[cfg(feature = "docker")]
+        {
+            use bollard::container::UpdateContainerOptions;
+
+            let options = UpdateContainerOptions::<String> {
+                nano_cp_us: limits.nano_cpus,
+                memory: limits.memory_bytes,
+                memory_swap: limits.memory_bytes,
+                ..Default::default()
+            };
+            self.docker.update_container(container, options).await?;
+            tracing::info!(
+                "Container {} ({}) limited to {:?} nano-CPUs, {:?} bytes",
+                container,
+                protocol,
+                limits.nano_cpus,
+                limits.memory_bytes
+            );
+            Ok(())
+        }
+        #[cfg(not(feature = "docker"))]
+        {
+            let _ = (container, limits);
+            Err(ContainerError::Disabled)
+        }
+    }
+
+    /// Current state of `protocol`'s container
+    pub async fn health(&self, protocol: &str) -> Result<ContainerHealth, ContainerError> {
+        let container = self.container_for(protocol)?;
+        #[cfg(feature = "docker")]
+        {
+            use bollard::container::InspectContainerOptions;
+
+            let inspect = self
+                .docker
+                .inspect_container(container, None::<InspectContainerOptions>)
+                .await?;
+            let state = inspect.state.unwrap_or_default();
+            Ok(ContainerHealth {
+                container: container.to_string(),
+                state: state.status.map_or_else(|| "unknown".to_string(), |s| s.to_string()),
+                running: state.running.unwrap_or(false),
+                health: state.health.and_then(|h| h.status).map(|s| s.to_string()),
+                restart_count: inspect.restart_count.unwrap_or(0),
+                started_at: state.started_at,
+            })
+        }
+        #[cfg(not(feature = "docker"))]
+        {
+            let _ = container;
+            Err(ContainerError::Disabled)
+        }
+    }
+}
+
+// ============================================================================
+// CONTAINERIZED ADAPTER
+// ============================================================================
+
+/// Adapter for a protocol whose node runs in a managed container
+///
+/// Applies each allocation's CPU and memory as container limits before
+/// handing it to the wrapped adapter, and reports the container's state in
+/// its health.
+pub struct ContainerizedAdapter {
+    inner: Box<dyn ProtocolAdapter>,
+    containers: Arc<ContainerManager>,
+    /// Protocol instance the adapter is registered as, keying its `containers` entry
+    instance: String,
+}
+
+impl ContainerizedAdapter {
+    /// Wrap `inner`, whose node runs in a container managed by `containers`, as
+    /// protocol instance `instance`
+    pub fn new(
+        inner: Box<dyn ProtocolAdapter>,
+        containers: Arc<ContainerManager>,
+        instance: impl Into<String>,
+    ) -> Self {
+        Self { inner, containers, instance: instance.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProtocolAdapter for ContainerizedAdapter {
+    fn protocol_name(&self) -> &str {
+        self.inner.protocol_name()
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        self.inner.disconnect().await
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.inner.connection_status()
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.inner.get_current_earnings().await
+    }
+
+    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        self.inner.get_historical_earnings(hours).await
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        self.inner.get_resource_usage().await
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        self.containers
+            .apply_limits(&self.instance, ContainerLimits::from(&strategy))
+            .await
+            .map_err(|e| ProtocolError::ResourceError(e.to_string()))?;
+        self.inner.apply_allocation(strategy).await
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        self.inner.get_current_allocation().await
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let mut status = self.inner.health_check().await?;
+        match self.containers.health(&self.instance).await {
+            Ok(container) => container.apply_to(&mut status),
+            Err(e) => {
+                tracing::warn!("Failed to inspect container of {}: {}", self.instance, e);
+                status.metrics.insert("container_error".to_string(), e.to_string().into());
+            }
+        }
+        Ok(status)
+    }
+
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        self.inner.check_credentials().await
+    }
+
+    fn configure_http(&mut self, http: &HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.inner.configure_http(http, protocol)
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        self.inner.get_config()
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        self.inner.resource_profile()
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        self.inner.metric_schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::basic_health_status;
+
+    #[test]
+    fn test_limits_follow_allocation_strategy() {
+        let strategy = AllocationStrategy {
+            cpu_cores: 2,
+            memory_gb: 1.5,
+            storage_gb: 500.0,
+            bandwidth_mbps: 100.0,
+            allocation_percent: 40.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+        let limits = ContainerLimits::from(&strategy);
+        assert_eq!(limits.nano_cpus, Some(2_000_000_000));
+        assert_eq!(limits.memory_bytes, Some(1_610_612_736));
+
+        // Unallocated resources keep their current limits
+        let unset = AllocationStrategy {
+            cpu_cores: 0,
+            memory_gb: 0.0,
+            ..strategy
+        };
+        assert_eq!(ContainerLimits::from(&unset), ContainerLimits::default());
+    }
+
+    #[test]
+    fn test_container_health_reported_in_adapter_health() {
+        let mut container = ContainerHealth {
+            container: "storagenode".to_string(),
+            state: "running".to_string(),
+            running: true,
+            health: Some("healthy".to_string()),
+            restart_count: 2,
+            started_at: None,
+        };
+        let mut status = basic_health_status(true, ConnectionStatus::Connected, None);
+        container.apply_to(&mut status);
+        assert!(status.is_healthy);
+        assert_eq!(status.metrics["container"]["restart_count"], 2);
+
+        container.health = Some("unhealthy".to_string());
+        container.apply_to(&mut status);
+        assert!(!status.is_healthy);
+        assert_eq!(status.error_message.as_deref(), Some("Container storagenode is unhealthy"));
+
+        container.running = false;
+        container.state = "exited".to_string();
+        let mut status = basic_health_status(true, ConnectionStatus::Connected, None);
+        container.apply_to(&mut status);
+        assert_eq!(status.error_message.as_deref(), Some("Container storagenode is exited"));
+    }
+
+    #[test]
+    fn test_container_config_validation() {
+        let config: ContainerConfig = toml::from_str(
+            r#"
+            enabled = true
+            [protocols]
+            storj = "storagenode"
+            golem = " "
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.stop_timeout_secs, 30);
+        let error = config.validate().unwrap_err();
+        if cfg!(feature = "docker") {
+            assert!(error.contains("protocols.golem"));
+        } else {
+            assert!(error.contains("docker feature"));
+            assert!(matches!(ContainerManager::connect(config), Err(ContainerError::Disabled)));
+        }
+        assert!(ContainerConfig::default().validate().is_ok());
+        assert_eq!("restart".parse::<ContainerAction>(), Ok(ContainerAction::Restart));
+        assert!("pause".parse::<ContainerAction>().is_err());
+    }
+}
@@ -1,20 +1,30 @@
 /// Database Module
 ///
 /// Database models, queries, and connection management for metrics persistence.
-
 pub mod models;
 pub mod queries;
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::migrate::Migrator;
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::time::Duration;
 use tracing::info;
 
+/// Account that owns rows recorded before accounts existed, and the local
+/// orchestrator unless it is assigned to another account
+pub const DEFAULT_ACCOUNT_ID: i64 = 1;
+
+/// Versioned schema migrations embedded from `migrations/`
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 // ============================================================================
 // DATABASE CONFIGURATION
 // ============================================================================
 
-/// Database configuration
-#[derive(Debug, Clone)]
+/// Database configuration (`[database]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DbConfig {
     pub database_url: String,
     pub max_connections: u32,
@@ -25,10 +35,10 @@ pub struct DbConfig {
 impl Default for DbConfig {
     fn default() -> Self {
         Self {
-            database_url: "sqlite:depin-orcha.db".to_string(),
+            database_url: "depin_orcha.db".to_string(),
             max_connections: 10,
             min_connections: 2,
-            connect_timeout: 30,
+            connect_timeout: 5,
         }
     }
 }
@@ -37,12 +47,21 @@ impl Default for DbConfig {
 // DATABASE INITIALIZATION
 // ============================================================================
 
-/// Initialize database connection pool
+/// Initialize database connection pool and apply pending migrations
 pub async fn init_pool(config: DbConfig) -> Result<SqlitePool, sqlx::Error> {
     info!("Initializing database: {}", config.database_url);
 
+    let pool = connect(&config).await?;
+    run_migrations(&pool).await?;
+
+    info!("✅ Database initialized successfully");
+    Ok(pool)
+}
+
+/// Open a connection pool without migrating
+pub async fn connect(config: &DbConfig) -> Result<SqlitePool, sqlx::Error> {
     // Create database file if it doesn't exist by opening with create flag
-    let pool = SqlitePoolOptions::new()
+    SqlitePoolOptions::new()
         .max_connections(config.max_connections)
         .min_connections(config.min_connections)
         .acquire_timeout(Duration::from_secs(config.connect_timeout))
@@ -50,93 +69,72 @@ pub async fn init_pool(config: DbConfig) -> Result<SqlitePool, sqlx::Error> {
             config.database_url.parse::<sqlx::sqlite::SqliteConnectOptions>()?
                 .create_if_missing(true)
         )
-        .await?;
+        .await
+}
 
-    // Run migrations
-    sqlx::migrate!("./migrations")
-        .run(&pool)
-        .await?;
+/// Apply pending migrations
+///
+/// The whole schema lives in `migrations/`; each file is applied once, in
+/// version order, and recorded in `_sqlx_migrations`.
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
 
-    info!("✅ Database initialized successfully");
-    Ok(pool)
+/// Migrated in-memory database for tests
+///
+/// One connection, so every query sees the same `sqlite::memory:` database.
+#[cfg(test)]
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in-memory database");
+    run_migrations(&pool).await.expect("migrations apply to an empty database");
+    pool
 }
 
-/// Create schema
-pub async fn create_schema(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-    info!("Creating database schema");
-
-    // Metrics table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS metrics (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp DATETIME NOT NULL,
-            total_earnings_per_hour REAL NOT NULL,
-            cpu_percent REAL,
-            memory_percent REAL,
-            bandwidth_percent REAL,
-            storage_percent REAL,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Protocol metrics table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS protocol_metrics (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            metrics_id INTEGER NOT NULL,
-            protocol_name TEXT NOT NULL,
-            earnings_per_hour REAL NOT NULL,
-            allocation_percent REAL NOT NULL,
-            connected BOOLEAN DEFAULT true,
-            FOREIGN KEY(metrics_id) REFERENCES metrics(id)
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Reallocations table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS reallocations (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp DATETIME NOT NULL,
-            protocol_name TEXT NOT NULL,
-            old_allocation REAL NOT NULL,
-            new_allocation REAL NOT NULL,
-            earnings_impact REAL,
-            reason TEXT,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Alerts table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS alerts (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp DATETIME NOT NULL,
-            alert_type TEXT NOT NULL,
-            severity REAL NOT NULL,
-            message TEXT NOT NULL,
-            acknowledged BOOLEAN DEFAULT false,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
+/// State of every known migration in the database behind `pool`, oldest first
+///
+/// Read-only: a database that was never migrated lists every migration as
+/// pending.
+pub async fn migration_status(pool: &SqlitePool) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+    let tracked: Option<(String,)> =
+        sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'")
+            .fetch_optional(pool)
+            .await?;
+    let applied: Vec<(i64, DateTime<Utc>, bool, Vec<u8>)> = if tracked.is_some() {
+        sqlx::query_as("SELECT version, installed_on, success, checksum FROM _sqlx_migrations")
+            .fetch_all(pool)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(MIGRATOR
+        .iter()
+        .map(|migration| {
+            let applied = applied.iter().find(|(version, ..)| *version == migration.version);
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                applied_at: applied.filter(|(_, _, success, _)| *success).map(|(_, at, ..)| *at),
+                modified: applied.is_some_and(|(.., checksum)| checksum.as_slice() != &*migration.checksum),
+            }
+        })
+        .collect())
+}
 
-    info!("✅ Schema created successfully");
-    Ok(())
+/// State of one migration in a database
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// When it was applied; None while pending
+    pub applied_at: Option<DateTime<Utc>>,
+    /// Applied from a file that has changed since
+    pub modified: bool,
 }
 
 #[cfg(test)]
@@ -160,4 +158,40 @@ mod tests {
         };
         assert_eq!(config.max_connections, 5);
     }
+
+    #[tokio::test]
+    async fn test_migrations_create_schema() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        let pending = migration_status(&pool).await.unwrap();
+        assert_eq!(pending.len(), MIGRATOR.iter().count());
+        assert!(pending.iter().all(|m| m.applied_at.is_none() && !m.modified));
+
+        run_migrations(&pool).await.unwrap();
+        let applied = migration_status(&pool).await.unwrap();
+        assert!(applied.iter().all(|m| m.applied_at.is_some() && !m.modified));
+        assert!(applied.windows(2).all(|w| w[0].version < w[1].version));
+
+        for table in ["metrics", "protocol_metrics", "alerts", "reallocations", "api_keys", "rate_limit_log"] {
+            let found: Option<(String,)> =
+                sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+                    .bind(table)
+                    .fetch_optional(&pool)
+                    .await
+                    .unwrap();
+            assert!(found.is_some(), "{} was not created", table);
+        }
+        let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('metrics')")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert!(columns.iter().any(|(name,)| name == "account_id"));
+
+        // Running again is a no-op
+        run_migrations(&pool).await.unwrap();
+    }
 }
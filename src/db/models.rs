@@ -1,8 +1,8 @@
 /// Database Models
 ///
 /// SQL models for database operations.
-
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
 // ============================================================================
@@ -46,6 +46,73 @@ impl MetricsRecord {
     }
 }
 
+/// Width of a metrics history bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricsInterval {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl MetricsInterval {
+    /// Bucket width in seconds
+    pub fn seconds(&self) -> i64 {
+        match self {
+            MetricsInterval::FiveMinutes => 300,
+            MetricsInterval::Hour => 3_600,
+            MetricsInterval::Day => 86_400,
+        }
+    }
+}
+
+/// Aggregate applied to the samples within a bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsAggregation {
+    #[default]
+    Avg,
+    Sum,
+    Min,
+    Max,
+}
+
+impl MetricsAggregation {
+    /// SQL aggregate function name
+    pub fn sql_function(&self) -> &'static str {
+        match self {
+            MetricsAggregation::Avg => "AVG",
+            MetricsAggregation::Sum => "SUM",
+            MetricsAggregation::Min => "MIN",
+            MetricsAggregation::Max => "MAX",
+        }
+    }
+}
+
+/// Aggregated metrics over one time bucket
+#[derive(Debug, Clone, FromRow)]
+pub struct MetricsBucketRecord {
+    /// Bucket start as a unix timestamp
+    pub bucket: i64,
+    pub samples: i64,
+    pub total_earnings_per_hour: f64,
+    pub cpu_percent: Option<f64>,
+    pub memory_percent: Option<f64>,
+    pub bandwidth_percent: Option<f64>,
+    pub storage_percent: Option<f64>,
+}
+
+/// Aggregated protocol earnings over one time bucket
+#[derive(Debug, Clone, FromRow)]
+pub struct ProtocolMetricsBucketRecord {
+    /// Bucket start as a unix timestamp
+    pub bucket: i64,
+    pub protocol_name: String,
+    pub earnings_per_hour: f64,
+}
+
 // ============================================================================
 // PROTOCOL METRICS MODELS
 // ============================================================================
@@ -81,6 +148,146 @@ impl ProtocolMetricsRecord {
     }
 }
 
+/// A protocol's allocation and earnings in one metrics snapshot
+#[derive(Debug, Clone, FromRow)]
+pub struct AllocationSampleRecord {
+    pub timestamp: String,
+    pub protocol_name: String,
+    pub earnings_per_hour: f64,
+    pub allocation_percent: f64,
+}
+
+/// A protocol's row of one metrics snapshot, with the snapshot's time and total
+#[derive(Debug, Clone, FromRow)]
+pub struct ProtocolSnapshotRecord {
+    pub timestamp: String,
+    pub total_earnings_per_hour: f64,
+    pub protocol_name: String,
+    pub earnings_per_hour: f64,
+    pub allocation_percent: f64,
+    pub connected: Option<bool>,
+}
+
+/// Protocol-specific metric value (e.g. a Storj satellite score)
+#[derive(Debug, Clone, FromRow)]
+pub struct ProtocolMetricValueRecord {
+    pub id: Option<i64>,
+    pub metrics_id: i64,
+    pub protocol_name: String,
+    pub name: String,
+    pub value: f64,
+    /// Unit declared in the adapter's metric schema
+    pub unit: String,
+    /// How the value combines over time (`sum`, `mean`, `min`, `max`, `last`)
+    pub aggregation: String,
+}
+
+impl ProtocolMetricValueRecord {
+    /// Create new protocol metric value record
+    pub fn new(
+        metrics_id: i64,
+        protocol_name: String,
+        name: String,
+        value: f64,
+        unit: String,
+        aggregation: String,
+    ) -> Self {
+        Self {
+            id: None,
+            metrics_id,
+            protocol_name,
+            name,
+            value,
+            unit,
+            aggregation,
+        }
+    }
+}
+
+/// Metrics snapshot and its per-protocol rows, written together
+///
+/// `metrics_id` of each protocol row and value is assigned when the snapshot
+/// is stored.
+#[derive(Debug, Clone)]
+pub struct MetricsSample {
+    pub account_id: i64,
+    pub metrics: MetricsRecord,
+    pub protocols: Vec<ProtocolMetricsRecord>,
+    pub values: Vec<ProtocolMetricValueRecord>,
+}
+
+/// Stretch of time a protocol was observed connected or disconnected
+#[derive(Debug, Clone, FromRow)]
+pub struct UptimeWindowRecord {
+    pub id: Option<i64>,
+    pub protocol_name: String,
+    pub connected: bool,
+    pub started_at: String,
+    /// Last poll that observed this state
+    pub ended_at: String,
+}
+
+impl UptimeWindowRecord {
+    /// Seconds of this window that fall inside `[since, until]`
+    pub fn overlap_secs(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> i64 {
+        let parse = |t: &str| DateTime::parse_from_rfc3339(t).ok().map(|t| t.with_timezone(&Utc));
+        match (parse(&self.started_at), parse(&self.ended_at)) {
+            (Some(start), Some(end)) => (end.min(until) - start.max(since)).num_seconds().max(0),
+            _ => 0,
+        }
+    }
+}
+
+/// Protocol instance and the protocol type it runs
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ProtocolInstanceRecord {
+    pub instance_id: String,
+    pub protocol_type: String,
+    pub first_seen_at: String,
+    /// Last time the orchestrator registered the instance
+    pub last_seen_at: String,
+}
+
+/// Latest earnings volatility of a protocol
+#[derive(Debug, Clone, FromRow)]
+pub struct ProtocolRiskRecord {
+    pub protocol_name: String,
+    pub computed_at: String,
+    /// Trailing hours the figures cover
+    pub window_hours: i64,
+    pub samples: i64,
+    pub mean_earnings: f64,
+    pub std_dev: f64,
+    pub max_drawdown_percent: f64,
+    pub risk_score: f64,
+}
+
+// ============================================================================
+// PAGINATION MODELS
+// ============================================================================
+
+/// Page of a newest-first listing
+///
+/// `before_id` continues from the last row of the previous page (a cursor) and
+/// is stable while rows are being added; `offset` skips rows after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub limit: i64,
+    pub offset: i64,
+    pub before_id: Option<i64>,
+}
+
+impl Page {
+    /// Cursor for the next page given this page's row count and last row id;
+    /// `None` once a page comes back short
+    pub fn next_cursor(&self, rows: usize, last_id: Option<i64>) -> Option<i64> {
+        if (rows as i64) < self.limit {
+            return None;
+        }
+        last_id
+    }
+}
+
 // ============================================================================
 // REALLOCATION MODELS
 // ============================================================================
@@ -95,6 +302,23 @@ pub struct ReallocationRecord {
     pub new_allocation: f64,
     pub earnings_impact: Option<f64>,
     pub reason: Option<String>,
+    /// Optimizer decision that made this change
+    pub decision_id: Option<i64>,
+    /// Change in total earnings (USD/hour) over the canary window after the
+    /// change against the window before it
+    pub realized_impact: Option<f64>,
+    /// `realized_impact` minus the predicted `earnings_impact`
+    pub impact_error: Option<f64>,
+    /// When the canary window was evaluated
+    pub evaluated_at: Option<String>,
+}
+
+/// Filters for listing reallocations; unset fields match every change
+#[derive(Debug, Clone, Default)]
+pub struct ReallocationFilter {
+    pub protocol: Option<String>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
 }
 
 impl ReallocationRecord {
@@ -115,10 +339,147 @@ impl ReallocationRecord {
             new_allocation,
             earnings_impact,
             reason,
+            decision_id: None,
+            realized_impact: None,
+            impact_error: None,
+            evaluated_at: None,
         }
     }
 }
 
+// ============================================================================
+// PENDING REALLOCATION MODELS
+// ============================================================================
+
+/// Lifecycle of a proposed reallocation awaiting confirmation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Superseded,
+    Expired,
+    Executed,
+    Failed,
+}
+
+impl PendingStatus {
+    /// Value stored in the `status` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PendingStatus::Pending => "pending",
+            PendingStatus::Approved => "approved",
+            PendingStatus::Rejected => "rejected",
+            PendingStatus::Superseded => "superseded",
+            PendingStatus::Expired => "expired",
+            PendingStatus::Executed => "executed",
+            PendingStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Pending reallocation record
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingReallocationRecord {
+    pub id: Option<i64>,
+    pub created_at: String,
+    pub status: String,
+    pub plan_json: String,
+    pub estimated_improvement: f64,
+    pub net_benefit: f64,
+    pub confidence: f64,
+    pub decided_at: Option<String>,
+    pub note: Option<String>,
+}
+
+impl PendingReallocationRecord {
+    /// Create new pending record for a proposed plan
+    pub fn new(plan: &crate::orchestration::AllocationPlan) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: None,
+            created_at: plan.created_at.to_rfc3339(),
+            status: PendingStatus::Pending.as_str().to_string(),
+            plan_json: serde_json::to_string(plan)?,
+            estimated_improvement: plan.estimated_improvement,
+            net_benefit: plan.net_benefit,
+            confidence: plan.confidence,
+            decided_at: None,
+            note: None,
+        })
+    }
+
+    /// Deserialize the stored plan
+    pub fn plan(&self) -> Result<crate::orchestration::AllocationPlan, serde_json::Error> {
+        serde_json::from_str(&self.plan_json)
+    }
+
+    /// Whether the plan still awaits a decision
+    pub fn is_pending(&self) -> bool {
+        self.status == PendingStatus::Pending.as_str()
+    }
+}
+
+// ============================================================================
+// OPTIMIZER DECISION MODELS
+// ============================================================================
+
+/// What became of a plan the optimizer considered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionOutcome {
+    Rejected,
+    PendingApproval,
+    Executing,
+    Executed,
+    Failed,
+}
+
+impl DecisionOutcome {
+    /// Value stored in the `outcome` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecisionOutcome::Rejected => "rejected",
+            DecisionOutcome::PendingApproval => "pending_approval",
+            DecisionOutcome::Executing => "executing",
+            DecisionOutcome::Executed => "executed",
+            DecisionOutcome::Failed => "failed",
+        }
+    }
+}
+
+/// Optimizer decision record
+#[derive(Debug, Clone, FromRow)]
+pub struct OptimizerDecisionRecord {
+    pub id: Option<i64>,
+    pub created_at: String,
+    pub outcome: String,
+    pub reason: Option<String>,
+    pub pending_reallocation_id: Option<i64>,
+    pub decision_json: String,
+}
+
+impl OptimizerDecisionRecord {
+    /// Create new decision record for `explanation`
+    pub fn new(
+        explanation: &crate::orchestration::optimizer::DecisionExplanation,
+        outcome: DecisionOutcome,
+        reason: Option<String>,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: None,
+            created_at: explanation.plan.created_at.to_rfc3339(),
+            outcome: outcome.as_str().to_string(),
+            reason,
+            pending_reallocation_id: None,
+            decision_json: serde_json::to_string(explanation)?,
+        })
+    }
+
+    /// Deserialize the stored explanation
+    pub fn explanation(&self) -> Result<crate::orchestration::optimizer::DecisionExplanation, serde_json::Error> {
+        serde_json::from_str(&self.decision_json)
+    }
+}
+
 // ============================================================================
 // ALERT MODELS
 // ============================================================================
@@ -132,6 +493,16 @@ pub struct AlertRecord {
     pub severity: f64,
     pub message: String,
     pub acknowledged: Option<bool>,
+    /// Identity of the condition (type + resource) used for deduplication
+    pub fingerprint: Option<String>,
+    /// Times the condition was observed while open
+    pub occurrences: Option<i64>,
+    pub last_seen_at: Option<String>,
+    /// Whether the condition has cleared
+    pub resolved: Option<bool>,
+    pub resolved_at: Option<String>,
+    /// Protocol the alert concerns, if any
+    pub protocol: Option<String>,
 }
 
 impl AlertRecord {
@@ -149,6 +520,279 @@ impl AlertRecord {
             severity,
             message,
             acknowledged: Some(false),
+            fingerprint: None,
+            occurrences: Some(1),
+            last_seen_at: Some(timestamp.to_rfc3339()),
+            resolved: Some(false),
+            resolved_at: None,
+            protocol: None,
+        }
+    }
+
+    /// Attach a deduplication fingerprint
+    pub fn with_fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Attach the protocol the alert concerns
+    pub fn with_protocol(mut self, protocol: Option<&str>) -> Self {
+        self.protocol = protocol.map(str::to_string);
+        self
+    }
+}
+
+/// Filters for listing alerts; unset fields match every alert
+#[derive(Debug, Clone, Default)]
+pub struct AlertFilter {
+    pub min_severity: Option<f64>,
+    pub acknowledged: Option<bool>,
+    pub protocol: Option<String>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// What recording an alert condition did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertOutcome {
+    /// New alert row created
+    Raised(i64),
+    /// Open alert seen again; only its counters were updated
+    Repeated(i64),
+    /// Alert resolved within the cooldown window was reopened
+    Reopened(i64),
+}
+
+// ============================================================================
+// EXPORT MODELS
+// ============================================================================
+
+/// Daily per-protocol earnings aggregate
+#[derive(Debug, Clone, FromRow)]
+pub struct DailyEarningsRecord {
+    /// Day as `YYYY-MM-DD` in the reporting time zone
+    pub day: String,
+    /// Length of the day, 23 or 25 across a DST change
+    pub hours: f64,
+    /// Protocol instance the earnings were recorded for
+    pub protocol_name: String,
+    /// Protocol type of the instance
+    pub protocol_type: String,
+    pub avg_earnings_per_hour: f64,
+    pub samples: i64,
+}
+
+/// One protocol's share of a metrics snapshot, as streamed by the metrics export
+///
+/// Snapshots without protocol rows appear once with the protocol fields unset.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MetricsExportRow {
+    pub timestamp: String,
+    pub total_earnings_per_hour: f64,
+    pub cpu_percent: Option<f64>,
+    pub memory_percent: Option<f64>,
+    pub bandwidth_percent: Option<f64>,
+    pub storage_percent: Option<f64>,
+    pub protocol_name: Option<String>,
+    pub earnings_per_hour: Option<f64>,
+    pub allocation_percent: Option<f64>,
+    pub connected: Option<bool>,
+}
+
+// ============================================================================
+// WALLET MODELS
+// ============================================================================
+
+/// Wallet balance record
+#[derive(Debug, Clone, FromRow)]
+pub struct WalletBalanceRecord {
+    pub id: Option<i64>,
+    pub label: String,
+    pub chain: String,
+    pub address: String,
+    pub protocol_name: Option<String>,
+    pub symbol: String,
+    pub balance: f64,
+    pub balance_usd: Option<f64>,
+    pub polled_at: String,
+}
+
+impl WalletBalanceRecord {
+    /// Create new wallet balance record
+    pub fn new(balance: &crate::wallets::WalletBalance) -> Self {
+        Self {
+            id: None,
+            label: balance.label.clone(),
+            chain: balance.chain.to_string(),
+            address: balance.address.clone(),
+            protocol_name: balance.protocol.clone(),
+            symbol: balance.symbol.clone(),
+            balance: balance.balance,
+            balance_usd: balance.balance_usd,
+            polled_at: balance.polled_at.to_rfc3339(),
+        }
+    }
+}
+
+// ============================================================================
+// NETWORK PROBE MODELS
+// ============================================================================
+
+/// Network quality measured by one probe
+#[derive(Debug, Clone, FromRow)]
+pub struct NetworkProbeRecord {
+    pub id: i64,
+    pub latency_ms: f64,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub measured_at: String,
+}
+
+// ============================================================================
+// REPORT MODELS
+// ============================================================================
+
+/// Stored performance report
+#[derive(Debug, Clone, FromRow)]
+pub struct ReportRecord {
+    pub id: Option<i64>,
+    pub generated_at: String,
+    pub period_start: String,
+    pub period_end: String,
+    pub total_earnings: f64,
+    pub report_json: String,
+}
+
+impl ReportRecord {
+    /// Create new report record
+    pub fn new(
+        report: &crate::orchestration::PerformanceReport,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: None,
+            generated_at: Utc::now().to_rfc3339(),
+            period_start: report.period_start.to_rfc3339(),
+            period_end: report.period_end.to_rfc3339(),
+            total_earnings: report.total_earnings,
+            report_json: serde_json::to_string(report)?,
+        })
+    }
+
+    /// Deserialize the stored report
+    pub fn report(&self) -> Result<crate::orchestration::PerformanceReport, serde_json::Error> {
+        serde_json::from_str(&self.report_json)
+    }
+}
+
+// ============================================================================
+// CREDENTIAL MODELS
+// ============================================================================
+
+/// Encrypted adapter credentials for one protocol instance
+///
+/// Only `ciphertext` holds secret values; `fields` lists the credential names
+/// so they can be shown without decrypting.
+#[derive(Debug, Clone, FromRow)]
+pub struct ProtocolCredentialRecord {
+    pub protocol: String,
+    pub fields: String,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub created_at: String,
+    pub rotated_at: String,
+}
+
+impl ProtocolCredentialRecord {
+    /// Credential field names
+    pub fn field_names(&self) -> Vec<String> {
+        serde_json::from_str(&self.fields).unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// ACCOUNT MODELS
+// ============================================================================
+
+/// Account owning API keys, credentials, and recorded data
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AccountRecord {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
+    pub is_active: bool,
+    /// Rate limit tier: `free`, `pro`, or `admin`
+    pub tier: String,
+}
+
+// ============================================================================
+// IDEMPOTENCY MODELS
+// ============================================================================
+
+/// Response remembered for an `Idempotency-Key`
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyRecord {
+    pub id: i64,
+    pub account_id: i64,
+    pub idempotency_key: String,
+    /// SHA-256 of the request's method, path, and body
+    pub request_hash: String,
+    pub created_at: String,
+    /// `None` while the original request is still being handled
+    pub status_code: Option<i64>,
+    pub content_type: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+}
+
+// ============================================================================
+// REQUEST LOG MODELS
+// ============================================================================
+
+/// Access log entry for one API request, with secrets already redacted
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RequestLogRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub timestamp: String,
+    pub request_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub status: i64,
+    pub latency_ms: f64,
+    pub api_key_id: Option<i64>,
+    pub account_id: Option<i64>,
+    /// Request headers as a JSON object
+    pub headers_json: String,
+    pub body: Option<String>,
+}
+
+// ============================================================================
+// NOTIFICATION RULE MODELS
+// ============================================================================
+
+/// Stored alert routing rule
+#[derive(Debug, Clone, FromRow)]
+pub struct NotificationRuleRecord {
+    pub id: i64,
+    pub alert_type: Option<String>,
+    pub min_severity: f64,
+    pub max_severity: f64,
+    /// Channel names as a JSON array
+    pub channels: String,
+    pub priority: i64,
+    pub created_at: String,
+}
+
+impl NotificationRuleRecord {
+    /// The routing rule this row stores
+    pub fn rule(&self) -> crate::notifications::routing::NotificationRule {
+        crate::notifications::routing::NotificationRule {
+            id: Some(self.id),
+            alert_type: self.alert_type.clone(),
+            min_severity: self.min_severity,
+            max_severity: self.max_severity,
+            channels: serde_json::from_str(&self.channels).unwrap_or_default(),
+            priority: self.priority,
         }
     }
 }
@@ -192,4 +836,41 @@ mod tests {
         assert_eq!(record.alert_type, "LOW_EARNINGS");
         assert_eq!(record.acknowledged, Some(false));
     }
+
+    #[test]
+    fn test_wallet_balance_record_creation() {
+        let now = Utc::now();
+        let record = WalletBalanceRecord::new(&crate::wallets::WalletBalance {
+            label: "helium".to_string(),
+            chain: crate::wallets::Chain::Solana,
+            address: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+            protocol: None,
+            symbol: "SOL".to_string(),
+            balance: 2.5,
+            balance_usd: Some(375.0),
+            polled_at: now,
+        });
+        assert_eq!(record.chain, "solana");
+        assert_eq!(record.polled_at, now.to_rfc3339());
+    }
+
+    #[test]
+    fn test_pending_reallocation_record_roundtrip() {
+        let plan = crate::orchestration::AllocationPlan {
+            allocation: [("storj".to_string(), 60.0), ("golem".to_string(), 40.0)]
+                .into_iter()
+                .collect(),
+            estimated_improvement: 1.5,
+            estimated_cost: 0.1,
+            net_benefit: 1.4,
+            roi_percent: 1400.0,
+            confidence: 0.85,
+            created_at: Utc::now(),
+        };
+
+        let record = PendingReallocationRecord::new(&plan).unwrap();
+        assert!(record.is_pending());
+        assert_eq!(record.net_benefit, 1.4);
+        assert_eq!(record.plan().unwrap().allocation["storj"], 60.0);
+    }
 }
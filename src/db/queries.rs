@@ -1,20 +1,23 @@
 /// Database Queries
 ///
 /// SQL query functions for metrics persistence and retrieval.
-
-use sqlx::SqlitePool;
-use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::stream::BoxStream;
 use std::collections::HashMap;
 
 use super::models::*;
+use crate::timezone::ReportingTimezone;
 
 // ============================================================================
 // METRICS QUERIES
 // ============================================================================
 
 /// Store metrics in database
+#[allow(clippy::too_many_arguments)]
 pub async fn store_metrics(
     pool: &SqlitePool,
+    account_id: i64,
     timestamp: DateTime<Utc>,
     total_earnings: f64,
     cpu: f64,
@@ -33,11 +36,12 @@ pub async fn store_metrics(
 
     let result = sqlx::query(
         r#"
-        INSERT INTO metrics 
-        (timestamp, total_earnings_per_hour, cpu_percent, memory_percent, bandwidth_percent, storage_percent)
-        VALUES (?, ?, ?, ?, ?, ?)
+        INSERT INTO metrics
+        (account_id, timestamp, total_earnings_per_hour, cpu_percent, memory_percent, bandwidth_percent, storage_percent)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
         "#,
     )
+    .bind(account_id)
     .bind(&record.timestamp)
     .bind(record.total_earnings_per_hour)
     .bind(record.cpu_percent)
@@ -77,13 +81,130 @@ pub async fn store_protocol_metrics(
     Ok(())
 }
 
+/// Snapshots per multi-row INSERT, keeping each statement well under
+/// SQLite's bound parameter limit
+const BATCH_INSERT_ROWS: usize = 500;
+
+/// Store metrics snapshots and their per-protocol rows in one transaction
+///
+/// Rows are written with multi-row INSERTs instead of one statement per row.
+pub async fn store_metrics_batch(pool: &SqlitePool, samples: &[MetricsSample]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for chunk in samples.chunks(BATCH_INSERT_ROWS) {
+        let mut insert = QueryBuilder::<Sqlite>::new(
+            "INSERT INTO metrics \
+             (account_id, timestamp, total_earnings_per_hour, cpu_percent, memory_percent, bandwidth_percent, storage_percent) ",
+        );
+        insert.push_values(chunk, |mut row, sample| {
+            row.push_bind(sample.account_id)
+                .push_bind(sample.metrics.timestamp.clone())
+                .push_bind(sample.metrics.total_earnings_per_hour)
+                .push_bind(sample.metrics.cpu_percent)
+                .push_bind(sample.metrics.memory_percent)
+                .push_bind(sample.metrics.bandwidth_percent)
+                .push_bind(sample.metrics.storage_percent);
+        });
+        insert.push(" RETURNING id");
+        // Ids are assigned in insertion order; RETURNING order is unspecified
+        let mut ids: Vec<i64> = insert.build_query_scalar().fetch_all(&mut *tx).await?;
+        ids.sort_unstable();
+
+        let protocol_rows: Vec<(i64, &ProtocolMetricsRecord)> = ids
+            .iter()
+            .zip(chunk)
+            .flat_map(|(id, sample)| sample.protocols.iter().map(move |p| (*id, p)))
+            .collect();
+        for rows in protocol_rows.chunks(BATCH_INSERT_ROWS) {
+            let mut insert = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO protocol_metrics \
+                 (metrics_id, protocol_name, earnings_per_hour, allocation_percent, connected) ",
+            );
+            insert.push_values(rows, |mut row, (metrics_id, protocol)| {
+                row.push_bind(*metrics_id)
+                    .push_bind(protocol.protocol_name.clone())
+                    .push_bind(protocol.earnings_per_hour)
+                    .push_bind(protocol.allocation_percent)
+                    .push_bind(protocol.connected);
+            });
+            insert.build().execute(&mut *tx).await?;
+        }
+
+        let value_rows: Vec<(i64, &ProtocolMetricValueRecord)> = ids
+            .iter()
+            .zip(chunk)
+            .flat_map(|(id, sample)| sample.values.iter().map(move |v| (*id, v)))
+            .collect();
+        for rows in value_rows.chunks(BATCH_INSERT_ROWS) {
+            let mut insert = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO protocol_metric_values (metrics_id, protocol_name, name, value, unit, aggregation) ",
+            );
+            insert.push_values(rows, |mut row, (metrics_id, value)| {
+                row.push_bind(*metrics_id)
+                    .push_bind(value.protocol_name.clone())
+                    .push_bind(value.name.clone())
+                    .push_bind(value.value)
+                    .push_bind(value.unit.clone())
+                    .push_bind(value.aggregation.clone());
+            });
+            insert.build().execute(&mut *tx).await?;
+        }
+    }
+
+    tx.commit().await
+}
+
+/// Store imported hourly earnings, one metrics row per `timestamp`
+///
+/// Resource columns are left empty since protocol APIs only report earnings.
+pub async fn store_historical_metrics(
+    pool: &SqlitePool,
+    account_id: i64,
+    timestamp: DateTime<Utc>,
+    earnings_by_protocol: &HashMap<String, f64>,
+    allocation_by_protocol: &HashMap<String, f64>,
+) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let metrics_id = sqlx::query(
+        "INSERT INTO metrics (account_id, timestamp, total_earnings_per_hour) VALUES (?, ?, ?)",
+    )
+    .bind(account_id)
+    .bind(timestamp.to_rfc3339())
+    .bind(earnings_by_protocol.values().sum::<f64>())
+    .execute(&mut *tx)
+    .await?
+    .last_insert_rowid();
+
+    for (protocol, earnings) in earnings_by_protocol {
+        sqlx::query(
+            r#"
+            INSERT INTO protocol_metrics
+            (metrics_id, protocol_name, earnings_per_hour, allocation_percent, connected)
+            VALUES (?, ?, ?, ?, true)
+            "#,
+        )
+        .bind(metrics_id)
+        .bind(protocol)
+        .bind(earnings)
+        .bind(allocation_by_protocol.get(protocol).copied().unwrap_or(0.0))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(metrics_id)
+}
+
 /// Get latest metrics
 pub async fn get_latest_metrics(
     pool: &SqlitePool,
+    account_id: i64,
 ) -> Result<Option<MetricsRecord>, sqlx::Error> {
     sqlx::query_as::<_, MetricsRecord>(
-        "SELECT * FROM metrics ORDER BY timestamp DESC LIMIT 1"
+        "SELECT * FROM metrics WHERE account_id = ? ORDER BY timestamp DESC LIMIT 1"
     )
+    .bind(account_id)
     .fetch_optional(pool)
     .await
 }
@@ -91,11 +212,13 @@ pub async fn get_latest_metrics(
 /// Get metrics history (last N records)
 pub async fn get_metrics_history(
     pool: &SqlitePool,
+    account_id: i64,
     limit: i64,
 ) -> Result<Vec<MetricsRecord>, sqlx::Error> {
     sqlx::query_as::<_, MetricsRecord>(
-        "SELECT * FROM metrics ORDER BY timestamp DESC LIMIT ?"
+        "SELECT * FROM metrics WHERE account_id = ? ORDER BY timestamp DESC LIMIT ?"
     )
+    .bind(account_id)
     .bind(limit)
     .fetch_all(pool)
     .await
@@ -104,49 +227,319 @@ pub async fn get_metrics_history(
 /// Get metrics by time range
 pub async fn get_metrics_by_range(
     pool: &SqlitePool,
+    account_id: i64,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
 ) -> Result<Vec<MetricsRecord>, sqlx::Error> {
     sqlx::query_as::<_, MetricsRecord>(
         r#"
-        SELECT * FROM metrics 
-        WHERE timestamp BETWEEN ? AND ?
+        SELECT * FROM metrics
+        WHERE account_id = ? AND timestamp BETWEEN ? AND ?
         ORDER BY timestamp DESC
         "#,
     )
+    .bind(account_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Average total earnings (USD/hour) of the snapshots recorded in `[start, end)`
+pub async fn get_average_earnings(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<f64>, sqlx::Error> {
+    let result: (Option<f64>,) = sqlx::query_as(
+        "SELECT AVG(total_earnings_per_hour) FROM metrics WHERE account_id = ? AND timestamp >= ? AND timestamp < ?",
+    )
+    .bind(account_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.0)
+}
+
+/// Get the per-protocol rows of metrics recorded between `start` and `end`
+pub async fn get_protocol_metrics_by_range(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<ProtocolMetricsRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolMetricsRecord>(
+        r#"
+        SELECT pm.* FROM protocol_metrics pm
+        JOIN metrics m ON m.id = pm.metrics_id
+        WHERE m.account_id = ? AND m.timestamp BETWEEN ? AND ?
+        "#,
+    )
+    .bind(account_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the per-protocol rows of the latest metrics snapshot recorded at or
+/// before `at`; empty when there is none
+pub async fn get_protocol_snapshot_at(
+    pool: &SqlitePool,
+    account_id: i64,
+    at: DateTime<Utc>,
+) -> Result<Vec<ProtocolSnapshotRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolSnapshotRecord>(
+        r#"
+        SELECT m.timestamp, m.total_earnings_per_hour, pm.protocol_name, pm.earnings_per_hour,
+               pm.allocation_percent, pm.connected
+        FROM protocol_metrics pm
+        JOIN metrics m ON m.id = pm.metrics_id
+        WHERE m.id = (
+            SELECT id FROM metrics WHERE account_id = ? AND timestamp <= ? ORDER BY timestamp DESC LIMIT 1
+        )
+        ORDER BY pm.protocol_name
+        "#,
+    )
+    .bind(account_id)
+    .bind(at.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Get each protocol's allocation samples recorded between `start` and `end`,
+/// oldest first, preceded by its latest sample from before `start`
+///
+/// `protocol_name` restricts the samples to one protocol.
+pub async fn get_allocation_samples(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol_name: Option<&str>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<AllocationSampleRecord>, sqlx::Error> {
+    // SQLite takes the bare columns of a MAX() aggregate from the row holding the maximum
+    sqlx::query_as::<_, AllocationSampleRecord>(
+        r#"
+        SELECT MAX(m.timestamp) AS timestamp, pm.protocol_name, pm.earnings_per_hour, pm.allocation_percent
+        FROM protocol_metrics pm
+        JOIN metrics m ON m.id = pm.metrics_id
+        WHERE m.account_id = ?1 AND (?2 IS NULL OR pm.protocol_name = ?2) AND m.timestamp < ?3
+        GROUP BY pm.protocol_name
+        UNION ALL
+        SELECT m.timestamp, pm.protocol_name, pm.earnings_per_hour, pm.allocation_percent
+        FROM protocol_metrics pm
+        JOIN metrics m ON m.id = pm.metrics_id
+        WHERE m.account_id = ?1 AND (?2 IS NULL OR pm.protocol_name = ?2) AND m.timestamp BETWEEN ?3 AND ?4
+        ORDER BY timestamp
+        "#,
+    )
+    .bind(account_id)
+    .bind(protocol_name)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Get protocol-specific metric values of `protocol_name` stored between
+/// `start` and `end`, oldest first
+pub async fn get_protocol_metric_values_by_range(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol_name: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<ProtocolMetricValueRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolMetricValueRecord>(
+        r#"
+        SELECT v.* FROM protocol_metric_values v
+        JOIN metrics m ON m.id = v.metrics_id
+        WHERE m.account_id = ? AND v.protocol_name = ? AND m.timestamp BETWEEN ? AND ?
+        ORDER BY m.timestamp, v.name
+        "#,
+    )
+    .bind(account_id)
+    .bind(protocol_name)
     .bind(start.to_rfc3339())
     .bind(end.to_rfc3339())
     .fetch_all(pool)
     .await
 }
 
+/// Get metrics recorded before `cutoff`, oldest first
+pub async fn get_metrics_before(
+    pool: &SqlitePool,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<MetricsRecord>, sqlx::Error> {
+    sqlx::query_as::<_, MetricsRecord>("SELECT * FROM metrics WHERE timestamp < ? ORDER BY timestamp")
+        .bind(cutoff.to_rfc3339())
+        .fetch_all(pool)
+        .await
+}
+
+/// Get the per-protocol rows of metrics recorded before `cutoff`
+pub async fn get_protocol_metrics_before(
+    pool: &SqlitePool,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<ProtocolMetricsRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolMetricsRecord>(
+        r#"
+        SELECT * FROM protocol_metrics
+        WHERE metrics_id IN (SELECT id FROM metrics WHERE timestamp < ?)
+        ORDER BY metrics_id, id
+        "#,
+    )
+    .bind(cutoff.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Names of every protocol with recorded metrics
+pub async fn get_protocol_names(pool: &SqlitePool, account_id: i64) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT pm.protocol_name FROM protocol_metrics pm
+        JOIN metrics m ON m.id = pm.metrics_id
+        WHERE m.account_id = ?
+        ORDER BY pm.protocol_name
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete metrics recorded before `cutoff` along with their per-protocol rows
+///
+/// Returns the number of `metrics` rows deleted.
+pub async fn delete_metrics_before(
+    pool: &SqlitePool,
+    cutoff: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let cutoff = cutoff.to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    for child in ["protocol_metrics", "protocol_metric_values"] {
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE metrics_id IN (SELECT id FROM metrics WHERE timestamp < ?)",
+            child
+        ))
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await?;
+    }
+    let deleted = sqlx::query("DELETE FROM metrics WHERE timestamp < ?")
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Get metrics since `start` aggregated into `interval` buckets, oldest first
+pub async fn get_bucketed_metrics(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    interval: MetricsInterval,
+    aggregation: MetricsAggregation,
+) -> Result<Vec<MetricsBucketRecord>, sqlx::Error> {
+    let agg = aggregation.sql_function();
+    let sql = format!(
+        r#"
+        SELECT
+            (CAST(strftime('%s', timestamp) AS INTEGER) / ?1) * ?1 AS bucket,
+            COUNT(*) AS samples,
+            {agg}(total_earnings_per_hour) AS total_earnings_per_hour,
+            {agg}(cpu_percent) AS cpu_percent,
+            {agg}(memory_percent) AS memory_percent,
+            {agg}(bandwidth_percent) AS bandwidth_percent,
+            {agg}(storage_percent) AS storage_percent
+        FROM metrics
+        WHERE timestamp >= ?2 AND account_id = ?3
+        GROUP BY bucket
+        ORDER BY bucket
+        "#,
+        agg = agg
+    );
+
+    sqlx::query_as::<_, MetricsBucketRecord>(&sql)
+        .bind(interval.seconds())
+        .bind(start.to_rfc3339())
+        .bind(account_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Get protocol earnings since `start` aggregated into `interval` buckets
+pub async fn get_bucketed_protocol_metrics(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    interval: MetricsInterval,
+    aggregation: MetricsAggregation,
+) -> Result<Vec<ProtocolMetricsBucketRecord>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        SELECT
+            (CAST(strftime('%s', m.timestamp) AS INTEGER) / ?1) * ?1 AS bucket,
+            pm.protocol_name,
+            {agg}(pm.earnings_per_hour) AS earnings_per_hour
+        FROM protocol_metrics pm
+        JOIN metrics m ON m.id = pm.metrics_id
+        WHERE m.timestamp >= ?2 AND m.account_id = ?3
+        GROUP BY bucket, pm.protocol_name
+        ORDER BY bucket, pm.protocol_name
+        "#,
+        agg = aggregation.sql_function()
+    );
+
+    sqlx::query_as::<_, ProtocolMetricsBucketRecord>(&sql)
+        .bind(interval.seconds())
+        .bind(start.to_rfc3339())
+        .bind(account_id)
+        .fetch_all(pool)
+        .await
+}
+
 // ============================================================================
 // REALLOCATION QUERIES
 // ============================================================================
 
 /// Store reallocation
+#[allow(clippy::too_many_arguments)]
 pub async fn store_reallocation(
     pool: &SqlitePool,
+    account_id: i64,
     timestamp: DateTime<Utc>,
     protocol_name: String,
     old_allocation: f64,
     new_allocation: f64,
     earnings_impact: Option<f64>,
     reason: Option<String>,
+    decision_id: Option<i64>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        INSERT INTO reallocations 
-        (timestamp, protocol_name, old_allocation, new_allocation, earnings_impact, reason)
-        VALUES (?, ?, ?, ?, ?, ?)
+        INSERT INTO reallocations
+        (account_id, timestamp, protocol_name, old_allocation, new_allocation, earnings_impact, reason, decision_id)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
+    .bind(account_id)
     .bind(timestamp.to_rfc3339())
     .bind(protocol_name)
     .bind(old_allocation)
     .bind(new_allocation)
     .bind(earnings_impact)
     .bind(reason)
+    .bind(decision_id)
     .execute(pool)
     .await?;
 
@@ -156,24 +549,79 @@ pub async fn store_reallocation(
 /// Get reallocation history
 pub async fn get_reallocation_history(
     pool: &SqlitePool,
+    account_id: i64,
     limit: i64,
 ) -> Result<Vec<ReallocationRecord>, sqlx::Error> {
     sqlx::query_as::<_, ReallocationRecord>(
-        "SELECT * FROM reallocations ORDER BY timestamp DESC LIMIT ?"
+        "SELECT * FROM reallocations WHERE account_id = ? ORDER BY timestamp DESC LIMIT ?"
     )
+    .bind(account_id)
     .bind(limit)
     .fetch_all(pool)
     .await
 }
 
+/// List reallocations matching `filter`, newest first
+pub async fn list_reallocations(
+    pool: &SqlitePool,
+    account_id: i64,
+    filter: &ReallocationFilter,
+    page: Page,
+) -> Result<Vec<ReallocationRecord>, sqlx::Error> {
+    let mut query = QueryBuilder::new("SELECT * FROM reallocations");
+    push_reallocation_filter(&mut query, account_id, filter);
+    push_page(&mut query, page);
+    query.build_query_as().fetch_all(pool).await
+}
+
+/// Count reallocations matching `filter`
+pub async fn count_reallocations(
+    pool: &SqlitePool,
+    account_id: i64,
+    filter: &ReallocationFilter,
+) -> Result<i64, sqlx::Error> {
+    let mut query = QueryBuilder::new("SELECT COUNT(*) FROM reallocations");
+    push_reallocation_filter(&mut query, account_id, filter);
+    let (count,): (i64,) = query.build_query_as().fetch_one(pool).await?;
+    Ok(count)
+}
+
+/// Append the WHERE clause selecting an account's reallocations by `filter`
+fn push_reallocation_filter(query: &mut QueryBuilder<'_, Sqlite>, account_id: i64, filter: &ReallocationFilter) {
+    query.push(" WHERE account_id = ").push_bind(account_id);
+    if let Some(protocol) = &filter.protocol {
+        query.push(" AND protocol_name = ").push_bind(protocol.clone());
+    }
+    push_time_range(query, filter.start, filter.end);
+}
+
+/// Get reallocations recorded between `start` and `end`, oldest first
+pub async fn get_reallocations_by_range(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<ReallocationRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ReallocationRecord>(
+        "SELECT * FROM reallocations WHERE account_id = ? AND timestamp BETWEEN ? AND ? ORDER BY timestamp, id"
+    )
+    .bind(account_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
 /// Get reallocation count by protocol
 pub async fn get_reallocation_count(
     pool: &SqlitePool,
+    account_id: i64,
     protocol: &str,
 ) -> Result<i64, sqlx::Error> {
     let result: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM reallocations WHERE protocol_name = ?"
+        "SELECT COUNT(*) FROM reallocations WHERE account_id = ? AND protocol_name = ?"
     )
+    .bind(account_id)
     .bind(protocol)
     .fetch_one(pool)
     .await?;
@@ -181,125 +629,1409 @@ pub async fn get_reallocation_count(
     Ok(result.0)
 }
 
-// ============================================================================
-// ALERT QUERIES
-// ============================================================================
-
-/// Store alert
-pub async fn store_alert(
+/// Get reallocations with a predicted impact, made at or before `before`,
+/// whose outcome has not been evaluated, oldest first
+pub async fn get_unevaluated_reallocations(
     pool: &SqlitePool,
-    timestamp: DateTime<Utc>,
-    alert_type: String,
-    severity: f64,
-    message: String,
-) -> Result<(), sqlx::Error> {
-    sqlx::query(
+    account_id: i64,
+    before: DateTime<Utc>,
+) -> Result<Vec<ReallocationRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ReallocationRecord>(
         r#"
-        INSERT INTO alerts 
-        (timestamp, alert_type, severity, message)
-        VALUES (?, ?, ?, ?)
+        SELECT * FROM reallocations
+        WHERE account_id = ? AND evaluated_at IS NULL AND earnings_impact IS NOT NULL AND timestamp <= ?
+        ORDER BY timestamp, id
         "#,
     )
-    .bind(timestamp.to_rfc3339())
-    .bind(alert_type)
-    .bind(severity)
-    .bind(message)
-    .execute(pool)
-    .await?;
+    .bind(account_id)
+    .bind(before.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Record the realized impact of reallocation `id` and its error against the
+/// prediction; both are `None` when there was no data to compare
+pub async fn record_reallocation_outcome(
+    pool: &SqlitePool,
+    id: i64,
+    realized_impact: Option<f64>,
+    impact_error: Option<f64>,
+    evaluated_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE reallocations SET realized_impact = ?, impact_error = ?, evaluated_at = ? WHERE id = ?")
+        .bind(realized_impact)
+        .bind(impact_error)
+        .bind(evaluated_at.to_rfc3339())
+        .bind(id)
+        .execute(pool)
+        .await?;
 
     Ok(())
 }
 
-/// Get alert history
-pub async fn get_alert_history(
+/// Get the `limit` most recent reallocations with a measured impact error
+pub async fn get_evaluated_reallocations(
     pool: &SqlitePool,
+    account_id: i64,
     limit: i64,
-) -> Result<Vec<AlertRecord>, sqlx::Error> {
-    sqlx::query_as::<_, AlertRecord>(
-        "SELECT * FROM alerts ORDER BY timestamp DESC LIMIT ?"
+) -> Result<Vec<ReallocationRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ReallocationRecord>(
+        r#"
+        SELECT * FROM reallocations
+        WHERE account_id = ? AND impact_error IS NOT NULL
+        ORDER BY timestamp DESC, id DESC
+        LIMIT ?
+        "#,
     )
+    .bind(account_id)
     .bind(limit)
     .fetch_all(pool)
     .await
 }
 
-/// Acknowledge alert
-pub async fn acknowledge_alert(
+// ============================================================================
+// PENDING REALLOCATION QUERIES
+// ============================================================================
+
+/// Queue a proposed plan for confirmation, superseding older pending plans
+pub async fn store_pending_reallocation(
     pool: &SqlitePool,
-    timestamp: DateTime<Utc>,
-) -> Result<(), sqlx::Error> {
+    account_id: i64,
+    record: &PendingReallocationRecord,
+) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
     sqlx::query(
-        "UPDATE alerts SET acknowledged = true WHERE timestamp = ?"
+        "UPDATE pending_reallocations SET status = ?, decided_at = ? WHERE account_id = ? AND status = ?",
     )
-    .bind(timestamp.to_rfc3339())
-    .execute(pool)
+    .bind(PendingStatus::Superseded.as_str())
+    .bind(Utc::now().to_rfc3339())
+    .bind(account_id)
+    .bind(PendingStatus::Pending.as_str())
+    .execute(&mut *tx)
     .await?;
 
-    Ok(())
+    let result = sqlx::query(
+        r#"
+        INSERT INTO pending_reallocations
+        (account_id, created_at, status, plan_json, estimated_improvement, net_benefit, confidence)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(&record.created_at)
+    .bind(&record.status)
+    .bind(&record.plan_json)
+    .bind(record.estimated_improvement)
+    .bind(record.net_benefit)
+    .bind(record.confidence)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(result.last_insert_rowid())
 }
 
-/// Get unacknowledged alerts
-pub async fn get_unacknowledged_alerts(
+/// Get plans awaiting confirmation, newest first
+pub async fn get_pending_reallocations(
     pool: &SqlitePool,
-) -> Result<Vec<AlertRecord>, sqlx::Error> {
-    sqlx::query_as::<_, AlertRecord>(
-        "SELECT * FROM alerts WHERE acknowledged = false ORDER BY timestamp DESC"
+    account_id: i64,
+) -> Result<Vec<PendingReallocationRecord>, sqlx::Error> {
+    sqlx::query_as::<_, PendingReallocationRecord>(
+        "SELECT * FROM pending_reallocations WHERE account_id = ? AND status = ? ORDER BY created_at DESC",
     )
+    .bind(account_id)
+    .bind(PendingStatus::Pending.as_str())
     .fetch_all(pool)
     .await
 }
 
-// ============================================================================
-// STATISTICS QUERIES
-// ============================================================================
+/// Get a queued plan by ID
+pub async fn get_pending_reallocation(
+    pool: &SqlitePool,
+    account_id: i64,
+    id: i64,
+) -> Result<Option<PendingReallocationRecord>, sqlx::Error> {
+    sqlx::query_as::<_, PendingReallocationRecord>(
+        "SELECT * FROM pending_reallocations WHERE account_id = ? AND id = ?",
+    )
+    .bind(account_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
 
-/// Get total earnings for period
-pub async fn get_total_earnings(
+/// Move a queued plan from `from` to `to`; returns false if it was not in `from`
+pub async fn transition_pending_reallocation(
     pool: &SqlitePool,
-    start: DateTime<Utc>,
-    end: DateTime<Utc>,
-) -> Result<f64, sqlx::Error> {
-    let result: (Option<f64>,) = sqlx::query_as(
+    account_id: i64,
+    id: i64,
+    from: PendingStatus,
+    to: PendingStatus,
+    note: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
         r#"
-        SELECT SUM(total_earnings_per_hour) FROM metrics 
-        WHERE timestamp BETWEEN ? AND ?
+        UPDATE pending_reallocations
+        SET status = ?, decided_at = ?, note = COALESCE(?, note)
+        WHERE account_id = ? AND id = ? AND status = ?
         "#,
     )
-    .bind(start.to_rfc3339())
-    .bind(end.to_rfc3339())
-    .fetch_one(pool)
+    .bind(to.as_str())
+    .bind(Utc::now().to_rfc3339())
+    .bind(note)
+    .bind(account_id)
+    .bind(id)
+    .bind(from.as_str())
+    .execute(pool)
     .await?;
 
-    Ok(result.0.unwrap_or(0.0))
+    Ok(result.rows_affected() == 1)
 }
 
-/// Get average uptime
-pub async fn get_average_uptime(
+// ============================================================================
+// OPTIMIZER DECISION QUERIES
+// ============================================================================
+
+/// Store an optimizer decision; returns its ID
+pub async fn store_optimizer_decision(
     pool: &SqlitePool,
-    start: DateTime<Utc>,
-    end: DateTime<Utc>,
-) -> Result<f64, sqlx::Error> {
-    let total: i64 = sqlx::query_scalar(
+    account_id: i64,
+    record: &OptimizerDecisionRecord,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
         r#"
-        SELECT COUNT(*) FROM metrics 
-        WHERE timestamp BETWEEN ? AND ?
+        INSERT INTO optimizer_decisions
+        (account_id, created_at, outcome, reason, pending_reallocation_id, decision_json)
+        VALUES (?, ?, ?, ?, ?, ?)
         "#,
     )
-    .bind(start.to_rfc3339())
-    .bind(end.to_rfc3339())
-    .fetch_one(pool)
+    .bind(account_id)
+    .bind(&record.created_at)
+    .bind(&record.outcome)
+    .bind(&record.reason)
+    .bind(record.pending_reallocation_id)
+    .bind(&record.decision_json)
+    .execute(pool)
     .await?;
 
-    if total == 0 {
-        return Ok(0.0);
-    }
+    Ok(result.last_insert_rowid())
+}
 
-    // Calculate uptime based on successful metric collection
-    // In a real system, this would be more sophisticated
-    Ok(100.0)
+/// Get an optimizer decision by ID
+pub async fn get_optimizer_decision(
+    pool: &SqlitePool,
+    account_id: i64,
+    id: i64,
+) -> Result<Option<OptimizerDecisionRecord>, sqlx::Error> {
+    sqlx::query_as::<_, OptimizerDecisionRecord>(
+        "SELECT * FROM optimizer_decisions WHERE account_id = ? AND id = ?",
+    )
+    .bind(account_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
 }
 
-#[cfg(test)]
+/// Get the decision that queued pending reallocation `pending_id`
+pub async fn get_optimizer_decision_for_pending(
+    pool: &SqlitePool,
+    account_id: i64,
+    pending_id: i64,
+) -> Result<Option<OptimizerDecisionRecord>, sqlx::Error> {
+    sqlx::query_as::<_, OptimizerDecisionRecord>(
+        "SELECT * FROM optimizer_decisions WHERE account_id = ? AND pending_reallocation_id = ? ORDER BY id DESC",
+    )
+    .bind(account_id)
+    .bind(pending_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record what became of a decision; `reason` replaces the stored one when set
+pub async fn set_optimizer_decision_outcome(
+    pool: &SqlitePool,
+    account_id: i64,
+    id: i64,
+    outcome: DecisionOutcome,
+    reason: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE optimizer_decisions SET outcome = ?, reason = COALESCE(?, reason) WHERE account_id = ? AND id = ?",
+    )
+    .bind(outcome.as_str())
+    .bind(reason)
+    .bind(account_id)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+// ============================================================================
+// ALERT QUERIES
+// ============================================================================
+
+/// Store alert
+pub async fn store_alert(
+    pool: &SqlitePool,
+    account_id: i64,
+    timestamp: DateTime<Utc>,
+    alert_type: String,
+    severity: f64,
+    message: String,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO alerts
+        (account_id, timestamp, alert_type, severity, message)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(timestamp.to_rfc3339())
+    .bind(alert_type)
+    .bind(severity)
+    .bind(message)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record an active alert condition, deduplicated by fingerprint
+///
+/// An open alert with the same fingerprint is updated in place. An alert that
+/// resolved less than `cooldown` ago is reopened rather than raised again,
+/// so a flapping condition does not flood the table.
+#[allow(clippy::too_many_arguments)]
+pub async fn record_alert_condition(
+    pool: &SqlitePool,
+    account_id: i64,
+    fingerprint: &str,
+    protocol: Option<&str>,
+    alert_type: &str,
+    severity: f64,
+    message: &str,
+    cooldown: chrono::Duration,
+) -> Result<AlertOutcome, sqlx::Error> {
+    let now = Utc::now();
+
+    let latest: Option<(i64, bool, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT id, resolved, resolved_at FROM alerts
+        WHERE account_id = ? AND fingerprint = ?
+        ORDER BY id DESC LIMIT 1
+        "#,
+    )
+    .bind(account_id)
+    .bind(fingerprint)
+    .fetch_optional(pool)
+    .await?;
+
+    let reopen = |resolved_at: &Option<String>| {
+        resolved_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|t| now - t.with_timezone(&Utc) < cooldown)
+    };
+
+    match latest {
+        Some((id, resolved, resolved_at)) if !resolved || reopen(&resolved_at) => {
+            sqlx::query(
+                r#"
+                UPDATE alerts
+                SET occurrences = occurrences + 1, last_seen_at = ?,
+                    severity = MAX(severity, ?), message = ?,
+                    resolved = false, resolved_at = NULL
+                WHERE id = ?
+                "#,
+            )
+            .bind(now.to_rfc3339())
+            .bind(severity)
+            .bind(message)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+            Ok(if resolved {
+                AlertOutcome::Reopened(id)
+            } else {
+                AlertOutcome::Repeated(id)
+            })
+        }
+        _ => {
+            let record = AlertRecord::new(now, alert_type.to_string(), severity, message.to_string())
+                .with_fingerprint(fingerprint)
+                .with_protocol(protocol);
+            let result = sqlx::query(
+                r#"
+                INSERT INTO alerts
+                (account_id, timestamp, alert_type, severity, message, fingerprint, last_seen_at, protocol)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(account_id)
+            .bind(&record.timestamp)
+            .bind(&record.alert_type)
+            .bind(record.severity)
+            .bind(&record.message)
+            .bind(&record.fingerprint)
+            .bind(&record.last_seen_at)
+            .bind(&record.protocol)
+            .execute(pool)
+            .await?;
+
+            Ok(AlertOutcome::Raised(result.last_insert_rowid()))
+        }
+    }
+}
+
+/// Mark the open alert for a fingerprint as resolved; returns false if none was open
+pub async fn resolve_alert_condition(
+    pool: &SqlitePool,
+    account_id: i64,
+    fingerprint: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE alerts SET resolved = true, resolved_at = ?
+        WHERE account_id = ? AND fingerprint = ? AND resolved = false
+        "#,
+    )
+    .bind(Utc::now().to_rfc3339())
+    .bind(account_id)
+    .bind(fingerprint)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get alert history
+pub async fn get_alert_history(
+    pool: &SqlitePool,
+    account_id: i64,
+    limit: i64,
+) -> Result<Vec<AlertRecord>, sqlx::Error> {
+    sqlx::query_as::<_, AlertRecord>(
+        "SELECT * FROM alerts WHERE account_id = ? ORDER BY timestamp DESC LIMIT ?"
+    )
+    .bind(account_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// List alerts matching `filter`, newest first
+pub async fn list_alerts(
+    pool: &SqlitePool,
+    account_id: i64,
+    filter: &AlertFilter,
+    page: Page,
+) -> Result<Vec<AlertRecord>, sqlx::Error> {
+    let mut query = QueryBuilder::new("SELECT * FROM alerts");
+    push_alert_filter(&mut query, account_id, filter);
+    push_page(&mut query, page);
+    query.build_query_as().fetch_all(pool).await
+}
+
+/// Count alerts matching `filter`, and how many of them are at or above
+/// `critical_severity`
+pub async fn count_alerts(
+    pool: &SqlitePool,
+    account_id: i64,
+    filter: &AlertFilter,
+    critical_severity: f64,
+) -> Result<(i64, i64), sqlx::Error> {
+    let mut query = QueryBuilder::new("SELECT COUNT(*), COALESCE(SUM(severity >= ");
+    query.push_bind(critical_severity).push("), 0) FROM alerts");
+    push_alert_filter(&mut query, account_id, filter);
+    query.build_query_as().fetch_one(pool).await
+}
+
+/// Append the WHERE clause selecting an account's alerts by `filter`
+fn push_alert_filter(query: &mut QueryBuilder<'_, Sqlite>, account_id: i64, filter: &AlertFilter) {
+    query.push(" WHERE account_id = ").push_bind(account_id);
+    if let Some(min_severity) = filter.min_severity {
+        query.push(" AND severity >= ").push_bind(min_severity);
+    }
+    if let Some(acknowledged) = filter.acknowledged {
+        query.push(" AND COALESCE(acknowledged, false) = ").push_bind(acknowledged);
+    }
+    if let Some(protocol) = &filter.protocol {
+        query.push(" AND protocol = ").push_bind(protocol.clone());
+    }
+    push_time_range(query, filter.start, filter.end);
+}
+
+/// Acknowledge the alerts raised at `timestamp`; returns their ids
+pub async fn acknowledge_alert(
+    pool: &SqlitePool,
+    account_id: i64,
+    timestamp: DateTime<Utc>,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "UPDATE alerts SET acknowledged = true WHERE account_id = ? AND timestamp = ? RETURNING id"
+    )
+    .bind(account_id)
+    .bind(timestamp.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Acknowledge one alert; returns false if the account has no alert `id`
+pub async fn acknowledge_alert_by_id(
+    pool: &SqlitePool,
+    account_id: i64,
+    id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE alerts SET acknowledged = true WHERE account_id = ? AND id = ?")
+        .bind(account_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Acknowledge every unacknowledged alert, or only those with a severity
+/// below `severity_below`; returns the ids acknowledged
+pub async fn acknowledge_alerts(
+    pool: &SqlitePool,
+    account_id: i64,
+    severity_below: Option<f64>,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "UPDATE alerts SET acknowledged = true WHERE COALESCE(acknowledged, false) = false AND account_id = ",
+    );
+    query.push_bind(account_id);
+    if let Some(severity) = severity_below {
+        query.push(" AND severity < ").push_bind(severity);
+    }
+    query.push(" RETURNING id");
+
+    query.build_query_scalar().fetch_all(pool).await
+}
+
+/// Get unacknowledged alerts, newest first
+///
+/// Walks `idx_alerts_acknowledged`, so acknowledged history is never read.
+pub async fn get_unacknowledged_alerts(
+    pool: &SqlitePool,
+    account_id: i64,
+) -> Result<Vec<AlertRecord>, sqlx::Error> {
+    sqlx::query_as::<_, AlertRecord>(
+        "SELECT * FROM alerts WHERE acknowledged = false AND account_id = ? ORDER BY created_at DESC, id DESC"
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================================
+// STATISTICS QUERIES
+// ============================================================================
+
+/// Get total earnings for period
+pub async fn get_total_earnings(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<f64, sqlx::Error> {
+    let result: (Option<f64>,) = sqlx::query_as(
+        r#"
+        SELECT SUM(total_earnings_per_hour) FROM metrics
+        WHERE account_id = ? AND timestamp BETWEEN ? AND ?
+        "#,
+    )
+    .bind(account_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.0.unwrap_or(0.0))
+}
+
+/// Get average uptime
+pub async fn get_average_uptime(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<f64, sqlx::Error> {
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM metrics
+        WHERE account_id = ? AND timestamp BETWEEN ? AND ?
+        "#,
+    )
+    .bind(account_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_one(pool)
+    .await?;
+
+    if total == 0 {
+        return Ok(0.0);
+    }
+
+    // Calculate uptime based on successful metric collection
+    // In a real system, this would be more sophisticated
+    Ok(100.0)
+}
+
+// ============================================================================
+// EXPORT QUERIES
+// ============================================================================
+
+/// Get average hourly earnings per protocol instance per day, for days in `[from, to]`
+///
+/// Days run from midnight to midnight in `tz`.
+pub async fn get_daily_protocol_earnings(
+    pool: &SqlitePool,
+    account_id: i64,
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: ReportingTimezone,
+) -> Result<Vec<DailyEarningsRecord>, sqlx::Error> {
+    let mut records = Vec::new();
+    for day in from.iter_days().take_while(|day| *day <= to) {
+        let (start, end) = tz.days(day, day);
+        let rows = sqlx::query_as::<_, DailyEarningsRecord>(
+            r#"
+            SELECT
+                ? AS day,
+                ? AS hours,
+                pm.protocol_name,
+                COALESCE(pi.protocol_type, pm.protocol_name) AS protocol_type,
+                AVG(pm.earnings_per_hour) AS avg_earnings_per_hour,
+                COUNT(*) AS samples
+            FROM protocol_metrics pm
+            JOIN metrics m ON m.id = pm.metrics_id
+            LEFT JOIN protocol_instances pi ON pi.account_id = m.account_id AND pi.instance_id = pm.protocol_name
+            WHERE m.account_id = ? AND m.timestamp >= ? AND m.timestamp < ?
+            GROUP BY pm.protocol_name
+            ORDER BY pm.protocol_name
+            "#,
+        )
+        .bind(day.format("%Y-%m-%d").to_string())
+        .bind(tz.hours_in_day(day))
+        .bind(account_id)
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(pool)
+        .await?;
+        records.extend(rows);
+    }
+    Ok(records)
+}
+
+/// Stream the per-protocol rows of metrics recorded in `[start, end]`, oldest first
+///
+/// Rows are read from the cursor as the stream is polled, so arbitrarily
+/// long ranges are never held in memory.
+pub fn stream_metrics_export(
+    pool: &SqlitePool,
+    account_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> BoxStream<'_, Result<MetricsExportRow, sqlx::Error>> {
+    sqlx::query_as::<_, MetricsExportRow>(
+        r#"
+        SELECT
+            m.timestamp, m.total_earnings_per_hour,
+            m.cpu_percent, m.memory_percent, m.bandwidth_percent, m.storage_percent,
+            pm.protocol_name, pm.earnings_per_hour, pm.allocation_percent, pm.connected
+        FROM metrics m
+        LEFT JOIN protocol_metrics pm ON pm.metrics_id = m.id
+        WHERE m.account_id = ? AND m.timestamp BETWEEN ? AND ?
+        ORDER BY m.timestamp, m.id, pm.protocol_name
+        "#,
+    )
+    .bind(account_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch(pool)
+}
+
+// ============================================================================
+// UPTIME QUERIES
+// ============================================================================
+
+/// Record whether `protocol` was connected when polled at `at`
+///
+/// Extends the protocol's latest window when the state is unchanged and it
+/// was last seen no more than `max_gap` ago. Otherwise a new window starts:
+/// at the previous observation for a state change, or at `at` after a gap.
+/// Returns true when the state differs from the previous observation.
+pub async fn record_protocol_connectivity(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol: &str,
+    connected: bool,
+    at: DateTime<Utc>,
+    max_gap: chrono::Duration,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let latest: Option<(i64, bool, String)> = sqlx::query_as(
+        r#"
+        SELECT id, connected, ended_at FROM protocol_uptime
+        WHERE account_id = ? AND protocol_name = ?
+        ORDER BY id DESC LIMIT 1
+        "#,
+    )
+    .bind(account_id)
+    .bind(protocol)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let last_seen = |ended_at: &str| {
+        DateTime::parse_from_rfc3339(ended_at)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+            .filter(|t| at - *t <= max_gap)
+    };
+    let (changed, started_at) = match &latest {
+        Some((id, was_connected, ended_at)) => match last_seen(ended_at) {
+            Some(_) if *was_connected == connected => {
+                sqlx::query("UPDATE protocol_uptime SET ended_at = ? WHERE id = ?")
+                    .bind(at.to_rfc3339())
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+                return Ok(false);
+            }
+            Some(seen) => (*was_connected != connected, seen),
+            None => (*was_connected != connected, at),
+        },
+        None => (false, at),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO protocol_uptime (account_id, protocol_name, connected, started_at, ended_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(protocol)
+    .bind(connected)
+    .bind(started_at.to_rfc3339())
+    .bind(at.to_rfc3339())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(changed)
+}
+
+/// Get every protocol's uptime windows still open after `since`, oldest first
+pub async fn get_uptime_windows(
+    pool: &SqlitePool,
+    account_id: i64,
+    since: DateTime<Utc>,
+) -> Result<Vec<UptimeWindowRecord>, sqlx::Error> {
+    sqlx::query_as::<_, UptimeWindowRecord>(
+        r#"
+        SELECT id, protocol_name, connected, started_at, ended_at FROM protocol_uptime
+        WHERE account_id = ? AND ended_at > ?
+        ORDER BY started_at, id
+        "#,
+    )
+    .bind(account_id)
+    .bind(since.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================================
+// PROTOCOL INSTANCE QUERIES
+// ============================================================================
+
+/// Record that protocol instance `instance_id` runs `protocol_type`, seen now
+pub async fn upsert_protocol_instance(
+    pool: &SqlitePool,
+    account_id: i64,
+    instance_id: &str,
+    protocol_type: &str,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        r#"
+        INSERT INTO protocol_instances (account_id, instance_id, protocol_type, first_seen_at, last_seen_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(account_id, instance_id) DO UPDATE SET
+            protocol_type = excluded.protocol_type,
+            last_seen_at = excluded.last_seen_at
+        "#,
+    )
+    .bind(account_id)
+    .bind(instance_id)
+    .bind(protocol_type)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get every protocol instance of an account, by instance id
+pub async fn get_protocol_instances(
+    pool: &SqlitePool,
+    account_id: i64,
+) -> Result<Vec<ProtocolInstanceRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolInstanceRecord>(
+        r#"
+        SELECT instance_id, protocol_type, first_seen_at, last_seen_at
+        FROM protocol_instances
+        WHERE account_id = ?
+        ORDER BY instance_id
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================================
+// RISK QUERIES
+// ============================================================================
+
+/// Replace a protocol's earnings volatility, computed at `at` over `window_hours`
+pub async fn upsert_protocol_risk(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol: &str,
+    risk: &crate::orchestration::ProtocolRisk,
+    window_hours: i64,
+    at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO protocol_risk
+        (account_id, protocol_name, computed_at, window_hours, samples, mean_earnings, std_dev,
+         max_drawdown_percent, risk_score)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(account_id, protocol_name) DO UPDATE SET
+            computed_at = excluded.computed_at,
+            window_hours = excluded.window_hours,
+            samples = excluded.samples,
+            mean_earnings = excluded.mean_earnings,
+            std_dev = excluded.std_dev,
+            max_drawdown_percent = excluded.max_drawdown_percent,
+            risk_score = excluded.risk_score
+        "#,
+    )
+    .bind(account_id)
+    .bind(protocol)
+    .bind(at.to_rfc3339())
+    .bind(window_hours)
+    .bind(risk.samples as i64)
+    .bind(risk.mean_earnings)
+    .bind(risk.std_dev)
+    .bind(risk.max_drawdown_percent)
+    .bind(risk.risk_score)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get a protocol's latest earnings volatility
+pub async fn get_protocol_risk(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol: &str,
+) -> Result<Option<ProtocolRiskRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolRiskRecord>(
+        r#"
+        SELECT protocol_name, computed_at, window_hours, samples, mean_earnings, std_dev,
+               max_drawdown_percent, risk_score
+        FROM protocol_risk
+        WHERE account_id = ? AND protocol_name = ?
+        "#,
+    )
+    .bind(account_id)
+    .bind(protocol)
+    .fetch_optional(pool)
+    .await
+}
+
+// ============================================================================
+// WALLET QUERIES
+// ============================================================================
+
+/// Store polled wallet balance
+pub async fn store_wallet_balance(
+    pool: &SqlitePool,
+    account_id: i64,
+    balance: &crate::wallets::WalletBalance,
+) -> Result<i64, sqlx::Error> {
+    let record = WalletBalanceRecord::new(balance);
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO wallet_balances
+        (account_id, label, chain, address, protocol_name, symbol, balance, balance_usd, polled_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(&record.label)
+    .bind(&record.chain)
+    .bind(&record.address)
+    .bind(&record.protocol_name)
+    .bind(&record.symbol)
+    .bind(record.balance)
+    .bind(record.balance_usd)
+    .bind(&record.polled_at)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Get the most recent balance of every tracked wallet asset
+pub async fn get_latest_wallet_balances(
+    pool: &SqlitePool,
+    account_id: i64,
+) -> Result<Vec<WalletBalanceRecord>, sqlx::Error> {
+    sqlx::query_as::<_, WalletBalanceRecord>(
+        r#"
+        SELECT id, label, chain, address, protocol_name, symbol, balance, balance_usd, polled_at
+        FROM wallet_balances w
+        WHERE account_id = ?1 AND polled_at = (
+            SELECT MAX(polled_at) FROM wallet_balances
+            WHERE account_id = ?1 AND chain = w.chain AND address = w.address AND symbol = w.symbol
+        )
+        ORDER BY chain, label
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get balance history of a wallet asset, newest first
+pub async fn get_wallet_balance_history(
+    pool: &SqlitePool,
+    account_id: i64,
+    chain: &str,
+    address: &str,
+    limit: i64,
+) -> Result<Vec<WalletBalanceRecord>, sqlx::Error> {
+    sqlx::query_as::<_, WalletBalanceRecord>(
+        r#"
+        SELECT id, label, chain, address, protocol_name, symbol, balance, balance_usd, polled_at
+        FROM wallet_balances
+        WHERE account_id = ? AND chain = ? AND address = ?
+        ORDER BY polled_at DESC LIMIT ?
+        "#,
+    )
+    .bind(account_id)
+    .bind(chain)
+    .bind(address)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================================
+// NETWORK PROBE QUERIES
+// ============================================================================
+
+/// Store a network probe result
+pub async fn store_network_probe(
+    pool: &SqlitePool,
+    account_id: i64,
+    probe: &crate::network_probe::NetworkProbe,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO network_probes (account_id, latency_ms, download_mbps, upload_mbps, measured_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(probe.latency_ms)
+    .bind(probe.download_mbps)
+    .bind(probe.upload_mbps)
+    .bind(probe.measured_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Get network probes measured since `since`, newest first
+pub async fn get_network_probes(
+    pool: &SqlitePool,
+    account_id: i64,
+    since: DateTime<Utc>,
+) -> Result<Vec<NetworkProbeRecord>, sqlx::Error> {
+    sqlx::query_as::<_, NetworkProbeRecord>(
+        r#"
+        SELECT id, latency_ms, download_mbps, upload_mbps, measured_at
+        FROM network_probes
+        WHERE account_id = ? AND measured_at >= ?
+        ORDER BY measured_at DESC
+        "#,
+    )
+    .bind(account_id)
+    .bind(since.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================================
+// REPORT QUERIES
+// ============================================================================
+
+/// Store generated performance report
+pub async fn store_report(
+    pool: &SqlitePool,
+    account_id: i64,
+    record: &ReportRecord,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO reports
+        (account_id, generated_at, period_start, period_end, total_earnings, report_json)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(&record.generated_at)
+    .bind(&record.period_start)
+    .bind(&record.period_end)
+    .bind(record.total_earnings)
+    .bind(&record.report_json)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Get stored report by id
+pub async fn get_report(
+    pool: &SqlitePool,
+    account_id: i64,
+    id: i64,
+) -> Result<Option<ReportRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ReportRecord>("SELECT * FROM reports WHERE account_id = ? AND id = ?")
+        .bind(account_id)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Get most recent stored reports, newest first
+pub async fn get_reports(
+    pool: &SqlitePool,
+    account_id: i64,
+    limit: i64,
+) -> Result<Vec<ReportRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ReportRecord>(
+        "SELECT * FROM reports WHERE account_id = ? ORDER BY generated_at DESC, id DESC LIMIT ?",
+    )
+    .bind(account_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================================
+// CREDENTIAL QUERIES
+// ============================================================================
+
+/// Insert or replace the encrypted credentials of a protocol
+///
+/// Replacing keeps the original `created_at` and bumps `rotated_at`.
+pub async fn upsert_protocol_credentials(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol: &str,
+    fields_json: &str,
+    nonce: &[u8],
+    ciphertext: &[u8],
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO protocol_credentials
+        (account_id, protocol, fields, nonce, ciphertext, created_at, rotated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+        ON CONFLICT(account_id, protocol) DO UPDATE SET
+            fields = excluded.fields,
+            nonce = excluded.nonce,
+            ciphertext = excluded.ciphertext,
+            rotated_at = excluded.rotated_at
+        "#,
+    )
+    .bind(account_id)
+    .bind(protocol)
+    .bind(fields_json)
+    .bind(nonce)
+    .bind(ciphertext)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the encrypted credentials of a protocol
+pub async fn get_protocol_credentials(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol: &str,
+) -> Result<Option<ProtocolCredentialRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolCredentialRecord>(
+        "SELECT * FROM protocol_credentials WHERE account_id = ? AND protocol = ?",
+    )
+    .bind(account_id)
+    .bind(protocol)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Get the encrypted credentials of every protocol, ordered by name
+pub async fn list_protocol_credentials(
+    pool: &SqlitePool,
+    account_id: i64,
+) -> Result<Vec<ProtocolCredentialRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ProtocolCredentialRecord>(
+        "SELECT * FROM protocol_credentials WHERE account_id = ? ORDER BY protocol",
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete the credentials of a protocol
+///
+/// Returns `false` if none were stored.
+pub async fn delete_protocol_credentials(
+    pool: &SqlitePool,
+    account_id: i64,
+    protocol: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM protocol_credentials WHERE account_id = ? AND protocol = ?")
+        .bind(account_id)
+        .bind(protocol)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// ============================================================================
+// ACCOUNT QUERIES
+// ============================================================================
+
+/// Create an account on rate limit `tier`; returns its ID
+pub async fn create_account(pool: &SqlitePool, name: &str, tier: &str) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query("INSERT INTO accounts (name, created_at, tier) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(Utc::now().to_rfc3339())
+        .bind(tier)
+        .execute(pool)
+        .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Get an account by ID
+pub async fn get_account(pool: &SqlitePool, id: i64) -> Result<Option<AccountRecord>, sqlx::Error> {
+    sqlx::query_as::<_, AccountRecord>("SELECT * FROM accounts WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Get every account, ordered by ID
+pub async fn list_accounts(pool: &SqlitePool) -> Result<Vec<AccountRecord>, sqlx::Error> {
+    sqlx::query_as::<_, AccountRecord>("SELECT * FROM accounts ORDER BY id")
+        .fetch_all(pool)
+        .await
+}
+
+/// Activate or deactivate an account; returns false if it does not exist
+///
+/// API keys of an inactive account are rejected.
+pub async fn set_account_active(
+    pool: &SqlitePool,
+    id: i64,
+    is_active: bool,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE accounts SET is_active = ? WHERE id = ?")
+        .bind(is_active)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Move an account to another rate limit tier; returns false if it does not exist
+pub async fn set_account_tier(pool: &SqlitePool, id: i64, tier: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE accounts SET tier = ? WHERE id = ?")
+        .bind(tier)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// ============================================================================
+// IDEMPOTENCY QUERIES
+// ============================================================================
+
+/// Claim `key` for a request; returns false if the account already used it
+pub async fn claim_idempotency_key(
+    pool: &SqlitePool,
+    account_id: i64,
+    key: &str,
+    request_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO idempotency_keys (account_id, idempotency_key, request_hash, created_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(key)
+    .bind(request_hash)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get what is recorded for an account's `key`
+pub async fn get_idempotency_record(
+    pool: &SqlitePool,
+    account_id: i64,
+    key: &str,
+) -> Result<Option<IdempotencyRecord>, sqlx::Error> {
+    sqlx::query_as::<_, IdempotencyRecord>(
+        "SELECT * FROM idempotency_keys WHERE account_id = ? AND idempotency_key = ?",
+    )
+    .bind(account_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record the response to the request that claimed `key`
+pub async fn complete_idempotency_key(
+    pool: &SqlitePool,
+    account_id: i64,
+    key: &str,
+    status_code: u16,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE idempotency_keys
+        SET status_code = ?, content_type = ?, response_body = ?
+        WHERE account_id = ? AND idempotency_key = ?
+        "#,
+    )
+    .bind(status_code as i64)
+    .bind(content_type)
+    .bind(body)
+    .bind(account_id)
+    .bind(key)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Forget `key` so the request can be retried
+pub async fn release_idempotency_key(pool: &SqlitePool, account_id: i64, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE account_id = ? AND idempotency_key = ?")
+        .bind(account_id)
+        .bind(key)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// ============================================================================
+// REQUEST LOG QUERIES
+// ============================================================================
+
+/// Store an access log entry
+pub async fn store_request_log(pool: &SqlitePool, record: &RequestLogRecord) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO request_log
+        (timestamp, request_id, method, path, query, status, latency_ms, api_key_id, account_id, headers_json, body)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&record.timestamp)
+    .bind(&record.request_id)
+    .bind(&record.method)
+    .bind(&record.path)
+    .bind(&record.query)
+    .bind(record.status)
+    .bind(record.latency_ms)
+    .bind(record.api_key_id)
+    .bind(record.account_id)
+    .bind(&record.headers_json)
+    .bind(&record.body)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Get the most recent access log entries, newest first
+pub async fn get_recent_request_log(pool: &SqlitePool, limit: i64) -> Result<Vec<RequestLogRecord>, sqlx::Error> {
+    sqlx::query_as::<_, RequestLogRecord>("SELECT * FROM request_log ORDER BY id DESC LIMIT ?")
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+// ============================================================================
+// NOTIFICATION RULE QUERIES
+// ============================================================================
+
+/// Store an alert routing rule; returns its ID
+pub async fn create_notification_rule(
+    pool: &SqlitePool,
+    account_id: i64,
+    rule: &crate::notifications::routing::NotificationRule,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO notification_rules
+        (account_id, alert_type, min_severity, max_severity, channels, priority, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(account_id)
+    .bind(&rule.alert_type)
+    .bind(rule.min_severity)
+    .bind(rule.max_severity)
+    .bind(serde_json::to_string(&rule.channels).unwrap_or_else(|_| "[]".to_string()))
+    .bind(rule.priority)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Replace an alert routing rule; returns whether it existed
+pub async fn update_notification_rule(
+    pool: &SqlitePool,
+    account_id: i64,
+    id: i64,
+    rule: &crate::notifications::routing::NotificationRule,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE notification_rules
+        SET alert_type = ?, min_severity = ?, max_severity = ?, channels = ?, priority = ?
+        WHERE account_id = ? AND id = ?
+        "#,
+    )
+    .bind(&rule.alert_type)
+    .bind(rule.min_severity)
+    .bind(rule.max_severity)
+    .bind(serde_json::to_string(&rule.channels).unwrap_or_else(|_| "[]".to_string()))
+    .bind(rule.priority)
+    .bind(account_id)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get an alert routing rule by ID
+pub async fn get_notification_rule(
+    pool: &SqlitePool,
+    account_id: i64,
+    id: i64,
+) -> Result<Option<NotificationRuleRecord>, sqlx::Error> {
+    sqlx::query_as::<_, NotificationRuleRecord>("SELECT * FROM notification_rules WHERE account_id = ? AND id = ?")
+        .bind(account_id)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Get an account's alert routing rules in evaluation order
+pub async fn list_notification_rules(
+    pool: &SqlitePool,
+    account_id: i64,
+) -> Result<Vec<NotificationRuleRecord>, sqlx::Error> {
+    sqlx::query_as::<_, NotificationRuleRecord>(
+        "SELECT * FROM notification_rules WHERE account_id = ? ORDER BY priority, id",
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete an alert routing rule; returns whether it existed
+pub async fn delete_notification_rule(pool: &SqlitePool, account_id: i64, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM notification_rules WHERE account_id = ? AND id = ?")
+        .bind(account_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// ============================================================================
+// LISTING HELPERS
+// ============================================================================
+
+/// Append `timestamp` bounds to a WHERE clause
+fn push_time_range(query: &mut QueryBuilder<'_, Sqlite>, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) {
+    if let Some(start) = start {
+        query.push(" AND timestamp >= ").push_bind(start.to_rfc3339());
+    }
+    if let Some(end) = end {
+        query.push(" AND timestamp <= ").push_bind(end.to_rfc3339());
+    }
+}
+
+/// Append the cursor bound, newest-first order, and page size
+fn push_page(query: &mut QueryBuilder<'_, Sqlite>, page: Page) {
+    if let Some(before_id) = page.before_id {
+        query.push(" AND id < ").push_bind(before_id);
+    }
+    query
+        .push(" ORDER BY id DESC LIMIT ")
+        .push_bind(page.limit)
+        .push(" OFFSET ")
+        .push_bind(page.offset);
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -328,4 +2060,586 @@ mod tests {
         );
         assert_eq!(record.alert_type, "TEST");
     }
+
+    #[tokio::test]
+    async fn test_daily_protocol_earnings() {
+        let pool = crate::db::test_pool().await;
+
+        let day = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        for (hour, rate) in [(1, 0.2), (13, 0.4)] {
+            let timestamp = day.and_hms_opt(hour, 0, 0).unwrap().and_utc();
+            let id = store_metrics(&pool, 1, timestamp, rate, 0.0, 0.0, 0.0, 0.0).await.unwrap();
+            store_protocol_metrics(&pool, id, "storj".to_string(), rate, 50.0, true)
+                .await
+                .unwrap();
+        }
+
+        let utc = ReportingTimezone::Utc;
+        let records = get_daily_protocol_earnings(&pool, 1, day, day, utc).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].day, "2026-10-01");
+        assert_eq!((records[0].samples, records[0].hours), (2, 24.0));
+        assert!((records[0].avg_earnings_per_hour - 0.3).abs() < 1e-9);
+
+        let next_day = day.succ_opt().unwrap();
+        assert!(get_daily_protocol_earnings(&pool, 1, next_day, next_day, utc)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // 01:00 UTC is still the previous evening five hours west
+        let west: ReportingTimezone = "-05:00".parse().unwrap();
+        let records = get_daily_protocol_earnings(&pool, 1, day.pred_opt().unwrap(), day, west).await.unwrap();
+        let days: Vec<(&str, i64)> = records.iter().map(|r| (r.day.as_str(), r.samples)).collect();
+        assert_eq!(days, vec![("2026-09-30", 1), ("2026-10-01", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_instances() {
+        let pool = crate::db::test_pool().await;
+
+        let day = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let id = store_metrics(&pool, 1, day.and_hms_opt(6, 0, 0).unwrap().and_utc(), 0.3, 0.0, 0.0, 0.0, 0.0)
+            .await
+            .unwrap();
+        for (instance, rate) in [("storj", 0.2), ("storj_backup", 0.1)] {
+            store_protocol_metrics(&pool, id, instance.to_string(), rate, 50.0, true).await.unwrap();
+        }
+
+        upsert_protocol_instance(&pool, 1, "storj", "storj").await.unwrap();
+        upsert_protocol_instance(&pool, 1, "storj_backup", "golem").await.unwrap();
+        // Registering again updates the type
+        upsert_protocol_instance(&pool, 1, "storj_backup", "storj").await.unwrap();
+
+        let instances = get_protocol_instances(&pool, 1).await.unwrap();
+        let ids: Vec<_> = instances.iter().map(|i| (i.instance_id.as_str(), i.protocol_type.as_str())).collect();
+        assert_eq!(ids, vec![("storj", "storj"), ("storj_backup", "storj")]);
+        assert!(get_protocol_instances(&pool, 2).await.unwrap().is_empty());
+
+        // Both nodes keep their own earnings, tagged with their shared type
+        let records = get_daily_protocol_earnings(&pool, 1, day, day, ReportingTimezone::Utc).await.unwrap();
+        let rows: Vec<_> = records
+            .iter()
+            .map(|r| (r.protocol_name.as_str(), r.protocol_type.as_str(), r.avg_earnings_per_hour))
+            .collect();
+        assert_eq!(rows, vec![("storj", "storj", 0.2), ("storj_backup", "storj", 0.1)]);
+    }
+
+    #[tokio::test]
+    async fn test_historical_metrics_leave_resources_empty() {
+        let pool = crate::db::test_pool().await;
+
+        let timestamp = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap().and_hms_opt(6, 0, 0).unwrap().and_utc();
+        let earnings = HashMap::from([("storj".to_string(), 0.25), ("golem".to_string(), 0.5)]);
+        let allocation = HashMap::from([("storj".to_string(), 40.0)]);
+        store_historical_metrics(&pool, 1, timestamp, &earnings, &allocation).await.unwrap();
+
+        let latest = get_latest_metrics(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(latest.total_earnings_per_hour, 0.75);
+        assert!(latest.cpu_percent.is_none());
+
+        let day = timestamp.date_naive();
+        let daily = get_daily_protocol_earnings(&pool, 1, day, day, ReportingTimezone::Utc).await.unwrap();
+        assert_eq!(daily.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pending_reallocation_lifecycle() {
+        let pool = crate::db::test_pool().await;
+
+        let plan = crate::orchestration::AllocationPlan {
+            allocation: [("storj".to_string(), 100.0)].into_iter().collect(),
+            estimated_improvement: 1.0,
+            estimated_cost: 0.05,
+            net_benefit: 0.95,
+            roi_percent: 1900.0,
+            confidence: 0.85,
+            created_at: Utc::now(),
+        };
+
+        let record = PendingReallocationRecord::new(&plan).unwrap();
+        let first = store_pending_reallocation(&pool, 1, &record).await.unwrap();
+        let second = store_pending_reallocation(&pool, 1, &record).await.unwrap();
+
+        // Only the newest plan stays pending
+        let pending = get_pending_reallocations(&pool, 1).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, Some(second));
+        let superseded = get_pending_reallocation(&pool, 1, first).await.unwrap().unwrap();
+        assert_eq!(superseded.status, "superseded");
+
+        assert!(transition_pending_reallocation(
+            &pool,
+            1,
+            second,
+            PendingStatus::Pending,
+            PendingStatus::Rejected,
+            Some("not now"),
+        )
+        .await
+        .unwrap());
+        // A decided plan cannot be decided again
+        assert!(!transition_pending_reallocation(
+            &pool,
+            1,
+            second,
+            PendingStatus::Pending,
+            PendingStatus::Approved,
+            None,
+        )
+        .await
+        .unwrap());
+
+        let rejected = get_pending_reallocation(&pool, 1, second).await.unwrap().unwrap();
+        assert_eq!(rejected.status, "rejected");
+        assert_eq!(rejected.note.as_deref(), Some("not now"));
+    }
+
+    #[tokio::test]
+    async fn test_optimizer_decision_lifecycle() {
+        use crate::orchestration::optimizer::{DecisionExplanation, Strategy};
+
+        let pool = crate::db::test_pool().await;
+
+        let explanation = DecisionExplanation {
+            strategy: Strategy::Diversified,
+            total_earnings_per_hour: 2.0,
+            scores: Vec::new(),
+            candidates: Vec::new(),
+            plan: crate::orchestration::AllocationPlan {
+                allocation: [("storj".to_string(), 100.0)].into_iter().collect(),
+                estimated_improvement: 1.0,
+                estimated_cost: 0.05,
+                net_benefit: 0.95,
+                roi_percent: 1900.0,
+                confidence: 0.85,
+                created_at: Utc::now(),
+            },
+        };
+        let pending = PendingReallocationRecord::new(&explanation.plan).unwrap();
+        let pending_id = store_pending_reallocation(&pool, 1, &pending).await.unwrap();
+        let record = OptimizerDecisionRecord {
+            pending_reallocation_id: Some(pending_id),
+            ..OptimizerDecisionRecord::new(&explanation, DecisionOutcome::PendingApproval, None).unwrap()
+        };
+        let id = store_optimizer_decision(&pool, 1, &record).await.unwrap();
+
+        let stored = get_optimizer_decision_for_pending(&pool, 1, pending_id).await.unwrap().unwrap();
+        assert_eq!(stored.id, Some(id));
+        assert_eq!(stored.outcome, "pending_approval");
+        assert_eq!(stored.explanation().unwrap().strategy, Strategy::Diversified);
+        assert!(get_optimizer_decision(&pool, 2, id).await.unwrap().is_none());
+
+        assert!(set_optimizer_decision_outcome(&pool, 1, id, DecisionOutcome::Failed, Some("adapter offline"))
+            .await
+            .unwrap());
+        let failed = get_optimizer_decision(&pool, 1, id).await.unwrap().unwrap();
+        assert_eq!(failed.outcome, "failed");
+        assert_eq!(failed.reason.as_deref(), Some("adapter offline"));
+
+        store_reallocation(&pool, 1, Utc::now(), "storj".to_string(), 50.0, 100.0, None, None, Some(id))
+            .await
+            .unwrap();
+        let changes = get_reallocation_history(&pool, 1, 10).await.unwrap();
+        assert_eq!(changes[0].decision_id, Some(id));
+    }
+
+    #[tokio::test]
+    async fn test_alert_condition_deduplication() {
+        let pool = crate::db::test_pool().await;
+        let cooldown = chrono::Duration::minutes(15);
+
+        let first = record_alert_condition(&pool, 1, "high_cpu", None, "HIGH_CPU_USAGE", 10.0, "CPU 91%", cooldown)
+            .await
+            .unwrap();
+        let AlertOutcome::Raised(id) = first else {
+            panic!("expected a new alert, got {:?}", first);
+        };
+        assert_eq!(
+            record_alert_condition(&pool, 1, "high_cpu", None, "HIGH_CPU_USAGE", 20.0, "CPU 95%", cooldown)
+                .await
+                .unwrap(),
+            AlertOutcome::Repeated(id)
+        );
+
+        assert!(resolve_alert_condition(&pool, 1, "high_cpu").await.unwrap());
+        assert!(!resolve_alert_condition(&pool, 1, "high_cpu").await.unwrap());
+
+        // Flapping back within the cooldown reopens the same alert
+        assert_eq!(
+            record_alert_condition(&pool, 1, "high_cpu", None, "HIGH_CPU_USAGE", 5.0, "CPU 90.5%", cooldown)
+                .await
+                .unwrap(),
+            AlertOutcome::Reopened(id)
+        );
+
+        let alerts = get_alert_history(&pool, 1, 10).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].occurrences, Some(3));
+        assert_eq!(alerts[0].severity, 20.0);
+        assert_eq!(alerts[0].resolved, Some(false));
+
+        // Outside the cooldown a fresh alert is raised
+        resolve_alert_condition(&pool, 1, "high_cpu").await.unwrap();
+        let outcome = record_alert_condition(
+            &pool,
+            1,
+            "high_cpu",
+            None,
+            "HIGH_CPU_USAGE",
+            5.0,
+            "CPU 90.5%",
+            chrono::Duration::zero(),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(outcome, AlertOutcome::Raised(new_id) if new_id != id));
+    }
+
+    #[tokio::test]
+    async fn test_acknowledge_alerts_by_id_and_in_bulk() {
+        let pool = crate::db::test_pool().await;
+        let cooldown = chrono::Duration::minutes(15);
+
+        let mut ids = Vec::new();
+        for (account, fingerprint, severity) in
+            [(1, "low", 10.0), (1, "medium", 40.0), (1, "high", 90.0), (2, "low", 10.0)]
+        {
+            let outcome = record_alert_condition(&pool, account, fingerprint, None, "TEST", severity, "test", cooldown)
+                .await
+                .unwrap();
+            let AlertOutcome::Raised(id) = outcome else { panic!("expected a new alert") };
+            ids.push(id);
+        }
+
+        // Ids are scoped to the owning account
+        assert!(!acknowledge_alert_by_id(&pool, 2, ids[2]).await.unwrap());
+        assert!(acknowledge_alert_by_id(&pool, 1, ids[2]).await.unwrap());
+        assert!(!acknowledge_alert_by_id(&pool, 1, 9999).await.unwrap());
+
+        assert_eq!(acknowledge_alerts(&pool, 1, Some(20.0)).await.unwrap(), [ids[0]]);
+        assert_eq!(acknowledge_alerts(&pool, 1, None).await.unwrap(), [ids[1]]);
+        assert!(acknowledge_alerts(&pool, 1, None).await.unwrap().is_empty());
+
+        let open = AlertFilter { acknowledged: Some(false), ..Default::default() };
+        assert_eq!(count_alerts(&pool, 1, &open, 75.0).await.unwrap(), (0, 0));
+        assert_eq!(count_alerts(&pool, 2, &open, 75.0).await.unwrap(), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_filtered_alert_and_reallocation_pages() {
+        let pool = crate::db::test_pool().await;
+        let cooldown = chrono::Duration::minutes(15);
+
+        for (fingerprint, protocol, severity) in [
+            ("resource:cpu", None, 10.0),
+            ("protocol_degraded:storj", Some("storj"), 75.0),
+            ("protocol_degraded:grass", Some("grass"), 75.0),
+            ("resource:memory", None, 40.0),
+        ] {
+            record_alert_condition(&pool, 1, fingerprint, protocol, "TEST", severity, "test", cooldown)
+                .await
+                .unwrap();
+        }
+        let now = Utc::now();
+        sqlx::query("UPDATE alerts SET acknowledged = true WHERE fingerprint = 'resource:cpu'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let all = AlertFilter::default();
+        let first = Page { limit: 3, offset: 0, before_id: None };
+        let alerts = list_alerts(&pool, 1, &all, first).await.unwrap();
+        let fingerprints: Vec<_> = alerts.iter().filter_map(|a| a.fingerprint.as_deref()).collect();
+        assert_eq!(fingerprints, ["resource:memory", "protocol_degraded:grass", "protocol_degraded:storj"]);
+        assert_eq!(count_alerts(&pool, 1, &all, 75.0).await.unwrap(), (4, 2));
+
+        // The cursor continues where the full first page ended
+        let cursor = first.next_cursor(alerts.len(), alerts.last().and_then(|a| a.id));
+        let rest = list_alerts(&pool, 1, &all, Page { before_id: cursor, ..first }).await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].fingerprint.as_deref(), Some("resource:cpu"));
+        assert_eq!(first.next_cursor(rest.len(), rest[0].id), None);
+
+        let storj = AlertFilter { protocol: Some("storj".to_string()), ..Default::default() };
+        assert_eq!(list_alerts(&pool, 1, &storj, first).await.unwrap()[0].protocol.as_deref(), Some("storj"));
+        let unacknowledged_severe = AlertFilter { acknowledged: Some(false), min_severity: Some(20.0), ..Default::default() };
+        assert_eq!(count_alerts(&pool, 1, &unacknowledged_severe, 75.0).await.unwrap(), (3, 2));
+        let future = AlertFilter { start: Some(now + chrono::Duration::hours(1)), ..Default::default() };
+        assert!(list_alerts(&pool, 1, &future, first).await.unwrap().is_empty());
+        assert_eq!(count_alerts(&pool, 2, &all, 75.0).await.unwrap(), (0, 0));
+
+        for (minutes, protocol) in [(30, "storj"), (20, "grass"), (10, "storj")] {
+            let timestamp = now - chrono::Duration::minutes(minutes);
+            store_reallocation(&pool, 1, timestamp, protocol.to_string(), 50.0, 60.0, None, None, None)
+                .await
+                .unwrap();
+        }
+        let storj = ReallocationFilter {
+            protocol: Some("storj".to_string()),
+            start: Some(now - chrono::Duration::minutes(15)),
+            end: None,
+        };
+        let changes = list_reallocations(&pool, 1, &storj, first).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].protocol_name, "storj");
+        assert_eq!(count_reallocations(&pool, 1, &ReallocationFilter::default()).await.unwrap(), 3);
+        let skipped = Page { offset: 2, ..first };
+        let oldest = list_reallocations(&pool, 1, &ReallocationFilter::default(), skipped).await.unwrap();
+        assert_eq!(oldest.len(), 1);
+        assert_eq!(oldest[0].timestamp, (now - chrono::Duration::minutes(30)).to_rfc3339());
+    }
+
+    #[tokio::test]
+    async fn test_report_storage() {
+        let pool = crate::db::test_pool().await;
+
+        let report = crate::orchestration::PerformanceReport {
+            period_start: Utc::now() - chrono::Duration::hours(24),
+            period_end: Utc::now(),
+            total_earnings: 42.0,
+            average_hourly_earnings: 1.75,
+            earnings_by_protocol: [("storj".to_string(), 42.0)].into_iter().collect(),
+            allocation_changes: vec![],
+            total_improvement: 0.0,
+            successful_optimizations: 0,
+            uptime_percent: 99.5,
+            attribution: Default::default(),
+        };
+
+        let record = ReportRecord::new(&report).unwrap();
+        let id = store_report(&pool, 1, &record).await.unwrap();
+        let stored = get_report(&pool, 1, id).await.unwrap().unwrap();
+        assert_eq!(stored.total_earnings, 42.0);
+        assert_eq!(stored.report().unwrap().earnings_by_protocol["storj"], 42.0);
+
+        assert_eq!(get_reports(&pool, 1, 10).await.unwrap().len(), 1);
+        assert!(get_report(&pool, 1, id + 1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bucketed_metrics() {
+        let pool = crate::db::test_pool().await;
+
+        let base = DateTime::parse_from_rfc3339("2026-10-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for (minutes, earnings) in [(0, 1.0), (20, 3.0), (70, 5.0)] {
+            let timestamp = base + chrono::Duration::minutes(minutes);
+            let id = store_metrics(&pool, 1, timestamp, earnings, 50.0, 50.0, 10.0, 10.0)
+                .await
+                .unwrap();
+            store_protocol_metrics(&pool, id, "storj".to_string(), earnings, 100.0, true)
+                .await
+                .unwrap();
+        }
+
+        let hourly = get_bucketed_metrics(&pool, 1, base, MetricsInterval::Hour, MetricsAggregation::Avg)
+            .await
+            .unwrap();
+        assert_eq!(hourly.len(), 2);
+        assert_eq!(hourly[0].bucket, base.timestamp());
+        assert_eq!(hourly[0].samples, 2);
+        assert_eq!(hourly[0].total_earnings_per_hour, 2.0);
+        assert_eq!(hourly[1].total_earnings_per_hour, 5.0);
+
+        let daily = get_bucketed_metrics(&pool, 1, base, MetricsInterval::Day, MetricsAggregation::Max)
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].total_earnings_per_hour, 5.0);
+
+        let protocols = get_bucketed_protocol_metrics(
+            &pool,
+            1,
+            base,
+            MetricsInterval::Hour,
+            MetricsAggregation::Sum,
+        )
+        .await
+        .unwrap();
+        assert_eq!(protocols.len(), 2);
+        assert_eq!(protocols[0].earnings_per_hour, 4.0);
+
+        // Samples before `start` are excluded
+        let recent = get_bucketed_metrics(
+            &pool,
+            1,
+            base + chrono::Duration::minutes(30),
+            MetricsInterval::FiveMinutes,
+            MetricsAggregation::Avg,
+        )
+        .await
+        .unwrap();
+        assert_eq!(recent.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_credentials_upsert_keeps_created_at() {
+        let pool = crate::db::test_pool().await;
+
+        let fields = vec!["auth_token".to_string()];
+        let fields_json = serde_json::to_string(&fields).unwrap();
+        upsert_protocol_credentials(&pool, 1, "grass", &fields_json, b"nonce-1", b"secret-1")
+            .await
+            .unwrap();
+        let first = get_protocol_credentials(&pool, 1, "grass").await.unwrap().unwrap();
+
+        upsert_protocol_credentials(&pool, 1, "grass", &fields_json, b"nonce-2", b"secret-2")
+            .await
+            .unwrap();
+        let rotated = get_protocol_credentials(&pool, 1, "grass").await.unwrap().unwrap();
+
+        assert_eq!(rotated.created_at, first.created_at);
+        assert_eq!(rotated.ciphertext, b"secret-2");
+        assert_eq!(rotated.field_names(), fields);
+        assert_eq!(list_protocol_credentials(&pool, 1).await.unwrap().len(), 1);
+
+        assert!(delete_protocol_credentials(&pool, 1, "grass").await.unwrap());
+        assert!(!delete_protocol_credentials(&pool, 1, "grass").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_accounts_scope_recorded_data() {
+        let pool = crate::db::test_pool().await;
+
+        let tenant = create_account(&pool, "tenant", "free").await.unwrap();
+        assert_eq!(list_accounts(&pool).await.unwrap().len(), 2);
+
+        let now = Utc::now();
+        store_metrics(&pool, 1, now, 1.0, 0.0, 0.0, 0.0, 0.0).await.unwrap();
+        store_metrics(&pool, tenant, now, 2.0, 0.0, 0.0, 0.0, 0.0).await.unwrap();
+        store_reallocation(&pool, tenant, now, "storj".to_string(), 50.0, 60.0, None, None, None)
+            .await
+            .unwrap();
+
+        let latest = get_latest_metrics(&pool, tenant).await.unwrap().unwrap();
+        assert_eq!(latest.total_earnings_per_hour, 2.0);
+        assert_eq!(get_metrics_history(&pool, 1, 10).await.unwrap().len(), 1);
+        assert_eq!(get_reallocation_count(&pool, 1, "storj").await.unwrap(), 0);
+        assert_eq!(get_reallocation_count(&pool, tenant, "storj").await.unwrap(), 1);
+
+        assert!(set_account_active(&pool, tenant, false).await.unwrap());
+        assert!(set_account_tier(&pool, tenant, "pro").await.unwrap());
+        let account = get_account(&pool, tenant).await.unwrap().unwrap();
+        assert!(!account.is_active);
+        assert_eq!(account.tier, "pro");
+        assert_eq!(get_account(&pool, 1).await.unwrap().unwrap().tier, "admin");
+        assert!(!set_account_active(&pool, tenant + 1, false).await.unwrap());
+    }
+
+    /// Plan SQLite picks for `sql`, one step per line
+    async fn query_plan(pool: &SqlitePool, sql: &str) -> String {
+        let steps: Vec<(i64, i64, i64, String)> = sqlx::query_as(&format!("EXPLAIN QUERY PLAN {}", sql))
+            .fetch_all(pool)
+            .await
+            .unwrap();
+        steps.into_iter().map(|step| step.3).collect::<Vec<_>>().join("\n")
+    }
+
+    #[tokio::test]
+    async fn test_cross_account_lookups_use_indexes() {
+        let pool = crate::db::test_pool().await;
+
+        for (sql, index) in [
+            ("SELECT * FROM metrics WHERE timestamp < ? ORDER BY timestamp", "idx_metrics_timestamp"),
+            (
+                "SELECT * FROM protocol_metrics WHERE metrics_id IN (SELECT id FROM metrics WHERE timestamp < ?)",
+                "idx_protocol_metrics_metrics",
+            ),
+            (
+                "SELECT * FROM alerts WHERE acknowledged = false AND account_id = ? ORDER BY created_at DESC, id DESC",
+                "idx_alerts_acknowledged",
+            ),
+            ("DELETE FROM reallocations WHERE timestamp < ?", "idx_reallocations_timestamp"),
+            ("SELECT * FROM rate_limit_log WHERE api_key_id = ? AND window_start < ?", "idx_rate_limit_key_window"),
+        ] {
+            let plan = query_plan(&pool, sql).await;
+            assert!(plan.contains(index), "{} does not use {}:\n{}", sql, index, plan);
+        }
+    }
+
+    /// Time the lookups covered by the query indexes
+    async fn time_indexed_lookups(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Vec<(&'static str, std::time::Duration)> {
+        let mut timings = Vec::new();
+
+        let started = std::time::Instant::now();
+        let rows = get_metrics_before(pool, cutoff).await.unwrap();
+        timings.push(("get_metrics_before", started.elapsed()));
+        assert_eq!(rows.len(), 1_000);
+
+        let started = std::time::Instant::now();
+        let rows = get_protocol_metrics_before(pool, cutoff).await.unwrap();
+        timings.push(("get_protocol_metrics_before", started.elapsed()));
+        assert_eq!(rows.len(), 1_000);
+
+        let started = std::time::Instant::now();
+        let rows = get_unacknowledged_alerts(pool, 1).await.unwrap();
+        timings.push(("get_unacknowledged_alerts", started.elapsed()));
+        assert_eq!(rows.len(), 1_000);
+
+        let started = std::time::Instant::now();
+        let (expiring,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM reallocations WHERE timestamp < ?")
+            .bind(cutoff.to_rfc3339())
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        timings.push(("expiring reallocations", started.elapsed()));
+        assert_eq!(expiring, 1_000);
+
+        timings
+    }
+
+    #[tokio::test]
+    #[ignore = "benchmark; run with `cargo test --release bench_ -- --ignored --nocapture`"]
+    async fn bench_query_indexes_on_million_rows() {
+        const ROWS: i64 = 1_000_000;
+        const EPOCH: i64 = 1_750_000_000;
+
+        let pool = crate::db::test_pool().await;
+
+        // One row a minute, spread over four accounts
+        let series = "WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i + 1 < ?) \
+                      SELECT 1 + i % 4 AS account, strftime('%Y-%m-%dT%H:%M:%S+00:00', ? + i * 60, 'unixepoch') AS ts, i \
+                      FROM n";
+        for insert in [
+            "INSERT INTO metrics (account_id, timestamp, total_earnings_per_hour) SELECT account, ts, i",
+            "INSERT INTO alerts (account_id, timestamp, alert_type, severity, message, acknowledged) \
+             SELECT account, ts, 'TEST', 50.0, 'test', i % 1000 != 0",
+            "INSERT INTO reallocations (account_id, timestamp, protocol_name, old_allocation, new_allocation) \
+             SELECT account, ts, 'storj', 25.0, 30.0",
+        ] {
+            sqlx::query(&format!("{} FROM ({})", insert, series))
+                .bind(ROWS)
+                .bind(EPOCH)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO protocol_metrics (metrics_id, protocol_name, earnings_per_hour, allocation_percent) \
+             SELECT id, 'storj', total_earnings_per_hour, 100.0 FROM metrics",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // The oldest 1,000 rows of each table have expired
+        let cutoff = DateTime::from_timestamp(EPOCH + 1_000 * 60, 0).unwrap();
+        let indexed = time_indexed_lookups(&pool, cutoff).await;
+        for index in [
+            "idx_metrics_timestamp",
+            "idx_protocol_metrics_metrics",
+            "idx_alerts_acknowledged",
+            "idx_reallocations_timestamp",
+        ] {
+            sqlx::query(&format!("DROP INDEX {}", index)).execute(&pool).await.unwrap();
+        }
+        let unindexed = time_indexed_lookups(&pool, cutoff).await;
+
+        for ((query, with), (_, without)) in indexed.iter().zip(&unindexed) {
+            println!("{:<28} {:>10.2?} indexed {:>10.2?} without", query, with, without);
+            assert!(with < without, "{} is not faster with its index", query);
+        }
+    }
 }
@@ -0,0 +1,507 @@
+/// Storage Protocol Disk Quotas
+///
+/// Storage nodes are disqualified when their disk fills up under them, and
+/// only honor allocations written to their own configuration. Protocols mapped
+/// in `[disk_quotas]` have every allocation's `storage_gb` checked against the
+/// filesystem backing their data directory (less a free-space reserve) and
+/// written to the node's config: `storage.allocated-disk-space` in a Storj
+/// node's `config.yaml`, `MaxStorage` in a Lotus storage path's
+/// `sectorstore.json`. Nodes read these at startup, so a change takes effect
+/// when the node restarts. Each poll reports the filesystem's free space as
+/// protocol metrics, which the alert task watches.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::protocols::http::HttpClientFactory;
+use crate::protocols::{
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus, MetricAggregation, MetricSchema,
+    ProtocolAdapter, ProtocolError, ProtocolResult, ResourceMetrics, ResourceProfile,
+};
+
+/// Bytes per GB of storage
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Metric with the percentage of the data directory's filesystem that is free
+pub const DISK_FREE_PERCENT_METRIC: &str = "disk_free_percent";
+
+/// Metric with the free space of the data directory's filesystem, in GB
+pub const DISK_FREE_GB_METRIC: &str = "disk_free_gb";
+
+/// Storj node setting holding the allocated disk space
+const STORJ_ALLOCATED_SPACE_KEY: &str = "storage.allocated-disk-space";
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Disk quota settings (`[disk_quotas]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskQuotaConfig {
+    /// Enforce storage allocations of the protocols below
+    pub enabled: bool,
+    /// Percent of each filesystem no allocation may claim
+    pub reserve_percent: f64,
+    /// Disk of each protocol (`[disk_quotas.protocols.<name>]`)
+    pub protocols: BTreeMap<String, ProtocolDisk>,
+}
+
+impl Default for DiskQuotaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            reserve_percent: 10.0,
+            protocols: BTreeMap::new(),
+        }
+    }
+}
+
+/// A storage protocol's disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolDisk {
+    /// Directory the node stores data in
+    pub data_dir: PathBuf,
+    /// Node config the allocated space is written to (Storj `config.yaml`,
+    /// Lotus `sectorstore.json`); allocations are only checked when unset
+    #[serde(default)]
+    pub node_config: Option<PathBuf>,
+}
+
+impl DiskQuotaConfig {
+    /// Validate the section
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !(0.0..100.0).contains(&self.reserve_percent) {
+            return Err("reserve_percent must be at least 0 and below 100".to_string());
+        }
+        for (protocol, disk) in &self.protocols {
+            if disk.node_config.is_some() && NodeConfigFormat::for_protocol(protocol).is_none() {
+                return Err(format!(
+                    "protocols.{}.node_config is only supported for storj and filecoin",
+                    protocol
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How a protocol's node stores its allocated space
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeConfigFormat {
+    /// Storj storage node `config.yaml`
+    StorjYaml,
+    /// Lotus storage path `sectorstore.json`
+    LotusSectorStore,
+}
+
+impl NodeConfigFormat {
+    fn for_protocol(protocol: &str) -> Option<Self> {
+        match protocol {
+            "storj" => Some(Self::StorjYaml),
+            "filecoin" => Some(Self::LotusSectorStore),
+            _ => None,
+        }
+    }
+
+    /// `contents` with the allocated space set to `storage_gb`
+    fn set_allocated_space(&self, contents: &str, storage_gb: f64) -> Result<String, String> {
+        match self {
+            Self::StorjYaml => Ok(set_storj_allocated_space(contents, storage_gb)),
+            Self::LotusSectorStore => set_lotus_max_storage(contents, storage_gb),
+        }
+    }
+}
+
+/// Replace (or append) the allocated disk space line of a Storj `config.yaml`
+fn set_storj_allocated_space(yaml: &str, storage_gb: f64) -> String {
+    let setting = format!("{}: {:.2} GB", STORJ_ALLOCATED_SPACE_KEY, storage_gb);
+    let mut replaced = false;
+    let mut lines: Vec<String> = yaml
+        .lines()
+        .map(|line| {
+            let key = line.trim_start().split(':').next().unwrap_or_default().trim();
+            if !replaced && key == STORJ_ALLOCATED_SPACE_KEY {
+                replaced = true;
+                setting.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(setting);
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Set `MaxStorage` (bytes) of a Lotus `sectorstore.json`
+fn set_lotus_max_storage(json: &str, storage_gb: f64) -> Result<String, String> {
+    let mut store: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let Some(fields) = store.as_object_mut() else {
+        return Err("expected a JSON object".to_string());
+    };
+    let bytes = (storage_gb * BYTES_PER_GB).round() as u64;
+    fields.insert("MaxStorage".to_string(), bytes.into());
+    serde_json::to_string_pretty(&store).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Disk quota errors
+#[derive(Debug, Error)]
+pub enum DiskQuotaError {
+    #[error("Protocol {0} has no managed disk")]
+    NotManaged(String),
+
+    #[error("{requested_gb:.1} GB exceeds the {available_gb:.1} GB {path} can hold")]
+    InsufficientSpace {
+        path: String,
+        requested_gb: f64,
+        available_gb: f64,
+    },
+
+    #[error("Invalid node config {path}: {message}")]
+    InvalidNodeConfig { path: String, message: String },
+
+    #[error("{path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Size and free space of the filesystem backing a data directory
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub total_gb: f64,
+    /// Space available to unprivileged writers
+    pub free_gb: f64,
+}
+
+impl DiskUsage {
+    /// Read the filesystem holding `path`
+    pub fn of(path: &Path) -> Result<Self, DiskQuotaError> {
+        let io_error = |source| DiskQuotaError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+
+            let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+                .map_err(|e| io_error(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)))?;
+            let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+            // SAFETY: `c_path` is NUL-terminated and `stats` is only read after
+            // statvfs reports success, having filled it.
+            let stats = unsafe {
+                if libc::statvfs(c_path.as_ptr(), stats.as_mut_ptr()) != 0 {
+                    return Err(io_error(std::io::Error::last_os_error()));
+                }
+                stats.assume_init()
+            };
+            let fragment = stats.f_frsize as f64;
+            Ok(Self {
+                total_gb: stats.f_blocks as f64 * fragment / BYTES_PER_GB,
+                free_gb: stats.f_bavail as f64 * fragment / BYTES_PER_GB,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            Err(io_error(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "disk usage is only available on Unix",
+            )))
+        }
+    }
+
+    /// Percent of the filesystem that is free
+    pub fn free_percent(&self) -> f64 {
+        if self.total_gb > 0.0 {
+            self.free_gb / self.total_gb * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Free space of a protocol's disk, from its polled metrics
+pub fn disk_free_space(metrics: &HashMap<String, f64>) -> Option<(f64, f64)> {
+    Some((*metrics.get(DISK_FREE_PERCENT_METRIC)?, *metrics.get(DISK_FREE_GB_METRIC)?))
+}
+
+// ============================================================================
+// DISK QUOTA MANAGER
+// ============================================================================
+
+/// Checks storage allocations against disks and writes them to node configs
+pub struct DiskQuotaManager {
+    config: DiskQuotaConfig,
+}
+
+impl DiskQuotaManager {
+    /// Enforce storage allocations of the protocols mapped in `config`
+    pub fn new(config: DiskQuotaConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether `protocol` has a managed disk
+    pub fn manages(&self, protocol: &str) -> bool {
+        self.config.protocols.contains_key(protocol)
+    }
+
+    fn disk(&self, protocol: &str) -> Result<&ProtocolDisk, DiskQuotaError> {
+        self.config
+            .protocols
+            .get(protocol)
+            .ok_or_else(|| DiskQuotaError::NotManaged(protocol.to_string()))
+    }
+
+    /// Usage of the filesystem backing `protocol`'s data directory
+    pub fn usage(&self, protocol: &str) -> Result<DiskUsage, DiskQuotaError> {
+        DiskUsage::of(&self.disk(protocol)?.data_dir)
+    }
+
+    /// Check `storage_gb` against `protocol`'s filesystem and write it to the node config
+    ///
+    /// The allocation may use the whole filesystem except the reserve; space
+    /// the node already fills counts towards it.
+    pub async fn apply(&self, protocol: &str, storage_gb: f64) -> Result<(), DiskQuotaError> {
+        let disk = self.disk(protocol)?;
+        let usage = DiskUsage::of(&disk.data_dir)?;
+        let available_gb = usage.total_gb * (1.0 - self.config.reserve_percent / 100.0);
+        if storage_gb > available_gb {
+            return Err(DiskQuotaError::InsufficientSpace {
+                path: disk.data_dir.display().to_string(),
+                requested_gb: storage_gb,
+                available_gb,
+            });
+        }
+
+        let (Some(path), Some(format)) = (&disk.node_config, NodeConfigFormat::for_protocol(protocol)) else {
+            return Ok(());
+        };
+        let io_error = |source| DiskQuotaError::Io {
+            path: path.clone(),
+            source,
+        };
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && format == NodeConfigFormat::StorjYaml => {
+                String::new()
+            }
+            Err(e) => return Err(io_error(e)),
+        };
+        let updated = format
+            .set_allocated_space(&contents, storage_gb)
+            .map_err(|message| DiskQuotaError::InvalidNodeConfig {
+                path: path.display().to_string(),
+                message,
+            })?;
+        if updated != contents {
+            // Written beside the config and renamed over it, so the node never reads half a file
+            let staging = path.with_extension("orcha-tmp");
+            tokio::fs::write(&staging, updated).await.map_err(io_error)?;
+            tokio::fs::rename(&staging, path).await.map_err(io_error)?;
+            tracing::info!("{} allocated {:.1} GB in {}", protocol, storage_gb, path.display());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// DISK QUOTA ADAPTER
+// ============================================================================
+
+/// Adapter for a storage protocol whose allocations are enforced on disk
+///
+/// Applies each allocation's `storage_gb` before handing it to the wrapped
+/// adapter, and reports the disk's free space with every earnings poll and
+/// health check.
+pub struct DiskQuotaAdapter {
+    inner: Box<dyn ProtocolAdapter>,
+    disks: Arc<DiskQuotaManager>,
+    /// Protocol instance the adapter is registered as, keying its `disks` entry
+    instance: String,
+}
+
+impl DiskQuotaAdapter {
+    /// Wrap `inner`, whose node stores data on a disk managed by `disks`, as
+    /// protocol instance `instance`
+    pub fn new(
+        inner: Box<dyn ProtocolAdapter>,
+        disks: Arc<DiskQuotaManager>,
+        instance: impl Into<String>,
+    ) -> Self {
+        Self { inner, disks, instance: instance.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProtocolAdapter for DiskQuotaAdapter {
+    fn protocol_name(&self) -> &str {
+        self.inner.protocol_name()
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        self.inner.disconnect().await
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.inner.connection_status()
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        let mut earnings = self.inner.get_current_earnings().await?;
+        match self.disks.usage(&self.instance) {
+            Ok(usage) => {
+                earnings.metrics.insert(DISK_FREE_PERCENT_METRIC.to_string(), usage.free_percent());
+                earnings.metrics.insert(DISK_FREE_GB_METRIC.to_string(), usage.free_gb);
+            }
+            Err(e) => tracing::warn!("Failed to read disk of {}: {}", self.instance, e),
+        }
+        Ok(earnings)
+    }
+
+    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        self.inner.get_historical_earnings(hours).await
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        self.inner.get_resource_usage().await
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        self.disks
+            .apply(&self.instance, strategy.storage_gb)
+            .await
+            .map_err(|e| ProtocolError::ResourceError(e.to_string()))?;
+        self.inner.apply_allocation(strategy).await
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        self.inner.get_current_allocation().await
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let mut status = self.inner.health_check().await?;
+        match self.disks.usage(&self.instance) {
+            Ok(usage) => {
+                if let Ok(value) = serde_json::to_value(usage) {
+                    status.metrics.insert("disk".to_string(), value);
+                }
+            }
+            Err(e) => {
+                status.metrics.insert("disk_error".to_string(), e.to_string().into());
+            }
+        }
+        Ok(status)
+    }
+
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        self.inner.check_credentials().await
+    }
+
+    fn configure_http(&mut self, http: &HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.inner.configure_http(http, protocol)
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        self.inner.get_config()
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        self.inner.resource_profile()
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        let mut schema = self.inner.metric_schema();
+        schema.extend([
+            MetricSchema::new(DISK_FREE_PERCENT_METRIC, "percent", MetricAggregation::Min, "Free disk space"),
+            MetricSchema::new(DISK_FREE_GB_METRIC, "GB", MetricAggregation::Min, "Free disk space"),
+        ]);
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storj_allocated_space_replaced_or_appended() {
+        let yaml = "# storage\nstorage.allocated-bandwidth: 0 B\nstorage.allocated-disk-space: 1.00 TB\nserver: x\n";
+        assert_eq!(
+            set_storj_allocated_space(yaml, 750.0),
+            "# storage\nstorage.allocated-bandwidth: 0 B\nstorage.allocated-disk-space: 750.00 GB\nserver: x\n"
+        );
+        assert_eq!(
+            set_storj_allocated_space("server: x", 2.5),
+            "server: x\nstorage.allocated-disk-space: 2.50 GB\n"
+        );
+    }
+
+    #[test]
+    fn test_lotus_max_storage_set_in_bytes() {
+        let json = r#"{"ID": "1d2c", "Weight": 10, "CanSeal": true, "CanStore": true, "MaxStorage": 0}"#;
+        let updated: serde_json::Value = serde_json::from_str(&set_lotus_max_storage(json, 2.0).unwrap()).unwrap();
+        assert_eq!(updated["MaxStorage"], 2_147_483_648u64);
+        assert_eq!(updated["ID"], "1d2c");
+        assert!(set_lotus_max_storage("[]", 1.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_allocation_checked_against_filesystem() {
+        let dir = std::env::temp_dir().join(format!("orcha-disk-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let node_config = dir.join("config.yaml");
+        let config: DiskQuotaConfig = toml::from_str(&format!(
+            "enabled = true\n[protocols.storj]\ndata_dir = {:?}\nnode_config = {:?}\n",
+            dir, node_config
+        ))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        let disks = DiskQuotaManager::new(config);
+
+        let usage = disks.usage("storj").unwrap();
+        assert!(usage.total_gb > 0.0 && (0.0..=100.0).contains(&usage.free_percent()));
+
+        disks.apply("storj", 0.5).await.unwrap();
+        let written = std::fs::read_to_string(&node_config).unwrap();
+        assert_eq!(written, "storage.allocated-disk-space: 0.50 GB\n");
+
+        let too_much = disks.apply("storj", usage.total_gb).await;
+        assert!(matches!(too_much, Err(DiskQuotaError::InsufficientSpace { .. })));
+        assert!(matches!(disks.apply("golem", 1.0).await, Err(DiskQuotaError::NotManaged(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disk_quota_config_validation() {
+        let config = |toml: &str| -> DiskQuotaConfig { toml::from_str(toml).unwrap() };
+        assert!(DiskQuotaConfig::default().validate().is_ok());
+        let golem = config("enabled = true\n[protocols.golem]\ndata_dir = \"/data\"\nnode_config = \"/etc/x\"\n");
+        assert!(golem.validate().unwrap_err().contains("protocols.golem.node_config"));
+        assert!(config("enabled = true\nreserve_percent = 100.0\n").validate().is_err());
+
+        let metrics = HashMap::from([
+            (DISK_FREE_PERCENT_METRIC.to_string(), 4.0),
+            (DISK_FREE_GB_METRIC.to_string(), 40.0),
+        ]);
+        assert_eq!(disk_free_space(&metrics), Some((4.0, 40.0)));
+        assert_eq!(disk_free_space(&HashMap::new()), None);
+    }
+}
@@ -0,0 +1,188 @@
+/// Orchestrator Event Bus
+///
+/// Producers (the polling and alert tasks, reallocations) publish
+/// [`OrchestratorEvent`]s to a shared [`EventBus`] without knowing who
+/// consumes them. Consumers implement [`EventSubscriber`] and are registered
+/// on the bus; each runs on its own task and receives every event in
+/// publication order. The monitor, the alert notifier and the WebSocket hub
+/// are subscribers, and code embedding the library can register its own
+/// handlers the same way.
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::notifications::Notification;
+use crate::orchestration::{AggregatedMetrics, AllocationChange};
+use crate::shutdown::ShutdownToken;
+
+/// Events buffered per subscriber before a slow subscriber starts missing events
+const BUS_CAPACITY: usize = 1024;
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// Something that happened in the orchestrator
+///
+/// Metrics events share the coordinator's snapshot, so fanning one out to
+/// every subscriber copies a pointer rather than the metrics.
+#[derive(Debug, Clone)]
+pub enum OrchestratorEvent {
+    /// Every protocol was polled
+    MetricsCollected(Arc<AggregatedMetrics>),
+    /// An alert was raised, or reopened after it had resolved
+    AlertRaised(RaisedAlert),
+    /// A reallocation plan was applied
+    ReallocationExecuted(ExecutedReallocation),
+    /// A protocol connected or disconnected since the previous poll
+    ProtocolStateChanged(ProtocolStateChange),
+}
+
+/// Alert carried by [`OrchestratorEvent::AlertRaised`]
+#[derive(Debug, Clone)]
+pub struct RaisedAlert {
+    /// Stored alert id
+    pub id: Option<i64>,
+    pub timestamp: DateTime<Utc>,
+    pub notification: Notification,
+}
+
+/// Reallocation carried by [`OrchestratorEvent::ReallocationExecuted`]
+#[derive(Debug, Clone)]
+pub struct ExecutedReallocation {
+    /// One change per protocol whose allocation moved
+    pub changes: Vec<AllocationChange>,
+    /// Optimizer decision behind the plan, when it came from the optimizer
+    pub decision_id: Option<i64>,
+}
+
+/// Connection change carried by [`OrchestratorEvent::ProtocolStateChanged`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolStateChange {
+    pub protocol: String,
+    pub connected: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+// ============================================================================
+// BUS
+// ============================================================================
+
+/// Handler for orchestrator events
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    /// Handle one event; events are delivered one at a time, in order
+    async fn on_event(&self, event: &OrchestratorEvent);
+}
+
+/// Shared fan-out of orchestrator events to subscribers
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<OrchestratorEvent>,
+}
+
+impl EventBus {
+    /// Create a bus buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Publish an event; returns the number of subscribers it reached
+    pub fn publish(&self, event: OrchestratorEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Receive all subsequently published events
+    pub fn subscribe(&self) -> broadcast::Receiver<OrchestratorEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Deliver all subsequently published events to `subscriber` until `shutdown`
+    ///
+    /// `name` identifies the subscriber in logs. A subscriber that falls more
+    /// than the bus capacity behind skips the oldest events.
+    pub fn register(
+        &self,
+        name: &str,
+        subscriber: Arc<dyn EventSubscriber>,
+        shutdown: ShutdownToken,
+    ) -> JoinHandle<()> {
+        let mut events = self.subscribe();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    event = events.recv() => event,
+                    _ = shutdown.cancelled() => break,
+                };
+                match event {
+                    Ok(event) => subscriber.on_event(&event).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("⚠️  Event subscriber {} lagging, skipped {} events", name, skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(BUS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// Forwards connection changes to a channel the test reads
+    struct Recorder(mpsc::UnboundedSender<String>);
+
+    #[async_trait]
+    impl EventSubscriber for Recorder {
+        async fn on_event(&self, event: &OrchestratorEvent) {
+            if let OrchestratorEvent::ProtocolStateChanged(change) = event {
+                let _ = self.0.send(format!("{}:{}", change.protocol, change.connected));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_events_in_order() {
+        let bus = EventBus::default();
+        let shutdown = crate::shutdown::Shutdown::new();
+        let mut receivers = Vec::new();
+        let mut handles = Vec::new();
+        for _ in 0..2 {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            receivers.push(receiver);
+            handles.push(bus.register("recorder", Arc::new(Recorder(sender)), shutdown.token()));
+        }
+
+        for (protocol, connected) in [("storj", false), ("grass", true), ("storj", true)] {
+            let change = ProtocolStateChange { protocol: protocol.to_string(), connected, timestamp: Utc::now() };
+            assert_eq!(bus.publish(OrchestratorEvent::ProtocolStateChanged(change)), 2);
+        }
+
+        for receiver in &mut receivers {
+            let mut received = Vec::new();
+            for _ in 0..3 {
+                let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await;
+                received.push(event.expect("subscriber did not receive the event").unwrap());
+            }
+            assert_eq!(received, ["storj:false", "grass:true", "storj:true"]);
+        }
+
+        shutdown.trigger();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    }
+}
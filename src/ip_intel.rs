@@ -0,0 +1,267 @@
+/// IP Reputation and Geolocation
+///
+/// Bandwidth-sharing protocols such as Grass pay for residential traffic: a
+/// datacenter, VPN or blocklisted IP earns little or nothing. The IP check
+/// task looks up the public IP each IP-sensitive protocol's requests leave
+/// from (through its `[http]` proxy, if any), classifies it, checks it
+/// against DNS blocklists, and alerts when it would hurt earnings — most
+/// often right after the IP changed.
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// ip-api.com lookup returning the fields `LookupResponse` reads
+const DEFAULT_LOOKUP_URL: &str =
+    "http://ip-api.com/json/?fields=status,message,query,countryCode,city,isp,mobile,proxy,hosting";
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// IP check settings (`[ip_intel]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IpIntelConfig {
+    /// Check the public IP on a schedule
+    pub enabled: bool,
+    /// Seconds between checks
+    pub interval_secs: u64,
+    /// Protocols whose earnings depend on the IP's quality
+    pub protocols: Vec<String>,
+    /// Lookup service answering with ip-api.com style JSON
+    pub lookup_url: String,
+    /// DNS blocklist zones the IP is checked against
+    pub blocklists: Vec<String>,
+    /// Country codes (ISO 3166-1 alpha-2) the IP is expected in; empty accepts any
+    pub expected_countries: Vec<String>,
+}
+
+impl Default for IpIntelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 3600,
+            protocols: vec!["grass".to_string()],
+            lookup_url: DEFAULT_LOOKUP_URL.to_string(),
+            blocklists: vec!["zen.spamhaus.org".to_string(), "b.barracudacentral.org".to_string()],
+            expected_countries: Vec::new(),
+        }
+    }
+}
+
+impl IpIntelConfig {
+    /// Validate configuration values
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_secs < 60 {
+            return Err("interval_secs must be at least 60".to_string());
+        }
+        Url::parse(&self.lookup_url).map_err(|e| format!("lookup_url: {}", e))?;
+        if self.blocklists.iter().any(|zone| zone.trim().is_empty()) {
+            return Err("blocklists must not contain empty zones".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// REPORTS
+// ============================================================================
+
+/// Kind of network an IP belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpType {
+    Residential,
+    Mobile,
+    /// Hosting provider or datacenter
+    Datacenter,
+}
+
+/// What is known about a public IP
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpReport {
+    pub ip: IpAddr,
+    pub ip_type: IpType,
+    /// Known VPN, proxy or Tor exit
+    pub proxy: bool,
+    pub country: Option<String>,
+    pub city: Option<String>,
+    pub isp: Option<String>,
+    /// Blocklist zones listing the IP
+    pub blocklists: Vec<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl IpReport {
+    /// Why the IP would hurt the earnings of IP-sensitive protocols
+    pub fn issues(&self, config: &IpIntelConfig) -> Vec<String> {
+        let mut issues = Vec::new();
+        if self.ip_type == IpType::Datacenter {
+            issues.push("a datacenter IP".to_string());
+        }
+        if self.proxy {
+            issues.push("a known VPN or proxy".to_string());
+        }
+        if !self.blocklists.is_empty() {
+            issues.push(format!("listed on {}", self.blocklists.join(", ")));
+        }
+        let country = self.country.as_deref().unwrap_or("an unknown country");
+        if !config.expected_countries.is_empty()
+            && !config.expected_countries.iter().any(|c| Some(c.as_str()) == self.country.as_deref())
+        {
+            issues.push(format!("located in {}", country));
+        }
+        issues
+    }
+}
+
+/// Lookup service response (ip-api.com format)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LookupResponse {
+    status: Option<String>,
+    message: Option<String>,
+    query: Option<IpAddr>,
+    country_code: Option<String>,
+    city: Option<String>,
+    isp: Option<String>,
+    #[serde(default)]
+    mobile: bool,
+    #[serde(default)]
+    proxy: bool,
+    #[serde(default)]
+    hosting: bool,
+}
+
+// ============================================================================
+// CHECKS
+// ============================================================================
+
+/// Look up the public IP `client`'s requests leave from and check it against
+/// the configured blocklists
+pub async fn check_ip(client: &reqwest::Client, config: &IpIntelConfig) -> Result<IpReport, String> {
+    let response: LookupResponse = client
+        .get(&config.lookup_url)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("IP lookup failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid IP lookup response: {}", e))?;
+
+    if response.status.as_deref().is_some_and(|s| s != "success") {
+        return Err(format!("IP lookup failed: {}", response.message.unwrap_or_default()));
+    }
+    let ip = response.query.ok_or("IP lookup response has no IP")?;
+    let ip_type = if response.hosting {
+        IpType::Datacenter
+    } else if response.mobile {
+        IpType::Mobile
+    } else {
+        IpType::Residential
+    };
+
+    Ok(IpReport {
+        ip,
+        ip_type,
+        proxy: response.proxy,
+        country: response.country_code,
+        city: response.city,
+        isp: response.isp,
+        blocklists: listed_on(ip, &config.blocklists).await,
+        checked_at: Utc::now(),
+    })
+}
+
+/// Blocklist `zones` listing `ip`
+///
+/// A zone lists an IP when its reversed-IP name resolves to a 127.0.0.0/8
+/// address; 127.255.255.x answers are the zone refusing the query and are
+/// ignored, as are lookup failures.
+pub async fn listed_on(ip: IpAddr, zones: &[String]) -> Vec<String> {
+    let mut listed = Vec::new();
+    for zone in zones {
+        let Ok(addresses) = tokio::net::lookup_host((dnsbl_name(ip, zone), 0)).await else {
+            continue;
+        };
+        let listing = addresses.map(|a| a.ip()).any(|answer| match answer {
+            IpAddr::V4(v4) => v4.octets()[0] == 127 && v4.octets()[1..3] != [255, 255],
+            IpAddr::V6(_) => false,
+        });
+        if listing {
+            listed.push(zone.clone());
+        }
+    }
+    listed
+}
+
+/// DNS name a blocklist `zone` answers for `ip`
+fn dnsbl_name(ip: IpAddr, zone: &str) -> String {
+    let reversed: Vec<String> = match ip {
+        IpAddr::V4(v4) => v4.octets().iter().rev().map(|o| o.to_string()).collect(),
+        IpAddr::V6(v6) => v6
+            .octets()
+            .iter()
+            .rev()
+            .flat_map(|b| [b & 0x0f, b >> 4])
+            .map(|nibble| format!("{:x}", nibble))
+            .collect(),
+    };
+    format!("{}.{}", reversed.join("."), zone.trim_end_matches('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dnsbl_name() {
+        assert_eq!(dnsbl_name("203.0.113.7".parse().unwrap(), "zen.spamhaus.org"), "7.113.0.203.zen.spamhaus.org");
+        let v6 = dnsbl_name("2001:db8::1".parse().unwrap(), "example.org.");
+        assert!(v6.starts_with("1.0.0.0.0.0.0.0"));
+        assert!(v6.ends_with("8.b.d.0.1.0.0.2.example.org"));
+    }
+
+    #[tokio::test]
+    async fn test_check_ip_classifies_lookup() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/json")
+            .with_body(
+                r#"{"status": "success", "query": "203.0.113.7", "countryCode": "DE", "city": "Frankfurt",
+                    "isp": "Hetzner Online GmbH", "mobile": false, "proxy": false, "hosting": true}"#,
+            )
+            .create_async()
+            .await;
+        let config = IpIntelConfig {
+            lookup_url: format!("{}/json", server.url()),
+            blocklists: Vec::new(),
+            expected_countries: vec!["US".to_string()],
+            ..IpIntelConfig::default()
+        };
+
+        let report = check_ip(&reqwest::Client::new(), &config).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(report.ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+        assert_eq!(report.ip_type, IpType::Datacenter);
+        assert_eq!(report.issues(&config), ["a datacenter IP", "located in DE"]);
+
+        let residential = IpReport { ip_type: IpType::Residential, country: Some("US".to_string()), ..report };
+        assert!(residential.issues(&config).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_lookup_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/json")
+            .with_body(r#"{"status": "fail", "message": "private range"}"#)
+            .create_async()
+            .await;
+        let config = IpIntelConfig { lookup_url: format!("{}/json", server.url()), ..IpIntelConfig::default() };
+        assert!(check_ip(&reqwest::Client::new(), &config).await.unwrap_err().contains("private range"));
+    }
+}
@@ -6,26 +6,50 @@
 #![warn(rust_2018_idioms)]
 
 pub mod api;
+pub mod backup;
+pub mod bootstrap;
+pub mod cgroups;
+pub mod cli;
+pub mod config;
+pub mod containers;
 pub mod db;
+pub mod disk_quotas;
+pub mod events;
+pub mod ip_intel;
+pub mod network_probe;
+pub mod notifications;
 pub mod orchestration;
+pub mod orchestrator;
 pub mod protocols;
 pub mod scheduler;
+pub mod secrets;
+pub mod services;
+pub mod shutdown;
+pub mod storage;
+pub mod timezone;
+pub mod wallets;
 
 // Re-export commonly used types
 pub use orchestration::{
-    AggregatedMetrics, Alert, AllocationChange, AllocationPlan, DashboardSnapshot,
+    AggregatedMetrics, Alert, AllocationChange, AllocationPlan, DashboardSnapshot, EarningsAttribution,
     OptimizationOpportunity, OrchestrationError, OrchestrationResult, PerformanceReport,
+    ResourceEfficiency,
 };
 
+pub use orchestration::cache::CacheConfig;
 pub use orchestration::coordinator::{ProtocolCoordinator, ProtocolStatus};
+pub use orchestration::ml_optimizer::{MlOptimizer, MlOptimizerConfig};
 pub use orchestration::monitor::{MonitorConfig, RealtimeMonitor};
-pub use orchestration::optimizer::{EarningsOptimizer, OptimizerConfig};
-pub use orchestration::reallocation::{ReallocationConfig, ReallocationEngine};
+pub use orchestration::optimizer::{EarningsOptimizer, OptimizerConfig, Strategy};
+pub use orchestration::reallocation::{
+    ProtocolConstraint, ReallocationConfig, ReallocationEngine, RolloutConfig, SwitchingCost,
+};
+pub use orchestration::resilience::ResilienceConfig;
+pub use orchestrator::{Orchestrator, OrchestratorBuilder, StartError};
+pub use protocols::registry::{AdapterFactory, ProtocolDescriptor, ProtocolInstance};
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn test_module_imports() {
         // Verify all modules are accessible
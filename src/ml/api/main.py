@@ -2,7 +2,7 @@
 
 from fastapi import FastAPI, HTTPException
 from pydantic import BaseModel
-from typing import Optional, List
+from typing import Dict, Optional, List
 import logging
 
 # Configure logging
@@ -49,11 +49,25 @@ async def predict_earnings(protocol: str, hours: int = 24):
     # TODO: Implement earnings prediction
     return {"message": f"Predictions for {protocol} not yet implemented"}
 
-@app.post("/optimize/allocation")
-async def optimize_allocation(protocols: List[str]):
-    """Optimize resource allocation"""
-    # TODO: Implement allocation optimization
-    return {"message": "Allocation optimization not yet implemented"}
+class AllocationRequest(BaseModel):
+    current: dict
+    history: List[dict] = []
+
+class AllocationRecommendation(BaseModel):
+    allocation: Dict[str, float]
+    confidence: float
+    model_version: Optional[str] = None
+
+@app.post("/optimize/allocation", response_model=AllocationRecommendation)
+async def optimize_allocation(request: AllocationRequest):
+    """Recommend resource allocation from the metrics history"""
+    # TODO: Serve the RL policy; until then echo the current allocation with
+    # zero confidence so the orchestrator keeps its heuristic plan
+    return AllocationRecommendation(
+        allocation=request.current.get("allocation_by_protocol", {}),
+        confidence=0.0,
+        model_version=None,
+    )
 
 if __name__ == "__main__":
     import uvicorn
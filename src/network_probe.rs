@@ -0,0 +1,212 @@
+/// Network Quality Probes
+///
+/// Bandwidth-sharing protocols can only sell the upstream the node actually
+/// has, which is often far less than what was configured. The probe task
+/// periodically measures latency, download and upload throughput with plain
+/// HTTP transfers against a speed test endpoint, stores the results as
+/// node-level metrics, and hands the latest one to the coordinator so the
+/// optimizer stops moving allocation to bandwidth protocols once the
+/// measured upstream is saturated.
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Network probe settings (`[network_probes]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkProbeConfig {
+    /// Run probes on a schedule
+    pub enabled: bool,
+    /// Seconds between probes; each one transfers `download_bytes` plus
+    /// `upload_bytes`, so keep this long on metered links
+    pub interval_secs: u64,
+    /// Endpoint answering quickly with an empty body, timed for latency
+    pub latency_url: String,
+    /// Requests timed for latency; the fastest one counts
+    pub latency_samples: u32,
+    /// Endpoint serving at least `download_bytes` for the download test
+    pub download_url: String,
+    /// Bytes read from `download_url`
+    pub download_bytes: u64,
+    /// Endpoint accepting a POST body for the upload test
+    pub upload_url: String,
+    /// Bytes sent to `upload_url`
+    pub upload_bytes: u64,
+    /// Timeout of each transfer
+    pub timeout_secs: u64,
+}
+
+impl Default for NetworkProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 6 * 3600,
+            latency_url: "https://speed.cloudflare.com/__down?bytes=0".to_string(),
+            latency_samples: 3,
+            download_url: "https://speed.cloudflare.com/__down?bytes=25000000".to_string(),
+            download_bytes: 25_000_000,
+            upload_url: "https://speed.cloudflare.com/__up".to_string(),
+            upload_bytes: 10_000_000,
+            timeout_secs: 60,
+        }
+    }
+}
+
+impl NetworkProbeConfig {
+    /// Validate configuration values
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_secs < 300 {
+            return Err("interval_secs must be at least 300".to_string());
+        }
+        for (name, url) in [
+            ("latency_url", &self.latency_url),
+            ("download_url", &self.download_url),
+            ("upload_url", &self.upload_url),
+        ] {
+            Url::parse(url).map_err(|e| format!("{}: {}", name, e))?;
+        }
+        if self.latency_samples == 0 {
+            return Err("latency_samples must be at least 1".to_string());
+        }
+        if self.download_bytes == 0 || self.upload_bytes == 0 {
+            return Err("download_bytes and upload_bytes must be at least 1".to_string());
+        }
+        if self.timeout_secs == 0 {
+            return Err("timeout_secs must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// PROBES
+// ============================================================================
+
+/// Measured network quality of the node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkProbe {
+    /// Fastest round trip to `latency_url`
+    pub latency_ms: f64,
+    pub download_mbps: f64,
+    /// Upstream bandwidth, what bandwidth protocols sell
+    pub upload_mbps: f64,
+    pub measured_at: DateTime<Utc>,
+}
+
+/// Measure latency, download and upload throughput through `client`
+pub async fn run_probe(client: &reqwest::Client, config: &NetworkProbeConfig) -> Result<NetworkProbe, String> {
+    let timeout = Duration::from_secs(config.timeout_secs);
+
+    let mut latency = Duration::MAX;
+    for _ in 0..config.latency_samples {
+        let started = Instant::now();
+        client
+            .get(&config.latency_url)
+            .timeout(timeout)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Latency probe failed: {}", e))?;
+        latency = latency.min(started.elapsed());
+    }
+
+    // Timed from the first byte, so the round trip doesn't count against throughput
+    let mut response = client
+        .get(&config.download_url)
+        .timeout(timeout)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Download probe failed: {}", e))?;
+    let started = Instant::now();
+    let mut downloaded = 0u64;
+    while downloaded < config.download_bytes {
+        match response.chunk().await.map_err(|e| format!("Download probe failed: {}", e))? {
+            Some(chunk) => downloaded += chunk.len() as u64,
+            None => break,
+        }
+    }
+    let download_mbps = mbps(downloaded, started.elapsed());
+
+    let started = Instant::now();
+    client
+        .post(&config.upload_url)
+        .timeout(timeout)
+        .body(vec![0u8; config.upload_bytes as usize])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Upload probe failed: {}", e))?;
+    let upload_mbps = mbps(config.upload_bytes, started.elapsed().saturating_sub(latency));
+
+    Ok(NetworkProbe {
+        latency_ms: latency.as_secs_f64() * 1000.0,
+        download_mbps,
+        upload_mbps,
+        measured_at: Utc::now(),
+    })
+}
+
+/// Throughput of `bytes` transferred in `elapsed`, in megabits per second
+fn mbps(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 * 8.0 / 1_000_000.0 / elapsed.as_secs_f64().max(0.001)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mbps() {
+        assert_eq!(mbps(12_500_000, Duration::from_secs(1)), 100.0);
+        assert_eq!(mbps(1_250_000, Duration::from_millis(500)), 20.0);
+        // Instant transfers don't divide by zero
+        assert!(mbps(1_000, Duration::ZERO).is_finite());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(NetworkProbeConfig::default().validate().is_ok());
+        let config = NetworkProbeConfig { interval_secs: 60, ..NetworkProbeConfig::default() };
+        assert!(config.validate().unwrap_err().contains("interval_secs"));
+        let config = NetworkProbeConfig { upload_url: "nowhere".to_string(), ..NetworkProbeConfig::default() };
+        assert!(config.validate().unwrap_err().contains("upload_url"));
+    }
+
+    #[tokio::test]
+    async fn test_run_probe_measures_transfers() {
+        let mut server = mockito::Server::new_async().await;
+        let latency = server.mock("GET", "/ping").expect(2).create_async().await;
+        let download = server.mock("GET", "/down").with_body(vec![b'x'; 200_000]).create_async().await;
+        let upload = server
+            .mock("POST", "/up")
+            .match_body(mockito::Matcher::Regex("^\0{1000}$".to_string()))
+            .create_async()
+            .await;
+        let config = NetworkProbeConfig {
+            latency_url: format!("{}/ping", server.url()),
+            latency_samples: 2,
+            download_url: format!("{}/down", server.url()),
+            download_bytes: 200_000,
+            upload_url: format!("{}/up", server.url()),
+            upload_bytes: 1_000,
+            ..NetworkProbeConfig::default()
+        };
+
+        let probe = run_probe(&reqwest::Client::new(), &config).await.unwrap();
+        latency.assert_async().await;
+        download.assert_async().await;
+        upload.assert_async().await;
+        assert!(probe.latency_ms >= 0.0);
+        assert!(probe.download_mbps > 0.0 && probe.upload_mbps > 0.0);
+
+        server.mock("POST", "/up").with_status(413).create_async().await;
+        let config = NetworkProbeConfig { upload_bytes: 1, ..config };
+        assert!(run_probe(&reqwest::Client::new(), &config).await.unwrap_err().contains("Upload probe"));
+    }
+}
@@ -0,0 +1,356 @@
+/// Earnings Digest
+///
+/// Summarizes the previous day or week (total earnings, best and worst
+/// protocol, allocation changes and uptime) and sends it through the
+/// notification channels at `[notifications.digest] hour`, on `weekday`
+/// for weekly digests.
+///
+/// A digest covers whole days ending at the midnight before it is sent, both
+/// in the `[reporting]` time zone.
+/// Earnings are estimated from each day's average hourly rate, as in
+/// earnings exports. Alerts raised in the period that a routing rule sends to
+/// the `digest` channel are listed at the end.
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use super::routing::{self, DIGEST_CHANNEL};
+use crate::db::models::{AlertFilter, Page};
+use crate::db::queries;
+use crate::orchestration::ProtocolUptime;
+use crate::timezone::ReportingTimezone;
+
+/// Most held alerts one digest collects
+const MAX_HELD_ALERTS: i64 = 200;
+
+/// Held alerts listed in the digest text; the rest are only counted
+const HELD_ALERT_LINES: usize = 10;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// How often a digest is sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    #[default]
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    /// Days one digest covers
+    pub fn days(self) -> i64 {
+        match self {
+            DigestFrequency::Off => 0,
+            DigestFrequency::Daily => 1,
+            DigestFrequency::Weekly => 7,
+        }
+    }
+}
+
+/// Digest schedule (`[notifications.digest]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub frequency: DigestFrequency,
+    /// Hour of the day (0-23, in the reporting time zone) the digest is sent
+    #[serde(alias = "hour_utc")]
+    pub hour: u32,
+    /// Day weekly digests are sent
+    pub weekday: Weekday,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            frequency: DigestFrequency::Off,
+            hour: 8,
+            weekday: Weekday::Mon,
+        }
+    }
+}
+
+impl DigestConfig {
+    /// Validate the send hour
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hour > 23 {
+            return Err(format!("digest.hour must be between 0 and 23, got {}", self.hour));
+        }
+        Ok(())
+    }
+
+    /// First send time after `now` in `tz`, or None when digests are off
+    pub fn next_send(&self, now: DateTime<Utc>, tz: ReportingTimezone) -> Option<DateTime<Utc>> {
+        if self.frequency == DigestFrequency::Off {
+            return None;
+        }
+        let time = NaiveTime::from_hms_opt(self.hour, 0, 0)?;
+        (0..=7)
+            .map(|days| tz.date_of(now) + Duration::days(days))
+            .filter(|day| self.frequency == DigestFrequency::Daily || day.weekday() == self.weekday)
+            .map(|day| tz.at(day, time))
+            .find(|at| *at > now)
+    }
+}
+
+// ============================================================================
+// DIGEST
+// ============================================================================
+
+/// Summary of one digest period
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub frequency: DigestFrequency,
+    /// First day covered (reporting time zone)
+    pub from: NaiveDate,
+    /// Last day covered (reporting time zone)
+    pub to: NaiveDate,
+    /// Estimated earnings by protocol (USD)
+    pub earnings_by_protocol: BTreeMap<String, f64>,
+    /// Allocation changes made, one per protocol moved
+    pub allocation_changes: usize,
+    /// Average uptime across protocols (%), `None` when nothing was tracked
+    pub uptime_percent: Option<f64>,
+    /// Alerts routed to the digest instead of being sent, oldest first
+    pub held_alerts: Vec<HeldAlert>,
+}
+
+/// Alert a routing rule held back for the digest
+#[derive(Debug, Clone, Serialize)]
+pub struct HeldAlert {
+    pub timestamp: String,
+    pub alert_type: String,
+    pub protocol: Option<String>,
+    /// Severity on the 0-100 alert scale
+    pub severity: f64,
+    pub message: String,
+}
+
+impl Digest {
+    /// Estimated earnings over the period (USD)
+    pub fn total_earnings(&self) -> f64 {
+        self.earnings_by_protocol.values().sum()
+    }
+
+    /// Highest-earning protocol
+    pub fn best_protocol(&self) -> Option<(&str, f64)> {
+        self.ranked().last().copied()
+    }
+
+    /// Lowest-earning protocol, when more than one protocol earned
+    pub fn worst_protocol(&self) -> Option<(&str, f64)> {
+        let ranked = self.ranked();
+        (ranked.len() > 1).then(|| ranked[0])
+    }
+
+    /// Protocols from lowest to highest earnings
+    fn ranked(&self) -> Vec<(&str, f64)> {
+        let mut ranked: Vec<(&str, f64)> = self
+            .earnings_by_protocol
+            .iter()
+            .map(|(protocol, earnings)| (protocol.as_str(), *earnings))
+            .collect();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+        ranked
+    }
+
+    /// Message text sent to the notification channels
+    pub fn render(&self) -> String {
+        let (title, period) = match self.frequency {
+            DigestFrequency::Weekly => ("Weekly", format!("{} to {}", self.from, self.to)),
+            _ => ("Daily", self.to.to_string()),
+        };
+
+        let mut text = format!("📊 {} earnings digest ({})\n", title, period);
+        let _ = writeln!(text, "Total earnings: ${:.2}", self.total_earnings());
+        if let Some((protocol, earnings)) = self.best_protocol() {
+            let _ = writeln!(text, "Best protocol: {} (${:.2})", protocol, earnings);
+        }
+        if let Some((protocol, earnings)) = self.worst_protocol() {
+            let _ = writeln!(text, "Worst protocol: {} (${:.2})", protocol, earnings);
+        }
+        let _ = writeln!(text, "Allocation changes: {}", self.allocation_changes);
+        match self.uptime_percent {
+            Some(uptime) => {
+                let _ = write!(text, "Uptime: {:.1}%", uptime);
+            }
+            None => text.push_str("Uptime: n/a"),
+        }
+        if !self.held_alerts.is_empty() {
+            let _ = write!(text, "\nHeld alerts: {}", self.held_alerts.len());
+            for alert in self.held_alerts.iter().take(HELD_ALERT_LINES) {
+                let _ = write!(text, "\n- [{}] {}", alert.alert_type, alert.message);
+            }
+            if self.held_alerts.len() > HELD_ALERT_LINES {
+                let _ = write!(text, "\n…and {} more", self.held_alerts.len() - HELD_ALERT_LINES);
+            }
+        }
+        text
+    }
+}
+
+/// Build the `frequency` digest sent at `at`, covering the days in `tz` before it
+pub async fn build_digest(
+    pool: &SqlitePool,
+    account_id: i64,
+    frequency: DigestFrequency,
+    at: DateTime<Utc>,
+    tz: ReportingTimezone,
+) -> Result<Digest, sqlx::Error> {
+    let to = tz.date_of(at) - Duration::days(1);
+    let from = to - Duration::days(frequency.days().max(1) - 1);
+    let (start, end) = tz.days(from, to);
+
+    let mut earnings_by_protocol = BTreeMap::new();
+    for record in queries::get_daily_protocol_earnings(pool, account_id, from, to, tz).await? {
+        *earnings_by_protocol.entry(record.protocol_name).or_insert(0.0) += record.avg_earnings_per_hour * record.hours;
+    }
+
+    let allocation_changes = queries::get_reallocations_by_range(pool, account_id, start, end).await?.len();
+
+    let mut windows: HashMap<String, Vec<_>> = HashMap::new();
+    for window in queries::get_uptime_windows(pool, account_id, start).await? {
+        windows.entry(window.protocol_name.clone()).or_default().push(window);
+    }
+    let uptimes: Vec<f64> = windows
+        .values()
+        .filter_map(|windows| {
+            let uptime = ProtocolUptime::from_windows(windows, end);
+            match frequency {
+                DigestFrequency::Weekly => uptime.last_7d,
+                _ => uptime.last_24h,
+            }
+        })
+        .collect();
+    let uptime_percent = (!uptimes.is_empty()).then(|| uptimes.iter().sum::<f64>() / uptimes.len() as f64);
+
+    let rules: Vec<_> = queries::list_notification_rules(pool, account_id).await?.iter().map(|r| r.rule()).collect();
+    let mut held_alerts = Vec::new();
+    if rules.iter().any(|rule| rule.sends_to(DIGEST_CHANNEL)) {
+        let filter = AlertFilter { start: Some(start), end: Some(end), ..Default::default() };
+        let page = Page { limit: MAX_HELD_ALERTS, offset: 0, before_id: None };
+        // Newest first
+        for alert in queries::list_alerts(pool, account_id, &filter, page).await?.into_iter().rev() {
+            let held = routing::matching_rule(&rules, &alert.alert_type, alert.severity)
+                .is_some_and(|rule| rule.sends_to(DIGEST_CHANNEL));
+            if held {
+                held_alerts.push(HeldAlert {
+                    timestamp: alert.timestamp,
+                    alert_type: alert.alert_type,
+                    protocol: alert.protocol,
+                    severity: alert.severity,
+                    message: alert.message,
+                });
+            }
+        }
+    }
+
+    Ok(Digest {
+        frequency,
+        from,
+        to,
+        earnings_by_protocol,
+        allocation_changes,
+        uptime_percent,
+        held_alerts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_send() {
+        // Wednesday 2026-10-14, 10:30 UTC
+        let now = Utc.with_ymd_and_hms(2026, 10, 14, 10, 30, 0).unwrap();
+        let at = |d, h| Utc.with_ymd_and_hms(2026, 10, d, h, 0, 0).unwrap();
+
+        let utc = ReportingTimezone::Utc;
+
+        let daily = DigestConfig { frequency: DigestFrequency::Daily, hour: 8, ..Default::default() };
+        assert_eq!(daily.next_send(now, utc), Some(at(15, 8)));
+        let later = DigestConfig { hour: 18, ..daily.clone() };
+        assert_eq!(later.next_send(now, utc), Some(at(14, 18)));
+        // 08:00 at UTC+09:00 is 23:00 UTC the day before
+        assert_eq!(daily.next_send(now, "+09:00".parse().unwrap()), Some(at(14, 23)));
+
+        let weekly = DigestConfig { frequency: DigestFrequency::Weekly, weekday: Weekday::Wed, ..daily };
+        assert_eq!(weekly.next_send(now, utc), Some(at(21, 8)));
+        let monday = DigestConfig { weekday: Weekday::Mon, ..weekly };
+        assert_eq!(monday.next_send(now, utc), Some(at(19, 8)));
+
+        assert_eq!(DigestConfig::default().next_send(now, utc), None);
+        assert!(DigestConfig { hour: 24, ..Default::default() }.validate().is_err());
+        let legacy: DigestConfig = toml::from_str("frequency = \"daily\"\nhour_utc = 6").unwrap();
+        assert_eq!(legacy.hour, 6);
+    }
+
+    #[tokio::test]
+    async fn test_build_and_render_digest() {
+        let pool = crate::db::test_pool().await;
+
+        let at = Utc.with_ymd_and_hms(2026, 10, 14, 8, 0, 0).unwrap();
+        let yesterday = at - Duration::hours(12);
+        for (hour, grass) in [(0, 1.0), (1, 3.0)] {
+            let timestamp = yesterday + Duration::hours(hour);
+            let id = queries::store_metrics(&pool, 1, timestamp, grass + 0.5, 0.0, 0.0, 0.0, 0.0).await.unwrap();
+            queries::store_protocol_metrics(&pool, id, "grass".into(), grass, 50.0, true).await.unwrap();
+            queries::store_protocol_metrics(&pool, id, "storj".into(), 0.5, 50.0, true).await.unwrap();
+        }
+        // Two days ago: outside a daily digest
+        let old = queries::store_metrics(&pool, 1, at - Duration::days(2), 9.0, 0.0, 0.0, 0.0, 0.0).await.unwrap();
+        queries::store_protocol_metrics(&pool, old, "grass".into(), 9.0, 100.0, true).await.unwrap();
+        queries::store_reallocation(&pool, 1, yesterday, "grass".into(), 40.0, 50.0, None, None, None)
+            .await
+            .unwrap();
+        let hint = routing::NotificationRule {
+            id: None,
+            alert_type: Some("OPTIMIZATION_POTENTIAL".to_string()),
+            min_severity: 0.0,
+            max_severity: 100.0,
+            channels: vec![DIGEST_CHANNEL.to_string()],
+            priority: 0,
+        };
+        queries::create_notification_rule(&pool, 1, &hint).await.unwrap();
+        for alert_type in ["OPTIMIZATION_POTENTIAL", "DISK_SPACE_LOW"] {
+            queries::store_alert(&pool, 1, yesterday, alert_type.into(), 20.0, "Move 10% to grass".into())
+                .await
+                .unwrap();
+        }
+
+        let digest = build_digest(&pool, 1, DigestFrequency::Daily, at, ReportingTimezone::Utc).await.unwrap();
+        assert_eq!(digest.to, NaiveDate::from_ymd_opt(2026, 10, 13).unwrap());
+        assert_eq!(digest.earnings_by_protocol["grass"], 48.0);
+        assert_eq!(digest.total_earnings(), 60.0);
+        assert_eq!(digest.best_protocol(), Some(("grass", 48.0)));
+        assert_eq!(digest.worst_protocol(), Some(("storj", 12.0)));
+        assert_eq!(digest.allocation_changes, 1);
+        assert_eq!(digest.uptime_percent, None);
+
+        let text = digest.render();
+        assert!(text.starts_with("📊 Daily earnings digest (2026-10-13)"));
+        assert!(text.contains("Total earnings: $60.00"));
+        assert!(text.contains("Worst protocol: storj ($12.00)"));
+        assert_eq!(digest.held_alerts.len(), 1);
+        assert!(text.contains("Uptime: n/a\nHeld alerts: 1\n- [OPTIMIZATION_POTENTIAL] Move 10% to grass"));
+
+        let weekly = build_digest(&pool, 1, DigestFrequency::Weekly, at, ReportingTimezone::Utc).await.unwrap();
+        assert_eq!(weekly.from, NaiveDate::from_ymd_opt(2026, 10, 7).unwrap());
+        assert_eq!(weekly.earnings_by_protocol["grass"], 48.0 + 9.0 * 24.0);
+
+        // Yesterday evening UTC falls on the morning of the 14th nine hours east
+        let tokyo = "+09:00".parse().unwrap();
+        let at = Utc.with_ymd_and_hms(2026, 10, 14, 23, 0, 0).unwrap();
+        let digest = build_digest(&pool, 1, DigestFrequency::Daily, at, tokyo).await.unwrap();
+        assert_eq!(digest.to, NaiveDate::from_ymd_opt(2026, 10, 14).unwrap());
+        assert_eq!(digest.earnings_by_protocol["grass"], 48.0);
+        assert_eq!(digest.allocation_changes, 1);
+    }
+}
@@ -0,0 +1,744 @@
+/// Alert Notifications
+///
+/// Pushes newly raised alerts to chat webhooks so operators hear about
+/// problems without keeping the dashboard open. Discord and Slack incoming
+/// webhooks, Telegram bots and a generic JSON webhook are supported; every
+/// channel renders the same message template. The same channels carry the
+/// earnings [`digest`].
+///
+/// Alerts reach the channels through [`AlertNotifier`], which subscribes to
+/// `AlertRaised` events on the orchestrator's event bus and picks each
+/// alert's channels with the [`routing`] rules. Alerts raised during
+/// [`quiet`] hours or a maintenance window are not sent.
+///
+/// Template placeholders:
+/// - `{alert_type}`, `{message}`
+/// - `{protocol}` (`all protocols` for host-wide alerts)
+/// - `{severity}` (0-100) and `{severity_label}` (`low`, `medium`, `high`, `critical`)
+/// - `{earnings}` and `{earnings_delta}` (USD/hour, delta over the last hour)
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::events::{EventSubscriber, OrchestratorEvent};
+
+pub mod digest;
+pub mod quiet;
+pub mod routing;
+
+use digest::DigestConfig;
+use quiet::{MaintenanceWindow, QuietHours};
+use routing::NotificationRule;
+
+/// Discord rejects message content longer than this many characters
+const DISCORD_MAX_CONTENT_CHARS: usize = 2000;
+
+/// Telegram rejects message text longer than this many characters
+const TELEGRAM_MAX_TEXT_CHARS: usize = 4096;
+
+/// Telegram Bot API base URL
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Template used when `[notifications] template` is not set
+pub const DEFAULT_TEMPLATE: &str = "🚨 [{severity_label}] {alert_type} on {protocol}: {message} \
+     (earnings {earnings}, {earnings_delta} over the last hour)";
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Notification configuration (`[notifications]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Discord incoming webhook URL
+    pub discord_webhook_url: Option<String>,
+    /// Slack incoming webhook URL
+    pub slack_webhook_url: Option<String>,
+    /// Telegram bot token, used together with `telegram_chat_id`
+    pub telegram_bot_token: Option<String>,
+    /// Telegram chat the bot posts to
+    pub telegram_chat_id: Option<String>,
+    /// Generic webhook URL, posted `{"text": "..."}`
+    pub webhook_url: Option<String>,
+    /// Message template
+    pub template: String,
+    /// Alerts below this severity (0-100) are not sent
+    pub min_severity: f64,
+    pub request_timeout_secs: u64,
+    /// Earnings digest schedule
+    pub digest: DigestConfig,
+    /// Recurring windows during which alerts are not sent
+    pub quiet_hours: Vec<QuietHours>,
+    /// One-off windows during which alerts are not sent
+    pub maintenance: Vec<MaintenanceWindow>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            discord_webhook_url: None,
+            slack_webhook_url: None,
+            telegram_bot_token: None,
+            telegram_chat_id: None,
+            webhook_url: None,
+            template: DEFAULT_TEMPLATE.to_string(),
+            min_severity: 0.0,
+            request_timeout_secs: 10,
+            digest: DigestConfig::default(),
+            quiet_hours: Vec::new(),
+            maintenance: Vec::new(),
+        }
+    }
+}
+
+impl NotificationsConfig {
+    /// Load from the `[notifications]` section of the config file, falling back to defaults
+    pub fn from_table(config: &toml::Table) -> Result<Self, NotificationError> {
+        let Some(section) = config.get("notifications") else {
+            return Ok(Self::default());
+        };
+
+        let config: Self = section
+            .clone()
+            .try_into()
+            .map_err(|e: toml::de::Error| NotificationError::Config(e.to_string()))?;
+        config.digest.validate().map_err(NotificationError::Config)?;
+        quiet::validate(&config.quiet_hours, &config.maintenance).map_err(NotificationError::Config)?;
+        Ok(config)
+    }
+
+    /// Why an alert of `severity` raised at `at` is not sent, if a quiet hours
+    /// or maintenance window silences it
+    pub fn silenced_by(&self, at: DateTime<Utc>, severity: f64) -> Option<String> {
+        quiet::silenced_by(&self.quiet_hours, &self.maintenance, at, severity)
+    }
+}
+
+// ============================================================================
+// ERRORS AND MESSAGES
+// ============================================================================
+
+/// Notification delivery errors
+#[derive(Error, Debug)]
+pub enum NotificationError {
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Webhook request failed: {0}")]
+    Network(String),
+
+    #[error("Webhook returned HTTP {status}: {body}")]
+    Rejected { status: u16, body: String },
+}
+
+/// Alert details available to the message template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub alert_type: String,
+    /// Affected protocol, `None` for host-wide alerts
+    pub protocol: Option<String>,
+    /// Severity on the 0-100 alert scale
+    pub severity: f64,
+    pub message: String,
+    /// Current total earnings (USD/hour)
+    pub earnings: Option<f64>,
+    /// Change in earnings over the last hour (USD/hour)
+    pub earnings_delta: Option<f64>,
+}
+
+impl Notification {
+    /// Human-readable severity bucket
+    pub fn severity_label(&self) -> &'static str {
+        match self.severity {
+            s if s >= 75.0 => "critical",
+            s if s >= 50.0 => "high",
+            s if s >= 25.0 => "medium",
+            _ => "low",
+        }
+    }
+
+    /// Render `template`, substituting every known placeholder
+    pub fn render(&self, template: &str) -> String {
+        let earnings = self
+            .earnings
+            .map_or_else(|| "n/a".to_string(), |e| format!("${:.2}/h", e));
+        let earnings_delta = self.earnings_delta.map_or_else(
+            || "n/a".to_string(),
+            |d| format!("{}${:.2}/h", if d < 0.0 { "-" } else { "+" }, d.abs()),
+        );
+
+        template
+            .replace("{alert_type}", &self.alert_type)
+            .replace("{protocol}", self.protocol.as_deref().unwrap_or("all protocols"))
+            .replace("{severity_label}", self.severity_label())
+            .replace("{severity}", &format!("{:.0}", self.severity))
+            .replace("{message}", &self.message)
+            .replace("{earnings_delta}", &earnings_delta)
+            .replace("{earnings}", &earnings)
+    }
+}
+
+// ============================================================================
+// NOTIFIERS
+// ============================================================================
+
+/// A channel that delivers rendered alert messages
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Channel name used in logs and API responses
+    fn name(&self) -> &'static str;
+
+    /// Deliver a rendered message
+    async fn send(&self, text: &str) -> Result<(), NotificationError>;
+}
+
+/// Discord incoming webhook
+pub struct DiscordNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl DiscordNotifier {
+    pub fn new(url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+
+    /// Webhook body for `text`, truncated to Discord's content limit
+    pub fn payload(text: &str) -> Value {
+        let content: String = if text.chars().count() > DISCORD_MAX_CONTENT_CHARS {
+            text.chars()
+                .take(DISCORD_MAX_CONTENT_CHARS - 1)
+                .chain(std::iter::once('…'))
+                .collect()
+        } else {
+            text.to_string()
+        };
+        json!({ "content": content })
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    async fn send(&self, text: &str) -> Result<(), NotificationError> {
+        post_webhook(&self.client, &self.url, &Self::payload(text)).await
+    }
+}
+
+/// Slack incoming webhook
+pub struct SlackNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl SlackNotifier {
+    pub fn new(url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+
+    /// Webhook body for `text`
+    pub fn payload(text: &str) -> Value {
+        json!({ "text": text })
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    async fn send(&self, text: &str) -> Result<(), NotificationError> {
+        post_webhook(&self.client, &self.url, &Self::payload(text)).await
+    }
+}
+
+/// Telegram bot posting to one chat
+pub struct TelegramNotifier {
+    url: String,
+    chat_id: String,
+    client: reqwest::Client,
+}
+
+impl TelegramNotifier {
+    pub fn new(bot_token: &str, chat_id: impl Into<String>, client: reqwest::Client) -> Self {
+        Self::with_base_url(TELEGRAM_API_URL, bot_token, chat_id, client)
+    }
+
+    /// Post to a Bot API server other than api.telegram.org
+    pub fn with_base_url(base_url: &str, bot_token: &str, chat_id: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            url: format!("{}/bot{}/sendMessage", base_url.trim_end_matches('/'), bot_token),
+            chat_id: chat_id.into(),
+            client,
+        }
+    }
+
+    /// `sendMessage` body for `text`, truncated to Telegram's text limit
+    pub fn payload(chat_id: &str, text: &str) -> Value {
+        let text: String = if text.chars().count() > TELEGRAM_MAX_TEXT_CHARS {
+            text.chars()
+                .take(TELEGRAM_MAX_TEXT_CHARS - 1)
+                .chain(std::iter::once('…'))
+                .collect()
+        } else {
+            text.to_string()
+        };
+        json!({ "chat_id": chat_id, "text": text })
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    async fn send(&self, text: &str) -> Result<(), NotificationError> {
+        post_webhook(&self.client, &self.url, &Self::payload(&self.chat_id, text)).await
+    }
+}
+
+/// Generic webhook receiving `{"text": "..."}`
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn send(&self, text: &str) -> Result<(), NotificationError> {
+        post_webhook(&self.client, &self.url, &json!({ "text": text })).await
+    }
+}
+
+async fn post_webhook(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &Value,
+) -> Result<(), NotificationError> {
+    let response = client
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| NotificationError::Network(e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(NotificationError::Rejected {
+        status: status.as_u16(),
+        body,
+    })
+}
+
+// ============================================================================
+// DISPATCHER
+// ============================================================================
+
+/// Outcome of delivering a message to one channel
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryResult {
+    pub channel: String,
+    pub delivered: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Fans alert notifications out to every configured channel
+pub struct NotificationDispatcher {
+    config: NotificationsConfig,
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl NotificationDispatcher {
+    /// Create a dispatcher with a notifier for each configured channel
+    pub fn new(config: NotificationsConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+        if let Some(url) = config.discord_webhook_url.as_deref().filter(|u| !u.is_empty()) {
+            notifiers.push(Box::new(DiscordNotifier::new(url, client.clone())));
+        }
+        if let Some(url) = config.slack_webhook_url.as_deref().filter(|u| !u.is_empty()) {
+            notifiers.push(Box::new(SlackNotifier::new(url, client.clone())));
+        }
+        let telegram_token = config.telegram_bot_token.as_deref().filter(|t| !t.is_empty());
+        let telegram_chat = config.telegram_chat_id.as_deref().filter(|c| !c.is_empty());
+        if let (Some(token), Some(chat_id)) = (telegram_token, telegram_chat) {
+            notifiers.push(Box::new(TelegramNotifier::new(token, chat_id, client.clone())));
+        }
+        if let Some(url) = config.webhook_url.as_deref().filter(|u| !u.is_empty()) {
+            notifiers.push(Box::new(WebhookNotifier::new(url, client)));
+        }
+
+        Self { config, notifiers }
+    }
+
+    /// Dispatcher configuration
+    pub fn config(&self) -> &NotificationsConfig {
+        &self.config
+    }
+
+    /// Whether no channels are configured
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    /// Names of the configured channels
+    pub fn channels(&self) -> Vec<&'static str> {
+        self.notifiers.iter().map(|n| n.name()).collect()
+    }
+
+    /// Send an alert notification, skipping alerts below `min_severity`
+    pub async fn notify(&self, notification: &Notification) -> Vec<DeliveryResult> {
+        if self.is_empty() || notification.severity < self.config.min_severity {
+            return Vec::new();
+        }
+        self.send(notification).await
+    }
+
+    /// Send an alert notification to the channels of the first matching rule
+    ///
+    /// Alerts no rule matches are sent as by [`Self::notify`]. Channels the
+    /// rule names but which are not configured are skipped, as is `digest`:
+    /// the digest picks those alerts up itself.
+    pub async fn notify_routed(&self, notification: &Notification, rules: &[NotificationRule]) -> Vec<DeliveryResult> {
+        match routing::matching_rule(rules, &notification.alert_type, notification.severity) {
+            Some(rule) => {
+                let text = notification.render(&self.config.template);
+                self.send_text_to(&text, |channel| rule.sends_to(channel)).await
+            }
+            None => self.notify(notification).await,
+        }
+    }
+
+    /// Send a notification to every channel regardless of severity
+    pub async fn send(&self, notification: &Notification) -> Vec<DeliveryResult> {
+        self.send_text(&notification.render(&self.config.template)).await
+    }
+
+    /// Send an already rendered message, such as a digest, to every channel
+    pub async fn send_text(&self, text: &str) -> Vec<DeliveryResult> {
+        self.send_text_to(text, |_| true).await
+    }
+
+    /// Send an already rendered message to the channels `include` accepts
+    async fn send_text_to(&self, text: &str, include: impl Fn(&str) -> bool) -> Vec<DeliveryResult> {
+        let mut results = Vec::with_capacity(self.notifiers.len());
+        for notifier in self.notifiers.iter().filter(|n| include(n.name())) {
+            let result = notifier.send(text).await;
+            if let Err(e) = &result {
+                log::warn!("⚠️  Failed to send {} notification: {}", notifier.name(), e);
+            }
+            results.push(DeliveryResult {
+                channel: notifier.name().to_string(),
+                delivered: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+        results
+    }
+}
+
+// ============================================================================
+// ALERT NOTIFIER
+// ============================================================================
+
+/// Sends alerts raised on the event bus to the notification channels
+pub struct AlertNotifier {
+    dispatcher: Arc<NotificationDispatcher>,
+    db_pool: SqlitePool,
+    /// Account whose earnings history the delta is computed from
+    account_id: i64,
+}
+
+impl AlertNotifier {
+    /// Create a notifier sending through `dispatcher`
+    pub fn new(dispatcher: Arc<NotificationDispatcher>, db_pool: SqlitePool, account_id: i64) -> Self {
+        Self {
+            dispatcher,
+            db_pool,
+            account_id,
+        }
+    }
+
+    /// Fill in the earnings delta over the last hour and send `notification`
+    /// to the channels the routing rules pick
+    ///
+    /// The delta compares current earnings with the oldest snapshot recorded in
+    /// the past hour, per protocol when the alert names one. Rules are read for
+    /// every alert, so changes apply immediately. Nothing is sent while a quiet
+    /// hours or maintenance window silences the alert.
+    async fn send_alert(&self, mut notification: Notification) {
+        use crate::db::queries::{get_metrics_by_range, get_protocol_metrics_by_range};
+
+        if self.dispatcher.is_empty() {
+            return;
+        }
+        let end = Utc::now();
+        if let Some(reason) = self.dispatcher.config().silenced_by(end, notification.severity) {
+            log::info!("🔕 {} alert not sent during {}", notification.alert_type, reason);
+            return;
+        }
+
+        let start = end - chrono::Duration::hours(1);
+        let baseline = match get_metrics_by_range(&self.db_pool, self.account_id, start, end).await {
+            // Newest first
+            Ok(history) => history.into_iter().last(),
+            Err(e) => {
+                log::warn!("⚠️  Failed to load earnings baseline for notification: {}", e);
+                None
+            }
+        };
+
+        if let (Some(baseline), Some(current)) = (baseline, notification.earnings) {
+            notification.earnings_delta = match &notification.protocol {
+                None => Some(current - baseline.total_earnings_per_hour),
+                Some(protocol) => get_protocol_metrics_by_range(&self.db_pool, self.account_id, start, end)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|row| Some(row.metrics_id) == baseline.id && &row.protocol_name == protocol)
+                    .map(|row| current - row.earnings_per_hour),
+            };
+        }
+
+        let rules = routing::load_rules(&self.db_pool, self.account_id).await;
+        self.dispatcher.notify_routed(&notification, &rules).await;
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for AlertNotifier {
+    async fn on_event(&self, event: &OrchestratorEvent) {
+        if let OrchestratorEvent::AlertRaised(alert) = event {
+            self.send_alert(alert.notification.clone()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification() -> Notification {
+        Notification {
+            alert_type: "PROTOCOL_DEGRADED".to_string(),
+            protocol: Some("grass".to_string()),
+            severity: 75.0,
+            message: "grass is failing repeatedly".to_string(),
+            earnings: Some(4.5),
+            earnings_delta: Some(-1.25),
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let text = notification().render(
+            "{alert_type} {protocol} {severity} {severity_label} {earnings} {earnings_delta}: {message}",
+        );
+        assert_eq!(
+            text,
+            "PROTOCOL_DEGRADED grass 75 critical $4.50/h -$1.25/h: grass is failing repeatedly"
+        );
+
+        let host_wide = Notification {
+            protocol: None,
+            severity: 10.0,
+            earnings: None,
+            earnings_delta: Some(0.5),
+            ..notification()
+        };
+        assert_eq!(
+            host_wide.render("{protocol} {severity_label} {earnings} {earnings_delta}"),
+            "all protocols low n/a +$0.50/h"
+        );
+    }
+
+    #[test]
+    fn test_payloads() {
+        assert_eq!(SlackNotifier::payload("hi"), json!({ "text": "hi" }));
+        assert_eq!(DiscordNotifier::payload("hi"), json!({ "content": "hi" }));
+        assert_eq!(TelegramNotifier::payload("42", "hi"), json!({ "chat_id": "42", "text": "hi" }));
+
+        let long = "x".repeat(DISCORD_MAX_CONTENT_CHARS + 10);
+        let content = DiscordNotifier::payload(&long)["content"].as_str().unwrap().to_string();
+        assert_eq!(content.chars().count(), DISCORD_MAX_CONTENT_CHARS);
+        assert!(content.ends_with('…'));
+        let long = "x".repeat(TELEGRAM_MAX_TEXT_CHARS + 10);
+        let text = TelegramNotifier::payload("42", &long)["text"].as_str().unwrap().to_string();
+        assert_eq!(text.chars().count(), TELEGRAM_MAX_TEXT_CHARS);
+    }
+
+    #[test]
+    fn test_config_from_table() {
+        let table: toml::Table = toml::from_str(
+            r#"
+            [notifications]
+            discord_webhook_url = "https://discord.example/hook"
+            telegram_bot_token = "123:abc"
+            min_severity = 50.0
+
+            [notifications.digest]
+            frequency = "weekly"
+            weekday = "Fri"
+
+            [[notifications.quiet_hours]]
+            start = "22:00"
+            end = "07:00"
+            bypass_severity = 90.0
+
+            [[notifications.maintenance]]
+            start = "2026-10-20T02:00:00Z"
+            end = "2026-10-20T04:00:00Z"
+            reason = "kernel upgrade"
+            "#,
+        )
+        .unwrap();
+
+        let config = NotificationsConfig::from_table(&table).unwrap();
+        assert_eq!(config.min_severity, 50.0);
+        assert_eq!(config.template, DEFAULT_TEMPLATE);
+        assert!(config.slack_webhook_url.is_none());
+        assert_eq!(config.digest.frequency, digest::DigestFrequency::Weekly);
+        assert_eq!(config.digest.weekday, chrono::Weekday::Fri);
+        assert_eq!(config.quiet_hours[0].bypass_severity, Some(90.0));
+        let during_maintenance = "2026-10-20T03:00:00Z".parse().unwrap();
+        assert!(config.silenced_by(during_maintenance, 95.0).unwrap().contains("kernel upgrade"));
+
+        // A Telegram token without a chat id adds no channel
+        let dispatcher = NotificationDispatcher::new(config);
+        assert_eq!(dispatcher.channels(), vec!["discord"]);
+        assert!(NotificationDispatcher::new(NotificationsConfig::default()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_to_webhooks() {
+        let mut server = mockito::Server::new_async().await;
+        let discord = server
+            .mock("POST", "/discord")
+            .match_body(mockito::Matcher::PartialJson(json!({ "content": "grass critical" })))
+            .with_status(204)
+            .create_async()
+            .await;
+        let slack = server
+            .mock("POST", "/slack")
+            .with_status(404)
+            .with_body("no_service")
+            .create_async()
+            .await;
+
+        let dispatcher = NotificationDispatcher::new(NotificationsConfig {
+            discord_webhook_url: Some(format!("{}/discord", server.url())),
+            slack_webhook_url: Some(format!("{}/slack", server.url())),
+            template: "{protocol} {severity_label}".to_string(),
+            min_severity: 80.0,
+            ..Default::default()
+        });
+
+        // Below min_severity: nothing is sent
+        assert!(dispatcher.notify(&notification()).await.is_empty());
+
+        let results = dispatcher.send(&notification()).await;
+        discord.assert_async().await;
+        slack.assert_async().await;
+        assert!(results[0].delivered);
+        assert!(!results[1].delivered);
+        assert_eq!(
+            results[1].error.as_deref(),
+            Some("Webhook returned HTTP 404: no_service")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_routed_dispatch() {
+        let mut server = mockito::Server::new_async().await;
+        let discord = server.mock("POST", "/discord").expect(0).create_async().await;
+        let webhook = server
+            .mock("POST", "/webhook")
+            .match_body(mockito::Matcher::Json(json!({ "text": "grass" })))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dispatcher = NotificationDispatcher::new(NotificationsConfig {
+            discord_webhook_url: Some(format!("{}/discord", server.url())),
+            webhook_url: Some(format!("{}/webhook", server.url())),
+            template: "{protocol}".to_string(),
+            ..Default::default()
+        });
+        let rule = |alert_type: &str, channels: &[&str]| NotificationRule {
+            id: None,
+            alert_type: Some(alert_type.to_string()),
+            min_severity: 50.0,
+            max_severity: 100.0,
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            priority: 0,
+        };
+        let rules = [
+            rule("PROTOCOL_DEGRADED", &["telegram", "webhook"]),
+            rule("OPTIMIZATION_POTENTIAL", &[routing::DIGEST_CHANNEL]),
+        ];
+
+        // Telegram is not configured, so only the webhook is sent to
+        let results = dispatcher.notify_routed(&notification(), &rules).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].channel, "webhook");
+
+        let hint = Notification { alert_type: "OPTIMIZATION_POTENTIAL".to_string(), ..notification() };
+        assert!(dispatcher.notify_routed(&hint, &rules).await.is_empty());
+
+        discord.assert_async().await;
+        webhook.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_telegram_send_message() {
+        let mut server = mockito::Server::new_async().await;
+        let telegram = server
+            .mock("POST", "/bot123:abc/sendMessage")
+            .match_body(mockito::Matcher::Json(json!({ "chat_id": "-100", "text": "digest" })))
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let notifier = TelegramNotifier::with_base_url(&server.url(), "123:abc", "-100", reqwest::Client::new());
+        notifier.send("digest").await.unwrap();
+        telegram.assert_async().await;
+    }
+}
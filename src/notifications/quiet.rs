@@ -0,0 +1,145 @@
+/// Quiet Hours and Maintenance Windows
+///
+/// Alerts raised during a quiet hours window (recurring, e.g. 22:00-07:00
+/// UTC every night) or a maintenance window (one-off, e.g. a planned host
+/// upgrade) are stored and shown on the dashboard as usual but not sent to
+/// the notification channels. Each window can let alerts at or above a
+/// `bypass_severity` through, so a real outage still pages someone.
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Recurring daily window (`[[notifications.quiet_hours]]`), in UTC
+///
+/// A window whose `end` is before its `start` runs past midnight.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Days the window starts on; empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Alerts at or above this severity (0-100) are still sent
+    #[serde(default)]
+    pub bypass_severity: Option<f64>,
+}
+
+impl QuietHours {
+    /// Whether `at` falls inside the window
+    pub fn covers(&self, at: DateTime<Utc>) -> bool {
+        let time = at.time();
+        let starts_on = |day: Weekday| self.days.is_empty() || self.days.contains(&day);
+        if self.start <= self.end {
+            starts_on(at.weekday()) && time >= self.start && time < self.end
+        } else {
+            (starts_on(at.weekday()) && time >= self.start) || (starts_on(at.weekday().pred()) && time < self.end)
+        }
+    }
+}
+
+/// One-off window (`[[notifications.maintenance]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Shown in logs, e.g. "kernel upgrade"
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Alerts at or above this severity (0-100) are still sent
+    #[serde(default)]
+    pub bypass_severity: Option<f64>,
+}
+
+impl MaintenanceWindow {
+    /// Whether `at` falls inside the window
+    pub fn covers(&self, at: DateTime<Utc>) -> bool {
+        at >= self.start && at < self.end
+    }
+}
+
+/// Check the windows' bounds and bypass severities
+pub fn validate(quiet_hours: &[QuietHours], maintenance: &[MaintenanceWindow]) -> Result<(), String> {
+    let bypasses = quiet_hours.iter().map(|w| w.bypass_severity).chain(maintenance.iter().map(|w| w.bypass_severity));
+    if bypasses.flatten().any(|s| !(0.0..=100.0).contains(&s)) {
+        return Err("bypass_severity must be between 0 and 100".to_string());
+    }
+    if let Some(window) = quiet_hours.iter().find(|w| w.start == w.end) {
+        return Err(format!("quiet_hours window starts and ends at {}", window.start));
+    }
+    if let Some(window) = maintenance.iter().find(|w| w.start >= w.end) {
+        return Err(format!("maintenance window ending {} does not start before it ends", window.end));
+    }
+    Ok(())
+}
+
+/// Why an alert of `severity` raised at `at` is not sent, if a window silences it
+pub fn silenced_by(
+    quiet_hours: &[QuietHours],
+    maintenance: &[MaintenanceWindow],
+    at: DateTime<Utc>,
+    severity: f64,
+) -> Option<String> {
+    let silences = |covers: bool, bypass: Option<f64>| covers && bypass.is_none_or(|b| severity < b);
+    if let Some(window) = maintenance.iter().find(|w| silences(w.covers(at), w.bypass_severity)) {
+        return Some(match &window.reason {
+            Some(reason) => format!("maintenance window ({})", reason),
+            None => "maintenance window".to_string(),
+        });
+    }
+    quiet_hours
+        .iter()
+        .any(|w| silences(w.covers(at), w.bypass_severity))
+        .then(|| "quiet hours".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_overnight_quiet_hours() {
+        let window: QuietHours = toml::from_str(
+            r#"
+            start = "22:00"
+            end = "07:00"
+            days = ["Fri"]
+            bypass_severity = 90.0
+            "#,
+        )
+        .unwrap();
+        // Friday 2026-10-16
+        let at = |d, h| Utc.with_ymd_and_hms(2026, 10, d, h, 30, 0).unwrap();
+
+        assert!(window.covers(at(16, 23)));
+        // Early Saturday belongs to Friday night
+        assert!(window.covers(at(17, 3)));
+        assert!(!window.covers(at(17, 23)));
+        assert!(!window.covers(at(16, 12)));
+
+        let quiet = [window];
+        assert_eq!(silenced_by(&quiet, &[], at(16, 23), 50.0).as_deref(), Some("quiet hours"));
+        assert_eq!(silenced_by(&quiet, &[], at(16, 23), 95.0), None);
+    }
+
+    #[test]
+    fn test_maintenance_window() {
+        let start = Utc.with_ymd_and_hms(2026, 10, 20, 2, 0, 0).unwrap();
+        let window = MaintenanceWindow {
+            start,
+            end: start + chrono::Duration::hours(2),
+            reason: Some("kernel upgrade".to_string()),
+            bypass_severity: None,
+        };
+        let maintenance = [window.clone()];
+        let during = start + chrono::Duration::minutes(30);
+        assert_eq!(
+            silenced_by(&[], &maintenance, during, 100.0).as_deref(),
+            Some("maintenance window (kernel upgrade)")
+        );
+        assert_eq!(silenced_by(&[], &maintenance, window.end, 10.0), None);
+
+        assert!(validate(&[], &maintenance).is_ok());
+        let backwards = MaintenanceWindow { end: start, ..window };
+        assert!(validate(&[], &[backwards]).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+/// Alert Routing
+///
+/// Rules in the `notification_rules` table send alerts of a type and
+/// severity range to chosen channels, e.g. disconnections of severity 80+ to
+/// Telegram and the webhook, optimization hints to the digest only. Rules are
+/// tried lowest `priority` first and the first match decides; alerts no rule
+/// matches go to every channel, subject to `[notifications] min_severity`.
+///
+/// The `digest` channel holds an alert back for the next earnings digest, and
+/// a rule without channels mutes the alerts it matches. The notifier reads the
+/// rules for every alert, so edits apply without a restart.
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::queries;
+
+/// Channel that holds alerts for the earnings digest instead of sending them
+pub const DIGEST_CHANNEL: &str = "digest";
+
+/// Channel names a rule can route to
+pub const CHANNELS: [&str; 5] = ["discord", "slack", "telegram", "webhook", DIGEST_CHANNEL];
+
+/// Routes matching alerts to a set of channels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRule {
+    pub id: Option<i64>,
+    /// Alert type matched case-insensitively (e.g. `PROTOCOL_DEGRADED`);
+    /// `None` matches every type
+    pub alert_type: Option<String>,
+    /// Lowest severity matched (0-100, inclusive)
+    pub min_severity: f64,
+    /// Highest severity matched (0-100, inclusive)
+    pub max_severity: f64,
+    /// Channels matching alerts go to
+    pub channels: Vec<String>,
+    /// Rules are tried in ascending priority, then creation order
+    pub priority: i64,
+}
+
+impl NotificationRule {
+    /// Check the severity range and channel names
+    pub fn validate(&self) -> Result<(), String> {
+        let severities = [self.min_severity, self.max_severity];
+        if severities.iter().any(|s| !(0.0..=100.0).contains(s)) {
+            return Err("Severities must be between 0 and 100".to_string());
+        }
+        if self.min_severity > self.max_severity {
+            return Err(format!(
+                "min_severity ({}) is above max_severity ({})",
+                self.min_severity, self.max_severity
+            ));
+        }
+        if self.alert_type.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            return Err("alert_type must not be empty; omit it to match every type".to_string());
+        }
+        for (i, channel) in self.channels.iter().enumerate() {
+            if !CHANNELS.contains(&channel.as_str()) {
+                return Err(format!("Unknown channel '{}', expected one of {}", channel, CHANNELS.join(", ")));
+            }
+            if self.channels[..i].contains(channel) {
+                return Err(format!("Channel '{}' is listed twice", channel));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an alert of `alert_type` at `severity` falls under this rule
+    pub fn matches(&self, alert_type: &str, severity: f64) -> bool {
+        self.alert_type.as_deref().is_none_or(|t| t.eq_ignore_ascii_case(alert_type))
+            && (self.min_severity..=self.max_severity).contains(&severity)
+    }
+
+    /// Whether matching alerts go to `channel`
+    pub fn sends_to(&self, channel: &str) -> bool {
+        self.channels.iter().any(|c| c == channel)
+    }
+}
+
+/// First of `rules` (in evaluation order) matching an alert
+pub fn matching_rule<'a>(
+    rules: &'a [NotificationRule],
+    alert_type: &str,
+    severity: f64,
+) -> Option<&'a NotificationRule> {
+    rules.iter().find(|rule| rule.matches(alert_type, severity))
+}
+
+/// An account's rules in evaluation order; none if they cannot be read
+pub async fn load_rules(pool: &SqlitePool, account_id: i64) -> Vec<NotificationRule> {
+    match queries::list_notification_rules(pool, account_id).await {
+        Ok(records) => records.iter().map(|r| r.rule()).collect(),
+        Err(e) => {
+            log::warn!("⚠️  Failed to load notification rules, sending to every channel: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(alert_type: Option<&str>, min_severity: f64, channels: &[&str]) -> NotificationRule {
+        NotificationRule {
+            id: None,
+            alert_type: alert_type.map(str::to_string),
+            min_severity,
+            max_severity: 100.0,
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            priority: 0,
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = [
+            rule(Some("DEVICE_OFFLINE"), 80.0, &["telegram", "webhook"]),
+            rule(Some("optimization_potential"), 0.0, &[DIGEST_CHANNEL]),
+            rule(None, 50.0, &["slack"]),
+        ];
+
+        let offline = matching_rule(&rules, "DEVICE_OFFLINE", 90.0).unwrap();
+        assert!(offline.sends_to("telegram") && offline.sends_to("webhook"));
+        // Below the first rule's range: falls through to the catch-all
+        assert!(matching_rule(&rules, "DEVICE_OFFLINE", 60.0).unwrap().sends_to("slack"));
+        assert!(matching_rule(&rules, "OPTIMIZATION_POTENTIAL", 10.0).unwrap().sends_to(DIGEST_CHANNEL));
+        assert!(matching_rule(&rules, "DISK_SPACE_LOW", 20.0).is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(rule(None, 0.0, &[]).validate().is_ok());
+        assert!(rule(None, 0.0, &["email"]).validate().unwrap_err().contains("email"));
+        assert!(rule(None, 0.0, &["slack", "slack"]).validate().is_err());
+        assert!(rule(Some(" "), 0.0, &["slack"]).validate().is_err());
+        assert!(NotificationRule { max_severity: 40.0, ..rule(None, 50.0, &[]) }.validate().is_err());
+        assert!(rule(None, 150.0, &[]).validate().is_err());
+    }
+}
@@ -0,0 +1,358 @@
+/// Adapter Response Cache
+///
+/// Several protocol dashboards rate-limit aggressively, so the coordinator can
+/// wrap each adapter in a `CachedAdapter`. Earnings, resource usage and health
+/// responses younger than `ttl_secs` are served without calling the protocol
+/// API again. When a refresh fails, earnings and resource usage up to
+/// `max_stale_secs` old are served instead of failing the poll, with their
+/// `stale_seconds` set to the data's age.
+///
+/// Health is never served past its TTL: a failing health check means the
+/// protocol cannot be reached and should count against its circuit breaker.
+/// Connecting, disconnecting or applying an allocation clears the cache.
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::protocols::http::HttpClientFactory;
+use crate::protocols::{
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus, MetricSchema, ProtocolAdapter,
+    ProtocolResult, ResourceMetrics, ResourceProfile,
+};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Response cache settings (`[cache]` section)
+///
+/// Both settings default to 0, which leaves adapters uncached.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Seconds a response is served without calling the protocol API again
+    pub ttl_secs: u64,
+    /// Oldest response (seconds since it was fetched) served when a refresh fails
+    pub max_stale_secs: u64,
+    /// Overrides by protocol (`[cache.protocols.<name>]`)
+    pub protocols: HashMap<String, CacheOverrides>,
+}
+
+/// Cache settings of one protocol that differ from the defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheOverrides {
+    pub ttl_secs: Option<u64>,
+    pub max_stale_secs: Option<u64>,
+}
+
+/// Cache lifetimes of one protocol's responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    pub ttl: Duration,
+    pub max_stale: Duration,
+}
+
+impl CachePolicy {
+    /// Whether responses are cached at all
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero() || !self.max_stale.is_zero()
+    }
+}
+
+impl CacheConfig {
+    /// Check that stale responses outlive fresh ones
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |name: &str, policy: CachePolicy| {
+            if !policy.max_stale.is_zero() && policy.max_stale < policy.ttl {
+                Err(format!("{}max_stale_secs must be 0 or at least ttl_secs", name))
+            } else {
+                Ok(())
+            }
+        };
+        check("", self.policy(""))?;
+        for protocol in self.protocols.keys() {
+            check(&format!("protocols.{}.", protocol), self.policy(protocol))?;
+        }
+        Ok(())
+    }
+
+    /// Cache lifetimes of `protocol`'s responses
+    pub fn policy(&self, protocol: &str) -> CachePolicy {
+        let overrides = self.protocols.get(protocol).cloned().unwrap_or_default();
+        CachePolicy {
+            ttl: Duration::from_secs(overrides.ttl_secs.unwrap_or(self.ttl_secs)),
+            max_stale: Duration::from_secs(overrides.max_stale_secs.unwrap_or(self.max_stale_secs)),
+        }
+    }
+}
+
+// ============================================================================
+// CACHED ADAPTER
+// ============================================================================
+
+/// Response cached at `fetched_at`
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+impl<T: Clone + Stale> Entry<T> {
+    /// The cached response, marked with its age
+    fn aged(&self) -> T {
+        let mut value = self.value.clone();
+        value.set_stale_seconds(self.fetched_at.elapsed().as_secs().max(1));
+        value
+    }
+}
+
+/// Responses that can report how old they are
+trait Stale {
+    fn set_stale_seconds(&mut self, seconds: u64);
+}
+
+impl Stale for EarningsData {
+    fn set_stale_seconds(&mut self, seconds: u64) {
+        self.stale_seconds = seconds;
+    }
+}
+
+impl Stale for ResourceMetrics {
+    fn set_stale_seconds(&mut self, seconds: u64) {
+        self.stale_seconds = seconds;
+    }
+}
+
+impl Stale for Vec<EarningsData> {
+    fn set_stale_seconds(&mut self, seconds: u64) {
+        self.iter_mut().for_each(|earnings| earnings.stale_seconds = seconds);
+    }
+}
+
+impl Stale for HealthStatus {
+    fn set_stale_seconds(&mut self, _seconds: u64) {}
+}
+
+type Slot<T> = Mutex<Option<Entry<T>>>;
+
+/// Adapter decorator serving recent responses from a cache
+pub struct CachedAdapter {
+    inner: Box<dyn ProtocolAdapter>,
+    policy: CachePolicy,
+    earnings: Slot<EarningsData>,
+    historical: Mutex<HashMap<u32, Entry<Vec<EarningsData>>>>,
+    resources: Slot<ResourceMetrics>,
+    health: Slot<HealthStatus>,
+}
+
+impl CachedAdapter {
+    /// Cache `inner`'s responses according to `policy`
+    pub fn new(inner: Box<dyn ProtocolAdapter>, policy: CachePolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            earnings: Mutex::new(None),
+            historical: Mutex::new(HashMap::new()),
+            resources: Mutex::new(None),
+            health: Mutex::new(None),
+        }
+    }
+
+    /// Drop every cached response
+    fn invalidate(&self) {
+        *self.earnings.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.historical.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        *self.resources.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        *self.health.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Serve `cached` while fresh, else `fetch` it, falling back to `cached`
+    /// while it is not too stale if `serve_stale`
+    ///
+    /// Returns the response and the entry to cache, if it was refreshed.
+    async fn through<T, F>(
+        &self,
+        cached: Option<Entry<T>>,
+        fetch: F,
+        serve_stale: bool,
+    ) -> (ProtocolResult<T>, Option<Entry<T>>)
+    where
+        T: Clone + Stale,
+        F: Future<Output = ProtocolResult<T>>,
+    {
+        if let Some(entry) = cached.as_ref().filter(|e| e.fetched_at.elapsed() < self.policy.ttl) {
+            return (Ok(entry.aged()), None);
+        }
+        match fetch.await {
+            Ok(value) => {
+                let entry = Entry { value: value.clone(), fetched_at: Instant::now() };
+                (Ok(value), Some(entry))
+            }
+            Err(e) => match cached.filter(|c| serve_stale && c.fetched_at.elapsed() <= self.policy.max_stale) {
+                Some(entry) => {
+                    tracing::debug!("Serving cached {} response after error: {}", self.inner.protocol_name(), e);
+                    (Ok(entry.aged()), None)
+                }
+                None => (Err(e), None),
+            },
+        }
+    }
+
+    /// `through` for a single cached response
+    async fn cached<T, F>(&self, slot: &Slot<T>, fetch: F, serve_stale: bool) -> ProtocolResult<T>
+    where
+        T: Clone + Stale,
+        F: Future<Output = ProtocolResult<T>>,
+    {
+        let cached = slot.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let (result, refreshed) = self.through(cached, fetch, serve_stale).await;
+        if refreshed.is_some() {
+            *slot.lock().unwrap_or_else(|e| e.into_inner()) = refreshed;
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for CachedAdapter {
+    fn protocol_name(&self) -> &str {
+        self.inner.protocol_name()
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        self.invalidate();
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        self.invalidate();
+        self.inner.disconnect().await
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.inner.connection_status()
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.cached(&self.earnings, self.inner.get_current_earnings(), true).await
+    }
+
+    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        let cached = self.historical.lock().unwrap_or_else(|e| e.into_inner()).get(&hours).cloned();
+        let (result, refreshed) = self.through(cached, self.inner.get_historical_earnings(hours), true).await;
+        if let Some(entry) = refreshed {
+            self.historical.lock().unwrap_or_else(|e| e.into_inner()).insert(hours, entry);
+        }
+        result
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        self.cached(&self.resources, self.inner.get_resource_usage(), true).await
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        self.invalidate();
+        self.inner.apply_allocation(strategy).await
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        self.inner.get_current_allocation().await
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        self.cached(&self.health, self.inner.health_check(), false).await
+    }
+
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        self.inner.check_credentials().await
+    }
+
+    fn configure_http(&mut self, http: &HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.inner.configure_http(http, protocol)
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        self.inner.get_config()
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        self.inner.resource_profile()
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        self.inner.metric_schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::mock::{EarningsCurve, MockAdapter, MockOperation};
+
+    fn cached(policy: CachePolicy) -> (CachedAdapter, crate::protocols::mock::MockHandle) {
+        let mock = MockAdapter::new("storj", EarningsCurve::Constant(2.0), 50.0);
+        let handle = mock.handle();
+        (CachedAdapter::new(Box::new(mock), policy), handle)
+    }
+
+    #[tokio::test]
+    async fn test_fresh_responses_are_served_from_cache() {
+        let policy = CachePolicy { ttl: Duration::from_secs(60), max_stale: Duration::ZERO };
+        let (adapter, handle) = cached(policy);
+
+        assert_eq!(adapter.get_current_earnings().await.unwrap().stale_seconds, 0);
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.amount_usd, 1.0);
+        assert!(earnings.stale_seconds >= 1);
+        assert_eq!(handle.calls(MockOperation::Earnings), 1);
+
+        // Different windows are cached separately
+        adapter.get_historical_earnings(2).await.unwrap();
+        adapter.get_historical_earnings(3).await.unwrap();
+        adapter.get_historical_earnings(2).await.unwrap();
+        assert_eq!(handle.calls(MockOperation::Earnings), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stale_responses_cover_failed_refreshes() {
+        let policy = CachePolicy { ttl: Duration::ZERO, max_stale: Duration::from_secs(60) };
+        let (mut adapter, handle) = cached(policy);
+        adapter.get_resource_usage().await.unwrap();
+        adapter.health_check().await.unwrap();
+
+        handle.fail_always(MockOperation::Resources);
+        handle.fail_always(MockOperation::Health);
+        assert!(adapter.get_resource_usage().await.unwrap().stale_seconds >= 1);
+        assert!(adapter.health_check().await.is_err());
+        assert_eq!(handle.calls(MockOperation::Resources), 2);
+
+        // Applying an allocation invalidates what was cached
+        let allocation = adapter.get_current_allocation().await.unwrap();
+        adapter.apply_allocation(allocation).await.unwrap();
+        assert!(adapter.get_resource_usage().await.is_err());
+    }
+
+    #[test]
+    fn test_policy_overrides_and_validation() {
+        let config: CacheConfig = toml::from_str(
+            r#"
+            ttl_secs = 30
+            max_stale_secs = 300
+            [protocols.golem]
+            max_stale_secs = 0
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.policy("storj").max_stale, Duration::from_secs(300));
+        assert_eq!(config.policy("golem").max_stale, Duration::ZERO);
+        assert!(config.policy("golem").is_enabled());
+        assert!(!CacheConfig::default().policy("storj").is_enabled());
+
+        let config = CacheConfig { ttl_secs: 60, max_stale_secs: 30, ..CacheConfig::default() };
+        assert!(config.validate().unwrap_err().contains("max_stale_secs"));
+    }
+}
@@ -2,12 +2,27 @@
 ///
 /// Monitors and aggregates data from all protocol adapters.
 /// Provides unified view of earnings, resources, and connection status.
-
-use super::{AggregatedMetrics, OrchestrationError, OrchestrationResult, ResourceUtilization};
-use crate::protocols::{ProtocolAdapter, EarningsData, ResourceMetrics};
+///
+/// Adapters are registered per protocol instance (e.g. `storj` and
+/// `storj_backup`, both of type `storj`), and every per-protocol map is keyed
+/// by instance id, so several nodes of one protocol never share an entry.
+use super::cache::{CacheConfig, CachedAdapter};
+use super::resilience::{retry_with_backoff, with_timeout, BreakerState, CircuitBreaker, ResilienceConfig};
+use super::window::{MetricsWindow, WindowSnapshot};
+use super::{
+    AggregatedMetrics, OrchestrationError, OrchestrationResult, ProtocolUptime, ResourceEfficiency,
+    ResourceUtilization,
+};
+use crate::ip_intel::IpReport;
+use crate::network_probe::NetworkProbe;
+use crate::protocols::{
+    CredentialStatus, HealthStatus, MetricSchema, ProtocolAdapter, ProtocolError, ProtocolResult, ResourceMetrics,
+    ResourceProfile,
+};
 use chrono::{DateTime, Utc};
+use futures::future::join_all;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
 // ============================================================================
@@ -18,14 +33,47 @@ use tokio::sync::RwLock;
 ///
 /// Manages connections to all protocol adapters and aggregates their data.
 pub struct ProtocolCoordinator {
-    /// Map of protocol name to adapter
+    /// Map of protocol instance id to adapter
     adapters: HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>>,
-    /// Historical metrics
-    metrics_history: Arc<RwLock<Vec<AggregatedMetrics>>>,
+    /// Protocol type of each instance
+    protocol_types: HashMap<String, String>,
+    /// Historical metrics, bounded by the maximum history size
+    metrics_history: MetricsWindow,
     /// Last update timestamp
     last_update: Arc<RwLock<Option<DateTime<Utc>>>>,
-    /// Maximum history size
-    max_history_size: usize,
+    /// Retry and circuit breaker settings for adapter calls
+    resilience: ResilienceConfig,
+    /// Response cache settings, applied to adapters as they are registered
+    cache: CacheConfig,
+    /// Circuit breaker per protocol
+    breakers: HashMap<String, Mutex<CircuitBreaker>>,
+    /// CPU cores on this host, for converting CPU percent into core-hours
+    host_cpu_cores: f64,
+    /// Account that owns these protocols and everything recorded for them
+    account_id: i64,
+    /// Latest rolling uptime per protocol, refreshed by the polling task
+    uptime: Mutex<HashMap<String, ProtocolUptime>>,
+    /// Age of the cached data served for each protocol in the latest poll
+    staleness: Mutex<HashMap<String, u64>>,
+    /// Latest public IP check per IP-sensitive protocol
+    public_ips: Mutex<HashMap<String, IpReport>>,
+    /// Latest network quality probe of the node
+    network_probe: Mutex<Option<NetworkProbe>>,
+    /// Protocol-specific metrics each adapter declares
+    metric_schemas: HashMap<String, Vec<MetricSchema>>,
+}
+
+/// Outcome of polling one adapter
+struct ProtocolPoll {
+    profile: ResourceProfile,
+    earnings: Option<f64>,
+    /// Protocol-specific metrics reported with the earnings
+    details: HashMap<String, f64>,
+    allocation: Option<f64>,
+    resources: Option<ResourceMetrics>,
+    connected: bool,
+    /// Age of the oldest cached response served, 0 if all were fresh
+    stale_seconds: u64,
 }
 
 impl ProtocolCoordinator {
@@ -33,20 +81,109 @@ impl ProtocolCoordinator {
     pub fn new(max_history_size: usize) -> Self {
         Self {
             adapters: HashMap::new(),
-            metrics_history: Arc::new(RwLock::new(Vec::new())),
+            protocol_types: HashMap::new(),
+            metrics_history: MetricsWindow::new(max_history_size),
             last_update: Arc::new(RwLock::new(None)),
-            max_history_size,
+            resilience: ResilienceConfig::default(),
+            cache: CacheConfig::default(),
+            breakers: HashMap::new(),
+            host_cpu_cores: std::thread::available_parallelism().map_or(1, |n| n.get()) as f64,
+            account_id: crate::db::DEFAULT_ACCOUNT_ID,
+            uptime: Mutex::new(HashMap::new()),
+            staleness: Mutex::new(HashMap::new()),
+            public_ips: Mutex::new(HashMap::new()),
+            network_probe: Mutex::new(None),
+            metric_schemas: HashMap::new(),
         }
     }
 
-    /// Register a protocol adapter
+    /// Run on behalf of `account_id`
+    pub fn with_account(mut self, account_id: i64) -> Self {
+        self.account_id = account_id;
+        self
+    }
+
+    /// Account that owns this coordinator
+    pub fn account_id(&self) -> i64 {
+        self.account_id
+    }
+
+    /// Use `config` for adapter call retries and circuit breakers
+    pub fn with_resilience(mut self, config: ResilienceConfig) -> Self {
+        for breaker in self.breakers.values_mut() {
+            *breaker = Mutex::new(CircuitBreaker::new(&config));
+        }
+        self.resilience = config;
+        self
+    }
+
+    /// Cache adapter responses according to `config`
+    ///
+    /// Only adapters registered afterwards are cached.
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        self.cache = config;
+        self
+    }
+
+    /// Override the host CPU core count used for efficiency metrics
+    pub fn with_host_cpu_cores(mut self, cores: f64) -> Self {
+        self.host_cpu_cores = cores.max(1.0);
+        self
+    }
+
+    /// Register a protocol adapter as an instance of the protocol type of the same name
     pub fn register_adapter(
         &mut self,
         protocol_name: String,
         adapter: Box<dyn ProtocolAdapter>,
     ) {
+        let protocol_type = protocol_name.clone();
+        self.register_instance(protocol_name, protocol_type, adapter);
+    }
+
+    /// Register the adapter of protocol instance `instance_id`, behind a
+    /// response cache if one is configured for it
+    pub fn register_instance(
+        &mut self,
+        instance_id: String,
+        protocol_type: String,
+        adapter: Box<dyn ProtocolAdapter>,
+    ) {
+        self.protocol_types.insert(instance_id.clone(), protocol_type);
+        self.breakers.insert(
+            instance_id.clone(),
+            Mutex::new(CircuitBreaker::new(&self.resilience)),
+        );
+        self.metric_schemas
+            .insert(instance_id.clone(), adapter.metric_schema());
+        let policy = self.cache.policy(&instance_id);
+        let adapter: Box<dyn ProtocolAdapter> = if policy.is_enabled() {
+            Box::new(CachedAdapter::new(adapter, policy))
+        } else {
+            adapter
+        };
         self.adapters
-            .insert(protocol_name, Arc::new(RwLock::new(adapter)));
+            .insert(instance_id, Arc::new(RwLock::new(adapter)));
+    }
+
+    /// Protocol type of a registered instance
+    pub fn protocol_type(&self, instance_id: &str) -> Option<&str> {
+        self.protocol_types.get(instance_id).map(String::as_str)
+    }
+
+    /// Protocol type of every registered instance, keyed by instance id
+    pub fn protocol_types(&self) -> &HashMap<String, String> {
+        &self.protocol_types
+    }
+
+    /// Registered adapters, keyed by protocol instance id
+    pub fn adapters(&self) -> &HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>> {
+        &self.adapters
+    }
+
+    /// Protocol-specific metrics each registered adapter declares, keyed by protocol name
+    pub fn metric_schemas(&self) -> &HashMap<String, Vec<MetricSchema>> {
+        &self.metric_schemas
     }
 
     /// Get list of registered protocols
@@ -54,80 +191,139 @@ impl ProtocolCoordinator {
         self.adapters.keys().cloned().collect()
     }
 
+    /// Circuit breaker state by protocol
+    pub fn breaker_states(&self) -> HashMap<String, BreakerState> {
+        self.breakers
+            .iter()
+            .map(|(name, breaker)| {
+                let state = breaker.lock().unwrap_or_else(|e| e.into_inner()).state();
+                (name.clone(), state)
+            })
+            .collect()
+    }
+
+    /// Protocols whose circuit breaker is not closed
+    pub fn degraded_protocols(&self) -> Vec<String> {
+        let mut degraded: Vec<String> = self
+            .breaker_states()
+            .into_iter()
+            .filter(|(_, state)| *state != BreakerState::Closed)
+            .map(|(name, _)| name)
+            .collect();
+        degraded.sort();
+        degraded
+    }
+
+    /// Latest rolling uptime by protocol
+    pub fn uptime(&self) -> HashMap<String, ProtocolUptime> {
+        self.uptime.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the rolling uptime of every protocol
+    pub fn set_uptime(&self, uptime: HashMap<String, ProtocolUptime>) {
+        *self.uptime.lock().unwrap_or_else(|e| e.into_inner()) = uptime;
+    }
+
+    /// Latest public IP check by protocol
+    pub fn public_ips(&self) -> HashMap<String, IpReport> {
+        self.public_ips.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Record the result of checking `protocol`'s public IP
+    pub fn set_public_ip(&self, protocol: &str, report: IpReport) {
+        self.public_ips.lock().unwrap_or_else(|e| e.into_inner()).insert(protocol.to_string(), report);
+    }
+
+    /// Latest network quality probe
+    pub fn network_probe(&self) -> Option<NetworkProbe> {
+        self.network_probe.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Record a network quality probe; later polls measure bandwidth use
+    /// against its upstream
+    pub fn set_network_probe(&self, probe: NetworkProbe) {
+        *self.network_probe.lock().unwrap_or_else(|e| e.into_inner()) = Some(probe);
+    }
+
+    /// Protocols served from the response cache in the latest poll, with the
+    /// age of their data in seconds
+    pub fn stale_protocols(&self) -> HashMap<String, u64> {
+        self.staleness.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
     /// Poll all adapters and aggregate metrics
-    pub async fn poll_all(&self) -> OrchestrationResult<AggregatedMetrics> {
+    ///
+    /// The result is shared with the metrics history; pass it on to the
+    /// monitor and optimizer rather than copying it.
+    pub async fn poll_all(&self) -> OrchestrationResult<Arc<AggregatedMetrics>> {
         let timestamp = Utc::now();
         let mut earnings_by_protocol = HashMap::new();
         let mut allocation_by_protocol = HashMap::new();
         let mut connection_status = HashMap::new();
+        let mut resource_profiles = HashMap::new();
+        let mut efficiency_by_protocol = HashMap::new();
+        let mut protocol_details = HashMap::new();
+        let mut staleness = HashMap::new();
 
         let mut total_cpu = 0.0;
         let mut total_memory = 0.0;
         let mut total_bandwidth = 0.0;
         let mut total_storage = 0.0;
         let mut count = 0u32;
-
-        // Poll each adapter
-        for (protocol_name, adapter_lock) in &self.adapters {
-            let adapter = adapter_lock.read().await;
-
-            // Get current earnings
-            match adapter.get_current_earnings().await {
-                Ok(earnings) => {
-                    earnings_by_protocol.insert(protocol_name.clone(), earnings.amount_usd);
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get earnings from {}: {}", protocol_name, e);
-                }
+        // Every protocol's traffic shares the uplink, low-resource ones included
+        let mut upstream_used = 0.0;
+
+        // Poll adapters concurrently so one slow protocol doesn't hold up the rest
+        let polls = join_all(
+            self.adapters
+                .iter()
+                .map(|(name, adapter)| async move { (name, self.poll_protocol(name, adapter).await) }),
+        )
+        .await;
+
+        for (protocol_name, poll) in polls {
+            let low_resource = poll.profile.low_resource;
+            // Instances of one protocol type share its profile
+            let protocol_type = self.protocol_type(protocol_name).unwrap_or(protocol_name);
+            resource_profiles.insert(protocol_type.to_string(), poll.profile);
+            connection_status.insert(protocol_name.clone(), poll.connected);
+            if poll.stale_seconds > 0 {
+                staleness.insert(protocol_name.clone(), poll.stale_seconds);
             }
 
-            // Get current allocation
-            match adapter.get_current_allocation().await {
-                Ok(allocation) => {
-                    allocation_by_protocol.insert(
-                        protocol_name.clone(),
-                        allocation.allocation_percent,
-                    );
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get allocation from {}: {}", protocol_name, e);
-                }
+            if let Some(earnings) = poll.earnings {
+                earnings_by_protocol.insert(protocol_name.clone(), earnings);
             }
-
-            // Get resource usage
-            match adapter.get_resource_usage().await {
-                Ok(resources) => {
-                    total_cpu += resources.cpu_percent;
-                    total_memory += resources.memory_mb;
-                    total_bandwidth += resources.bandwidth_mbps;
-                    total_storage += resources.storage_gb;
-                    count += 1;
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get resources from {}: {}", protocol_name, e);
-                }
+            if !poll.details.is_empty() {
+                protocol_details.insert(protocol_name.clone(), poll.details);
+            }
+            if let Some(allocation) = poll.allocation {
+                allocation_by_protocol.insert(protocol_name.clone(), allocation);
+            }
+            if let (Some(earnings), Some(resources)) = (poll.earnings, &poll.resources) {
+                efficiency_by_protocol.insert(
+                    protocol_name.clone(),
+                    ResourceEfficiency::from_usage(earnings, resources, self.host_cpu_cores),
+                );
             }
 
-            // Get health status
-            match adapter.health_check().await {
-                Ok(health) => {
-                    connection_status.insert(
-                        protocol_name.clone(),
-                        health.is_healthy && health.connection_status
-                            == crate::protocols::ConnectionStatus::Connected,
-                    );
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get health from {}: {}", protocol_name, e);
-                    connection_status.insert(protocol_name.clone(), false);
-                }
+            upstream_used += poll.resources.as_ref().map_or(0.0, |r| r.bandwidth_mbps);
+            // Low-resource protocols would only dilute the averages
+            if let Some(resources) = poll.resources.filter(|_| !low_resource) {
+                total_cpu += resources.cpu_percent;
+                total_memory += resources.memory_mb;
+                total_bandwidth += resources.bandwidth_mbps;
+                total_storage += resources.storage_gb;
+                count += 1;
             }
         }
 
         let total_earnings_per_hour: f64 = earnings_by_protocol.values().sum();
+        *self.staleness.lock().unwrap_or_else(|e| e.into_inner()) = staleness;
 
         // Calculate average resource utilization
-        let resource_utilization = if count > 0 {
+        let upstream_mbps = self.network_probe().map(|p| p.upload_mbps).filter(|mbps| *mbps > 0.0);
+        let mut resource_utilization = if count > 0 {
             ResourceUtilization {
                 cpu_percent: (total_cpu / count as f64).min(100.0),
                 memory_percent: (total_memory / count as f64).min(100.0),
@@ -142,22 +338,26 @@ impl ProtocolCoordinator {
                 storage_percent: 0.0,
             }
         };
+        if let Some(upstream) = upstream_mbps {
+            resource_utilization.bandwidth_percent = (upstream_used / upstream * 100.0).min(100.0);
+        }
 
-        let metrics = AggregatedMetrics {
+        let metrics = Arc::new(AggregatedMetrics {
             timestamp,
             total_earnings_per_hour,
             earnings_by_protocol,
             allocation_by_protocol,
             resource_utilization,
             connection_status,
-        };
+            resource_profiles,
+            protocol_types: self.protocol_types.clone(),
+            efficiency_by_protocol,
+            protocol_details,
+            upstream_mbps,
+        });
 
-        // Update history
-        let mut history = self.metrics_history.write().await;
-        history.push(metrics.clone());
-        if history.len() > self.max_history_size {
-            history.remove(0);
-        }
+        // Update history; the stored snapshot is the one returned
+        self.metrics_history.push(metrics.clone());
 
         *self.last_update.write().await = Some(timestamp);
 
@@ -166,40 +366,162 @@ impl ProtocolCoordinator {
         Ok(metrics)
     }
 
+    /// Poll one adapter through its circuit breaker
+    ///
+    /// Each call is retried with backoff. Failing earnings or health calls
+    /// count against the breaker; while it is open the adapter is skipped
+    /// and reported disconnected. Stale earnings served by the response cache
+    /// count as a success.
+    async fn poll_protocol(
+        &self,
+        protocol_name: &str,
+        adapter_lock: &RwLock<Box<dyn ProtocolAdapter>>,
+    ) -> ProtocolPoll {
+        let adapter = adapter_lock.read().await;
+        let adapter: &dyn ProtocolAdapter = &**adapter;
+        let mut poll = ProtocolPoll {
+            profile: adapter.resource_profile(),
+            earnings: None,
+            details: HashMap::new(),
+            allocation: None,
+            resources: None,
+            connected: false,
+            stale_seconds: 0,
+        };
+
+        let breaker = &self.breakers[protocol_name];
+        if !breaker.lock().unwrap_or_else(|e| e.into_inner()).allow(std::time::Instant::now()) {
+            tracing::debug!("Skipping {}: circuit breaker open", protocol_name);
+            return poll;
+        }
+
+        let config = &self.resilience;
+        let timeout = config.timeouts(protocol_name).call;
+        let mut failed = false;
+
+        match retry_with_backoff(config, timeout, || adapter.get_current_earnings()).await {
+            Ok(earnings) => {
+                poll.stale_seconds = earnings.stale_seconds;
+                poll.earnings = Some(earnings.amount_usd);
+                poll.details = earnings.metrics;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to get earnings from {}: {}", protocol_name, e);
+                failed = true;
+            }
+        }
+
+        match retry_with_backoff(config, timeout, || adapter.get_current_allocation()).await {
+            Ok(allocation) => poll.allocation = Some(allocation.allocation_percent),
+            Err(e) => tracing::warn!("Failed to get allocation from {}: {}", protocol_name, e),
+        }
+
+        match retry_with_backoff(config, timeout, || adapter.get_resource_usage()).await {
+            Ok(resources) => {
+                poll.stale_seconds = poll.stale_seconds.max(resources.stale_seconds);
+                poll.resources = Some(resources);
+            }
+            Err(e) => tracing::warn!("Failed to get resources from {}: {}", protocol_name, e),
+        }
+
+        match retry_with_backoff(config, timeout, || adapter.health_check()).await {
+            Ok(health) => {
+                poll.connected = health.is_healthy
+                    && health.connection_status == crate::protocols::ConnectionStatus::Connected;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to get health from {}: {}", protocol_name, e);
+                failed = true;
+            }
+        }
+
+        let mut breaker = breaker.lock().unwrap_or_else(|e| e.into_inner());
+        if failed {
+            if breaker.record_failure(std::time::Instant::now()) {
+                tracing::warn!(
+                    "Circuit breaker opened for {}, skipping it for {}s",
+                    protocol_name,
+                    config.open_duration_secs
+                );
+            }
+        } else if breaker.record_success() {
+            tracing::info!("Circuit breaker closed for {}, protocol recovered", protocol_name);
+        }
+        if breaker.state() != BreakerState::Closed {
+            poll.connected = false;
+        }
+
+        poll
+    }
+
+    /// Adapter registered as `protocol_name`
+    fn adapter(
+        &self,
+        protocol_name: &str,
+    ) -> OrchestrationResult<&Arc<RwLock<Box<dyn ProtocolAdapter>>>> {
+        self.adapters.get(protocol_name).ok_or_else(|| {
+            OrchestrationError::CoordinationError(format!("Protocol {} not registered", protocol_name))
+        })
+    }
+
+    /// Connect `protocol_name` and report its resulting health
+    ///
+    /// A successful connect also closes the protocol's circuit breaker, so a
+    /// manually cycled adapter is polled again straight away; a connect that
+    /// times out counts as a failed poll, degrading the protocol.
+    pub async fn connect_protocol(&self, protocol_name: &str) -> OrchestrationResult<HealthStatus> {
+        let mut adapter = self.adapter(protocol_name)?.write().await;
+        let timeouts = self.resilience.timeouts(protocol_name);
+        let connected = with_timeout(timeouts.connect, "connect", adapter.connect()).await;
+        if let Some(breaker) = self.breakers.get(protocol_name) {
+            let mut breaker = breaker.lock().unwrap_or_else(|e| e.into_inner());
+            match &connected {
+                Ok(()) => {
+                    breaker.record_success();
+                }
+                Err(ProtocolError::TimeoutError(_)) => {
+                    breaker.record_failure(std::time::Instant::now());
+                }
+                Err(_) => {}
+            }
+        }
+        connected?;
+        tracing::info!("Connected protocol {}", protocol_name);
+        Ok(with_timeout(timeouts.call, "health_check", adapter.health_check()).await?)
+    }
+
+    /// Disconnect `protocol_name` and report its resulting health
+    pub async fn disconnect_protocol(&self, protocol_name: &str) -> OrchestrationResult<HealthStatus> {
+        let mut adapter = self.adapter(protocol_name)?.write().await;
+        let timeouts = self.resilience.timeouts(protocol_name);
+        with_timeout(timeouts.connect, "disconnect", adapter.disconnect()).await?;
+        tracing::info!("Disconnected protocol {}", protocol_name);
+        Ok(with_timeout(timeouts.call, "health_check", adapter.health_check()).await?)
+    }
+
     /// Get protocol status
     pub async fn get_protocol_status(
         &self,
         protocol_name: &str,
     ) -> OrchestrationResult<ProtocolStatus> {
-        let adapter_lock = self
-            .adapters
-            .get(protocol_name)
-            .ok_or_else(|| OrchestrationError::CoordinationError(format!(
-                "Protocol {} not registered",
-                protocol_name
-            )))?;
+        let adapter = self.adapter(protocol_name)?.read().await;
+        let timeout = self.resilience.timeouts(protocol_name).call;
 
-        let adapter = adapter_lock.read().await;
-
-        let earnings = adapter
-            .get_current_earnings()
+        let earnings = with_timeout(timeout, "get_current_earnings", adapter.get_current_earnings())
             .await
             .map(|e| e.amount_usd)
             .unwrap_or(0.0);
 
-        let allocation = adapter
-            .get_current_allocation()
+        let allocation = with_timeout(timeout, "get_current_allocation", adapter.get_current_allocation())
             .await
             .map(|a| a.allocation_percent)
             .unwrap_or(0.0);
 
-        let resources = adapter
-            .get_resource_usage()
+        let resources = with_timeout(timeout, "get_resource_usage", adapter.get_resource_usage())
             .await
             .ok();
 
-        let health = adapter
-            .health_check()
+        let health = with_timeout(timeout, "health_check", adapter.health_check())
             .await
             .ok();
 
@@ -209,27 +531,37 @@ impl ProtocolCoordinator {
             allocation_percent: allocation,
             resources,
             health_status: health,
+            uptime: self.uptime.lock().unwrap_or_else(|e| e.into_inner()).get(protocol_name).copied(),
+            public_ip: self.public_ips.lock().unwrap_or_else(|e| e.into_inner()).get(protocol_name).cloned(),
         })
     }
 
+    /// Check every adapter's credentials, keyed by protocol
+    ///
+    /// Checks bypass the circuit breakers so rejected credentials are still
+    /// reported for protocols that polling is skipping.
+    pub async fn check_credentials(&self) -> HashMap<String, ProtocolResult<CredentialStatus>> {
+        let checks = self.adapters.iter().map(|(name, adapter)| async move {
+            let timeout = self.resilience.timeouts(name).call;
+            let adapter = adapter.read().await;
+            (name.clone(), with_timeout(timeout, "check_credentials", adapter.check_credentials()).await)
+        });
+        join_all(checks).await.into_iter().collect()
+    }
+
     /// Get metrics history
-    pub async fn get_metrics_history(&self) -> Vec<AggregatedMetrics> {
-        self.metrics_history.read().await.clone()
+    pub async fn get_metrics_history(&self) -> WindowSnapshot {
+        self.metrics_history.snapshot()
     }
 
     /// Calculate total earnings across all protocols
     pub async fn calculate_total_earnings(&self) -> OrchestrationResult<f64> {
-        let history = self.metrics_history.read().await;
-        if history.is_empty() {
-            return Ok(0.0);
-        }
-        Ok(history.iter().map(|m| m.total_earnings_per_hour).sum())
+        Ok(self.metrics_history.snapshot().iter().map(|m| m.total_earnings_per_hour).sum())
     }
 
     /// Get current aggregated metrics
-    pub async fn get_current_metrics(&self) -> OrchestrationResult<Option<AggregatedMetrics>> {
-        let history = self.metrics_history.read().await;
-        Ok(history.last().cloned())
+    pub async fn get_current_metrics(&self) -> OrchestrationResult<Option<Arc<AggregatedMetrics>>> {
+        Ok(self.metrics_history.latest())
     }
 
     /// Get metrics for time period
@@ -237,13 +569,8 @@ impl ProtocolCoordinator {
         &self,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
-    ) -> OrchestrationResult<Vec<AggregatedMetrics>> {
-        let history = self.metrics_history.read().await;
-        Ok(history
-            .iter()
-            .filter(|m| m.timestamp >= start && m.timestamp <= end)
-            .cloned()
-            .collect())
+    ) -> OrchestrationResult<Vec<Arc<AggregatedMetrics>>> {
+        Ok(self.metrics_history.snapshot().shared_between(start, end))
     }
 
     /// Get last update timestamp
@@ -253,7 +580,7 @@ impl ProtocolCoordinator {
 
     /// Clear history
     pub async fn clear_history(&self) {
-        self.metrics_history.write().await.clear();
+        self.metrics_history.clear();
     }
 }
 
@@ -270,6 +597,10 @@ pub struct ProtocolStatus {
     pub resources: Option<ResourceMetrics>,
     /// Health status
     pub health_status: Option<crate::protocols::HealthStatus>,
+    /// Rolling uptime, once the protocol has been polled
+    pub uptime: Option<ProtocolUptime>,
+    /// Latest public IP check, for IP-sensitive protocols
+    pub public_ip: Option<IpReport>,
 }
 
 #[cfg(test)]
@@ -285,7 +616,7 @@ mod tests {
     #[test]
     fn test_coordinator_max_history() {
         let coordinator = ProtocolCoordinator::new(5);
-        assert_eq!(coordinator.max_history_size, 5);
+        assert_eq!(coordinator.metrics_history.capacity(), 5);
     }
 
     #[tokio::test]
@@ -297,12 +628,105 @@ mod tests {
 
     #[tokio::test]
     async fn test_registered_protocols() {
-        let mut coordinator = ProtocolCoordinator::new(1000);
+        let coordinator = ProtocolCoordinator::new(1000);
         // Note: In real tests, we would create mock adapters
         let protocols = coordinator.registered_protocols();
         assert_eq!(protocols.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_lifecycle_of_unregistered_protocol() {
+        let coordinator = ProtocolCoordinator::new(1000);
+        assert!(matches!(
+            coordinator.connect_protocol("storj").await,
+            Err(OrchestrationError::CoordinationError(_))
+        ));
+        assert!(matches!(
+            coordinator.disconnect_protocol("storj").await,
+            Err(OrchestrationError::CoordinationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_no_degraded_protocols_without_adapters() {
+        let coordinator = ProtocolCoordinator::new(1000).with_resilience(ResilienceConfig::default());
+        assert!(coordinator.degraded_protocols().is_empty());
+        assert!(coordinator.breaker_states().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cached_earnings_keep_failing_protocol_polled() {
+        use crate::protocols::mock::{EarningsCurve, MockAdapter, MockOperation};
+
+        let resilience = ResilienceConfig { max_retries: 0, failure_threshold: 1, ..ResilienceConfig::default() };
+        let cache = CacheConfig { max_stale_secs: 60, ..CacheConfig::default() };
+        let mut coordinator = ProtocolCoordinator::new(10).with_resilience(resilience).with_cache(cache);
+        let mut mock = MockAdapter::new("storj", EarningsCurve::Constant(4.0), 50.0);
+        mock.connect().await.unwrap();
+        let handle = mock.handle();
+        coordinator.register_adapter("storj".to_string(), Box::new(mock));
+
+        coordinator.poll_all().await.unwrap();
+        assert!(coordinator.stale_protocols().is_empty());
+
+        handle.fail_always(MockOperation::Earnings);
+        let metrics = coordinator.poll_all().await.unwrap();
+        assert_eq!(metrics.earnings_by_protocol["storj"], 2.0);
+        assert!(coordinator.stale_protocols()["storj"] >= 1);
+        assert!(coordinator.degraded_protocols().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bandwidth_measured_against_probed_upstream() {
+        use crate::protocols::mock::{EarningsCurve, MockAdapter};
+
+        let mut coordinator = ProtocolCoordinator::new(10);
+        // Uses half of the mock's 200 Mbps
+        let mut mock = MockAdapter::new("grass", EarningsCurve::Constant(1.0), 50.0);
+        mock.connect().await.unwrap();
+        coordinator.register_adapter("grass".to_string(), Box::new(mock));
+
+        let metrics = coordinator.poll_all().await.unwrap();
+        assert_eq!(metrics.upstream_mbps, None);
+
+        coordinator.set_network_probe(NetworkProbe {
+            latency_ms: 12.0,
+            download_mbps: 900.0,
+            upload_mbps: 400.0,
+            measured_at: Utc::now(),
+        });
+        let metrics = coordinator.poll_all().await.unwrap();
+        assert_eq!(metrics.upstream_mbps, Some(400.0));
+        assert_eq!(metrics.resource_utilization.bandwidth_percent, 25.0);
+    }
+
+    #[tokio::test]
+    async fn test_instances_of_one_protocol_poll_separately() {
+        use crate::protocols::mock::{EarningsCurve, MockAdapter};
+
+        let mut coordinator = ProtocolCoordinator::new(10);
+        for (instance, rate) in [("storj", 4.0), ("storj_backup", 1.0)] {
+            let mut mock = MockAdapter::new("storj", EarningsCurve::Constant(rate), 50.0);
+            mock.connect().await.unwrap();
+            coordinator.register_instance(instance.to_string(), "storj".to_string(), Box::new(mock));
+        }
+        let mut grass = MockAdapter::new("grass", EarningsCurve::Constant(1.0), 50.0);
+        grass.connect().await.unwrap();
+        coordinator.register_adapter("grass".to_string(), Box::new(grass));
+
+        assert_eq!(coordinator.protocol_type("storj_backup"), Some("storj"));
+        assert_eq!(coordinator.protocol_type("grass"), Some("grass"));
+        assert_eq!(coordinator.protocol_type("storj_2"), None);
+
+        let metrics = coordinator.poll_all().await.unwrap();
+        assert_eq!(metrics.earnings_by_protocol["storj"], 2.0);
+        assert_eq!(metrics.earnings_by_protocol["storj_backup"], 0.5);
+        assert_eq!(metrics.allocation_by_protocol.len(), 3);
+        assert_eq!(metrics.protocol_type("storj_backup"), "storj");
+        assert!(metrics.resource_profile("storj_backup").is_some());
+        assert!(!metrics.resource_profiles.contains_key("storj_backup"));
+    }
+
     #[tokio::test]
     async fn test_metrics_history_limit() {
         let coordinator = ProtocolCoordinator::new(3);
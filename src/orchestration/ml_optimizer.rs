@@ -0,0 +1,421 @@
+/// ML-Assisted Optimizer
+///
+/// Sends the metrics history to the external ML engine (`[ml_engine] api_url`),
+/// receives allocation recommendations from its reinforcement-learning policy,
+/// and blends them with the heuristic optimizer's plan. Falls back to the
+/// heuristic plan whenever the ML service is unreachable, slow, or unsure.
+use super::{AggregatedMetrics, AllocationPlan, OrchestrationError, OrchestrationResult};
+use crate::protocols::ResourceKind;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// ML optimizer configuration (`[ml_engine]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MlOptimizerConfig {
+    /// Use ML recommendations at all
+    pub enabled: bool,
+    /// ML engine base URL
+    pub api_url: String,
+    /// Request timeout in milliseconds
+    pub request_timeout_ms: u64,
+    /// Minimum ML confidence to use a recommendation (0.0-1.0)
+    pub confidence_threshold: f64,
+    /// Weight of the ML allocation when blending (0.0-1.0)
+    pub blend_weight: f64,
+    /// Most recent history entries sent to the ML engine
+    pub history_limit: usize,
+}
+
+impl Default for MlOptimizerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_url: "http://localhost:6702".to_string(),
+            request_timeout_ms: 5000,
+            confidence_threshold: 0.75,
+            blend_weight: 0.5,
+            history_limit: 288,
+        }
+    }
+}
+
+// ============================================================================
+// ML ENGINE CONTRACT
+// ============================================================================
+
+/// Request body for `POST {api_url}/optimize/allocation`
+#[derive(Debug, Serialize)]
+struct AllocationRequest<'a> {
+    current: &'a AggregatedMetrics,
+    history: &'a [&'a AggregatedMetrics],
+}
+
+/// ML engine allocation recommendation
+#[derive(Debug, Clone, Deserialize)]
+pub struct MlRecommendation {
+    /// Recommended allocation percent by protocol
+    pub allocation: HashMap<String, f64>,
+    /// Model confidence (0-1)
+    pub confidence: f64,
+    /// Model that produced the recommendation
+    #[serde(default)]
+    pub model_version: Option<String>,
+}
+
+/// Where a plan came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanSource {
+    /// Heuristic optimizer only
+    Heuristic,
+    /// Heuristic blended with an ML recommendation
+    MlBlended,
+}
+
+/// Allocation plan with its provenance
+#[derive(Debug, Clone)]
+pub struct BlendedPlan {
+    pub plan: AllocationPlan,
+    pub source: PlanSource,
+}
+
+// ============================================================================
+// ML OPTIMIZER
+// ============================================================================
+
+/// Blends ML engine recommendations with heuristic allocation plans
+pub struct MlOptimizer {
+    config: MlOptimizerConfig,
+    client: reqwest::Client,
+}
+
+impl MlOptimizer {
+    /// Create ML optimizer
+    pub fn new(config: MlOptimizerConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()
+            .unwrap_or_default();
+
+        Self { config, client }
+    }
+
+    /// ML optimizer configuration
+    pub fn config(&self) -> &MlOptimizerConfig {
+        &self.config
+    }
+
+    /// Blend the heuristic plan with the ML recommendation
+    ///
+    /// Returns the heuristic plan unchanged when ML is disabled, unreachable,
+    /// or below the confidence threshold.
+    pub async fn refine(
+        &self,
+        heuristic: AllocationPlan,
+        current: &AggregatedMetrics,
+        history: &[&AggregatedMetrics],
+    ) -> BlendedPlan {
+        if !self.config.enabled {
+            return BlendedPlan {
+                plan: heuristic,
+                source: PlanSource::Heuristic,
+            };
+        }
+
+        let recommendation = match self.recommend(current, history).await {
+            Ok(r) if r.confidence >= self.config.confidence_threshold => r,
+            Ok(r) => {
+                log::debug!(
+                    "ML recommendation below confidence threshold ({:.2} < {:.2})",
+                    r.confidence,
+                    self.config.confidence_threshold
+                );
+                return BlendedPlan {
+                    plan: heuristic,
+                    source: PlanSource::Heuristic,
+                };
+            }
+            Err(e) => {
+                log::warn!("⚠️  ML engine unavailable, using heuristic plan: {}", e);
+                return BlendedPlan {
+                    plan: heuristic,
+                    source: PlanSource::Heuristic,
+                };
+            }
+        };
+
+        BlendedPlan {
+            plan: self.blend(heuristic, &recommendation, current),
+            source: PlanSource::MlBlended,
+        }
+    }
+
+    /// Request an allocation recommendation from the ML engine
+    pub async fn recommend(
+        &self,
+        current: &AggregatedMetrics,
+        history: &[&AggregatedMetrics],
+    ) -> OrchestrationResult<MlRecommendation> {
+        let start = history.len().saturating_sub(self.config.history_limit);
+        let url = format!(
+            "{}/optimize/allocation",
+            self.config.api_url.trim_end_matches('/')
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&AllocationRequest {
+                current,
+                history: &history[start..],
+            })
+            .send()
+            .await
+            .map_err(|e| OrchestrationError::OptimizationError(format!("ML request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(OrchestrationError::OptimizationError(format!(
+                "ML engine returned HTTP {}",
+                response.status()
+            )));
+        }
+
+        let recommendation: MlRecommendation = response.json().await.map_err(|e| {
+            OrchestrationError::DataError(format!("Invalid ML recommendation: {}", e))
+        })?;
+
+        if !(0.0..=1.0).contains(&recommendation.confidence)
+            || recommendation
+                .allocation
+                .values()
+                .any(|v| !v.is_finite() || *v < 0.0)
+        {
+            return Err(OrchestrationError::DataError(
+                "ML recommendation out of range".to_string(),
+            ));
+        }
+
+        Ok(recommendation)
+    }
+
+    /// Weighted blend of heuristic and ML allocations
+    ///
+    /// Observe-only protocols keep their current allocation, each protocol
+    /// stays within its profile bounds, and every resource group keeps its
+    /// current total so share never moves across resource dimensions.
+    fn blend(
+        &self,
+        heuristic: AllocationPlan,
+        recommendation: &MlRecommendation,
+        current: &AggregatedMetrics,
+    ) -> AllocationPlan {
+        let weight = self.config.blend_weight.clamp(0.0, 1.0);
+
+        let mut groups: HashMap<Option<ResourceKind>, Vec<String>> = HashMap::new();
+        for name in heuristic.allocation.keys() {
+            groups
+                .entry(current.resource_profile(name).map(|p| p.primary))
+                .or_default()
+                .push(name.clone());
+        }
+
+        let mut allocation = heuristic.allocation.clone();
+        for members in groups.values() {
+            let current_total: f64 = members
+                .iter()
+                .map(|name| heuristic.allocation[name])
+                .sum();
+
+            let mut blended = HashMap::new();
+            for name in members {
+                let base = heuristic.allocation[name];
+                let value = match current.resource_profile(name) {
+                    Some(profile) if profile.observe_only => continue,
+                    Some(profile) => {
+                        let ml = recommendation.allocation.get(name).copied().unwrap_or(base);
+                        profile.clamp(weight * ml + (1.0 - weight) * base)
+                    }
+                    None => {
+                        let ml = recommendation.allocation.get(name).copied().unwrap_or(base);
+                        weight * ml + (1.0 - weight) * base
+                    }
+                };
+                blended.insert(name.clone(), value);
+            }
+
+            // Rescale the movable protocols so the group total is unchanged
+            let fixed: f64 = members
+                .iter()
+                .filter(|name| !blended.contains_key(*name))
+                .map(|name| heuristic.allocation[name])
+                .sum();
+            let blended_total: f64 = blended.values().sum();
+            let scale = if blended_total > 0.0 {
+                (current_total - fixed) / blended_total
+            } else {
+                1.0
+            };
+            for (name, value) in blended {
+                allocation.insert(name, value * scale);
+            }
+        }
+
+        let estimated_improvement = current
+            .earnings_by_protocol
+            .iter()
+            .map(|(name, rate)| rate * allocation.get(name).copied().unwrap_or(0.0) / 100.0)
+            .sum::<f64>();
+        let cost = estimated_improvement * 0.05;
+
+        AllocationPlan {
+            allocation,
+            estimated_improvement,
+            estimated_cost: cost,
+            net_benefit: estimated_improvement - cost,
+            roi_percent: if cost > 0.001 {
+                (estimated_improvement / cost) * 100.0
+            } else {
+                100.0
+            },
+            confidence: weight * recommendation.confidence + (1.0 - weight) * heuristic.confidence,
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::ResourceProfile;
+    use chrono::Utc;
+
+    fn metrics() -> AggregatedMetrics {
+        let protocols = [("streamr", 3.0, 30.0), ("storj", 4.0, 40.0), ("golem", 2.5, 30.0)];
+        AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: 9.5,
+            earnings_by_protocol: protocols.iter().map(|(n, e, _)| (n.to_string(), *e)).collect(),
+            allocation_by_protocol: protocols.iter().map(|(n, _, a)| (n.to_string(), *a)).collect(),
+            resource_utilization: super::super::ResourceUtilization {
+                cpu_percent: 50.0,
+                memory_percent: 60.0,
+                bandwidth_percent: 40.0,
+                storage_percent: 30.0,
+            },
+            connection_status: protocols.iter().map(|(n, _, _)| (n.to_string(), true)).collect(),
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        }
+    }
+
+    fn heuristic_plan(current: &AggregatedMetrics) -> AllocationPlan {
+        AllocationPlan {
+            allocation: current.allocation_by_protocol.clone(),
+            estimated_improvement: 0.0,
+            estimated_cost: 0.0,
+            net_benefit: 0.0,
+            roi_percent: 100.0,
+            confidence: 0.85,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn optimizer(url: &str) -> MlOptimizer {
+        MlOptimizer::new(MlOptimizerConfig {
+            enabled: true,
+            api_url: url.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_blends_confident_recommendation() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/optimize/allocation")
+            .with_status(200)
+            .with_body(r#"{"allocation":{"streamr":10.0,"storj":60.0,"golem":30.0},"confidence":0.9}"#)
+            .create_async()
+            .await;
+
+        let current = metrics();
+        let result = optimizer(&server.url())
+            .refine(heuristic_plan(&current), &current, &[&current])
+            .await;
+
+        assert_eq!(result.source, PlanSource::MlBlended);
+        assert!((result.plan.allocation["storj"] - 50.0).abs() < 1e-9);
+        assert!((result.plan.allocation["streamr"] - 20.0).abs() < 1e-9);
+        assert!((result.plan.allocation.values().sum::<f64>() - 100.0).abs() < 1e-9);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_when_unreachable_or_unsure() {
+        let current = metrics();
+
+        let unreachable = optimizer("http://127.0.0.1:1")
+            .refine(heuristic_plan(&current), &current, &[])
+            .await;
+        assert_eq!(unreachable.source, PlanSource::Heuristic);
+        assert_eq!(unreachable.plan.allocation["storj"], 40.0);
+
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/optimize/allocation")
+            .with_status(200)
+            .with_body(r#"{"allocation":{"storj":100.0},"confidence":0.2}"#)
+            .create_async()
+            .await;
+        let unsure = optimizer(&server.url())
+            .refine(heuristic_plan(&current), &current, &[])
+            .await;
+        assert_eq!(unsure.source, PlanSource::Heuristic);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_skips_ml_engine() {
+        let current = metrics();
+        let result = MlOptimizer::new(MlOptimizerConfig::default())
+            .refine(heuristic_plan(&current), &current, &[])
+            .await;
+        assert_eq!(result.source, PlanSource::Heuristic);
+    }
+
+    #[test]
+    fn test_blend_keeps_resource_groups_and_observe_only() {
+        let mut current = metrics();
+        current.resource_profiles.insert(
+            "streamr".to_string(),
+            ResourceProfile::new(ResourceKind::Bandwidth, 0.0, 100.0),
+        );
+        current
+            .resource_profiles
+            .insert("golem".to_string(), ResourceProfile::observe_only());
+
+        let recommendation = MlRecommendation {
+            allocation: [("streamr", 100.0), ("storj", 0.0), ("golem", 0.0)]
+                .into_iter()
+                .map(|(n, v)| (n.to_string(), v))
+                .collect(),
+            confidence: 1.0,
+            model_version: None,
+        };
+
+        let plan = optimizer("http://unused").blend(heuristic_plan(&current), &recommendation, &current);
+
+        // streamr only shares its group with observe-only golem; storj is ungrouped
+        assert!((plan.allocation["streamr"] - 30.0).abs() < 1e-9);
+        assert_eq!(plan.allocation["golem"], 30.0);
+        assert!((plan.allocation["storj"] - 40.0).abs() < 1e-9);
+    }
+}
@@ -2,16 +2,26 @@
 ///
 /// Coordinates all protocol adapters and optimizes earnings across networks.
 /// Provides multi-protocol monitoring, earnings optimization, and resource reallocation.
+pub mod cache;
 pub mod coordinator;
+pub mod ml_optimizer;
 pub mod monitor;
 pub mod optimizer;
 pub mod reallocation;
+pub mod resilience;
+#[cfg(any(test, feature = "test-util"))]
+pub mod simulation;
+pub mod stress;
+pub mod window;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 
+use crate::db::models::UptimeWindowRecord;
+use crate::protocols::{ResourceMetrics, ResourceProfile};
+
 // ============================================================================
 // ERROR TYPES
 // ============================================================================
@@ -57,14 +67,48 @@ pub struct AggregatedMetrics {
     pub timestamp: DateTime<Utc>,
     /// Total earnings across all protocols (USD/hour)
     pub total_earnings_per_hour: f64,
-    /// Earnings by protocol
+    /// Earnings by protocol instance id
     pub earnings_by_protocol: HashMap<String, f64>,
-    /// Current allocation by protocol (percentage)
+    /// Current allocation by protocol instance id (percentage)
     pub allocation_by_protocol: HashMap<String, f64>,
     /// Total resource utilization
     pub resource_utilization: ResourceUtilization,
     /// Connection status by protocol
     pub connection_status: HashMap<String, bool>,
+    /// Resource profile by protocol type
+    #[serde(default)]
+    pub resource_profiles: HashMap<String, ResourceProfile>,
+    /// Protocol type by protocol instance id
+    #[serde(default)]
+    pub protocol_types: HashMap<String, String>,
+    /// Earnings per resource unit by protocol
+    #[serde(default)]
+    pub efficiency_by_protocol: HashMap<String, ResourceEfficiency>,
+    /// Protocol-specific metrics reported with earnings (e.g. Storj satellite scores)
+    #[serde(default)]
+    pub protocol_details: HashMap<String, HashMap<String, f64>>,
+    /// Upstream bandwidth measured by the latest network probe (Mbps); while
+    /// set, `bandwidth_percent` is the share of it protocols are using
+    #[serde(default)]
+    pub upstream_mbps: Option<f64>,
+}
+
+impl AggregatedMetrics {
+    /// Protocol type of the instance `protocol`
+    pub fn protocol_type<'a>(&'a self, protocol: &'a str) -> &'a str {
+        protocol_type(&self.protocol_types, protocol)
+    }
+
+    /// Resource profile of the instance `protocol`, shared by its protocol type
+    pub fn resource_profile(&self, protocol: &str) -> Option<&ResourceProfile> {
+        self.resource_profiles.get(self.protocol_type(protocol))
+    }
+}
+
+/// Protocol type of the instance `protocol` in `protocol_types`; instances
+/// missing from it are named after their type
+pub fn protocol_type<'a>(protocol_types: &'a HashMap<String, String>, protocol: &'a str) -> &'a str {
+    protocol_types.get(protocol).map_or(protocol, String::as_str)
 }
 
 /// Resource utilization metrics
@@ -80,6 +124,36 @@ pub struct ResourceUtilization {
     pub storage_percent: f64,
 }
 
+/// Earnings normalized by the resources a protocol consumes
+///
+/// Each rate is `None` when the protocol uses (next to) none of that resource,
+/// so protocols can be compared on like terms instead of raw allocation percent.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceEfficiency {
+    /// USD per CPU-core-hour
+    pub usd_per_cpu_core_hour: Option<f64>,
+    /// USD per GB stored, per hour
+    pub usd_per_gb_hour: Option<f64>,
+    /// USD per Mbps shared, per hour
+    pub usd_per_mbps_hour: Option<f64>,
+}
+
+impl ResourceEfficiency {
+    /// Smallest resource amount a rate is computed for
+    const MIN_UNITS: f64 = 0.01;
+
+    /// Efficiency of earning `earnings_per_hour` with `resources` on a host
+    /// with `host_cpu_cores` cores (`cpu_percent` is a share of the host)
+    pub fn from_usage(earnings_per_hour: f64, resources: &ResourceMetrics, host_cpu_cores: f64) -> Self {
+        let per_unit = |units: f64| (units >= Self::MIN_UNITS).then(|| earnings_per_hour / units);
+        Self {
+            usd_per_cpu_core_hour: per_unit(resources.cpu_percent / 100.0 * host_cpu_cores),
+            usd_per_gb_hour: per_unit(resources.storage_gb),
+            usd_per_mbps_hour: per_unit(resources.bandwidth_mbps),
+        }
+    }
+}
+
 /// Optimization opportunity
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationOpportunity {
@@ -97,6 +171,10 @@ pub struct OptimizationOpportunity {
     pub confidence: f64,
     /// Implementation complexity (0-1, lower is easier)
     pub complexity: f64,
+    /// Earnings volatility of the target protocol (0-1, lower is steadier);
+    /// 0 until its risk has been computed
+    #[serde(default)]
+    pub risk_score: f64,
 }
 
 /// Allocation plan
@@ -106,7 +184,8 @@ pub struct AllocationPlan {
     pub allocation: HashMap<String, f64>,
     /// Estimated total earnings improvement (USD/hour)
     pub estimated_improvement: f64,
-    /// Estimated cost of reallocation
+    /// Estimated cost of reallocation (USD/hour once priced, with one-off
+    /// switching costs spread over the amortization period)
     pub estimated_cost: f64,
     /// Net benefit after cost
     pub net_benefit: f64,
@@ -131,10 +210,86 @@ pub struct AllocationChange {
     pub new_allocation: f64,
     /// Reason for change
     pub reason: String,
-    /// Expected earnings impact
+    /// Change in earnings attributed to this change (USD/hour)
     pub earnings_impact: f64,
 }
 
+/// Rolling uptime of a protocol as a percentage of the time it was polled
+///
+/// A period with no observations is `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolUptime {
+    pub last_24h: Option<f64>,
+    pub last_7d: Option<f64>,
+    pub last_30d: Option<f64>,
+}
+
+impl ProtocolUptime {
+    /// Uptime as of `now` from one protocol's connectivity windows
+    pub fn from_windows(windows: &[UptimeWindowRecord], now: DateTime<Utc>) -> Self {
+        let percent = |days: i64| {
+            let since = now - chrono::Duration::days(days);
+            let (up, tracked) = windows.iter().fold((0, 0), |(up, tracked), window| {
+                let secs = window.overlap_secs(since, now);
+                (up + if window.connected { secs } else { 0 }, tracked + secs)
+            });
+            (tracked > 0).then(|| up as f64 / tracked as f64 * 100.0)
+        };
+
+        Self {
+            last_24h: percent(1),
+            last_7d: percent(7),
+            last_30d: percent(30),
+        }
+    }
+}
+
+/// Volatility of a protocol's hourly earnings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolRisk {
+    /// Hourly earnings the figures are computed from
+    pub samples: usize,
+    /// Mean hourly earnings (USD/hour)
+    pub mean_earnings: f64,
+    /// Standard deviation of hourly earnings (USD/hour)
+    pub std_dev: f64,
+    /// Largest fall from a running peak, as a percentage of that peak
+    pub max_drawdown_percent: f64,
+    /// 0 (steady) to 1 (volatile): mean of the coefficient of variation,
+    /// capped at 1, and the max drawdown
+    pub risk_score: f64,
+}
+
+impl ProtocolRisk {
+    /// Risk of hourly `earnings` in time order; `None` for fewer than two hours
+    pub fn from_hourly(earnings: &[f64]) -> Option<Self> {
+        if earnings.len() < 2 {
+            return None;
+        }
+        let count = earnings.len() as f64;
+        let mean = earnings.iter().sum::<f64>() / count;
+        let std_dev = (earnings.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / count).sqrt();
+
+        let mut peak = 0.0f64;
+        let mut max_drawdown = 0.0f64;
+        for &rate in earnings {
+            peak = peak.max(rate);
+            if peak > 0.0 {
+                max_drawdown = max_drawdown.max((peak - rate) / peak);
+            }
+        }
+
+        let variation = if mean > 0.0 { (std_dev / mean).min(1.0) } else { 0.0 };
+        Some(Self {
+            samples: earnings.len(),
+            mean_earnings: mean,
+            std_dev,
+            max_drawdown_percent: max_drawdown * 100.0,
+            risk_score: (variation + max_drawdown) / 2.0,
+        })
+    }
+}
+
 /// Dashboard snapshot
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DashboardSnapshot {
@@ -173,11 +328,21 @@ pub enum AlertType {
     ResourceContention { resource: String },
     /// Optimization potential
     OptimizationPotential { potential_improvement: f64 },
+    /// Protocol earnings deviate sharply from their recent history
+    EarningsAnomaly {
+        protocol: String,
+        current_rate: f64,
+        expected_rate: f64,
+        z_score: f64,
+    },
 }
 
 /// Alert
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alert {
+    /// Row id in the `alerts` table; `None` until the alert is stored
+    #[serde(default)]
+    pub id: Option<i64>,
     /// Timestamp of alert
     pub timestamp: DateTime<Utc>,
     /// Alert type
@@ -188,6 +353,30 @@ pub struct Alert {
     pub message: String,
     /// Whether alert has been acknowledged
     pub acknowledged: bool,
+    /// When the underlying condition cleared
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl Alert {
+    /// Identity of the alerting condition (type + resource) used for deduplication
+    pub fn fingerprint(&self) -> String {
+        match &self.alert_type {
+            AlertType::LowEarnings { .. } => "low_earnings".to_string(),
+            AlertType::ProtocolDisconnected { protocol } => {
+                format!("protocol_disconnected:{}", protocol)
+            }
+            AlertType::ReallocationOpportunity { opportunity } => format!(
+                "reallocation_opportunity:{}:{}",
+                opportunity.from_protocol, opportunity.to_protocol
+            ),
+            AlertType::ResourceContention { resource } => {
+                format!("resource_contention:{}", resource.to_lowercase())
+            }
+            AlertType::OptimizationPotential { .. } => "optimization_potential".to_string(),
+            AlertType::EarningsAnomaly { protocol, .. } => format!("earnings_anomaly:{}", protocol),
+        }
+    }
 }
 
 /// Performance report
@@ -204,12 +393,43 @@ pub struct PerformanceReport {
     pub earnings_by_protocol: HashMap<String, f64>,
     /// Allocation changes made
     pub allocation_changes: Vec<AllocationChange>,
-    /// Total improvement from reallocations
+    /// Change in earnings rate caused by reallocations (the attribution's
+    /// allocation effect, USD/hour)
     pub total_improvement: f64,
     /// Number of successful optimizations
     pub successful_optimizations: u32,
     /// Uptime percentage
     pub uptime_percent: f64,
+    /// Change in earnings rate over the period, split into market and
+    /// allocation effects
+    #[serde(default)]
+    pub attribution: EarningsAttribution,
+}
+
+/// Change in earnings rate over a period, split by cause
+///
+/// The market effect is what rate changes did at the allocation we held; the
+/// allocation effect is what our reallocations did. The two add up to the
+/// change from `start_earnings_per_hour` to `end_earnings_per_hour`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EarningsAttribution {
+    /// Earnings rate at the first snapshot of the period (USD/hour)
+    pub start_earnings_per_hour: f64,
+    /// Earnings rate at the last snapshot of the period (USD/hour)
+    pub end_earnings_per_hour: f64,
+    /// Change caused by protocol rates moving (USD/hour)
+    pub market_effect: f64,
+    /// Change caused by reallocations (USD/hour)
+    pub allocation_effect: f64,
+    /// The same split per protocol
+    pub by_protocol: HashMap<String, ProtocolAttribution>,
+}
+
+/// One protocol's share of an [`EarningsAttribution`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolAttribution {
+    pub market_effect: f64,
+    pub allocation_effect: f64,
 }
 
 #[cfg(test)]
@@ -230,11 +450,37 @@ mod tests {
                 storage_percent: 30.0,
             },
             connection_status: HashMap::new(),
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
         };
 
         assert_eq!(metrics.total_earnings_per_hour, 10.50);
     }
 
+    #[test]
+    fn test_resource_efficiency_from_usage() {
+        let resources = ResourceMetrics {
+            cpu_percent: 25.0,
+            memory_mb: 512.0,
+            bandwidth_mbps: 50.0,
+            storage_gb: 0.0,
+            uptime_seconds: 3600,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
+        };
+
+        // 25% of 8 cores is 2 cores
+        let efficiency = ResourceEfficiency::from_usage(1.0, &resources, 8.0);
+        assert_eq!(efficiency.usd_per_cpu_core_hour, Some(0.5));
+        assert_eq!(efficiency.usd_per_mbps_hour, Some(0.02));
+        assert_eq!(efficiency.usd_per_gb_hour, None);
+    }
+
     #[test]
     fn test_allocation_plan_creation() {
         let plan = AllocationPlan {
@@ -254,6 +500,7 @@ mod tests {
     #[test]
     fn test_alert_creation() {
         let alert = Alert {
+            id: None,
             timestamp: Utc::now(),
             alert_type: AlertType::LowEarnings {
                 current_rate: 5.0,
@@ -262,9 +509,31 @@ mod tests {
             severity: 0.6,
             message: "Earnings below threshold".to_string(),
             acknowledged: false,
+            resolved_at: None,
         };
 
         assert!(!alert.acknowledged);
         assert_eq!(alert.severity, 0.6);
     }
+
+    #[test]
+    fn test_protocol_uptime_from_windows() {
+        let now = Utc::now();
+        let window = |connected, from_hours: i64, to_hours: i64| UptimeWindowRecord {
+            id: None,
+            protocol_name: "storj".to_string(),
+            connected,
+            started_at: (now - chrono::Duration::hours(from_hours)).to_rfc3339(),
+            ended_at: (now - chrono::Duration::hours(to_hours)).to_rfc3339(),
+        };
+
+        // Up for 2 days, down 12 hours, untracked 6 hours, then up 6 hours
+        let windows = [window(true, 84, 36), window(false, 36, 24), window(false, 24, 12), window(true, 6, 0)];
+        let uptime = ProtocolUptime::from_windows(&windows, now);
+
+        assert!((uptime.last_24h.unwrap() - 100.0 * 6.0 / 18.0).abs() < 1e-9);
+        assert!((uptime.last_7d.unwrap() - 100.0 * 54.0 / 78.0).abs() < 1e-9);
+        assert_eq!(uptime.last_30d, uptime.last_7d);
+        assert_eq!(ProtocolUptime::from_windows(&[], now), ProtocolUptime::default());
+    }
 }
@@ -2,13 +2,22 @@
 ///
 /// Provides dashboard metrics, alerting, and performance reporting.
 /// Generates real-time insights and historical analysis.
-
+///
+/// With a database attached, reports and trends read the persisted
+/// `metrics`/`protocol_metrics` history; the in-memory snapshots then only
+/// serve as a hot cache for periods they fully cover.
 use super::{
-    AggregatedMetrics, Alert, AlertType, DashboardSnapshot, OptimizationOpportunity,
-    PerformanceReport, OrchestrationError, OrchestrationResult,
+    AggregatedMetrics, Alert, AlertType, AllocationChange, DashboardSnapshot, EarningsAttribution,
+    OptimizationOpportunity, PerformanceReport, OrchestrationError, OrchestrationResult,
+    ResourceUtilization,
 };
+use super::window::MetricsWindow;
+use crate::db::models::{MetricsRecord, ProtocolMetricsRecord};
+use crate::events::{EventSubscriber, OrchestratorEvent};
+use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
-use std::collections::HashMap;
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -27,6 +36,16 @@ pub struct MonitorConfig {
     pub connection_timeout: Duration,
     /// Maximum alerts to keep
     pub max_alerts: usize,
+    /// Detect per-protocol earnings anomalies
+    pub anomaly_detection_enabled: bool,
+    /// Snapshots of history used as the anomaly baseline
+    pub anomaly_window: usize,
+    /// Minimum baseline snapshots before anomalies are reported
+    pub anomaly_min_samples: usize,
+    /// Robust z-score beyond which earnings are anomalous
+    pub anomaly_z_threshold: f64,
+    /// Minimum time before an open alert with the same fingerprint fires again
+    pub alert_cooldown: Duration,
 }
 
 impl Default for MonitorConfig {
@@ -36,6 +55,11 @@ impl Default for MonitorConfig {
             optimization_threshold: 0.25,
             connection_timeout: Duration::minutes(5),
             max_alerts: 1000,
+            anomaly_detection_enabled: true,
+            anomaly_window: 48,
+            anomaly_min_samples: 12,
+            anomaly_z_threshold: 3.5,
+            alert_cooldown: Duration::minutes(15),
         }
     }
 }
@@ -44,14 +68,23 @@ impl Default for MonitorConfig {
 // MONITOR IMPLEMENTATION
 // ============================================================================
 
+/// Most recent metrics snapshots kept in memory
+const MAX_SNAPSHOTS: usize = 10_000;
+
 /// Real-Time Monitor
 ///
 /// Monitors protocol performance and generates alerts.
 pub struct RealtimeMonitor {
     config: MonitorConfig,
     alerts: Arc<RwLock<Vec<Alert>>>,
-    metrics_snapshots: Arc<RwLock<Vec<AggregatedMetrics>>>,
+    metrics_snapshots: MetricsWindow,
     last_dashboard_update: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// Last time each open alert fingerprint fired
+    open_alerts: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+    /// Persisted metrics history for reports and trends
+    db: Option<SqlitePool>,
+    /// Account whose persisted history is read
+    account_id: i64,
 }
 
 impl RealtimeMonitor {
@@ -60,11 +93,22 @@ impl RealtimeMonitor {
         Self {
             config,
             alerts: Arc::new(RwLock::new(Vec::new())),
-            metrics_snapshots: Arc::new(RwLock::new(Vec::new())),
+            metrics_snapshots: MetricsWindow::new(MAX_SNAPSHOTS),
             last_dashboard_update: Arc::new(RwLock::new(None)),
+            open_alerts: Arc::new(RwLock::new(HashMap::new())),
+            db: None,
+            account_id: crate::db::DEFAULT_ACCOUNT_ID,
         }
     }
 
+    /// Read reports and trends from the database, falling back to the
+    /// in-memory snapshots only when they cover the requested period
+    pub fn with_database(mut self, pool: SqlitePool, account_id: i64) -> Self {
+        self.db = Some(pool);
+        self.account_id = account_id;
+        self
+    }
+
     /// Get dashboard metrics
     pub async fn get_dashboard_metrics(
         &self,
@@ -112,6 +156,7 @@ impl RealtimeMonitor {
         // Check low earnings
         if current_metrics.total_earnings_per_hour < self.config.low_earnings_threshold {
             new_alerts.push(Alert {
+                id: None,
                 timestamp: Utc::now(),
                 alert_type: AlertType::LowEarnings {
                     current_rate: current_metrics.total_earnings_per_hour,
@@ -123,6 +168,7 @@ impl RealtimeMonitor {
                     current_metrics.total_earnings_per_hour, self.config.low_earnings_threshold
                 ),
                 acknowledged: false,
+                resolved_at: None,
             });
         }
 
@@ -130,6 +176,7 @@ impl RealtimeMonitor {
         for (protocol, connected) in &current_metrics.connection_status {
             if !connected {
                 new_alerts.push(Alert {
+                    id: None,
                     timestamp: Utc::now(),
                     alert_type: AlertType::ProtocolDisconnected {
                         protocol: protocol.clone(),
@@ -137,6 +184,7 @@ impl RealtimeMonitor {
                     severity: 0.9,
                     message: format!("Protocol {} disconnected", protocol),
                     acknowledged: false,
+                    resolved_at: None,
                 });
             }
         }
@@ -145,6 +193,7 @@ impl RealtimeMonitor {
         if let Some(best_opp) = opportunities.first() {
             if best_opp.earnings_improvement > self.config.optimization_threshold {
                 new_alerts.push(Alert {
+                    id: None,
                     timestamp: Utc::now(),
                     alert_type: AlertType::ReallocationOpportunity {
                         opportunity: best_opp.clone(),
@@ -155,6 +204,7 @@ impl RealtimeMonitor {
                         best_opp.earnings_improvement
                     ),
                     acknowledged: false,
+                    resolved_at: None,
                 });
             }
         }
@@ -162,6 +212,7 @@ impl RealtimeMonitor {
         // Check high resource usage
         if current_metrics.resource_utilization.cpu_percent > 95.0 {
             new_alerts.push(Alert {
+                id: None,
                 timestamp: Utc::now(),
                 alert_type: AlertType::ResourceContention {
                     resource: "CPU".to_string(),
@@ -169,11 +220,48 @@ impl RealtimeMonitor {
                 severity: 0.8,
                 message: "CPU utilization critically high".to_string(),
                 acknowledged: false,
+                resolved_at: None,
             });
         }
 
+        // Check per-protocol earnings anomalies
+        if self.config.anomaly_detection_enabled {
+            new_alerts.extend(self.detect_earnings_anomalies(current_metrics).await);
+        }
+
+        // Deduplicate against open alerts and resolve conditions that cleared
+        let now = Utc::now();
+        let mut open_alerts = self.open_alerts.write().await;
+        let active: std::collections::HashSet<String> =
+            new_alerts.iter().map(|a| a.fingerprint()).collect();
+        let cleared: Vec<String> = open_alerts
+            .keys()
+            .filter(|fingerprint| !active.contains(*fingerprint))
+            .cloned()
+            .collect();
+
+        new_alerts.retain(|alert| {
+            let fingerprint = alert.fingerprint();
+            match open_alerts.get(&fingerprint) {
+                Some(last_fired) if now - *last_fired < self.config.alert_cooldown => false,
+                _ => {
+                    open_alerts.insert(fingerprint, now);
+                    true
+                }
+            }
+        });
+
         // Add new alerts to history
         let mut alerts = self.alerts.write().await;
+        for fingerprint in cleared {
+            open_alerts.remove(&fingerprint);
+            for alert in alerts
+                .iter_mut()
+                .filter(|a| a.resolved_at.is_none() && a.fingerprint() == fingerprint)
+            {
+                alert.resolved_at = Some(now);
+            }
+        }
         for alert in &new_alerts {
             alerts.push(alert.clone());
         }
@@ -187,18 +275,70 @@ impl RealtimeMonitor {
         Ok(new_alerts)
     }
 
+    /// Detect protocols whose earnings drop or spike against their history
+    ///
+    /// Uses a robust z-score (median and MAD) over the last `anomaly_window`
+    /// snapshots, so a stalled node that still reports "connected" is caught
+    /// without one noisy sample skewing the baseline.
+    pub async fn detect_earnings_anomalies(&self, current_metrics: &AggregatedMetrics) -> Vec<Alert> {
+        let snapshots = self.metrics_snapshots.snapshot();
+        let baseline: Vec<_> = snapshots
+            .iter()
+            .filter(|m| m.timestamp < current_metrics.timestamp)
+            .rev()
+            .take(self.config.anomaly_window)
+            .collect();
+
+        let mut anomalies = Vec::new();
+        for (protocol, &rate) in &current_metrics.earnings_by_protocol {
+            let history: Vec<f64> = baseline
+                .iter()
+                .filter_map(|m| m.earnings_by_protocol.get(protocol).copied())
+                .collect();
+            if history.len() < self.config.anomaly_min_samples {
+                continue;
+            }
+
+            let (expected, z_score) = robust_z_score(&history, rate);
+            if z_score.abs() < self.config.anomaly_z_threshold {
+                continue;
+            }
+
+            let (direction, base_severity) = if z_score < 0.0 {
+                ("dropped", 0.7)
+            } else {
+                ("spiked", 0.4)
+            };
+            anomalies.push(Alert {
+                id: None,
+                timestamp: Utc::now(),
+                alert_type: AlertType::EarningsAnomaly {
+                    protocol: protocol.clone(),
+                    current_rate: rate,
+                    expected_rate: expected,
+                    z_score,
+                },
+                severity: (base_severity + z_score.abs() / 50.0).min(1.0),
+                message: format!(
+                    "{} earnings {} to {:.2}/hr (expected {:.2}/hr, z={:.1})",
+                    protocol, direction, rate, expected, z_score
+                ),
+                acknowledged: false,
+                resolved_at: None,
+            });
+        }
+
+        anomalies
+    }
+
     /// Generate performance report
     pub async fn generate_report(
         &self,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
     ) -> OrchestrationResult<PerformanceReport> {
-        let snapshots = self.metrics_snapshots.read().await;
-
-        let period_metrics: Vec<_> = snapshots
-            .iter()
-            .filter(|m| m.timestamp >= period_start && m.timestamp <= period_end)
-            .collect();
+        let snapshots = self.snapshots_between(period_start, period_end).await?;
+        let period_metrics: Vec<_> = snapshots.iter().map(Arc::as_ref).collect();
 
         if period_metrics.is_empty() {
             return Err(OrchestrationError::MonitoringError(
@@ -221,13 +361,9 @@ impl RealtimeMonitor {
             }
         }
 
-        let allocation_changes = self.get_recent_allocation_changes(
-            (period_end - period_start).num_hours()
-        ).await;
-
-        let total_improvement: f64 = allocation_changes.iter()
-            .map(|c| c.earnings_impact)
-            .sum();
+        let allocation_changes = allocation_changes(period_metrics.iter().copied());
+        let attribution = attribute_earnings(&period_metrics);
+        let total_improvement = attribution.allocation_effect;
 
         let successful_optimizations = allocation_changes.len() as u32;
 
@@ -243,6 +379,7 @@ impl RealtimeMonitor {
             total_improvement,
             successful_optimizations,
             uptime_percent,
+            attribution,
         })
     }
 
@@ -251,43 +388,60 @@ impl RealtimeMonitor {
         &self,
         hours: i64,
     ) -> OrchestrationResult<Vec<(DateTime<Utc>, f64)>> {
-        let cutoff = Utc::now() - Duration::hours(hours);
-        let snapshots = self.metrics_snapshots.read().await;
+        let now = Utc::now();
+        let snapshots = self.snapshots_between(now - Duration::hours(hours), now).await?;
 
         Ok(snapshots
             .iter()
-            .filter(|m| m.timestamp > cutoff)
             .map(|m| (m.timestamp, m.total_earnings_per_hour))
             .collect())
     }
 
+    /// Snapshots taken between `start` and `end`, oldest first
+    ///
+    /// Served from memory when no database is attached or the in-memory
+    /// snapshots reach back to `start`; otherwise loaded from the database.
+    async fn snapshots_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> OrchestrationResult<Vec<Arc<AggregatedMetrics>>> {
+        let snapshots = self.metrics_snapshots.snapshot();
+        let cached = snapshots.first().is_some_and(|oldest| oldest.timestamp <= start);
+        if self.db.is_none() || cached {
+            return Ok(snapshots.shared_between(start, end));
+        }
+
+        let Some(pool) = &self.db else {
+            return Ok(Vec::new());
+        };
+        let db_error = |e: sqlx::Error| OrchestrationError::DataError(e.to_string());
+        let metrics = crate::db::queries::get_metrics_by_range(pool, self.account_id, start, end)
+            .await
+            .map_err(db_error)?;
+        let protocols = crate::db::queries::get_protocol_metrics_by_range(pool, self.account_id, start, end)
+            .await
+            .map_err(db_error)?;
+
+        Ok(snapshots_from_records(metrics, protocols).into_iter().map(Arc::new).collect())
+    }
+
     /// Export metrics to JSON
     pub async fn export_metrics(
         &self,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
     ) -> OrchestrationResult<String> {
-        let snapshots = self.metrics_snapshots.read().await;
-
-        let filtered: Vec<_> = snapshots
-            .iter()
-            .filter(|m| m.timestamp >= period_start && m.timestamp <= period_end)
-            .collect();
+        let snapshots = self.metrics_snapshots.snapshot();
+        let filtered: Vec<_> = snapshots.between(period_start, period_end).collect();
 
         serde_json::to_string_pretty(&filtered)
             .map_err(|e| OrchestrationError::DataError(e.to_string()))
     }
 
     /// Update metrics snapshot
-    pub async fn update_snapshot(&self, metrics: AggregatedMetrics) {
-        let mut snapshots = self.metrics_snapshots.write().await;
-        snapshots.push(metrics);
-
-        // Keep last 10,000 snapshots
-        if snapshots.len() > 10000 {
-            let excess = snapshots.len() - 10000;
-            snapshots.drain(0..excess);
-        }
+    pub async fn update_snapshot(&self, metrics: impl Into<Arc<AggregatedMetrics>>) {
+        self.metrics_snapshots.push(metrics);
     }
 
     /// Get alert history
@@ -307,49 +461,38 @@ impl RealtimeMonitor {
         Err(OrchestrationError::MonitoringError("Alert not found".to_string()))
     }
 
+    /// Mark in-memory alerts acknowledged in the `alerts` table as acknowledged
+    ///
+    /// Returns how many held alerts carried one of `ids`.
+    pub async fn acknowledge_alerts(&self, ids: &[i64]) -> usize {
+        let mut alerts = self.alerts.write().await;
+        let mut matched = 0;
+        for alert in alerts
+            .iter_mut()
+            .filter(|a| a.id.is_some_and(|id| ids.contains(&id)))
+        {
+            alert.acknowledged = true;
+            matched += 1;
+        }
+        matched
+    }
+
     /// Clear old data
     pub async fn cleanup_old_data(&self, retention_days: i64) {
         let cutoff = Utc::now() - Duration::days(retention_days);
 
-        let mut snapshots = self.metrics_snapshots.write().await;
-        snapshots.retain(|m| m.timestamp > cutoff);
+        self.metrics_snapshots.retain(|m| m.timestamp > cutoff);
 
         let mut alerts = self.alerts.write().await;
         alerts.retain(|a| a.timestamp > cutoff);
     }
 
     /// Get recent allocation changes
-    async fn get_recent_allocation_changes(&self, hours: i64) -> Vec<super::AllocationChange> {
+    async fn get_recent_allocation_changes(&self, hours: i64) -> Vec<AllocationChange> {
         let cutoff = Utc::now() - Duration::hours(hours);
-        let snapshots = self.metrics_snapshots.read().await;
-
-        // Extract allocation changes from metrics history
-        let mut changes = Vec::new();
-        let mut prev_allocations: HashMap<String, f64> = HashMap::new();
-
-        for snapshot in snapshots.iter() {
-            if snapshot.timestamp < cutoff {
-                continue;
-            }
-
-            for (protocol, allocation) in &snapshot.allocation_by_protocol {
-                if let Some(prev_alloc) = prev_allocations.get(protocol) {
-                    if (allocation - prev_alloc).abs() > 0.1 {
-                        changes.push(super::AllocationChange {
-                            timestamp: snapshot.timestamp,
-                            protocol: protocol.clone(),
-                            old_allocation: *prev_alloc,
-                            new_allocation: *allocation,
-                            reason: "Automatic reallocation".to_string(),
-                            earnings_impact: 0.0,
-                        });
-                    }
-                }
-                prev_allocations.insert(protocol.clone(), *allocation);
-            }
-        }
+        let snapshots = self.metrics_snapshots.snapshot();
 
-        changes
+        allocation_changes(snapshots.iter().filter(|s| s.timestamp >= cutoff))
     }
 
     /// Calculate uptime percentage
@@ -367,6 +510,190 @@ impl RealtimeMonitor {
     }
 }
 
+/// Keeps the monitor's snapshots in step with every poll
+#[async_trait]
+impl EventSubscriber for RealtimeMonitor {
+    async fn on_event(&self, event: &OrchestratorEvent) {
+        if let OrchestratorEvent::MetricsCollected(metrics) = event {
+            self.update_snapshot(metrics.clone()).await;
+        }
+    }
+}
+
+/// Extract allocation changes from consecutive snapshots
+fn allocation_changes<'a>(
+    snapshots: impl IntoIterator<Item = &'a AggregatedMetrics>,
+) -> Vec<AllocationChange> {
+    let mut changes = Vec::new();
+    // Last seen (allocation, earnings) per protocol
+    let mut prev_positions: HashMap<String, (f64, f64)> = HashMap::new();
+
+    for snapshot in snapshots {
+        for (protocol, allocation) in &snapshot.allocation_by_protocol {
+            let earnings = snapshot.earnings_by_protocol.get(protocol).copied().unwrap_or(0.0);
+            if let Some(&(prev_alloc, prev_earnings)) = prev_positions.get(protocol) {
+                if (allocation - prev_alloc).abs() > 0.1 {
+                    changes.push(AllocationChange {
+                        timestamp: snapshot.timestamp,
+                        protocol: protocol.clone(),
+                        old_allocation: prev_alloc,
+                        new_allocation: *allocation,
+                        reason: "Automatic reallocation".to_string(),
+                        earnings_impact: allocation_effect((prev_alloc, prev_earnings), (*allocation, earnings)),
+                    });
+                }
+            }
+            prev_positions.insert(protocol.clone(), (*allocation, earnings));
+        }
+    }
+
+    changes
+}
+
+/// Part of a protocol's change in earnings caused by its allocation moving
+///
+/// Positions are (allocation percent, earnings per hour). The allocation
+/// change is valued at the protocol's yield per allocation point after the
+/// change, or before it when the protocol was drained to zero; the rest of
+/// the change is down to its rate.
+fn allocation_effect(old: (f64, f64), new: (f64, f64)) -> f64 {
+    let (old_allocation, old_earnings) = old;
+    let (new_allocation, new_earnings) = new;
+    let yield_per_point = if new_allocation > 0.0 {
+        new_earnings / new_allocation
+    } else if old_allocation > 0.0 {
+        old_earnings / old_allocation
+    } else {
+        0.0
+    };
+    (new_allocation - old_allocation) * yield_per_point
+}
+
+/// Split the change in earnings rate across `snapshots` into market and allocation effects
+///
+/// Consecutive snapshots are split pairwise and the steps summed, so each
+/// reallocation is valued at the rates that held right after it rather than
+/// at the end of the period. A protocol without an allocation in both
+/// snapshots of a step (connecting, disconnecting) counts as market effect.
+fn attribute_earnings(snapshots: &[&AggregatedMetrics]) -> EarningsAttribution {
+    let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) else {
+        return EarningsAttribution::default();
+    };
+    let mut attribution = EarningsAttribution {
+        start_earnings_per_hour: first.total_earnings_per_hour,
+        end_earnings_per_hour: last.total_earnings_per_hour,
+        ..EarningsAttribution::default()
+    };
+
+    for pair in snapshots.windows(2) {
+        let (prev, curr) = (pair[0], pair[1]);
+        let protocols: HashSet<_> = prev.earnings_by_protocol.keys().chain(curr.earnings_by_protocol.keys()).collect();
+        for protocol in protocols {
+            let entry = attribution.by_protocol.entry(protocol.clone()).or_default();
+            let old_earnings = prev.earnings_by_protocol.get(protocol).copied().unwrap_or(0.0);
+            let new_earnings = curr.earnings_by_protocol.get(protocol).copied().unwrap_or(0.0);
+            let allocations = (prev.allocation_by_protocol.get(protocol), curr.allocation_by_protocol.get(protocol));
+            let effect = match allocations {
+                (Some(&old), Some(&new)) => allocation_effect((old, old_earnings), (new, new_earnings)),
+                _ => 0.0,
+            };
+            entry.allocation_effect += effect;
+            entry.market_effect += new_earnings - old_earnings - effect;
+        }
+    }
+
+    attribution.allocation_effect = attribution.by_protocol.values().map(|p| p.allocation_effect).sum();
+    attribution.market_effect =
+        attribution.end_earnings_per_hour - attribution.start_earnings_per_hour - attribution.allocation_effect;
+    attribution
+}
+
+/// Rebuild snapshots, oldest first, from persisted metrics rows
+///
+/// Rows with an unparseable timestamp are skipped. Resource profiles are not
+/// persisted and come back empty.
+fn snapshots_from_records(
+    metrics: Vec<MetricsRecord>,
+    protocols: Vec<ProtocolMetricsRecord>,
+) -> Vec<AggregatedMetrics> {
+    let mut by_metrics_id: HashMap<i64, Vec<ProtocolMetricsRecord>> = HashMap::new();
+    for protocol in protocols {
+        by_metrics_id.entry(protocol.metrics_id).or_default().push(protocol);
+    }
+
+    let mut snapshots: Vec<AggregatedMetrics> = metrics
+        .into_iter()
+        .filter_map(|record| {
+            let timestamp = DateTime::parse_from_rfc3339(&record.timestamp)
+                .ok()?
+                .with_timezone(&Utc);
+            let rows = record
+                .id
+                .and_then(|id| by_metrics_id.remove(&id))
+                .unwrap_or_default();
+
+            let mut snapshot = AggregatedMetrics {
+                timestamp,
+                total_earnings_per_hour: record.total_earnings_per_hour,
+                earnings_by_protocol: HashMap::new(),
+                allocation_by_protocol: HashMap::new(),
+                resource_utilization: ResourceUtilization {
+                    cpu_percent: record.cpu_percent.unwrap_or(0.0),
+                    memory_percent: record.memory_percent.unwrap_or(0.0),
+                    bandwidth_percent: record.bandwidth_percent.unwrap_or(0.0),
+                    storage_percent: record.storage_percent.unwrap_or(0.0),
+                },
+                connection_status: HashMap::new(),
+                resource_profiles: HashMap::new(),
+                protocol_types: HashMap::new(),
+                efficiency_by_protocol: HashMap::new(),
+                protocol_details: HashMap::new(),
+                upstream_mbps: None,
+            };
+            for row in rows {
+                snapshot
+                    .earnings_by_protocol
+                    .insert(row.protocol_name.clone(), row.earnings_per_hour);
+                snapshot
+                    .allocation_by_protocol
+                    .insert(row.protocol_name.clone(), row.allocation_percent);
+                snapshot
+                    .connection_status
+                    .insert(row.protocol_name, row.connected.unwrap_or(false));
+            }
+            Some(snapshot)
+        })
+        .collect();
+
+    snapshots.sort_by_key(|s| s.timestamp);
+    snapshots
+}
+
+/// Median of a non-empty sample
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Robust z-score of `value` against `history`, returned with the median
+///
+/// The MAD is scaled to be comparable with a standard deviation. A floor of
+/// 1% of the median keeps perfectly flat histories from dividing by zero.
+fn robust_z_score(history: &[f64], value: f64) -> (f64, f64) {
+    let mut sorted = history.to_vec();
+    let center = median(&mut sorted);
+    let mut deviations: Vec<f64> = history.iter().map(|x| (x - center).abs()).collect();
+    let mad = median(&mut deviations);
+
+    let scale = (mad * 1.4826).max(center.abs() * 0.01).max(1e-6);
+    (center, (value - center) / scale)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -396,9 +723,44 @@ mod tests {
                 storage_percent: 30.0,
             },
             connection_status: status,
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
         }
     }
 
+    #[test]
+    fn test_attribution_separates_market_and_allocation_effects() {
+        let snapshot = |storj: (f64, f64), streamr: (f64, f64)| {
+            let mut metrics = create_test_metrics();
+            metrics.allocation_by_protocol.insert("storj".to_string(), storj.0);
+            metrics.allocation_by_protocol.insert("streamr".to_string(), streamr.0);
+            metrics.earnings_by_protocol.insert("storj".to_string(), storj.1);
+            metrics.earnings_by_protocol.insert("streamr".to_string(), streamr.1);
+            metrics.total_earnings_per_hour = storj.1 + streamr.1;
+            metrics
+        };
+        // Shift 20 points to storj at unchanged yields, then storj's rate halves
+        let snapshots = [
+            snapshot((50.0, 4.0), (50.0, 3.0)),
+            snapshot((70.0, 5.6), (30.0, 1.8)),
+            snapshot((70.0, 2.8), (30.0, 1.8)),
+        ];
+        let snapshots: Vec<_> = snapshots.iter().collect();
+
+        let attribution = attribute_earnings(&snapshots);
+        assert!((attribution.allocation_effect - 0.4).abs() < 1e-9);
+        assert!((attribution.market_effect + 2.8).abs() < 1e-9);
+        assert!((attribution.by_protocol["storj"].allocation_effect - 1.6).abs() < 1e-9);
+        assert!((attribution.by_protocol["streamr"].market_effect).abs() < 1e-9);
+
+        let changes = allocation_changes(snapshots.iter().copied());
+        let storj = changes.iter().find(|c| c.protocol == "storj").unwrap();
+        assert!((storj.earnings_impact - 1.6).abs() < 1e-9);
+    }
+
     #[test]
     fn test_monitor_creation() {
         let monitor = RealtimeMonitor::new(MonitorConfig::default());
@@ -437,4 +799,138 @@ mod tests {
         let config = MonitorConfig::default();
         assert_eq!(config.max_alerts, 1000);
     }
+
+    fn metrics_at(minutes_ago: i64, storj_rate: f64) -> AggregatedMetrics {
+        let mut metrics = create_test_metrics();
+        metrics.timestamp = Utc::now() - Duration::minutes(minutes_ago);
+        metrics.earnings_by_protocol.insert("storj".to_string(), storj_rate);
+        metrics
+    }
+
+    #[test]
+    fn test_robust_z_score() {
+        let history = [1.0, 1.1, 0.9, 1.0, 1.05, 0.95];
+        let (expected, z) = robust_z_score(&history, 1.0);
+        assert!((expected - 1.0).abs() < 1e-9);
+        assert!(z.abs() < 1e-9);
+
+        let (_, z) = robust_z_score(&history, 0.0);
+        assert!(z < -3.5);
+
+        // Flat history does not divide by zero
+        let (_, z) = robust_z_score(&[0.5; 10], 0.0);
+        assert!(z.is_finite() && z < -3.5);
+    }
+
+    #[tokio::test]
+    async fn test_detects_stalled_protocol() {
+        let monitor = RealtimeMonitor::new(MonitorConfig::default());
+        for i in 0..20 {
+            let jitter = if i % 2 == 0 { 0.1 } else { -0.1 };
+            monitor.update_snapshot(metrics_at(100 - i, 4.0 + jitter)).await;
+        }
+
+        // storj still "connected" but earning nothing
+        let current = metrics_at(0, 0.0);
+        let alerts = monitor.check_alerts(&current, &[]).await.unwrap();
+
+        let anomaly = alerts
+            .iter()
+            .find_map(|a| match &a.alert_type {
+                AlertType::EarningsAnomaly { protocol, z_score, .. } => Some((protocol, *z_score)),
+                _ => None,
+            })
+            .expect("expected an earnings anomaly");
+        assert_eq!(anomaly.0, "storj");
+        assert!(anomaly.1 < 0.0);
+
+        // Normal earnings raise nothing; streamr's flat history is not flagged either
+        let alerts = monitor.detect_earnings_anomalies(&metrics_at(0, 4.05)).await;
+        assert!(alerts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_needs_min_samples() {
+        let monitor = RealtimeMonitor::new(MonitorConfig::default());
+        for i in 0..5 {
+            monitor.update_snapshot(metrics_at(10 - i, 4.0)).await;
+        }
+        assert!(monitor.detect_earnings_anomalies(&metrics_at(0, 0.0)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alert_deduplication_and_resolution() {
+        let monitor = RealtimeMonitor::new(MonitorConfig {
+            low_earnings_threshold: 10.0,
+            ..Default::default()
+        });
+        let metrics = create_test_metrics();
+
+        let first = monitor.check_alerts(&metrics, &[]).await.unwrap();
+        assert_eq!(first.len(), 1);
+
+        // Same condition within the cooldown is suppressed
+        let repeat = monitor.check_alerts(&metrics, &[]).await.unwrap();
+        assert!(repeat.is_empty());
+        assert_eq!(monitor.get_alert_history().await.len(), 1);
+
+        // Condition clears: the open alert is resolved
+        let mut recovered = create_test_metrics();
+        recovered.total_earnings_per_hour = 12.0;
+        monitor.check_alerts(&recovered, &[]).await.unwrap();
+        assert!(monitor.get_alert_history().await[0].resolved_at.is_some());
+
+        // And can fire again afterwards
+        let again = monitor.check_alerts(&metrics, &[]).await.unwrap();
+        assert_eq!(again.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_report_and_trends_read_from_database() {
+        let pool = crate::db::test_pool().await;
+
+        let now = Utc::now();
+        for (hours_ago, allocation) in [(3, 50.0), (2, 70.0)] {
+            let id = crate::db::queries::store_metrics(
+                &pool,
+                1,
+                now - Duration::hours(hours_ago),
+                7.0,
+                50.0,
+                60.0,
+                40.0,
+                30.0,
+            )
+            .await
+            .unwrap();
+            crate::db::queries::store_protocol_metrics(
+                &pool,
+                id,
+                "storj".to_string(),
+                7.0,
+                allocation,
+                true,
+            )
+            .await
+            .unwrap();
+        }
+
+        // Nothing in memory, as after a restart
+        let other = RealtimeMonitor::new(MonitorConfig::default()).with_database(pool.clone(), 2);
+        assert!(other.generate_report(now - Duration::hours(4), now).await.is_err());
+
+        let monitor = RealtimeMonitor::new(MonitorConfig::default()).with_database(pool, 1);
+        let report = monitor
+            .generate_report(now - Duration::hours(4), now)
+            .await
+            .unwrap();
+        assert_eq!(report.total_earnings, 14.0);
+        assert_eq!(report.earnings_by_protocol["storj"], 14.0);
+        assert_eq!(report.allocation_changes.len(), 1);
+        assert_eq!(report.uptime_percent, 100.0);
+
+        let trends = monitor.get_earnings_trends(4).await.unwrap();
+        assert_eq!(trends.len(), 2);
+        assert!(trends[0].0 < trends[1].0);
+    }
 }
@@ -6,14 +6,34 @@ use super::{
     AggregatedMetrics, AllocationPlan, OptimizationOpportunity, OrchestrationError,
     OrchestrationResult,
 };
+use crate::protocols::{ResourceKind, DEMAND_INDEX_METRIC, YIELD_INDEX_METRIC};
+use serde::{Deserialize, Serialize};
+use super::window::{MetricsWindow, WindowSnapshot};
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 // ============================================================================
 // OPTIMIZER CONFIGURATION
 // ============================================================================
 
+/// Optimization strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Score protocols by their current earnings rate
+    #[default]
+    MaximizeEarnings,
+    /// Penalize protocols whose earnings vary over the analysis window
+    RiskAdjusted,
+    /// Maximize earnings but cap any single protocol's share
+    Diversified,
+    /// Score by worst observed rate and move half as much per change
+    Conservative,
+}
+
 /// Optimizer configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OptimizerConfig {
     /// Minimum improvement (USD/hour) to trigger reallocation
     pub min_improvement_threshold: f64,
@@ -23,6 +43,25 @@ pub struct OptimizerConfig {
     pub max_allocation_change: f64,
     /// Consider past X hours for analysis
     pub analysis_window_hours: u32,
+    /// How protocols are scored against each other
+    pub strategy: Strategy,
+    /// Standard deviations subtracted from the mean rate (risk-adjusted)
+    pub risk_aversion: f64,
+    /// Largest allocation percent any protocol may reach (diversified)
+    pub max_protocol_share: f64,
+    /// Score boost per unit of marketplace demand above a protocol's baseline
+    /// (0 ignores demand signals)
+    pub demand_weight: f64,
+    /// Score change per unit of yield change against a protocol's baseline,
+    /// e.g. Streamr sponsorship APYs (0 ignores yield changes)
+    pub yield_weight: f64,
+    /// Share of opportunity confidence lost to the relative error of past
+    /// reallocations' predicted improvements, 0-1 (0 ignores canary results)
+    pub prediction_error_weight: f64,
+    /// Share of the measured upstream (0-100) in use above which allocation
+    /// is not moved to bandwidth protocols from protocols that don't use it;
+    /// only applies once network probes have run
+    pub max_bandwidth_percent: f64,
 }
 
 impl Default for OptimizerConfig {
@@ -32,38 +71,173 @@ impl Default for OptimizerConfig {
             min_improvement_percent: 5.0,    // 5%
             max_allocation_change: 20.0,     // 20% per change
             analysis_window_hours: 24,
+            strategy: Strategy::MaximizeEarnings,
+            risk_aversion: 1.0,
+            max_protocol_share: 40.0,
+            demand_weight: 0.5,
+            yield_weight: 0.5,
+            prediction_error_weight: 0.5,
+            max_bandwidth_percent: 90.0,
+        }
+    }
+}
+
+impl OptimizerConfig {
+    /// Validate configuration values
+    pub fn validate(&self) -> OrchestrationResult<()> {
+        if !(self.max_allocation_change > 0.0 && self.max_allocation_change <= 100.0) {
+            return Err(OrchestrationError::ConfigurationError(
+                "max_allocation_change must be in (0, 100]".to_string(),
+            ));
+        }
+        if !(self.max_protocol_share > 0.0 && self.max_protocol_share <= 100.0) {
+            return Err(OrchestrationError::ConfigurationError(
+                "max_protocol_share must be in (0, 100]".to_string(),
+            ));
+        }
+        if [
+            self.risk_aversion,
+            self.min_improvement_threshold,
+            self.demand_weight,
+            self.yield_weight,
+        ]
+        .iter()
+        .any(|v| *v < 0.0)
+        {
+            return Err(OrchestrationError::ConfigurationError(
+                "risk_aversion, min_improvement_threshold, demand_weight, and yield_weight must not be negative"
+                    .to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.prediction_error_weight) {
+            return Err(OrchestrationError::ConfigurationError(
+                "prediction_error_weight must be in [0, 1]".to_string(),
+            ));
+        }
+        if !(self.max_bandwidth_percent > 0.0 && self.max_bandwidth_percent <= 100.0) {
+            return Err(OrchestrationError::ConfigurationError(
+                "max_bandwidth_percent must be in (0, 100]".to_string(),
+            ));
         }
+        Ok(())
     }
 }
 
+// ============================================================================
+// DECISION EXPLANATIONS
+// ============================================================================
+
+/// How the optimizer scored one protocol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolScore {
+    pub protocol: String,
+    pub earnings_per_hour: f64,
+    pub allocation_percent: f64,
+    /// Strategy score including market signals
+    pub score: f64,
+    /// Multiplier from reported demand and yield indices
+    pub signal_factor: f64,
+}
+
+/// A move of allocation from one protocol to a higher-scoring one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateEvaluation {
+    pub from_protocol: String,
+    pub to_protocol: String,
+    pub from_score: f64,
+    pub to_score: f64,
+    /// Set when the move qualifies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opportunity: Option<OptimizationOpportunity>,
+    /// Why the move does not qualify
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejection: Option<String>,
+}
+
+/// Inputs, candidates and plan behind one optimizer decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionExplanation {
+    pub strategy: Strategy,
+    pub total_earnings_per_hour: f64,
+    pub scores: Vec<ProtocolScore>,
+    pub candidates: Vec<CandidateEvaluation>,
+    pub plan: AllocationPlan,
+}
+
 // ============================================================================
 // OPTIMIZER IMPLEMENTATION
 // ============================================================================
 
+/// Most recent metrics kept for history-based scoring
+const MAX_HISTORY: usize = 1000;
+
 /// Earnings Optimizer
 ///
 /// Analyzes protocol earnings and identifies optimization opportunities.
+///
+/// The optimizer is shared as a plain `Arc`: configuration and history sit
+/// behind short-lived read/write locks and are published as immutable
+/// `Arc`s, so analysis runs on an [`OptimizerSnapshot`] without holding any
+/// lock and concurrent callers never wait on each other.
 pub struct EarningsOptimizer {
-    config: OptimizerConfig,
-    metrics_history: Vec<AggregatedMetrics>,
+    config: RwLock<Arc<OptimizerConfig>>,
+    metrics_history: MetricsWindow,
+    prediction_errors: RwLock<Arc<HashMap<String, f64>>>,
+    risk_scores: RwLock<Arc<HashMap<String, f64>>>,
 }
 
 impl EarningsOptimizer {
     /// Create a new optimizer
     pub fn new(config: OptimizerConfig) -> Self {
         Self {
-            config,
-            metrics_history: Vec::new(),
+            config: RwLock::new(Arc::new(config)),
+            metrics_history: MetricsWindow::new(MAX_HISTORY),
+            prediction_errors: RwLock::new(Arc::new(HashMap::new())),
+            risk_scores: RwLock::new(Arc::new(HashMap::new())),
         }
     }
 
+    /// Current configuration
+    pub fn config(&self) -> Arc<OptimizerConfig> {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the configuration (e.g. to switch strategy at runtime)
+    pub fn set_config(&self, config: OptimizerConfig) -> OrchestrationResult<()> {
+        config.validate()?;
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
+        Ok(())
+    }
+
     /// Update with new metrics
-    pub fn update_metrics(&mut self, metrics: AggregatedMetrics) {
+    ///
+    /// Snapshots taken earlier keep the history they were taken with.
+    pub fn update_metrics(&self, metrics: impl Into<Arc<AggregatedMetrics>>) {
         self.metrics_history.push(metrics);
+    }
+
+    /// Replace the relative prediction error (0-1) of past reallocations,
+    /// by protocol, that discounts opportunity confidence
+    pub fn set_prediction_errors(&self, errors: HashMap<String, f64>) {
+        *self.prediction_errors.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(errors);
+    }
+
+    /// Replace the earnings risk score (0-1) reported on opportunities, by protocol
+    pub fn set_risk_scores(&self, scores: HashMap<String, f64>) {
+        *self.risk_scores.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(scores);
+    }
 
-        // Keep only recent history
-        if self.metrics_history.len() > 1000 {
-            self.metrics_history.remove(0);
+    /// Immutable view of the current configuration and history
+    pub fn snapshot(&self) -> OptimizerSnapshot {
+        OptimizerSnapshot {
+            config: self.config(),
+            metrics_history: self.metrics_history.snapshot(),
+            prediction_errors: self
+                .prediction_errors
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+            risk_scores: self.risk_scores.read().unwrap_or_else(|e| e.into_inner()).clone(),
         }
     }
 
@@ -72,58 +246,175 @@ impl EarningsOptimizer {
         &self,
         current_metrics: &AggregatedMetrics,
     ) -> OrchestrationResult<Vec<OptimizationOpportunity>> {
-        let mut opportunities = Vec::new();
+        self.snapshot().analyze_opportunities(current_metrics)
+    }
+
+    /// Calculate optimal allocation
+    pub fn calculate_optimal_allocation(
+        &self,
+        current_metrics: &AggregatedMetrics,
+    ) -> OrchestrationResult<AllocationPlan> {
+        self.snapshot().calculate_optimal_allocation(current_metrics)
+    }
+
+    /// Estimate earnings improvement for a given allocation
+    pub fn estimate_earnings_improvement(
+        &self,
+        new_allocation: &HashMap<String, f64>,
+        earnings_rates: &HashMap<String, f64>,
+    ) -> f64 {
+        self.snapshot()
+            .estimate_earnings_improvement(new_allocation, earnings_rates)
+    }
+
+    /// Determine if reallocation should be executed
+    pub fn should_reallocate(
+        &self,
+        opportunities: &[OptimizationOpportunity],
+        current_plan: Option<&AllocationPlan>,
+    ) -> bool {
+        self.snapshot().should_reallocate(opportunities, current_plan)
+    }
+}
+
+// ============================================================================
+// ANALYSIS ENGINE
+// ============================================================================
+
+/// Immutable analysis engine: a configuration plus the metrics history at
+/// the moment it was taken
+///
+/// Cheap to clone and never locked; analyze as often as needed.
+#[derive(Debug, Clone)]
+pub struct OptimizerSnapshot {
+    config: Arc<OptimizerConfig>,
+    metrics_history: WindowSnapshot,
+    prediction_errors: Arc<HashMap<String, f64>>,
+    risk_scores: Arc<HashMap<String, f64>>,
+}
+
+impl OptimizerSnapshot {
+    /// Configuration the snapshot analyzes with
+    pub fn config(&self) -> &OptimizerConfig {
+        &self.config
+    }
+
+    /// Number of history samples in the snapshot
+    pub fn history_len(&self) -> usize {
+        self.metrics_history.len()
+    }
+
+    /// Analyze optimization opportunities
+    pub fn analyze_opportunities(
+        &self,
+        current_metrics: &AggregatedMetrics,
+    ) -> OrchestrationResult<Vec<OptimizationOpportunity>> {
+        Ok(Self::opportunities(&self.evaluate_candidates(current_metrics)))
+    }
+
+    /// Qualifying opportunities among `candidates`, largest improvement first
+    pub fn opportunities(candidates: &[CandidateEvaluation]) -> Vec<OptimizationOpportunity> {
+        let mut opportunities: Vec<_> = candidates.iter().filter_map(|c| c.opportunity.clone()).collect();
+        opportunities.sort_by(|a, b| b.earnings_improvement.total_cmp(&a.earnings_improvement));
+        opportunities
+    }
+
+    /// Weigh moving allocation between every pair of connected protocols
+    ///
+    /// Only pairs whose target scores higher than their source are
+    /// candidates; each carries either an opportunity or why it has none.
+    pub fn evaluate_candidates(&self, current_metrics: &AggregatedMetrics) -> Vec<CandidateEvaluation> {
+        let mut candidates = Vec::new();
 
         let earnings = &current_metrics.earnings_by_protocol;
         let allocation = &current_metrics.allocation_by_protocol;
 
         // Get list of connected protocols
-        let connected_protocols: Vec<_> = current_metrics
+        let mut connected_protocols: Vec<_> = current_metrics
             .connection_status
             .iter()
             .filter(|(_, &connected)| connected)
             .map(|(name, _)| name.clone())
             .collect();
+        connected_protocols.sort();
 
         if connected_protocols.len() < 2 {
-            return Ok(opportunities);
+            return candidates;
         }
 
         // Analyze pairwise reallocations
-        for i in 0..connected_protocols.len() {
-            for j in 0..connected_protocols.len() {
-                if i == j {
+        for from_protocol in &connected_protocols {
+            for to_protocol in &connected_protocols {
+                if from_protocol == to_protocol {
                     continue;
                 }
 
-                let from_protocol = &connected_protocols[i];
-                let to_protocol = &connected_protocols[j];
-
                 let from_rate = earnings.get(from_protocol).copied().unwrap_or(0.0);
                 let to_rate = earnings.get(to_protocol).copied().unwrap_or(0.0);
                 let from_allocation = allocation.get(from_protocol).copied().unwrap_or(0.0);
                 let to_allocation = allocation.get(to_protocol).copied().unwrap_or(0.0);
 
-                // Skip if rates are similar or insufficient allocation
-                if (to_rate - from_rate).abs() < 0.01 || from_allocation < 1.0 {
+                let from_score = self.score(from_protocol, from_rate)
+                    * self.signal_factor(current_metrics, from_protocol);
+                let to_score = self.score(to_protocol, to_rate)
+                    * self.signal_factor(current_metrics, to_protocol);
+
+                // The reverse pair covers moves toward the lower score
+                if to_score <= from_score {
                     continue;
                 }
+                let mut candidate = CandidateEvaluation {
+                    from_protocol: from_protocol.clone(),
+                    to_protocol: to_protocol.clone(),
+                    from_score,
+                    to_score,
+                    opportunity: None,
+                    rejection: None,
+                };
 
-                // Calculate potential improvement
-                if to_rate > from_rate {
-                    let reallocation_amount =
-                        (from_allocation * 0.1).min(self.config.max_allocation_change);
-                    let rate_difference = to_rate - from_rate;
-                    let improvement = rate_difference * (reallocation_amount / 100.0);
+                // Skip if scores are similar or insufficient allocation
+                if to_score - from_score < 0.01 {
+                    candidate.rejection = Some("Scores are within 0.01 of each other".to_string());
+                } else if from_allocation < 1.0 {
+                    candidate.rejection = Some(format!("{} has under 1% allocated", from_protocol));
+                } else if !Self::can_shift(current_metrics, from_protocol, to_protocol) {
+                    // Allocation only moves within the same resource dimension
+                    candidate.rejection = Some("Protocols do not compete for the same resource".to_string());
+                } else if self.upstream_saturated(current_metrics, from_protocol, to_protocol) {
+                    candidate.rejection = Some(format!(
+                        "Measured upstream bandwidth is {:.0}% used, above max_bandwidth_percent {}%",
+                        current_metrics.resource_utilization.bandwidth_percent, self.config.max_bandwidth_percent
+                    ));
+                } else {
+                    // Calculate potential improvement
+                    let headroom = self.shift_headroom(
+                        current_metrics,
+                        from_protocol,
+                        to_protocol,
+                        from_allocation,
+                        to_allocation,
+                    );
+                    let reallocation_amount = self.move_amount(from_allocation).min(headroom);
+                    let improvement = (to_score - from_score) * (reallocation_amount / 100.0);
 
                     // Check if improvement meets threshold
-                    if improvement > self.config.min_improvement_threshold
-                        && (improvement / from_rate * 100.0) > self.config.min_improvement_percent
-                    {
+                    if reallocation_amount <= 0.0 {
+                        candidate.rejection = Some("Allocation floors or caps leave no room to move".to_string());
+                    } else if improvement <= self.config.min_improvement_threshold {
+                        candidate.rejection = Some(format!(
+                            "Improvement ${:.4}/hour is not above min_improvement_threshold ${:.4}/hour",
+                            improvement, self.config.min_improvement_threshold
+                        ));
+                    } else if improvement / from_rate * 100.0 <= self.config.min_improvement_percent {
+                        candidate.rejection = Some(format!(
+                            "Improvement is not above min_improvement_percent {}% of {}'s earnings",
+                            self.config.min_improvement_percent, from_protocol
+                        ));
+                    } else {
                         let confidence =
                             self.calculate_opportunity_confidence(from_protocol, to_protocol);
 
-                        opportunities.push(OptimizationOpportunity {
+                        candidate.opportunity = Some(OptimizationOpportunity {
                             from_protocol: from_protocol.clone(),
                             to_protocol: to_protocol.clone(),
                             current_rate: from_rate,
@@ -131,20 +422,39 @@ impl EarningsOptimizer {
                             earnings_improvement: improvement,
                             confidence,
                             complexity: 0.3, // Simple reallocation
+                            risk_score: self.risk_scores.get(to_protocol).copied().unwrap_or(0.0),
                         });
                     }
                 }
+                candidates.push(candidate);
             }
         }
 
-        // Sort by earnings improvement
-        opportunities.sort_by(|a, b| {
-            b.earnings_improvement
-                .partial_cmp(&a.earnings_improvement)
-                .unwrap()
-        });
+        candidates
+    }
 
-        Ok(opportunities)
+    /// Rate, score and signal factor of every protocol with earnings, by name
+    pub fn protocol_scores(&self, current_metrics: &AggregatedMetrics) -> Vec<ProtocolScore> {
+        let mut scores: Vec<_> = current_metrics
+            .earnings_by_protocol
+            .iter()
+            .map(|(protocol, rate)| {
+                let signal_factor = self.signal_factor(current_metrics, protocol);
+                ProtocolScore {
+                    protocol: protocol.clone(),
+                    earnings_per_hour: *rate,
+                    allocation_percent: current_metrics
+                        .allocation_by_protocol
+                        .get(protocol)
+                        .copied()
+                        .unwrap_or(0.0),
+                    score: self.score(protocol, *rate) * signal_factor,
+                    signal_factor,
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| a.protocol.cmp(&b.protocol));
+        scores
     }
 
     /// Calculate optimal allocation
@@ -153,41 +463,65 @@ impl EarningsOptimizer {
         current_metrics: &AggregatedMetrics,
     ) -> OrchestrationResult<AllocationPlan> {
         let earnings = &current_metrics.earnings_by_protocol;
-        let current_allocation = &current_metrics.allocation_by_protocol;
 
-        // Greedy allocation: allocate more to higher earning protocols
-        let mut optimal = current_allocation.clone();
-        let mut total_allocation = current_allocation.values().sum::<f64>();
-
-        // Normalize to 100%
-        if (total_allocation - 100.0).abs() > 0.1 {
-            for value in optimal.values_mut() {
-                *value = (*value / total_allocation) * 100.0;
-            }
-        }
+        // Greedy allocation: allocate more to higher earning protocols,
+        // starting from the current allocation normalized to 100%
+        let optimal = Self::fit_to_bounds(current_metrics);
 
         // Calculate earnings rate per allocated unit for each protocol
-        let mut protocol_efficiency: Vec<_> = earnings
+        let protocol_efficiency: Vec<_> = earnings
             .iter()
             .map(|(name, rate)| {
                 let allocation = optimal.get(name).copied().unwrap_or(1.0).max(0.1);
-                (name.clone(), rate / allocation)
+                let score = self.score(name, *rate) * self.signal_factor(current_metrics, name);
+                (name.clone(), score / allocation)
             })
             .collect();
 
-        // Sort by efficiency
-        protocol_efficiency.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        // Group protocols by the resource they compete for; protocols without
+        // a profile share a single fungible pool.
+        let mut groups: HashMap<Option<ResourceKind>, Vec<(String, f64)>> = HashMap::new();
+        for (name, efficiency) in protocol_efficiency {
+            let profile = current_metrics.resource_profile(&name);
+            // Protocols whose allocation is unknown are left where they are
+            if profile.is_some_and(|p| p.observe_only) || !optimal.contains_key(&name) {
+                continue;
+            }
+            groups
+                .entry(profile.map(|p| p.primary))
+                .or_default()
+                .push((name, efficiency));
+        }
+
+        // Reallocate to most efficient protocols within each resource group
+        let mut new_allocation = optimal.clone();
+
+        for group in groups.values_mut() {
+            if group.len() < 2 {
+                continue;
+            }
 
-        // Reallocate to most efficient protocols
-        let mut new_allocation = current_allocation.clone();
+            // Sort by efficiency
+            group.sort_by(|a, b| b.1.total_cmp(&a.1));
 
-        if protocol_efficiency.len() >= 2 {
             // Move allocation from lowest to highest efficiency
-            let bottom_protocol = &protocol_efficiency[protocol_efficiency.len() - 1].0;
-            let top_protocol = &protocol_efficiency[0].0;
+            let bottom_protocol = &group[group.len() - 1].0;
+            let top_protocol = &group[0].0;
+            if !Self::can_shift(current_metrics, bottom_protocol, top_protocol) {
+                continue;
+            }
+
+            let bottom_allocation = new_allocation.get(bottom_protocol).copied().unwrap_or(0.0);
+            let top_allocation = new_allocation.get(top_protocol).copied().unwrap_or(0.0);
+            let headroom = self.shift_headroom(
+                current_metrics,
+                bottom_protocol,
+                top_protocol,
+                bottom_allocation,
+                top_allocation,
+            );
 
-            let move_amount = (new_allocation.get(bottom_protocol).copied().unwrap_or(0.0) * 0.1)
-                .min(self.config.max_allocation_change);
+            let move_amount = self.move_amount(bottom_allocation).min(headroom);
 
             if move_amount > 0.1 {
                 *new_allocation.entry(bottom_protocol.clone()).or_insert(0.0) -= move_amount;
@@ -196,8 +530,7 @@ impl EarningsOptimizer {
         }
 
         // Calculate improvement
-        let current_earnings = earnings.values().sum::<f64>();
-        let estimated_improvement = self.estimate_earnings_improvement(&new_allocation, earnings);
+        let estimated_improvement = self.estimate_earnings_improvement(&new_allocation, earnings).max(0.0);
         let net_benefit = estimated_improvement;
         let cost = estimated_improvement * 0.05; // Assume 5% cost
         let roi_percent = if cost > 0.001 {
@@ -206,7 +539,7 @@ impl EarningsOptimizer {
             100.0
         };
 
-        Ok(AllocationPlan {
+        let plan = AllocationPlan {
             allocation: new_allocation,
             estimated_improvement,
             estimated_cost: cost,
@@ -214,7 +547,10 @@ impl EarningsOptimizer {
             roi_percent,
             confidence: 0.85,
             created_at: chrono::Utc::now(),
-        })
+        };
+        debug_assert_eq!(plan_invariant_violation(current_metrics, &plan), None);
+
+        Ok(plan)
     }
 
     /// Estimate earnings improvement for a given allocation
@@ -239,34 +575,261 @@ impl EarningsOptimizer {
         opportunities: &[OptimizationOpportunity],
         current_plan: Option<&AllocationPlan>,
     ) -> bool {
-        if opportunities.is_empty() {
-            return false;
-        }
+        self.reallocation_blocker(opportunities, current_plan).is_none()
+    }
 
-        let best_opportunity = &opportunities[0];
+    /// Why reallocation should not be executed, if it should not
+    pub fn reallocation_blocker(
+        &self,
+        opportunities: &[OptimizationOpportunity],
+        current_plan: Option<&AllocationPlan>,
+    ) -> Option<String> {
+        let Some(best_opportunity) = opportunities.first() else {
+            return Some("No qualifying opportunities".to_string());
+        };
 
         // Check if improvement meets threshold
         if best_opportunity.earnings_improvement < self.config.min_improvement_threshold {
-            return false;
+            return Some(format!(
+                "Best improvement ${:.4}/hour is below min_improvement_threshold ${:.4}/hour",
+                best_opportunity.earnings_improvement, self.config.min_improvement_threshold
+            ));
         }
 
         // Check confidence
         if best_opportunity.confidence < 0.7 {
-            return false;
+            return Some(format!(
+                "Best opportunity confidence {:.2} is below 0.70",
+                best_opportunity.confidence
+            ));
         }
 
         // Check ROI if we have a plan
         if let Some(plan) = current_plan {
             if plan.net_benefit < self.config.min_improvement_threshold {
-                return false;
+                return Some(format!(
+                    "Plan net benefit ${:.4}/hour is below min_improvement_threshold ${:.4}/hour",
+                    plan.net_benefit, self.config.min_improvement_threshold
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// Explain a decision on `plan`, weighed against `current_metrics`
+    pub fn explain(
+        &self,
+        current_metrics: &AggregatedMetrics,
+        candidates: Vec<CandidateEvaluation>,
+        plan: AllocationPlan,
+    ) -> DecisionExplanation {
+        DecisionExplanation {
+            strategy: self.config.strategy,
+            total_earnings_per_hour: current_metrics.total_earnings_per_hour,
+            scores: self.protocol_scores(current_metrics),
+            candidates,
+            plan,
+        }
+    }
+
+    /// Current allocation scaled to total 100% within each protocol's
+    /// profile bounds
+    ///
+    /// Observe-only protocols keep their share. The rest are scaled to fill
+    /// what remains, clamped into their bounds, and any shortfall or excess
+    /// is spread over them in proportion to their room to move. Bounds that
+    /// cannot reach 100% leave the total off, which plan validation rejects.
+    fn fit_to_bounds(metrics: &AggregatedMetrics) -> HashMap<String, f64> {
+        let mut allocation = metrics.allocation_by_protocol.clone();
+        let adjustable: Vec<(String, (f64, f64))> = allocation
+            .keys()
+            .filter_map(|name| Some((name.clone(), allocation_bounds(metrics, name)?)))
+            .collect();
+
+        let fixed: f64 = allocation
+            .iter()
+            .filter(|(name, _)| allocation_bounds(metrics, name).is_none())
+            .map(|(_, value)| value)
+            .sum();
+        let total: f64 = adjustable.iter().map(|(name, _)| allocation[name]).sum();
+        if total > 0.0 && (fixed + total - 100.0).abs() > 0.1 {
+            let scale = (100.0 - fixed).max(0.0) / total;
+            for (name, _) in &adjustable {
+                *allocation.get_mut(name).unwrap() *= scale;
+            }
+        }
+
+        for (name, (min, max)) in &adjustable {
+            let value = allocation.get_mut(name).unwrap();
+            *value = value.clamp(*min, *max);
+        }
+
+        let shortfall = 100.0 - allocation.values().sum::<f64>();
+        let room = |name: &String, (min, max): (f64, f64)| {
+            if shortfall > 0.0 {
+                max - allocation[name]
+            } else {
+                allocation[name] - min
+            }
+        };
+        let rooms: Vec<f64> = adjustable.iter().map(|(name, bounds)| room(name, *bounds)).collect();
+        let total_room: f64 = rooms.iter().sum();
+        if shortfall.abs() > 1e-9 && total_room > 0.0 {
+            let share = (shortfall.abs() / total_room).min(1.0);
+            for ((name, _), room) in adjustable.iter().zip(rooms) {
+                *allocation.get_mut(name).unwrap() += shortfall.signum() * room * share;
+            }
+        }
+
+        allocation
+    }
+
+    /// Whether allocation can move between two protocols
+    ///
+    /// Protocols without a resource profile are treated as fungible. GPU-backed
+    /// share is only moved to protocols that can use GPUs, and observe-only
+    /// protocols are never moved.
+    fn can_shift(metrics: &AggregatedMetrics, from_protocol: &str, to_protocol: &str) -> bool {
+        match (
+            metrics.resource_profile(from_protocol),
+            metrics.resource_profile(to_protocol),
+        ) {
+            (Some(from), Some(to)) => from.competes_with(to),
+            (Some(profile), None) | (None, Some(profile)) => !profile.observe_only,
+            (None, None) => true,
+        }
+    }
+
+    /// Score a protocol's earnings rate under the configured strategy
+    fn score(&self, protocol: &str, rate: f64) -> f64 {
+        match self.config.strategy {
+            Strategy::MaximizeEarnings | Strategy::Diversified => rate,
+            Strategy::RiskAdjusted => {
+                let history = self.rate_history(protocol);
+                if history.len() < 2 {
+                    return rate;
+                }
+                let mean = history.iter().sum::<f64>() / history.len() as f64;
+                let variance = history.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+                    / history.len() as f64;
+                mean - self.config.risk_aversion * variance.sqrt()
             }
+            Strategy::Conservative => self
+                .rate_history(protocol)
+                .into_iter()
+                .fold(rate, f64::min),
+        }
+    }
+
+    /// Score multiplier from the market signals a protocol reports
+    ///
+    /// Only demand above the protocol's baseline raises the score, so a quiet
+    /// market doesn't count against protocols that report no demand at all.
+    /// Yield changes count both ways, so allocation follows yields as they
+    /// move.
+    fn signal_factor(&self, metrics: &AggregatedMetrics, protocol: &str) -> f64 {
+        let index = |metric: &str| {
+            metrics
+                .protocol_details
+                .get(protocol)
+                .and_then(|details| details.get(metric))
+                .copied()
+                .unwrap_or(1.0)
+        };
+        let demand = 1.0 + self.config.demand_weight * (index(DEMAND_INDEX_METRIC) - 1.0).max(0.0);
+        let yields = (1.0 + self.config.yield_weight * (index(YIELD_INDEX_METRIC) - 1.0)).max(0.0);
+        demand * yields
+    }
+
+    /// Earnings rates observed for a protocol within the analysis window
+    fn rate_history(&self, protocol: &str) -> Vec<f64> {
+        let cutoff =
+            chrono::Utc::now() - chrono::Duration::hours(self.config.analysis_window_hours as i64);
+        self.metrics_history
+            .iter()
+            .filter(|m| m.timestamp >= cutoff)
+            .filter_map(|m| m.earnings_by_protocol.get(protocol).copied())
+            .collect()
+    }
+
+    /// Allocation percent to move away from a protocol in one change
+    fn move_amount(&self, from_allocation: f64) -> f64 {
+        let amount = (from_allocation * 0.1).min(self.config.max_allocation_change);
+        match self.config.strategy {
+            Strategy::Conservative => amount / 2.0,
+            _ => amount,
+        }
+    }
+
+    /// Maximum percent that can move without breaching min/max bounds
+    fn shift_headroom(
+        &self,
+        metrics: &AggregatedMetrics,
+        from_protocol: &str,
+        to_protocol: &str,
+        from_allocation: f64,
+        to_allocation: f64,
+    ) -> f64 {
+        if self.upstream_saturated(metrics, from_protocol, to_protocol) {
+            return 0.0;
         }
+        let from_floor = metrics
+            .resource_profile(from_protocol)
+            .map(|p| p.min_allocation_percent)
+            .unwrap_or(0.0);
+        let to_cap = metrics
+            .resource_profile(to_protocol)
+            .map(|p| p.max_allocation_percent)
+            .unwrap_or(100.0);
+
+        let to_cap = match self.config.strategy {
+            Strategy::Diversified => to_cap.min(self.config.max_protocol_share),
+            _ => to_cap,
+        };
+
+        (from_allocation - from_floor)
+            .min(to_cap - to_allocation)
+            .max(0.0)
+    }
 
-        true
+    /// Whether moving allocation between two protocols would add traffic to
+    /// an upstream the network probes found saturated
+    ///
+    /// Moves between bandwidth protocols only reshuffle the traffic already
+    /// on the uplink, so they are always allowed.
+    fn upstream_saturated(&self, metrics: &AggregatedMetrics, from_protocol: &str, to_protocol: &str) -> bool {
+        let uses_bandwidth = |protocol: &str| {
+            metrics.resource_profile(protocol).is_some_and(|p| p.uses(ResourceKind::Bandwidth))
+        };
+        metrics.upstream_mbps.is_some()
+            && metrics.resource_utilization.bandwidth_percent >= self.config.max_bandwidth_percent
+            && uses_bandwidth(to_protocol)
+            && !uses_bandwidth(from_protocol)
     }
 
     /// Calculate opportunity confidence
     fn calculate_opportunity_confidence(&self, from_protocol: &str, to_protocol: &str) -> f64 {
+        self.stability_confidence(from_protocol, to_protocol) * self.prediction_accuracy(from_protocol, to_protocol)
+    }
+
+    /// Discount for how far realized earnings missed the predictions of past
+    /// reallocations involving either protocol (1 without canary results)
+    fn prediction_accuracy(&self, from_protocol: &str, to_protocol: &str) -> f64 {
+        let errors: Vec<f64> = [from_protocol, to_protocol]
+            .iter()
+            .filter_map(|p| self.prediction_errors.get(*p).copied())
+            .collect();
+        if errors.is_empty() {
+            return 1.0;
+        }
+
+        let error = errors.iter().sum::<f64>() / errors.len() as f64;
+        1.0 - self.config.prediction_error_weight * error.clamp(0.0, 1.0)
+    }
+
+    /// Confidence from how steady both protocols' earnings have been
+    fn stability_confidence(&self, from_protocol: &str, to_protocol: &str) -> f64 {
         // Base confidence on history consistency
         let from_history: Vec<_> = self
             .metrics_history
@@ -303,6 +866,63 @@ impl EarningsOptimizer {
     }
 }
 
+// ============================================================================
+// INVARIANTS
+// ============================================================================
+
+/// Slack allowed when checking plan totals and bounds, in allocation percent
+const INVARIANT_TOLERANCE: f64 = 0.01;
+
+/// Allocation bounds of a protocol the optimizer may move; `None` for
+/// observe-only protocols, and 0-100% for protocols without a profile
+fn allocation_bounds(metrics: &AggregatedMetrics, protocol: &str) -> Option<(f64, f64)> {
+    match metrics.resource_profile(protocol) {
+        Some(profile) if profile.observe_only => None,
+        Some(profile) => Some((
+            profile.min_allocation_percent,
+            profile.max_allocation_percent.max(profile.min_allocation_percent),
+        )),
+        None => Some((0.0, 100.0)),
+    }
+}
+
+/// First optimizer invariant `plan` breaks, if any
+///
+/// Plans total 100% whenever the profile bounds allow it, keep every protocol
+/// the optimizer may move within its `ResourceProfile` bounds, and never
+/// estimate a negative improvement.
+pub fn plan_invariant_violation(metrics: &AggregatedMetrics, plan: &AllocationPlan) -> Option<String> {
+    if plan.estimated_improvement.is_nan() || plan.estimated_improvement < 0.0 {
+        return Some(format!("Estimated improvement {} is negative", plan.estimated_improvement));
+    }
+
+    let (mut fixed, mut lowest, mut highest) = (0.0, 0.0, 0.0);
+    let mut adjustable = 0;
+    for (protocol, percent) in &plan.allocation {
+        if !percent.is_finite() {
+            return Some(format!("{} allocation {} is not finite", protocol, percent));
+        }
+        let Some((min, max)) = allocation_bounds(metrics, protocol) else {
+            fixed += percent;
+            continue;
+        };
+        if *percent < min - INVARIANT_TOLERANCE || *percent > max + INVARIANT_TOLERANCE {
+            return Some(format!("{} allocation {:.2}% is outside {}-{}%", protocol, percent, min, max));
+        }
+        lowest += min;
+        highest += max;
+        adjustable += 1;
+    }
+
+    let total: f64 = plan.allocation.values().sum();
+    let reachable = adjustable > 0 && fixed + lowest <= 100.0 && fixed + highest >= 100.0;
+    if reachable && (total - 100.0).abs() > INVARIANT_TOLERANCE {
+        return Some(format!("Allocations total {:.4}%, not 100%", total));
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -336,22 +956,109 @@ mod tests {
                 storage_percent: 30.0,
             },
             connection_status: status,
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
         }
     }
 
     #[test]
     fn test_optimizer_creation() {
         let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
-        assert_eq!(optimizer.metrics_history.len(), 0);
+        assert_eq!(optimizer.snapshot().history_len(), 0);
     }
 
     #[test]
     fn test_analyze_opportunities() {
         let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
-        let metrics = create_test_metrics();
+        let mut metrics = create_test_metrics();
+        // Moving 3% from golem is worth (12 - 2.5) * 0.03, above the $0.25/hour threshold
+        metrics.earnings_by_protocol.insert("storj".to_string(), 12.0);
 
         let opportunities = optimizer.analyze_opportunities(&metrics).unwrap();
         assert!(!opportunities.is_empty());
+        assert_eq!(opportunities[0].from_protocol, "golem");
+    }
+
+    #[test]
+    fn test_candidates_explain_rejections() {
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default()).snapshot();
+        let mut metrics = create_test_metrics();
+        metrics.earnings_by_protocol.insert("storj".to_string(), 12.0);
+        metrics.allocation_by_protocol.insert("streamr".to_string(), 0.5);
+
+        // One candidate per pair, toward the higher score
+        let candidates = optimizer.evaluate_candidates(&metrics);
+        assert_eq!(candidates.len(), 3);
+        let candidate = |from: &str, to: &str| {
+            candidates
+                .iter()
+                .find(|c| c.from_protocol == from && c.to_protocol == to)
+                .unwrap()
+                .clone()
+        };
+        assert!(candidate("golem", "storj").opportunity.is_some());
+        let starved = candidate("streamr", "storj");
+        assert!(starved.opportunity.is_none());
+        assert!(starved.rejection.unwrap().contains("under 1%"));
+        assert!(candidates.iter().all(|c| c.opportunity.is_some() != c.rejection.is_some()));
+
+        let opportunities = OptimizerSnapshot::opportunities(&candidates);
+        assert_eq!(opportunities[0].to_protocol, "storj");
+        assert!(optimizer.reallocation_blocker(&[], None).unwrap().contains("No qualifying"));
+
+        let plan = optimizer.calculate_optimal_allocation(&metrics).unwrap();
+        let explanation = optimizer.explain(&metrics, candidates, plan);
+        let scored: Vec<_> = explanation.scores.iter().map(|s| (s.protocol.as_str(), s.score)).collect();
+        assert_eq!(scored, [("golem", 2.5), ("storj", 12.0), ("streamr", 3.0)]);
+    }
+
+    #[test]
+    fn test_opportunities_carry_target_risk() {
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut metrics = create_test_metrics();
+        metrics.earnings_by_protocol.insert("storj".to_string(), 12.0);
+        let opportunity = |optimizer: &EarningsOptimizer| {
+            let opportunities = optimizer.analyze_opportunities(&metrics).unwrap();
+            opportunities.into_iter().find(|o| o.from_protocol == "golem").unwrap()
+        };
+        assert_eq!(opportunity(&optimizer).risk_score, 0.0);
+
+        optimizer.set_risk_scores(HashMap::from([("storj".to_string(), 0.4), ("golem".to_string(), 0.1)]));
+        let opportunity = opportunity(&optimizer);
+        assert_eq!((opportunity.to_protocol.as_str(), opportunity.risk_score), ("storj", 0.4));
+    }
+
+    #[test]
+    fn test_prediction_errors_lower_confidence() {
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut metrics = create_test_metrics();
+        metrics.earnings_by_protocol.insert("storj".to_string(), 12.0);
+        let confidence = |optimizer: &EarningsOptimizer| {
+            let opportunities = optimizer.analyze_opportunities(&metrics).unwrap();
+            opportunities.iter().find(|o| o.from_protocol == "golem").unwrap().confidence
+        };
+        let before = confidence(&optimizer);
+
+        // Past moves into storj realized half of what was predicted
+        optimizer.set_prediction_errors(HashMap::from([("storj".to_string(), 0.5)]));
+        assert!((confidence(&optimizer) - before * 0.75).abs() < 1e-9);
+
+        optimizer
+            .set_config(OptimizerConfig {
+                prediction_error_weight: 0.0,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(confidence(&optimizer), before);
+        assert!(OptimizerConfig {
+            prediction_error_weight: 1.5,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
     }
 
     #[test]
@@ -363,6 +1070,168 @@ mod tests {
         assert!(plan.confidence > 0.8);
     }
 
+    #[test]
+    fn test_demand_spike_raises_allocation() {
+        let mut metrics = create_test_metrics();
+        metrics.protocol_details.insert(
+            "golem".to_string(),
+            HashMap::from([(DEMAND_INDEX_METRIC.to_string(), 3.0)]),
+        );
+
+        let plan = strategy_optimizer(Strategy::MaximizeEarnings)
+            .calculate_optimal_allocation(&metrics)
+            .unwrap();
+        assert!(plan.allocation["golem"] > 30.0);
+
+        let ignore_demand = EarningsOptimizer::new(OptimizerConfig {
+            demand_weight: 0.0,
+            ..Default::default()
+        });
+        let plan = ignore_demand.calculate_optimal_allocation(&metrics).unwrap();
+        assert!(plan.allocation["golem"] < 30.0);
+    }
+
+    #[test]
+    fn test_allocation_follows_yield_changes() {
+        let mut metrics = create_test_metrics();
+        metrics.protocol_details.insert(
+            "streamr".to_string(),
+            HashMap::from([(YIELD_INDEX_METRIC.to_string(), 0.5)]),
+        );
+        metrics.protocol_details.insert(
+            "golem".to_string(),
+            HashMap::from([(YIELD_INDEX_METRIC.to_string(), 2.0)]),
+        );
+
+        let plan = strategy_optimizer(Strategy::MaximizeEarnings)
+            .calculate_optimal_allocation(&metrics)
+            .unwrap();
+        assert!(plan.allocation["streamr"] < 30.0);
+        assert!(plan.allocation["golem"] > 30.0);
+    }
+
+    #[test]
+    fn test_no_opportunities_across_resource_dimensions() {
+        use crate::protocols::ResourceProfile;
+
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut metrics = create_test_metrics();
+        metrics.resource_profiles.insert(
+            "streamr".to_string(),
+            ResourceProfile::new(ResourceKind::Bandwidth, 5.0, 60.0),
+        );
+        metrics.resource_profiles.insert(
+            "storj".to_string(),
+            ResourceProfile::new(ResourceKind::Storage, 10.0, 60.0),
+        );
+        metrics.resource_profiles.insert(
+            "golem".to_string(),
+            ResourceProfile::new(ResourceKind::Cpu, 10.0, 60.0),
+        );
+
+        let opportunities = optimizer.analyze_opportunities(&metrics).unwrap();
+        assert!(opportunities.is_empty());
+
+        let plan = optimizer.calculate_optimal_allocation(&metrics).unwrap();
+        assert_eq!(plan.allocation, metrics.allocation_by_protocol);
+    }
+
+    #[test]
+    fn test_saturated_upstream_blocks_moves_to_bandwidth_protocols() {
+        use crate::protocols::ResourceProfile;
+
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut metrics = create_test_metrics();
+        metrics.resource_profiles.insert(
+            "streamr".to_string(),
+            ResourceProfile::new(ResourceKind::Bandwidth, 5.0, 60.0),
+        );
+        metrics.earnings_by_protocol.insert("streamr".to_string(), 20.0);
+        metrics.resource_utilization.bandwidth_percent = 95.0;
+        let golem_to_streamr = |optimizer: &EarningsOptimizer, metrics: &AggregatedMetrics| {
+            optimizer
+                .snapshot()
+                .evaluate_candidates(metrics)
+                .into_iter()
+                .find(|c| c.from_protocol == "golem" && c.to_protocol == "streamr")
+                .unwrap()
+        };
+
+        // Without a probe the configured utilization figure is not trusted
+        assert!(golem_to_streamr(&optimizer, &metrics).opportunity.is_some());
+
+        metrics.upstream_mbps = Some(20.0);
+        let candidate = golem_to_streamr(&optimizer, &metrics);
+        assert!(candidate.opportunity.is_none());
+        assert!(candidate.rejection.unwrap().contains("95% used"));
+
+        metrics.resource_utilization.bandwidth_percent = 50.0;
+        assert!(golem_to_streamr(&optimizer, &metrics).opportunity.is_some());
+    }
+
+    #[test]
+    fn test_no_gpu_reallocation_to_cpu_only_protocol() {
+        use crate::protocols::ResourceProfile;
+
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut metrics = create_test_metrics();
+        metrics.earnings_by_protocol.insert("storj".to_string(), 20.0);
+        // golem offers GPUs, the other CPU protocols do not
+        metrics.resource_profiles.insert(
+            "golem".to_string(),
+            ResourceProfile::new(ResourceKind::Cpu, 10.0, 60.0)
+                .with_secondary(&[ResourceKind::Gpu]),
+        );
+        for name in ["streamr", "storj"] {
+            metrics.resource_profiles.insert(
+                name.to_string(),
+                ResourceProfile::new(ResourceKind::Cpu, 10.0, 60.0),
+            );
+        }
+
+        let opportunities = optimizer.analyze_opportunities(&metrics).unwrap();
+        assert!(!opportunities.is_empty());
+        assert!(opportunities.iter().all(|o| o.from_protocol != "golem"));
+    }
+
+    #[test]
+    fn test_observe_only_protocol_skipped() {
+        use crate::protocols::ResourceProfile;
+
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut metrics = create_test_metrics();
+        metrics.earnings_by_protocol.insert("storj".to_string(), 20.0);
+        metrics
+            .resource_profiles
+            .insert("storj".to_string(), ResourceProfile::observe_only());
+
+        let opportunities = optimizer.analyze_opportunities(&metrics).unwrap();
+        assert!(opportunities
+            .iter()
+            .all(|o| o.from_protocol != "storj" && o.to_protocol != "storj"));
+
+        let plan = optimizer.calculate_optimal_allocation(&metrics).unwrap();
+        assert_eq!(plan.allocation["storj"], 40.0);
+    }
+
+    #[test]
+    fn test_shift_respects_profile_bounds() {
+        use crate::protocols::ResourceProfile;
+
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut metrics = create_test_metrics();
+        // golem sits at its floor, so nothing can move out of it
+        for name in ["streamr", "storj", "golem"] {
+            metrics.resource_profiles.insert(
+                name.to_string(),
+                ResourceProfile::new(ResourceKind::Cpu, 30.0, 100.0),
+            );
+        }
+
+        let plan = optimizer.calculate_optimal_allocation(&metrics).unwrap();
+        assert!(plan.allocation.values().all(|v| *v >= 30.0));
+    }
+
     #[test]
     fn test_estimate_earnings_improvement() {
         let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
@@ -377,4 +1246,211 @@ mod tests {
         let improvement = optimizer.estimate_earnings_improvement(&allocation, &earnings);
         assert!(improvement > 0.0);
     }
+
+    fn strategy_optimizer(strategy: Strategy) -> EarningsOptimizer {
+        EarningsOptimizer::new(OptimizerConfig {
+            strategy,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_risk_adjusted_penalizes_volatile_protocol() {
+        let optimizer = strategy_optimizer(Strategy::RiskAdjusted);
+        for storj_rate in [0.0, 8.0, 0.0, 8.0] {
+            let mut sample = create_test_metrics();
+            sample.earnings_by_protocol.insert("storj".to_string(), storj_rate);
+            optimizer.update_metrics(sample);
+        }
+
+        let plan = optimizer
+            .calculate_optimal_allocation(&create_test_metrics())
+            .unwrap();
+        assert!(plan.allocation["storj"] < 40.0);
+        assert!(plan.allocation["streamr"] > 30.0);
+    }
+
+    #[test]
+    fn test_diversified_caps_protocol_share() {
+        let mut metrics = create_test_metrics();
+        metrics.earnings_by_protocol.insert("storj".to_string(), 20.0);
+
+        let plan = strategy_optimizer(Strategy::MaximizeEarnings)
+            .calculate_optimal_allocation(&metrics)
+            .unwrap();
+        assert_eq!(plan.allocation["storj"], 43.0);
+
+        let plan = strategy_optimizer(Strategy::Diversified)
+            .calculate_optimal_allocation(&metrics)
+            .unwrap();
+        assert!(plan.allocation["storj"] <= 40.0);
+    }
+
+    #[test]
+    fn test_conservative_moves_half_as_much() {
+        let mut metrics = create_test_metrics();
+        metrics.earnings_by_protocol.insert("storj".to_string(), 20.0);
+
+        let plan = strategy_optimizer(Strategy::Conservative)
+            .calculate_optimal_allocation(&metrics)
+            .unwrap();
+        assert_eq!(plan.allocation["storj"], 41.5);
+        assert_eq!(plan.allocation["golem"], 28.5);
+    }
+
+    /// Metrics for two to six protocols, with or without resource profiles
+    ///
+    /// Floors stay low and the first two protocols are never observe-only,
+    /// with caps of at least 50%, so every generated set of bounds can total
+    /// 100%.
+    fn arb_metrics() -> impl proptest::strategy::Strategy<Value = AggregatedMetrics> {
+        use crate::protocols::ResourceProfile;
+        use proptest::prelude::*;
+
+        let kind = prop::sample::select(vec![ResourceKind::Cpu, ResourceKind::Bandwidth, ResourceKind::Storage]);
+        let profile = prop::option::of((kind, 0.0..15.0f64, 50.0..=100.0f64, prop::bool::weighted(0.1)).prop_map(
+            |(kind, min, max, observe_only)| ResourceProfile {
+                observe_only,
+                ..ResourceProfile::new(kind, min, max)
+            },
+        ));
+        let protocol = (0.0..50.0f64, 0.0..100.0f64, profile);
+        prop::collection::vec(protocol, 2..=6).prop_map(|protocols| {
+            let mut metrics = create_test_metrics();
+            metrics.earnings_by_protocol.clear();
+            metrics.allocation_by_protocol.clear();
+            metrics.connection_status.clear();
+            for (i, (rate, allocation, mut profile)) in protocols.into_iter().enumerate() {
+                let name = format!("protocol{}", i);
+                if let Some(profile) = profile.as_mut().filter(|_| i < 2) {
+                    profile.observe_only = false;
+                }
+                // Observe-only protocols keep their share, so keep it small
+                let allocation = match &profile {
+                    Some(profile) if profile.observe_only => allocation / 10.0,
+                    _ => allocation,
+                };
+                metrics.earnings_by_protocol.insert(name.clone(), rate);
+                metrics.allocation_by_protocol.insert(name.clone(), allocation);
+                metrics.connection_status.insert(name.clone(), true);
+                if let Some(profile) = profile {
+                    metrics.resource_profiles.insert(name, profile);
+                }
+            }
+            metrics.total_earnings_per_hour = metrics.earnings_by_protocol.values().sum();
+            metrics
+        })
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_plans_keep_optimizer_invariants(
+            metrics in arb_metrics(),
+            strategy in proptest::sample::select(vec![
+                Strategy::MaximizeEarnings,
+                Strategy::RiskAdjusted,
+                Strategy::Diversified,
+                Strategy::Conservative,
+            ]),
+        ) {
+            let optimizer = strategy_optimizer(strategy);
+            optimizer.update_metrics(metrics.clone());
+            let plan = optimizer.calculate_optimal_allocation(&metrics).unwrap();
+
+            let total: f64 = plan.allocation.values().sum();
+            proptest::prop_assert!((total - 100.0).abs() <= INVARIANT_TOLERANCE, "total {}", total);
+            for (name, percent) in &plan.allocation {
+                if let Some((min, max)) = allocation_bounds(&metrics, name) {
+                    let within = *percent >= min - INVARIANT_TOLERANCE && *percent <= max + INVARIANT_TOLERANCE;
+                    proptest::prop_assert!(within, "{} at {}% outside {}-{}%", name, percent, min, max);
+                }
+            }
+            proptest::prop_assert!(plan.estimated_improvement >= 0.0);
+            proptest::prop_assert!(plan.net_benefit >= 0.0);
+            proptest::prop_assert_eq!(plan_invariant_violation(&metrics, &plan), None);
+        }
+    }
+
+    #[test]
+    fn test_plan_normalized_into_bounds() {
+        use crate::protocols::ResourceProfile;
+
+        let mut metrics = create_test_metrics();
+        // Adapters report shares that total 60%, and golem sits above its cap
+        metrics.allocation_by_protocol.insert("storj".to_string(), 10.0);
+        metrics.allocation_by_protocol.insert("golem".to_string(), 20.0);
+        metrics.resource_profiles.insert(
+            "golem".to_string(),
+            ResourceProfile::new(ResourceKind::Cpu, 0.0, 25.0),
+        );
+
+        let plan = EarningsOptimizer::new(OptimizerConfig::default())
+            .calculate_optimal_allocation(&metrics)
+            .unwrap();
+        assert!((plan.allocation.values().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert!(plan.allocation["golem"] <= 25.0);
+
+        let mut broken = plan.clone();
+        broken.allocation.insert("golem".to_string(), 30.0);
+        assert!(plan_invariant_violation(&metrics, &broken).unwrap().contains("outside"));
+    }
+
+    #[test]
+    fn test_set_config_validates() {
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let invalid = OptimizerConfig {
+            max_protocol_share: 0.0,
+            ..Default::default()
+        };
+        assert!(optimizer.set_config(invalid).is_err());
+
+        let config: OptimizerConfig =
+            toml::from_str("strategy = \"risk_adjusted\"\nrisk_aversion = 2.0").unwrap();
+        optimizer.set_config(config).unwrap();
+        assert_eq!(optimizer.config().strategy, Strategy::RiskAdjusted);
+        assert_eq!(optimizer.config().max_allocation_change, 20.0);
+    }
+
+    #[test]
+    fn test_snapshot_is_isolated_from_later_updates() {
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        optimizer.update_metrics(create_test_metrics());
+        let snapshot = optimizer.snapshot();
+
+        optimizer.update_metrics(create_test_metrics());
+        optimizer
+            .set_config(OptimizerConfig {
+                strategy: Strategy::Conservative,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(snapshot.history_len(), 1);
+        assert_eq!(snapshot.config().strategy, Strategy::MaximizeEarnings);
+        assert_eq!(optimizer.snapshot().history_len(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_analysis_while_updating() {
+        let optimizer = Arc::new(EarningsOptimizer::new(OptimizerConfig::default()));
+        let metrics = create_test_metrics();
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let (optimizer, metrics) = (optimizer.clone(), metrics.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        optimizer.analyze_opportunities(&metrics).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for _ in 0..50 {
+            optimizer.update_metrics(metrics.clone());
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(optimizer.snapshot().history_len(), 50);
+    }
 }
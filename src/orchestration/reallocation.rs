@@ -2,18 +2,130 @@
 ///
 /// Executes allocation changes across protocols.
 /// Manages reallocation history and validates changes.
-
-use super::{AllocationChange, AllocationPlan, OrchestrationError, OrchestrationResult};
+use super::resilience::{with_timeout, ResilienceConfig};
+use super::{
+    protocol_type, AggregatedMetrics, AllocationChange, AllocationPlan, OrchestrationError,
+    OrchestrationResult,
+};
+use crate::shutdown::ShutdownToken;
 use crate::protocols::{AllocationStrategy, ProtocolAdapter};
 use chrono::{DateTime, Duration, Utc};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Node upstream handed to adapters until a network probe has measured it
+const DEFAULT_BANDWIDTH_MBPS: f64 = 200.0;
+
 // ============================================================================
 // REALLOCATION CONFIGURATION
 // ============================================================================
 
+/// Hard allocation limits for one protocol
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtocolConstraint {
+    /// Allocation percent the protocol must never drop below (e.g. to stay
+    /// clear of disqualification)
+    pub min_percent: Option<f64>,
+    /// Allocation percent the protocol must never exceed
+    pub max_percent: Option<f64>,
+    /// Keep the protocol on its current allocation; plans must not change it
+    pub pinned: bool,
+}
+
+/// Cost of moving allocation into or out of one protocol
+///
+/// Protocols such as Storj withhold part of their payouts and vet new
+/// capacity, so shifting allocation costs more than the flat base fee.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwitchingCost {
+    /// One-off USD lost when allocation is taken away (e.g. held amount),
+    /// scaled by the share of the protocol's allocation removed
+    pub penalty_usd: f64,
+    /// Hours added allocation earns nothing while it is vetted or ramps up
+    pub ramp_up_hours: f64,
+    /// Any reduction forfeits the full penalty, however small
+    pub irreversible: bool,
+}
+
+/// Staged rollout settings (`[reallocation.rollout]` section)
+///
+/// When enabled, plans are applied in steps that move no protocol by more
+/// than `step_percent`. Earnings are checked between steps and the whole
+/// rollout is rolled back if they fall too far below the pre-rollout level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RolloutConfig {
+    /// Apply plans in steps instead of all at once
+    pub enabled: bool,
+    /// Largest allocation change for any protocol in one step
+    pub step_percent: f64,
+    /// Seconds to observe earnings after each step
+    pub step_interval_secs: u64,
+    /// Drop below the pre-rollout earnings, in percent, that aborts the rollout
+    pub degradation_tolerance_percent: f64,
+}
+
+impl Default for RolloutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            step_percent: 5.0,
+            step_interval_secs: 900,
+            degradation_tolerance_percent: 10.0,
+        }
+    }
+}
+
+impl RolloutConfig {
+    /// Whether `earnings` fell more than the tolerance below `baseline`
+    pub fn is_degraded(&self, baseline: f64, earnings: f64) -> bool {
+        baseline > 0.0 && earnings < baseline * (1.0 - self.degradation_tolerance_percent / 100.0)
+    }
+}
+
+/// Intermediate allocations from `current` to `target`, ending at `target`
+///
+/// Every protocol moves linearly, so each step keeps the total unchanged and
+/// none moves by more than `step_percent`. Protocols new to the plan ramp up
+/// from zero; protocols left out of it ramp down to zero.
+pub fn rollout_steps(
+    current: &HashMap<String, f64>,
+    target: &HashMap<String, f64>,
+    step_percent: f64,
+) -> Vec<HashMap<String, f64>> {
+    let protocols: HashSet<&String> = current.keys().chain(target.keys()).collect();
+    let moves: Vec<(&String, f64, f64)> = protocols
+        .into_iter()
+        .map(|protocol| {
+            let from = current.get(protocol).copied().unwrap_or(0.0);
+            let to = target.get(protocol).copied().unwrap_or(0.0);
+            (protocol, from, to)
+        })
+        .collect();
+    let largest_move = moves.iter().map(|(_, from, to)| (to - from).abs()).fold(0.0, f64::max);
+    let count = ((largest_move / step_percent).ceil() as usize).max(1);
+
+    (1..count)
+        .map(|step| {
+            moves
+                .iter()
+                .map(|(protocol, from, to)| {
+                    ((*protocol).clone(), from + (to - from) * step as f64 / count as f64)
+                })
+                .collect()
+        })
+        .chain(std::iter::once(target.clone()))
+        .collect()
+}
+
+/// Tolerance when comparing allocation percents
+const ALLOCATION_EPSILON: f64 = 0.01;
+
 /// Reallocation configuration
 #[derive(Debug, Clone)]
 pub struct ReallocationConfig {
@@ -25,6 +137,19 @@ pub struct ReallocationConfig {
     pub auto_rollback: bool,
     /// Require confirmation before execution
     pub require_confirmation: bool,
+    /// How long a proposed plan may wait for confirmation
+    pub confirmation_timeout: Duration,
+    /// Hard floors, caps and pins by protocol type
+    pub constraints: HashMap<String, ProtocolConstraint>,
+    /// Flat USD cost for every protocol whose allocation changes
+    pub base_cost_usd: f64,
+    /// Switching costs by protocol type, on top of the base cost
+    pub switching_costs: HashMap<String, SwitchingCost>,
+    /// Hours over which one-off costs are spread when comparing them with
+    /// hourly earnings improvements
+    pub cost_amortization_hours: f64,
+    /// Staged rollout of plans
+    pub rollout: RolloutConfig,
 }
 
 impl Default for ReallocationConfig {
@@ -34,10 +159,186 @@ impl Default for ReallocationConfig {
             max_per_hour: 4,
             auto_rollback: true,
             require_confirmation: false,
+            confirmation_timeout: Duration::hours(6),
+            constraints: HashMap::new(),
+            base_cost_usd: 0.05,
+            switching_costs: HashMap::new(),
+            cost_amortization_hours: 24.0,
+            rollout: RolloutConfig::default(),
         }
     }
 }
 
+impl ReallocationConfig {
+    /// Validate configuration values
+    pub fn validate(&self) -> OrchestrationResult<()> {
+        if self.max_per_hour == 0 {
+            return Err(OrchestrationError::ConfigurationError(
+                "max_per_hour must be at least 1".to_string(),
+            ));
+        }
+        if self.min_hold_duration < Duration::zero() {
+            return Err(OrchestrationError::ConfigurationError(
+                "min_hold_duration must not be negative".to_string(),
+            ));
+        }
+        if self.confirmation_timeout <= Duration::zero() {
+            return Err(OrchestrationError::ConfigurationError(
+                "confirmation_timeout must be positive".to_string(),
+            ));
+        }
+
+        if self.base_cost_usd < 0.0 || self.cost_amortization_hours <= 0.0 {
+            return Err(OrchestrationError::ConfigurationError(
+                "base_cost_usd must not be negative and cost_amortization_hours must be positive"
+                    .to_string(),
+            ));
+        }
+        if !(self.rollout.step_percent > 0.0 && self.rollout.step_percent <= 100.0)
+            || self.rollout.degradation_tolerance_percent < 0.0
+        {
+            return Err(OrchestrationError::ConfigurationError(
+                "rollout.step_percent must be in (0, 100] and rollout.degradation_tolerance_percent must not be negative"
+                    .to_string(),
+            ));
+        }
+        for (protocol, cost) in &self.switching_costs {
+            if cost.penalty_usd < 0.0 || cost.ramp_up_hours < 0.0 {
+                return Err(OrchestrationError::ConfigurationError(format!(
+                    "switching costs for {} must not be negative",
+                    protocol
+                )));
+            }
+        }
+
+        let mut total_floor = 0.0;
+        for (protocol, constraint) in &self.constraints {
+            let min = constraint.min_percent.unwrap_or(0.0);
+            let max = constraint.max_percent.unwrap_or(100.0);
+            if !(0.0..=100.0).contains(&min) || !(0.0..=100.0).contains(&max) || min > max {
+                return Err(OrchestrationError::ConfigurationError(format!(
+                    "constraints for {} must satisfy 0 <= min_percent <= max_percent <= 100",
+                    protocol
+                )));
+            }
+            total_floor += min;
+        }
+        if total_floor > 100.0 + ALLOCATION_EPSILON {
+            return Err(OrchestrationError::ConfigurationError(format!(
+                "constraint floors add up to {}%, more than 100%",
+                total_floor
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check a target allocation against the constraints
+    ///
+    /// `current` holds the present allocation of pinned protocols, which the
+    /// target must leave unchanged. Each instance is held to the constraint
+    /// of its type in `protocol_types`.
+    pub fn check_allocation(
+        &self,
+        allocation: &HashMap<String, f64>,
+        current: &HashMap<String, f64>,
+        protocol_types: &HashMap<String, String>,
+    ) -> OrchestrationResult<()> {
+        for (protocol, &target) in allocation {
+            let Some(constraint) = self.constraints.get(protocol_type(protocol_types, protocol)) else {
+                continue;
+            };
+            if let Some(min) = constraint.min_percent.filter(|min| target < min - ALLOCATION_EPSILON) {
+                return Err(OrchestrationError::ReallocationError(format!(
+                    "{} would drop to {}%, below its floor of {}%",
+                    protocol, target, min
+                )));
+            }
+            if let Some(max) = constraint.max_percent.filter(|max| target > max + ALLOCATION_EPSILON) {
+                return Err(OrchestrationError::ReallocationError(format!(
+                    "{} would rise to {}%, above its cap of {}%",
+                    protocol, target, max
+                )));
+            }
+            if constraint.pinned {
+                let pinned_at = current.get(protocol).copied();
+                if pinned_at.is_none_or(|at| (target - at).abs() > ALLOCATION_EPSILON) {
+                    return Err(OrchestrationError::ReallocationError(format!(
+                        "{} is pinned and cannot be reallocated",
+                        protocol
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// One-off USD cost of moving from the allocation in `metrics` to `target`
+    ///
+    /// Every changed protocol costs `base_cost_usd`. Reductions add the
+    /// protocol's penalty (in full if irreversible); increases add the
+    /// earnings the new share forgoes while ramping up.
+    pub fn switching_cost(&self, metrics: &AggregatedMetrics, target: &HashMap<String, f64>) -> f64 {
+        let mut total = 0.0;
+        for (protocol, &new_allocation) in target {
+            let old_allocation = metrics.allocation_by_protocol.get(protocol).copied().unwrap_or(0.0);
+            let delta = new_allocation - old_allocation;
+            if delta.abs() <= ALLOCATION_EPSILON {
+                continue;
+            }
+            total += self.base_cost_usd;
+
+            let Some(cost) = self.switching_costs.get(metrics.protocol_type(protocol)) else {
+                continue;
+            };
+            if delta < 0.0 {
+                let share_removed = if cost.irreversible || old_allocation <= 0.0 {
+                    1.0
+                } else {
+                    (-delta / old_allocation).min(1.0)
+                };
+                total += cost.penalty_usd * share_removed;
+            } else if old_allocation > 0.0 {
+                let rate = metrics.earnings_by_protocol.get(protocol).copied().unwrap_or(0.0);
+                total += rate / old_allocation * delta * cost.ramp_up_hours;
+            }
+        }
+        total
+    }
+
+    /// Narrow the resource profiles in `metrics` to the constraints so the
+    /// optimizer only proposes plans that pass [`Self::check_allocation`]
+    ///
+    /// Floors and caps tighten the profile bounds; pinned protocols become
+    /// observe-only.
+    pub fn constrain_metrics(&self, metrics: &mut AggregatedMetrics) {
+        for (protocol, constraint) in &self.constraints {
+            let Some(profile) = metrics.resource_profiles.get_mut(protocol) else {
+                continue;
+            };
+            if let Some(min) = constraint.min_percent {
+                profile.min_allocation_percent = profile.min_allocation_percent.max(min);
+            }
+            if let Some(max) = constraint.max_percent {
+                profile.max_allocation_percent = profile.max_allocation_percent.min(max);
+            }
+            profile.observe_only |= constraint.pinned;
+        }
+    }
+
+    /// Shared `metrics` narrowed by [`Self::constrain_metrics`]
+    ///
+    /// Copies the metrics only when a constraint names a profiled protocol;
+    /// otherwise the same snapshot is returned.
+    pub fn constrained_metrics(&self, metrics: &Arc<AggregatedMetrics>) -> Arc<AggregatedMetrics> {
+        if !self.constraints.keys().any(|p| metrics.resource_profiles.contains_key(p)) {
+            return metrics.clone();
+        }
+        let mut constrained = (**metrics).clone();
+        self.constrain_metrics(&mut constrained);
+        Arc::new(constrained)
+    }
+}
+
 // ============================================================================
 // REALLOCATION ENGINE
 // ============================================================================
@@ -46,41 +347,158 @@ impl Default for ReallocationConfig {
 ///
 /// Manages allocation changes across all protocols.
 pub struct ReallocationEngine {
-    config: ReallocationConfig,
+    config: std::sync::RwLock<ReallocationConfig>,
     history: Arc<RwLock<Vec<AllocationChange>>>,
     last_reallocation: Arc<RwLock<Option<DateTime<Utc>>>>,
     previous_allocation: Arc<RwLock<HashMap<String, f64>>>,
+    /// Set while a staged rollout is between its first and last step
+    rollout_active: AtomicBool,
+    shutdown: ShutdownToken,
+    /// Timeouts bounding each adapter call
+    resilience: ResilienceConfig,
+    /// Upstream bandwidth measured by the latest network probe (Mbps)
+    upstream_mbps: std::sync::RwLock<Option<f64>>,
+    /// Protocol type of each instance, for looking up its constraint
+    protocol_types: HashMap<String, String>,
 }
 
 impl ReallocationEngine {
     /// Create a new reallocation engine
     pub fn new(config: ReallocationConfig) -> Self {
         Self {
-            config,
+            config: std::sync::RwLock::new(config),
             history: Arc::new(RwLock::new(Vec::new())),
             last_reallocation: Arc::new(RwLock::new(None)),
             previous_allocation: Arc::new(RwLock::new(HashMap::new())),
+            rollout_active: AtomicBool::new(false),
+            shutdown: ShutdownToken::never(),
+            resilience: ResilienceConfig::default(),
+            upstream_mbps: std::sync::RwLock::new(None),
+            protocol_types: HashMap::new(),
         }
     }
 
+    /// Bound adapter calls by the timeouts of `resilience`, so a hung
+    /// adapter fails its step instead of stalling the reallocation
+    pub fn with_resilience(mut self, resilience: ResilienceConfig) -> Self {
+        self.resilience = resilience;
+        self
+    }
+
+    /// Hold each protocol instance to the constraints of its type in
+    /// `protocol_types` rather than to those named after the instance
+    pub fn with_protocol_types(mut self, protocol_types: HashMap<String, String>) -> Self {
+        self.protocol_types = protocol_types;
+        self
+    }
+
+    /// Refuse new reallocations, and roll back partially applied ones, once
+    /// `shutdown` is triggered
+    pub fn with_shutdown(mut self, shutdown: ShutdownToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Snapshot of the current reallocation configuration
+    pub fn config(&self) -> ReallocationConfig {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the configuration at runtime
+    ///
+    /// Takes effect for the next reallocation; in-flight executions keep the
+    /// values they started with.
+    pub fn set_config(&self, config: ReallocationConfig) -> OrchestrationResult<()> {
+        config.validate()?;
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+        Ok(())
+    }
+
+    /// Hand `upstream_mbps` to adapters as the node's bandwidth from the next
+    /// allocation change on, instead of the default figure
+    pub fn set_upstream_mbps(&self, upstream_mbps: f64) {
+        *self.upstream_mbps.write().unwrap_or_else(|e| e.into_inner()) = Some(upstream_mbps);
+    }
+
+    /// Bandwidth applied allocations share
+    fn bandwidth_mbps(&self) -> f64 {
+        self.upstream_mbps.read().unwrap_or_else(|e| e.into_inner()).unwrap_or(DEFAULT_BANDWIDTH_MBPS)
+    }
+
+    /// Whether proposed plans must be approved before execution
+    pub fn requires_confirmation(&self) -> bool {
+        self.config().require_confirmation
+    }
+
+    /// Whether plans are applied in stages, taking several step intervals
+    pub fn is_staged(&self) -> bool {
+        self.config().rollout.enabled
+    }
+
+    /// Whether a plan proposed at `proposed_at` is too old to approve
+    pub fn is_confirmation_expired(&self, proposed_at: DateTime<Utc>) -> bool {
+        Utc::now() - proposed_at > self.config().confirmation_timeout
+    }
+
     /// Execute a reallocation plan
     pub async fn execute_reallocation(
         &self,
         plan: &AllocationPlan,
         adapters: &HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>>,
     ) -> OrchestrationResult<()> {
+        if self.shutdown.is_triggered() {
+            return Err(OrchestrationError::ReallocationError(
+                "Shutting down, reallocation not started".to_string(),
+            ));
+        }
+
         // Validate constraints
         self.can_reallocate().await?;
 
         // Validate plan
         self.validate_plan(plan, adapters).await?;
 
+        let rollout = self.config().rollout;
+        if rollout.enabled {
+            return self.execute_staged(plan, adapters, &rollout).await;
+        }
+
+        self.apply_plan(plan, adapters, "Optimization reallocation").await?;
+        *self.last_reallocation.write().await = Some(Utc::now());
+
+        tracing::info!(
+            "Reallocation completed with {:.2}/hour improvement",
+            plan.estimated_improvement
+        );
+
+        Ok(())
+    }
+
+    /// Apply every allocation in `plan`, rolling back on failure when
+    /// `auto_rollback` is set
+    async fn apply_plan(
+        &self,
+        plan: &AllocationPlan,
+        adapters: &HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>>,
+        reason: &str,
+    ) -> OrchestrationResult<()> {
         // Store previous allocation for rollback
         let mut previous = self.previous_allocation.write().await;
         previous.clear();
 
         // Execute reallocation for each protocol
         for (protocol_name, target_allocation) in &plan.allocation {
+            // Leave every protocol on its previous allocation rather than a
+            // half-applied plan
+            if self.shutdown.is_triggered() {
+                drop(previous);
+                tracing::warn!("Shutdown during reallocation, rolling back applied changes");
+                self.rollback_allocation(adapters).await?;
+                return Err(OrchestrationError::ReallocationError(
+                    "Reallocation interrupted by shutdown and rolled back".to_string(),
+                ));
+            }
+
             let adapter_lock = adapters.get(protocol_name).ok_or_else(|| {
                 OrchestrationError::ReallocationError(format!(
                     "Protocol {} not found",
@@ -89,10 +507,13 @@ impl ReallocationEngine {
             })?;
 
             let mut adapter = adapter_lock.write().await;
+            let timeouts = self.resilience.timeouts(protocol_name);
 
             // Store previous allocation
-            let current = adapter.get_current_allocation().await.ok();
-            if let Some(current_alloc) = current {
+            let current = with_timeout(timeouts.call, "get_current_allocation", adapter.get_current_allocation())
+                .await
+                .ok();
+            if let Some(current_alloc) = &current {
                 previous.insert(protocol_name.clone(), current_alloc.allocation_percent);
             }
 
@@ -101,11 +522,15 @@ impl ReallocationEngine {
                 cpu_cores: 4,
                 memory_gb: 8.0,
                 storage_gb: 100.0,
-                bandwidth_mbps: 200.0,
+                bandwidth_mbps: self.bandwidth_mbps(),
                 allocation_percent: *target_allocation,
+                // GPUs are whole devices; keep the adapter's current assignment
+                gpu_count: current.as_ref().map_or(0, |c| c.gpu_count),
+                vram_gb: current.as_ref().map_or(0.0, |c| c.vram_gb),
+                pricing: None,
             };
 
-            match adapter.apply_allocation(strategy).await {
+            match with_timeout(timeouts.apply, "apply_allocation", adapter.apply_allocation(strategy)).await {
                 Ok(()) => {
                     tracing::info!(
                         "Applied allocation {} to {}",
@@ -122,14 +547,17 @@ impl ReallocationEngine {
                             .copied()
                             .unwrap_or(0.0),
                         new_allocation: *target_allocation,
-                        reason: "Optimization reallocation".to_string(),
+                        reason: reason.to_string(),
                         earnings_impact: plan.estimated_improvement,
                     };
 
                     self.history.write().await.push(change);
                 }
                 Err(e) => {
-                    if self.config.auto_rollback {
+                    if self.config().auto_rollback {
+                        // Rollback takes these locks itself
+                        drop(adapter);
+                        drop(previous);
                         tracing::warn!(
                             "Reallocation failed for {}, rolling back: {}",
                             protocol_name,
@@ -145,28 +573,147 @@ impl ReallocationEngine {
             }
         }
 
-        *self.last_reallocation.write().await = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Apply `plan` in rollout steps, watching earnings between them
+    ///
+    /// Any failed step, an earnings drop beyond the tolerance, or shutdown
+    /// during a step interval restores the allocation from before the rollout.
+    async fn execute_staged(
+        &self,
+        plan: &AllocationPlan,
+        adapters: &HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>>,
+        rollout: &RolloutConfig,
+    ) -> OrchestrationResult<()> {
+        if self.rollout_active.swap(true, Ordering::SeqCst) {
+            return Err(OrchestrationError::ReallocationError(
+                "A staged rollout is already in progress".to_string(),
+            ));
+        }
+        let result = self.run_rollout(plan, adapters, rollout).await;
+        self.rollout_active.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn run_rollout(
+        &self,
+        plan: &AllocationPlan,
+        adapters: &HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>>,
+        rollout: &RolloutConfig,
+    ) -> OrchestrationResult<()> {
+        let mut original = HashMap::new();
+        for protocol_name in plan.allocation.keys() {
+            let adapter = adapters[protocol_name].read().await;
+            let timeout = self.resilience.timeouts(protocol_name).call;
+            let current = with_timeout(timeout, "get_current_allocation", adapter.get_current_allocation()).await;
+            if let Ok(current) = current {
+                original.insert(protocol_name.clone(), current.allocation_percent);
+            }
+        }
+        let baseline = self.current_earnings(plan, adapters).await;
+        let steps = rollout_steps(&original, &plan.allocation, rollout.step_percent);
+        let step_count = steps.len();
+
+        for (index, allocation) in steps.into_iter().enumerate() {
+            let step = index + 1;
+            let step_plan = AllocationPlan {
+                allocation,
+                ..plan.clone()
+            };
+            let reason = format!("Staged rollout step {}/{}", step, step_count);
+            if let Err(e) = self.apply_plan(&step_plan, adapters, &reason).await {
+                self.restore_allocation(&original, adapters).await?;
+                return Err(e);
+            }
+            *self.last_reallocation.write().await = Some(Utc::now());
+            tracing::info!("Applied rollout step {}/{}", step, step_count);
+
+            if step == step_count {
+                break;
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_secs(rollout.step_interval_secs)) => {}
+                _ = self.shutdown.cancelled() => {
+                    tracing::warn!("Shutdown during staged rollout, restoring previous allocation");
+                    self.restore_allocation(&original, adapters).await?;
+                    return Err(OrchestrationError::ReallocationError(
+                        "Staged rollout interrupted by shutdown and rolled back".to_string(),
+                    ));
+                }
+            }
+
+            let earnings = self.current_earnings(plan, adapters).await;
+            if rollout.is_degraded(baseline, earnings) {
+                tracing::warn!(
+                    "Earnings fell from {:.2} to {:.2}/hour after rollout step {}, rolling back",
+                    baseline,
+                    earnings,
+                    step
+                );
+                self.restore_allocation(&original, adapters).await?;
+                return Err(OrchestrationError::ReallocationError(format!(
+                    "Earnings fell from {:.2} to {:.2}/hour after step {}/{}; rollout rolled back",
+                    baseline, earnings, step, step_count
+                )));
+            }
+        }
 
         tracing::info!(
-            "Reallocation completed with {:.2}/hour improvement",
+            "Staged rollout completed in {} steps with {:.2}/hour improvement",
+            step_count,
             plan.estimated_improvement
         );
-
         Ok(())
     }
 
+    /// Put the protocols back on `allocation`
+    async fn restore_allocation(
+        &self,
+        allocation: &HashMap<String, f64>,
+        adapters: &HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>>,
+    ) -> OrchestrationResult<()> {
+        *self.previous_allocation.write().await = allocation.clone();
+        self.rollback_allocation(adapters).await
+    }
+
+    /// Combined earnings rate of the protocols in `plan`
+    async fn current_earnings(
+        &self,
+        plan: &AllocationPlan,
+        adapters: &HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>>,
+    ) -> f64 {
+        let mut total = 0.0;
+        for protocol_name in plan.allocation.keys() {
+            let adapter = adapters[protocol_name].read().await;
+            let timeout = self.resilience.timeouts(protocol_name).call;
+            if let Ok(earnings) = with_timeout(timeout, "get_current_earnings", adapter.get_current_earnings()).await {
+                total += earnings.amount_usd;
+            }
+        }
+        total
+    }
+
     /// Check if reallocation is possible
     pub async fn can_reallocate(&self) -> OrchestrationResult<()> {
+        if self.rollout_active.load(Ordering::SeqCst) {
+            return Err(OrchestrationError::ReallocationError(
+                "A staged rollout is in progress".to_string(),
+            ));
+        }
+
         let last = *self.last_reallocation.read().await;
+        let config = self.config();
 
         if let Some(last_time) = last {
             let elapsed = Utc::now() - last_time;
 
-            if elapsed < self.config.min_hold_duration {
+            if elapsed < config.min_hold_duration {
                 return Err(OrchestrationError::ReallocationError(
                     format!(
                         "Minimum hold duration not met. Wait {:?} more",
-                        self.config.min_hold_duration - elapsed
+                        config.min_hold_duration - elapsed
                     ),
                 ));
             }
@@ -183,7 +730,7 @@ impl ReallocationEngine {
                 })
                 .count();
 
-            if recent_count as u32 >= self.config.max_per_hour {
+            if recent_count as u32 >= config.max_per_hour {
                 return Err(OrchestrationError::ReallocationError(
                     "Rate limit exceeded for reallocations".to_string(),
                 ));
@@ -209,16 +756,23 @@ impl ReallocationEngine {
             })?;
 
             let mut adapter = adapter_lock.write().await;
+            let timeouts = self.resilience.timeouts(&protocol_name);
+            let current = with_timeout(timeouts.call, "get_current_allocation", adapter.get_current_allocation())
+                .await
+                .ok();
 
             let strategy = AllocationStrategy {
                 cpu_cores: 4,
                 memory_gb: 8.0,
                 storage_gb: 100.0,
-                bandwidth_mbps: 200.0,
+                bandwidth_mbps: self.bandwidth_mbps(),
                 allocation_percent: previous_allocation,
+                gpu_count: current.as_ref().map_or(0, |c| c.gpu_count),
+                vram_gb: current.as_ref().map_or(0.0, |c| c.vram_gb),
+                pricing: None,
             };
 
-            adapter.apply_allocation(strategy).await?;
+            with_timeout(timeouts.apply, "apply_allocation", adapter.apply_allocation(strategy)).await?;
 
             tracing::info!(
                 "Rolled back {} to {}%",
@@ -250,13 +804,30 @@ impl ReallocationEngine {
             .collect()
     }
 
-    /// Estimate reallocation cost
+    /// Estimate the one-off USD cost of moving to `target`
     pub fn estimate_reallocation_cost(
         &self,
-        protocol_count: usize,
+        metrics: &AggregatedMetrics,
+        target: &HashMap<String, f64>,
     ) -> f64 {
-        // Estimate based on number of protocols affected
-        0.05 * protocol_count as f64 // $0.05 per protocol
+        self.config().switching_cost(metrics, target)
+    }
+
+    /// Replace a plan's cost estimate with the configured switching costs
+    ///
+    /// `estimated_cost` becomes the one-off cost spread over
+    /// `cost_amortization_hours`, so it is comparable with the hourly
+    /// improvement; net benefit and ROI follow from it.
+    pub fn price_plan(&self, plan: &mut AllocationPlan, metrics: &AggregatedMetrics) {
+        let config = self.config();
+        let cost = config.switching_cost(metrics, &plan.allocation) / config.cost_amortization_hours;
+        plan.estimated_cost = cost;
+        plan.net_benefit = plan.estimated_improvement - cost;
+        plan.roi_percent = if cost > 0.001 {
+            plan.estimated_improvement / cost * 100.0
+        } else {
+            100.0
+        };
     }
 
     /// Validate a plan before execution
@@ -290,6 +861,23 @@ impl ReallocationEngine {
             ));
         }
 
+        // Check floors, caps and pins against the live allocation
+        let config = self.config();
+        let mut current = HashMap::new();
+        for protocol_name in plan.allocation.keys() {
+            let constraint = config.constraints.get(protocol_type(&self.protocol_types, protocol_name));
+            if !constraint.is_some_and(|c| c.pinned) {
+                continue;
+            }
+            let adapter = adapters[protocol_name].read().await;
+            let timeout = self.resilience.timeouts(protocol_name).call;
+            let allocation = with_timeout(timeout, "get_current_allocation", adapter.get_current_allocation()).await;
+            if let Ok(allocation) = allocation {
+                current.insert(protocol_name.clone(), allocation.allocation_percent);
+            }
+        }
+        config.check_allocation(&plan.allocation, &current, &self.protocol_types)?;
+
         Ok(())
     }
 }
@@ -304,11 +892,108 @@ mod tests {
         assert!(engine.history.blocking_read().is_empty());
     }
 
+    fn three_protocol_metrics() -> AggregatedMetrics {
+        AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: 6.0,
+            earnings_by_protocol: HashMap::from([
+                ("storj".to_string(), 2.0),
+                ("golem".to_string(), 3.0),
+                ("streamr".to_string(), 1.0),
+            ]),
+            allocation_by_protocol: HashMap::from([
+                ("storj".to_string(), 40.0),
+                ("golem".to_string(), 30.0),
+                ("streamr".to_string(), 30.0),
+            ]),
+            resource_utilization: crate::orchestration::ResourceUtilization {
+                cpu_percent: 50.0,
+                memory_percent: 50.0,
+                bandwidth_percent: 50.0,
+                storage_percent: 50.0,
+            },
+            connection_status: HashMap::new(),
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        }
+    }
+
     #[test]
     fn test_estimate_cost() {
         let engine = ReallocationEngine::new(ReallocationConfig::default());
-        let cost = engine.estimate_reallocation_cost(3);
-        assert_eq!(cost, 0.15);
+        let target = HashMap::from([
+            ("storj".to_string(), 30.0),
+            ("golem".to_string(), 35.0),
+            ("streamr".to_string(), 35.0),
+        ]);
+        let cost = engine.estimate_reallocation_cost(&three_protocol_metrics(), &target);
+        assert!((cost - 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_switching_costs_add_penalty_and_ramp_up() {
+        let mut config = ReallocationConfig {
+            base_cost_usd: 0.0,
+            switching_costs: HashMap::from([
+                (
+                    "storj".to_string(),
+                    SwitchingCost {
+                        penalty_usd: 20.0,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "golem".to_string(),
+                    SwitchingCost {
+                        ramp_up_hours: 10.0,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+        let metrics = three_protocol_metrics();
+        let target = HashMap::from([
+            ("storj".to_string(), 30.0),
+            ("golem".to_string(), 40.0),
+            ("streamr".to_string(), 30.0),
+        ]);
+
+        // A quarter of storj's share forfeits a quarter of its penalty ($5);
+        // golem's extra 10% earns $1/hour and is idle for 10 hours ($10)
+        assert!((config.switching_cost(&metrics, &target) - 15.0).abs() < 1e-9);
+
+        config.switching_costs.get_mut("storj").unwrap().irreversible = true;
+        assert!((config.switching_cost(&metrics, &target) - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_price_plan_amortizes_cost() {
+        let engine = ReallocationEngine::new(ReallocationConfig {
+            base_cost_usd: 2.4,
+            ..Default::default()
+        });
+        let mut plan = AllocationPlan {
+            allocation: HashMap::from([
+                ("storj".to_string(), 30.0),
+                ("golem".to_string(), 40.0),
+                ("streamr".to_string(), 30.0),
+            ]),
+            estimated_improvement: 1.0,
+            estimated_cost: 0.0,
+            net_benefit: 1.0,
+            roi_percent: 0.0,
+            confidence: 0.8,
+            created_at: Utc::now(),
+        };
+
+        engine.price_plan(&mut plan, &three_protocol_metrics());
+        // Two changed protocols at $2.40 each, spread over 24 hours
+        assert!((plan.estimated_cost - 0.2).abs() < 1e-9);
+        assert!((plan.net_benefit - 0.8).abs() < 1e-9);
     }
 
     #[tokio::test]
@@ -323,4 +1008,263 @@ mod tests {
         assert_eq!(config.max_per_hour, 4);
         assert!(config.auto_rollback);
     }
+
+    #[test]
+    fn test_confirmation_expiry() {
+        let engine = ReallocationEngine::new(ReallocationConfig {
+            require_confirmation: true,
+            ..Default::default()
+        });
+        assert!(engine.requires_confirmation());
+        assert!(!engine.is_confirmation_expired(Utc::now() - Duration::hours(1)));
+        assert!(engine.is_confirmation_expired(Utc::now() - Duration::hours(7)));
+    }
+
+    #[test]
+    fn test_set_config_at_runtime() {
+        let engine = ReallocationEngine::new(ReallocationConfig::default());
+        engine
+            .set_config(ReallocationConfig {
+                require_confirmation: true,
+                max_per_hour: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(engine.requires_confirmation());
+        assert_eq!(engine.config().max_per_hour, 1);
+
+        let invalid = ReallocationConfig {
+            max_per_hour: 0,
+            ..Default::default()
+        };
+        assert!(engine.set_config(invalid).is_err());
+        assert_eq!(engine.config().max_per_hour, 1);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_reallocation_after_shutdown() {
+        let shutdown = crate::shutdown::Shutdown::new();
+        let engine = ReallocationEngine::new(ReallocationConfig::default())
+            .with_shutdown(shutdown.token());
+        shutdown.trigger();
+
+        let plan = AllocationPlan {
+            allocation: HashMap::from([("streamr".to_string(), 50.0)]),
+            estimated_improvement: 1.0,
+            estimated_cost: 0.1,
+            net_benefit: 0.9,
+            roi_percent: 900.0,
+            confidence: 0.8,
+            created_at: Utc::now(),
+        };
+        let result = engine.execute_reallocation(&plan, &HashMap::new()).await;
+        assert!(matches!(result, Err(OrchestrationError::ReallocationError(_))));
+        assert!(engine.get_reallocation_history().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_adapters_get_probed_upstream() {
+        use crate::protocols::mock::{EarningsCurve, MockAdapter};
+
+        let engine = ReallocationEngine::new(ReallocationConfig::default());
+        engine.set_upstream_mbps(40.0);
+        let mut adapters: HashMap<String, Arc<RwLock<Box<dyn ProtocolAdapter>>>> = HashMap::new();
+        for (name, percent) in [("grass", 50.0), ("streamr", 50.0)] {
+            let adapter = MockAdapter::new(name, EarningsCurve::Constant(1.0), percent);
+            adapters.insert(name.to_string(), Arc::new(RwLock::new(Box::new(adapter) as Box<dyn ProtocolAdapter>)));
+        }
+
+        let plan = AllocationPlan {
+            allocation: HashMap::from([("grass".to_string(), 70.0), ("streamr".to_string(), 30.0)]),
+            estimated_improvement: 1.0,
+            estimated_cost: 0.1,
+            net_benefit: 0.9,
+            roi_percent: 900.0,
+            confidence: 0.8,
+            created_at: Utc::now(),
+        };
+        engine.execute_reallocation(&plan, &adapters).await.unwrap();
+
+        let grass = adapters["grass"].read().await.get_current_allocation().await.unwrap();
+        assert_eq!((grass.allocation_percent, grass.bandwidth_mbps), (70.0, 40.0));
+    }
+
+    fn constrained_config() -> ReallocationConfig {
+        ReallocationConfig {
+            constraints: HashMap::from([
+                (
+                    "storj".to_string(),
+                    ProtocolConstraint {
+                        min_percent: Some(20.0),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "golem".to_string(),
+                    ProtocolConstraint {
+                        max_percent: Some(50.0),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "streamr".to_string(),
+                    ProtocolConstraint {
+                        pinned: true,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_constraint_validation() {
+        assert!(constrained_config().validate().is_ok());
+
+        let mut config = constrained_config();
+        config.constraints.get_mut("golem").unwrap().min_percent = Some(60.0);
+        assert!(config.validate().is_err());
+
+        let mut config = constrained_config();
+        config.constraints.get_mut("storj").unwrap().min_percent = Some(90.0);
+        config.constraints.get_mut("golem").unwrap().min_percent = Some(20.0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_check_allocation_enforces_floor_cap_and_pin() {
+        let config = constrained_config();
+        let current = HashMap::from([("streamr".to_string(), 30.0)]);
+        let plan = |storj: f64, golem: f64, streamr: f64| {
+            HashMap::from([
+                ("storj".to_string(), storj),
+                ("golem".to_string(), golem),
+                ("streamr".to_string(), streamr),
+            ])
+        };
+
+        assert!(config.check_allocation(&plan(20.0, 50.0, 30.0), &current, &HashMap::new()).is_ok());
+        assert!(config.check_allocation(&plan(15.0, 55.0, 30.0), &current, &HashMap::new()).is_err());
+        assert!(config.check_allocation(&plan(25.0, 55.0, 20.0), &current, &HashMap::new()).is_err());
+        assert!(config.check_allocation(&plan(30.0, 50.0, 20.0), &current, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_instances_are_held_to_their_type_constraints() {
+        let config = constrained_config();
+        let protocol_types = HashMap::from([("storj_backup".to_string(), "storj".to_string())]);
+        let plan = |backup: f64| {
+            HashMap::from([
+                ("storj".to_string(), 30.0),
+                ("storj_backup".to_string(), backup),
+                ("golem".to_string(), 40.0),
+            ])
+        };
+
+        // The storj floor of 20% applies to every storj instance
+        assert!(config.check_allocation(&plan(25.0), &HashMap::new(), &protocol_types).is_ok());
+        assert!(config.check_allocation(&plan(15.0), &HashMap::new(), &protocol_types).is_err());
+        assert!(config.check_allocation(&plan(15.0), &HashMap::new(), &HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_constrained_metrics_keep_optimizer_within_limits() {
+        use crate::orchestration::ResourceUtilization;
+        use crate::protocols::{ResourceKind, ResourceProfile};
+        use crate::{EarningsOptimizer, OptimizerConfig};
+
+        // storj earns least and golem most, so unconstrained the optimizer
+        // would drain storj into golem
+        let metrics = Arc::new(AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: 9.0,
+            earnings_by_protocol: HashMap::from([
+                ("storj".to_string(), 0.5),
+                ("golem".to_string(), 8.0),
+                ("streamr".to_string(), 0.5),
+            ]),
+            allocation_by_protocol: HashMap::from([
+                ("storj".to_string(), 21.0),
+                ("golem".to_string(), 49.0),
+                ("streamr".to_string(), 30.0),
+            ]),
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 50.0,
+                memory_percent: 50.0,
+                bandwidth_percent: 50.0,
+                storage_percent: 50.0,
+            },
+            connection_status: HashMap::new(),
+            resource_profiles: ["storj", "golem", "streamr"]
+                .into_iter()
+                .map(|name| (name.to_string(), ResourceProfile::new(ResourceKind::Cpu, 0.0, 100.0)))
+                .collect(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        });
+
+        // Without constraints the snapshot is shared, not copied
+        assert!(Arc::ptr_eq(&ReallocationConfig::default().constrained_metrics(&metrics), &metrics));
+
+        let config = constrained_config();
+        let metrics = config.constrained_metrics(&metrics);
+        assert!(metrics.resource_profiles["streamr"].observe_only);
+
+        let plan = EarningsOptimizer::new(OptimizerConfig::default())
+            .calculate_optimal_allocation(&metrics)
+            .unwrap();
+        let current = HashMap::from([("streamr".to_string(), 30.0)]);
+        assert!(config.check_allocation(&plan.allocation, &current, &HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_rollout_steps_move_in_bounded_increments() {
+        let current = HashMap::from([("storj".to_string(), 50.0), ("golem".to_string(), 50.0)]);
+        let target = HashMap::from([
+            ("storj".to_string(), 62.0),
+            ("golem".to_string(), 30.0),
+            ("streamr".to_string(), 8.0),
+        ]);
+
+        let steps = rollout_steps(&current, &target, 5.0);
+        // golem moves 20 points, so four steps of 5
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps.last(), Some(&target));
+
+        // streamr is new to the plan and ramps up from zero
+        assert!((steps[0]["streamr"] - 2.0).abs() < ALLOCATION_EPSILON);
+
+        let mut previous = current.clone();
+        previous.insert("streamr".to_string(), 0.0);
+        for step in &steps {
+            assert!((step.values().sum::<f64>() - 100.0).abs() < ALLOCATION_EPSILON);
+            for (protocol, value) in step {
+                assert!((value - previous[protocol]).abs() <= 5.0 + ALLOCATION_EPSILON);
+            }
+            previous = step.clone();
+        }
+
+        // Already on target: a single step
+        assert_eq!(rollout_steps(&target, &target, 5.0).len(), 1);
+    }
+
+    #[test]
+    fn test_rollout_degradation_and_validation() {
+        let rollout = RolloutConfig::default();
+        assert!(!rollout.is_degraded(10.0, 9.5));
+        assert!(rollout.is_degraded(10.0, 8.9));
+        assert!(!rollout.is_degraded(0.0, 0.0));
+
+        let config = ReallocationConfig {
+            rollout: RolloutConfig {
+                step_percent: 0.0,
+                ..RolloutConfig::default()
+            },
+            ..ReallocationConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
 }
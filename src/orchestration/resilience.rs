@@ -0,0 +1,363 @@
+/// Adapter Call Resilience
+///
+/// Retry-with-backoff and a per-protocol circuit breaker for adapter calls,
+/// so one slow or failing protocol API cannot stall a whole poll cycle.
+/// After `failure_threshold` consecutive failed polls a protocol's breaker
+/// opens and it is skipped (reported disconnected) for `open_duration_secs`;
+/// the next poll after that is a half-open trial that closes or re-opens it.
+///
+/// Every adapter call is bounded by a timeout: `connect_timeout_ms` for
+/// connecting and disconnecting, `call_timeout_ms` for polls (earnings,
+/// allocation, resources, health), and `apply_timeout_ms` for applying an
+/// allocation, each overridable per protocol.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::protocols::{ProtocolError, ProtocolResult};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Retry and circuit breaker settings (`[resilience]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResilienceConfig {
+    /// Retries after the first failed attempt of a call
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for each further retry
+    pub initial_backoff_ms: u64,
+    /// Upper bound for a single backoff
+    pub max_backoff_ms: u64,
+    /// Timeout for a single attempt of a poll call
+    pub call_timeout_ms: u64,
+    /// Timeout for connecting or disconnecting an adapter
+    pub connect_timeout_ms: u64,
+    /// Timeout for applying an allocation to an adapter
+    pub apply_timeout_ms: u64,
+    /// Consecutive failed polls that open a protocol's breaker
+    pub failure_threshold: u32,
+    /// How long an open breaker skips its protocol
+    pub open_duration_secs: u64,
+    /// Timeout overrides by protocol (`[resilience.protocols.<name>]`)
+    pub protocols: HashMap<String, TimeoutOverrides>,
+}
+
+/// Timeouts of one protocol that differ from the defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimeoutOverrides {
+    pub call_timeout_ms: Option<u64>,
+    pub connect_timeout_ms: Option<u64>,
+    pub apply_timeout_ms: Option<u64>,
+}
+
+/// Timeouts of one protocol's adapter calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallTimeouts {
+    pub call: Duration,
+    pub connect: Duration,
+    pub apply: Duration,
+}
+
+impl Default for ResilienceConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff_ms: 200,
+            max_backoff_ms: 2000,
+            call_timeout_ms: 10_000,
+            connect_timeout_ms: 30_000,
+            apply_timeout_ms: 60_000,
+            failure_threshold: 3,
+            open_duration_secs: 60,
+            protocols: HashMap::new(),
+        }
+    }
+}
+
+impl ResilienceConfig {
+    /// Check that every setting is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.call_timeout_ms == 0 {
+            return Err("call_timeout_ms must be at least 1".to_string());
+        }
+        if self.connect_timeout_ms == 0 {
+            return Err("connect_timeout_ms must be at least 1".to_string());
+        }
+        if self.apply_timeout_ms == 0 {
+            return Err("apply_timeout_ms must be at least 1".to_string());
+        }
+        for (protocol, overrides) in &self.protocols {
+            let timeouts = [overrides.call_timeout_ms, overrides.connect_timeout_ms, overrides.apply_timeout_ms];
+            if timeouts.contains(&Some(0)) {
+                return Err(format!("protocols.{} timeouts must be at least 1", protocol));
+            }
+        }
+        if self.initial_backoff_ms > self.max_backoff_ms {
+            return Err("initial_backoff_ms must not exceed max_backoff_ms".to_string());
+        }
+        if self.failure_threshold == 0 {
+            return Err("failure_threshold must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Timeouts of `protocol`'s adapter calls
+    pub fn timeouts(&self, protocol: &str) -> CallTimeouts {
+        let overrides = self.protocols.get(protocol).cloned().unwrap_or_default();
+        CallTimeouts {
+            call: Duration::from_millis(overrides.call_timeout_ms.unwrap_or(self.call_timeout_ms)),
+            connect: Duration::from_millis(overrides.connect_timeout_ms.unwrap_or(self.connect_timeout_ms)),
+            apply: Duration::from_millis(overrides.apply_timeout_ms.unwrap_or(self.apply_timeout_ms)),
+        }
+    }
+
+    /// Backoff before retry number `retry` (1-based)
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64.checked_shl(retry.saturating_sub(1)).unwrap_or(u64::MAX);
+        Duration::from_millis(
+            self.initial_backoff_ms
+                .saturating_mul(factor)
+                .min(self.max_backoff_ms),
+        )
+    }
+}
+
+// ============================================================================
+// RETRY
+// ============================================================================
+
+/// Failure of a call after all retries
+#[derive(Debug)]
+pub enum CallError<E> {
+    /// The last attempt exceeded the call timeout
+    Timeout(Duration),
+    /// The last attempt returned an error
+    Failed(E),
+}
+
+impl<E: fmt::Display> fmt::Display for CallError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Timeout(timeout) => write!(f, "timed out after {:?}", timeout),
+            CallError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Run `call` until it succeeds or retries are exhausted
+///
+/// Each attempt is bounded by `timeout`; retries back off exponentially up
+/// to `max_backoff_ms`.
+pub async fn retry_with_backoff<T, E, F, Fut>(
+    config: &ResilienceConfig,
+    timeout: Duration,
+    mut call: F,
+) -> Result<T, CallError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut retry = 0;
+    loop {
+        let error = match tokio::time::timeout(timeout, call()).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(e)) => CallError::Failed(e),
+            Err(_) => CallError::Timeout(timeout),
+        };
+
+        if retry >= config.max_retries {
+            return Err(error);
+        }
+        retry += 1;
+        tokio::time::sleep(config.backoff(retry)).await;
+    }
+}
+
+/// Run `call` once, bounded by `timeout`
+///
+/// An overrun is reported as `ProtocolError::TimeoutError` naming `operation`.
+pub async fn with_timeout<T, Fut>(timeout: Duration, operation: &str, call: Fut) -> ProtocolResult<T>
+where
+    Fut: Future<Output = ProtocolResult<T>>,
+{
+    tokio::time::timeout(timeout, call)
+        .await
+        .unwrap_or_else(|_| Err(ProtocolError::TimeoutError(format!("{} timed out after {:?}", operation, timeout))))
+}
+
+// ============================================================================
+// CIRCUIT BREAKER
+// ============================================================================
+
+/// Circuit breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Calls flow normally
+    Closed,
+    /// Calls are skipped until the open period ends
+    Open,
+    /// One trial poll decides whether to close or re-open
+    HalfOpen,
+}
+
+/// Consecutive-failure circuit breaker for one protocol
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker
+    pub fn new(config: &ResilienceConfig) -> Self {
+        Self {
+            failure_threshold: config.failure_threshold.max(1),
+            open_duration: Duration::from_secs(config.open_duration_secs),
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    /// Whether a call may be made at `now`; moves Open to HalfOpen once the
+    /// open period has elapsed
+    pub fn allow(&mut self, now: Instant) -> bool {
+        match (self.state, self.opened_at) {
+            (BreakerState::Open, Some(opened)) if now.duration_since(opened) < self.open_duration => {
+                false
+            }
+            (BreakerState::Open, _) => {
+                self.state = BreakerState::HalfOpen;
+                true
+            }
+            _ => true,
+        }
+    }
+
+    /// Record a successful poll; returns true if the breaker was not closed
+    pub fn record_success(&mut self) -> bool {
+        let recovered = self.state != BreakerState::Closed;
+        self.state = BreakerState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        recovered
+    }
+
+    /// Record a failed poll at `now`; returns true if the breaker just opened
+    pub fn record_failure(&mut self, now: Instant) -> bool {
+        self.consecutive_failures += 1;
+        let should_open = self.state == BreakerState::HalfOpen
+            || self.consecutive_failures >= self.failure_threshold;
+        if should_open && self.state != BreakerState::Open {
+            self.state = BreakerState::Open;
+            self.opened_at = Some(now);
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn fast_config() -> ResilienceConfig {
+        ResilienceConfig {
+            max_retries: 2,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 5,
+            call_timeout_ms: 50,
+            failure_threshold: 2,
+            open_duration_secs: 60,
+            ..ResilienceConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let config = ResilienceConfig::default();
+        assert_eq!(config.backoff(1), Duration::from_millis(200));
+        assert_eq!(config.backoff(2), Duration::from_millis(400));
+        assert_eq!(config.backoff(10), Duration::from_millis(2000));
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_transient_failures() {
+        let attempts = AtomicU32::new(0);
+        let result: Result<u32, CallError<&str>> = retry_with_backoff(&fast_config(), TIMEOUT, || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err("flaky"),
+                n => Ok(n),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_and_times_out() {
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), CallError<&str>> = retry_with_backoff(&fast_config(), TIMEOUT, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            std::future::pending().await
+        })
+        .await;
+        assert!(matches!(result, Err(CallError::Timeout(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_overridable_per_protocol() {
+        let config: ResilienceConfig =
+            toml::from_str("apply_timeout_ms = 5000\n[protocols.golem]\ncall_timeout_ms = 50\n").unwrap();
+        assert!(config.validate().is_ok());
+        let golem = config.timeouts("golem");
+        assert_eq!(golem.call, TIMEOUT);
+        assert_eq!(golem.apply, Duration::from_millis(5000));
+        assert_eq!(config.timeouts("storj").call, Duration::from_millis(10_000));
+
+        let hung = with_timeout(golem.call, "get_current_earnings", std::future::pending::<ProtocolResult<()>>()).await;
+        assert!(matches!(hung, Err(ProtocolError::TimeoutError(message)) if message.contains("get_current_earnings")));
+        assert_eq!(with_timeout(golem.call, "connect", async { Ok(1) }).await.unwrap(), 1);
+
+        let zero: ResilienceConfig = toml::from_str("[protocols.golem]\nconnect_timeout_ms = 0\n").unwrap();
+        assert!(zero.validate().unwrap_err().contains("protocols.golem"));
+    }
+
+    #[test]
+    fn test_breaker_opens_half_opens_and_closes() {
+        let mut breaker = CircuitBreaker::new(&fast_config());
+        let start = Instant::now();
+
+        assert!(!breaker.record_failure(start));
+        assert!(breaker.record_failure(start));
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.allow(start + Duration::from_secs(30)));
+
+        // Trial after the open period; a failure re-opens immediately
+        assert!(breaker.allow(start + Duration::from_secs(61)));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.record_failure(start + Duration::from_secs(61)));
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        assert!(breaker.allow(start + Duration::from_secs(122)));
+        assert!(breaker.record_success());
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+}
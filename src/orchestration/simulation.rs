@@ -0,0 +1,288 @@
+/// Orchestration Simulation Harness
+///
+/// Drives the coordinator, optimizer and reallocation engine over mock
+/// adapters through scripted scenarios, one optimization tick per step.
+/// Available to this crate's tests and, with the `test-util` feature, to
+/// downstream crates.
+///
+/// A step mirrors the scheduler's optimization tick without the database:
+/// poll every adapter, update the optimizer, plan within the reallocation
+/// constraints and execute the plan unless something blocks it. Reallocation
+/// hold times and rate limits still run on the wall clock, so scenarios that
+/// reallocate repeatedly should zero `min_hold_duration`.
+use super::coordinator::ProtocolCoordinator;
+use super::optimizer::{EarningsOptimizer, OptimizerConfig, OptimizerSnapshot};
+use super::reallocation::{ReallocationConfig, ReallocationEngine};
+use super::resilience::ResilienceConfig;
+use super::{AggregatedMetrics, AllocationPlan, OrchestrationResult};
+use crate::protocols::mock::{EarningsCurve, MockAdapter, MockHandle, MockOperation};
+use crate::protocols::ProtocolAdapter;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+// ============================================================================
+// SCENARIOS
+// ============================================================================
+
+/// Change made to a mock protocol at a scripted step
+#[derive(Debug, Clone)]
+pub enum ScenarioEvent {
+    /// Switch the protocol to a new earnings curve
+    SetEarnings { protocol: String, curve: EarningsCurve },
+    /// Fail the next `times` calls, or every call until `Recover` when `None`
+    Fail { protocol: String, operation: MockOperation, times: Option<u32> },
+    /// Stop injecting failures
+    Recover { protocol: String, operation: MockOperation },
+    /// Delay every call
+    SetLatency { protocol: String, operation: MockOperation, latency: Duration },
+}
+
+/// Events to apply, by the step they happen at
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    events: BTreeMap<u64, Vec<ScenarioEvent>>,
+}
+
+impl Scenario {
+    /// Create an empty scenario
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply `event` at the start of `step`
+    pub fn at(mut self, step: u64, event: ScenarioEvent) -> Self {
+        self.events.entry(step).or_default().push(event);
+        self
+    }
+
+    /// Events scripted for `step`
+    fn events_at(&self, step: u64) -> &[ScenarioEvent] {
+        self.events.get(&step).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// What happened during one simulation step
+#[derive(Debug, Clone)]
+pub struct StepReport {
+    pub step: u64,
+    /// Metrics polled at the start of the step
+    pub metrics: Arc<AggregatedMetrics>,
+    /// Plan the optimizer proposed
+    pub plan: AllocationPlan,
+    /// Why the plan was not executed
+    pub blocker: Option<String>,
+    /// Whether the plan was executed
+    pub executed: bool,
+    /// Why executing the plan failed
+    pub error: Option<String>,
+    /// Allocation percent by protocol at the end of the step
+    pub allocation: HashMap<String, f64>,
+}
+
+// ============================================================================
+// SIMULATION
+// ============================================================================
+
+/// Orchestration loop over mock adapters
+pub struct Simulation {
+    coordinator: ProtocolCoordinator,
+    optimizer: EarningsOptimizer,
+    reallocation: ReallocationEngine,
+    handles: HashMap<String, MockHandle>,
+    step: u64,
+}
+
+impl Simulation {
+    /// Create a simulation with no protocols
+    pub fn new(optimizer: OptimizerConfig, reallocation: ReallocationConfig) -> Self {
+        let resilience = ResilienceConfig::default();
+        Self {
+            coordinator: ProtocolCoordinator::new(1000).with_resilience(resilience.clone()),
+            optimizer: EarningsOptimizer::new(optimizer),
+            reallocation: ReallocationEngine::new(reallocation).with_resilience(resilience),
+            handles: HashMap::new(),
+            step: 0,
+        }
+    }
+
+    /// Use `config` for adapter call retries, timeouts and circuit breakers
+    pub fn with_resilience(mut self, config: ResilienceConfig) -> Self {
+        self.coordinator = self.coordinator.with_resilience(config.clone());
+        self.reallocation = self.reallocation.with_resilience(config);
+        self
+    }
+
+    /// Register `adapter` under its protocol name
+    pub fn with_protocol(mut self, adapter: MockAdapter) -> Self {
+        let name = adapter.protocol_name().to_string();
+        self.handles.insert(name.clone(), adapter.handle());
+        self.coordinator.register_adapter(name, Box::new(adapter));
+        self
+    }
+
+    /// Handle of the mock registered as `protocol`
+    pub fn handle(&self, protocol: &str) -> Option<&MockHandle> {
+        self.handles.get(protocol)
+    }
+
+    /// Coordinator polling the mocks
+    pub fn coordinator(&self) -> &ProtocolCoordinator {
+        &self.coordinator
+    }
+
+    /// Optimizer planning each step
+    pub fn optimizer(&self) -> &EarningsOptimizer {
+        &self.optimizer
+    }
+
+    /// Engine executing the plans
+    pub fn reallocation(&self) -> &ReallocationEngine {
+        &self.reallocation
+    }
+
+    /// Run one optimization tick, then advance every earnings curve
+    pub async fn step(&mut self) -> OrchestrationResult<StepReport> {
+        let metrics = self.coordinator.poll_all().await?;
+        self.optimizer.update_metrics(metrics.clone());
+
+        let constrained = self.reallocation.config().constrained_metrics(&metrics);
+
+        let optimizer = self.optimizer.snapshot();
+        let candidates = optimizer.evaluate_candidates(&constrained);
+        let opportunities = OptimizerSnapshot::opportunities(&candidates);
+        let mut plan = optimizer.calculate_optimal_allocation(&constrained)?;
+        self.reallocation.price_plan(&mut plan, &constrained);
+        let blocker = optimizer.reallocation_blocker(&opportunities, Some(&plan));
+
+        let mut error = None;
+        if blocker.is_none() {
+            if let Err(e) = self.reallocation.execute_reallocation(&plan, self.coordinator.adapters()).await {
+                error = Some(e.to_string());
+            }
+        }
+
+        let report = StepReport {
+            step: self.step,
+            metrics,
+            executed: blocker.is_none() && error.is_none(),
+            plan,
+            blocker,
+            error,
+            allocation: self
+                .handles
+                .iter()
+                .map(|(name, handle)| (name.clone(), handle.allocation_percent()))
+                .collect(),
+        };
+
+        for handle in self.handles.values() {
+            handle.advance();
+        }
+        self.step += 1;
+
+        Ok(report)
+    }
+
+    /// Run `steps` steps, applying `scenario`'s events as their steps come up
+    pub async fn run(&mut self, scenario: &Scenario, steps: u64) -> OrchestrationResult<Vec<StepReport>> {
+        let mut reports = Vec::new();
+        for _ in 0..steps {
+            for event in scenario.events_at(self.step) {
+                self.apply(event);
+            }
+            reports.push(self.step().await?);
+        }
+        Ok(reports)
+    }
+
+    /// Apply a scenario event to its protocol's mock; unknown protocols are ignored
+    fn apply(&self, event: &ScenarioEvent) {
+        let protocol = match event {
+            ScenarioEvent::SetEarnings { protocol, .. }
+            | ScenarioEvent::Fail { protocol, .. }
+            | ScenarioEvent::Recover { protocol, .. }
+            | ScenarioEvent::SetLatency { protocol, .. } => protocol,
+        };
+        let Some(handle) = self.handles.get(protocol) else {
+            tracing::warn!("Scenario event for unknown protocol {}", protocol);
+            return;
+        };
+
+        match event {
+            ScenarioEvent::SetEarnings { curve, .. } => handle.set_earnings(curve.clone()),
+            ScenarioEvent::Fail { operation, times: Some(times), .. } => handle.fail(*operation, *times),
+            ScenarioEvent::Fail { operation, times: None, .. } => handle.fail_always(*operation),
+            ScenarioEvent::Recover { operation, .. } => handle.recover(*operation),
+            ScenarioEvent::SetLatency { operation, latency, .. } => handle.set_latency(*operation, *latency),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulation() -> Simulation {
+        let reallocation = ReallocationConfig {
+            min_hold_duration: chrono::Duration::zero(),
+            max_per_hour: 100,
+            base_cost_usd: 0.0,
+            ..ReallocationConfig::default()
+        };
+        let resilience = ResilienceConfig {
+            max_retries: 0,
+            call_timeout_ms: 50,
+            apply_timeout_ms: 50,
+            failure_threshold: 2,
+            ..ResilienceConfig::default()
+        };
+        Simulation::new(OptimizerConfig::default(), reallocation)
+            .with_resilience(resilience)
+            .with_protocol(MockAdapter::new("grass", EarningsCurve::Constant(40.0), 50.0))
+            .with_protocol(MockAdapter::new("theta", EarningsCurve::Constant(4.0), 50.0))
+    }
+
+    #[tokio::test]
+    async fn test_allocation_moves_to_better_earner() {
+        let mut sim = simulation();
+        let reports = sim.run(&Scenario::new(), 3).await.unwrap();
+
+        let executed = reports.iter().find(|r| r.executed).expect("a reallocation");
+        assert!(executed.allocation["grass"] > 50.0);
+        assert!(executed.allocation["theta"] < 50.0);
+        assert!(!sim.handle("grass").unwrap().applied().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failing_protocol_is_degraded_and_recovers() {
+        let mut sim = simulation();
+        let down = ScenarioEvent::Fail { protocol: "theta".into(), operation: MockOperation::Health, times: None };
+        let up = ScenarioEvent::Recover { protocol: "theta".into(), operation: MockOperation::Health };
+        let scenario = Scenario::new().at(0, down).at(2, up);
+
+        let reports = sim.run(&scenario, 2).await.unwrap();
+        assert!(!reports[1].metrics.connection_status["theta"]);
+        assert_eq!(sim.coordinator().degraded_protocols(), vec!["theta".to_string()]);
+
+        sim.run(&scenario, 1).await.unwrap();
+        sim.coordinator().connect_protocol("theta").await.unwrap();
+        assert!(sim.coordinator().degraded_protocols().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hung_apply_times_out() {
+        let mut sim = simulation();
+        let hang = ScenarioEvent::SetLatency {
+            protocol: "theta".into(),
+            operation: MockOperation::ApplyAllocation,
+            latency: Duration::from_secs(5),
+        };
+
+        let reports = sim.run(&Scenario::new().at(0, hang), 3).await.unwrap();
+        let failed = reports.iter().find(|r| r.error.is_some()).expect("a failed reallocation");
+        assert!(failed.error.as_ref().unwrap().contains("timed out"));
+        assert!(!reports.iter().any(|r| r.executed));
+        assert!(sim.handle("theta").unwrap().applied().is_empty());
+    }
+}
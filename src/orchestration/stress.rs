@@ -0,0 +1,288 @@
+/// Scenario Stress Testing
+///
+/// Applies hypothetical shocks — a protocol's rate dropping, a protocol going
+/// offline, a token's price crashing — to a copy of the current metrics, and
+/// reports the earnings impact and the allocation the optimizer would move to
+/// in response. Nothing is reallocated.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use super::optimizer::OptimizerSnapshot;
+use super::reallocation::ReallocationEngine;
+use super::AggregatedMetrics;
+
+/// Hours a rate drop or price crash is assumed to last when totalling its impact
+pub const STRESS_HORIZON_HOURS: f64 = 24.0;
+
+/// Shock size of the default scenarios (percent)
+const DEFAULT_SHOCK_PERCENT: f64 = 50.0;
+
+/// Outage length of the default scenarios (hours)
+const DEFAULT_OFFLINE_HOURS: f64 = 24.0;
+
+fn default_shock_percent() -> f64 {
+    DEFAULT_SHOCK_PERCENT
+}
+
+fn default_offline_hours() -> f64 {
+    DEFAULT_OFFLINE_HOURS
+}
+
+// ============================================================================
+// SCENARIOS
+// ============================================================================
+
+/// Hypothetical shock to the current metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StressScenario {
+    /// The protocol's earnings rate drops by `percent` (default 50)
+    RateDrop {
+        protocol: String,
+        #[serde(default = "default_shock_percent")]
+        percent: f64,
+    },
+    /// The protocol earns nothing for `hours` (default 24)
+    Offline {
+        protocol: String,
+        #[serde(default = "default_offline_hours")]
+        hours: f64,
+    },
+    /// The token's price drops by `percent` (default 50), cutting the USD
+    /// earnings of every protocol paid in it
+    TokenPriceCrash {
+        token: String,
+        #[serde(default = "default_shock_percent")]
+        percent: f64,
+    },
+}
+
+impl StressScenario {
+    /// A rate drop and an outage for every connected protocol, and a price
+    /// crash for every token they are paid in
+    ///
+    /// `tokens` maps protocols to the symbol of the token they pay in.
+    pub fn defaults(metrics: &AggregatedMetrics, tokens: &HashMap<String, String>) -> Vec<Self> {
+        let connected: BTreeSet<_> =
+            metrics.connection_status.iter().filter(|(_, connected)| **connected).map(|(p, _)| p.clone()).collect();
+        let mut scenarios = Vec::new();
+        for protocol in &connected {
+            scenarios.push(StressScenario::RateDrop { protocol: protocol.clone(), percent: DEFAULT_SHOCK_PERCENT });
+            scenarios.push(StressScenario::Offline { protocol: protocol.clone(), hours: DEFAULT_OFFLINE_HOURS });
+        }
+        let symbols: BTreeSet<_> = connected.iter().filter_map(|p| tokens.get(p)).collect();
+        for token in symbols {
+            scenarios.push(StressScenario::TokenPriceCrash { token: token.clone(), percent: DEFAULT_SHOCK_PERCENT });
+        }
+        scenarios
+    }
+
+    /// Check the shock size
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            StressScenario::RateDrop { percent, .. } | StressScenario::TokenPriceCrash { percent, .. }
+                if !(0.0..=100.0).contains(percent) =>
+            {
+                Err(format!("percent must be between 0 and 100, got {}", percent))
+            }
+            StressScenario::Offline { hours, .. } if !hours.is_finite() || *hours <= 0.0 => {
+                Err(format!("hours must be positive, got {}", hours))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Hours the shock's impact is totalled over
+    pub fn horizon_hours(&self) -> f64 {
+        match self {
+            StressScenario::Offline { hours, .. } => *hours,
+            _ => STRESS_HORIZON_HOURS,
+        }
+    }
+
+    /// `metrics` with the shock applied, and the protocols it hit
+    ///
+    /// Fails if the scenario names a protocol without metrics or a token no
+    /// protocol pays in.
+    pub fn apply(
+        &self,
+        metrics: &AggregatedMetrics,
+        tokens: &HashMap<String, String>,
+    ) -> Result<(AggregatedMetrics, Vec<String>), String> {
+        let mut shocked = metrics.clone();
+        let known = |protocol: &String| {
+            if metrics.earnings_by_protocol.contains_key(protocol) {
+                Ok(vec![protocol.clone()])
+            } else {
+                Err(format!("Protocol {} has no current metrics", protocol))
+            }
+        };
+
+        let (affected, factor) = match self {
+            StressScenario::RateDrop { protocol, percent } => (known(protocol)?, 1.0 - percent / 100.0),
+            StressScenario::Offline { protocol, .. } => {
+                let affected = known(protocol)?;
+                shocked.connection_status.insert(protocol.clone(), false);
+                (affected, 0.0)
+            }
+            StressScenario::TokenPriceCrash { token, percent } => {
+                let mut affected: Vec<_> = metrics
+                    .earnings_by_protocol
+                    .keys()
+                    .filter(|p| tokens.get(*p).is_some_and(|symbol| symbol.eq_ignore_ascii_case(token)))
+                    .cloned()
+                    .collect();
+                if affected.is_empty() {
+                    return Err(format!("No protocol with current metrics is paid in {}", token));
+                }
+                affected.sort();
+                (affected, 1.0 - percent / 100.0)
+            }
+        };
+
+        for protocol in &affected {
+            if let Some(rate) = shocked.earnings_by_protocol.get_mut(protocol) {
+                *rate *= factor;
+            }
+        }
+        shocked.total_earnings_per_hour = shocked.earnings_by_protocol.values().sum();
+        Ok((shocked, affected))
+    }
+}
+
+// ============================================================================
+// RESULTS
+// ============================================================================
+
+/// Outcome of one stress scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressResult {
+    pub scenario: StressScenario,
+    /// Protocols the shock hit
+    pub affected_protocols: Vec<String>,
+    /// Earnings before the shock (USD/hour)
+    pub baseline_earnings_per_hour: f64,
+    /// Earnings under the shock at the current allocation (USD/hour)
+    pub shocked_earnings_per_hour: f64,
+    /// Change in earnings (USD/hour, negative for a loss)
+    pub impact_per_hour: f64,
+    /// Hours the shock is assumed to last
+    pub horizon_hours: f64,
+    /// Change in earnings over the horizon (USD)
+    pub total_impact: f64,
+    /// Allocation the optimizer would move to under the shock
+    pub suggested_allocation: HashMap<String, f64>,
+    /// Net earnings the suggested allocation would win back (USD/hour)
+    pub recovered_per_hour: f64,
+    /// Why the optimizer would not rebalance, if it would not
+    pub blocker: Option<String>,
+}
+
+/// Run `scenario` against `metrics` and plan the optimizer's response
+///
+/// `metrics` should already respect the reallocation constraints, as the
+/// optimization task's do.
+pub fn run_scenario(
+    scenario: &StressScenario,
+    metrics: &AggregatedMetrics,
+    optimizer: &OptimizerSnapshot,
+    reallocation: &ReallocationEngine,
+    tokens: &HashMap<String, String>,
+) -> Result<StressResult, String> {
+    scenario.validate()?;
+    let (shocked, affected_protocols) = scenario.apply(metrics, tokens)?;
+
+    let mut plan = optimizer.calculate_optimal_allocation(&shocked).map_err(|e| e.to_string())?;
+    reallocation.price_plan(&mut plan, &shocked);
+    let opportunities = optimizer.analyze_opportunities(&shocked).map_err(|e| e.to_string())?;
+    let blocker = optimizer.reallocation_blocker(&opportunities, Some(&plan));
+
+    let impact_per_hour = shocked.total_earnings_per_hour - metrics.total_earnings_per_hour;
+    let horizon_hours = scenario.horizon_hours();
+    Ok(StressResult {
+        scenario: scenario.clone(),
+        affected_protocols,
+        baseline_earnings_per_hour: metrics.total_earnings_per_hour,
+        shocked_earnings_per_hour: shocked.total_earnings_per_hour,
+        impact_per_hour,
+        horizon_hours,
+        total_impact: impact_per_hour * horizon_hours,
+        suggested_allocation: plan.allocation,
+        recovered_per_hour: plan.net_benefit.max(0.0),
+        blocker,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::optimizer::{EarningsOptimizer, OptimizerConfig};
+    use crate::orchestration::reallocation::ReallocationConfig;
+    use crate::orchestration::ResourceUtilization;
+    use chrono::Utc;
+
+    fn metrics() -> AggregatedMetrics {
+        let protocols = [("storj", 4.0, 40.0), ("filecoin", 3.0, 30.0), ("streamr", 3.0, 30.0)];
+        AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: 10.0,
+            earnings_by_protocol: protocols.iter().map(|(p, rate, _)| (p.to_string(), *rate)).collect(),
+            allocation_by_protocol: protocols.iter().map(|(p, _, allocation)| (p.to_string(), *allocation)).collect(),
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 50.0,
+                memory_percent: 50.0,
+                bandwidth_percent: 50.0,
+                storage_percent: 50.0,
+            },
+            connection_status: protocols.iter().map(|(p, _, _)| (p.to_string(), true)).collect(),
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        }
+    }
+
+    fn tokens() -> HashMap<String, String> {
+        HashMap::from([("storj".to_string(), "STORJ".to_string()), ("filecoin".to_string(), "FIL".to_string())])
+    }
+
+    #[test]
+    fn test_scenarios_shock_the_named_protocols() {
+        let metrics = metrics();
+        let offline = StressScenario::Offline { protocol: "storj".to_string(), hours: 12.0 };
+        let (shocked, affected) = offline.apply(&metrics, &tokens()).unwrap();
+        assert_eq!(affected, ["storj"]);
+        assert_eq!(shocked.total_earnings_per_hour, 6.0);
+        assert!(!shocked.connection_status["storj"]);
+
+        let crash = StressScenario::TokenPriceCrash { token: "fil".to_string(), percent: 60.0 };
+        let (shocked, affected) = crash.apply(&metrics, &tokens()).unwrap();
+        assert_eq!(affected, ["filecoin"]);
+        assert!((shocked.earnings_by_protocol["filecoin"] - 1.2).abs() < 1e-9);
+
+        let unknown = StressScenario::RateDrop { protocol: "golem".to_string(), percent: 50.0 };
+        assert!(unknown.apply(&metrics, &tokens()).unwrap_err().contains("golem"));
+        let unpaid = StressScenario::TokenPriceCrash { token: "DATA".to_string(), percent: 50.0 };
+        assert!(unpaid.apply(&metrics, &tokens()).is_err());
+        assert!(StressScenario::RateDrop { protocol: "storj".to_string(), percent: 150.0 }.validate().is_err());
+
+        // Two per connected protocol plus one per token
+        assert_eq!(StressScenario::defaults(&metrics, &tokens()).len(), 8);
+    }
+
+    #[test]
+    fn test_run_scenario_reports_impact_and_response() {
+        let metrics = metrics();
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default()).snapshot();
+        let reallocation = ReallocationEngine::new(ReallocationConfig::default());
+        let scenario: StressScenario = serde_json::from_str(r#"{"type": "rate_drop", "protocol": "storj"}"#).unwrap();
+
+        let result = run_scenario(&scenario, &metrics, &optimizer, &reallocation, &tokens()).unwrap();
+        assert_eq!(result.scenario, StressScenario::RateDrop { protocol: "storj".to_string(), percent: 50.0 });
+        assert_eq!((result.baseline_earnings_per_hour, result.shocked_earnings_per_hour), (10.0, 8.0));
+        assert_eq!((result.impact_per_hour, result.total_impact), (-2.0, -48.0));
+        // storj now earns least, so the optimizer moves allocation off it
+        assert!(result.suggested_allocation["storj"] < 40.0);
+    }
+}
@@ -0,0 +1,199 @@
+/// Sliding-Window Metrics Store
+///
+/// Bounded history of aggregated metrics shared by the coordinator, monitor
+/// and optimizer. Snapshots are kept behind `Arc`, so the coordinator's poll
+/// result is stored once however many components keep it.
+///
+/// The window is a ring buffer: pushing onto a full window drops the oldest
+/// snapshot in O(1). Readers take a `WindowSnapshot`, which shares the buffer
+/// instead of copying it; a push while snapshots are held copies only the
+/// buffer's pointers, never the metrics.
+use super::AggregatedMetrics;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+type Entries = VecDeque<Arc<AggregatedMetrics>>;
+
+/// Bounded, oldest-first window of metrics snapshots
+#[derive(Debug)]
+pub struct MetricsWindow {
+    capacity: usize,
+    entries: RwLock<Arc<Entries>>,
+}
+
+impl MetricsWindow {
+    /// Create an empty window keeping at most `capacity` snapshots
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: RwLock::new(Arc::new(VecDeque::new())),
+        }
+    }
+
+    /// Most snapshots the window keeps
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Append `metrics`, dropping the oldest snapshot once the window is full
+    pub fn push(&self, metrics: impl Into<Arc<AggregatedMetrics>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut guard = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let entries = Arc::make_mut(&mut guard);
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(metrics.into());
+    }
+
+    /// Immutable view of the window as it is now
+    pub fn snapshot(&self) -> WindowSnapshot {
+        WindowSnapshot {
+            entries: self.entries.read().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+
+    /// Most recent snapshot
+    pub fn latest(&self) -> Option<Arc<AggregatedMetrics>> {
+        self.entries.read().unwrap_or_else(|e| e.into_inner()).back().cloned()
+    }
+
+    /// Number of snapshots in the window
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether the window holds no snapshots
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keep only the snapshots `keep` accepts
+    pub fn retain(&self, mut keep: impl FnMut(&AggregatedMetrics) -> bool) {
+        let mut guard = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        Arc::make_mut(&mut guard).retain(|m| keep(m));
+    }
+
+    /// Drop every snapshot
+    pub fn clear(&self) {
+        *self.entries.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(VecDeque::new());
+    }
+}
+
+/// Read-only view of a `MetricsWindow`, oldest first
+///
+/// Cheap to clone; later pushes to the window do not change it.
+#[derive(Debug, Clone, Default)]
+pub struct WindowSnapshot {
+    entries: Arc<Entries>,
+}
+
+impl WindowSnapshot {
+    /// Snapshots, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &AggregatedMetrics> + ExactSizeIterator {
+        self.entries.iter().map(|m| m.as_ref())
+    }
+
+    /// Number of snapshots
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no snapshots
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Oldest snapshot
+    pub fn first(&self) -> Option<&AggregatedMetrics> {
+        self.entries.front().map(|m| m.as_ref())
+    }
+
+    /// Most recent snapshot
+    pub fn last(&self) -> Option<&AggregatedMetrics> {
+        self.entries.back().map(|m| m.as_ref())
+    }
+
+    /// Snapshots taken between `start` and `end` inclusive, oldest first
+    pub fn between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> impl Iterator<Item = &AggregatedMetrics> {
+        self.iter().filter(move |m| m.timestamp >= start && m.timestamp <= end)
+    }
+
+    /// Shared handles to the snapshots taken between `start` and `end` inclusive
+    pub fn shared_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Arc<AggregatedMetrics>> {
+        self.entries
+            .iter()
+            .filter(|m| m.timestamp >= start && m.timestamp <= end)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::ResourceUtilization;
+    use std::collections::HashMap;
+
+    fn metrics(total: f64) -> AggregatedMetrics {
+        AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: total,
+            earnings_by_protocol: HashMap::new(),
+            allocation_by_protocol: HashMap::new(),
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 0.0,
+                memory_percent: 0.0,
+                bandwidth_percent: 0.0,
+                storage_percent: 0.0,
+            },
+            connection_status: HashMap::new(),
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        }
+    }
+
+    fn totals(snapshot: &WindowSnapshot) -> Vec<f64> {
+        snapshot.iter().map(|m| m.total_earnings_per_hour).collect()
+    }
+
+    #[test]
+    fn test_window_drops_oldest_when_full() {
+        let window = MetricsWindow::new(3);
+        for total in 1..=5 {
+            window.push(metrics(total as f64));
+        }
+
+        assert_eq!(window.len(), 3);
+        assert_eq!(totals(&window.snapshot()), [3.0, 4.0, 5.0]);
+        assert_eq!(window.latest().unwrap().total_earnings_per_hour, 5.0);
+
+        window.retain(|m| m.total_earnings_per_hour != 4.0);
+        assert_eq!(totals(&window.snapshot()), [3.0, 5.0]);
+        window.clear();
+        assert!(window.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_is_unaffected_by_later_pushes() {
+        let window = MetricsWindow::new(2);
+        let shared = Arc::new(metrics(1.0));
+        window.push(shared.clone());
+        let before = window.snapshot();
+
+        window.push(metrics(2.0));
+        window.push(metrics(3.0));
+
+        assert_eq!(totals(&before), [1.0]);
+        assert!(Arc::ptr_eq(&before.shared_between(shared.timestamp, Utc::now())[0], &shared));
+        assert_eq!(totals(&window.snapshot()), [2.0, 3.0]);
+        // The window stored the caller's snapshot, not a copy of it
+        assert!(Arc::ptr_eq(&before.entries[0], &shared));
+    }
+}
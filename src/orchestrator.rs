@@ -0,0 +1,607 @@
+/// Embeddable Orchestrator
+///
+/// [`Orchestrator::builder`] wires the coordinator, optimizer, reallocation
+/// engine, monitor and background schedulers together — and optionally the
+/// HTTP/WebSocket server — the same way the `depin-orcha` binary does, so
+/// other Rust programs can embed the orchestrator instead of running it:
+///
+/// ```no_run
+/// # use depin_orcha::protocols::ProtocolAdapter;
+/// # async fn run(adapter: Box<dyn ProtocolAdapter>) -> Result<(), Box<dyn std::error::Error>> {
+/// let orchestrator = depin_orcha::Orchestrator::builder()
+///     .with_config(depin_orcha::config::Config::load()?)
+///     .with_adapter("storj", adapter)
+///     .with_http_server()
+///     .start()
+///     .await?;
+/// orchestrator.wait().await?;
+/// # Ok(())
+/// # }
+/// ```
+use actix_web::{dev::Server, middleware, web, App, HttpServer};
+use sqlx::SqlitePool;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+
+use crate::api::access_log::AccessLog;
+use crate::api::middleware::{JwtConfig, RateLimiter};
+use crate::api::sessions::LoginThrottle;
+use crate::api::{dashboard::DashboardCache, export::ExportConfig, routes::configure_routes, websocket, AppState};
+use crate::config::{Config, ConfigError};
+use crate::containers::ContainerManager;
+use crate::db::{init_pool, queries, run_migrations};
+use crate::events::{EventBus, EventSubscriber};
+use crate::notifications::{AlertNotifier, NotificationDispatcher, NotificationsConfig};
+use crate::protocols::http::HttpClientFactory;
+use crate::protocols::registry::ProtocolInstance;
+use crate::protocols::ProtocolAdapter;
+use crate::scheduler::{self, SchedulerStatus};
+use crate::secrets::SecretsVault;
+use crate::services::ServiceManager;
+use crate::shutdown::Shutdown;
+use crate::wallets::{WalletTracker, WalletsConfig};
+use crate::{EarningsOptimizer, MlOptimizer, ProtocolCoordinator, RealtimeMonitor, ReallocationEngine};
+
+/// Metrics snapshots the coordinator keeps in memory
+const COORDINATOR_HISTORY: usize = 1000;
+
+/// Error starting an [`Orchestrator`]
+#[derive(Error, Debug)]
+pub enum StartError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Failed to start HTTP server: {0}")]
+    Server(#[from] std::io::Error),
+}
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+/// Fluent configuration of an [`Orchestrator`]
+#[derive(Default)]
+pub struct OrchestratorBuilder {
+    config: Config,
+    db_pool: Option<SqlitePool>,
+    adapters: Vec<ProtocolInstance>,
+    subscribers: Vec<(String, Arc<dyn EventSubscriber>)>,
+    containers: Option<Arc<ContainerManager>>,
+    services: Option<Arc<ServiceManager>>,
+    vault: Option<SecretsVault>,
+    jwt: Option<JwtConfig>,
+    http: bool,
+}
+
+impl OrchestratorBuilder {
+    /// Use `config` instead of the defaults
+    ///
+    /// Protocol adapters are not created from `config.protocols`; register
+    /// them with [`with_adapter`](Self::with_adapter).
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Use an existing database pool instead of opening `config.database`
+    ///
+    /// The pool must come from [`init_pool`], which applies the migrations.
+    pub fn with_db(mut self, pool: SqlitePool) -> Self {
+        self.db_pool = Some(pool);
+        self
+    }
+
+    /// Register a protocol adapter as an instance of the protocol type of the
+    /// same name; it is connected on start
+    pub fn with_adapter(mut self, name: impl Into<String>, adapter: Box<dyn ProtocolAdapter>) -> Self {
+        let id = name.into();
+        self.adapters.push(ProtocolInstance { protocol_type: id.clone(), id, adapter });
+        self
+    }
+
+    /// Register a protocol instance, e.g. a second Storj node; it is connected on start
+    pub fn with_instance(mut self, instance: ProtocolInstance) -> Self {
+        self.adapters.push(instance);
+        self
+    }
+
+    /// Register an event subscriber before any event is published
+    pub fn with_subscriber(mut self, name: impl Into<String>, subscriber: Arc<dyn EventSubscriber>) -> Self {
+        self.subscribers.push((name.into(), subscriber));
+        self
+    }
+
+    /// Expose the Docker containers protocol nodes run in to the API
+    pub fn with_containers(mut self, containers: Arc<ContainerManager>) -> Self {
+        self.containers = Some(containers);
+        self
+    }
+
+    /// Expose the systemd units protocol daemons run as to the API
+    pub fn with_services(mut self, services: Arc<ServiceManager>) -> Self {
+        self.services = Some(services);
+        self
+    }
+
+    /// Serve the credentials API from `vault`
+    pub fn with_vault(mut self, vault: SecretsVault) -> Self {
+        self.vault = Some(vault);
+        self
+    }
+
+    /// Sign bearer tokens with `jwt` instead of loading it from `JWT_SECRET`
+    pub fn with_jwt(mut self, jwt: JwtConfig) -> Self {
+        self.jwt = Some(jwt);
+        self
+    }
+
+    /// Also serve the HTTP API, dashboard and WebSocket at `config.server`
+    ///
+    /// Starting fails if no [`JwtConfig`] was given and `JWT_SECRET` is unset.
+    pub fn with_http_server(mut self) -> Self {
+        self.http = true;
+        self
+    }
+
+    /// Validate the configuration, connect the adapters, and start the
+    /// background schedulers (and the HTTP server, if enabled)
+    pub async fn start(self) -> Result<Orchestrator, StartError> {
+        let OrchestratorBuilder {
+            config,
+            db_pool,
+            adapters,
+            subscribers,
+            containers,
+            services,
+            vault,
+            jwt,
+            http,
+        } = self;
+        config.validate()?;
+        let jwt = match jwt {
+            Some(jwt) => Some(jwt),
+            None if http => Some(JwtConfig::from_env()?),
+            None => None,
+        };
+        let account_id = config.server.account_id;
+
+        let db_pool = match db_pool {
+            Some(pool) => {
+                run_migrations(&pool).await?;
+                pool
+            }
+            None => init_pool(config.database.clone()).await?,
+        };
+
+        let mut coordinator = ProtocolCoordinator::new(COORDINATOR_HISTORY)
+            .with_resilience(config.resilience.clone())
+            .with_cache(config.cache.clone())
+            .with_account(account_id);
+        for ProtocolInstance { id, protocol_type, mut adapter } in adapters {
+            if let Err(e) = adapter.connect().await {
+                log::warn!("⚠️  {} failed to connect: {}", id, e);
+            }
+            if let Err(e) = queries::upsert_protocol_instance(&db_pool, account_id, &id, &protocol_type).await {
+                log::warn!("⚠️  Failed to record protocol instance {}: {}", id, e);
+            }
+            coordinator.register_instance(id, protocol_type, adapter);
+        }
+        let coordinator = Arc::new(coordinator);
+        log::info!(
+            "✅ Protocol Coordinator initialized with {} protocols",
+            coordinator.registered_protocols().len()
+        );
+
+        log::info!("   Optimization strategy: {:?}", config.optimizer.strategy);
+        let optimizer = Arc::new(EarningsOptimizer::new(config.optimizer.clone()));
+        let ml_optimizer = Arc::new(MlOptimizer::new(config.ml_engine.clone()));
+        if ml_optimizer.config().enabled {
+            log::info!("✅ ML Optimizer enabled ({})", ml_optimizer.config().api_url);
+        }
+
+        let reallocation_config = config.reallocation_config();
+        if reallocation_config.require_confirmation {
+            log::info!("   Reallocations require approval via /api/v1/reallocations/pending");
+        }
+        let shutdown = Arc::new(Shutdown::new());
+        let reallocation = Arc::new(
+            ReallocationEngine::new(reallocation_config)
+                .with_shutdown(shutdown.token())
+                .with_resilience(config.resilience.clone())
+                .with_protocol_types(coordinator.protocol_types().clone()),
+        );
+        let monitor =
+            Arc::new(RealtimeMonitor::new(config.monitor_config()).with_database(db_pool.clone(), account_id));
+
+        let (scheduler_tx, scheduler_rx) = tokio::sync::watch::channel(config.scheduler.clone());
+        let scheduler_status = Arc::new(SchedulerStatus::new());
+        let events = Arc::new(EventBus::default());
+        let state = web::Data::new(AppState {
+            coordinator: coordinator.clone(),
+            optimizer: optimizer.clone(),
+            reallocation: reallocation.clone(),
+            monitor: monitor.clone(),
+            dashboard: Arc::new(DashboardCache::new()),
+            ml_optimizer: Some(ml_optimizer),
+            containers,
+            services,
+            ws_hub: Arc::new(websocket::WsHub::with_config(config.websocket.clone())),
+            events: events.clone(),
+            scheduler_config: Arc::new(scheduler_tx),
+            scheduler_status: scheduler_status.clone(),
+            shutdown: shutdown.token(),
+        });
+
+        let notifications = Arc::new(NotificationDispatcher::new(
+            NotificationsConfig::from_table(&config.raw).unwrap_or_else(|e| {
+                log::error!("❌ Invalid notification configuration: {}", e);
+                NotificationsConfig::default()
+            }),
+        ));
+        if !notifications.is_empty() {
+            log::info!("✅ Alert notifications enabled ({})", notifications.channels().join(", "));
+        }
+
+        // Subscribe before the schedulers publish their first events
+        shutdown.track(events.register("monitor", monitor, shutdown.token()));
+        shutdown.track(events.register("ws_hub", state.ws_hub.clone(), shutdown.token()));
+        shutdown.track(events.register(
+            "notifier",
+            Arc::new(AlertNotifier::new(notifications.clone(), db_pool.clone(), account_id)),
+            shutdown.token(),
+        ));
+        for (name, subscriber) in subscribers {
+            shutdown.track(events.register(&name, subscriber, shutdown.token()));
+        }
+
+        log::info!("🔧 Starting background schedulers...");
+        // Before the tasks store their first metrics, which would end "first run"
+        scheduler::start_backfill(coordinator.clone(), optimizer.clone(), db_pool.clone(), &config.scheduler, &shutdown)
+            .await;
+        match HttpClientFactory::new(config.http.clone()) {
+            Ok(http) => {
+                match http.client("") {
+                    Ok(client) => scheduler::start_network_probes(
+                        config.network_probes.clone(),
+                        client,
+                        coordinator.clone(),
+                        reallocation.clone(),
+                        db_pool.clone(),
+                        scheduler_status.clone(),
+                        &shutdown,
+                    ),
+                    Err(e) => log::error!("❌ Network probes disabled: {}", e),
+                }
+                scheduler::start_ip_checks(
+                    config.ip_intel.clone(),
+                    http,
+                    coordinator.clone(),
+                    db_pool.clone(),
+                    events.clone(),
+                    scheduler_status.clone(),
+                    &shutdown,
+                )
+            }
+            Err(e) => log::error!("❌ Public IP checks and network probes disabled: {}", e),
+        }
+        scheduler::start_schedulers(
+            coordinator,
+            state.dashboard.clone(),
+            optimizer,
+            reallocation,
+            db_pool.clone(),
+            events,
+            scheduler_rx,
+            config.reporting.timezone,
+            scheduler_status.clone(),
+            &shutdown,
+        );
+        match WalletsConfig::from_table(&config.raw) {
+            Ok(wallets_config) => scheduler::start_wallet_tracking(
+                WalletTracker::new(wallets_config),
+                db_pool.clone(),
+                account_id,
+                scheduler_status.clone(),
+                &shutdown,
+            ),
+            Err(e) => log::error!("❌ Invalid wallet configuration: {}", e),
+        }
+        scheduler::start_backups(config.backup.clone(), db_pool.clone(), scheduler_status.clone(), &shutdown);
+        scheduler::start_digests(
+            notifications.clone(),
+            db_pool.clone(),
+            account_id,
+            config.reporting.timezone,
+            scheduler_status,
+            &shutdown,
+        );
+        log::info!("✅ Background schedulers started successfully");
+
+        let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout);
+        let mut orchestrator = Orchestrator {
+            state,
+            db_pool,
+            shutdown,
+            shutdown_timeout,
+            server: None,
+            http_addrs: Vec::new(),
+        };
+        if let Some(jwt) = jwt.filter(|_| http) {
+            let (server, addrs) = orchestrator.serve(config, notifications, vault, jwt)?;
+            orchestrator.server = Some(server);
+            orchestrator.http_addrs = addrs;
+        }
+        Ok(orchestrator)
+    }
+}
+
+// ============================================================================
+// ORCHESTRATOR
+// ============================================================================
+
+/// A running orchestrator
+///
+/// Runs until [`stop`](Self::stop) is called; [`wait`](Self::wait) then lets
+/// the background tasks finish their current work.
+pub struct Orchestrator {
+    state: web::Data<AppState>,
+    db_pool: SqlitePool,
+    shutdown: Arc<Shutdown>,
+    /// How long [`wait`](Self::wait) lets background tasks finish
+    shutdown_timeout: Duration,
+    server: Option<JoinHandle<std::io::Result<()>>>,
+    http_addrs: Vec<SocketAddr>,
+}
+
+impl Orchestrator {
+    /// Start configuring an orchestrator
+    pub fn builder() -> OrchestratorBuilder {
+        OrchestratorBuilder::default()
+    }
+
+    /// Shared state the API handlers run against
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    pub fn coordinator(&self) -> &Arc<ProtocolCoordinator> {
+        &self.state.coordinator
+    }
+
+    pub fn optimizer(&self) -> &Arc<EarningsOptimizer> {
+        &self.state.optimizer
+    }
+
+    pub fn reallocation(&self) -> &Arc<ReallocationEngine> {
+        &self.state.reallocation
+    }
+
+    pub fn monitor(&self) -> &Arc<RealtimeMonitor> {
+        &self.state.monitor
+    }
+
+    /// Bus the background tasks publish their events to
+    pub fn events(&self) -> &Arc<EventBus> {
+        &self.state.events
+    }
+
+    pub fn db_pool(&self) -> &SqlitePool {
+        &self.db_pool
+    }
+
+    /// Addresses the HTTP server listens on; empty without one
+    pub fn http_addrs(&self) -> &[SocketAddr] {
+        &self.http_addrs
+    }
+
+    /// Shutdown signal; trigger it from another task to stop the
+    /// orchestrator, or track extra tasks for [`wait`](Self::wait) to drain
+    pub fn shutdown(&self) -> &Arc<Shutdown> {
+        &self.shutdown
+    }
+
+    /// Register an event subscriber; it only sees events published from now on
+    pub fn subscribe(&self, name: &str, subscriber: Arc<dyn EventSubscriber>) {
+        self.shutdown.track(self.state.events.register(name, subscriber, self.shutdown.token()));
+    }
+
+    /// Start shutting down: stop the HTTP server and background loops
+    pub fn stop(&self) {
+        self.shutdown.trigger();
+    }
+
+    /// Run until stopped, then wait for background tasks to finish their
+    /// current work and close the database pool
+    ///
+    /// Returns the HTTP server's error, if it failed.
+    pub async fn wait(self) -> std::io::Result<()> {
+        let result = match self.server {
+            Some(server) => {
+                let result = server.await.unwrap_or_else(|e| Err(std::io::Error::other(e)));
+                log::info!("✅ Server shutdown complete");
+                result
+            }
+            None => {
+                self.shutdown.token().cancelled().await;
+                Ok(())
+            }
+        };
+        // The server may also have stopped on its own
+        self.shutdown.trigger();
+
+        if self.shutdown.drain(self.shutdown_timeout).await {
+            log::info!("✅ Background tasks stopped");
+        } else {
+            log::warn!(
+                "⚠️  Background tasks still running after {}s, aborted",
+                self.shutdown_timeout.as_secs()
+            );
+        }
+        self.db_pool.close().await;
+        result
+    }
+
+    /// Start the HTTP server; returns its task and listening addresses
+    fn serve(
+        &self,
+        config: Config,
+        notifications: Arc<NotificationDispatcher>,
+        vault: Option<SecretsVault>,
+        jwt: JwtConfig,
+    ) -> std::io::Result<(JoinHandle<std::io::Result<()>>, Vec<SocketAddr>)> {
+        let api_config = config.server.clone();
+        let bind_address = format!("{}:{}", api_config.host, api_config.port);
+        log::info!("🌐 Starting HTTP server at http://{}", bind_address);
+
+        let app_state = self.state.clone();
+        let db_pool = self.db_pool.clone();
+        let db_pool_arc = Arc::new(db_pool.clone());
+        let export_config = web::Data::new(ExportConfig::from_table(&config.raw));
+        let display_config = web::Data::new(config.display.clone());
+        let reporting_timezone = web::Data::new(config.reporting.timezone);
+        let access_log = if config.access_log.enabled {
+            let access_log = AccessLog::new(config.access_log.clone(), db_pool.clone())?;
+            log::info!("📝 Access log enabled ({:?} sink)", config.access_log.sink);
+            Some(web::Data::new(access_log))
+        } else {
+            None
+        };
+        let notifications = web::Data::from(notifications);
+        let config = web::Data::new(config);
+        let vault = vault.map(web::Data::new);
+        let jwt_config = web::Data::new(jwt);
+        let login_throttle = web::Data::new(LoginThrottle::default());
+        let rate_limiter = Arc::new(RateLimiter::new(config.rate_limits.clone()));
+        self.shutdown.track(rate_limiter.clone().start_persistence(
+            db_pool_arc.clone(),
+            Duration::from_secs(60),
+            self.shutdown.token(),
+        ));
+
+        let server = HttpServer::new(move || {
+            App::new()
+                // Add application state
+                .app_data(app_state.clone())
+                .app_data(web::Data::new(db_pool.clone()))
+                .app_data(export_config.clone())
+                .app_data(display_config.clone())
+                .app_data(reporting_timezone.clone())
+                .app_data(notifications.clone())
+                .app_data(jwt_config.clone())
+                .app_data(login_throttle.clone())
+                .app_data(config.clone())
+                .configure(|cfg| {
+                    if let Some(vault) = &vault {
+                        cfg.app_data(vault.clone());
+                    }
+                    if let Some(access_log) = &access_log {
+                        cfg.app_data(access_log.clone());
+                    }
+                })
+                // Add middleware
+                .wrap(middleware::Logger::new(
+                    r#"%a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T request_id=%{x-request-id}o"#,
+                ))
+                .wrap(middleware::Compress::default())
+                // Configure API routes with database pool
+                .configure(|cfg| {
+                    configure_routes(
+                        cfg,
+                        db_pool_arc.clone(),
+                        jwt_config.clone().into_inner(),
+                        rate_limiter.clone(),
+                    )
+                })
+                // Add WebSocket endpoint
+                .route("/ws", web::get().to(websocket::ws_handler))
+                // Add health check at root
+                .route("/", web::get().to(|| async { "DePIN-Orcha Orchestration System v1.0.0" }))
+        })
+        .workers(api_config.workers)
+        .shutdown_timeout(api_config.shutdown_timeout)
+        // Embedding programs own signal handling; `stop` ends the server
+        .disable_signals()
+        .bind(&bind_address)?;
+        let addrs = server.addrs();
+        let server: Server = server.run();
+
+        let handle = server.handle();
+        let stopped = self.shutdown.token();
+        tokio::spawn(async move {
+            stopped.cancelled().await;
+            handle.stop(true).await;
+        });
+
+        log::info!("✅ HTTP server started successfully");
+        log::info!("📡 WebSocket endpoint: ws://{}/ws", bind_address);
+        log::info!("🏥 Health check: http://{}/api/v1/health", bind_address);
+        log::info!("📊 Dashboard: http://{}/api/v1/dashboard", bind_address);
+        Ok((tokio::spawn(server), addrs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::OrchestratorEvent;
+    use crate::protocols::mock::{EarningsCurve, MockAdapter};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct MetricsCounter(AtomicUsize);
+
+    #[async_trait]
+    impl EventSubscriber for MetricsCounter {
+        async fn on_event(&self, event: &OrchestratorEvent) {
+            if let OrchestratorEvent::MetricsCollected(_) = event {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_builder_wires_adapters_subscribers_and_server() {
+        let mut config = Config::default();
+        config.database.database_url = "sqlite::memory:".to_string();
+        config.database.max_connections = 1;
+        config.database.min_connections = 1;
+        config.scheduler.poll_interval = 1;
+        // Validation rejects port 0, so reserve a free port up front
+        config.server.port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        config.server.workers = 1;
+        config.server.shutdown_timeout = 1;
+
+        let counter = Arc::new(MetricsCounter::default());
+        let orchestrator = Orchestrator::builder()
+            .with_config(config)
+            .with_adapter("mock", Box::new(MockAdapter::new("mock", EarningsCurve::Constant(2.0), 100.0)))
+            .with_subscriber("counter", counter.clone())
+            .with_jwt(JwtConfig::new("test-secret", Duration::from_secs(60)))
+            .with_http_server()
+            .start()
+            .await
+            .unwrap();
+        assert_eq!(orchestrator.coordinator().registered_protocols(), ["mock"]);
+
+        let addr = orchestrator.http_addrs()[0];
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(counter.0.load(Ordering::SeqCst) >= 1);
+
+        orchestrator.stop();
+        tokio::time::timeout(Duration::from_secs(10), orchestrator.wait()).await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,546 @@
+/// Akash Compute Provider Adapter
+///
+/// Akash is a decentralized cloud marketplace where providers lease CPU, memory,
+/// and storage to tenants in exchange for AKT.
+///
+/// This adapter manages:
+/// - Connection to the Akash provider daemon status endpoint
+/// - Active lease tracking and leased resource totals
+/// - AKT revenue derived from bid pricing
+/// - Resource offering (CPU/memory/storage units) driven by allocation percent
+use super::{
+    AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// uakt per AKT
+const UAKT_PER_AKT: f64 = 1_000_000.0;
+
+/// Approximate Akash blocks per hour (~6s block time)
+const BLOCKS_PER_HOUR: f64 = 600.0;
+
+/// Bytes per GB
+const BYTES_PER_GB: f64 = 1_000_000_000.0;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Akash protocol configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AkashConfig {
+    /// Provider daemon status endpoint
+    pub provider_status_url: String,
+    /// Provider account address (akash1...)
+    pub provider_address: String,
+    /// AKT price used for USD conversion
+    pub akt_price_usd: f64,
+    /// Host CPU cores available to the provider
+    pub host_cpu_cores: u32,
+    /// Host memory available to the provider in GB
+    pub host_memory_gb: f64,
+    /// Host storage available to the provider in GB
+    pub host_storage_gb: f64,
+    /// Bid price per CPU core per block (uakt)
+    pub bid_price_cpu_uakt: f64,
+    /// Bid price per GB memory per block (uakt)
+    pub bid_price_memory_uakt: f64,
+    /// Bid price per GB storage per block (uakt)
+    pub bid_price_storage_uakt: f64,
+    /// Where to write the computed resource offering for the provider's
+    /// inventory operator to pick up
+    pub offering_path: Option<PathBuf>,
+    /// Minimum allocation percent
+    pub min_allocation_percent: f64,
+    /// Maximum allocation percent
+    pub max_allocation_percent: f64,
+}
+
+impl Default for AkashConfig {
+    fn default() -> Self {
+        Self {
+            provider_status_url: "https://localhost:8443/status".to_string(),
+            provider_address: String::new(),
+            akt_price_usd: 3.0,
+            host_cpu_cores: 16,
+            host_memory_gb: 64.0,
+            host_storage_gb: 1000.0,
+            bid_price_cpu_uakt: 1.2,
+            bid_price_memory_uakt: 0.8,
+            bid_price_storage_uakt: 0.02,
+            offering_path: None,
+            min_allocation_percent: 10.0,
+            max_allocation_percent: 70.0,
+        }
+    }
+}
+
+// ============================================================================
+// PROVIDER STATUS
+// ============================================================================
+
+/// Resource units offered to the Akash marketplace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderOffering {
+    /// CPU offered in millicores
+    pub cpu_millicores: u64,
+    /// Memory offered in GB
+    pub memory_gb: f64,
+    /// Storage offered in GB
+    pub storage_gb: f64,
+}
+
+impl ProviderOffering {
+    /// Offering for a share of host capacity
+    fn for_allocation(config: &AkashConfig, allocation_percent: f64) -> Self {
+        let share = allocation_percent / 100.0;
+        Self {
+            cpu_millicores: (config.host_cpu_cores as f64 * 1000.0 * share) as u64,
+            memory_gb: config.host_memory_gb * share,
+            storage_gb: config.host_storage_gb * share,
+        }
+    }
+}
+
+/// Subset of the provider `/status` response
+#[derive(Debug, Deserialize)]
+struct ProviderStatus {
+    cluster: ClusterStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClusterStatus {
+    leases: u32,
+    inventory: Inventory,
+}
+
+#[derive(Debug, Deserialize)]
+struct Inventory {
+    #[serde(default)]
+    active: Vec<LeaseResources>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LeaseResources {
+    /// Millicores
+    cpu: u64,
+    /// Bytes
+    memory: u64,
+    /// Bytes
+    #[serde(default)]
+    storage_ephemeral: u64,
+}
+
+// ============================================================================
+// INTERNAL STATE
+// ============================================================================
+
+/// Akash metrics tracked internally
+#[derive(Debug, Clone, Default)]
+struct AkashMetrics {
+    active_leases: u32,
+    leased: ProviderOffering,
+    connected_at: Option<DateTime<Utc>>,
+    last_refresh: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// Akash Compute Provider Adapter
+pub struct AkashAdapter {
+    config: AkashConfig,
+    http: reqwest::Client,
+    status: Arc<RwLock<ConnectionStatus>>,
+    allocation: Arc<RwLock<AllocationStrategy>>,
+    offering: Arc<RwLock<ProviderOffering>>,
+    metrics: Arc<RwLock<AkashMetrics>>,
+}
+
+impl AkashAdapter {
+    /// Create a new Akash adapter
+    pub fn new(config: AkashConfig) -> Self {
+        let allocation_percent = 30.0;
+        let offering = ProviderOffering::for_allocation(&config, allocation_percent);
+        let allocation = AllocationStrategy {
+            cpu_cores: (offering.cpu_millicores / 1000) as u32,
+            memory_gb: offering.memory_gb,
+            storage_gb: offering.storage_gb,
+            bandwidth_mbps: 100.0,
+            allocation_percent,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+
+        Self {
+            config,
+            // Provider daemons commonly serve self-signed certificates
+            http: reqwest::Client::builder()
+                .danger_accept_invalid_certs(true)
+                .build()
+                .unwrap_or_default(),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            allocation: Arc::new(RwLock::new(allocation)),
+            offering: Arc::new(RwLock::new(offering)),
+            metrics: Arc::new(RwLock::new(AkashMetrics::default())),
+        }
+    }
+
+    /// Current resource offering
+    pub async fn offering(&self) -> ProviderOffering {
+        self.offering.read().await.clone()
+    }
+
+    /// Refresh lease state from the provider daemon
+    async fn refresh(&self) -> ProtocolResult<()> {
+        let response = self
+            .http
+            .get(&self.config.provider_status_url)
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ProtocolError::ApiError(format!(
+                "Provider status returned {}",
+                response.status()
+            )));
+        }
+
+        let status: ProviderStatus = response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))?;
+
+        let leased = status.cluster.inventory.active.iter().fold(
+            ProviderOffering::default(),
+            |mut acc, lease| {
+                acc.cpu_millicores += lease.cpu;
+                acc.memory_gb += lease.memory as f64 / BYTES_PER_GB;
+                acc.storage_gb += lease.storage_ephemeral as f64 / BYTES_PER_GB;
+                acc
+            },
+        );
+
+        let mut metrics = self.metrics.write().await;
+        metrics.active_leases = status.cluster.leases;
+        metrics.leased = leased;
+        metrics.last_refresh = Some(Utc::now());
+        Ok(())
+    }
+
+    /// AKT revenue per hour for currently leased resources
+    fn hourly_revenue_akt(&self, leased: &ProviderOffering) -> f64 {
+        let per_block_uakt = (leased.cpu_millicores as f64 / 1000.0) * self.config.bid_price_cpu_uakt
+            + leased.memory_gb * self.config.bid_price_memory_uakt
+            + leased.storage_gb * self.config.bid_price_storage_uakt;
+
+        per_block_uakt * BLOCKS_PER_HOUR / UAKT_PER_AKT
+    }
+
+    /// Persist the offering for the provider's inventory operator
+    async fn publish_offering(&self, offering: &ProviderOffering) -> ProtocolResult<()> {
+        if let Some(path) = &self.config.offering_path {
+            let json = serde_json::to_vec_pretty(offering)
+                .map_err(|e| ProtocolError::DataError(e.to_string()))?;
+            tokio::fs::write(path, json)
+                .await
+                .map_err(|e| ProtocolError::ResourceError(format!("{}: {}", path.display(), e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for AkashAdapter {
+    fn protocol_name(&self) -> &str {
+        "Akash Network"
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        if self.config.provider_address.is_empty() {
+            return Err(ProtocolError::ConfigurationError(
+                "Provider address not configured".to_string(),
+            ));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        if let Err(e) = self.refresh().await {
+            *self.status.write().await = ConnectionStatus::Failed;
+            return Err(ProtocolError::ConnectionError(format!(
+                "Akash provider unreachable: {}",
+                e
+            )));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.metrics.write().await.connected_at = Some(Utc::now());
+
+        tracing::info!("Connected to Akash provider {}", self.config.provider_address);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        if *self.status.read().await == ConnectionStatus::Disconnected {
+            return Ok(());
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.metrics.write().await.connected_at = None;
+
+        tracing::info!("Disconnected from Akash Network");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .try_read()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Connecting)
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.refresh().await?;
+
+        let metrics = self.metrics.read().await;
+        let revenue_akt = self.hourly_revenue_akt(&metrics.leased);
+
+        let mut metric_map = HashMap::new();
+        metric_map.insert("active_leases".to_string(), metrics.active_leases as f64);
+        metric_map.insert("revenue_akt_per_hour".to_string(), revenue_akt);
+        metric_map.insert(
+            "leased_cpu_cores".to_string(),
+            metrics.leased.cpu_millicores as f64 / 1000.0,
+        );
+        metric_map.insert("leased_memory_gb".to_string(), metrics.leased.memory_gb);
+        metric_map.insert("leased_storage_gb".to_string(), metrics.leased.storage_gb);
+
+        Ok(EarningsData {
+            timestamp: Utc::now(),
+            amount_usd: revenue_akt * self.config.akt_price_usd,
+            protocol_id: "akash".to_string(),
+            metrics: metric_map,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // The provider daemon only exposes current leases
+        Err(ProtocolError::UnsupportedError(
+            "The Akash provider does not expose historical earnings".to_string(),
+        ))
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        let metrics = self.metrics.read().await;
+        let uptime_seconds = metrics
+            .connected_at
+            .map(|t| Utc::now().signed_duration_since(t).num_seconds().max(0) as u64)
+            .unwrap_or(0);
+
+        Ok(ResourceMetrics {
+            cpu_percent: (metrics.leased.cpu_millicores as f64
+                / (self.config.host_cpu_cores as f64 * 1000.0).max(1.0)
+                * 100.0)
+                .min(100.0),
+            memory_mb: metrics.leased.memory_gb * 1024.0,
+            bandwidth_mbps: 50.0,
+            storage_gb: metrics.leased.storage_gb,
+            uptime_seconds,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        if !(self.config.min_allocation_percent..=self.config.max_allocation_percent)
+            .contains(&strategy.allocation_percent)
+        {
+            return Err(ProtocolError::AllocationError(format!(
+                "Allocation must be between {} and {}%",
+                self.config.min_allocation_percent, self.config.max_allocation_percent
+            )));
+        }
+
+        let offering = ProviderOffering::for_allocation(&self.config, strategy.allocation_percent);
+
+        // Active leases cannot be evicted; the offering must still cover them.
+        let leased = self.metrics.read().await.leased.clone();
+        if offering.cpu_millicores < leased.cpu_millicores
+            || offering.memory_gb < leased.memory_gb
+            || offering.storage_gb < leased.storage_gb
+        {
+            return Err(ProtocolError::AllocationError(
+                "Offering would drop below resources held by active leases".to_string(),
+            ));
+        }
+
+        self.publish_offering(&offering).await?;
+
+        *self.allocation.write().await = AllocationStrategy {
+            cpu_cores: (offering.cpu_millicores / 1000) as u32,
+            memory_gb: offering.memory_gb,
+            storage_gb: offering.storage_gb,
+            ..strategy
+        };
+        *self.offering.write().await = offering;
+
+        tracing::info!("Applied allocation strategy to Akash");
+        Ok(())
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        Ok(self.allocation.read().await.clone())
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let status = *self.status.read().await;
+        let is_healthy = status == ConnectionStatus::Connected;
+        let metrics = self.metrics.read().await;
+        let offering = self.offering.read().await;
+
+        let mut health_metrics = HashMap::new();
+        health_metrics.insert("active_leases".into(), serde_json::json!(metrics.active_leases));
+        health_metrics.insert("offering".into(), serde_json::json!(*offering));
+
+        Ok(HealthStatus {
+            is_healthy,
+            connection_status: status,
+            last_operation: metrics.last_refresh,
+            error_message: if is_healthy {
+                None
+            } else {
+                Some("Not connected to Akash provider".to_string())
+            },
+            metrics: health_metrics,
+        })
+    }
+
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        // Provider daemons commonly serve self-signed certificates
+        self.http = http
+            .builder(protocol)?
+            .danger_accept_invalid_certs(true)
+            .build()
+            .map_err(|e| ProtocolError::ConfigurationError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "protocol": "akash",
+            "provider_status_url": self.config.provider_status_url,
+            "provider_address": self.config.provider_address,
+            "bid_price_cpu_uakt": self.config.bid_price_cpu_uakt,
+            "bid_price_memory_uakt": self.config.bid_price_memory_uakt,
+            "bid_price_storage_uakt": self.config.bid_price_storage_uakt,
+            "min_allocation_percent": self.config.min_allocation_percent,
+            "max_allocation_percent": self.config.max_allocation_percent,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(
+            ResourceKind::Cpu,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        )
+        .with_secondary(&[ResourceKind::Memory, ResourceKind::Storage])
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new(
+                "active_leases",
+                "count",
+                MetricAggregation::Last,
+                "Leases currently running on the provider",
+            ),
+            MetricSchema::new("revenue_akt_per_hour", "AKT/hour", MetricAggregation::Mean, "Lease revenue rate"),
+            MetricSchema::new("leased_cpu_cores", "cores", MetricAggregation::Mean, "CPU cores leased to tenants"),
+            MetricSchema::new("leased_memory_gb", "GB", MetricAggregation::Mean, "Memory leased to tenants"),
+            MetricSchema::new("leased_storage_gb", "GB", MetricAggregation::Mean, "Storage leased to tenants"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(percent: f64) -> AllocationStrategy {
+        AllocationStrategy {
+            cpu_cores: 0,
+            memory_gb: 0.0,
+            storage_gb: 0.0,
+            bandwidth_mbps: 100.0,
+            allocation_percent: percent,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_akash_creation() {
+        let adapter = AkashAdapter::new(AkashConfig::default());
+        assert_eq!(adapter.protocol_name(), "Akash Network");
+    }
+
+    #[tokio::test]
+    async fn test_akash_connect_requires_address() {
+        let mut adapter = AkashAdapter::new(AkashConfig::default());
+        assert!(adapter.connect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_allocation_adjusts_offering() {
+        let mut adapter = AkashAdapter::new(AkashConfig::default());
+        adapter.apply_allocation(strategy(50.0)).await.unwrap();
+
+        let offering = adapter.offering().await;
+        assert_eq!(offering.cpu_millicores, 8000);
+        assert_eq!(offering.memory_gb, 32.0);
+
+        let allocation = adapter.get_current_allocation().await.unwrap();
+        assert_eq!(allocation.cpu_cores, 8);
+    }
+
+    #[tokio::test]
+    async fn test_allocation_cannot_evict_leases() {
+        let mut adapter = AkashAdapter::new(AkashConfig::default());
+        adapter.metrics.write().await.leased = ProviderOffering {
+            cpu_millicores: 6000,
+            memory_gb: 8.0,
+            storage_gb: 10.0,
+        };
+
+        assert!(adapter.apply_allocation(strategy(20.0)).await.is_err());
+        assert!(adapter.apply_allocation(strategy(50.0)).await.is_ok());
+    }
+
+    #[test]
+    fn test_hourly_revenue() {
+        let adapter = AkashAdapter::new(AkashConfig::default());
+        let leased = ProviderOffering {
+            cpu_millicores: 2000,
+            memory_gb: 0.0,
+            storage_gb: 0.0,
+        };
+        // 2 cores * 1.2 uakt * 600 blocks / 1e6
+        let revenue = adapter.hourly_revenue_akt(&leased);
+        assert!((revenue - 0.00144).abs() < 1e-9);
+    }
+}
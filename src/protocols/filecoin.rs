@@ -0,0 +1,576 @@
+/// Filecoin Storage Provider Adapter
+///
+/// Filecoin is a decentralized storage market where storage providers earn FIL
+/// for sealing sectors and serving storage deals.
+///
+/// This adapter manages:
+/// - Connection to a Lotus full node via its JSON-RPC API
+/// - Sealed sector power (storage committed to the network)
+/// - Deal revenue tracked through miner available balance
+/// - Pledge collateral locked against sealed sectors
+use super::{
+    AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// attoFIL per FIL
+const ATTO_PER_FIL: f64 = 1e18;
+
+/// Bytes per GiB
+const BYTES_PER_GIB: f64 = 1_073_741_824.0;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Filecoin protocol configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilecoinConfig {
+    /// Lotus JSON-RPC endpoint
+    pub lotus_rpc_url: String,
+    /// Lotus API token (read permission is sufficient)
+    pub api_token: String,
+    /// Storage provider (miner) actor address, e.g. `f01234`
+    pub miner_address: String,
+    /// FIL price used for USD conversion
+    pub fil_price_usd: f64,
+    /// Storage capacity available for sealing in GB
+    pub allocated_storage_gb: f64,
+    /// Minimum allocation percent
+    pub min_allocation_percent: f64,
+    /// Maximum allocation percent
+    pub max_allocation_percent: f64,
+}
+
+impl Default for FilecoinConfig {
+    fn default() -> Self {
+        Self {
+            lotus_rpc_url: "http://localhost:1234/rpc/v0".to_string(),
+            api_token: String::new(),
+            miner_address: String::new(),
+            fil_price_usd: 5.0,
+            allocated_storage_gb: 4000.0,
+            min_allocation_percent: 20.0,
+            max_allocation_percent: 80.0,
+        }
+    }
+}
+
+// ============================================================================
+// LOTUS JSON-RPC
+// ============================================================================
+
+/// JSON-RPC response envelope
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<serde_json::Value>,
+    error: Option<RpcError>,
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Parse a Lotus big-int string (attoFIL or bytes) into f64
+fn parse_big_int(value: &serde_json::Value) -> ProtocolResult<f64> {
+    value
+        .as_str()
+        .ok_or_else(|| ProtocolError::ParseError(format!("Expected big-int string, got {}", value)))?
+        .parse::<f64>()
+        .map_err(|e| ProtocolError::ParseError(e.to_string()))
+}
+
+// ============================================================================
+// INTERNAL STATE
+// ============================================================================
+
+/// Filecoin metrics tracked internally
+#[derive(Debug, Clone, Default)]
+struct FilecoinMetrics {
+    sealed_storage_gb: f64,
+    available_balance_fil: f64,
+    pledge_collateral_fil: f64,
+    locked_rewards_fil: f64,
+    /// First balance sample, used to derive deal revenue rate
+    baseline: Option<(DateTime<Utc>, f64)>,
+    connected_at: Option<DateTime<Utc>>,
+    last_refresh: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// Filecoin Storage Provider Adapter
+pub struct FilecoinAdapter {
+    config: FilecoinConfig,
+    http: reqwest::Client,
+    status: Arc<RwLock<ConnectionStatus>>,
+    allocation: Arc<RwLock<AllocationStrategy>>,
+    metrics: Arc<RwLock<FilecoinMetrics>>,
+}
+
+impl FilecoinAdapter {
+    /// Create a new Filecoin adapter
+    pub fn new(config: FilecoinConfig) -> Self {
+        let allocation = AllocationStrategy {
+            cpu_cores: 4,
+            memory_gb: 16.0,
+            storage_gb: config.allocated_storage_gb,
+            bandwidth_mbps: 100.0,
+            allocation_percent: 40.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            allocation: Arc::new(RwLock::new(allocation)),
+            metrics: Arc::new(RwLock::new(FilecoinMetrics::default())),
+        }
+    }
+
+    /// Issue a Lotus JSON-RPC call
+    async fn rpc(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> ProtocolResult<serde_json::Value> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1,
+        });
+
+        let mut request = self.http.post(&self.config.lotus_rpc_url).json(&body);
+        if !self.config.api_token.is_empty() {
+            request = request.bearer_auth(&self.config.api_token);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                ProtocolError::TimeoutError(format!("{}: {}", method, e))
+            } else {
+                ProtocolError::NetworkError(format!("{}: {}", method, e))
+            }
+        })?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(ProtocolError::AuthenticationError(
+                "Lotus rejected API token".to_string(),
+            ));
+        }
+
+        let envelope: RpcResponse = response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))?;
+
+        if let Some(error) = envelope.error {
+            return Err(ProtocolError::ApiError(format!(
+                "{} failed ({}): {}",
+                method, error.code, error.message
+            )));
+        }
+
+        envelope
+            .result
+            .ok_or_else(|| ProtocolError::DataError(format!("{} returned no result", method)))
+    }
+
+    /// Refresh sector power, balance, and pledge from Lotus
+    async fn refresh(&self) -> ProtocolResult<()> {
+        let miner = &self.config.miner_address;
+
+        let power = self
+            .rpc("Filecoin.StateMinerPower", serde_json::json!([miner, null]))
+            .await?;
+        let raw_bytes = parse_big_int(&power["MinerPower"]["RawBytePower"])?;
+
+        let balance = self
+            .rpc(
+                "Filecoin.StateMinerAvailableBalance",
+                serde_json::json!([miner, null]),
+            )
+            .await?;
+        let available_fil = parse_big_int(&balance)? / ATTO_PER_FIL;
+
+        let state = self
+            .rpc("Filecoin.StateReadState", serde_json::json!([miner, null]))
+            .await?;
+        let pledge_fil = parse_big_int(&state["State"]["InitialPledge"]).unwrap_or(0.0) / ATTO_PER_FIL;
+        let locked_fil = parse_big_int(&state["State"]["LockedFunds"]).unwrap_or(0.0) / ATTO_PER_FIL;
+
+        let now = Utc::now();
+        let mut metrics = self.metrics.write().await;
+        metrics.sealed_storage_gb = raw_bytes / BYTES_PER_GIB;
+        metrics.available_balance_fil = available_fil;
+        metrics.pledge_collateral_fil = pledge_fil;
+        metrics.locked_rewards_fil = locked_fil;
+        if metrics.baseline.is_none() {
+            metrics.baseline = Some((now, available_fil + locked_fil));
+        }
+        metrics.last_refresh = Some(now);
+
+        Ok(())
+    }
+
+    /// Deal and block revenue in USD/hour derived from balance growth
+    async fn calculate_current_earnings(&self) -> f64 {
+        let metrics = self.metrics.read().await;
+
+        let Some((since, baseline_fil)) = metrics.baseline else {
+            return 0.0;
+        };
+        let hours = Utc::now().signed_duration_since(since).num_seconds() as f64 / 3600.0;
+        if hours <= 0.0 {
+            return 0.0;
+        }
+
+        let earned_fil =
+            (metrics.available_balance_fil + metrics.locked_rewards_fil - baseline_fil).max(0.0);
+        earned_fil / hours * self.config.fil_price_usd
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for FilecoinAdapter {
+    fn protocol_name(&self) -> &str {
+        "Filecoin"
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        if self.config.miner_address.is_empty() {
+            return Err(ProtocolError::ConfigurationError(
+                "Miner address not configured".to_string(),
+            ));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        if let Err(e) = self.rpc("Filecoin.Version", serde_json::json!([])).await {
+            *self.status.write().await = ConnectionStatus::Failed;
+            return Err(ProtocolError::ConnectionError(format!(
+                "Lotus node unreachable: {}",
+                e
+            )));
+        }
+
+        self.refresh().await?;
+
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.metrics.write().await.connected_at = Some(Utc::now());
+
+        tracing::info!("Connected to Filecoin via Lotus at {}", self.config.lotus_rpc_url);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        if *self.status.read().await == ConnectionStatus::Disconnected {
+            return Ok(());
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.metrics.write().await.connected_at = None;
+
+        tracing::info!("Disconnected from Filecoin");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .try_read()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Connecting)
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.refresh().await?;
+
+        let earnings_usd = self.calculate_current_earnings().await;
+        let metrics = self.metrics.read().await;
+
+        let mut metric_map = HashMap::new();
+        metric_map.insert("sealed_storage_gb".to_string(), metrics.sealed_storage_gb);
+        metric_map.insert("available_balance_fil".to_string(), metrics.available_balance_fil);
+        metric_map.insert("pledge_collateral_fil".to_string(), metrics.pledge_collateral_fil);
+        metric_map.insert("locked_rewards_fil".to_string(), metrics.locked_rewards_fil);
+
+        Ok(EarningsData {
+            timestamp: Utc::now(),
+            amount_usd: earnings_usd,
+            protocol_id: "filecoin".to_string(),
+            metrics: metric_map,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // Lotus exposes chain state, not a per-hour revenue ledger
+        Err(ProtocolError::UnsupportedError(
+            "Lotus does not expose historical Filecoin earnings".to_string(),
+        ))
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        let metrics = self.metrics.read().await;
+        let uptime_seconds = metrics
+            .connected_at
+            .map(|t| Utc::now().signed_duration_since(t).num_seconds().max(0) as u64)
+            .unwrap_or(0);
+
+        Ok(ResourceMetrics {
+            cpu_percent: 20.0,
+            memory_mb: 8192.0,
+            bandwidth_mbps: 40.0,
+            storage_gb: metrics.sealed_storage_gb,
+            uptime_seconds,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        if !(self.config.min_allocation_percent..=self.config.max_allocation_percent)
+            .contains(&strategy.allocation_percent)
+        {
+            return Err(ProtocolError::AllocationError(format!(
+                "Allocation must be between {} and {}%",
+                self.config.min_allocation_percent, self.config.max_allocation_percent
+            )));
+        }
+
+        if strategy.storage_gb > self.config.allocated_storage_gb {
+            return Err(ProtocolError::AllocationError(
+                "Storage allocation exceeds limit".to_string(),
+            ));
+        }
+
+        // Sealed sectors cannot be shrunk without terminating them (and
+        // forfeiting pledge), so never allocate below what is already sealed.
+        let sealed = self.metrics.read().await.sealed_storage_gb;
+        if strategy.storage_gb < sealed {
+            return Err(ProtocolError::AllocationError(format!(
+                "Storage allocation {:.0}GB is below sealed capacity {:.0}GB",
+                strategy.storage_gb, sealed
+            )));
+        }
+
+        *self.allocation.write().await = strategy;
+        tracing::info!("Applied allocation strategy to Filecoin");
+        Ok(())
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        Ok(self.allocation.read().await.clone())
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let status = *self.status.read().await;
+        let rpc_ok = self.rpc("Filecoin.Version", serde_json::json!([])).await;
+        let is_healthy = status == ConnectionStatus::Connected && rpc_ok.is_ok();
+        let metrics = self.metrics.read().await;
+
+        let mut health_metrics = HashMap::new();
+        health_metrics.insert(
+            "sealed_storage_gb".into(),
+            serde_json::json!(metrics.sealed_storage_gb),
+        );
+        health_metrics.insert(
+            "pledge_collateral_fil".into(),
+            serde_json::json!(metrics.pledge_collateral_fil),
+        );
+        if let Ok(version) = rpc_ok {
+            health_metrics.insert("lotus_version".into(), version["Version"].clone());
+        }
+
+        Ok(HealthStatus {
+            is_healthy,
+            connection_status: status,
+            last_operation: metrics.last_refresh,
+            error_message: if is_healthy {
+                None
+            } else {
+                Some("Lotus node not reachable".to_string())
+            },
+            metrics: health_metrics,
+        })
+    }
+
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "protocol": "filecoin",
+            "lotus_rpc_url": self.config.lotus_rpc_url,
+            "miner_address": self.config.miner_address,
+            "allocated_storage_gb": self.config.allocated_storage_gb,
+            "min_allocation_percent": self.config.min_allocation_percent,
+            "max_allocation_percent": self.config.max_allocation_percent,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(
+            ResourceKind::Storage,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        )
+        .with_secondary(&[ResourceKind::Cpu, ResourceKind::Memory])
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new("sealed_storage_gb", "GB", MetricAggregation::Last, "Storage sealed into sectors"),
+            MetricSchema::new(
+                "available_balance_fil",
+                "FIL",
+                MetricAggregation::Last,
+                "Miner balance available to withdraw",
+            ),
+            MetricSchema::new(
+                "pledge_collateral_fil",
+                "FIL",
+                MetricAggregation::Last,
+                "Collateral pledged for sectors",
+            ),
+            MetricSchema::new("locked_rewards_fil", "FIL", MetricAggregation::Last, "Block rewards still vesting"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(url: String) -> FilecoinConfig {
+        FilecoinConfig {
+            lotus_rpc_url: url,
+            miner_address: "f01234".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filecoin_creation() {
+        let adapter = FilecoinAdapter::new(FilecoinConfig::default());
+        assert_eq!(adapter.protocol_name(), "Filecoin");
+    }
+
+    #[tokio::test]
+    async fn test_filecoin_connect_requires_miner() {
+        let mut adapter = FilecoinAdapter::new(FilecoinConfig::default());
+        assert!(adapter.connect().await.is_err());
+    }
+
+    #[test]
+    fn test_parse_big_int() {
+        let value = serde_json::json!("2000000000000000000");
+        assert_eq!(parse_big_int(&value).unwrap() / ATTO_PER_FIL, 2.0);
+        assert!(parse_big_int(&serde_json::json!(12)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filecoin_connect_reads_lotus_state() {
+        let mut server = mockito::Server::new_async().await;
+        let _version = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"method": "Filecoin.Version"}),
+            ))
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":{"Version":"1.25.0"}}"#)
+            .create_async()
+            .await;
+        let _power = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"method": "Filecoin.StateMinerPower"}),
+            ))
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"MinerPower":{"RawBytePower":"107374182400"}}}"#,
+            )
+            .create_async()
+            .await;
+        let _balance = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"method": "Filecoin.StateMinerAvailableBalance"}),
+            ))
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":"1000000000000000000"}"#)
+            .create_async()
+            .await;
+        let _state = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"method": "Filecoin.StateReadState"}),
+            ))
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"State":{"InitialPledge":"5000000000000000000","LockedFunds":"0"}}}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut adapter = FilecoinAdapter::new(test_config(server.url()));
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.protocol_id, "filecoin");
+        assert_eq!(earnings.metrics["sealed_storage_gb"], 100.0);
+        assert_eq!(earnings.metrics["pledge_collateral_fil"], 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_filecoin_earnings_are_not_scaled_by_allocation() {
+        let adapter = FilecoinAdapter::new(FilecoinConfig::default());
+        {
+            let mut metrics = adapter.metrics.write().await;
+            metrics.baseline = Some((Utc::now() - chrono::Duration::hours(2), 1.0));
+            metrics.available_balance_fil = 2.0;
+        }
+
+        // 1 FIL over 2 hours at $5/FIL, whatever the allocation
+        let earnings = adapter.calculate_current_earnings().await;
+        assert!((earnings - 2.5).abs() < 0.01);
+
+        assert!(matches!(
+            adapter.get_historical_earnings(24).await,
+            Err(ProtocolError::UnsupportedError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_filecoin_rejects_shrinking_below_sealed() {
+        let mut adapter = FilecoinAdapter::new(FilecoinConfig::default());
+        adapter.metrics.write().await.sealed_storage_gb = 500.0;
+
+        let strategy = AllocationStrategy {
+            cpu_cores: 4,
+            memory_gb: 16.0,
+            storage_gb: 100.0,
+            bandwidth_mbps: 100.0,
+            allocation_percent: 40.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+        assert!(adapter.apply_allocation(strategy).await.is_err());
+    }
+}
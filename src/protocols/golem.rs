@@ -8,18 +8,29 @@
 /// - Provider node registration and management
 /// - Computational task execution and earnings
 /// - Resource allocation and optimization
-
+/// - Provider preset pricing (GLM per CPU-hour, per env-hour, start price),
+///   adjusted through `apply_allocation`
+/// - Proposal acceptance/rejection rates and marketplace demand, reported in
+///   the earnings metrics so the optimizer can follow demand spikes
 use super::{
     AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
-    ProtocolError, ProtocolResult, ResourceMetrics,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourcePricing,
+    ResourceProfile, DEMAND_INDEX_METRIC,
 };
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Name of the provider preset this adapter manages
+const PRESET_NAME: &str = "depin-orcha";
+
+/// Weight of the latest sample in the demand baseline (exponential moving average)
+const DEMAND_BASELINE_SMOOTHING: f64 = 0.1;
+
 // ============================================================================
 // CONFIGURATION
 // ============================================================================
@@ -37,10 +48,35 @@ pub struct GolemConfig {
     pub memory_gb: f64,
     /// GPU enabled
     pub gpu_enabled: bool,
+    /// GPUs available to the provider
+    #[serde(default)]
+    pub gpu_count: u32,
+    /// VRAM available across all GPUs in GB
+    #[serde(default)]
+    pub vram_gb: f64,
     /// Minimum allocation percent
     pub min_allocation_percent: f64,
     /// Maximum allocation percent
     pub max_allocation_percent: f64,
+    /// Provider preset prices in GLM
+    pub pricing: ResourcePricing,
+    /// Where to write the provider preset for `ya-provider` to load
+    pub preset_path: Option<PathBuf>,
+    /// Golem network stats API for marketplace demand and this provider's
+    /// proposal outcomes (e.g. `https://api.stats.golem.network/v2`); empty
+    /// disables market tracking
+    pub market_api_url: String,
+}
+
+impl GolemConfig {
+    /// GPUs the provider may offer (zero unless GPU support is enabled)
+    pub fn usable_gpus(&self) -> u32 {
+        if self.gpu_enabled {
+            self.gpu_count
+        } else {
+            0
+        }
+    }
 }
 
 impl Default for GolemConfig {
@@ -51,12 +87,88 @@ impl Default for GolemConfig {
             cpu_cores: 8,
             memory_gb: 16.0,
             gpu_enabled: false,
+            gpu_count: 0,
+            vram_gb: 0.0,
             min_allocation_percent: 10.0,
             max_allocation_percent: 40.0,
+            pricing: ResourcePricing {
+                cpu_per_hour: 0.1,
+                env_per_hour: 0.02,
+                start_price: 0.0,
+            },
+            preset_path: None,
+            market_api_url: String::new(),
+        }
+    }
+}
+
+// ============================================================================
+// PROVIDER PRESET
+// ============================================================================
+
+/// `ya-provider` presets file holding the single preset this adapter manages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetsFile {
+    pub ver: String,
+    pub active: Vec<String>,
+    pub presets: Vec<ProviderPreset>,
+}
+
+/// Linear pricing preset; usage coefficients are GLM per second of usage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProviderPreset {
+    pub name: String,
+    pub exeunit_name: String,
+    pub pricing_model: String,
+    pub initial_price: f64,
+    pub usage_coeffs: HashMap<String, f64>,
+}
+
+impl PresetsFile {
+    /// Presets file offering `pricing` through the VM runtime
+    pub fn for_pricing(pricing: &ResourcePricing) -> Self {
+        let usage_coeffs = HashMap::from([
+            ("golem.usage.cpu_sec".to_string(), pricing.cpu_per_hour / 3600.0),
+            ("golem.usage.duration_sec".to_string(), pricing.env_per_hour / 3600.0),
+        ]);
+        Self {
+            ver: "V1".to_string(),
+            active: vec![PRESET_NAME.to_string()],
+            presets: vec![ProviderPreset {
+                name: PRESET_NAME.to_string(),
+                exeunit_name: "vm".to_string(),
+                pricing_model: "linear".to_string(),
+                initial_price: pricing.start_price,
+                usage_coeffs,
+            }],
         }
     }
 }
 
+// ============================================================================
+// MARKET STATS API
+// ============================================================================
+
+/// Subset of the network demand response
+#[derive(Debug, Deserialize)]
+struct DemandResponse {
+    /// Open requestor demands on the marketplace
+    #[serde(default)]
+    active_demands: f64,
+}
+
+/// Subset of the provider proposals response
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ProposalsResponse {
+    #[serde(default)]
+    received: u64,
+    #[serde(default)]
+    accepted: u64,
+    #[serde(default)]
+    rejected: u64,
+}
+
 // ============================================================================
 // INTERNAL STATE
 // ============================================================================
@@ -70,6 +182,12 @@ struct GolemMetrics {
     connected_at: Option<DateTime<Utc>>,
     cpu_utilization_percent: f64,
     gpu_utilization_percent: f64,
+    proposals: ProposalsResponse,
+    /// Latest open requestor demands and their moving average
+    market_demand: Option<f64>,
+    demand_baseline: Option<f64>,
+    demand_index: Option<f64>,
+    last_market_refresh: Option<DateTime<Utc>>,
 }
 
 impl Default for GolemMetrics {
@@ -81,10 +199,38 @@ impl Default for GolemMetrics {
             connected_at: None,
             cpu_utilization_percent: 0.0,
             gpu_utilization_percent: 0.0,
+            proposals: ProposalsResponse::default(),
+            market_demand: None,
+            demand_baseline: None,
+            demand_index: None,
+            last_market_refresh: None,
         }
     }
 }
 
+impl GolemMetrics {
+    /// Share of received proposals that were accepted
+    fn acceptance_rate(&self) -> Option<f64> {
+        (self.proposals.received > 0)
+            .then(|| self.proposals.accepted as f64 / self.proposals.received as f64)
+    }
+
+    /// Share of received proposals that were rejected
+    fn rejection_rate(&self) -> Option<f64> {
+        (self.proposals.received > 0)
+            .then(|| self.proposals.rejected as f64 / self.proposals.received as f64)
+    }
+
+    /// Record the latest marketplace demand against its moving average
+    fn observe_demand(&mut self, demand: f64) {
+        let baseline = self.demand_baseline.unwrap_or(demand);
+        self.demand_index = Some(if baseline > 0.0 { demand / baseline } else { 1.0 });
+        self.demand_baseline =
+            Some(baseline + DEMAND_BASELINE_SMOOTHING * (demand - baseline));
+        self.market_demand = Some(demand);
+    }
+}
+
 // ============================================================================
 // ADAPTER IMPLEMENTATION
 // ============================================================================
@@ -92,6 +238,7 @@ impl Default for GolemMetrics {
 /// Golem Decentralized Compute Network Adapter
 pub struct GolemAdapter {
     config: GolemConfig,
+    http: reqwest::Client,
     status: Arc<RwLock<ConnectionStatus>>,
     allocation: Arc<RwLock<AllocationStrategy>>,
     metrics: Arc<RwLock<GolemMetrics>>,
@@ -106,10 +253,14 @@ impl GolemAdapter {
             storage_gb: 20.0,
             bandwidth_mbps: 100.0,
             allocation_percent: 30.0,
+            gpu_count: config.usable_gpus(),
+            vram_gb: if config.usable_gpus() > 0 { config.vram_gb } else { 0.0 },
+            pricing: Some(config.pricing),
         };
 
         Self {
             config,
+            http: reqwest::Client::new(),
             status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             allocation: Arc::new(RwLock::new(allocation)),
             metrics: Arc::new(RwLock::new(GolemMetrics::default())),
@@ -132,6 +283,56 @@ impl GolemAdapter {
         }
     }
 
+    /// GET a market stats API resource
+    async fn get<T: serde::de::DeserializeOwned>(&self, resource: &str) -> ProtocolResult<T> {
+        let url = format!("{}/{}", self.config.market_api_url.trim_end_matches('/'), resource);
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ProtocolError::ApiError(format!(
+                "Golem stats {} returned {}",
+                resource,
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))
+    }
+
+    /// Refresh marketplace demand and proposal outcomes from the stats API
+    async fn refresh_market(&self) -> ProtocolResult<()> {
+        let demand: DemandResponse = self.get("network/demand").await?;
+        let proposals: ProposalsResponse = self
+            .get(&format!("provider/wallet/{}/proposals", self.config.eth_wallet))
+            .await?;
+
+        let mut metrics = self.metrics.write().await;
+        metrics.observe_demand(demand.active_demands);
+        metrics.proposals = proposals;
+        metrics.last_market_refresh = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Persist the provider preset for `ya-provider`
+    async fn publish_preset(&self, pricing: &ResourcePricing) -> ProtocolResult<()> {
+        if let Some(path) = &self.config.preset_path {
+            let json = serde_json::to_vec_pretty(&PresetsFile::for_pricing(pricing))
+                .map_err(|e| ProtocolError::DataError(e.to_string()))?;
+            tokio::fs::write(path, json)
+                .await
+                .map_err(|e| ProtocolError::ResourceError(format!("{}: {}", path.display(), e)))?;
+        }
+        Ok(())
+    }
+
     /// Simulate earning for demonstration
     async fn calculate_current_earnings(&self) -> f64 {
         let metrics = self.metrics.read().await;
@@ -198,6 +399,13 @@ impl ProtocolAdapter for GolemAdapter {
 
     async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
         self.update_metrics().await;
+        // Market stats only inform the optimizer; keep the last known values
+        // rather than failing the poll when the API is unreachable
+        if !self.config.market_api_url.is_empty() {
+            if let Err(e) = self.refresh_market().await {
+                tracing::warn!("Failed to refresh Golem market stats: {}", e);
+            }
+        }
 
         let earnings_usd = self.calculate_current_earnings().await;
         let metrics = self.metrics.read().await;
@@ -208,42 +416,45 @@ impl ProtocolAdapter for GolemAdapter {
         metric_map.insert("tasks_failed".to_string(), metrics.tasks_failed as f64);
         metric_map.insert("cpu_cores_allocated".to_string(), allocation.cpu_cores as f64);
         metric_map.insert("memory_gb_allocated".to_string(), allocation.memory_gb);
+        metric_map.insert("gpu_count_allocated".to_string(), allocation.gpu_count as f64);
+        if let Some(pricing) = &allocation.pricing {
+            metric_map.insert("cpu_per_hour_glm".to_string(), pricing.cpu_per_hour);
+            metric_map.insert("env_per_hour_glm".to_string(), pricing.env_per_hour);
+            metric_map.insert("start_price_glm".to_string(), pricing.start_price);
+        }
+        if let Some(rate) = metrics.acceptance_rate() {
+            metric_map.insert("task_acceptance_rate".to_string(), rate);
+        }
+        if let Some(rate) = metrics.rejection_rate() {
+            metric_map.insert("task_rejection_rate".to_string(), rate);
+        }
+        if let Some(demand) = metrics.market_demand {
+            metric_map.insert("market_demand".to_string(), demand);
+        }
+        if let Some(index) = metrics.demand_index {
+            metric_map.insert(DEMAND_INDEX_METRIC.to_string(), index);
+        }
 
         Ok(EarningsData {
             timestamp: Utc::now(),
             amount_usd: earnings_usd,
             protocol_id: "golem".to_string(),
             metrics: metric_map,
+            stale_seconds: 0,
         })
     }
 
-    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
-        let mut earnings = Vec::new();
-        let current_earnings = self.calculate_current_earnings().await;
-
-        // Simulate historical data
-        for i in 0..hours {
-            let hours_ago = Duration::hours(i as i64);
-            let timestamp = Utc::now() - hours_ago;
-
-            // Simulate earnings growth over time
-            let growth_factor = 1.0 + (i as f64 / hours as f64) * 0.5;
-            let amount = current_earnings / growth_factor;
-
-            earnings.push(EarningsData {
-                timestamp,
-                amount_usd: amount,
-                protocol_id: "golem".to_string(),
-                metrics: HashMap::new(),
-            });
-        }
-
-        Ok(earnings)
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // Yagna reports current activity and payments, not an hourly ledger
+        Err(ProtocolError::UnsupportedError(
+            "Yagna does not expose historical Golem earnings".to_string(),
+        ))
     }
 
     async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
         self.update_metrics().await;
         let metrics = self.metrics.read().await;
+        let allocation = self.allocation.read().await;
 
         Ok(ResourceMetrics {
             cpu_percent: metrics.cpu_utilization_percent,
@@ -251,6 +462,10 @@ impl ProtocolAdapter for GolemAdapter {
             bandwidth_mbps: 75.0,
             storage_gb: 15.0,
             uptime_seconds: (metrics.total_compute_hours * 3600.0) as u64,
+            gpu_count: allocation.gpu_count,
+            vram_gb: allocation.vram_gb * (metrics.gpu_utilization_percent / 100.0),
+            gpu_utilization_percent: metrics.gpu_utilization_percent,
+            stale_seconds: 0,
         })
     }
 
@@ -279,7 +494,30 @@ impl ProtocolAdapter for GolemAdapter {
             ));
         }
 
-        *self.allocation.write().await = strategy;
+        // Validate GPUs don't exceed available
+        if strategy.gpu_count > self.config.usable_gpus() || strategy.vram_gb > self.config.vram_gb {
+            return Err(ProtocolError::AllocationError(
+                "GPU allocation exceeds available".to_string(),
+            ));
+        }
+
+        // Without new prices the current preset stays in place
+        let pricing = match strategy.pricing {
+            Some(pricing) => {
+                pricing.validate()?;
+                self.publish_preset(&pricing).await?;
+                tracing::info!(
+                    "Golem pricing set to {} GLM/CPU-hour, {} GLM/env-hour, {} GLM start",
+                    pricing.cpu_per_hour,
+                    pricing.env_per_hour,
+                    pricing.start_price
+                );
+                Some(pricing)
+            }
+            None => self.allocation.read().await.pricing,
+        };
+
+        *self.allocation.write().await = AllocationStrategy { pricing, ..strategy };
         tracing::info!("Applied allocation strategy to Golem");
         Ok(())
     }
@@ -306,6 +544,12 @@ impl ProtocolAdapter for GolemAdapter {
             "gpu_utilization".into(),
             serde_json::json!(metrics.gpu_utilization_percent),
         );
+        if let Some(rate) = metrics.acceptance_rate() {
+            health_metrics.insert("task_acceptance_rate".into(), serde_json::json!(rate));
+        }
+        if let Some(index) = metrics.demand_index {
+            health_metrics.insert(DEMAND_INDEX_METRIC.into(), serde_json::json!(index));
+        }
 
         Ok(HealthStatus {
             is_healthy,
@@ -320,6 +564,11 @@ impl ProtocolAdapter for GolemAdapter {
         })
     }
 
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
     fn get_config(&self) -> serde_json::Value {
         serde_json::json!({
             "protocol": "golem",
@@ -327,10 +576,61 @@ impl ProtocolAdapter for GolemAdapter {
             "cpu_cores": self.config.cpu_cores,
             "memory_gb": self.config.memory_gb,
             "gpu_enabled": self.config.gpu_enabled,
+            "gpu_count": self.config.gpu_count,
+            "vram_gb": self.config.vram_gb,
             "min_allocation_percent": self.config.min_allocation_percent,
             "max_allocation_percent": self.config.max_allocation_percent,
+            "pricing": self.config.pricing,
+            "preset_path": self.config.preset_path,
+            "market_api_url": self.config.market_api_url,
         })
     }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        let profile = ResourceProfile::new(
+            ResourceKind::Cpu,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        );
+
+        if self.config.usable_gpus() > 0 {
+            profile.with_secondary(&[ResourceKind::Memory, ResourceKind::Gpu])
+        } else {
+            profile.with_secondary(&[ResourceKind::Memory])
+        }
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new("tasks_completed", "count", MetricAggregation::Last, "Tasks completed since start"),
+            MetricSchema::new("tasks_failed", "count", MetricAggregation::Last, "Tasks failed since start"),
+            MetricSchema::new(
+                "cpu_cores_allocated",
+                "cores",
+                MetricAggregation::Mean,
+                "CPU cores offered to requestors",
+            ),
+            MetricSchema::new("memory_gb_allocated", "GB", MetricAggregation::Mean, "Memory offered to requestors"),
+            MetricSchema::new("gpu_count_allocated", "count", MetricAggregation::Mean, "GPUs offered to requestors"),
+            MetricSchema::new("cpu_per_hour_glm", "GLM/hour", MetricAggregation::Last, "Preset price per CPU hour"),
+            MetricSchema::new(
+                "env_per_hour_glm",
+                "GLM/hour",
+                MetricAggregation::Last,
+                "Preset price per environment hour",
+            ),
+            MetricSchema::new("start_price_glm", "GLM", MetricAggregation::Last, "Preset price per activity start"),
+            MetricSchema::new("task_acceptance_rate", "ratio", MetricAggregation::Mean, "Share of proposals accepted"),
+            MetricSchema::new("task_rejection_rate", "ratio", MetricAggregation::Mean, "Share of proposals rejected"),
+            MetricSchema::new("market_demand", "demands", MetricAggregation::Mean, "Active marketplace demands"),
+            MetricSchema::new(
+                DEMAND_INDEX_METRIC,
+                "ratio",
+                MetricAggregation::Mean,
+                "Marketplace demand relative to its baseline",
+            ),
+        ]
+    }
 }
 
 #[cfg(test)]
@@ -397,6 +697,9 @@ mod tests {
             storage_gb: 20.0,
             bandwidth_mbps: 100.0,
             allocation_percent: 25.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
         assert!(adapter.apply_allocation(strategy).await.is_ok());
 
@@ -407,10 +710,105 @@ mod tests {
             storage_gb: 20.0,
             bandwidth_mbps: 100.0,
             allocation_percent: 25.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
         assert!(adapter.apply_allocation(invalid_strategy).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_golem_pricing_via_allocation() {
+        let preset_path = std::env::temp_dir().join(format!("orcha-golem-preset-{}.json", uuid::Uuid::new_v4()));
+        let config = GolemConfig {
+            eth_wallet: "0x123...".to_string(),
+            preset_path: Some(preset_path.clone()),
+            ..Default::default()
+        };
+        let mut adapter = GolemAdapter::new(config);
+        let current = adapter.get_current_allocation().await.unwrap();
+        assert_eq!(current.pricing, Some(GolemConfig::default().pricing));
+
+        let pricing = ResourcePricing {
+            cpu_per_hour: 0.18,
+            env_per_hour: 0.036,
+            start_price: 0.01,
+        };
+        let strategy = AllocationStrategy {
+            pricing: Some(pricing),
+            ..current.clone()
+        };
+        adapter.apply_allocation(strategy).await.unwrap();
+
+        let presets: PresetsFile =
+            serde_json::from_slice(&std::fs::read(&preset_path).unwrap()).unwrap();
+        let preset = &presets.presets[0];
+        assert_eq!(presets.active, [PRESET_NAME]);
+        assert_eq!(preset.name, PRESET_NAME);
+        assert_eq!(preset.initial_price, 0.01);
+        assert!((preset.usage_coeffs["golem.usage.cpu_sec"] - 0.00005).abs() < 1e-12);
+        assert!((preset.usage_coeffs["golem.usage.duration_sec"] - 0.00001).abs() < 1e-12);
+        std::fs::remove_file(&preset_path).unwrap();
+
+        // Allocation changes without prices keep the preset
+        let strategy = AllocationStrategy {
+            allocation_percent: 20.0,
+            pricing: None,
+            ..current.clone()
+        };
+        adapter.apply_allocation(strategy).await.unwrap();
+        assert_eq!(adapter.get_current_allocation().await.unwrap().pricing, Some(pricing));
+
+        let negative = AllocationStrategy {
+            pricing: Some(ResourcePricing {
+                cpu_per_hour: -1.0,
+                ..pricing
+            }),
+            ..current
+        };
+        assert!(adapter.apply_allocation(negative).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_golem_market_stats() {
+        let mut server = mockito::Server::new_async().await;
+        let demand = server
+            .mock("GET", "/network/demand")
+            .with_body(r#"{"active_demands":40.0}"#)
+            .create_async()
+            .await;
+        let _proposals = server
+            .mock("GET", "/provider/wallet/0x123/proposals")
+            .with_body(r#"{"received":50,"accepted":40,"rejected":8}"#)
+            .create_async()
+            .await;
+
+        let config = GolemConfig {
+            eth_wallet: "0x123".to_string(),
+            market_api_url: server.url(),
+            ..Default::default()
+        };
+        let mut adapter = GolemAdapter::new(config);
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.metrics["task_acceptance_rate"], 0.8);
+        assert_eq!(earnings.metrics["task_rejection_rate"], 0.16);
+        assert_eq!(earnings.metrics[DEMAND_INDEX_METRIC], 1.0);
+        assert_eq!(earnings.metrics["cpu_per_hour_glm"], 0.1);
+
+        // Demand triples against the baseline set by the first sample
+        demand.remove_async().await;
+        let _spike = server
+            .mock("GET", "/network/demand")
+            .with_body(r#"{"active_demands":120.0}"#)
+            .create_async()
+            .await;
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.metrics["market_demand"], 120.0);
+        assert_eq!(earnings.metrics[DEMAND_INDEX_METRIC], 3.0);
+    }
+
     #[tokio::test]
     async fn test_golem_resource_usage() {
         let config = GolemConfig {
@@ -427,4 +825,26 @@ mod tests {
         assert!(resources.cpu_percent > 0.0);
         assert!(resources.memory_mb > 0.0);
     }
+
+    #[tokio::test]
+    async fn test_golem_gpu_allocation() {
+        let config = GolemConfig {
+            eth_wallet: "0x123...".to_string(),
+            gpu_enabled: true,
+            gpu_count: 2,
+            vram_gb: 24.0,
+            ..Default::default()
+        };
+        let mut adapter = GolemAdapter::new(config);
+        adapter.connect().await.unwrap();
+
+        let resources = adapter.get_resource_usage().await.unwrap();
+        assert_eq!(resources.gpu_count, 2);
+        assert!(resources.gpu_utilization_percent > 0.0);
+        assert!(adapter.resource_profile().uses(ResourceKind::Gpu));
+
+        let mut strategy = adapter.get_current_allocation().await.unwrap();
+        strategy.gpu_count = 4;
+        assert!(adapter.apply_allocation(strategy).await.is_err());
+    }
 }
@@ -8,13 +8,18 @@
 /// - Bandwidth sharing and monitoring
 /// - Earnings tracking from data provision
 /// - Resource allocation and optimization
-
+/// - Every device (IP) running Grass for the account, with bandwidth and
+///   earnings aggregated across them and each device's state reported so
+///   the alert task can flag devices that drop offline
+/// - Auth token checks: the `exp` claim of JWT tokens, and with device
+///   tracking a request that Grass must accept
 use super::{
-    AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
-    ProtocolError, ProtocolResult, ResourceMetrics,
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+    DEVICE_CONNECTED_METRIC_PREFIX,
 };
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -37,6 +42,11 @@ pub struct GrassConfig {
     pub min_allocation_percent: f64,
     /// Maximum allocation percent
     pub max_allocation_percent: f64,
+    /// Enumerate the account's devices from `{api_endpoint}/devices` and
+    /// report their combined bandwidth and earnings
+    pub track_devices: bool,
+    /// USD value of a Grass point, used to convert device earnings
+    pub point_price_usd: f64,
 }
 
 impl Default for GrassConfig {
@@ -47,10 +57,41 @@ impl Default for GrassConfig {
             email: String::new(),
             min_allocation_percent: 20.0,
             max_allocation_percent: 100.0,
+            track_devices: false,
+            point_price_usd: 0.0001,
         }
     }
 }
 
+// ============================================================================
+// DEVICES API
+// ============================================================================
+
+/// Device sharing bandwidth for the account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrassDevice {
+    pub device_id: String,
+    #[serde(default)]
+    pub ip_address: String,
+    #[serde(default)]
+    pub connected: bool,
+    /// Bandwidth currently shared in Mbps
+    #[serde(default)]
+    pub bandwidth_mbps: f64,
+    /// Points earned per hour at the current rate
+    #[serde(default)]
+    pub points_per_hour: f64,
+    #[serde(default)]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// Subset of the devices response
+#[derive(Debug, Deserialize)]
+struct DevicesResponse {
+    #[serde(default)]
+    devices: Vec<GrassDevice>,
+}
+
 // ============================================================================
 // INTERNAL STATE
 // ============================================================================
@@ -63,6 +104,8 @@ struct GrassMetrics {
     connected_at: Option<DateTime<Utc>>,
     connection_uptime_hours: u64,
     user_rank: u32,
+    devices: Vec<GrassDevice>,
+    last_device_refresh: Option<DateTime<Utc>>,
 }
 
 impl Default for GrassMetrics {
@@ -73,10 +116,19 @@ impl Default for GrassMetrics {
             connected_at: None,
             connection_uptime_hours: 0,
             user_rank: 100000,
+            devices: Vec::new(),
+            last_device_refresh: None,
         }
     }
 }
 
+impl GrassMetrics {
+    /// Devices currently connected
+    fn connected_devices(&self) -> impl Iterator<Item = &GrassDevice> {
+        self.devices.iter().filter(|d| d.connected)
+    }
+}
+
 // ============================================================================
 // ADAPTER IMPLEMENTATION
 // ============================================================================
@@ -84,6 +136,7 @@ impl Default for GrassMetrics {
 /// Grass Network Protocol Adapter
 pub struct GrassAdapter {
     config: GrassConfig,
+    http: reqwest::Client,
     status: Arc<RwLock<ConnectionStatus>>,
     allocation: Arc<RwLock<AllocationStrategy>>,
     metrics: Arc<RwLock<GrassMetrics>>,
@@ -98,10 +151,14 @@ impl GrassAdapter {
             storage_gb: 10.0,
             bandwidth_mbps: 500.0, // Grass primarily uses bandwidth
             allocation_percent: 75.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
 
         Self {
             config,
+            http: reqwest::Client::new(),
             status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             allocation: Arc::new(RwLock::new(allocation)),
             metrics: Arc::new(RwLock::new(GrassMetrics::default())),
@@ -123,9 +180,55 @@ impl GrassAdapter {
         }
     }
 
-    /// Simulate earning for demonstration
+    /// Refresh the account's devices from the Grass API
+    async fn refresh_devices(&self) -> ProtocolResult<()> {
+        let devices = self.fetch_devices().await?;
+        let mut metrics = self.metrics.write().await;
+        metrics.devices = devices;
+        metrics.last_device_refresh = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Fetch the account's devices, authenticating with the auth token
+    async fn fetch_devices(&self) -> ProtocolResult<Vec<GrassDevice>> {
+        let url = format!("{}/devices", self.config.api_endpoint.trim_end_matches('/'));
+        let response = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.config.auth_token)
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(ProtocolError::AuthenticationError(
+                "Grass rejected the auth token".to_string(),
+            ));
+        }
+        if !response.status().is_success() {
+            return Err(ProtocolError::ApiError(format!(
+                "Grass devices returned {}",
+                response.status()
+            )));
+        }
+
+        let devices: DevicesResponse = response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))?;
+        Ok(devices.devices)
+    }
+
+    /// Current earnings in USD per hour
+    ///
+    /// With device tracking this is what the connected devices earn;
+    /// otherwise it is simulated.
     async fn calculate_current_earnings(&self) -> f64 {
         let metrics = self.metrics.read().await;
+        if self.config.track_devices {
+            let points_per_hour: f64 = metrics.connected_devices().map(|d| d.points_per_hour).sum();
+            return points_per_hour * self.config.point_price_usd;
+        }
         let allocation = self.allocation.read().await;
 
         // Simulate earnings: based on bandwidth shared
@@ -135,10 +238,7 @@ impl GrassAdapter {
         // Rank multiplier: better rank = higher earnings
         let rank_multiplier = (100000.0 / metrics.user_rank.max(100) as f64).min(3.0);
 
-        let allocated_earnings =
-            bandwidth_earnings * rank_multiplier * (allocation.allocation_percent / 100.0);
-
-        allocated_earnings
+        bandwidth_earnings * rank_multiplier * (allocation.allocation_percent / 100.0)
     }
 }
 
@@ -198,6 +298,9 @@ impl ProtocolAdapter for GrassAdapter {
 
     async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
         self.update_uptime().await;
+        if self.config.track_devices {
+            self.refresh_devices().await?;
+        }
 
         let earnings_usd = self.calculate_current_earnings().await;
         let metrics = self.metrics.read().await;
@@ -211,49 +314,57 @@ impl ProtocolAdapter for GrassAdapter {
             "bandwidth_mbps_allocated".to_string(),
             allocation.bandwidth_mbps,
         );
+        if self.config.track_devices {
+            let connected: Vec<_> = metrics.connected_devices().collect();
+            metric_map.insert("devices_total".to_string(), metrics.devices.len() as f64);
+            metric_map.insert("devices_connected".to_string(), connected.len() as f64);
+            metric_map.insert(
+                "bandwidth_mbps_shared".to_string(),
+                connected.iter().map(|d| d.bandwidth_mbps).sum(),
+            );
+            for device in &metrics.devices {
+                metric_map.insert(
+                    format!("{}{}", DEVICE_CONNECTED_METRIC_PREFIX, device.device_id),
+                    if device.connected { 1.0 } else { 0.0 },
+                );
+            }
+        }
 
         Ok(EarningsData {
             timestamp: Utc::now(),
             amount_usd: earnings_usd,
             protocol_id: "grass".to_string(),
             metrics: metric_map,
+            stale_seconds: 0,
         })
     }
 
-    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
-        let mut earnings = Vec::new();
-        let current_earnings = self.calculate_current_earnings().await;
-
-        // Simulate historical data
-        for i in 0..hours {
-            let hours_ago = Duration::hours(i as i64);
-            let timestamp = Utc::now() - hours_ago;
-
-            // Simulate consistent earnings
-            let variance = 0.8 + ((i % 20) as f64 / 20.0) * 0.4;
-            let amount = current_earnings * variance;
-
-            earnings.push(EarningsData {
-                timestamp,
-                amount_usd: amount,
-                protocol_id: "grass".to_string(),
-                metrics: HashMap::new(),
-            });
-        }
-
-        Ok(earnings)
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // The Grass API reports points earned so far, not an hourly ledger
+        Err(ProtocolError::UnsupportedError(
+            "The Grass API does not expose historical earnings".to_string(),
+        ))
     }
 
     async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
         self.update_uptime().await;
         let metrics = self.metrics.read().await;
+        let bandwidth_mbps = if self.config.track_devices {
+            metrics.connected_devices().map(|d| d.bandwidth_mbps).sum()
+        } else {
+            metrics.bandwidth_shared_gb * 1000.0 / 3600.0 // Convert GB/hour to Mbps
+        };
 
         Ok(ResourceMetrics {
             cpu_percent: 5.0,
             memory_mb: 128.0,
-            bandwidth_mbps: metrics.bandwidth_shared_gb * 1000.0 / 3600.0, // Convert GB/hour to Mbps
+            bandwidth_mbps,
             storage_gb: 1.0,
             uptime_seconds: metrics.connection_uptime_hours * 3600,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
         })
     }
 
@@ -286,8 +397,12 @@ impl ProtocolAdapter for GrassAdapter {
 
     async fn health_check(&self) -> ProtocolResult<HealthStatus> {
         let status = *self.status.read().await;
-        let is_healthy = status == ConnectionStatus::Connected;
         let metrics = self.metrics.read().await;
+        // An account whose devices are all offline isn't sharing anything
+        let devices_offline = self.config.track_devices
+            && !metrics.devices.is_empty()
+            && metrics.connected_devices().next().is_none();
+        let is_healthy = status == ConnectionStatus::Connected && !devices_offline;
 
         let mut health_metrics = HashMap::new();
         health_metrics.insert(
@@ -299,13 +414,22 @@ impl ProtocolAdapter for GrassAdapter {
             serde_json::json!(metrics.connection_uptime_hours),
         );
         health_metrics.insert("user_rank".into(), serde_json::json!(metrics.user_rank));
+        if self.config.track_devices {
+            health_metrics.insert(
+                "devices_connected".into(),
+                serde_json::json!(metrics.connected_devices().count()),
+            );
+            health_metrics.insert("devices".into(), serde_json::json!(metrics.devices));
+        }
 
         Ok(HealthStatus {
             is_healthy,
             connection_status: status,
-            last_operation: Some(Utc::now()),
+            last_operation: metrics.last_device_refresh.or(Some(Utc::now())),
             error_message: if is_healthy {
                 None
+            } else if devices_offline {
+                Some("All Grass devices are offline".to_string())
             } else {
                 Some("Not connected to Grass".to_string())
             },
@@ -313,6 +437,25 @@ impl ProtocolAdapter for GrassAdapter {
         })
     }
 
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        if self.config.auth_token.is_empty() {
+            return Err(ProtocolError::AuthenticationError(
+                "Authentication token not configured".to_string(),
+            ));
+        }
+        if self.config.track_devices {
+            self.fetch_devices().await?;
+        }
+        Ok(CredentialStatus {
+            expires_at: token_expiry(&self.config.auth_token),
+        })
+    }
+
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
     fn get_config(&self) -> serde_json::Value {
         serde_json::json!({
             "protocol": "grass",
@@ -320,8 +463,65 @@ impl ProtocolAdapter for GrassAdapter {
             "email": self.config.email,
             "min_allocation_percent": self.config.min_allocation_percent,
             "max_allocation_percent": self.config.max_allocation_percent,
+            "track_devices": self.config.track_devices,
+            "point_price_usd": self.config.point_price_usd,
         })
     }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(
+            ResourceKind::Bandwidth,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        )
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new("bandwidth_shared_gb", "GB", MetricAggregation::Last, "Bandwidth shared since start"),
+            MetricSchema::new("data_points_shared", "count", MetricAggregation::Last, "Data points shared in total"),
+            MetricSchema::new("user_rank", "rank", MetricAggregation::Last, "Network leaderboard rank"),
+            MetricSchema::new(
+                "bandwidth_mbps_allocated",
+                "Mbps",
+                MetricAggregation::Mean,
+                "Bandwidth offered to the network",
+            ),
+            MetricSchema::new("devices_total", "count", MetricAggregation::Last, "Devices registered to the account"),
+            MetricSchema::new("devices_connected", "count", MetricAggregation::Min, "Devices currently connected"),
+            MetricSchema::new(
+                "bandwidth_mbps_shared",
+                "Mbps",
+                MetricAggregation::Mean,
+                "Bandwidth shared by connected devices",
+            ),
+            MetricSchema::new(
+                "device_connected",
+                "bool",
+                MetricAggregation::Min,
+                "Whether the device is connected (1 or 0)",
+            )
+            .labeled("device"),
+        ]
+    }
+}
+
+/// Expiry (`exp` claim) of a JWT auth token, `None` for opaque tokens
+///
+/// Only the claims are read; Grass verifies the signature.
+fn token_expiry(token: &str) -> Option<DateTime<Utc>> {
+    #[derive(Deserialize)]
+    struct Claims {
+        exp: i64,
+    }
+
+    let mut validation = jsonwebtoken::Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+    let key = jsonwebtoken::DecodingKey::from_secret(&[]);
+    let claims = jsonwebtoken::decode::<Claims>(token, &key, &validation).ok()?.claims;
+    DateTime::from_timestamp(claims.exp, 0)
 }
 
 #[cfg(test)]
@@ -390,6 +590,9 @@ mod tests {
             storage_gb: 10.0,
             bandwidth_mbps: 500.0,
             allocation_percent: 75.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
         assert!(adapter.apply_allocation(strategy).await.is_ok());
 
@@ -400,10 +603,109 @@ mod tests {
             storage_gb: 10.0,
             bandwidth_mbps: 500.0,
             allocation_percent: 10.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
         assert!(adapter.apply_allocation(invalid_strategy).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_grass_aggregates_devices() {
+        let mut server = mockito::Server::new_async().await;
+        let _devices = server
+            .mock("GET", "/devices")
+            .match_header("authorization", "Bearer test_token")
+            .with_body(
+                r#"{"devices":[
+                    {"device_id":"home","ip_address":"203.0.113.4","connected":true,"bandwidth_mbps":12.5,"points_per_hour":600.0},
+                    {"device_id":"office","ip_address":"198.51.100.7","connected":true,"bandwidth_mbps":7.5,"points_per_hour":400.0},
+                    {"device_id":"vps","ip_address":"192.0.2.9","connected":false,"bandwidth_mbps":0.0,"points_per_hour":0.0}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let config = GrassConfig {
+            api_endpoint: server.url(),
+            auth_token: "test_token".to_string(),
+            email: "test@example.com".to_string(),
+            track_devices: true,
+            point_price_usd: 0.001,
+            ..Default::default()
+        };
+        let mut adapter = GrassAdapter::new(config);
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert!((earnings.amount_usd - 1.0).abs() < 1e-9);
+        assert_eq!(earnings.metrics["devices_connected"], 2.0);
+        assert_eq!(earnings.metrics["bandwidth_mbps_shared"], 20.0);
+        assert_eq!(
+            super::super::device_connectivity(&earnings.metrics),
+            [("home", true), ("office", true), ("vps", false)]
+        );
+        let schema = adapter.metric_schema();
+        assert!(earnings.metrics.keys().all(|key| super::super::find_metric_schema(&schema, key).is_some()));
+
+        let resources = adapter.get_resource_usage().await.unwrap();
+        assert_eq!(resources.bandwidth_mbps, 20.0);
+
+        let health = adapter.health_check().await.unwrap();
+        assert!(health.is_healthy);
+        assert_eq!(health.metrics["devices_connected"], serde_json::json!(2));
+        assert_eq!(health.metrics["devices"][2]["ip_address"], "192.0.2.9");
+        assert_eq!(health.metrics["devices"][2]["connected"], false);
+    }
+
+    #[tokio::test]
+    async fn test_grass_check_credentials() {
+        let expires_at = DateTime::from_timestamp(Utc::now().timestamp() + 3600, 0).unwrap();
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "sub": "user", "exp": expires_at.timestamp() }),
+            &jsonwebtoken::EncodingKey::from_secret(b"grass"),
+        )
+        .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let _accepted = server
+            .mock("GET", "/devices")
+            .match_header("authorization", format!("Bearer {}", token).as_str())
+            .with_body(r#"{"devices":[]}"#)
+            .create_async()
+            .await;
+        let _rejected = server
+            .mock("GET", "/devices")
+            .match_header("authorization", "Bearer revoked")
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let config = GrassConfig {
+            api_endpoint: server.url(),
+            auth_token: token,
+            email: "test@example.com".to_string(),
+            track_devices: true,
+            ..Default::default()
+        };
+        let status = GrassAdapter::new(config.clone()).check_credentials().await.unwrap();
+        assert_eq!(status.expires_at, Some(expires_at));
+
+        let revoked = GrassAdapter::new(GrassConfig { auth_token: "revoked".to_string(), ..config.clone() });
+        assert!(matches!(
+            revoked.check_credentials().await,
+            Err(ProtocolError::AuthenticationError(_))
+        ));
+
+        // Opaque tokens have no expiry; without device tracking nothing is requested
+        let opaque = GrassConfig { auth_token: "opaque".to_string(), track_devices: false, ..config };
+        assert_eq!(GrassAdapter::new(opaque).check_credentials().await.unwrap().expires_at, None);
+
+        let missing = GrassAdapter::new(GrassConfig::default());
+        assert!(missing.check_credentials().await.is_err());
+    }
+
     #[tokio::test]
     async fn test_grass_historical_earnings() {
         let config = GrassConfig {
@@ -414,8 +716,10 @@ mod tests {
         let mut adapter = GrassAdapter::new(config);
         adapter.connect().await.unwrap();
 
-        let history = adapter.get_historical_earnings(24).await.unwrap();
-        assert_eq!(history.len(), 24);
-        assert!(history.iter().all(|e| e.protocol_id == "grass"));
+        // Nothing is invented for hours the adapter never observed
+        assert!(matches!(
+            adapter.get_historical_earnings(24).await,
+            Err(ProtocolError::UnsupportedError(_))
+        ));
     }
 }
@@ -0,0 +1,624 @@
+/// gRPC Remote Adapter
+///
+/// Drives a protocol integration running out of process (Python, Go, ...)
+/// through the `ProtocolAdapterService` contract in
+/// `proto/protocol_adapter.proto`. The remote side implements the same
+/// operations as `ProtocolAdapter`; this adapter forwards calls, applies
+/// connect/request timeouts, and maps gRPC status codes onto `ProtocolError`.
+///
+/// Until the first successful `connect`, the adapter reports an observe-only
+/// resource profile so the optimizer never reallocates an unknown sidecar.
+
+use super::{
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus,
+    ProtocolAdapter, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tonic::transport::{Channel, Endpoint};
+
+/// Generated protobuf types and client/server stubs
+pub mod pb {
+    tonic::include_proto!("depin_orcha.adapter.v1");
+}
+
+use pb::protocol_adapter_service_client::ProtocolAdapterServiceClient;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// gRPC remote adapter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcRemoteConfig {
+    /// Sidecar endpoint (e.g. `http://127.0.0.1:50051`)
+    pub endpoint: String,
+    /// Protocol name reported to the coordinator
+    pub name: String,
+    /// Timeout for establishing the channel in milliseconds
+    pub connect_timeout_ms: u64,
+    /// Timeout for each RPC in milliseconds
+    pub request_timeout_ms: u64,
+}
+
+impl Default for GrpcRemoteConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://127.0.0.1:50051".to_string(),
+            name: "Remote Adapter".to_string(),
+            connect_timeout_ms: 2_000,
+            request_timeout_ms: 5_000,
+        }
+    }
+}
+
+// ============================================================================
+// CONVERSIONS
+// ============================================================================
+
+/// Map a gRPC status onto the adapter error types
+fn status_to_error(status: tonic::Status) -> ProtocolError {
+    use tonic::Code;
+
+    let message = status.message().to_string();
+    match status.code() {
+        Code::DeadlineExceeded => ProtocolError::TimeoutError(message),
+        Code::Unavailable => ProtocolError::NetworkError(message),
+        Code::Unauthenticated | Code::PermissionDenied => {
+            ProtocolError::AuthenticationError(message)
+        }
+        Code::InvalidArgument | Code::FailedPrecondition => ProtocolError::AllocationError(message),
+        Code::ResourceExhausted => ProtocolError::ResourceError(message),
+        Code::Unimplemented => ProtocolError::UnsupportedError(message),
+        Code::DataLoss => ProtocolError::DataError(message),
+        _ => ProtocolError::ApiError(format!("{:?}: {}", status.code(), message)),
+    }
+}
+
+fn timestamp(secs: i64) -> Option<DateTime<Utc>> {
+    if secs <= 0 {
+        return None;
+    }
+    Utc.timestamp_opt(secs, 0).single()
+}
+
+fn resource_kind(kind: pb::ResourceKind) -> ResourceKind {
+    match kind {
+        pb::ResourceKind::Cpu => ResourceKind::Cpu,
+        pb::ResourceKind::Memory => ResourceKind::Memory,
+        pb::ResourceKind::Storage => ResourceKind::Storage,
+        pb::ResourceKind::Gpu => ResourceKind::Gpu,
+        pb::ResourceKind::Bandwidth | pb::ResourceKind::Unspecified => ResourceKind::Bandwidth,
+    }
+}
+
+impl From<pb::ResourceProfile> for ResourceProfile {
+    fn from(profile: pb::ResourceProfile) -> Self {
+        let secondary: Vec<_> = profile.secondary().map(resource_kind).collect();
+        let mut converted = ResourceProfile::new(
+            resource_kind(profile.primary()),
+            profile.min_allocation_percent,
+            profile.max_allocation_percent,
+        )
+        .with_secondary(&secondary);
+        converted.low_resource = profile.low_resource;
+        converted.observe_only = profile.observe_only;
+        converted
+    }
+}
+
+impl From<pb::Earnings> for EarningsData {
+    fn from(earnings: pb::Earnings) -> Self {
+        Self {
+            timestamp: timestamp(earnings.timestamp).unwrap_or_else(Utc::now),
+            amount_usd: earnings.amount_usd,
+            protocol_id: earnings.protocol_id,
+            metrics: earnings.metrics.into_iter().collect(),
+            stale_seconds: 0,
+        }
+    }
+}
+
+impl From<pb::ResourceUsage> for ResourceMetrics {
+    fn from(usage: pb::ResourceUsage) -> Self {
+        Self {
+            cpu_percent: usage.cpu_percent,
+            memory_mb: usage.memory_mb,
+            bandwidth_mbps: usage.bandwidth_mbps,
+            storage_gb: usage.storage_gb,
+            uptime_seconds: usage.uptime_seconds,
+            gpu_count: usage.gpu_count,
+            vram_gb: usage.vram_gb,
+            gpu_utilization_percent: usage.gpu_utilization_percent,
+            stale_seconds: 0,
+        }
+    }
+}
+
+impl From<pb::Allocation> for AllocationStrategy {
+    fn from(allocation: pb::Allocation) -> Self {
+        Self {
+            cpu_cores: allocation.cpu_cores,
+            memory_gb: allocation.memory_gb,
+            storage_gb: allocation.storage_gb,
+            bandwidth_mbps: allocation.bandwidth_mbps,
+            allocation_percent: allocation.allocation_percent,
+            gpu_count: allocation.gpu_count,
+            vram_gb: allocation.vram_gb,
+            pricing: None,
+        }
+    }
+}
+
+impl From<AllocationStrategy> for pb::Allocation {
+    fn from(strategy: AllocationStrategy) -> Self {
+        Self {
+            cpu_cores: strategy.cpu_cores,
+            memory_gb: strategy.memory_gb,
+            storage_gb: strategy.storage_gb,
+            bandwidth_mbps: strategy.bandwidth_mbps,
+            allocation_percent: strategy.allocation_percent,
+            gpu_count: strategy.gpu_count,
+            vram_gb: strategy.vram_gb,
+        }
+    }
+}
+
+impl From<pb::ConnectionStatus> for ConnectionStatus {
+    fn from(status: pb::ConnectionStatus) -> Self {
+        match status {
+            pb::ConnectionStatus::Disconnected => Self::Disconnected,
+            pb::ConnectionStatus::Connecting => Self::Connecting,
+            pb::ConnectionStatus::Connected => Self::Connected,
+            pb::ConnectionStatus::Reconnecting => Self::Reconnecting,
+            pb::ConnectionStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// Protocol adapter backed by a gRPC sidecar
+pub struct GrpcRemoteAdapter {
+    config: GrpcRemoteConfig,
+    client: Arc<RwLock<Option<ProtocolAdapterServiceClient<Channel>>>>,
+    status: Arc<RwLock<ConnectionStatus>>,
+    // Read from the sync trait methods, so these use std locks
+    profile: Arc<StdRwLock<ResourceProfile>>,
+    remote_config: Arc<StdRwLock<serde_json::Value>>,
+}
+
+impl GrpcRemoteAdapter {
+    /// Create a new remote adapter (no connection is made until `connect`)
+    pub fn new(config: GrpcRemoteConfig) -> Self {
+        Self {
+            config,
+            client: Arc::new(RwLock::new(None)),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            profile: Arc::new(StdRwLock::new(ResourceProfile::observe_only())),
+            remote_config: Arc::new(StdRwLock::new(serde_json::Value::Null)),
+        }
+    }
+
+    /// Connected client, or an error if `connect` has not succeeded
+    async fn client(&self) -> ProtocolResult<ProtocolAdapterServiceClient<Channel>> {
+        self.client.read().await.clone().ok_or_else(|| {
+            ProtocolError::ConnectionError(format!("{} is not connected", self.config.name))
+        })
+    }
+
+    /// Run one RPC under the request timeout
+    async fn call<T>(
+        &self,
+        rpc: &str,
+        request: impl Future<Output = Result<tonic::Response<T>, tonic::Status>>,
+    ) -> ProtocolResult<T> {
+        let timeout = Duration::from_millis(self.config.request_timeout_ms);
+        match tokio::time::timeout(timeout, request).await {
+            Ok(Ok(response)) => Ok(response.into_inner()),
+            Ok(Err(status)) => Err(status_to_error(status)),
+            Err(_) => Err(ProtocolError::TimeoutError(format!(
+                "{} {} timed out after {}ms",
+                self.config.name, rpc, self.config.request_timeout_ms
+            ))),
+        }
+    }
+
+    /// Open a channel to the sidecar with the configured timeouts
+    async fn open_channel(&self) -> ProtocolResult<Channel> {
+        Endpoint::from_shared(self.config.endpoint.clone())
+            .map_err(|e| ProtocolError::ConfigurationError(e.to_string()))?
+            .connect_timeout(Duration::from_millis(self.config.connect_timeout_ms))
+            .connect()
+            .await
+            .map_err(|e| {
+                ProtocolError::ConnectionError(format!("{}: {}", self.config.endpoint, e))
+            })
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for GrpcRemoteAdapter {
+    fn protocol_name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        let result = async {
+            let mut client = ProtocolAdapterServiceClient::new(self.open_channel().await?);
+
+            let info = self.call("GetInfo", client.get_info(pb::Empty {})).await?;
+            self.call("Connect", client.connect(pb::Empty {})).await?;
+
+            Ok::<_, ProtocolError>((client, info))
+        }
+        .await;
+
+        let (client, info) = match result {
+            Ok(connected) => connected,
+            Err(e) => {
+                *self.status.write().await = ConnectionStatus::Failed;
+                return Err(e);
+            }
+        };
+
+        if let Some(profile) = info.resource_profile {
+            if let Ok(mut cached) = self.profile.write() {
+                *cached = profile.into();
+            }
+        }
+        if let Ok(mut cached) = self.remote_config.write() {
+            *cached = serde_json::from_str(&info.config_json).unwrap_or(serde_json::Value::Null);
+        }
+
+        *self.client.write().await = Some(client);
+        *self.status.write().await = ConnectionStatus::Connected;
+
+        tracing::info!(
+            "Connected to remote adapter {} ({}) at {}",
+            self.config.name,
+            info.protocol_id,
+            self.config.endpoint
+        );
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        if let Some(mut client) = self.client.write().await.take() {
+            // Best effort: the sidecar may already be gone
+            if let Err(e) = self.call("Disconnect", client.disconnect(pb::Empty {})).await {
+                tracing::warn!("Remote adapter {} disconnect failed: {}", self.config.name, e);
+            }
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        tracing::info!("Disconnected from remote adapter {}", self.config.name);
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .try_read()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Connecting)
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        let mut client = self.client().await?;
+        let earnings = self
+            .call("GetCurrentEarnings", client.get_current_earnings(pb::Empty {}))
+            .await?;
+
+        Ok(earnings.into())
+    }
+
+    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        let mut client = self.client().await?;
+        let list = self
+            .call(
+                "GetHistoricalEarnings",
+                client.get_historical_earnings(pb::HistoricalEarningsRequest { hours }),
+            )
+            .await?;
+
+        Ok(list.earnings.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        let mut client = self.client().await?;
+        let usage = self
+            .call("GetResourceUsage", client.get_resource_usage(pb::Empty {}))
+            .await?;
+
+        Ok(usage.into())
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        let mut client = self.client().await?;
+        self.call(
+            "ApplyAllocation",
+            client.apply_allocation(pb::Allocation::from(strategy)),
+        )
+        .await?;
+
+        tracing::info!("Applied allocation strategy to remote adapter {}", self.config.name);
+        Ok(())
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        let mut client = self.client().await?;
+        let allocation = self
+            .call("GetCurrentAllocation", client.get_current_allocation(pb::Empty {}))
+            .await?;
+
+        Ok(allocation.into())
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let status = *self.status.read().await;
+        if status != ConnectionStatus::Connected {
+            return Ok(HealthStatus {
+                is_healthy: false,
+                connection_status: status,
+                last_operation: None,
+                error_message: Some(format!("{} is not connected", self.config.name)),
+                metrics: Default::default(),
+            });
+        }
+
+        let mut client = self.client().await?;
+        let health = match self.call("HealthCheck", client.health_check(pb::Empty {})).await {
+            Ok(health) => health,
+            Err(e) => {
+                // An unreachable sidecar is unhealthy rather than an adapter error
+                return Ok(HealthStatus {
+                    is_healthy: false,
+                    connection_status: ConnectionStatus::Reconnecting,
+                    last_operation: None,
+                    error_message: Some(e.to_string()),
+                    metrics: Default::default(),
+                });
+            }
+        };
+
+        Ok(HealthStatus {
+            is_healthy: health.is_healthy,
+            connection_status: health.connection_status().into(),
+            last_operation: timestamp(health.last_operation),
+            error_message: Some(health.error_message).filter(|m| !m.is_empty()),
+            metrics: serde_json::from_str(&health.metrics_json).unwrap_or_default(),
+        })
+    }
+
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        let mut client = self.client().await?;
+        let status = self
+            .call("CheckCredentials", client.check_credentials(pb::Empty {}))
+            .await?;
+
+        Ok(CredentialStatus {
+            expires_at: timestamp(status.expires_at),
+        })
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        let remote = self
+            .remote_config
+            .read()
+            .map(|c| c.clone())
+            .unwrap_or(serde_json::Value::Null);
+
+        serde_json::json!({
+            "protocol": "grpc",
+            "endpoint": self.config.endpoint,
+            "name": self.config.name,
+            "connect_timeout_ms": self.config.connect_timeout_ms,
+            "request_timeout_ms": self.config.request_timeout_ms,
+            "remote": remote,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        self.profile
+            .read()
+            .map(|p| p.clone())
+            .unwrap_or_else(|_| ResourceProfile::observe_only())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pb::protocol_adapter_service_server::{
+        ProtocolAdapterService, ProtocolAdapterServiceServer,
+    };
+    use super::*;
+    use tonic::{Request, Response, Status};
+
+    /// In-process sidecar used to exercise the client
+    #[derive(Default)]
+    struct FakeSidecar {
+        slow: bool,
+    }
+
+    #[tonic::async_trait]
+    impl ProtocolAdapterService for FakeSidecar {
+        async fn get_info(&self, _: Request<pb::Empty>) -> Result<Response<pb::AdapterInfo>, Status> {
+            Ok(Response::new(pb::AdapterInfo {
+                protocol_id: "fake".to_string(),
+                display_name: "Fake".to_string(),
+                resource_profile: Some(pb::ResourceProfile {
+                    primary: pb::ResourceKind::Storage as i32,
+                    secondary: vec![pb::ResourceKind::Bandwidth as i32],
+                    min_allocation_percent: 10.0,
+                    max_allocation_percent: 50.0,
+                    low_resource: false,
+                    observe_only: false,
+                }),
+                config_json: r#"{"region":"eu"}"#.to_string(),
+            }))
+        }
+
+        async fn connect(&self, _: Request<pb::Empty>) -> Result<Response<pb::Empty>, Status> {
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn disconnect(&self, _: Request<pb::Empty>) -> Result<Response<pb::Empty>, Status> {
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_current_earnings(
+            &self,
+            _: Request<pb::Empty>,
+        ) -> Result<Response<pb::Earnings>, Status> {
+            if self.slow {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            Ok(Response::new(pb::Earnings {
+                timestamp: 1_700_000_000,
+                amount_usd: 1.25,
+                protocol_id: "fake".to_string(),
+                metrics: [("jobs".to_string(), 3.0)].into_iter().collect(),
+            }))
+        }
+
+        async fn get_historical_earnings(
+            &self,
+            request: Request<pb::HistoricalEarningsRequest>,
+        ) -> Result<Response<pb::EarningsList>, Status> {
+            let earnings = (0..request.into_inner().hours)
+                .map(|_| pb::Earnings::default())
+                .collect();
+            Ok(Response::new(pb::EarningsList { earnings }))
+        }
+
+        async fn get_resource_usage(
+            &self,
+            _: Request<pb::Empty>,
+        ) -> Result<Response<pb::ResourceUsage>, Status> {
+            Ok(Response::new(pb::ResourceUsage::default()))
+        }
+
+        async fn apply_allocation(
+            &self,
+            request: Request<pb::Allocation>,
+        ) -> Result<Response<pb::Empty>, Status> {
+            if request.into_inner().allocation_percent > 50.0 {
+                return Err(Status::invalid_argument("allocation above 50%"));
+            }
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_current_allocation(
+            &self,
+            _: Request<pb::Empty>,
+        ) -> Result<Response<pb::Allocation>, Status> {
+            Ok(Response::new(pb::Allocation {
+                allocation_percent: 25.0,
+                ..Default::default()
+            }))
+        }
+
+        async fn health_check(&self, _: Request<pb::Empty>) -> Result<Response<pb::Health>, Status> {
+            Ok(Response::new(pb::Health {
+                is_healthy: true,
+                connection_status: pb::ConnectionStatus::Connected as i32,
+                last_operation: 0,
+                error_message: String::new(),
+                metrics_json: r#"{"queue":0}"#.to_string(),
+            }))
+        }
+
+        async fn check_credentials(
+            &self,
+            _: Request<pb::Empty>,
+        ) -> Result<Response<pb::CredentialStatus>, Status> {
+            Err(Status::unauthenticated("api key revoked"))
+        }
+    }
+
+    async fn spawn_sidecar(sidecar: FakeSidecar) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(ProtocolAdapterServiceServer::new(sidecar))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        format!("http://{}", addr)
+    }
+
+    fn config(endpoint: String) -> GrpcRemoteConfig {
+        GrpcRemoteConfig {
+            endpoint,
+            name: "Fake Remote".to_string(),
+            request_timeout_ms: 200,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_adapter_round_trip() {
+        let endpoint = spawn_sidecar(FakeSidecar::default()).await;
+        let mut adapter = GrpcRemoteAdapter::new(config(endpoint));
+
+        assert!(adapter.resource_profile().observe_only);
+        adapter.connect().await.unwrap();
+
+        let profile = adapter.resource_profile();
+        assert_eq!(profile.primary, ResourceKind::Storage);
+        assert_eq!(profile.secondary, vec![ResourceKind::Bandwidth]);
+        assert_eq!(adapter.get_config()["remote"]["region"], "eu");
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.amount_usd, 1.25);
+        assert_eq!(earnings.metrics["jobs"], 3.0);
+
+        assert_eq!(adapter.get_historical_earnings(3).await.unwrap().len(), 3);
+
+        let mut strategy = adapter.get_current_allocation().await.unwrap();
+        assert_eq!(strategy.allocation_percent, 25.0);
+        strategy.allocation_percent = 80.0;
+        assert!(matches!(
+            adapter.apply_allocation(strategy).await,
+            Err(ProtocolError::AllocationError(_))
+        ));
+
+        let health = adapter.health_check().await.unwrap();
+        assert!(health.is_healthy);
+        assert_eq!(health.metrics["queue"], 0);
+
+        assert!(matches!(
+            adapter.check_credentials().await,
+            Err(ProtocolError::AuthenticationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_remote_adapter_timeout() {
+        let endpoint = spawn_sidecar(FakeSidecar { slow: true }).await;
+        let mut adapter = GrpcRemoteAdapter::new(config(endpoint));
+        adapter.connect().await.unwrap();
+
+        assert!(matches!(
+            adapter.get_current_earnings().await,
+            Err(ProtocolError::TimeoutError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_remote_adapter_unreachable() {
+        let mut adapter = GrpcRemoteAdapter::new(config("http://127.0.0.1:1".to_string()));
+        assert!(adapter.connect().await.is_err());
+        assert_eq!(adapter.connection_status(), ConnectionStatus::Failed);
+        assert!(adapter.get_current_earnings().await.is_err());
+    }
+}
@@ -0,0 +1,422 @@
+/// Hivemapper Dashcam Mapping Adapter
+///
+/// Hivemapper rewards contributors in HONEY for dashcam imagery that builds a
+/// decentralized street map. Mapping happens on the dashcam itself; the host
+/// only relays uploads, so the protocol is treated as low-resource.
+///
+/// This adapter manages:
+/// - Connection to the Hivemapper contributor API
+/// - HONEY reward tracking and USD conversion
+/// - Map coverage contribution (km mapped, unique km, hexes)
+use super::{
+    AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Hivemapper pays rewards weekly
+const HOURS_PER_REWARD_PERIOD: f64 = 168.0;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Hivemapper protocol configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HivemapperConfig {
+    /// Hivemapper contributor API base URL
+    pub api_url: String,
+    /// Contributor API key
+    pub api_key: String,
+    /// Contributor wallet address
+    pub wallet_address: String,
+    /// HONEY price used for USD conversion
+    pub honey_price_usd: f64,
+    /// Upload bandwidth reserved for dashcam imagery in Mbps
+    pub upload_bandwidth_mbps: f64,
+}
+
+impl Default for HivemapperConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://hivemapper.com/api/developer".to_string(),
+            api_key: String::new(),
+            wallet_address: String::new(),
+            honey_price_usd: 0.03,
+            upload_bandwidth_mbps: 5.0,
+        }
+    }
+}
+
+// ============================================================================
+// CONTRIBUTOR API
+// ============================================================================
+
+/// Subset of the rewards response
+#[derive(Debug, Deserialize)]
+struct RewardsResponse {
+    /// Reward periods, most recent first
+    #[serde(default)]
+    rewards: Vec<RewardPeriod>,
+    #[serde(default)]
+    total_honey: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RewardPeriod {
+    honey: f64,
+}
+
+/// Subset of the coverage response
+#[derive(Debug, Default, Deserialize)]
+struct CoverageResponse {
+    #[serde(default)]
+    km_mapped: f64,
+    #[serde(default)]
+    unique_km: f64,
+    #[serde(default)]
+    hexes_contributed: u64,
+}
+
+// ============================================================================
+// INTERNAL STATE
+// ============================================================================
+
+/// Hivemapper metrics tracked internally
+#[derive(Debug, Clone, Default)]
+struct HivemapperMetrics {
+    last_period_honey: f64,
+    total_honey: f64,
+    km_mapped: f64,
+    unique_km: f64,
+    hexes_contributed: u64,
+    connected_at: Option<DateTime<Utc>>,
+    last_refresh: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// Hivemapper Dashcam Mapping Adapter
+pub struct HivemapperAdapter {
+    config: HivemapperConfig,
+    http: reqwest::Client,
+    status: Arc<RwLock<ConnectionStatus>>,
+    allocation: Arc<RwLock<AllocationStrategy>>,
+    metrics: Arc<RwLock<HivemapperMetrics>>,
+}
+
+impl HivemapperAdapter {
+    /// Create a new Hivemapper adapter
+    pub fn new(config: HivemapperConfig) -> Self {
+        let allocation = AllocationStrategy {
+            cpu_cores: 0,
+            memory_gb: 0.0,
+            storage_gb: 0.0,
+            bandwidth_mbps: config.upload_bandwidth_mbps,
+            allocation_percent: 0.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            allocation: Arc::new(RwLock::new(allocation)),
+            metrics: Arc::new(RwLock::new(HivemapperMetrics::default())),
+        }
+    }
+
+    /// GET a contributor API resource for the configured wallet
+    async fn get<T: serde::de::DeserializeOwned>(&self, resource: &str) -> ProtocolResult<T> {
+        let url = format!(
+            "{}/{}/{}",
+            self.config.api_url.trim_end_matches('/'),
+            resource,
+            self.config.wallet_address
+        );
+
+        let response = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(ProtocolError::AuthenticationError(
+                "Hivemapper rejected the API key".to_string(),
+            ));
+        }
+        if !response.status().is_success() {
+            return Err(ProtocolError::ApiError(format!(
+                "Hivemapper {} returned {}",
+                resource,
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))
+    }
+
+    /// Refresh rewards and coverage from the contributor API
+    async fn refresh(&self) -> ProtocolResult<()> {
+        let rewards: RewardsResponse = self.get("rewards").await?;
+        let coverage: CoverageResponse = self.get("coverage").await?;
+
+        let mut metrics = self.metrics.write().await;
+        metrics.last_period_honey = rewards.rewards.first().map(|r| r.honey).unwrap_or(0.0);
+        metrics.total_honey = rewards.total_honey;
+        metrics.km_mapped = coverage.km_mapped;
+        metrics.unique_km = coverage.unique_km;
+        metrics.hexes_contributed = coverage.hexes_contributed;
+        metrics.last_refresh = Some(Utc::now());
+        Ok(())
+    }
+
+    /// USD per hour implied by the most recent reward period
+    fn hourly_usd(&self, metrics: &HivemapperMetrics) -> f64 {
+        metrics.last_period_honey / HOURS_PER_REWARD_PERIOD * self.config.honey_price_usd
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for HivemapperAdapter {
+    fn protocol_name(&self) -> &str {
+        "Hivemapper"
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        if self.config.wallet_address.is_empty() {
+            return Err(ProtocolError::ConfigurationError(
+                "Hivemapper wallet address not configured".to_string(),
+            ));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        if let Err(e) = self.refresh().await {
+            *self.status.write().await = ConnectionStatus::Failed;
+            return Err(ProtocolError::ConnectionError(format!(
+                "Hivemapper API unreachable: {}",
+                e
+            )));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.metrics.write().await.connected_at = Some(Utc::now());
+
+        tracing::info!("Connected to Hivemapper as {}", self.config.wallet_address);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        if *self.status.read().await == ConnectionStatus::Disconnected {
+            return Ok(());
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.metrics.write().await.connected_at = None;
+
+        tracing::info!("Disconnected from Hivemapper");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .try_read()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Connecting)
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.refresh().await?;
+
+        let metrics = self.metrics.read().await;
+
+        let mut metric_map = HashMap::new();
+        metric_map.insert("honey_last_period".to_string(), metrics.last_period_honey);
+        metric_map.insert("honey_total".to_string(), metrics.total_honey);
+        metric_map.insert("km_mapped".to_string(), metrics.km_mapped);
+        metric_map.insert("unique_km".to_string(), metrics.unique_km);
+        metric_map.insert(
+            "hexes_contributed".to_string(),
+            metrics.hexes_contributed as f64,
+        );
+
+        Ok(EarningsData {
+            timestamp: Utc::now(),
+            amount_usd: self.hourly_usd(&metrics),
+            protocol_id: "hivemapper".to_string(),
+            metrics: metric_map,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // Rewards are paid per week; the API has no hourly history
+        Err(ProtocolError::UnsupportedError(
+            "The Hivemapper API does not expose hourly earnings history".to_string(),
+        ))
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        let metrics = self.metrics.read().await;
+        let uptime_seconds = metrics
+            .connected_at
+            .map(|t| Utc::now().signed_duration_since(t).num_seconds().max(0) as u64)
+            .unwrap_or(0);
+
+        Ok(ResourceMetrics {
+            cpu_percent: 0.0,
+            memory_mb: 0.0,
+            bandwidth_mbps: self.config.upload_bandwidth_mbps,
+            storage_gb: 0.0,
+            uptime_seconds,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        // Mapping happens on the dashcam; only the upload reservation is tunable.
+        if strategy.bandwidth_mbps < 0.0 {
+            return Err(ProtocolError::AllocationError(
+                "Upload bandwidth cannot be negative".to_string(),
+            ));
+        }
+
+        *self.allocation.write().await = strategy;
+        tracing::info!("Applied allocation strategy to Hivemapper");
+        Ok(())
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        Ok(self.allocation.read().await.clone())
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let status = *self.status.read().await;
+        let is_healthy = status == ConnectionStatus::Connected;
+        let metrics = self.metrics.read().await;
+
+        let mut health_metrics = HashMap::new();
+        health_metrics.insert("km_mapped".into(), serde_json::json!(metrics.km_mapped));
+        health_metrics.insert(
+            "hexes_contributed".into(),
+            serde_json::json!(metrics.hexes_contributed),
+        );
+
+        Ok(HealthStatus {
+            is_healthy,
+            connection_status: status,
+            last_operation: metrics.last_refresh,
+            error_message: if is_healthy {
+                None
+            } else {
+                Some("Not connected to Hivemapper".to_string())
+            },
+            metrics: health_metrics,
+        })
+    }
+
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "protocol": "hivemapper",
+            "api_url": self.config.api_url,
+            "wallet_address": self.config.wallet_address,
+            "honey_price_usd": self.config.honey_price_usd,
+            "upload_bandwidth_mbps": self.config.upload_bandwidth_mbps,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(ResourceKind::Bandwidth, 0.0, 0.0).low_resource()
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new(
+                "honey_last_period",
+                "HONEY",
+                MetricAggregation::Last,
+                "HONEY earned in the last reward period",
+            ),
+            MetricSchema::new("honey_total", "HONEY", MetricAggregation::Last, "HONEY earned since start"),
+            MetricSchema::new("km_mapped", "km", MetricAggregation::Last, "Kilometres mapped"),
+            MetricSchema::new("unique_km", "km", MetricAggregation::Last, "Kilometres mapped for the first time"),
+            MetricSchema::new("hexes_contributed", "count", MetricAggregation::Last, "Map hexes contributed"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(api_url: String) -> HivemapperConfig {
+        HivemapperConfig {
+            api_url,
+            api_key: "key".to_string(),
+            wallet_address: "wallet1".to_string(),
+            honey_price_usd: 0.05,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hivemapper_connect_requires_wallet() {
+        let mut adapter = HivemapperAdapter::new(HivemapperConfig::default());
+        assert!(adapter.connect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hivemapper_earnings_and_coverage() {
+        let mut server = mockito::Server::new_async().await;
+        let _rewards = server
+            .mock("GET", "/rewards/wallet1")
+            .match_header("authorization", "Bearer key")
+            .with_body(r#"{"rewards":[{"honey":336.0},{"honey":100.0}],"total_honey":436.0}"#)
+            .create_async()
+            .await;
+        let _coverage = server
+            .mock("GET", "/coverage/wallet1")
+            .with_body(r#"{"km_mapped":812.5,"unique_km":240.0,"hexes_contributed":1200}"#)
+            .create_async()
+            .await;
+
+        let mut adapter = HivemapperAdapter::new(test_config(server.url()));
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.protocol_id, "hivemapper");
+        // 336 HONEY / 168h * $0.05
+        assert!((earnings.amount_usd - 0.1).abs() < 1e-9);
+        assert_eq!(earnings.metrics["unique_km"], 240.0);
+    }
+
+    #[test]
+    fn test_hivemapper_is_low_resource() {
+        let adapter = HivemapperAdapter::new(HivemapperConfig::default());
+        assert!(adapter.resource_profile().low_resource);
+    }
+}
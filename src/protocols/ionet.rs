@@ -0,0 +1,535 @@
+/// io.net GPU Cluster Adapter
+///
+/// io.net aggregates GPUs from independent workers into on-demand clusters for
+/// AI/ML workloads. Workers earn USD-denominated rewards while their GPUs are
+/// hired into clusters.
+///
+/// This adapter manages:
+/// - Worker registration checks against the io.net worker API
+/// - Cluster participation (hired GPUs, active cluster)
+/// - Hourly USD earnings
+/// - GPU health metrics (utilization, temperature, VRAM)
+use super::{
+    AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// io.net protocol configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IoNetConfig {
+    /// io.net worker API base URL
+    pub api_url: String,
+    /// Worker API token
+    pub api_token: String,
+    /// Registered device ID
+    pub device_id: String,
+    /// GPUs installed on the worker
+    pub gpu_count: u32,
+    /// VRAM per GPU in GB
+    pub vram_per_gpu_gb: f64,
+    /// GPU temperature above which the worker is reported unhealthy
+    pub max_gpu_temperature_c: f64,
+    /// Minimum allocation percent
+    pub min_allocation_percent: f64,
+    /// Maximum allocation percent
+    pub max_allocation_percent: f64,
+}
+
+impl Default for IoNetConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://api.io.solutions/v1/io-worker".to_string(),
+            api_token: String::new(),
+            device_id: String::new(),
+            gpu_count: 1,
+            vram_per_gpu_gb: 24.0,
+            max_gpu_temperature_c: 85.0,
+            min_allocation_percent: 0.0,
+            max_allocation_percent: 100.0,
+        }
+    }
+}
+
+// ============================================================================
+// WORKER API
+// ============================================================================
+
+/// Envelope returned by the worker API
+#[derive(Debug, Deserialize)]
+struct ApiEnvelope<T> {
+    data: T,
+}
+
+/// Subset of the device details response
+#[derive(Debug, Deserialize)]
+struct DeviceDetails {
+    /// `up`, `down`, `unregistered`, ...
+    status: String,
+    #[serde(default)]
+    cluster_id: Option<String>,
+    #[serde(default)]
+    hired_gpus: u32,
+    #[serde(default)]
+    hourly_earnings_usd: f64,
+    #[serde(default)]
+    total_earnings_usd: f64,
+    #[serde(default)]
+    gpus: Vec<GpuStats>,
+}
+
+/// Per-GPU health reported by the worker
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GpuStats {
+    /// GPU model name
+    #[serde(default)]
+    pub model: String,
+    /// Utilization percentage (0-100)
+    #[serde(default)]
+    pub utilization_percent: f64,
+    /// Core temperature in Celsius
+    #[serde(default)]
+    pub temperature_c: f64,
+    /// VRAM in use in GB
+    #[serde(default)]
+    pub vram_used_gb: f64,
+}
+
+// ============================================================================
+// INTERNAL STATE
+// ============================================================================
+
+/// io.net metrics tracked internally
+#[derive(Debug, Clone, Default)]
+struct IoNetMetrics {
+    registered: bool,
+    cluster_id: Option<String>,
+    hired_gpus: u32,
+    hourly_earnings_usd: f64,
+    total_earnings_usd: f64,
+    gpus: Vec<GpuStats>,
+    connected_at: Option<DateTime<Utc>>,
+    last_refresh: Option<DateTime<Utc>>,
+}
+
+impl IoNetMetrics {
+    fn avg_gpu_utilization(&self) -> f64 {
+        if self.gpus.is_empty() {
+            return 0.0;
+        }
+        self.gpus.iter().map(|g| g.utilization_percent).sum::<f64>() / self.gpus.len() as f64
+    }
+
+    fn max_gpu_temperature(&self) -> f64 {
+        self.gpus.iter().map(|g| g.temperature_c).fold(0.0, f64::max)
+    }
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// io.net GPU Cluster Adapter
+pub struct IoNetAdapter {
+    config: IoNetConfig,
+    http: reqwest::Client,
+    status: Arc<RwLock<ConnectionStatus>>,
+    allocation: Arc<RwLock<AllocationStrategy>>,
+    metrics: Arc<RwLock<IoNetMetrics>>,
+}
+
+impl IoNetAdapter {
+    /// Create a new io.net adapter
+    pub fn new(config: IoNetConfig) -> Self {
+        let allocation = AllocationStrategy {
+            cpu_cores: 2,
+            memory_gb: 8.0,
+            storage_gb: 50.0,
+            bandwidth_mbps: 100.0,
+            allocation_percent: 100.0,
+            gpu_count: config.gpu_count,
+            vram_gb: config.gpu_count as f64 * config.vram_per_gpu_gb,
+            pricing: None,
+        };
+
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            allocation: Arc::new(RwLock::new(allocation)),
+            metrics: Arc::new(RwLock::new(IoNetMetrics::default())),
+        }
+    }
+
+    /// GPUs offered for a given allocation percent
+    fn gpus_for_allocation(&self, allocation_percent: f64) -> u32 {
+        (self.config.gpu_count as f64 * allocation_percent / 100.0).round() as u32
+    }
+
+    /// Refresh worker state from the io.net API
+    async fn refresh(&self) -> ProtocolResult<()> {
+        let url = format!(
+            "{}/devices/{}/details",
+            self.config.api_url.trim_end_matches('/'),
+            self.config.device_id
+        );
+
+        let response = self
+            .http
+            .get(&url)
+            .header("token", &self.config.api_token)
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        match response.status() {
+            s if s == reqwest::StatusCode::UNAUTHORIZED || s == reqwest::StatusCode::FORBIDDEN => {
+                return Err(ProtocolError::AuthenticationError(
+                    "io.net rejected the worker API token".to_string(),
+                ));
+            }
+            s if !s.is_success() => {
+                return Err(ProtocolError::ApiError(format!("Worker API returned {}", s)));
+            }
+            _ => {}
+        }
+
+        let details: ApiEnvelope<DeviceDetails> = response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))?;
+        let details = details.data;
+
+        let mut metrics = self.metrics.write().await;
+        metrics.registered = details.status != "unregistered";
+        metrics.cluster_id = details.cluster_id;
+        metrics.hired_gpus = details.hired_gpus;
+        metrics.hourly_earnings_usd = details.hourly_earnings_usd;
+        metrics.total_earnings_usd = details.total_earnings_usd;
+        metrics.gpus = details.gpus;
+        metrics.last_refresh = Some(Utc::now());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for IoNetAdapter {
+    fn protocol_name(&self) -> &str {
+        "io.net"
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        if self.config.device_id.is_empty() || self.config.api_token.is_empty() {
+            return Err(ProtocolError::ConfigurationError(
+                "io.net device ID and API token must be configured".to_string(),
+            ));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        if let Err(e) = self.refresh().await {
+            *self.status.write().await = ConnectionStatus::Failed;
+            return Err(ProtocolError::ConnectionError(format!(
+                "io.net worker API unreachable: {}",
+                e
+            )));
+        }
+
+        if !self.metrics.read().await.registered {
+            *self.status.write().await = ConnectionStatus::Failed;
+            return Err(ProtocolError::AuthenticationError(format!(
+                "Device {} is not registered with io.net",
+                self.config.device_id
+            )));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.metrics.write().await.connected_at = Some(Utc::now());
+
+        tracing::info!("Connected to io.net as device {}", self.config.device_id);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        if *self.status.read().await == ConnectionStatus::Disconnected {
+            return Ok(());
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.metrics.write().await.connected_at = None;
+
+        tracing::info!("Disconnected from io.net");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .try_read()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Connecting)
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.refresh().await?;
+
+        let metrics = self.metrics.read().await;
+
+        let mut metric_map = HashMap::new();
+        metric_map.insert("hired_gpus".to_string(), metrics.hired_gpus as f64);
+        metric_map.insert(
+            "in_cluster".to_string(),
+            if metrics.cluster_id.is_some() { 1.0 } else { 0.0 },
+        );
+        metric_map.insert("total_earnings_usd".to_string(), metrics.total_earnings_usd);
+        metric_map.insert("gpu_utilization".to_string(), metrics.avg_gpu_utilization());
+
+        Ok(EarningsData {
+            timestamp: Utc::now(),
+            amount_usd: metrics.hourly_earnings_usd,
+            protocol_id: "ionet".to_string(),
+            metrics: metric_map,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // The worker API only reports the current hourly rate
+        Err(ProtocolError::UnsupportedError(
+            "The io.net worker API does not expose historical earnings".to_string(),
+        ))
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        let metrics = self.metrics.read().await;
+        let uptime_seconds = metrics
+            .connected_at
+            .map(|t| Utc::now().signed_duration_since(t).num_seconds().max(0) as u64)
+            .unwrap_or(0);
+
+        Ok(ResourceMetrics {
+            cpu_percent: 10.0,
+            memory_mb: 4096.0,
+            bandwidth_mbps: 50.0,
+            storage_gb: 50.0,
+            uptime_seconds,
+            gpu_count: metrics.hired_gpus,
+            vram_gb: metrics.gpus.iter().map(|g| g.vram_used_gb).sum(),
+            gpu_utilization_percent: metrics.avg_gpu_utilization(),
+            stale_seconds: 0,
+        })
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        if !(self.config.min_allocation_percent..=self.config.max_allocation_percent)
+            .contains(&strategy.allocation_percent)
+        {
+            return Err(ProtocolError::AllocationError(format!(
+                "Allocation must be between {} and {}%",
+                self.config.min_allocation_percent, self.config.max_allocation_percent
+            )));
+        }
+
+        let gpus = self.gpus_for_allocation(strategy.allocation_percent);
+
+        // GPUs hired into a running cluster cannot be withdrawn mid-job.
+        let hired = self.metrics.read().await.hired_gpus;
+        if gpus < hired {
+            return Err(ProtocolError::AllocationError(format!(
+                "{} GPUs are hired into a cluster; cannot offer only {}",
+                hired, gpus
+            )));
+        }
+
+        *self.allocation.write().await = AllocationStrategy {
+            gpu_count: gpus,
+            vram_gb: gpus as f64 * self.config.vram_per_gpu_gb,
+            ..strategy
+        };
+
+        tracing::info!("Applied allocation strategy to io.net ({} GPUs offered)", gpus);
+        Ok(())
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        Ok(self.allocation.read().await.clone())
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let status = *self.status.read().await;
+        let metrics = self.metrics.read().await;
+        let max_temperature = metrics.max_gpu_temperature();
+        let overheating = max_temperature > self.config.max_gpu_temperature_c;
+        let is_healthy = status == ConnectionStatus::Connected && !overheating;
+
+        let mut health_metrics = HashMap::new();
+        health_metrics.insert("hired_gpus".into(), serde_json::json!(metrics.hired_gpus));
+        health_metrics.insert("cluster_id".into(), serde_json::json!(metrics.cluster_id));
+        health_metrics.insert(
+            "gpu_utilization".into(),
+            serde_json::json!(metrics.avg_gpu_utilization()),
+        );
+        health_metrics.insert(
+            "max_gpu_temperature_c".into(),
+            serde_json::json!(max_temperature),
+        );
+        health_metrics.insert("gpus".into(), serde_json::json!(metrics.gpus));
+
+        Ok(HealthStatus {
+            is_healthy,
+            connection_status: status,
+            last_operation: metrics.last_refresh,
+            error_message: if overheating {
+                Some(format!("GPU temperature {:.0}°C exceeds limit", max_temperature))
+            } else if !is_healthy {
+                Some("Not connected to io.net".to_string())
+            } else {
+                None
+            },
+            metrics: health_metrics,
+        })
+    }
+
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "protocol": "ionet",
+            "api_url": self.config.api_url,
+            "device_id": self.config.device_id,
+            "gpu_count": self.config.gpu_count,
+            "vram_per_gpu_gb": self.config.vram_per_gpu_gb,
+            "min_allocation_percent": self.config.min_allocation_percent,
+            "max_allocation_percent": self.config.max_allocation_percent,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(
+            ResourceKind::Gpu,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        )
+        .with_secondary(&[ResourceKind::Cpu, ResourceKind::Memory])
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new("hired_gpus", "count", MetricAggregation::Mean, "GPUs hired by clusters"),
+            MetricSchema::new(
+                "in_cluster",
+                "bool",
+                MetricAggregation::Mean,
+                "Whether the worker is in a cluster (1 or 0)",
+            ),
+            MetricSchema::new("total_earnings_usd", "USD", MetricAggregation::Last, "Earnings since start"),
+            MetricSchema::new("gpu_utilization", "percent", MetricAggregation::Mean, "Average GPU utilization"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DETAILS: &str = r#"{"data":{"status":"up","cluster_id":"c-42","hired_gpus":2,
+        "hourly_earnings_usd":0.84,"total_earnings_usd":120.5,
+        "gpus":[{"model":"RTX 4090","utilization_percent":90.0,"temperature_c":70.0,"vram_used_gb":20.0},
+                {"model":"RTX 4090","utilization_percent":70.0,"temperature_c":91.0,"vram_used_gb":18.0}]}}"#;
+
+    fn test_config(api_url: String) -> IoNetConfig {
+        IoNetConfig {
+            api_url,
+            api_token: "token".to_string(),
+            device_id: "dev-1".to_string(),
+            gpu_count: 4,
+            ..Default::default()
+        }
+    }
+
+    fn strategy(percent: f64) -> AllocationStrategy {
+        AllocationStrategy {
+            cpu_cores: 2,
+            memory_gb: 8.0,
+            storage_gb: 50.0,
+            bandwidth_mbps: 100.0,
+            allocation_percent: percent,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ionet_connect_requires_device() {
+        let mut adapter = IoNetAdapter::new(IoNetConfig::default());
+        assert!(adapter.connect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ionet_earnings_and_gpu_health() {
+        let mut server = mockito::Server::new_async().await;
+        let _details = server
+            .mock("GET", "/devices/dev-1/details")
+            .match_header("token", "token")
+            .with_body(DETAILS)
+            .create_async()
+            .await;
+
+        let mut adapter = IoNetAdapter::new(test_config(server.url()));
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.protocol_id, "ionet");
+        assert_eq!(earnings.amount_usd, 0.84);
+        assert_eq!(earnings.metrics["in_cluster"], 1.0);
+
+        let resources = adapter.get_resource_usage().await.unwrap();
+        assert_eq!(resources.gpu_count, 2);
+        assert_eq!(resources.gpu_utilization_percent, 80.0);
+
+        // Second GPU is above the 85°C default limit
+        let health = adapter.health_check().await.unwrap();
+        assert!(!health.is_healthy);
+    }
+
+    #[tokio::test]
+    async fn test_ionet_unregistered_device_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        let _details = server
+            .mock("GET", "/devices/dev-1/details")
+            .with_body(r#"{"data":{"status":"unregistered"}}"#)
+            .create_async()
+            .await;
+
+        let mut adapter = IoNetAdapter::new(test_config(server.url()));
+        assert!(matches!(
+            adapter.connect().await,
+            Err(ProtocolError::AuthenticationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ionet_allocation_keeps_hired_gpus() {
+        let mut adapter = IoNetAdapter::new(test_config("http://localhost".to_string()));
+        adapter.metrics.write().await.hired_gpus = 2;
+
+        assert!(adapter.apply_allocation(strategy(25.0)).await.is_err());
+        adapter.apply_allocation(strategy(75.0)).await.unwrap();
+
+        let allocation = adapter.get_current_allocation().await.unwrap();
+        assert_eq!(allocation.gpu_count, 3);
+        assert_eq!(allocation.vram_gb, 72.0);
+    }
+}
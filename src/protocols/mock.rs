@@ -0,0 +1,395 @@
+/// Mock Protocol Adapter
+///
+/// Scriptable adapter for testing the orchestration loop without live
+/// protocol accounts. Available to this crate's tests and, with the
+/// `test-util` feature, to downstream crates.
+///
+/// This adapter provides:
+/// - Earnings curves, advanced one step at a time
+/// - Failure injection per operation, for a number of calls or until recovered
+/// - Latency injection per operation
+/// - A record of every allocation applied
+///
+/// The adapter is moved into the coordinator when registered; keep its
+/// `MockHandle` to script it afterwards.
+use super::{
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Earnings of a mock protocol over simulation steps, in USD/hour at 100%
+/// allocation
+///
+/// Reported earnings are scaled by the adapter's current allocation percent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EarningsCurve {
+    /// Same rate every step
+    Constant(f64),
+    /// `start` at step 0, changing by `slope` per step (never below zero)
+    Linear { start: f64, slope: f64 },
+    /// One rate per step, the last repeated once the steps run out
+    Steps(Vec<f64>),
+}
+
+impl EarningsCurve {
+    /// Rate at 100% allocation on `step`
+    pub fn rate_at(&self, step: u64) -> f64 {
+        match self {
+            Self::Constant(rate) => *rate,
+            Self::Linear { start, slope } => (start + slope * step as f64).max(0.0),
+            Self::Steps(rates) => {
+                let index = (step as usize).min(rates.len().saturating_sub(1));
+                rates.get(index).copied().unwrap_or(0.0)
+            }
+        }
+    }
+}
+
+/// Adapter call that failures and latency can be injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockOperation {
+    Connect,
+    Disconnect,
+    /// Current and historical earnings
+    Earnings,
+    Resources,
+    ApplyAllocation,
+    GetAllocation,
+    Health,
+    /// Fails as a rejected credential (`AuthenticationError`)
+    Credentials,
+}
+
+/// Mutable state shared between a mock adapter and its handle
+#[derive(Debug)]
+struct MockState {
+    curve: EarningsCurve,
+    step: u64,
+    status: ConnectionStatus,
+    allocation: AllocationStrategy,
+    /// Remaining injected failures per operation; `None` fails until recovered
+    failures: HashMap<MockOperation, Option<u32>>,
+    latency: HashMap<MockOperation, Duration>,
+    calls: HashMap<MockOperation, u32>,
+    applied: Vec<f64>,
+}
+
+// ============================================================================
+// HANDLE
+// ============================================================================
+
+/// Scripts a mock adapter after it has been handed to the coordinator
+#[derive(Debug, Clone)]
+pub struct MockHandle {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockHandle {
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Move the earnings curve on by one step
+    pub fn advance(&self) {
+        self.state().step += 1;
+    }
+
+    /// Current simulation step
+    pub fn step(&self) -> u64 {
+        self.state().step
+    }
+
+    /// Replace the earnings curve; the step count carries on
+    pub fn set_earnings(&self, curve: EarningsCurve) {
+        self.state().curve = curve;
+    }
+
+    /// Fail the next `times` calls of `operation`
+    pub fn fail(&self, operation: MockOperation, times: u32) {
+        if times > 0 {
+            self.state().failures.insert(operation, Some(times));
+        }
+    }
+
+    /// Fail every call of `operation` until `recover`
+    pub fn fail_always(&self, operation: MockOperation) {
+        self.state().failures.insert(operation, None);
+    }
+
+    /// Stop injecting failures into `operation`
+    pub fn recover(&self, operation: MockOperation) {
+        self.state().failures.remove(&operation);
+    }
+
+    /// Delay every call of `operation` by `latency`
+    pub fn set_latency(&self, operation: MockOperation, latency: Duration) {
+        self.state().latency.insert(operation, latency);
+    }
+
+    /// Current allocation percent
+    pub fn allocation_percent(&self) -> f64 {
+        self.state().allocation.allocation_percent
+    }
+
+    /// Allocation percents applied so far, oldest first
+    pub fn applied(&self) -> Vec<f64> {
+        self.state().applied.clone()
+    }
+
+    /// Calls made to `operation`, including failed ones
+    pub fn calls(&self, operation: MockOperation) -> u32 {
+        self.state().calls.get(&operation).copied().unwrap_or(0)
+    }
+
+    /// Count a call to `operation`, wait out its latency and fail it if a
+    /// failure is injected
+    async fn call(&self, operation: MockOperation) -> ProtocolResult<()> {
+        let latency = {
+            let mut state = self.state();
+            *state.calls.entry(operation).or_insert(0) += 1;
+            state.latency.get(&operation).copied()
+        };
+        if let Some(latency) = latency {
+            tokio::time::sleep(latency).await;
+        }
+
+        let mut state = self.state();
+        let Some(remaining) = state.failures.get_mut(&operation) else {
+            return Ok(());
+        };
+        if let Some(count) = remaining {
+            *count -= 1;
+            if *count == 0 {
+                state.failures.remove(&operation);
+            }
+        }
+        Err(ProtocolError::ApiError(format!("Injected {:?} failure", operation)))
+    }
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// Mock Protocol Adapter
+pub struct MockAdapter {
+    name: String,
+    profile: ResourceProfile,
+    handle: MockHandle,
+}
+
+impl MockAdapter {
+    /// Create a connected mock earning `curve`, starting at `allocation_percent`
+    ///
+    /// Allocation bounds are 0-100% on bandwidth until `with_profile`.
+    pub fn new(name: &str, curve: EarningsCurve, allocation_percent: f64) -> Self {
+        let allocation = AllocationStrategy {
+            cpu_cores: 4,
+            memory_gb: 8.0,
+            storage_gb: 100.0,
+            bandwidth_mbps: 200.0,
+            allocation_percent,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+        let state = MockState {
+            curve,
+            step: 0,
+            status: ConnectionStatus::Connected,
+            allocation,
+            failures: HashMap::new(),
+            latency: HashMap::new(),
+            calls: HashMap::new(),
+            applied: Vec::new(),
+        };
+
+        Self {
+            name: name.to_string(),
+            profile: ResourceProfile::new(ResourceKind::Bandwidth, 0.0, 100.0),
+            handle: MockHandle { state: Arc::new(Mutex::new(state)) },
+        }
+    }
+
+    /// Report `profile` as this protocol's resource profile
+    pub fn with_profile(mut self, profile: ResourceProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Handle for scripting this adapter
+    pub fn handle(&self) -> MockHandle {
+        self.handle.clone()
+    }
+
+    /// Earnings rate in USD/hour at the current step and allocation
+    fn earnings_rate(&self) -> f64 {
+        let state = self.handle.state();
+        state.curve.rate_at(state.step) * state.allocation.allocation_percent / 100.0
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for MockAdapter {
+    fn protocol_name(&self) -> &str {
+        &self.name
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        self.handle.call(MockOperation::Connect).await?;
+        self.handle.state().status = ConnectionStatus::Connected;
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        self.handle.call(MockOperation::Disconnect).await?;
+        self.handle.state().status = ConnectionStatus::Disconnected;
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.handle.state().status
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.handle.call(MockOperation::Earnings).await?;
+
+        Ok(EarningsData {
+            timestamp: Utc::now(),
+            amount_usd: self.earnings_rate(),
+            protocol_id: self.name.clone(),
+            metrics: HashMap::new(),
+            stale_seconds: 0,
+        })
+    }
+
+    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        self.handle.call(MockOperation::Earnings).await?;
+
+        let rate = self.earnings_rate();
+        Ok((0..hours)
+            .map(|i| EarningsData {
+                timestamp: Utc::now() - chrono::Duration::hours(i as i64),
+                amount_usd: rate,
+                protocol_id: self.name.clone(),
+                metrics: HashMap::new(),
+                stale_seconds: 0,
+            })
+            .collect())
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        self.handle.call(MockOperation::Resources).await?;
+
+        let state = self.handle.state();
+        let share = state.allocation.allocation_percent / 100.0;
+        Ok(ResourceMetrics {
+            cpu_percent: 50.0 * share,
+            memory_mb: state.allocation.memory_gb * 1024.0 * share,
+            bandwidth_mbps: state.allocation.bandwidth_mbps * share,
+            storage_gb: state.allocation.storage_gb * share,
+            uptime_seconds: state.step * 60,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        self.handle.call(MockOperation::ApplyAllocation).await?;
+        super::validate_allocation_percent(strategy.allocation_percent)?;
+
+        let mut state = self.handle.state();
+        state.applied.push(strategy.allocation_percent);
+        state.allocation = strategy;
+        Ok(())
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        self.handle.call(MockOperation::GetAllocation).await?;
+        Ok(self.handle.state().allocation.clone())
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        self.handle.call(MockOperation::Health).await?;
+
+        let status = self.handle.state().status;
+        let is_healthy = status == ConnectionStatus::Connected;
+        let error = (!is_healthy).then(|| format!("{} is {}", self.name, status));
+        Ok(super::basic_health_status(is_healthy, status, error))
+    }
+
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        self.handle
+            .call(MockOperation::Credentials)
+            .await
+            .map_err(|e| ProtocolError::AuthenticationError(e.to_string()))?;
+        Ok(CredentialStatus::default())
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "protocol": self.name,
+            "mock": true,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        self.profile.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_earnings_curves() {
+        assert_eq!(EarningsCurve::Constant(2.0).rate_at(9), 2.0);
+        assert_eq!(EarningsCurve::Linear { start: 1.0, slope: -0.5 }.rate_at(1), 0.5);
+        assert_eq!(EarningsCurve::Linear { start: 1.0, slope: -0.5 }.rate_at(4), 0.0);
+
+        let steps = EarningsCurve::Steps(vec![1.0, 3.0]);
+        assert_eq!(steps.rate_at(0), 1.0);
+        assert_eq!(steps.rate_at(5), 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_injected_failures_and_latency() {
+        let mut adapter = MockAdapter::new("mock", EarningsCurve::Steps(vec![4.0, 8.0]), 50.0);
+        let handle = adapter.handle();
+
+        assert_eq!(adapter.get_current_earnings().await.unwrap().amount_usd, 2.0);
+        handle.advance();
+        assert_eq!(adapter.get_current_earnings().await.unwrap().amount_usd, 4.0);
+
+        handle.fail(MockOperation::Earnings, 2);
+        assert!(adapter.get_current_earnings().await.is_err());
+        assert!(adapter.get_current_earnings().await.is_err());
+        assert!(adapter.get_current_earnings().await.is_ok());
+        assert_eq!(handle.calls(MockOperation::Earnings), 5);
+
+        handle.fail_always(MockOperation::ApplyAllocation);
+        let current = adapter.get_current_allocation().await.unwrap();
+        let strategy = AllocationStrategy { allocation_percent: 80.0, ..current };
+        assert!(adapter.apply_allocation(strategy.clone()).await.is_err());
+        handle.recover(MockOperation::ApplyAllocation);
+        adapter.apply_allocation(strategy).await.unwrap();
+        assert_eq!(handle.applied(), vec![80.0]);
+        assert_eq!(handle.allocation_percent(), 80.0);
+
+        handle.set_latency(MockOperation::Health, Duration::from_millis(200));
+        let hung = tokio::time::timeout(Duration::from_millis(20), adapter.health_check()).await;
+        assert!(hung.is_err());
+    }
+}
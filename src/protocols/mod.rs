@@ -3,11 +3,22 @@
 /// Provides trait definitions and implementations for all supported DePIN protocols.
 /// Each protocol adapter manages connection, earnings tracking, and resource allocation
 /// for its respective network.
-
 pub mod streamr;
 pub mod storj;
 pub mod golem;
 pub mod grass;
+pub mod filecoin;
+pub mod akash;
+pub mod theta;
+pub mod ionet;
+pub mod hivemapper;
+pub mod weatherxm;
+pub mod registry;
+pub mod http;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+#[cfg(feature = "grpc-adapters")]
+pub mod grpc;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -61,6 +72,111 @@ pub type ProtocolResult<T> = Result<T, ProtocolError>;
 // DATA STRUCTURES
 // ============================================================================
 
+/// Earnings metric in which adapters report marketplace demand relative to
+/// its recent baseline (1.0 is normal, 2.0 twice the usual demand)
+pub const DEMAND_INDEX_METRIC: &str = "demand_index";
+
+/// Earnings metric in which adapters report yield relative to its recent
+/// baseline (1.0 is unchanged, 1.2 a fifth higher than usual)
+pub const YIELD_INDEX_METRIC: &str = "yield_index";
+
+/// Prefix of the earnings metrics in which multi-device adapters report each
+/// device (`device_connected:<device id>`, 1.0 connected and 0.0 offline)
+pub const DEVICE_CONNECTED_METRIC_PREFIX: &str = "device_connected:";
+
+/// Devices and whether each is connected, from an adapter's earnings
+/// metrics, ordered by device id
+pub fn device_connectivity(metrics: &HashMap<String, f64>) -> Vec<(&str, bool)> {
+    let mut devices: Vec<_> = metrics
+        .iter()
+        .filter_map(|(name, value)| {
+            let device = name.strip_prefix(DEVICE_CONNECTED_METRIC_PREFIX)?;
+            Some((device, *value > 0.0))
+        })
+        .collect();
+    devices.sort_unstable();
+    devices
+}
+
+/// How a protocol-specific metric combines over time (e.g. into an hourly
+/// or daily value)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricAggregation {
+    /// Amounts accrued per poll, summed
+    Sum,
+    /// Rates and ratios, averaged
+    Mean,
+    /// Scores where the worst value matters
+    Min,
+    Max,
+    /// Running totals and gauges, latest value wins
+    Last,
+}
+
+impl MetricAggregation {
+    /// Name stored with metric values
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Mean => "mean",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Last => "last",
+        }
+    }
+}
+
+/// Declaration of one protocol-specific earnings metric
+///
+/// Labeled metrics are reported once per entity as `<name>:<label value>`
+/// (e.g. `audit_score:<satellite id>`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSchema {
+    pub name: String,
+    pub unit: String,
+    pub description: String,
+    pub aggregation: MetricAggregation,
+    /// What the `:<label>` suffix identifies, for labeled metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl MetricSchema {
+    /// Declare an unlabeled metric
+    pub fn new(name: &str, unit: &str, aggregation: MetricAggregation, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            unit: unit.to_string(),
+            description: description.to_string(),
+            aggregation,
+            label: None,
+        }
+    }
+
+    /// Report this metric once per `label` (`<name>:<label value>`)
+    pub fn labeled(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Whether `key` (an `EarningsData.metrics` key) is an instance of this metric
+    pub fn matches(&self, key: &str) -> bool {
+        match &self.label {
+            None => key == self.name,
+            Some(_) => key
+                .strip_prefix(self.name.as_str())
+                .and_then(|rest| rest.strip_prefix(':'))
+                .is_some_and(|value| !value.is_empty()),
+        }
+    }
+}
+
+/// Declaration in `schema` of the metric reported as `key`
+pub fn find_metric_schema<'a>(schema: &'a [MetricSchema], key: &str) -> Option<&'a MetricSchema> {
+    schema.iter().find(|metric| metric.matches(key))
+}
+
 /// Connection status states
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConnectionStatus {
@@ -99,6 +215,9 @@ pub struct EarningsData {
     pub protocol_id: String,
     /// Optional detailed metrics
     pub metrics: HashMap<String, f64>,
+    /// Age of the data in seconds when served from the response cache; 0 when fresh
+    #[serde(default)]
+    pub stale_seconds: u64,
 }
 
 /// Resource usage metrics
@@ -114,6 +233,18 @@ pub struct ResourceMetrics {
     pub storage_gb: f64,
     /// Uptime in seconds
     pub uptime_seconds: u64,
+    /// GPUs in use
+    #[serde(default)]
+    pub gpu_count: u32,
+    /// VRAM in use in GB
+    #[serde(default)]
+    pub vram_gb: f64,
+    /// GPU utilization percentage (0-100)
+    #[serde(default)]
+    pub gpu_utilization_percent: f64,
+    /// Age of the data in seconds when served from the response cache; 0 when fresh
+    #[serde(default)]
+    pub stale_seconds: u64,
 }
 
 /// Resource allocation strategy
@@ -129,6 +260,146 @@ pub struct AllocationStrategy {
     pub bandwidth_mbps: f64,
     /// Allocation percentage (0-100)
     pub allocation_percent: f64,
+    /// GPUs allocated
+    #[serde(default)]
+    pub gpu_count: u32,
+    /// VRAM allocated in GB
+    #[serde(default)]
+    pub vram_gb: f64,
+    /// Prices to offer resources at; `None` keeps the adapter's current pricing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ResourcePricing>,
+}
+
+/// Prices a compute provider offers its resources at, in the protocol's token
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResourcePricing {
+    /// Price per CPU-hour
+    pub cpu_per_hour: f64,
+    /// Price per hour an activity's environment is running
+    pub env_per_hour: f64,
+    /// Price charged when an activity starts
+    pub start_price: f64,
+}
+
+impl ResourcePricing {
+    /// Validate that every price is a finite, non-negative amount
+    pub fn validate(&self) -> ProtocolResult<()> {
+        let prices = [self.cpu_per_hour, self.env_per_hour, self.start_price];
+        if prices.iter().all(|p| p.is_finite() && *p >= 0.0) {
+            Ok(())
+        } else {
+            Err(ProtocolError::AllocationError(
+                "Prices must be finite and not negative".to_string(),
+            ))
+        }
+    }
+}
+
+/// Resource dimension a protocol consumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    /// CPU cores
+    Cpu,
+    /// System memory
+    Memory,
+    /// Disk storage
+    Storage,
+    /// Network bandwidth
+    Bandwidth,
+    /// GPU compute
+    Gpu,
+}
+
+impl std::fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cpu => write!(f, "cpu"),
+            Self::Memory => write!(f, "memory"),
+            Self::Storage => write!(f, "storage"),
+            Self::Bandwidth => write!(f, "bandwidth"),
+            Self::Gpu => write!(f, "gpu"),
+        }
+    }
+}
+
+/// Capability descriptor for a protocol adapter
+///
+/// Allocation percent is only fungible between protocols that share a
+/// primary resource: moving share from Grass (bandwidth) to Golem (CPU) frees
+/// nothing Golem can use.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceProfile {
+    /// Resource that drives this protocol's earnings
+    pub primary: ResourceKind,
+    /// Other resources consumed as a side effect
+    pub secondary: Vec<ResourceKind>,
+    /// Minimum allocation percent the protocol accepts
+    pub min_allocation_percent: f64,
+    /// Maximum allocation percent the protocol accepts
+    pub max_allocation_percent: f64,
+    /// Protocol uses a negligible share of host resources (e.g. dashcam or
+    /// sensor uploads) and is left out of utilization averages
+    #[serde(default)]
+    pub low_resource: bool,
+    /// Earnings are aggregated but the optimizer never reallocates the protocol
+    #[serde(default)]
+    pub observe_only: bool,
+}
+
+impl ResourceProfile {
+    /// Create a profile for a primary resource and allocation bounds
+    pub fn new(primary: ResourceKind, min_allocation_percent: f64, max_allocation_percent: f64) -> Self {
+        Self {
+            primary,
+            secondary: Vec::new(),
+            min_allocation_percent,
+            max_allocation_percent,
+            low_resource: false,
+            observe_only: false,
+        }
+    }
+
+    /// Profile for a protocol with no allocatable resources
+    pub fn observe_only() -> Self {
+        Self {
+            observe_only: true,
+            ..Self::new(ResourceKind::Bandwidth, 0.0, 0.0).low_resource()
+        }
+    }
+
+    /// Mark the protocol as consuming negligible host resources
+    pub fn low_resource(mut self) -> Self {
+        self.low_resource = true;
+        self
+    }
+
+    /// Add secondary resources consumed by the protocol
+    pub fn with_secondary(mut self, secondary: &[ResourceKind]) -> Self {
+        self.secondary = secondary.to_vec();
+        self
+    }
+
+    /// Whether the protocol consumes the given resource at all
+    pub fn uses(&self, kind: ResourceKind) -> bool {
+        self.primary == kind || self.secondary.contains(&kind)
+    }
+
+    /// Whether allocation can be moved between the two protocols
+    ///
+    /// Share backed by GPUs is never moved to a protocol that cannot use them.
+    pub fn competes_with(&self, other: &ResourceProfile) -> bool {
+        !self.observe_only
+            && !other.observe_only
+            && self.primary == other.primary
+            && (!self.uses(ResourceKind::Gpu) || other.uses(ResourceKind::Gpu))
+    }
+
+    /// Clamp an allocation percent into this profile's bounds
+    pub fn clamp(&self, percent: f64) -> f64 {
+        percent.clamp(self.min_allocation_percent, self.max_allocation_percent)
+    }
 }
 
 /// Health status report
@@ -146,6 +417,13 @@ pub struct HealthStatus {
     pub metrics: HashMap<String, serde_json::Value>,
 }
 
+/// Outcome of a credential check that passed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CredentialStatus {
+    /// When the credential stops working, if it carries an expiry
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
 // ============================================================================
 // PROTOCOL ADAPTER TRAIT
 // ============================================================================
@@ -169,6 +447,10 @@ pub trait ProtocolAdapter: Send + Sync {
     async fn get_current_earnings(&self) -> ProtocolResult<EarningsData>;
 
     /// Get historical earnings
+    ///
+    /// Only adapters that read a real earnings ledger return history; the
+    /// others return `UnsupportedError` rather than simulating past hours or
+    /// projecting the current rate backwards.
     async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>>;
 
     /// Get resource usage metrics
@@ -183,8 +465,34 @@ pub trait ProtocolAdapter: Send + Sync {
     /// Health check
     async fn health_check(&self) -> ProtocolResult<HealthStatus>;
 
+    /// Validate the configured credentials, independent of polling
+    ///
+    /// Returns `AuthenticationError` when a credential is missing or was
+    /// rejected. Adapters without credentials to check report an empty status.
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        Ok(CredentialStatus::default())
+    }
+
+    /// Send API requests through a client from `http`
+    ///
+    /// `protocol` is the `[protocols.<name>]` section the adapter was built
+    /// from. Adapters without an HTTP API ignore it.
+    fn configure_http(&mut self, _http: &http::HttpClientFactory, _protocol: &str) -> ProtocolResult<()> {
+        Ok(())
+    }
+
     /// Get configuration as JSON value
     fn get_config(&self) -> serde_json::Value;
+
+    /// Describe which resources this protocol consumes and its allocation bounds
+    fn resource_profile(&self) -> ResourceProfile;
+
+    /// Protocol-specific metrics this adapter reports in `EarningsData.metrics`
+    ///
+    /// Only declared metrics are persisted with metrics snapshots.
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        Vec::new()
+    }
 }
 
 // ============================================================================
@@ -244,6 +552,60 @@ mod tests {
         assert!(status.error_message.is_none());
     }
 
+    #[test]
+    fn test_resource_profile_competition() {
+        let grass = ResourceProfile::new(ResourceKind::Bandwidth, 20.0, 100.0);
+        let theta = ResourceProfile::new(ResourceKind::Bandwidth, 5.0, 60.0);
+        let golem = ResourceProfile::new(ResourceKind::Cpu, 10.0, 40.0)
+            .with_secondary(&[ResourceKind::Memory]);
+
+        assert!(grass.competes_with(&theta));
+        assert!(!grass.competes_with(&golem));
+        assert_eq!(golem.clamp(55.0), 40.0);
+        assert_eq!(golem.clamp(5.0), 10.0);
+    }
+
+    #[test]
+    fn test_observe_only_never_competes() {
+        let weather = ResourceProfile::observe_only();
+        let grass = ResourceProfile::new(ResourceKind::Bandwidth, 20.0, 100.0);
+
+        assert!(weather.low_resource);
+        assert!(!weather.competes_with(&grass));
+        assert!(!grass.competes_with(&weather));
+    }
+
+    #[test]
+    fn test_gpu_share_not_moved_to_cpu_only_protocol() {
+        let gpu = ResourceProfile::new(ResourceKind::Cpu, 10.0, 40.0)
+            .with_secondary(&[ResourceKind::Memory, ResourceKind::Gpu]);
+        let cpu_only = ResourceProfile::new(ResourceKind::Cpu, 10.0, 60.0)
+            .with_secondary(&[ResourceKind::Memory]);
+
+        assert!(!gpu.competes_with(&cpu_only));
+        assert!(cpu_only.competes_with(&gpu));
+        assert!(gpu.uses(ResourceKind::Gpu));
+    }
+
+    #[test]
+    fn test_metric_schema_matching() {
+        let schema = [
+            MetricSchema::new("repair_count", "count", MetricAggregation::Last, "Repairs"),
+            MetricSchema::new("audit_score", "score", MetricAggregation::Min, "Audit score").labeled("satellite"),
+        ];
+        assert_eq!(find_metric_schema(&schema, "repair_count").unwrap().unit, "count");
+        assert_eq!(find_metric_schema(&schema, "audit_score:sat1").unwrap().name, "audit_score");
+        assert!(find_metric_schema(&schema, "audit_score").is_none());
+        assert!(find_metric_schema(&schema, "audit_score:").is_none());
+        assert!(find_metric_schema(&schema, "repair_count:x").is_none());
+        assert!(find_metric_schema(&schema, "audit_scores:sat1").is_none());
+
+        let json = serde_json::to_value(&schema[1]).unwrap();
+        assert_eq!(json["aggregation"], "min");
+        assert_eq!(json["label"], "satellite");
+        assert!(serde_json::to_value(&schema[0]).unwrap().get("label").is_none());
+    }
+
     #[test]
     fn test_earnings_data_serialization() {
         let earnings = EarningsData {
@@ -251,6 +613,7 @@ mod tests {
             amount_usd: 10.50,
             protocol_id: "streamr-1".to_string(),
             metrics: HashMap::new(),
+            stale_seconds: 0,
         };
 
         let json = serde_json::to_string(&earnings).unwrap();
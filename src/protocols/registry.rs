@@ -0,0 +1,421 @@
+/// Adapter Registry
+///
+/// `AdapterFactory` maps protocol names to `ProtocolDescriptor`s so adapters
+/// can be constructed from configuration instead of hard-coded wiring. Built-in
+/// adapters register through `AdapterFactory::with_builtin`; external adapters
+/// can be added at runtime from dynamic libraries (`dynamic-adapters` feature)
+/// or run out of process behind the `grpc` descriptor (`grpc-adapters` feature).
+///
+/// Each `[protocols.<name>]` config section selects a descriptor by its section
+/// name, or by an explicit `adapter = "<descriptor>"` key so several instances
+/// of one adapter can run side by side. The section name is the instance id the
+/// adapter is registered, polled and recorded under; the descriptor name is its
+/// protocol type. With an `HttpClientFactory` set,
+/// adapters built from config send their API requests through its clients.
+use super::{
+    akash::{AkashAdapter, AkashConfig},
+    filecoin::{FilecoinAdapter, FilecoinConfig},
+    golem::{GolemAdapter, GolemConfig},
+    grass::{GrassAdapter, GrassConfig},
+    hivemapper::{HivemapperAdapter, HivemapperConfig},
+    ionet::{IoNetAdapter, IoNetConfig},
+    storj::{StorjAdapter, StorjConfig},
+    streamr::{StreamrAdapter, StreamrConfig},
+    theta::{ThetaAdapter, ThetaConfig},
+    weatherxm::{WeatherXMAdapter, WeatherXMConfig},
+    http::HttpClientFactory,
+    ProtocolAdapter, ProtocolError, ProtocolResult,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Symbol a dynamic adapter library must export
+///
+/// ```ignore
+/// #[no_mangle]
+/// pub fn depin_orcha_register_adapters(factory: &mut AdapterFactory) {
+///     factory.register(ProtocolDescriptor::typed("myproto", "My Protocol", MyAdapter::new));
+/// }
+/// ```
+///
+/// The library must be built with the same compiler and `depin-orcha` version
+/// as the orchestrator; the Rust ABI is not stable across either.
+pub const PLUGIN_ENTRY_SYMBOL: &[u8] = b"depin_orcha_register_adapters";
+
+/// Signature of `PLUGIN_ENTRY_SYMBOL`
+pub type PluginEntry = fn(&mut AdapterFactory);
+
+/// Builds an adapter from its JSON configuration
+pub type AdapterConstructor =
+    Arc<dyn Fn(Value) -> ProtocolResult<Box<dyn ProtocolAdapter>> + Send + Sync>;
+
+/// Config keys consumed by the registry rather than the adapter
+const RESERVED_KEYS: [&str; 2] = ["enabled", "adapter"];
+
+// ============================================================================
+// PROTOCOL DESCRIPTOR
+// ============================================================================
+
+/// Describes how to construct one kind of protocol adapter
+#[derive(Clone)]
+pub struct ProtocolDescriptor {
+    /// Registry key (matches the `[protocols.<name>]` section)
+    pub name: String,
+    /// Human-readable protocol name
+    pub display_name: String,
+    /// Accepted configuration fields with their default values
+    pub config_schema: Value,
+    constructor: AdapterConstructor,
+}
+
+impl ProtocolDescriptor {
+    /// Create a descriptor from an arbitrary constructor
+    pub fn new(
+        name: impl Into<String>,
+        display_name: impl Into<String>,
+        config_schema: Value,
+        constructor: impl Fn(Value) -> ProtocolResult<Box<dyn ProtocolAdapter>> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            display_name: display_name.into(),
+            config_schema,
+            constructor: Arc::new(constructor),
+        }
+    }
+
+    /// Create a descriptor for an adapter with a serde config type
+    ///
+    /// Supplied config fields are layered over `C::default()`, so config files
+    /// only need to list the fields they change.
+    pub fn typed<C, A>(
+        name: impl Into<String>,
+        display_name: impl Into<String>,
+        build: fn(C) -> A,
+    ) -> Self
+    where
+        C: Default + Serialize + DeserializeOwned + 'static,
+        A: ProtocolAdapter + 'static,
+    {
+        let defaults = serde_json::to_value(C::default()).unwrap_or(Value::Null);
+        let schema = defaults.clone();
+
+        Self::new(name, display_name, schema, move |overrides| {
+            let mut config = defaults.clone();
+            merge_config(&mut config, overrides);
+            let config: C = serde_json::from_value(config)
+                .map_err(|e| ProtocolError::ConfigurationError(e.to_string()))?;
+            Ok(Box::new(build(config)) as Box<dyn ProtocolAdapter>)
+        })
+    }
+
+    /// Construct an adapter from configuration
+    pub fn build(&self, config: Value) -> ProtocolResult<Box<dyn ProtocolAdapter>> {
+        (self.constructor)(config)
+    }
+}
+
+impl std::fmt::Debug for ProtocolDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProtocolDescriptor")
+            .field("name", &self.name)
+            .field("display_name", &self.display_name)
+            .finish()
+    }
+}
+
+/// Overlay `overrides` onto `base` (top-level object keys only)
+fn merge_config(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (_, Value::Null) => {}
+        (Value::Object(base), Value::Object(overrides)) => base.extend(overrides),
+        (base, overrides) => *base = overrides,
+    }
+}
+
+// ============================================================================
+// PROTOCOL INSTANCE
+// ============================================================================
+
+/// Adapter built for one `[protocols.<id>]` section
+pub struct ProtocolInstance {
+    /// Instance id (the section name), unique on this node
+    pub id: String,
+    /// Descriptor the adapter was built from, e.g. `storj` for `[protocols.storj_backup]`
+    pub protocol_type: String,
+    pub adapter: Box<dyn ProtocolAdapter>,
+}
+
+// ============================================================================
+// ADAPTER FACTORY
+// ============================================================================
+
+/// Registry of protocol descriptors
+#[derive(Default)]
+pub struct AdapterFactory {
+    // Declared before `libraries` so descriptors (whose constructors may live
+    // in a loaded library) are dropped before the library is unloaded.
+    descriptors: HashMap<String, ProtocolDescriptor>,
+    /// Clients given to adapters built from config
+    http: Option<HttpClientFactory>,
+    #[cfg(feature = "dynamic-adapters")]
+    libraries: Vec<libloading::Library>,
+}
+
+impl AdapterFactory {
+    /// Create an empty factory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a factory with every built-in adapter registered
+    pub fn with_builtin() -> Self {
+        let mut factory = Self::new();
+        factory.register(ProtocolDescriptor::typed::<StreamrConfig, _>(
+            "streamr",
+            "Streamr Network",
+            StreamrAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<StorjConfig, _>(
+            "storj",
+            "Storj Network",
+            StorjAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<GolemConfig, _>(
+            "golem",
+            "Golem Network",
+            GolemAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<GrassConfig, _>(
+            "grass",
+            "Grass Network",
+            GrassAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<FilecoinConfig, _>(
+            "filecoin",
+            "Filecoin",
+            FilecoinAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<AkashConfig, _>(
+            "akash",
+            "Akash Network",
+            AkashAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<ThetaConfig, _>(
+            "theta",
+            "Theta Edge Network",
+            ThetaAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<IoNetConfig, _>(
+            "ionet",
+            "io.net",
+            IoNetAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<HivemapperConfig, _>(
+            "hivemapper",
+            "Hivemapper",
+            HivemapperAdapter::new,
+        ));
+        factory.register(ProtocolDescriptor::typed::<WeatherXMConfig, _>(
+            "weatherxm",
+            "WeatherXM",
+            WeatherXMAdapter::new,
+        ));
+        #[cfg(feature = "grpc-adapters")]
+        factory.register(ProtocolDescriptor::typed::<super::grpc::GrpcRemoteConfig, _>(
+            "grpc",
+            "Remote gRPC Adapter",
+            super::grpc::GrpcRemoteAdapter::new,
+        ));
+        factory
+    }
+
+    /// Register a descriptor, returning any descriptor it replaced
+    pub fn register(&mut self, descriptor: ProtocolDescriptor) -> Option<ProtocolDescriptor> {
+        self.descriptors.insert(descriptor.name.clone(), descriptor)
+    }
+
+    /// Give adapters built by `create_from_config` clients from `http`
+    pub fn set_http(&mut self, http: HttpClientFactory) {
+        self.http = Some(http);
+    }
+
+    /// Look up a descriptor by name
+    pub fn descriptor(&self, name: &str) -> Option<&ProtocolDescriptor> {
+        self.descriptors.get(name)
+    }
+
+    /// All registered descriptors, sorted by name
+    pub fn descriptors(&self) -> Vec<&ProtocolDescriptor> {
+        let mut descriptors: Vec<_> = self.descriptors.values().collect();
+        descriptors.sort_by(|a, b| a.name.cmp(&b.name));
+        descriptors
+    }
+
+    /// Construct an adapter by descriptor name
+    pub fn create(&self, name: &str, config: Value) -> ProtocolResult<Box<dyn ProtocolAdapter>> {
+        let descriptor = self.descriptor(name).ok_or_else(|| {
+            ProtocolError::ConfigurationError(format!("Unknown protocol adapter '{}'", name))
+        })?;
+        descriptor.build(config)
+    }
+
+    /// Construct every enabled adapter from a `[protocols]` config table
+    ///
+    /// Sections default to enabled; `enabled = false` skips them.
+    pub fn create_from_config(&self, protocols: &toml::Table) -> ProtocolResult<Vec<ProtocolInstance>> {
+        let mut instances = Vec::new();
+
+        for (instance, section) in protocols {
+            let Some(table) = section.as_table() else {
+                continue;
+            };
+
+            if !table.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true) {
+                continue;
+            }
+
+            let adapter_name = table
+                .get("adapter")
+                .and_then(|v| v.as_str())
+                .unwrap_or(instance);
+
+            let mut config = serde_json::to_value(table)
+                .map_err(|e| ProtocolError::ConfigurationError(e.to_string()))?;
+            if let Value::Object(map) = &mut config {
+                for key in RESERVED_KEYS {
+                    map.remove(key);
+                }
+            }
+
+            let adapter = self
+                .create(adapter_name, config)
+                .and_then(|mut adapter| {
+                    if let Some(http) = &self.http {
+                        adapter.configure_http(http, instance)?;
+                    }
+                    Ok(adapter)
+                })
+                .map_err(|e| ProtocolError::ConfigurationError(format!("[protocols.{}]: {}", instance, e)))?;
+            instances.push(ProtocolInstance {
+                id: instance.clone(),
+                protocol_type: adapter_name.to_string(),
+                adapter,
+            });
+        }
+
+        Ok(instances)
+    }
+
+    /// Load external adapters from a dynamic library
+    ///
+    /// Calls the library's `PLUGIN_ENTRY_SYMBOL` and returns how many
+    /// descriptors it registered. The library stays loaded for the lifetime of
+    /// the factory, so adapters built from it must be dropped first.
+    ///
+    /// # Safety
+    /// The library runs arbitrary code on load and must have been built against
+    /// the same compiler and crate version as this binary.
+    #[cfg(feature = "dynamic-adapters")]
+    pub unsafe fn load_library(&mut self, path: &std::path::Path) -> ProtocolResult<usize> {
+        let library = libloading::Library::new(path).map_err(|e| {
+            ProtocolError::ConfigurationError(format!("{}: {}", path.display(), e))
+        })?;
+
+        let before = self.descriptors.len();
+        {
+            let entry: libloading::Symbol<'_, PluginEntry> =
+                library.get(PLUGIN_ENTRY_SYMBOL).map_err(|e| {
+                    ProtocolError::ConfigurationError(format!("{}: {}", path.display(), e))
+                })?;
+            entry(self);
+        }
+        self.libraries.push(library);
+
+        let added = self.descriptors.len().saturating_sub(before);
+        tracing::info!("Loaded {} adapter(s) from {}", added, path.display());
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_descriptors() {
+        let factory = AdapterFactory::with_builtin();
+        let names: Vec<_> = factory.descriptors().iter().map(|d| d.name.clone()).collect();
+        assert!(names.contains(&"storj".to_string()));
+        assert!(names.contains(&"weatherxm".to_string()));
+        assert!(factory.descriptor("golem").unwrap().config_schema["cpu_cores"].is_number());
+    }
+
+    #[test]
+    fn test_create_merges_defaults() {
+        let factory = AdapterFactory::with_builtin();
+        let adapter = factory
+            .create("golem", serde_json::json!({"cpu_cores": 4}))
+            .unwrap();
+        assert_eq!(adapter.protocol_name(), "Golem Network");
+        assert_eq!(adapter.get_config()["cpu_cores"], 4);
+        assert_eq!(adapter.get_config()["memory_gb"], 16.0);
+    }
+
+    #[test]
+    fn test_unknown_adapter() {
+        let factory = AdapterFactory::with_builtin();
+        assert!(matches!(
+            factory.create("nope", Value::Null),
+            Err(ProtocolError::ConfigurationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_create_from_config() {
+        let protocols: toml::Table = toml::from_str(
+            r#"
+            [storj]
+            enabled = true
+            allocated_storage_gb = 500.0
+
+            [grass]
+            enabled = false
+
+            [storj_backup]
+            adapter = "storj"
+            api_endpoint = "http://localhost:14003"
+            "#,
+        )
+        .unwrap();
+
+        let factory = AdapterFactory::with_builtin();
+        let mut instances = factory.create_from_config(&protocols).unwrap();
+        instances.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let ids: Vec<_> = instances.iter().map(|i| (i.id.as_str(), i.protocol_type.as_str())).collect();
+        assert_eq!(ids, vec![("storj", "storj"), ("storj_backup", "storj")]);
+        assert_eq!(instances[1].adapter.get_config()["api_endpoint"], "http://localhost:14003");
+    }
+
+    #[test]
+    fn test_custom_descriptor() {
+        let mut factory = AdapterFactory::new();
+        factory.register(ProtocolDescriptor::new(
+            "golem_gpu",
+            "Golem (GPU)",
+            Value::Null,
+            |_| {
+                Ok(Box::new(GolemAdapter::new(GolemConfig {
+                    gpu_enabled: true,
+                    ..Default::default()
+                })) as Box<dyn ProtocolAdapter>)
+            },
+        ));
+
+        let adapter = factory.create("golem_gpu", Value::Null).unwrap();
+        assert_eq!(adapter.get_config()["gpu_enabled"], true);
+    }
+}
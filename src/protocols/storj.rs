@@ -8,13 +8,19 @@
 /// - Storage node participation
 /// - Earnings tracking from storage services
 /// - Resource allocation and utilization
-
+/// - Satellite audit, suspension, and online scores from the node dashboard
+///
+/// Each satellite disqualifies a node whose audit score drops below 96% and
+/// suspends it below 60% suspension or online score. Scores are reported in
+/// the earnings metrics as `<score>:<satellite>` (e.g.
+/// `audit_score:us1.storj.io:7777`) so they are stored with each snapshot and
+/// the alert task can warn before a threshold is crossed.
 use super::{
     AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
-    ProtocolError, ProtocolResult, ResourceMetrics,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
 };
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -39,6 +45,10 @@ pub struct StorjConfig {
     pub min_allocation_percent: f64,
     /// Maximum allocation percent
     pub max_allocation_percent: f64,
+    /// Storage node dashboard API (e.g. `http://localhost:14002`) to read
+    /// satellite scores from; empty disables score tracking
+    #[serde(default)]
+    pub node_api_url: String,
 }
 
 impl Default for StorjConfig {
@@ -50,10 +60,110 @@ impl Default for StorjConfig {
             allocated_storage_gb: 1000.0,
             min_allocation_percent: 10.0,
             max_allocation_percent: 50.0,
+            node_api_url: String::new(),
         }
     }
 }
 
+// ============================================================================
+// SATELLITE SCORES
+// ============================================================================
+
+/// Satellite score and the level at which the satellite acts on it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreThreshold {
+    /// Metric name prefix (`<metric>:<satellite>`)
+    pub metric: &'static str,
+    /// Human-readable score name
+    pub label: &'static str,
+    /// The satellite acts once the score drops below this
+    pub limit: f64,
+    /// Alert once the score drops below this
+    pub warn_below: f64,
+    /// What happens below `limit`
+    pub consequence: &'static str,
+}
+
+/// Scores each satellite holds a node to
+pub const SCORE_THRESHOLDS: [ScoreThreshold; 3] = [
+    ScoreThreshold {
+        metric: "audit_score",
+        label: "audit score",
+        limit: 0.96,
+        warn_below: 0.98,
+        consequence: "disqualification",
+    },
+    ScoreThreshold {
+        metric: "suspension_score",
+        label: "suspension score",
+        limit: 0.60,
+        warn_below: 0.75,
+        consequence: "suspension",
+    },
+    ScoreThreshold {
+        metric: "online_score",
+        label: "online score",
+        limit: 0.60,
+        warn_below: 0.75,
+        consequence: "suspension",
+    },
+];
+
+/// One satellite's score read back from a metrics map
+#[derive(Debug, Clone, PartialEq)]
+pub struct SatelliteScore {
+    pub satellite: String,
+    pub threshold: ScoreThreshold,
+    pub value: f64,
+}
+
+impl SatelliteScore {
+    /// Whether the score is below the warning level
+    pub fn at_risk(&self) -> bool {
+        self.value < self.threshold.warn_below
+    }
+
+    /// Whether the satellite has already acted on the score
+    pub fn breached(&self) -> bool {
+        self.value < self.threshold.limit
+    }
+}
+
+/// Satellite scores in a protocol's earnings metrics, ordered by satellite
+/// and score; other metrics are ignored
+pub fn satellite_scores(metrics: &HashMap<String, f64>) -> Vec<SatelliteScore> {
+    let mut scores: Vec<_> = metrics
+        .iter()
+        .filter_map(|(name, value)| {
+            let (metric, satellite) = name.split_once(':')?;
+            let threshold = SCORE_THRESHOLDS.iter().find(|t| t.metric == metric)?;
+            Some(SatelliteScore {
+                satellite: satellite.to_string(),
+                threshold: *threshold,
+                value: *value,
+            })
+        })
+        .collect();
+    scores.sort_by(|a, b| (&a.satellite, a.threshold.metric).cmp(&(&b.satellite, b.threshold.metric)));
+    scores
+}
+
+/// Subset of the node dashboard satellites response
+#[derive(Debug, Deserialize)]
+struct SatellitesResponse {
+    #[serde(default)]
+    audits: Vec<SatelliteAudits>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SatelliteAudits {
+    satellite_name: String,
+    audit_score: f64,
+    suspension_score: f64,
+    online_score: f64,
+}
+
 // ============================================================================
 // INTERNAL STATE
 // ============================================================================
@@ -62,22 +172,23 @@ impl Default for StorjConfig {
 #[derive(Debug, Clone)]
 struct StorjMetrics {
     storage_used_gb: f64,
-    bytes_downloaded: u64,
-    bytes_uploaded: u64,
     uptime_hours: u64,
     connected_at: Option<DateTime<Utc>>,
     repair_count: u32,
+    /// Latest scores by metric name (`<score>:<satellite>`)
+    satellite_scores: HashMap<String, f64>,
+    last_score_refresh: Option<DateTime<Utc>>,
 }
 
 impl Default for StorjMetrics {
     fn default() -> Self {
         Self {
             storage_used_gb: 0.0,
-            bytes_downloaded: 0,
-            bytes_uploaded: 0,
             uptime_hours: 0,
             connected_at: None,
             repair_count: 0,
+            satellite_scores: HashMap::new(),
+            last_score_refresh: None,
         }
     }
 }
@@ -89,6 +200,7 @@ impl Default for StorjMetrics {
 /// Storj Decentralized Storage Protocol Adapter
 pub struct StorjAdapter {
     config: StorjConfig,
+    http: reqwest::Client,
     status: Arc<RwLock<ConnectionStatus>>,
     allocation: Arc<RwLock<AllocationStrategy>>,
     metrics: Arc<RwLock<StorjMetrics>>,
@@ -103,10 +215,14 @@ impl StorjAdapter {
             storage_gb: config.allocated_storage_gb,
             bandwidth_mbps: 50.0,
             allocation_percent: 30.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
 
         Self {
             config,
+            http: reqwest::Client::new(),
             status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             allocation: Arc::new(RwLock::new(allocation)),
             metrics: Arc::new(RwLock::new(StorjMetrics::default())),
@@ -122,6 +238,42 @@ impl StorjAdapter {
         }
     }
 
+    /// Refresh satellite scores from the node dashboard API
+    async fn refresh_scores(&self) -> ProtocolResult<()> {
+        let url = format!("{}/api/sno/satellites", self.config.node_api_url.trim_end_matches('/'));
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ProtocolError::ApiError(format!(
+                "Storj node dashboard returned {}",
+                response.status()
+            )));
+        }
+
+        let satellites: SatellitesResponse = response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))?;
+
+        let mut scores = HashMap::new();
+        for audits in satellites.audits {
+            let satellite = audits.satellite_name;
+            scores.insert(format!("audit_score:{}", satellite), audits.audit_score);
+            scores.insert(format!("suspension_score:{}", satellite), audits.suspension_score);
+            scores.insert(format!("online_score:{}", satellite), audits.online_score);
+        }
+
+        let mut metrics = self.metrics.write().await;
+        metrics.satellite_scores = scores;
+        metrics.last_score_refresh = Some(Utc::now());
+        Ok(())
+    }
+
     /// Simulate earning for demonstration
     async fn calculate_current_earnings(&self) -> f64 {
         let metrics = self.metrics.read().await;
@@ -191,6 +343,14 @@ impl ProtocolAdapter for StorjAdapter {
     }
 
     async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        // Earnings don't depend on the dashboard; keep the last known scores
+        // rather than failing the poll when it is unreachable
+        if !self.config.node_api_url.is_empty() {
+            if let Err(e) = self.refresh_scores().await {
+                tracing::warn!("Failed to refresh Storj satellite scores: {}", e);
+            }
+        }
+
         let earnings_usd = self.calculate_current_earnings().await;
         let metrics = self.metrics.read().await;
 
@@ -198,37 +358,22 @@ impl ProtocolAdapter for StorjAdapter {
         metric_map.insert("storage_used_gb".to_string(), metrics.storage_used_gb);
         metric_map.insert("uptime_hours".to_string(), metrics.uptime_hours as f64);
         metric_map.insert("repair_count".to_string(), metrics.repair_count as f64);
+        metric_map.extend(metrics.satellite_scores.clone());
 
         Ok(EarningsData {
             timestamp: Utc::now(),
             amount_usd: earnings_usd,
             protocol_id: "storj".to_string(),
             metrics: metric_map,
+            stale_seconds: 0,
         })
     }
 
-    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
-        let mut earnings = Vec::new();
-        let current_earnings = self.calculate_current_earnings().await;
-
-        // Simulate historical data
-        for i in 0..hours {
-            let hours_ago = Duration::hours(i as i64);
-            let timestamp = Utc::now() - hours_ago;
-
-            // Simulate varying earnings with some variance
-            let variance = 1.0 - (i as f64 / hours as f64) * 0.2;
-            let amount = current_earnings * variance;
-
-            earnings.push(EarningsData {
-                timestamp,
-                amount_usd: amount,
-                protocol_id: "storj".to_string(),
-                metrics: HashMap::new(),
-            });
-        }
-
-        Ok(earnings)
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // The node dashboard reports the current payout period, not an hourly ledger
+        Err(ProtocolError::UnsupportedError(
+            "The Storj node API does not expose historical earnings".to_string(),
+        ))
     }
 
     async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
@@ -241,6 +386,10 @@ impl ProtocolAdapter for StorjAdapter {
             bandwidth_mbps: 25.0,
             storage_gb: metrics.storage_used_gb,
             uptime_seconds: metrics.uptime_hours * 3600,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
         })
     }
 
@@ -285,6 +434,16 @@ impl ProtocolAdapter for StorjAdapter {
             "uptime_hours".into(),
             serde_json::json!(metrics.uptime_hours),
         );
+        for threshold in &SCORE_THRESHOLDS {
+            let lowest = satellite_scores(&metrics.satellite_scores)
+                .into_iter()
+                .filter(|s| s.threshold == *threshold)
+                .map(|s| s.value)
+                .reduce(f64::min);
+            if let Some(lowest) = lowest {
+                health_metrics.insert(format!("min_{}", threshold.metric), serde_json::json!(lowest));
+            }
+        }
 
         Ok(HealthStatus {
             is_healthy,
@@ -299,6 +458,11 @@ impl ProtocolAdapter for StorjAdapter {
         })
     }
 
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
     fn get_config(&self) -> serde_json::Value {
         serde_json::json!({
             "protocol": "storj",
@@ -306,8 +470,36 @@ impl ProtocolAdapter for StorjAdapter {
             "allocated_storage_gb": self.config.allocated_storage_gb,
             "min_allocation_percent": self.config.min_allocation_percent,
             "max_allocation_percent": self.config.max_allocation_percent,
+            "node_api_url": self.config.node_api_url,
         })
     }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(
+            ResourceKind::Storage,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        )
+        .with_secondary(&[ResourceKind::Bandwidth])
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        let mut schema = vec![
+            MetricSchema::new("storage_used_gb", "GB", MetricAggregation::Last, "Storage used by pieces"),
+            MetricSchema::new("uptime_hours", "hours", MetricAggregation::Last, "Node uptime"),
+            MetricSchema::new("repair_count", "count", MetricAggregation::Last, "Repair traffic transfers"),
+        ];
+        schema.extend(SCORE_THRESHOLDS.iter().map(|threshold| {
+            MetricSchema::new(
+                threshold.metric,
+                "score",
+                MetricAggregation::Min,
+                &format!("Satellite {} (0-1)", threshold.label),
+            )
+            .labeled("satellite")
+        }));
+        schema
+    }
 }
 
 #[cfg(test)]
@@ -360,6 +552,51 @@ mod tests {
         assert!(earnings.amount_usd >= 0.0);
     }
 
+    #[tokio::test]
+    async fn test_storj_satellite_scores() {
+        let mut server = mockito::Server::new_async().await;
+        let _satellites = server
+            .mock("GET", "/api/sno/satellites")
+            .with_body(
+                r#"{"audits":[
+                    {"satelliteName":"us1.storj.io:7777","auditScore":0.97,"suspensionScore":1.0,"onlineScore":0.99},
+                    {"satelliteName":"eu1.storj.io:7777","auditScore":1.0,"suspensionScore":0.55,"onlineScore":0.9}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let config = StorjConfig {
+            node_id: "test_node".to_string(),
+            wallet_address: "0x123...".to_string(),
+            node_api_url: server.url(),
+            ..Default::default()
+        };
+        let mut adapter = StorjAdapter::new(config);
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.metrics["audit_score:us1.storj.io:7777"], 0.97);
+
+        let scores = satellite_scores(&earnings.metrics);
+        assert_eq!(scores.len(), 6);
+        let at_risk: Vec<_> = scores
+            .iter()
+            .filter(|s| s.at_risk())
+            .map(|s| (s.satellite.as_str(), s.threshold.metric, s.breached()))
+            .collect();
+        assert_eq!(
+            at_risk,
+            [
+                ("eu1.storj.io:7777", "suspension_score", true),
+                ("us1.storj.io:7777", "audit_score", false),
+            ]
+        );
+
+        let health = adapter.health_check().await.unwrap();
+        assert_eq!(health.metrics["min_suspension_score"], serde_json::json!(0.55));
+    }
+
     #[tokio::test]
     async fn test_storj_allocation_validation() {
         let config = StorjConfig {
@@ -377,6 +614,9 @@ mod tests {
             storage_gb: 50.0,
             bandwidth_mbps: 50.0,
             allocation_percent: 30.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
         assert!(adapter.apply_allocation(strategy).await.is_ok());
 
@@ -387,6 +627,9 @@ mod tests {
             storage_gb: 150.0,
             bandwidth_mbps: 50.0,
             allocation_percent: 30.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
         assert!(adapter.apply_allocation(invalid_strategy).await.is_err());
     }
@@ -8,18 +8,42 @@
 /// - Data stream publishing and subscription
 /// - Earnings tracking from network participation
 /// - Resource allocation and optimization
-
+/// - Operator staking: DATA staked per sponsorship, sponsorship APYs, and
+///   slashing events from the Streamr network subgraph
+///
+/// With an operator configured, earnings are the yield of the operator's
+/// stakes and the stake-weighted APY is reported against its moving average
+/// as a yield index, so the optimizer follows sponsorship yield changes.
 use super::{
     AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
-    ProtocolError, ProtocolResult, ResourceMetrics,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+    YIELD_INDEX_METRIC,
 };
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Token amounts on chain are in wei (10^18 per DATA)
+const WEI_PER_DATA: f64 = 1e18;
+
+const HOURS_PER_YEAR: f64 = 8760.0;
+
+/// Weight of the latest sample in the yield baseline (exponential moving average)
+const YIELD_BASELINE_SMOOTHING: f64 = 0.1;
+
+/// Operator stakes and slashings, newest slashings first
+const OPERATOR_QUERY: &str = r#"
+query Operator($id: ID!) {
+  operator(id: $id) {
+    stakes { amountWei sponsorship { id spotAPY stream { id } } }
+    slashingEvents(orderBy: date, orderDirection: desc) { amount date sponsorship { id } }
+  }
+}
+"#;
+
 // ============================================================================
 // CONFIGURATION
 // ============================================================================
@@ -39,6 +63,12 @@ pub struct StreamrConfig {
     pub min_allocation_percent: f64,
     /// Maximum allocation percent
     pub max_allocation_percent: f64,
+    /// Operator contract address; empty disables staking tracking
+    pub operator_address: String,
+    /// Streamr network subgraph (GraphQL) endpoint
+    pub subgraph_url: String,
+    /// DATA price used for USD conversion
+    pub data_price_usd: f64,
 }
 
 impl Default for StreamrConfig {
@@ -50,33 +80,130 @@ impl Default for StreamrConfig {
             publish_interval_seconds: 60,
             min_allocation_percent: 5.0,
             max_allocation_percent: 30.0,
+            operator_address: String::new(),
+            subgraph_url: "https://api.thegraph.com/subgraphs/name/streamr-dev/network-subgraphs"
+                .to_string(),
+            data_price_usd: 0.03,
         }
     }
 }
 
+// ============================================================================
+// OPERATOR STAKING
+// ============================================================================
+
+/// DATA the operator has staked in one sponsorship
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SponsorshipStake {
+    pub sponsorship_id: String,
+    pub stream_id: Option<String>,
+    pub staked_data: f64,
+    /// Current annual yield of the sponsorship (0.12 = 12%)
+    pub apy: f64,
+}
+
+/// Subgraph response; amounts are decimal strings
+#[derive(Debug, Deserialize)]
+struct GraphResponse {
+    data: Option<GraphData>,
+    #[serde(default)]
+    errors: Vec<GraphError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphData {
+    operator: Option<OperatorNode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OperatorNode {
+    #[serde(default)]
+    stakes: Vec<StakeNode>,
+    #[serde(default)]
+    slashing_events: Vec<SlashingNode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StakeNode {
+    amount_wei: String,
+    sponsorship: SponsorshipNode,
+}
+
+#[derive(Debug, Deserialize)]
+struct SponsorshipNode {
+    id: String,
+    #[serde(default, rename = "spotAPY")]
+    spot_apy: Option<String>,
+    #[serde(default)]
+    stream: Option<StreamNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamNode {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlashingNode {
+    amount: String,
+    date: String,
+    sponsorship: Option<SponsorshipRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SponsorshipRef {
+    id: String,
+}
+
+/// Parse a subgraph decimal string
+fn parse_decimal(value: &str) -> ProtocolResult<f64> {
+    value
+        .parse()
+        .map_err(|_| ProtocolError::ParseError(format!("Invalid subgraph number: {}", value)))
+}
+
 // ============================================================================
 // INTERNAL STATE
 // ============================================================================
 
 /// Streamr metrics tracked internally
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct StreamrMetrics {
-    messages_published: u64,
-    bytes_published: u64,
-    last_publish_time: Option<DateTime<Utc>>,
     connection_uptime_seconds: u64,
     connected_at: Option<DateTime<Utc>>,
+    stakes: Vec<SponsorshipStake>,
+    slashing_events: usize,
+    slashed_data: f64,
+    last_slashed_at: Option<DateTime<Utc>>,
+    yield_baseline: Option<f64>,
+    yield_index: Option<f64>,
+    last_operator_refresh: Option<DateTime<Utc>>,
 }
 
-impl Default for StreamrMetrics {
-    fn default() -> Self {
-        Self {
-            messages_published: 0,
-            bytes_published: 0,
-            last_publish_time: None,
-            connection_uptime_seconds: 0,
-            connected_at: None,
-        }
+impl StreamrMetrics {
+    /// DATA staked across all sponsorships
+    fn staked_data(&self) -> f64 {
+        self.stakes.iter().map(|s| s.staked_data).sum()
+    }
+
+    /// APY of the operator's stake as a whole
+    fn weighted_apy(&self) -> Option<f64> {
+        let staked = self.staked_data();
+        (staked > 0.0).then(|| self.stakes.iter().map(|s| s.staked_data * s.apy).sum::<f64>() / staked)
+    }
+
+    /// Record the latest weighted APY against its moving average
+    fn observe_yield(&mut self, apy: f64) {
+        let baseline = self.yield_baseline.unwrap_or(apy);
+        self.yield_index = Some(if baseline > 0.0 { apy / baseline } else { 1.0 });
+        self.yield_baseline = Some(baseline + YIELD_BASELINE_SMOOTHING * (apy - baseline));
     }
 }
 
@@ -87,6 +214,7 @@ impl Default for StreamrMetrics {
 /// Streamr Network Protocol Adapter
 pub struct StreamrAdapter {
     config: StreamrConfig,
+    http: reqwest::Client,
     status: Arc<RwLock<ConnectionStatus>>,
     allocation: Arc<RwLock<AllocationStrategy>>,
     metrics: Arc<RwLock<StreamrMetrics>>,
@@ -101,10 +229,14 @@ impl StreamrAdapter {
             storage_gb: 50.0,
             bandwidth_mbps: 100.0,
             allocation_percent: 20.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
 
         Self {
             config,
+            http: reqwest::Client::new(),
             status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             allocation: Arc::new(RwLock::new(allocation)),
             metrics: Arc::new(RwLock::new(StreamrMetrics::default())),
@@ -120,9 +252,100 @@ impl StreamrAdapter {
         }
     }
 
-    /// Simulate earning for demonstration
+    /// Whether an operator is configured for staking tracking
+    fn tracks_operator(&self) -> bool {
+        !self.config.operator_address.is_empty()
+    }
+
+    /// Refresh the operator's stakes and slashings from the subgraph
+    async fn refresh_operator(&self) -> ProtocolResult<()> {
+        let response = self
+            .http
+            .post(&self.config.subgraph_url)
+            .json(&serde_json::json!({
+                "query": OPERATOR_QUERY,
+                "variables": { "id": self.config.operator_address.to_lowercase() },
+            }))
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ProtocolError::ApiError(format!(
+                "Streamr subgraph returned {}",
+                response.status()
+            )));
+        }
+
+        let body: GraphResponse = response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))?;
+        if let Some(error) = body.errors.first() {
+            return Err(ProtocolError::ApiError(error.message.clone()));
+        }
+        let operator = body.data.and_then(|d| d.operator).ok_or_else(|| {
+            ProtocolError::ConfigurationError(format!(
+                "Operator {} not found",
+                self.config.operator_address
+            ))
+        })?;
+
+        let stakes = operator
+            .stakes
+            .into_iter()
+            .map(|stake| {
+                Ok(SponsorshipStake {
+                    staked_data: parse_decimal(&stake.amount_wei)? / WEI_PER_DATA,
+                    apy: stake.sponsorship.spot_apy.as_deref().map(parse_decimal).transpose()?.unwrap_or(0.0),
+                    stream_id: stake.sponsorship.stream.map(|s| s.id),
+                    sponsorship_id: stake.sponsorship.id,
+                })
+            })
+            .collect::<ProtocolResult<Vec<_>>>()?;
+
+        let mut slashed_data = 0.0;
+        for event in &operator.slashing_events {
+            slashed_data += parse_decimal(&event.amount)? / WEI_PER_DATA;
+        }
+        let last_slashed_at = match operator.slashing_events.first() {
+            Some(event) => DateTime::from_timestamp(parse_decimal(&event.date)? as i64, 0),
+            None => None,
+        };
+
+        let mut metrics = self.metrics.write().await;
+        let new_slashings = operator.slashing_events.len().saturating_sub(metrics.slashing_events);
+        if metrics.last_operator_refresh.is_some() && new_slashings > 0 {
+            for event in &operator.slashing_events[..new_slashings] {
+                tracing::warn!(
+                    "Streamr operator slashed {} DATA in sponsorship {}",
+                    parse_decimal(&event.amount)? / WEI_PER_DATA,
+                    event.sponsorship.as_ref().map_or("unknown", |s| s.id.as_str())
+                );
+            }
+        }
+
+        metrics.stakes = stakes;
+        metrics.slashing_events = operator.slashing_events.len();
+        metrics.slashed_data = slashed_data;
+        metrics.last_slashed_at = last_slashed_at;
+        if let Some(apy) = metrics.weighted_apy() {
+            metrics.observe_yield(apy);
+        }
+        metrics.last_operator_refresh = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Current earnings in USD per hour
+    ///
+    /// With an operator this is the yield of its stakes; otherwise it is
+    /// simulated.
     async fn calculate_current_earnings(&self) -> f64 {
         let metrics = self.metrics.read().await;
+        if self.tracks_operator() {
+            let yearly_data: f64 = metrics.stakes.iter().map(|s| s.staked_data * s.apy).sum();
+            return yearly_data / HOURS_PER_YEAR * self.config.data_price_usd;
+        }
         let allocation = self.allocation.read().await;
 
         // Simulate earnings: base rate * allocation percent * uptime hours
@@ -183,42 +406,46 @@ impl ProtocolAdapter for StreamrAdapter {
     }
 
     async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        if self.tracks_operator() {
+            self.refresh_operator().await?;
+        }
+
         let earnings_usd = self.calculate_current_earnings().await;
         let allocation = self.allocation.read().await;
 
         let mut metrics = HashMap::new();
         metrics.insert("allocation_percent".to_string(), allocation.allocation_percent);
         metrics.insert("cpu_cores".to_string(), allocation.cpu_cores as f64);
+        if self.tracks_operator() {
+            let staking = self.metrics.read().await;
+            metrics.insert("staked_data".to_string(), staking.staked_data());
+            metrics.insert("slashing_events".to_string(), staking.slashing_events as f64);
+            metrics.insert("slashed_data".to_string(), staking.slashed_data);
+            if let Some(apy) = staking.weighted_apy() {
+                metrics.insert("staking_apy".to_string(), apy);
+            }
+            if let Some(index) = staking.yield_index {
+                metrics.insert(YIELD_INDEX_METRIC.to_string(), index);
+            }
+            for stake in &staking.stakes {
+                metrics.insert(format!("sponsorship_apy:{}", stake.sponsorship_id), stake.apy);
+            }
+        }
 
         Ok(EarningsData {
             timestamp: Utc::now(),
             amount_usd: earnings_usd,
             protocol_id: "streamr".to_string(),
             metrics,
+            stale_seconds: 0,
         })
     }
 
-    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
-        let mut earnings = Vec::new();
-        let current_earnings = self.calculate_current_earnings().await;
-
-        // Simulate historical data with decreasing earnings
-        for i in 0..hours {
-            let hours_ago = Duration::hours(i as i64);
-            let timestamp = Utc::now() - hours_ago;
-
-            // Simulate varying earnings
-            let amount = current_earnings * (1.0 - (i as f64 / hours as f64) * 0.3);
-
-            earnings.push(EarningsData {
-                timestamp,
-                amount_usd: amount,
-                protocol_id: "streamr".to_string(),
-                metrics: HashMap::new(),
-            });
-        }
-
-        Ok(earnings)
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // The operator contract reports stakes and yield, not an hourly ledger
+        Err(ProtocolError::UnsupportedError(
+            "Streamr does not expose historical operator earnings".to_string(),
+        ))
     }
 
     async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
@@ -231,6 +458,10 @@ impl ProtocolAdapter for StreamrAdapter {
             bandwidth_mbps: 45.0,
             storage_gb: 2.5,
             uptime_seconds: metrics.connection_uptime_seconds,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
         })
     }
 
@@ -258,6 +489,18 @@ impl ProtocolAdapter for StreamrAdapter {
         let status = *self.status.read().await;
         let is_healthy = status == ConnectionStatus::Connected;
 
+        let mut health_metrics = HashMap::new();
+        if self.tracks_operator() {
+            let metrics = self.metrics.read().await;
+            // Most profitable sponsorships first
+            let mut stakes = metrics.stakes.clone();
+            stakes.sort_by(|a, b| b.apy.total_cmp(&a.apy));
+            health_metrics.insert("staked_data".into(), serde_json::json!(metrics.staked_data()));
+            health_metrics.insert("sponsorships".into(), serde_json::json!(stakes));
+            health_metrics.insert("slashing_events".into(), serde_json::json!(metrics.slashing_events));
+            health_metrics.insert("last_slashed_at".into(), serde_json::json!(metrics.last_slashed_at));
+        }
+
         Ok(HealthStatus {
             is_healthy,
             connection_status: status,
@@ -267,10 +510,15 @@ impl ProtocolAdapter for StreamrAdapter {
             } else {
                 Some("Not connected to Streamr".to_string())
             },
-            metrics: HashMap::new(),
+            metrics: health_metrics,
         })
     }
 
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
     fn get_config(&self) -> serde_json::Value {
         serde_json::json!({
             "protocol": "streamr",
@@ -279,8 +527,43 @@ impl ProtocolAdapter for StreamrAdapter {
             "publish_interval_seconds": self.config.publish_interval_seconds,
             "min_allocation_percent": self.config.min_allocation_percent,
             "max_allocation_percent": self.config.max_allocation_percent,
+            "operator_address": self.config.operator_address,
+            "subgraph_url": self.config.subgraph_url,
+            "data_price_usd": self.config.data_price_usd,
         })
     }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(
+            ResourceKind::Bandwidth,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        )
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new(
+                "allocation_percent",
+                "percent",
+                MetricAggregation::Mean,
+                "Allocation applied to the node",
+            ),
+            MetricSchema::new("cpu_cores", "cores", MetricAggregation::Mean, "CPU cores allocated to the node"),
+            MetricSchema::new("staked_data", "DATA", MetricAggregation::Last, "DATA the operator has staked"),
+            MetricSchema::new("staking_apy", "ratio", MetricAggregation::Mean, "Stake-weighted sponsorship APY"),
+            MetricSchema::new(
+                YIELD_INDEX_METRIC,
+                "ratio",
+                MetricAggregation::Mean,
+                "Staking APY relative to its baseline",
+            ),
+            MetricSchema::new("slashing_events", "count", MetricAggregation::Last, "Times the operator was slashed"),
+            MetricSchema::new("slashed_data", "DATA", MetricAggregation::Last, "DATA lost to slashing"),
+            MetricSchema::new("sponsorship_apy", "ratio", MetricAggregation::Mean, "Current APY of the sponsorship")
+                .labeled("sponsorship"),
+        ]
+    }
 }
 
 #[cfg(test)]
@@ -337,6 +620,9 @@ mod tests {
             storage_gb: 100.0,
             bandwidth_mbps: 200.0,
             allocation_percent: 25.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
         };
 
         assert!(adapter.apply_allocation(strategy.clone()).await.is_ok());
@@ -357,4 +643,71 @@ mod tests {
         let health = adapter.health_check().await.unwrap();
         assert!(health.is_healthy);
     }
+
+    #[tokio::test]
+    async fn test_streamr_operator_staking() {
+        let mut server = mockito::Server::new_async().await;
+        let operator = |apy_a: &str, slashings: &str| {
+            format!(
+                r#"{{"data":{{"operator":{{
+                    "stakes":[
+                        {{"amountWei":"30000000000000000000000",
+                          "sponsorship":{{"id":"0xaaa","spotAPY":"{}","stream":{{"id":"prices/eth"}}}}}},
+                        {{"amountWei":"10000000000000000000000","sponsorship":{{"id":"0xbbb","spotAPY":"0.1"}}}}
+                    ],
+                    "slashingEvents":[{}]
+                }}}}}}"#,
+                apy_a, slashings
+            )
+        };
+        let first = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "variables": { "id": "0xoperator" }
+            })))
+            .with_body(operator("0.2", ""))
+            .create_async()
+            .await;
+
+        let config = StreamrConfig {
+            private_key: "test_key".to_string(),
+            operator_address: "0xOperator".to_string(),
+            subgraph_url: server.url(),
+            data_price_usd: 0.876,
+            ..Default::default()
+        };
+        let mut adapter = StreamrAdapter::new(config);
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.metrics["staked_data"], 40_000.0);
+        assert!((earnings.metrics["staking_apy"] - 0.175).abs() < 1e-9);
+        assert_eq!(earnings.metrics["sponsorship_apy:0xbbb"], 0.1);
+        assert_eq!(earnings.metrics[YIELD_INDEX_METRIC], 1.0);
+        // 7000 DATA/year / 8760h * $0.876
+        assert!((earnings.amount_usd - 0.7).abs() < 1e-9);
+
+        // The first sponsorship's yield doubles and the operator is slashed
+        first.remove_async().await;
+        let _second = server
+            .mock("POST", "/")
+            .with_body(operator(
+                "0.4",
+                r#"{"amount":"500000000000000000000","date":"1760000000","sponsorship":{"id":"0xaaa"}}"#,
+            ))
+            .create_async()
+            .await;
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert!((earnings.metrics["staking_apy"] - 0.325).abs() < 1e-9);
+        assert!((earnings.metrics[YIELD_INDEX_METRIC] - 0.325 / 0.175).abs() < 1e-9);
+        assert_eq!(earnings.metrics["slashing_events"], 1.0);
+        assert_eq!(earnings.metrics["slashed_data"], 500.0);
+        let schema = adapter.metric_schema();
+        assert!(earnings.metrics.keys().all(|key| super::super::find_metric_schema(&schema, key).is_some()));
+
+        let health = adapter.health_check().await.unwrap();
+        assert_eq!(health.metrics["sponsorships"][0]["sponsorship_id"], "0xaaa");
+        assert_eq!(health.metrics["sponsorships"][0]["stream_id"], "prices/eth");
+        assert_eq!(health.metrics["last_slashed_at"], "2025-10-09T08:53:20Z");
+    }
 }
@@ -0,0 +1,398 @@
+/// Theta Edge Node Protocol Adapter
+///
+/// Theta Edge Network pays TFUEL to edge nodes that relay video bandwidth and
+/// complete compute jobs (transcoding, ML inference) for the network.
+///
+/// This adapter manages:
+/// - Connection to the local Theta Edge Node
+/// - Relayed bandwidth and job completion tracking
+/// - TFUEL earnings tracking
+/// - Bandwidth allocation within min/max boundaries
+use super::{
+    AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceKind, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Theta protocol configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThetaConfig {
+    /// Edge Node RPC endpoint
+    pub edge_node_url: String,
+    /// Edge Node wallet address receiving TFUEL
+    pub wallet_address: String,
+    /// TFUEL price used for USD conversion
+    pub tfuel_price_usd: f64,
+    /// Maximum upstream bandwidth the node may relay (Mbps)
+    pub max_bandwidth_mbps: f64,
+    /// Minimum allocation percent
+    pub min_allocation_percent: f64,
+    /// Maximum allocation percent
+    pub max_allocation_percent: f64,
+}
+
+impl Default for ThetaConfig {
+    fn default() -> Self {
+        Self {
+            edge_node_url: "http://localhost:15888/rpc".to_string(),
+            wallet_address: String::new(),
+            tfuel_price_usd: 0.05,
+            max_bandwidth_mbps: 100.0,
+            min_allocation_percent: 5.0,
+            max_allocation_percent: 60.0,
+        }
+    }
+}
+
+// ============================================================================
+// INTERNAL STATE
+// ============================================================================
+
+/// Theta metrics tracked internally
+#[derive(Debug, Clone, Default)]
+struct ThetaMetrics {
+    relayed_bandwidth_gb: f64,
+    jobs_completed: u64,
+    jobs_failed: u64,
+    tfuel_earned: f64,
+    connected_at: Option<DateTime<Utc>>,
+    uptime_hours: u64,
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// Theta Edge Node Protocol Adapter
+pub struct ThetaAdapter {
+    config: ThetaConfig,
+    status: Arc<RwLock<ConnectionStatus>>,
+    allocation: Arc<RwLock<AllocationStrategy>>,
+    metrics: Arc<RwLock<ThetaMetrics>>,
+}
+
+impl ThetaAdapter {
+    /// Create a new Theta adapter
+    pub fn new(config: ThetaConfig) -> Self {
+        let allocation = AllocationStrategy {
+            cpu_cores: 2,
+            memory_gb: 2.0,
+            storage_gb: 20.0,
+            bandwidth_mbps: config.max_bandwidth_mbps * 0.25, // Theta primarily uses bandwidth
+            allocation_percent: 25.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+
+        Self {
+            config,
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            allocation: Arc::new(RwLock::new(allocation)),
+            metrics: Arc::new(RwLock::new(ThetaMetrics::default())),
+        }
+    }
+
+    /// Update relay and job counters with uptime
+    async fn update_uptime(&self) {
+        let allocation = self.allocation.read().await.clone();
+        let mut metrics = self.metrics.write().await;
+        if let Some(connected_at) = metrics.connected_at {
+            let duration = Utc::now().signed_duration_since(connected_at);
+            metrics.uptime_hours = duration.num_hours() as u64;
+
+            // Simulate relaying: allocated Mbps sustained at ~30% duty cycle
+            let hours = duration.num_seconds() as f64 / 3600.0;
+            metrics.relayed_bandwidth_gb = allocation.bandwidth_mbps * 0.3 * hours * 0.45;
+
+            // Simulate edge compute jobs: ~4 per hour
+            metrics.jobs_completed = metrics.uptime_hours * 4;
+
+            // ~0.05 TFUEL per relayed GB plus 0.2 TFUEL per job
+            metrics.tfuel_earned =
+                metrics.relayed_bandwidth_gb * 0.05 + metrics.jobs_completed as f64 * 0.2;
+        }
+    }
+
+    /// Current TFUEL earning rate in USD/hour
+    async fn calculate_current_earnings(&self) -> f64 {
+        let metrics = self.metrics.read().await;
+        let allocation = self.allocation.read().await;
+
+        let hours = metrics
+            .connected_at
+            .map(|t| Utc::now().signed_duration_since(t).num_seconds() as f64 / 3600.0)
+            .unwrap_or(0.0)
+            .max(1.0);
+
+        let tfuel_per_hour = metrics.tfuel_earned / hours;
+        tfuel_per_hour * self.config.tfuel_price_usd * (allocation.allocation_percent / 100.0)
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for ThetaAdapter {
+    fn protocol_name(&self) -> &str {
+        "Theta Edge Network"
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        if self.config.wallet_address.is_empty() {
+            return Err(ProtocolError::ConfigurationError(
+                "Wallet address not configured".to_string(),
+            ));
+        }
+
+        // Simulate connection
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        // Simulate connection delay
+        tokio::time::sleep(tokio::time::Duration::from_millis(120)).await;
+
+        *self.status.write().await = ConnectionStatus::Connected;
+
+        let mut metrics = self.metrics.write().await;
+        metrics.connected_at = Some(Utc::now());
+        metrics.uptime_hours = 0;
+
+        tracing::info!("Connected to Theta Edge Network");
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        if *self.status.read().await == ConnectionStatus::Disconnected {
+            return Ok(());
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+
+        let mut metrics = self.metrics.write().await;
+        metrics.connected_at = None;
+
+        tracing::info!("Disconnected from Theta Edge Network");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .try_read()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Connecting)
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.update_uptime().await;
+
+        let earnings_usd = self.calculate_current_earnings().await;
+        let metrics = self.metrics.read().await;
+        let allocation = self.allocation.read().await;
+
+        let mut metric_map = HashMap::new();
+        metric_map.insert("tfuel_earned".to_string(), metrics.tfuel_earned);
+        metric_map.insert("relayed_bandwidth_gb".to_string(), metrics.relayed_bandwidth_gb);
+        metric_map.insert("jobs_completed".to_string(), metrics.jobs_completed as f64);
+        metric_map.insert("jobs_failed".to_string(), metrics.jobs_failed as f64);
+        metric_map.insert(
+            "bandwidth_mbps_allocated".to_string(),
+            allocation.bandwidth_mbps,
+        );
+
+        Ok(EarningsData {
+            timestamp: Utc::now(),
+            amount_usd: earnings_usd,
+            protocol_id: "theta".to_string(),
+            metrics: metric_map,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // The edge node reports current relay rewards, not an hourly ledger
+        Err(ProtocolError::UnsupportedError(
+            "The Theta edge node does not expose historical earnings".to_string(),
+        ))
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        self.update_uptime().await;
+        let metrics = self.metrics.read().await;
+        let allocation = self.allocation.read().await;
+
+        Ok(ResourceMetrics {
+            cpu_percent: 15.0,
+            memory_mb: 1024.0,
+            bandwidth_mbps: allocation.bandwidth_mbps * 0.3,
+            storage_gb: 10.0,
+            uptime_seconds: metrics.uptime_hours * 3600,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        // Validate allocation
+        if !(self.config.min_allocation_percent..=self.config.max_allocation_percent)
+            .contains(&strategy.allocation_percent)
+        {
+            return Err(ProtocolError::AllocationError(format!(
+                "Allocation must be between {} and {}%",
+                self.config.min_allocation_percent, self.config.max_allocation_percent
+            )));
+        }
+
+        // Validate bandwidth does not exceed node uplink
+        if strategy.bandwidth_mbps > self.config.max_bandwidth_mbps {
+            return Err(ProtocolError::AllocationError(
+                "Bandwidth allocation exceeds maximum".to_string(),
+            ));
+        }
+
+        *self.allocation.write().await = strategy;
+        tracing::info!("Applied allocation strategy to Theta");
+        Ok(())
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        Ok(self.allocation.read().await.clone())
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let status = *self.status.read().await;
+        let is_healthy = status == ConnectionStatus::Connected;
+        let metrics = self.metrics.read().await;
+
+        let mut health_metrics = HashMap::new();
+        health_metrics.insert(
+            "relayed_bandwidth_gb".into(),
+            serde_json::json!(metrics.relayed_bandwidth_gb),
+        );
+        health_metrics.insert(
+            "jobs_completed".into(),
+            serde_json::json!(metrics.jobs_completed),
+        );
+        health_metrics.insert("jobs_failed".into(), serde_json::json!(metrics.jobs_failed));
+
+        Ok(HealthStatus {
+            is_healthy,
+            connection_status: status,
+            last_operation: Some(Utc::now()),
+            error_message: if is_healthy {
+                None
+            } else {
+                Some("Not connected to Theta Edge Node".to_string())
+            },
+            metrics: health_metrics,
+        })
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "protocol": "theta",
+            "edge_node_url": self.config.edge_node_url,
+            "max_bandwidth_mbps": self.config.max_bandwidth_mbps,
+            "min_allocation_percent": self.config.min_allocation_percent,
+            "max_allocation_percent": self.config.max_allocation_percent,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::new(
+            ResourceKind::Bandwidth,
+            self.config.min_allocation_percent,
+            self.config.max_allocation_percent,
+        )
+        .with_secondary(&[ResourceKind::Cpu])
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new("tfuel_earned", "TFUEL", MetricAggregation::Last, "TFUEL earned since start"),
+            MetricSchema::new("relayed_bandwidth_gb", "GB", MetricAggregation::Last, "Bandwidth relayed since start"),
+            MetricSchema::new("jobs_completed", "count", MetricAggregation::Last, "Edge jobs completed since start"),
+            MetricSchema::new("jobs_failed", "count", MetricAggregation::Last, "Edge jobs failed since start"),
+            MetricSchema::new(
+                "bandwidth_mbps_allocated",
+                "Mbps",
+                MetricAggregation::Mean,
+                "Bandwidth offered to the network",
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> ThetaConfig {
+        ThetaConfig {
+            wallet_address: "0xabc...".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_theta_creation() {
+        let adapter = ThetaAdapter::new(test_config());
+        assert_eq!(adapter.protocol_name(), "Theta Edge Network");
+    }
+
+    #[tokio::test]
+    async fn test_theta_connect_requires_wallet() {
+        let mut adapter = ThetaAdapter::new(ThetaConfig::default());
+        assert!(adapter.connect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_theta_earnings() {
+        let mut adapter = ThetaAdapter::new(test_config());
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.protocol_id, "theta");
+        assert!(earnings.amount_usd >= 0.0);
+        assert!(earnings.metrics.contains_key("tfuel_earned"));
+    }
+
+    #[tokio::test]
+    async fn test_theta_allocation_bounds() {
+        let mut adapter = ThetaAdapter::new(test_config());
+
+        let strategy = AllocationStrategy {
+            cpu_cores: 2,
+            memory_gb: 2.0,
+            storage_gb: 20.0,
+            bandwidth_mbps: 50.0,
+            allocation_percent: 40.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        };
+        assert!(adapter.apply_allocation(strategy.clone()).await.is_ok());
+
+        // Above max allocation
+        let too_high = AllocationStrategy {
+            allocation_percent: 80.0,
+            ..strategy.clone()
+        };
+        assert!(adapter.apply_allocation(too_high).await.is_err());
+
+        // Bandwidth above uplink
+        let too_fast = AllocationStrategy {
+            bandwidth_mbps: 500.0,
+            ..strategy
+        };
+        assert!(adapter.apply_allocation(too_fast).await.is_err());
+    }
+}
@@ -0,0 +1,436 @@
+/// WeatherXM Weather Station Adapter
+///
+/// WeatherXM rewards station owners in WXM for weather observations, scaled by
+/// a daily data-quality score. Stations run on their own hardware, so the
+/// adapter has nothing to allocate and runs in observe-only mode: earnings are
+/// aggregated, but the optimizer never reallocates it.
+///
+/// This adapter manages:
+/// - Connection to the WeatherXM API
+/// - Daily WXM reward tracking per station
+/// - Data-quality scores per station
+use super::{
+    AllocationStrategy, ConnectionStatus, EarningsData, HealthStatus, ProtocolAdapter,
+    MetricAggregation, MetricSchema, ProtocolError, ProtocolResult, ResourceMetrics, ResourceProfile,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// WeatherXM pays rewards daily
+const HOURS_PER_REWARD_PERIOD: f64 = 24.0;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// WeatherXM protocol configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherXMConfig {
+    /// WeatherXM API base URL
+    pub api_url: String,
+    /// API access token
+    pub api_token: String,
+    /// Station device IDs owned by the operator
+    pub station_ids: Vec<String>,
+    /// WXM price used for USD conversion
+    pub wxm_price_usd: f64,
+    /// Data-quality score (0-100) below which a station is reported unhealthy
+    pub min_quality_score: f64,
+}
+
+impl Default for WeatherXMConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://api.weatherxm.com/api/v1".to_string(),
+            api_token: String::new(),
+            station_ids: Vec::new(),
+            wxm_price_usd: 0.8,
+            min_quality_score: 80.0,
+        }
+    }
+}
+
+// ============================================================================
+// WEATHERXM API
+// ============================================================================
+
+/// Subset of the device rewards response
+#[derive(Debug, Deserialize)]
+struct RewardsResponse {
+    #[serde(default)]
+    latest: Option<LatestReward>,
+    #[serde(default)]
+    total_rewards: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestReward {
+    total_reward: f64,
+    /// Data-quality score (0-100)
+    base_reward_score: f64,
+}
+
+/// Latest reward state for one station
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StationRewards {
+    /// WXM earned in the latest daily period
+    pub daily_wxm: f64,
+    /// Lifetime WXM earned
+    pub total_wxm: f64,
+    /// Data-quality score (0-100)
+    pub quality_score: f64,
+}
+
+// ============================================================================
+// INTERNAL STATE
+// ============================================================================
+
+/// WeatherXM metrics tracked internally
+#[derive(Debug, Clone, Default)]
+struct WeatherXMMetrics {
+    stations: HashMap<String, StationRewards>,
+    connected_at: Option<DateTime<Utc>>,
+    last_refresh: Option<DateTime<Utc>>,
+}
+
+impl WeatherXMMetrics {
+    fn daily_wxm(&self) -> f64 {
+        self.stations.values().map(|s| s.daily_wxm).sum()
+    }
+
+    fn avg_quality(&self) -> f64 {
+        if self.stations.is_empty() {
+            return 0.0;
+        }
+        self.stations.values().map(|s| s.quality_score).sum::<f64>() / self.stations.len() as f64
+    }
+}
+
+// ============================================================================
+// ADAPTER IMPLEMENTATION
+// ============================================================================
+
+/// WeatherXM Weather Station Adapter
+pub struct WeatherXMAdapter {
+    config: WeatherXMConfig,
+    http: reqwest::Client,
+    status: Arc<RwLock<ConnectionStatus>>,
+    metrics: Arc<RwLock<WeatherXMMetrics>>,
+}
+
+impl WeatherXMAdapter {
+    /// Create a new WeatherXM adapter
+    pub fn new(config: WeatherXMConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            metrics: Arc::new(RwLock::new(WeatherXMMetrics::default())),
+        }
+    }
+
+    /// Fetch rewards for a single station
+    async fn fetch_station(&self, station_id: &str) -> ProtocolResult<StationRewards> {
+        let url = format!(
+            "{}/me/devices/{}/rewards",
+            self.config.api_url.trim_end_matches('/'),
+            station_id
+        );
+
+        let response = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.config.api_token)
+            .send()
+            .await
+            .map_err(|e| ProtocolError::NetworkError(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(ProtocolError::AuthenticationError(
+                "WeatherXM rejected the API token".to_string(),
+            ));
+        }
+        if !response.status().is_success() {
+            return Err(ProtocolError::ApiError(format!(
+                "Rewards for station {} returned {}",
+                station_id,
+                response.status()
+            )));
+        }
+
+        let rewards: RewardsResponse = response
+            .json()
+            .await
+            .map_err(|e| ProtocolError::ParseError(e.to_string()))?;
+
+        Ok(StationRewards {
+            daily_wxm: rewards.latest.as_ref().map_or(0.0, |l| l.total_reward),
+            total_wxm: rewards.total_rewards,
+            quality_score: rewards.latest.as_ref().map_or(0.0, |l| l.base_reward_score),
+        })
+    }
+
+    /// Refresh every configured station
+    async fn refresh(&self) -> ProtocolResult<()> {
+        let mut stations = HashMap::new();
+        for station_id in &self.config.station_ids {
+            stations.insert(station_id.clone(), self.fetch_station(station_id).await?);
+        }
+
+        let mut metrics = self.metrics.write().await;
+        metrics.stations = stations;
+        metrics.last_refresh = Some(Utc::now());
+        Ok(())
+    }
+
+    /// USD per hour implied by the latest daily rewards
+    fn hourly_usd(&self, metrics: &WeatherXMMetrics) -> f64 {
+        metrics.daily_wxm() / HOURS_PER_REWARD_PERIOD * self.config.wxm_price_usd
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for WeatherXMAdapter {
+    fn protocol_name(&self) -> &str {
+        "WeatherXM"
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        if self.config.station_ids.is_empty() {
+            return Err(ProtocolError::ConfigurationError(
+                "No WeatherXM stations configured".to_string(),
+            ));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        if let Err(e) = self.refresh().await {
+            *self.status.write().await = ConnectionStatus::Failed;
+            return Err(ProtocolError::ConnectionError(format!(
+                "WeatherXM API unreachable: {}",
+                e
+            )));
+        }
+
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.metrics.write().await.connected_at = Some(Utc::now());
+
+        tracing::info!(
+            "Connected to WeatherXM ({} stations)",
+            self.config.station_ids.len()
+        );
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        if *self.status.read().await == ConnectionStatus::Disconnected {
+            return Ok(());
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.metrics.write().await.connected_at = None;
+
+        tracing::info!("Disconnected from WeatherXM");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.status
+            .try_read()
+            .map(|s| *s)
+            .unwrap_or(ConnectionStatus::Connecting)
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.refresh().await?;
+
+        let metrics = self.metrics.read().await;
+
+        let mut metric_map = HashMap::new();
+        metric_map.insert("daily_wxm".to_string(), metrics.daily_wxm());
+        metric_map.insert("avg_quality_score".to_string(), metrics.avg_quality());
+        metric_map.insert("stations".to_string(), metrics.stations.len() as f64);
+
+        Ok(EarningsData {
+            timestamp: Utc::now(),
+            amount_usd: self.hourly_usd(&metrics),
+            protocol_id: "weatherxm".to_string(),
+            metrics: metric_map,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn get_historical_earnings(&self, _hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        // Rewards are paid per day; the API has no hourly history
+        Err(ProtocolError::UnsupportedError(
+            "The WeatherXM API does not expose hourly earnings history".to_string(),
+        ))
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        let metrics = self.metrics.read().await;
+        let uptime_seconds = metrics
+            .connected_at
+            .map(|t| Utc::now().signed_duration_since(t).num_seconds().max(0) as u64)
+            .unwrap_or(0);
+
+        Ok(ResourceMetrics {
+            cpu_percent: 0.0,
+            memory_mb: 0.0,
+            bandwidth_mbps: 0.0,
+            storage_gb: 0.0,
+            uptime_seconds,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            gpu_utilization_percent: 0.0,
+            stale_seconds: 0,
+        })
+    }
+
+    async fn apply_allocation(&mut self, _strategy: AllocationStrategy) -> ProtocolResult<()> {
+        Err(ProtocolError::AllocationError(
+            "WeatherXM is observe-only and has no allocatable resources".to_string(),
+        ))
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        Ok(AllocationStrategy {
+            cpu_cores: 0,
+            memory_gb: 0.0,
+            storage_gb: 0.0,
+            bandwidth_mbps: 0.0,
+            allocation_percent: 0.0,
+            gpu_count: 0,
+            vram_gb: 0.0,
+            pricing: None,
+        })
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let status = *self.status.read().await;
+        let metrics = self.metrics.read().await;
+
+        let low_quality: Vec<_> = metrics
+            .stations
+            .iter()
+            .filter(|(_, s)| s.quality_score < self.config.min_quality_score)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let is_healthy = status == ConnectionStatus::Connected && low_quality.is_empty();
+
+        let mut health_metrics = HashMap::new();
+        health_metrics.insert(
+            "avg_quality_score".into(),
+            serde_json::json!(metrics.avg_quality()),
+        );
+        health_metrics.insert("stations".into(), serde_json::json!(metrics.stations));
+
+        Ok(HealthStatus {
+            is_healthy,
+            connection_status: status,
+            last_operation: metrics.last_refresh,
+            error_message: if !low_quality.is_empty() {
+                Some(format!("Low data quality: {}", low_quality.join(", ")))
+            } else if !is_healthy {
+                Some("Not connected to WeatherXM".to_string())
+            } else {
+                None
+            },
+            metrics: health_metrics,
+        })
+    }
+
+    fn configure_http(&mut self, http: &super::http::HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.http = http.client(protocol)?;
+        Ok(())
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "protocol": "weatherxm",
+            "api_url": self.config.api_url,
+            "station_ids": self.config.station_ids,
+            "wxm_price_usd": self.config.wxm_price_usd,
+            "min_quality_score": self.config.min_quality_score,
+        })
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile::observe_only()
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        vec![
+            MetricSchema::new("daily_wxm", "WXM/day", MetricAggregation::Mean, "Daily rewards across stations"),
+            MetricSchema::new(
+                "avg_quality_score",
+                "score",
+                MetricAggregation::Min,
+                "Average station data quality (0-100)",
+            ),
+            MetricSchema::new("stations", "count", MetricAggregation::Last, "Stations owned"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(api_url: String) -> WeatherXMConfig {
+        WeatherXMConfig {
+            api_url,
+            api_token: "token".to_string(),
+            station_ids: vec!["st-1".to_string(), "st-2".to_string()],
+            wxm_price_usd: 1.0,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_weatherxm_connect_requires_stations() {
+        let mut adapter = WeatherXMAdapter::new(WeatherXMConfig::default());
+        assert!(adapter.connect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_weatherxm_rewards_and_quality() {
+        let mut server = mockito::Server::new_async().await;
+        let _st1 = server
+            .mock("GET", "/me/devices/st-1/rewards")
+            .with_body(r#"{"latest":{"total_reward":2.4,"base_reward_score":95.0},"total_rewards":300.0}"#)
+            .create_async()
+            .await;
+        let _st2 = server
+            .mock("GET", "/me/devices/st-2/rewards")
+            .with_body(r#"{"latest":{"total_reward":2.4,"base_reward_score":60.0},"total_rewards":120.0}"#)
+            .create_async()
+            .await;
+
+        let mut adapter = WeatherXMAdapter::new(test_config(server.url()));
+        adapter.connect().await.unwrap();
+
+        let earnings = adapter.get_current_earnings().await.unwrap();
+        assert_eq!(earnings.protocol_id, "weatherxm");
+        // 4.8 WXM/day / 24h * $1.00
+        assert!((earnings.amount_usd - 0.2).abs() < 1e-9);
+
+        // st-2 is below the default 80 quality floor
+        let health = adapter.health_check().await.unwrap();
+        assert!(!health.is_healthy);
+        assert!(health.error_message.unwrap().contains("st-2"));
+    }
+
+    #[tokio::test]
+    async fn test_weatherxm_is_observe_only() {
+        let mut adapter = WeatherXMAdapter::new(WeatherXMConfig::default());
+        assert!(adapter.resource_profile().observe_only);
+
+        let allocation = adapter.get_current_allocation().await.unwrap();
+        assert!(adapter.apply_allocation(allocation).await.is_err());
+    }
+}
@@ -0,0 +1,246 @@
+/// First-Run History Import
+///
+/// On a fresh database, earnings history is fetched from every protocol whose
+/// API keeps an earnings ledger and stored as hourly metrics, so reports,
+/// trends and optimizer confidence have data from day one instead of starting
+/// empty. Protocols without one report `UnsupportedError` and start empty;
+/// nothing is estimated for hours the orchestrator did not observe.
+use chrono::{DateTime, DurationRound, Utc};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use super::SchedulerConfig;
+use crate::db::queries;
+use crate::orchestration::{AggregatedMetrics, ResourceUtilization};
+use crate::protocols::{EarningsData, ProtocolError};
+use crate::shutdown::Shutdown;
+use crate::{EarningsOptimizer, ProtocolCoordinator};
+
+/// Most history that can be imported
+pub const MAX_BACKFILL_DAYS: u32 = 90;
+
+/// Import history in the background if the coordinator's account has no
+/// metrics yet
+///
+/// Imports `backfill_days`, capped at the metrics retention period so the
+/// cleanup task doesn't immediately delete what was imported.
+pub async fn start_backfill(
+    coordinator: Arc<ProtocolCoordinator>,
+    optimizer: Arc<EarningsOptimizer>,
+    db_pool: SqlitePool,
+    config: &SchedulerConfig,
+    shutdown: &Shutdown,
+) {
+    let days = config
+        .backfill_days
+        .min(config.metrics_retention_days.max(0) as u32);
+    if days == 0 {
+        return;
+    }
+
+    match queries::get_latest_metrics(&db_pool, coordinator.account_id()).await {
+        Ok(None) => {}
+        Ok(Some(_)) => return,
+        Err(e) => {
+            log::error!("❌ Failed to check for existing metrics: {}", e);
+            return;
+        }
+    }
+
+    log::info!("📥 First run: importing {} days of earnings history", days);
+    let token = shutdown.token();
+    shutdown.track(tokio::spawn(async move {
+        tokio::select! {
+            result = backfill_history(&coordinator, &optimizer, &db_pool, days) => match result {
+                Ok(hours) => log::info!("✅ Imported {} hours of earnings history", hours),
+                Err(e) => log::error!("❌ Failed to import earnings history: {}", e),
+            },
+            _ = token.cancelled() => log::info!("📥 Earnings history import stopped by shutdown"),
+        }
+    }));
+}
+
+/// Fetch `days` of earnings history from every adapter and store it hourly
+///
+/// Protocols without an earnings ledger, or whose API fails, are skipped. Allocations are not part of the
+/// history, so each protocol's current allocation is recorded. The imported
+/// hours also seed the optimizer's history. Returns the hours stored.
+pub async fn backfill_history(
+    coordinator: &ProtocolCoordinator,
+    optimizer: &EarningsOptimizer,
+    db_pool: &SqlitePool,
+    days: u32,
+) -> Result<usize, sqlx::Error> {
+    let mut history = HashMap::new();
+    let mut allocation_by_protocol = HashMap::new();
+    let mut resource_profiles = HashMap::new();
+
+    for (protocol, adapter) in coordinator.adapters() {
+        let adapter = adapter.read().await;
+        match adapter.get_historical_earnings(days * 24).await {
+            Ok(earnings) => {
+                history.insert(protocol.clone(), earnings);
+            }
+            Err(ProtocolError::UnsupportedError(_)) => {
+                log::debug!("📥 {} has no earnings history to import", protocol);
+                continue;
+            }
+            Err(e) => {
+                log::warn!("⚠️ Skipping {} history import: {}", protocol, e);
+                continue;
+            }
+        }
+        if let Ok(allocation) = adapter.get_current_allocation().await {
+            allocation_by_protocol.insert(protocol.clone(), allocation.allocation_percent);
+        }
+        let protocol_type = coordinator.protocol_type(protocol).unwrap_or(protocol);
+        resource_profiles.insert(protocol_type.to_string(), adapter.resource_profile());
+    }
+
+    let since = Utc::now() - chrono::Duration::days(days as i64);
+    let buckets = hourly_buckets(&history, since);
+    for (timestamp, earnings_by_protocol) in &buckets {
+        queries::store_historical_metrics(db_pool, coordinator.account_id(), *timestamp, earnings_by_protocol, &allocation_by_protocol)
+            .await?;
+
+        optimizer.update_metrics(AggregatedMetrics {
+            timestamp: *timestamp,
+            total_earnings_per_hour: earnings_by_protocol.values().sum(),
+            earnings_by_protocol: earnings_by_protocol.clone(),
+            allocation_by_protocol: allocation_by_protocol.clone(),
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 0.0,
+                memory_percent: 0.0,
+                bandwidth_percent: 0.0,
+                storage_percent: 0.0,
+            },
+            connection_status: earnings_by_protocol.keys().map(|p| (p.clone(), true)).collect(),
+            resource_profiles: resource_profiles.clone(),
+            protocol_types: coordinator.protocol_types().clone(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        });
+    }
+
+    Ok(buckets.len())
+}
+
+/// Average earnings per protocol for each hour since `since`, oldest first
+fn hourly_buckets(
+    history: &HashMap<String, Vec<EarningsData>>,
+    since: DateTime<Utc>,
+) -> BTreeMap<DateTime<Utc>, HashMap<String, f64>> {
+    let mut sums: BTreeMap<DateTime<Utc>, HashMap<String, (f64, u32)>> = BTreeMap::new();
+    for (protocol, earnings) in history {
+        for point in earnings.iter().filter(|e| e.timestamp >= since) {
+            let hour = point
+                .timestamp
+                .duration_trunc(chrono::Duration::hours(1))
+                .unwrap_or(point.timestamp);
+            let (sum, count) = sums
+                .entry(hour)
+                .or_default()
+                .entry(protocol.clone())
+                .or_default();
+            *sum += point.amount_usd;
+            *count += 1;
+        }
+    }
+
+    sums.into_iter()
+        .map(|(hour, by_protocol)| {
+            let averages = by_protocol
+                .into_iter()
+                .map(|(protocol, (sum, count))| (protocol, sum / count as f64))
+                .collect();
+            (hour, averages)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OptimizerConfig;
+    use chrono::TimeZone;
+
+    fn point(protocol: &str, timestamp: DateTime<Utc>, amount_usd: f64) -> EarningsData {
+        EarningsData {
+            timestamp,
+            amount_usd,
+            protocol_id: protocol.to_string(),
+            metrics: HashMap::new(),
+            stale_seconds: 0,
+        }
+    }
+
+    #[test]
+    fn test_hourly_buckets_average_within_hour() {
+        let hour = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        let history = HashMap::from([
+            (
+                "storj".to_string(),
+                vec![
+                    point("storj", hour + chrono::Duration::minutes(10), 0.2),
+                    point("storj", hour + chrono::Duration::minutes(40), 0.4),
+                    point("storj", hour - chrono::Duration::hours(1), 0.1),
+                    // Before the import window
+                    point("storj", hour - chrono::Duration::days(5), 9.0),
+                ],
+            ),
+            ("golem".to_string(), vec![point("golem", hour, 1.0)]),
+        ]);
+
+        let buckets = hourly_buckets(&history, hour - chrono::Duration::days(1));
+        assert_eq!(buckets.len(), 2);
+        let (first, earlier) = buckets.iter().next().unwrap();
+        assert_eq!(*first, hour - chrono::Duration::hours(1));
+        assert_eq!(earlier.len(), 1);
+        assert!((buckets[&hour]["storj"] - 0.3).abs() < 1e-9);
+        assert_eq!(buckets[&hour]["golem"], 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_stores_only_ledger_history() {
+        use crate::protocols::grass::{GrassAdapter, GrassConfig};
+        use crate::protocols::mock::{EarningsCurve, MockAdapter};
+
+        let pool = crate::db::test_pool().await;
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+        let mut coordinator = ProtocolCoordinator::new(10);
+        let mock = MockAdapter::new("mock", EarningsCurve::Constant(2.0), 50.0);
+        coordinator.register_adapter("mock".to_string(), Box::new(mock));
+        // Has no earnings ledger, so it must not contribute estimated hours
+        coordinator.register_adapter("grass".to_string(), Box::new(GrassAdapter::new(GrassConfig::default())));
+
+        let hours = backfill_history(&coordinator, &optimizer, &pool, 2).await.unwrap();
+        assert_eq!(hours, 48);
+        assert_eq!(optimizer.snapshot().history_len(), 48);
+
+        let stored = queries::get_metrics_history(&pool, 1, 100).await.unwrap();
+        assert_eq!(stored.len(), 48);
+        assert!(stored.iter().all(|m| (m.total_earnings_per_hour - 1.0).abs() < 1e-9));
+
+        let now = Utc::now();
+        let rows = queries::get_protocol_metrics_by_range(&pool, 1, now - chrono::Duration::days(3), now)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 48);
+        assert!(rows.iter().all(|r| r.protocol_name == "mock" && r.allocation_percent == 50.0));
+    }
+
+    #[tokio::test]
+    async fn test_backfill_without_adapters_stores_nothing() {
+        let pool = crate::db::test_pool().await;
+        let optimizer = EarningsOptimizer::new(OptimizerConfig::default());
+
+        let hours = backfill_history(&ProtocolCoordinator::new(10), &optimizer, &pool, 30)
+            .await
+            .unwrap();
+        assert_eq!(hours, 0);
+        assert!(queries::get_latest_metrics(&pool, 1).await.unwrap().is_none());
+        assert_eq!(optimizer.snapshot().history_len(), 0);
+    }
+}
@@ -0,0 +1,135 @@
+/// Reallocation Canary Evaluation
+///
+/// Once the canary window after an executed reallocation has passed, the
+/// optimization task compares average total earnings over the window after
+/// the change with the window before it, and stores that realized impact and
+/// its error against the predicted improvement on the `reallocations` row.
+/// The relative errors of recent reallocations, by protocol, discount the
+/// optimizer's confidence in moves involving protocols it has mispredicted.
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+use crate::db::models::ReallocationRecord;
+use crate::db::queries;
+
+/// Evaluated reallocations the prediction errors are computed from
+const RECENT_OUTCOMES: i64 = 50;
+
+/// Predicted impacts (USD/hour) smaller than this are measured against it
+const MIN_PREDICTION: f64 = 0.01;
+
+/// Evaluate every reallocation whose canary `window` ended by `now`;
+/// returns how many were evaluated
+///
+/// A reallocation without earnings recorded on both sides of it is marked
+/// evaluated with no realized impact.
+pub async fn evaluate_reallocations(
+    pool: &SqlitePool,
+    account_id: i64,
+    window: Duration,
+    now: DateTime<Utc>,
+) -> Result<usize, sqlx::Error> {
+    let due = queries::get_unevaluated_reallocations(pool, account_id, now - window).await?;
+    for reallocation in &due {
+        let Some(id) = reallocation.id else {
+            continue;
+        };
+        let realized = match DateTime::parse_from_rfc3339(&reallocation.timestamp) {
+            Ok(at) => {
+                let at = at.with_timezone(&Utc);
+                let before = queries::get_average_earnings(pool, account_id, at - window, at).await?;
+                let after = queries::get_average_earnings(pool, account_id, at, at + window).await?;
+                before.zip(after).map(|(before, after)| after - before)
+            }
+            Err(_) => None,
+        };
+        let error = realized.zip(reallocation.earnings_impact).map(|(realized, predicted)| realized - predicted);
+        queries::record_reallocation_outcome(pool, id, realized, error, now).await?;
+    }
+    Ok(due.len())
+}
+
+/// Relative prediction error (0-1) of recent reallocations, by protocol
+pub async fn prediction_errors(pool: &SqlitePool, account_id: i64) -> Result<HashMap<String, f64>, sqlx::Error> {
+    let outcomes = queries::get_evaluated_reallocations(pool, account_id, RECENT_OUTCOMES).await?;
+    Ok(relative_errors(&outcomes))
+}
+
+/// Mean of each protocol's `|error| / |predicted|`, each capped at 1
+fn relative_errors(outcomes: &[ReallocationRecord]) -> HashMap<String, f64> {
+    let mut totals: HashMap<String, (f64, usize)> = HashMap::new();
+    for outcome in outcomes {
+        let (Some(error), Some(predicted)) = (outcome.impact_error, outcome.earnings_impact) else {
+            continue;
+        };
+        let relative = (error.abs() / predicted.abs().max(MIN_PREDICTION)).min(1.0);
+        let total = totals.entry(outcome.protocol_name.clone()).or_default();
+        *total = (total.0 + relative, total.1 + 1);
+    }
+    totals
+        .into_iter()
+        .map(|(protocol, (sum, count))| (protocol, sum / count as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reallocation_outcomes_feed_prediction_errors() {
+        let pool = crate::db::test_pool().await;
+        let now = Utc::now();
+        let window = Duration::hours(6);
+        let changed_at = now - Duration::hours(8);
+
+        // Earnings rose by $0.50/hour after a change predicted to add $1/hour
+        for hours in [-4, -2] {
+            let earnings = HashMap::from([("storj".to_string(), 1.0)]);
+            queries::store_historical_metrics(&pool, 1, changed_at + Duration::hours(hours), &earnings, &earnings)
+                .await
+                .unwrap();
+        }
+        for hours in [1, 3] {
+            let earnings = HashMap::from([("storj".to_string(), 1.5)]);
+            queries::store_historical_metrics(&pool, 1, changed_at + Duration::hours(hours), &earnings, &earnings)
+                .await
+                .unwrap();
+        }
+        for (at, protocol) in [(changed_at, "storj"), (now - Duration::hours(1), "golem")] {
+            queries::store_reallocation(&pool, 1, at, protocol.to_string(), 40.0, 60.0, Some(1.0), None, None)
+                .await
+                .unwrap();
+        }
+
+        // The golem change is still inside its window
+        assert_eq!(evaluate_reallocations(&pool, 1, window, now).await.unwrap(), 1);
+        assert_eq!(evaluate_reallocations(&pool, 1, window, now).await.unwrap(), 0);
+
+        let storj = &queries::get_evaluated_reallocations(&pool, 1, 10).await.unwrap()[0];
+        assert_eq!(storj.protocol_name, "storj");
+        assert!((storj.realized_impact.unwrap() - 0.5).abs() < 1e-9);
+        assert!((storj.impact_error.unwrap() + 0.5).abs() < 1e-9);
+
+        let errors = prediction_errors(&pool, 1).await.unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!((errors["storj"] - 0.5).abs() < 1e-9);
+        assert!(prediction_errors(&pool, 2).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_relative_errors_are_capped() {
+        let outcome = |protocol: &str, predicted: f64, error: f64| ReallocationRecord {
+            impact_error: Some(error),
+            ..ReallocationRecord::new(Utc::now(), protocol.to_string(), 10.0, 20.0, Some(predicted), None)
+        };
+        let errors = relative_errors(&[
+            outcome("grass", 1.0, -0.2),
+            outcome("grass", 1.0, 3.0),
+            outcome("streamr", 0.0, 0.001),
+        ]);
+        assert!((errors["grass"] - 0.6).abs() < 1e-9);
+        assert!((errors["streamr"] - 0.1).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,168 @@
+/// Credential Health Checks
+///
+/// Auth tokens and API keys can be revoked or expire while the orchestrator
+/// runs, after which polling quietly reports no earnings. The credential
+/// check task asks every adapter to validate its credentials on a separate,
+/// slower schedule and alerts when one is rejected or about to expire
+/// (Grass auth tokens carry an expiry).
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::protocols::{CredentialStatus, ProtocolError, ProtocolResult};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Credential check settings (`[scheduler.credentials]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CredentialCheckConfig {
+    /// Seconds between credential checks (default: 21600 = 6 hours)
+    pub interval_secs: u64,
+    /// Alert this many hours before a credential expires (default: 72)
+    pub expiry_warning_hours: u64,
+}
+
+impl Default for CredentialCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 21600,
+            expiry_warning_hours: 72,
+        }
+    }
+}
+
+impl CredentialCheckConfig {
+    /// Validate the check interval
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_secs == 0 {
+            return Err("credentials.interval_secs must be at least 1 second".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// CLASSIFICATION
+// ============================================================================
+
+/// Health of one protocol's credentials
+#[derive(Debug, Clone, PartialEq)]
+pub enum CredentialHealth {
+    /// Accepted and not close to expiring
+    Valid,
+    /// Expires within the warning window
+    Expiring(DateTime<Utc>),
+    /// Missing, rejected or already expired
+    Rejected(String),
+    /// The check failed for another reason (network, timeout), so the
+    /// credentials' state is unknown
+    Unknown(String),
+}
+
+impl CredentialHealth {
+    /// Classify a credential check result at `now`
+    pub fn from_check(
+        result: &ProtocolResult<CredentialStatus>,
+        config: &CredentialCheckConfig,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let status = match result {
+            Ok(status) => status,
+            Err(ProtocolError::AuthenticationError(message)) => return Self::Rejected(message.clone()),
+            Err(e) => return Self::Unknown(e.to_string()),
+        };
+        match status.expires_at {
+            Some(at) if at <= now => Self::Rejected(format!("credentials expired at {}", at.to_rfc3339())),
+            Some(at) if at <= now + chrono::Duration::hours(config.expiry_warning_hours as i64) => {
+                Self::Expiring(at)
+            }
+            _ => Self::Valid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_credential_health() {
+        let config = CredentialCheckConfig::default();
+        let now = Utc::now();
+        let expiring = |hours| {
+            Ok(CredentialStatus {
+                expires_at: Some(now + Duration::hours(hours)),
+            })
+        };
+
+        let unchecked = Ok(CredentialStatus::default());
+        assert_eq!(CredentialHealth::from_check(&unchecked, &config, now), CredentialHealth::Valid);
+        assert_eq!(CredentialHealth::from_check(&expiring(100), &config, now), CredentialHealth::Valid);
+        assert_eq!(
+            CredentialHealth::from_check(&expiring(24), &config, now),
+            CredentialHealth::Expiring(now + Duration::hours(24))
+        );
+        assert!(matches!(
+            CredentialHealth::from_check(&expiring(-1), &config, now),
+            CredentialHealth::Rejected(message) if message.starts_with("credentials expired")
+        ));
+
+        let rejected = Err(ProtocolError::AuthenticationError("Grass rejected the auth token".to_string()));
+        assert_eq!(
+            CredentialHealth::from_check(&rejected, &config, now),
+            CredentialHealth::Rejected("Grass rejected the auth token".to_string())
+        );
+        let offline = Err(ProtocolError::NetworkError("connection refused".to_string()));
+        assert!(matches!(CredentialHealth::from_check(&offline, &config, now), CredentialHealth::Unknown(_)));
+    }
+
+    #[tokio::test]
+    async fn test_wrapped_adapter_reports_rejected_credentials() {
+        use crate::cgroups::{CgroupAdapter, CgroupConfig, CgroupEnforcer};
+        use crate::disk_quotas::{DiskQuotaAdapter, DiskQuotaConfig, DiskQuotaManager};
+        use crate::protocols::mock::{EarningsCurve, MockAdapter, MockOperation};
+        use crate::protocols::ProtocolAdapter;
+        use crate::services::{ServiceAdapter, ServiceConfig, ServiceManager};
+        use std::sync::Arc;
+
+        let mock = MockAdapter::new("mock", EarningsCurve::Constant(1.0), 50.0);
+        let handle = mock.handle();
+        let mut adapter: Box<dyn ProtocolAdapter> = Box::new(mock);
+        adapter = Box::new(DiskQuotaAdapter::new(
+            adapter,
+            Arc::new(DiskQuotaManager::new(DiskQuotaConfig::default())),
+            "mock",
+        ));
+        adapter = Box::new(CgroupAdapter::new(adapter, Arc::new(CgroupEnforcer::new(CgroupConfig::default())), "mock"));
+        #[cfg(feature = "docker")]
+        {
+            use crate::containers::{ContainerConfig, ContainerManager, ContainerizedAdapter};
+
+            // The client connects lazily, so no daemon is needed here
+            let config = ContainerConfig {
+                docker_host: Some("tcp://127.0.0.1:1".to_string()),
+                ..Default::default()
+            };
+            adapter = Box::new(ContainerizedAdapter::new(
+                adapter,
+                Arc::new(ContainerManager::connect(config).unwrap()),
+                "mock",
+            ));
+        }
+        adapter = Box::new(ServiceAdapter::new(adapter, Arc::new(ServiceManager::new(ServiceConfig::default())), "mock"));
+
+        let config = CredentialCheckConfig::default();
+        let check = adapter.check_credentials().await;
+        assert_eq!(CredentialHealth::from_check(&check, &config, Utc::now()), CredentialHealth::Valid);
+
+        handle.fail_always(MockOperation::Credentials);
+        let check = adapter.check_credentials().await;
+        assert!(matches!(
+            CredentialHealth::from_check(&check, &config, Utc::now()),
+            CredentialHealth::Rejected(_)
+        ));
+    }
+}
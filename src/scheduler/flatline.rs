@@ -0,0 +1,141 @@
+/// Earnings Flatline Detection
+///
+/// A connected protocol whose reported earnings stay exactly the same poll
+/// after poll is rarely earning steadily: usually its API is serving cached
+/// or stuck data, or the node silently stopped working while the adapter
+/// still reports it connected. The alert task raises an alert once the same
+/// value has been reported for `polls` consecutive polls.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::orchestration::window::WindowSnapshot;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Flatline alert settings (`[scheduler.flatline]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlatlineConfig {
+    /// Consecutive polls reporting identical earnings that count as a
+    /// flatline, 0 to disable (default: 30)
+    ///
+    /// Responses served from the `[cache]` count as polls, so keep
+    /// `polls × poll_interval` well above the cache's `ttl_secs`.
+    pub polls: usize,
+    /// Protocols whose earnings legitimately hold still, e.g. fixed payouts
+    pub exclude: Vec<String>,
+}
+
+impl Default for FlatlineConfig {
+    fn default() -> Self {
+        Self { polls: 30, exclude: Vec::new() }
+    }
+}
+
+impl FlatlineConfig {
+    /// Validate the poll count
+    pub fn validate(&self) -> Result<(), String> {
+        if self.polls == 1 {
+            return Err("flatline.polls must be 0 (disabled) or at least 2".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// DETECTION
+// ============================================================================
+
+/// Connected protocols of the latest snapshot in `history` with their
+/// earnings, and whether each has flatlined
+///
+/// A protocol has flatlined when it was connected and reported the same
+/// earnings in each of the latest `config.polls` snapshots. Excluded
+/// protocols are left out.
+pub fn flatlined_protocols(history: &WindowSnapshot, config: &FlatlineConfig) -> BTreeMap<String, (f64, bool)> {
+    let mut protocols = BTreeMap::new();
+    let Some(latest) = history.last() else {
+        return protocols;
+    };
+
+    for (protocol, earnings) in &latest.earnings_by_protocol {
+        if latest.connection_status.get(protocol) != Some(&true) || config.exclude.contains(protocol) {
+            continue;
+        }
+        let flatlined = config.polls > 0
+            && history.len() >= config.polls
+            && history.iter().rev().take(config.polls).all(|snapshot| {
+                snapshot.connection_status.get(protocol) == Some(&true)
+                    && snapshot.earnings_by_protocol.get(protocol) == Some(earnings)
+            });
+        protocols.insert(protocol.clone(), (*earnings, flatlined));
+    }
+    protocols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::window::MetricsWindow;
+    use crate::orchestration::{AggregatedMetrics, ResourceUtilization};
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn snapshot(earnings: &[(&str, f64)], connected: bool) -> AggregatedMetrics {
+        AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: earnings.iter().map(|(_, e)| e).sum(),
+            earnings_by_protocol: earnings.iter().map(|(p, e)| (p.to_string(), *e)).collect(),
+            allocation_by_protocol: HashMap::new(),
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 0.0,
+                memory_percent: 0.0,
+                bandwidth_percent: 0.0,
+                storage_percent: 0.0,
+            },
+            connection_status: earnings.iter().map(|(p, _)| (p.to_string(), connected)).collect(),
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        }
+    }
+
+    #[test]
+    fn test_identical_earnings_across_polls_flatline() {
+        let config = FlatlineConfig { polls: 3, exclude: vec!["streamr".to_string()] };
+        let window = MetricsWindow::new(10);
+        window.push(snapshot(&[("storj", 1.0), ("grass", 0.5), ("streamr", 2.0)], true));
+        window.push(snapshot(&[("storj", 1.2), ("grass", 0.5), ("streamr", 2.0)], true));
+        window.push(snapshot(&[("storj", 1.2), ("grass", 0.5), ("streamr", 2.0)], true));
+
+        let protocols = flatlined_protocols(&window.snapshot(), &config);
+        assert_eq!(protocols["grass"], (0.5, true));
+        assert_eq!(protocols["storj"], (1.2, false));
+        assert!(!protocols.contains_key("streamr"));
+
+        window.push(snapshot(&[("storj", 1.2), ("grass", 0.6)], true));
+        let protocols = flatlined_protocols(&window.snapshot(), &config);
+        assert_eq!(protocols["grass"], (0.6, false));
+        assert_eq!(protocols["storj"], (1.2, true));
+    }
+
+    #[test]
+    fn test_disconnected_polls_do_not_flatline() {
+        let config = FlatlineConfig { polls: 2, exclude: Vec::new() };
+        let window = MetricsWindow::new(10);
+        window.push(snapshot(&[("storj", 1.0)], false));
+        window.push(snapshot(&[("storj", 1.0)], true));
+        assert_eq!(flatlined_protocols(&window.snapshot(), &config)["storj"], (1.0, false));
+
+        // Too little history, and disabled detection, never flatline
+        let config = FlatlineConfig { polls: 5, exclude: Vec::new() };
+        window.push(snapshot(&[("storj", 1.0)], true));
+        assert_eq!(flatlined_protocols(&window.snapshot(), &config)["storj"], (1.0, false));
+        let config = FlatlineConfig { polls: 0, exclude: Vec::new() };
+        assert_eq!(flatlined_protocols(&window.snapshot(), &config)["storj"], (1.0, false));
+    }
+}
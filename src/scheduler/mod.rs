@@ -13,27 +13,136 @@
 //! ## Architecture
 //! ```text
 //! Scheduler
+//!   ├─> PollingTask (every N seconds)
+//!   │     └─> Poll adapters → Record uptime → Update dashboard snapshot → Publish MetricsCollected
 //!   ├─> OptimizationTask (every N seconds)
-//!   │     └─> Analyze opportunities → Trigger reallocations
+//!   │     └─> Queue metrics → Refresh earnings risk (hourly) → Evaluate past reallocations
+//!   │         → Analyze opportunities → Trigger reallocations
+//!   ├─> MetricsFlushTask (every N seconds, and on shutdown)
+//!   │     └─> Write queued metrics to DB in one transaction
 //!   ├─> CleanupTask (daily)
-//!   │     └─> Remove old metrics → Archive alerts
+//!   │     └─> Archive expiring metrics → Remove rows past each table's retention
 //!   ├─> AlertProcessor (every minute)
-//!   │     └─> Check thresholds → Generate alerts
-//!   └─> ReportGenerator (hourly)
-//!         └─> Generate performance reports → Store to DB
+//!   │     └─> Check thresholds, uptime and flatlined earnings → Generate alerts
+//!   ├─> CredentialCheckTask (every N hours)
+//!   │     └─> Validate adapter credentials → Alert on rejected or expiring ones
+//!   ├─> ReportGenerator (hourly)
+//!   │     └─> Generate performance reports → Store to DB
+//!   ├─> WalletBalanceTask (every N seconds)
+//!   │     └─> Poll payout addresses → Store balances to DB
+//!   ├─> BackupTask (every N hours)
+//!   │     └─> VACUUM INTO a backup file → Upload to S3 → Prune old backups
+//!   ├─> DigestTask (daily or weekly, at a configured hour)
+//!   │     └─> Summarize earnings, allocation changes and uptime → Send to notification channels
+//!   ├─> IpCheckTask (every N seconds)
+//!   │     └─> Look up IP-sensitive protocols' public IP → Alert on datacenter or blocklisted IPs
+//!   ├─> NetworkProbeTask (every N seconds)
+//!   │     └─> Measure latency and throughput → Store to DB → Hand upstream to coordinator and reallocation
+//!   └─> Backfill (once, on an empty database)
+//!         └─> Import protocol earnings history → Store hourly metrics to DB
 //! ```
+//!
+//! Every task heartbeats into a shared [`SchedulerStatus`]; a [`Watchdog`]
+//! restarts tasks that panic or stop ticking. Metrics, alerts and
+//! reallocations are published on the [`EventBus`] rather than delivered to
+//! the monitor, notification channels or WebSocket sessions directly.
+
+mod backfill;
+mod canary;
+mod credentials;
+mod flatline;
+mod retention;
+mod risk;
+mod uptime;
+mod watchdog;
+mod write_buffer;
 
-use chrono::{DateTime, Utc};
+pub use backfill::{backfill_history, start_backfill};
+pub use credentials::{CredentialCheckConfig, CredentialHealth};
+pub use flatline::{flatlined_protocols, FlatlineConfig};
+pub use retention::{
+    archive_metrics, ArchiveConfig, ArchiveError, RetainedTable, RetentionConfig, RETAINED_TABLES,
+};
+pub use risk::{refresh_risk, RISK_WINDOW_HOURS};
+pub use uptime::{record_connectivity, UptimeConfig};
+pub use watchdog::{SchedulerStatus, TaskStatus, Watchdog};
+pub use write_buffer::MetricsWriteBuffer;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::time::{interval, Duration};
+use tokio::sync::watch;
+use tokio::time::{interval, interval_at, Duration, Instant, Interval};
+
+use crate::api::dashboard::DashboardCache;
+use crate::backup::BackupConfig;
+use crate::db::models::{DecisionOutcome, OptimizerDecisionRecord, PendingReallocationRecord};
+use crate::notifications::digest::{self, DigestFrequency};
+use crate::events::{EventBus, ExecutedReallocation, OrchestratorEvent, ProtocolStateChange, RaisedAlert};
+use crate::ip_intel::{check_ip, IpIntelConfig};
+use crate::network_probe::{run_probe, NetworkProbeConfig};
+use crate::notifications::{Notification, NotificationDispatcher};
+use crate::orchestration::optimizer::{DecisionExplanation, OptimizerSnapshot};
+use crate::orchestration::{AggregatedMetrics, AllocationChange};
+use crate::protocols::http::HttpClientFactory;
+use crate::shutdown::{Shutdown, ShutdownToken};
+use crate::timezone::ReportingTimezone;
+use crate::wallets::WalletTracker;
+
+/// How often the watchdog checks task liveness
+const WATCHDOG_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Cleanup task period
+const CLEANUP_INTERVAL_SECS: u64 = 86400;
+
+/// Severity of a degraded-protocol alert
+const DEGRADED_PROTOCOL_SEVERITY: f64 = 75.0;
+
+/// Severity of an uptime alert while uptime is still above the payout minimum
+const UPTIME_AT_RISK_SEVERITY: f64 = 50.0;
+
+/// Severity of an uptime alert once uptime is below the payout minimum
+const UPTIME_BREACHED_SEVERITY: f64 = 90.0;
+
+/// Severity of an alert for a single device of a multi-device protocol going offline
+const DEVICE_OFFLINE_SEVERITY: f64 = 60.0;
+
+/// Severity of a satellite score alert while the score is still above the
+/// level the satellite acts on
+const SATELLITE_SCORE_AT_RISK_SEVERITY: f64 = 85.0;
+
+/// Severity of a satellite score alert once the node is disqualified or suspended
+const SATELLITE_SCORE_BREACHED_SEVERITY: f64 = 100.0;
+
+/// Severity of a disk space alert while free space is above half the threshold
+const DISK_SPACE_LOW_SEVERITY: f64 = 70.0;
+
+/// Severity of a disk space alert once free space is below half the threshold
+const DISK_SPACE_CRITICAL_SEVERITY: f64 = 95.0;
+
+/// Severity of an alert for credentials that expire soon
+const CREDENTIAL_EXPIRING_SEVERITY: f64 = 60.0;
 
-use crate::ProtocolCoordinator;
+/// Severity of an alert for credentials that were rejected or have expired
+const CREDENTIAL_REJECTED_SEVERITY: f64 = 90.0;
 
-/// Configuration for scheduler tasks
-#[derive(Debug, Clone)]
+/// Severity of an alert for a public IP that hurts earnings
+const IP_REPUTATION_SEVERITY: f64 = 70.0;
+
+/// Severity of an alert for a public IP that changed to one that hurts earnings
+const IP_CHANGED_SEVERITY: f64 = 85.0;
+
+/// Severity of an alert for a connected protocol whose earnings stopped changing
+const EARNINGS_FLATLINE_SEVERITY: f64 = 65.0;
+use crate::{EarningsOptimizer, ProtocolCoordinator, ReallocationEngine};
+
+/// Configuration for scheduler tasks (`[scheduler]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SchedulerConfig {
+    /// Protocol polling interval in seconds (default: 60 = 1 minute)
+    pub poll_interval: u64,
     /// Optimization interval in seconds (default: 300 = 5 minutes)
     pub optimization_interval: u64,
     /// Metrics retention period in days (default: 30)
@@ -46,102 +155,564 @@ pub struct SchedulerConfig {
     pub cpu_alert_threshold: f64,
     /// Memory usage alert threshold (default: 85.0%)
     pub memory_alert_threshold: f64,
+    /// Free space (% of the filesystem) below which a storage protocol's disk alerts (default: 10.0%)
+    pub disk_free_alert_percent: f64,
+    /// Seconds a resolved alert can be reopened instead of raised anew (default: 900)
+    pub alert_cooldown_secs: u64,
+    /// Seconds between writes of queued metrics snapshots (default: 60)
+    pub metrics_flush_interval: u64,
+    /// Queued metrics snapshots that trigger a write before the next flush (default: 100)
+    pub metrics_batch_size: usize,
+    /// Days of protocol earnings history imported on first run, 0 to skip (default: 30)
+    pub backfill_days: u32,
+    /// Hours of earnings compared before and after an executed reallocation (default: 6)
+    pub canary_window_hours: u64,
+    /// Per-table retention and metrics archival (`[scheduler.retention]`)
+    pub retention: RetentionConfig,
+    /// Uptime alert thresholds (`[scheduler.uptime]`)
+    pub uptime: UptimeConfig,
+    /// Credential check schedule (`[scheduler.credentials]`)
+    pub credentials: CredentialCheckConfig,
+    /// Flatlined earnings detection (`[scheduler.flatline]`)
+    pub flatline: FlatlineConfig,
 }
 
 impl Default for SchedulerConfig {
     fn default() -> Self {
         Self {
+            poll_interval: 60,
             optimization_interval: 300,
             metrics_retention_days: 30,
             alert_processing_interval: 60,
             min_reallocation_threshold: 5.0,
             cpu_alert_threshold: 90.0,
             memory_alert_threshold: 85.0,
+            disk_free_alert_percent: 10.0,
+            alert_cooldown_secs: 900,
+            metrics_flush_interval: 60,
+            metrics_batch_size: 100,
+            backfill_days: 30,
+            canary_window_hours: 6,
+            retention: RetentionConfig::default(),
+            uptime: UptimeConfig::default(),
+            credentials: CredentialCheckConfig::default(),
+            flatline: FlatlineConfig::default(),
         }
     }
 }
 
 impl SchedulerConfig {
-    /// Load configuration from environment variables
-    pub fn from_env() -> Self {
-        Self {
-            optimization_interval: std::env::var("OPTIMIZATION_INTERVAL")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(300),
-            metrics_retention_days: std::env::var("METRICS_RETENTION_DAYS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(30),
-            alert_processing_interval: std::env::var("ALERT_PROCESSING_INTERVAL")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(60),
-            min_reallocation_threshold: std::env::var("MIN_REALLOCATION_THRESHOLD")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(5.0),
-            cpu_alert_threshold: std::env::var("CPU_ALERT_THRESHOLD")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(90.0),
-            memory_alert_threshold: std::env::var("MEMORY_ALERT_THRESHOLD")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(85.0),
+    /// Validate configuration values
+    pub fn validate(&self) -> Result<(), String> {
+        if self.poll_interval == 0 {
+            return Err("poll_interval must be at least 1 second".to_string());
+        }
+        if self.optimization_interval == 0 {
+            return Err("optimization_interval must be at least 1 second".to_string());
+        }
+        if self.alert_processing_interval == 0 {
+            return Err("alert_processing_interval must be at least 1 second".to_string());
+        }
+        if self.metrics_flush_interval == 0 {
+            return Err("metrics_flush_interval must be at least 1 second".to_string());
         }
+        if self.metrics_batch_size == 0 {
+            return Err("metrics_batch_size must be at least 1".to_string());
+        }
+        if self.metrics_retention_days < 1 {
+            return Err("metrics_retention_days must be at least 1".to_string());
+        }
+        if self.min_reallocation_threshold < 0.0 {
+            return Err("min_reallocation_threshold must not be negative".to_string());
+        }
+        if self.canary_window_hours == 0 {
+            return Err("canary_window_hours must be at least 1".to_string());
+        }
+        if !(0.0..100.0).contains(&self.disk_free_alert_percent) {
+            return Err("disk_free_alert_percent must be at least 0 and below 100".to_string());
+        }
+        if self.backfill_days > backfill::MAX_BACKFILL_DAYS {
+            return Err(format!(
+                "backfill_days must be at most {}",
+                backfill::MAX_BACKFILL_DAYS
+            ));
+        }
+        self.retention.validate()?;
+        self.uptime.validate()?;
+        self.credentials.validate()?;
+        self.flatline.validate()?;
+        Ok(())
     }
 }
 
 /// Start all background schedulers
+///
+/// Tasks read the latest value from `config` on every tick, so updates sent
+/// through the matching `watch::Sender` take effect without a restart. On
+/// shutdown each task finishes its current run and exits; `shutdown` waits
+/// for them.
+#[allow(clippy::too_many_arguments)]
 pub fn start_schedulers(
     coordinator: Arc<ProtocolCoordinator>,
+    dashboard: Arc<DashboardCache>,
+    optimizer: Arc<EarningsOptimizer>,
+    reallocation: Arc<ReallocationEngine>,
     db_pool: SqlitePool,
-    config: SchedulerConfig,
+    events: Arc<EventBus>,
+    config: watch::Receiver<SchedulerConfig>,
+    tz: ReportingTimezone,
+    status: Arc<SchedulerStatus>,
+    shutdown: &Shutdown,
 ) {
     log::info!("🕐 Starting background schedulers...");
-    log::info!("   Optimization interval: {}s", config.optimization_interval);
-    log::info!("   Alert processing interval: {}s", config.alert_processing_interval);
-    log::info!("   Metrics retention: {} days", config.metrics_retention_days);
+    let (poll_interval, optimization_interval, alert_interval, flush_interval, batch_size, credential_interval) = {
+        let config = config.borrow();
+        log::info!("   Poll interval: {}s", config.poll_interval);
+        log::info!("   Optimization interval: {}s", config.optimization_interval);
+        log::info!("   Alert processing interval: {}s", config.alert_processing_interval);
+        log::info!("   Metrics flush interval: {}s", config.metrics_flush_interval);
+        log::info!("   Metrics retention: {} days", config.metrics_retention_days);
+        log::info!("   Credential check interval: {}s", config.credentials.interval_secs);
+        (
+            config.poll_interval,
+            config.optimization_interval,
+            config.alert_processing_interval,
+            config.metrics_flush_interval,
+            config.metrics_batch_size,
+            config.credentials.interval_secs,
+        )
+    };
+    let metrics_buffer = Arc::new(
+        MetricsWriteBuffer::new(db_pool.clone(), batch_size).with_metric_schemas(coordinator.metric_schemas().clone()),
+    );
+
+    let mut watchdog = Watchdog::new(status.clone());
+    let token = shutdown.token();
+
+    // Spawn protocol polling task
+    {
+        let (coordinator, optimizer, db_pool, events, config, status, token) = (
+            coordinator.clone(),
+            optimizer.clone(),
+            db_pool.clone(),
+            events.clone(),
+            config.clone(),
+            status.clone(),
+            token.clone(),
+        );
+        watchdog.supervise("polling", poll_interval, move || {
+            polling_task(
+                coordinator.clone(),
+                events.clone(),
+                dashboard.clone(),
+                optimizer.clone(),
+                db_pool.clone(),
+                config.clone(),
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
 
     // Spawn optimization task
-    tokio::spawn(optimization_task(
-        coordinator.clone(),
-        db_pool.clone(),
-        config.clone(),
-    ));
+    {
+        let (coordinator, db_pool, events, metrics_buffer, config, status, token) = (
+            coordinator.clone(),
+            db_pool.clone(),
+            events.clone(),
+            metrics_buffer.clone(),
+            config.clone(),
+            status.clone(),
+            token.clone(),
+        );
+        watchdog.supervise("optimization", optimization_interval, move || {
+            optimization_task(
+                coordinator.clone(),
+                optimizer.clone(),
+                reallocation.clone(),
+                db_pool.clone(),
+                events.clone(),
+                metrics_buffer.clone(),
+                config.clone(),
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
+
+    // Spawn metrics flush task
+    {
+        let (config, status, token) = (config.clone(), status.clone(), token.clone());
+        watchdog.supervise("metrics_flush", flush_interval, move || {
+            metrics_flush_task(metrics_buffer.clone(), config.clone(), status.clone(), token.clone())
+        });
+    }
+
+    // Spawn credential check task
+    {
+        let (coordinator, db_pool, events, config, status, token) = (
+            coordinator.clone(),
+            db_pool.clone(),
+            events.clone(),
+            config.clone(),
+            status.clone(),
+            token.clone(),
+        );
+        watchdog.supervise("credential_check", credential_interval, move || {
+            credential_check_task(
+                coordinator.clone(),
+                db_pool.clone(),
+                events.clone(),
+                config.clone(),
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
 
     // Spawn alert processing task
-    tokio::spawn(alert_processing_task(
-        coordinator.clone(),
-        db_pool.clone(),
-        config.clone(),
-    ));
+    {
+        let (db_pool, config, status, token) =
+            (db_pool.clone(), config.clone(), status.clone(), token.clone());
+        watchdog.supervise("alert_processing", alert_interval, move || {
+            alert_processing_task(
+                coordinator.clone(),
+                db_pool.clone(),
+                events.clone(),
+                config.clone(),
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
 
     // Spawn cleanup task (runs once per day)
-    tokio::spawn(cleanup_task(db_pool.clone(), config.clone()));
+    {
+        let token = token.clone();
+        watchdog.supervise("cleanup", CLEANUP_INTERVAL_SECS, move || {
+            cleanup_task(db_pool.clone(), config.clone(), tz, status.clone(), token.clone())
+        });
+    }
 
+    shutdown.track(watchdog.start(WATCHDOG_CHECK_INTERVAL, token));
     log::info!("✅ All schedulers started successfully");
 }
 
+/// Start wallet balance polling, storing balances for `account_id`
+///
+/// Does nothing when no payout addresses are configured.
+pub fn start_wallet_tracking(
+    tracker: WalletTracker,
+    db_pool: SqlitePool,
+    account_id: i64,
+    status: Arc<SchedulerStatus>,
+    shutdown: &Shutdown,
+) {
+    if tracker.is_empty() {
+        log::info!("👛 No wallets configured, balance tracking disabled");
+        return;
+    }
+
+    log::info!(
+        "👛 Tracking {} wallet(s) every {}s",
+        tracker.config().addresses.len(),
+        tracker.config().poll_interval_secs
+    );
+
+    let poll_interval_secs = tracker.config().poll_interval_secs;
+    let tracker = Arc::new(tracker);
+    let token = shutdown.token();
+    let mut watchdog = Watchdog::new(status.clone());
+    {
+        let token = token.clone();
+        watchdog.supervise("wallet_balance", poll_interval_secs, move || {
+            wallet_balance_task(
+                tracker.clone(),
+                db_pool.clone(),
+                account_id,
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
+    shutdown.track(watchdog.start(WATCHDOG_CHECK_INTERVAL, token));
+}
+
+/// Start scheduled database backups
+pub fn start_backups(
+    config: BackupConfig,
+    db_pool: SqlitePool,
+    status: Arc<SchedulerStatus>,
+    shutdown: &Shutdown,
+) {
+    if !config.enabled {
+        log::info!("💾 Scheduled backups disabled");
+        return;
+    }
+
+    log::info!(
+        "💾 Backing up the database every {}h to {}",
+        config.interval_hours,
+        config.directory.display()
+    );
+
+    let interval_secs = config.interval_hours * 3600;
+    let config = Arc::new(config);
+    let token = shutdown.token();
+    let mut watchdog = Watchdog::new(status.clone());
+    {
+        let token = token.clone();
+        watchdog.supervise("backup", interval_secs, move || {
+            backup_task(config.clone(), db_pool.clone(), status.clone(), token.clone())
+        });
+    }
+    shutdown.track(watchdog.start(WATCHDOG_CHECK_INTERVAL, token));
+}
+
+/// Start the earnings digest for `account_id`
+///
+/// Does nothing when digests are off or no notification channel is configured.
+pub fn start_digests(
+    notifications: Arc<NotificationDispatcher>,
+    db_pool: SqlitePool,
+    account_id: i64,
+    tz: ReportingTimezone,
+    status: Arc<SchedulerStatus>,
+    shutdown: &Shutdown,
+) {
+    let config = &notifications.config().digest;
+    if config.frequency == DigestFrequency::Off {
+        log::info!("📊 Earnings digest disabled");
+        return;
+    }
+    if notifications.is_empty() {
+        log::warn!("⚠️  Earnings digest enabled but no notification channel is configured");
+        return;
+    }
+
+    log::info!(
+        "📊 Sending a {:?} earnings digest at {:02}:00 ({}) to {}",
+        config.frequency,
+        config.hour,
+        tz,
+        notifications.channels().join(", ")
+    );
+
+    let interval_secs = config.frequency.days() as u64 * 86400;
+    let token = shutdown.token();
+    let mut watchdog = Watchdog::new(status.clone());
+    {
+        let token = token.clone();
+        watchdog.supervise("digest", interval_secs, move || {
+            digest_task(
+                notifications.clone(),
+                db_pool.clone(),
+                account_id,
+                tz,
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
+    shutdown.track(watchdog.start(WATCHDOG_CHECK_INTERVAL, token));
+}
+
+/// Start public IP checks of the IP-sensitive protocols
+///
+/// Does nothing when checks are off. Each protocol's IP is looked up through
+/// its client from `http`, so a per-protocol proxy is checked as such.
+pub fn start_ip_checks(
+    config: IpIntelConfig,
+    http: HttpClientFactory,
+    coordinator: Arc<ProtocolCoordinator>,
+    db_pool: SqlitePool,
+    events: Arc<EventBus>,
+    status: Arc<SchedulerStatus>,
+    shutdown: &Shutdown,
+) {
+    if !config.enabled {
+        log::info!("🌐 Public IP checks disabled");
+        return;
+    }
+
+    log::info!("🌐 Checking the public IP of {} every {}s", config.protocols.join(", "), config.interval_secs);
+
+    let interval_secs = config.interval_secs;
+    let (config, http) = (Arc::new(config), Arc::new(http));
+    let token = shutdown.token();
+    let mut watchdog = Watchdog::new(status.clone());
+    {
+        let token = token.clone();
+        watchdog.supervise("ip_check", interval_secs, move || {
+            ip_check_task(
+                config.clone(),
+                http.clone(),
+                coordinator.clone(),
+                db_pool.clone(),
+                events.clone(),
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
+    shutdown.track(watchdog.start(WATCHDOG_CHECK_INTERVAL, token));
+}
+
+/// Start network quality probes
+///
+/// Does nothing when probes are off. Probes go through `client`, so they
+/// measure the path the shared `[http]` settings route through.
+pub fn start_network_probes(
+    config: NetworkProbeConfig,
+    client: reqwest::Client,
+    coordinator: Arc<ProtocolCoordinator>,
+    reallocation: Arc<ReallocationEngine>,
+    db_pool: SqlitePool,
+    status: Arc<SchedulerStatus>,
+    shutdown: &Shutdown,
+) {
+    if !config.enabled {
+        log::info!("📶 Network probes disabled");
+        return;
+    }
+
+    log::info!("📶 Probing network quality every {}s", config.interval_secs);
+
+    let interval_secs = config.interval_secs;
+    let config = Arc::new(config);
+    let token = shutdown.token();
+    let mut watchdog = Watchdog::new(status.clone());
+    {
+        let token = token.clone();
+        watchdog.supervise("network_probe", interval_secs, move || {
+            network_probe_task(
+                config.clone(),
+                client.clone(),
+                coordinator.clone(),
+                reallocation.clone(),
+                db_pool.clone(),
+                status.clone(),
+                token.clone(),
+            )
+        });
+    }
+    shutdown.track(watchdog.start(WATCHDOG_CHECK_INTERVAL, token));
+}
+
+/// Protocol polling task
+///
+/// Runs every N seconds to poll every adapter through the coordinator, which
+/// records the aggregated metrics in its history, refreshes the dashboard
+/// snapshot and publishes `MetricsCollected` (plus `ProtocolStateChanged`
+/// for protocols that connected or disconnected since the previous poll).
+/// Each protocol's connected state is recorded to refresh its rolling
+/// uptime. The other tasks work from these metrics.
+#[allow(clippy::too_many_arguments)]
+async fn polling_task(
+    coordinator: Arc<ProtocolCoordinator>,
+    events: Arc<EventBus>,
+    dashboard: Arc<DashboardCache>,
+    optimizer: Arc<EarningsOptimizer>,
+    db_pool: SqlitePool,
+    mut config_rx: watch::Receiver<SchedulerConfig>,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "polling";
+    let mut interval = interval(Duration::from_secs(config_rx.borrow().poll_interval));
+    let mut previous_status = coordinator
+        .get_current_metrics()
+        .await
+        .ok()
+        .flatten()
+        .map(|metrics| metrics.connection_status.clone())
+        .unwrap_or_default();
+
+    log::info!("📡 Protocol polling task started");
+
+    loop {
+        tokio::select! {
+            _ = next_tick(&mut interval, &mut config_rx, |c| c.poll_interval, &status, TASK) => {}
+            _ = shutdown.cancelled() => break,
+        }
+        let poll_interval = config_rx.borrow().poll_interval;
+        status.heartbeat(TASK, poll_interval);
+
+        match coordinator.poll_all().await {
+            Ok(metrics) => {
+                log::debug!(
+                    "📡 Polled {} protocols: ${:.2}/hour",
+                    metrics.connection_status.len(),
+                    metrics.total_earnings_per_hour
+                );
+                let account_id = coordinator.account_id();
+                match record_connectivity(&db_pool, account_id, &metrics.connection_status, Utc::now(), poll_interval)
+                    .await
+                {
+                    Ok(uptime) => coordinator.set_uptime(uptime),
+                    Err(e) => {
+                        log::error!("❌ Failed to record protocol uptime: {}", e);
+                        status.record_error(TASK, &e);
+                    }
+                }
+                dashboard.refresh(&metrics, &optimizer, &coordinator.uptime());
+                for (protocol, connected) in &metrics.connection_status {
+                    if previous_status.get(protocol) != Some(connected) {
+                        events.publish(OrchestratorEvent::ProtocolStateChanged(ProtocolStateChange {
+                            protocol: protocol.clone(),
+                            connected: *connected,
+                            timestamp: metrics.timestamp,
+                        }));
+                    }
+                }
+                previous_status = metrics.connection_status.clone();
+                events.publish(OrchestratorEvent::MetricsCollected(metrics));
+            }
+            Err(e) => {
+                log::error!("❌ Failed to poll protocols: {}", e);
+                status.record_error(TASK, &e);
+            }
+        }
+    }
+}
+
 /// Periodic optimization task
 ///
 /// Runs every N seconds to:
 /// 1. Collect current metrics
-/// 2. Analyze optimization opportunities
-/// 3. Execute automatic reallocations if threshold met
+/// 2. Refresh each protocol's earnings risk (hourly)
+/// 3. Analyze optimization opportunities
+/// 4. Execute automatic reallocations if threshold met, or queue them for
+///    approval when confirmation is required
+///
+/// Metrics are queued in `metrics_buffer`, which is flushed once more when
+/// the task stops.
+#[allow(clippy::too_many_arguments)]
 async fn optimization_task(
     coordinator: Arc<ProtocolCoordinator>,
+    optimizer: Arc<EarningsOptimizer>,
+    reallocation: Arc<ReallocationEngine>,
     db_pool: SqlitePool,
-    config: SchedulerConfig,
+    events: Arc<EventBus>,
+    metrics_buffer: Arc<MetricsWriteBuffer>,
+    mut config_rx: watch::Receiver<SchedulerConfig>,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
 ) {
-    let mut interval = interval(Duration::from_secs(config.optimization_interval));
+    const TASK: &str = "optimization";
+    let mut interval = interval(Duration::from_secs(config_rx.borrow().optimization_interval));
     let mut run_count = 0u64;
+    let mut risk_refreshed_at: Option<chrono::DateTime<Utc>> = None;
 
     log::info!("🔄 Optimization task started");
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = next_tick(&mut interval, &mut config_rx, |c| c.optimization_interval, &status, TASK) => {}
+            _ = shutdown.cancelled() => break,
+        }
+        let config = config_rx.borrow().clone();
+        status.heartbeat(TASK, config.optimization_interval);
         run_count += 1;
 
         log::debug!("🔄 Running optimization task (run #{})", run_count);
@@ -155,40 +726,255 @@ async fn optimization_task(
             }
             Err(e) => {
                 log::error!("Failed to get metrics: {}", e);
+                status.record_error(TASK, &e);
                 continue;
             }
         };
 
-        // Store metrics to database
-        if let Err(e) = store_metrics_to_db(&db_pool, &metrics).await {
+        // Queue metrics for the next batched write
+        if let Err(e) = metrics_buffer.push(coordinator.account_id(), Utc::now(), &metrics).await {
             log::error!("❌ Failed to store metrics: {}", e);
+            status.record_error(TASK, &e);
             continue;
         }
 
-        log::debug!("✅ Metrics collected and stored successfully");
+        log::debug!("✅ Metrics collected and queued successfully");
 
-        // TODO: Implement optimization logic with EarningsOptimizer
-        // Will be added in a follow-up commit
+        optimizer.update_metrics(metrics.clone());
+
+        // Earnings volatility moves slowly; refresh it hourly
+        if risk_refreshed_at.is_none_or(|at| Utc::now() - at >= chrono::Duration::hours(1)) {
+            match risk::refresh_risk(&db_pool, coordinator.account_id(), Utc::now()).await {
+                Ok(risks) => {
+                    optimizer.set_risk_scores(risks.into_iter().map(|(p, risk)| (p, risk.risk_score)).collect());
+                    risk_refreshed_at = Some(Utc::now());
+                }
+                Err(e) => {
+                    log::error!("❌ Failed to refresh protocol risk: {}", e);
+                    status.record_error(TASK, &e);
+                }
+            }
+        }
+
+        // Learn from reallocations whose canary window has passed
+        let window = chrono::Duration::hours(config.canary_window_hours as i64);
+        match canary::evaluate_reallocations(&db_pool, coordinator.account_id(), window, Utc::now()).await {
+            Ok(evaluated) if evaluated > 0 || run_count == 1 => {
+                log::debug!("🐤 Evaluated {} reallocation outcome(s)", evaluated);
+                match canary::prediction_errors(&db_pool, coordinator.account_id()).await {
+                    Ok(errors) => optimizer.set_prediction_errors(errors),
+                    Err(e) => log::error!("❌ Failed to load prediction errors: {}", e),
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("❌ Failed to evaluate reallocation outcomes: {}", e);
+                status.record_error(TASK, &e);
+            }
+        }
+
+        // Only propose plans that respect the reallocation floors, caps and pins
+        let metrics = reallocation.config().constrained_metrics(&metrics);
+
+        let (plan, explanation, blocker) = {
+            let optimizer = optimizer.snapshot();
+
+            let candidates = optimizer.evaluate_candidates(&metrics);
+            let opportunities = OptimizerSnapshot::opportunities(&candidates);
+            let mut plan = match optimizer.calculate_optimal_allocation(&metrics) {
+                Ok(p) => p,
+                Err(e) => {
+                    log::error!("❌ Failed to calculate allocation: {}", e);
+                    status.record_error(TASK, &e);
+                    continue;
+                }
+            };
+            reallocation.price_plan(&mut plan, &metrics);
+
+            let blocker = optimizer.reallocation_blocker(&opportunities, Some(&plan));
+            (plan.clone(), optimizer.explain(&metrics, candidates, plan), blocker)
+        };
+
+        let improvement_percent = if metrics.total_earnings_per_hour > 0.0 {
+            plan.net_benefit / metrics.total_earnings_per_hour * 100.0
+        } else {
+            0.0
+        };
+        let blocker = blocker.or_else(|| {
+            (improvement_percent < config.min_reallocation_threshold).then(|| {
+                format!(
+                    "Net benefit is {:.2}% of earnings, below min_reallocation_threshold {}%",
+                    improvement_percent, config.min_reallocation_threshold
+                )
+            })
+        });
+        if let Some(reason) = blocker {
+            // Ticks without a single qualifying move are not worth a record
+            if explanation.candidates.iter().any(|c| c.opportunity.is_some()) {
+                let outcome = DecisionOutcome::Rejected;
+                record_decision(&db_pool, coordinator.account_id(), &explanation, outcome, Some(reason), None).await;
+            }
+            continue;
+        }
+
+        if reallocation.requires_confirmation() {
+            let queued = match PendingReallocationRecord::new(&plan) {
+                Ok(record) => crate::db::queries::store_pending_reallocation(
+                    &db_pool,
+                    coordinator.account_id(),
+                    &record,
+                )
+                .await
+                .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match queued {
+                Ok(id) => {
+                    log::info!("📝 Reallocation #{} awaiting approval (+${:.2}/hour)", id, plan.net_benefit);
+                    let outcome = DecisionOutcome::PendingApproval;
+                    record_decision(&db_pool, coordinator.account_id(), &explanation, outcome, None, Some(id)).await;
+                }
+                Err(e) => {
+                    log::error!("❌ Failed to queue reallocation: {}", e);
+                    status.record_error(TASK, &e);
+                }
+            }
+            continue;
+        }
+
+        let outcome = DecisionOutcome::Executing;
+        let decision_id = record_decision(&db_pool, coordinator.account_id(), &explanation, outcome, None, None).await;
+
+        // A staged rollout spans several step intervals, so it runs on its
+        // own task to keep this one ticking for the watchdog
+        let rollout = {
+            let (coordinator, reallocation) = (coordinator.clone(), reallocation.clone());
+            let (db_pool, events, status) = (db_pool.clone(), events.clone(), status.clone());
+            async move {
+                let reason = "Scheduled optimization";
+                let executed =
+                    execute_plan(&coordinator, &reallocation, &db_pool, &events, &plan, reason, decision_id).await;
+                let (outcome, error) = match executed {
+                    Ok(_) => {
+                        log::info!("✅ Reallocation executed (+${:.2}/hour)", plan.net_benefit);
+                        (DecisionOutcome::Executed, None)
+                    }
+                    Err(e) => {
+                        log::error!("❌ Reallocation failed: {}", e);
+                        status.record_error(TASK, &e);
+                        (DecisionOutcome::Failed, Some(e.to_string()))
+                    }
+                };
+                if let Some(id) = decision_id {
+                    let account_id = coordinator.account_id();
+                    let result = crate::db::queries::set_optimizer_decision_outcome(
+                        &db_pool,
+                        account_id,
+                        id,
+                        outcome,
+                        error.as_deref(),
+                    );
+                    if let Err(e) = result.await {
+                        log::error!("❌ Failed to update optimizer decision #{}: {}", id, e);
+                    }
+                }
+            }
+        };
+        if reallocation.is_staged() {
+            tokio::spawn(rollout);
+        } else {
+            rollout.await;
+        }
+    }
+
+    // Snapshots queued after the flush task stopped
+    if let Err(e) = metrics_buffer.flush().await {
+        log::error!("❌ Failed to flush queued metrics: {}", e);
     }
 }
 
+/// Execute an allocation plan, record the resulting changes and publish
+/// `ReallocationExecuted`
+///
+/// Used by the optimization task, by approved pending reallocations and by
+/// `POST /api/v1/reallocate`. Returns the changes that were applied.
+pub async fn execute_plan(
+    coordinator: &ProtocolCoordinator,
+    reallocation: &ReallocationEngine,
+    db_pool: &SqlitePool,
+    events: &EventBus,
+    plan: &crate::AllocationPlan,
+    reason: &str,
+    decision_id: Option<i64>,
+) -> crate::OrchestrationResult<Vec<AllocationChange>> {
+    let started = Utc::now();
+
+    reallocation
+        .execute_reallocation(plan, coordinator.adapters())
+        .await?;
+
+    let changes: Vec<_> = reallocation
+        .get_reallocation_history()
+        .await
+        .into_iter()
+        .filter(|c| c.timestamp >= started)
+        .collect();
+    for change in &changes {
+        if let Err(e) = store_reallocation_to_db(
+            db_pool,
+            coordinator.account_id(),
+            &change.protocol,
+            change.old_allocation,
+            change.new_allocation,
+            Some(change.earnings_impact),
+            Some(reason),
+            decision_id,
+        )
+        .await
+        {
+            log::error!("❌ Failed to store reallocation: {}", e);
+        }
+    }
+
+    if !changes.is_empty() {
+        let executed = ExecutedReallocation { changes: changes.clone(), decision_id };
+        events.publish(OrchestratorEvent::ReallocationExecuted(executed));
+    }
+
+    Ok(changes)
+}
+
 /// Alert processing task
 ///
 /// Runs every minute to:
 /// 1. Check resource thresholds (CPU, memory, bandwidth)
 /// 2. Generate alerts for anomalies
-/// 3. Store alerts to database
+/// 3. Store alerts to database, deduplicated by fingerprint, and resolve
+///    alerts whose condition cleared
+/// 4. Warn about protocols close to losing payouts (uptime, Storj satellite
+///    scores), devices that went offline and protocols whose earnings
+///    flatlined
+/// 5. Send newly raised alerts to the configured chat webhooks
 async fn alert_processing_task(
     coordinator: Arc<ProtocolCoordinator>,
     db_pool: SqlitePool,
-    config: SchedulerConfig,
+    events: Arc<EventBus>,
+    mut config_rx: watch::Receiver<SchedulerConfig>,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
 ) {
-    let mut interval = interval(Duration::from_secs(config.alert_processing_interval));
+    const TASK: &str = "alert_processing";
+    let mut interval = interval(Duration::from_secs(config_rx.borrow().alert_processing_interval));
 
     log::info!("🚨 Alert processing task started");
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = next_tick(&mut interval, &mut config_rx, |c| c.alert_processing_interval, &status, TASK) => {}
+            _ = shutdown.cancelled() => break,
+        }
+        let config = config_rx.borrow().clone();
+        status.heartbeat(TASK, config.alert_processing_interval);
 
         // Get current metrics (ProtocolCoordinator is already thread-safe)
         let metrics = match coordinator.get_current_metrics().await {
@@ -198,211 +984,1131 @@ async fn alert_processing_task(
             }
             Err(e) => {
                 log::error!("❌ Failed to get metrics for alerts: {}", e);
+                status.record_error(TASK, &e);
                 continue;
             }
         };
 
         // Check CPU threshold
-        if metrics.resource_utilization.cpu_percent > config.cpu_alert_threshold {
-            let severity = calculate_severity(
-                metrics.resource_utilization.cpu_percent,
-                config.cpu_alert_threshold,
-            );
+        process_threshold_alert(
+            &db_pool,
+            coordinator.account_id(),
+            &config,
+            &events,
+            &metrics,
+            "resource:cpu",
+            "HIGH_CPU_USAGE",
+            "CPU",
+            metrics.resource_utilization.cpu_percent,
+            config.cpu_alert_threshold,
+        )
+        .await;
 
-            if let Err(e) = store_alert_to_db(
-                &db_pool,
-                "HIGH_CPU_USAGE",
-                severity,
-                &format!(
-                    "CPU usage at {:.1}% (threshold: {:.1}%)",
-                    metrics.resource_utilization.cpu_percent, config.cpu_alert_threshold
+        // Check memory threshold
+        process_threshold_alert(
+            &db_pool,
+            coordinator.account_id(),
+            &config,
+            &events,
+            &metrics,
+            "resource:memory",
+            "HIGH_MEMORY_USAGE",
+            "Memory",
+            metrics.resource_utilization.memory_percent,
+            config.memory_alert_threshold,
+        )
+        .await;
+
+        process_degraded_protocols(&db_pool, &config, &events, &metrics, &coordinator)
+            .await;
+        process_uptime_alerts(&db_pool, &config, &events, &metrics, &coordinator).await;
+        process_satellite_score_alerts(&db_pool, &config, &events, &metrics, &coordinator)
+            .await;
+        process_device_alerts(&db_pool, &config, &events, &metrics, &coordinator).await;
+        process_disk_space_alerts(&db_pool, &config, &events, &metrics, &coordinator).await;
+        process_flatline_alerts(&db_pool, &config, &events, &coordinator).await;
+    }
+}
+
+/// Credential check task
+///
+/// Validates every adapter's credentials at startup and then every
+/// `credentials.interval_secs`, independent of polling
+async fn credential_check_task(
+    coordinator: Arc<ProtocolCoordinator>,
+    db_pool: SqlitePool,
+    events: Arc<EventBus>,
+    mut config_rx: watch::Receiver<SchedulerConfig>,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "credential_check";
+    let mut interval = interval(Duration::from_secs(config_rx.borrow().credentials.interval_secs));
+
+    log::info!("🔑 Credential check task started");
+
+    loop {
+        tokio::select! {
+            _ = next_tick(&mut interval, &mut config_rx, |c| c.credentials.interval_secs, &status, TASK) => {}
+            _ = shutdown.cancelled() => break,
+        }
+        let config = config_rx.borrow().clone();
+        status.heartbeat(TASK, config.credentials.interval_secs);
+
+        process_credential_alerts(&db_pool, &config, &events, &coordinator).await;
+    }
+}
+
+/// Raise an alert for each protocol whose credentials were rejected or
+/// expire soon, and resolve it once they check out again
+///
+/// Checks that fail for other reasons (network errors, timeouts) leave
+/// existing alerts as they are.
+async fn process_credential_alerts(
+    db_pool: &SqlitePool,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    coordinator: &ProtocolCoordinator,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+
+    let account_id = coordinator.account_id();
+    let metrics = coordinator.get_current_metrics().await.ok().flatten();
+    let now = Utc::now();
+    for (protocol, result) in coordinator.check_credentials().await {
+        let rejected_fingerprint = format!("credential_rejected:{}", protocol);
+        let expiring_fingerprint = format!("credential_expiring:{}", protocol);
+
+        let (alert_type, severity, message, fingerprint, cleared) =
+            match CredentialHealth::from_check(&result, &config.credentials, now) {
+                CredentialHealth::Unknown(e) => {
+                    log::warn!("⚠️  Could not check {} credentials: {}", protocol, e);
+                    continue;
+                }
+                CredentialHealth::Valid => {
+                    for fingerprint in [&rejected_fingerprint, &expiring_fingerprint] {
+                        match resolve_alert_condition(db_pool, account_id, fingerprint).await {
+                            Ok(true) => log::info!("✅ {} credentials accepted, alert resolved", protocol),
+                            Ok(false) => {}
+                            Err(e) => log::error!("❌ Failed to resolve {} credential alert: {}", protocol, e),
+                        }
+                    }
+                    continue;
+                }
+                CredentialHealth::Expiring(at) => (
+                    "CREDENTIAL_EXPIRING",
+                    CREDENTIAL_EXPIRING_SEVERITY,
+                    format!("{} credentials expire at {}; renew them to keep earning", protocol, at.to_rfc3339()),
+                    expiring_fingerprint,
+                    rejected_fingerprint,
+                ),
+                CredentialHealth::Rejected(reason) => (
+                    "CREDENTIAL_REJECTED",
+                    CREDENTIAL_REJECTED_SEVERITY,
+                    format!("{} credentials are not accepted ({}); earnings have stopped", protocol, reason),
+                    rejected_fingerprint,
+                    expiring_fingerprint,
                 ),
+            };
+
+        if let Err(e) = resolve_alert_condition(db_pool, account_id, &cleared).await {
+            log::error!("❌ Failed to resolve {} credential alert: {}", protocol, e);
+        }
+        match record_alert_condition(
+            db_pool,
+            account_id,
+            &fingerprint,
+            Some(&protocol),
+            alert_type,
+            severity,
+            &message,
+            chrono::Duration::seconds(config.alert_cooldown_secs as i64),
+        )
+        .await
+        {
+            Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+                log::warn!("🚨 CREDENTIAL ALERT: {}", message);
+                let notification = Notification {
+                    alert_type: alert_type.to_string(),
+                    protocol: Some(protocol.clone()),
+                    severity,
+                    message,
+                    earnings: metrics.as_ref().and_then(|m| m.earnings_by_protocol.get(&protocol).copied()),
+                    earnings_delta: None,
+                };
+                publish_alert(events, id, notification);
+            }
+            Ok(AlertOutcome::Repeated(_)) => {
+                log::debug!("{} credentials still failing checks", protocol);
+            }
+            Err(e) => log::error!("❌ Failed to store {} credential alert: {}", protocol, e),
+        }
+    }
+}
+
+/// Raise an alert for each protocol whose circuit breaker is open, and
+/// resolve it once the protocol recovers
+async fn process_degraded_protocols(
+    db_pool: &SqlitePool,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    metrics: &AggregatedMetrics,
+    coordinator: &ProtocolCoordinator,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+
+    let account_id = coordinator.account_id();
+    let degraded = coordinator.degraded_protocols();
+    for protocol in coordinator.registered_protocols() {
+        let fingerprint = format!("protocol_degraded:{}", protocol);
+
+        if !degraded.contains(&protocol) {
+            match resolve_alert_condition(db_pool, account_id, &fingerprint).await {
+                Ok(true) => log::info!("✅ {} recovered, degraded alert resolved", protocol),
+                Ok(false) => {}
+                Err(e) => log::error!("❌ Failed to resolve {} degraded alert: {}", protocol, e),
+            }
+            continue;
+        }
+
+        let message = format!(
+            "{} is failing repeatedly and is skipped by polling until it recovers",
+            protocol
+        );
+        match record_alert_condition(
+            db_pool,
+            account_id,
+            &fingerprint,
+            Some(&protocol),
+            "PROTOCOL_DEGRADED",
+            DEGRADED_PROTOCOL_SEVERITY,
+            &message,
+            chrono::Duration::seconds(config.alert_cooldown_secs as i64),
+        )
+        .await
+        {
+            Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+                log::warn!("🚨 PROTOCOL DEGRADED ALERT: {}", protocol);
+                let notification = Notification {
+                    alert_type: "PROTOCOL_DEGRADED".to_string(),
+                    protocol: Some(protocol.clone()),
+                    severity: DEGRADED_PROTOCOL_SEVERITY,
+                    message,
+                    earnings: metrics.earnings_by_protocol.get(&protocol).copied(),
+                    earnings_delta: None,
+                };
+                publish_alert(events, id, notification);
+            }
+            Ok(AlertOutcome::Repeated(_)) => {
+                log::debug!("{} still degraded", protocol);
+            }
+            Err(e) => log::error!("❌ Failed to store {} degraded alert: {}", protocol, e),
+        }
+    }
+}
+
+/// Raise an alert for each protocol whose 30-day uptime is within the
+/// warning margin of, or below, its payout minimum, and resolve it once
+/// uptime recovers
+async fn process_uptime_alerts(
+    db_pool: &SqlitePool,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    metrics: &AggregatedMetrics,
+    coordinator: &ProtocolCoordinator,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+
+    let account_id = coordinator.account_id();
+    for (protocol, uptime) in coordinator.uptime() {
+        let Some(percent) = uptime.last_30d else {
+            continue;
+        };
+        let fingerprint = format!("uptime_at_risk:{}", protocol);
+        let min_percent = config.uptime.min_percent_for(&protocol);
+
+        if percent >= min_percent + config.uptime.warning_margin {
+            match resolve_alert_condition(db_pool, account_id, &fingerprint).await {
+                Ok(true) => log::info!("✅ {} uptime back to {:.1}%, alert resolved", protocol, percent),
+                Ok(false) => {}
+                Err(e) => log::error!("❌ Failed to resolve {} uptime alert: {}", protocol, e),
+            }
+            continue;
+        }
+
+        let (severity, message) = if percent < min_percent {
+            (
+                UPTIME_BREACHED_SEVERITY,
+                format!("{} 30-day uptime {:.1}% is below the {:.1}% payout minimum", protocol, percent, min_percent),
+            )
+        } else {
+            (
+                UPTIME_AT_RISK_SEVERITY,
+                format!("{} 30-day uptime {:.1}% is close to the {:.1}% payout minimum", protocol, percent, min_percent),
+            )
+        };
+        match record_alert_condition(
+            db_pool,
+            account_id,
+            &fingerprint,
+            Some(&protocol),
+            "UPTIME_AT_RISK",
+            severity,
+            &message,
+            chrono::Duration::seconds(config.alert_cooldown_secs as i64),
+        )
+        .await
+        {
+            Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+                log::warn!("🚨 UPTIME ALERT: {}", message);
+                let notification = Notification {
+                    alert_type: "UPTIME_AT_RISK".to_string(),
+                    protocol: Some(protocol.clone()),
+                    severity,
+                    message,
+                    earnings: metrics.earnings_by_protocol.get(&protocol).copied(),
+                    earnings_delta: None,
+                };
+                publish_alert(events, id, notification);
+            }
+            Ok(AlertOutcome::Repeated(_)) => {
+                log::debug!("{} uptime still at {:.1}%", protocol, percent);
+            }
+            Err(e) => log::error!("❌ Failed to store {} uptime alert: {}", protocol, e),
+        }
+    }
+}
+
+/// Raise an alert for each device of a multi-device protocol (e.g. Grass
+/// devices on separate IPs) that is offline, and resolve it once the device
+/// reconnects
+async fn process_device_alerts(
+    db_pool: &SqlitePool,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    metrics: &AggregatedMetrics,
+    coordinator: &ProtocolCoordinator,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+    use crate::protocols::device_connectivity;
+
+    let account_id = coordinator.account_id();
+    for (protocol, details) in &metrics.protocol_details {
+        for (device, connected) in device_connectivity(details) {
+            let fingerprint = format!("device_offline:{}:{}", protocol, device);
+
+            if connected {
+                match resolve_alert_condition(db_pool, account_id, &fingerprint).await {
+                    Ok(true) => log::info!("✅ {} device {} back online, alert resolved", protocol, device),
+                    Ok(false) => {}
+                    Err(e) => log::error!("❌ Failed to resolve {} device alert: {}", protocol, e),
+                }
+                continue;
+            }
+
+            let message = format!("{} device {} is offline", protocol, device);
+            match record_alert_condition(
+                db_pool,
+                account_id,
+                &fingerprint,
+                Some(protocol),
+                "DEVICE_OFFLINE",
+                DEVICE_OFFLINE_SEVERITY,
+                &message,
+                chrono::Duration::seconds(config.alert_cooldown_secs as i64),
             )
             .await
             {
-                log::error!("❌ Failed to store CPU alert: {}", e);
-            } else {
-                log::warn!(
-                    "🚨 HIGH CPU USAGE ALERT: {:.1}% (severity: {:.1})",
-                    metrics.resource_utilization.cpu_percent,
-                    severity
-                );
+                Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+                    log::warn!("🚨 DEVICE ALERT: {}", message);
+                    let notification = Notification {
+                        alert_type: "DEVICE_OFFLINE".to_string(),
+                        protocol: Some(protocol.clone()),
+                        severity: DEVICE_OFFLINE_SEVERITY,
+                        message,
+                        earnings: metrics.earnings_by_protocol.get(protocol).copied(),
+                        earnings_delta: None,
+                    };
+                    publish_alert(events, id, notification);
+                }
+                Ok(AlertOutcome::Repeated(_)) => {
+                    log::debug!("{} device {} still offline", protocol, device);
+                }
+                Err(e) => log::error!("❌ Failed to store {} device alert: {}", protocol, e),
             }
         }
+    }
+}
 
-        // Check memory threshold
-        if metrics.resource_utilization.memory_percent > config.memory_alert_threshold {
-            let severity = calculate_severity(
-                metrics.resource_utilization.memory_percent,
-                config.memory_alert_threshold,
-            );
+/// Raise an alert for each connected protocol whose earnings have not changed
+/// over the last `flatline.polls` polls, and resolve it once they move
+async fn process_flatline_alerts(
+    db_pool: &SqlitePool,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    coordinator: &ProtocolCoordinator,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+
+    let account_id = coordinator.account_id();
+    let history = coordinator.get_metrics_history().await;
+    for (protocol, (earnings, flatlined)) in flatlined_protocols(&history, &config.flatline) {
+        let fingerprint = format!("earnings_flatline:{}", protocol);
+
+        if !flatlined {
+            match resolve_alert_condition(db_pool, account_id, &fingerprint).await {
+                Ok(true) => log::info!("✅ {} earnings changing again, alert resolved", protocol),
+                Ok(false) => {}
+                Err(e) => log::error!("❌ Failed to resolve {} flatline alert: {}", protocol, e),
+            }
+            continue;
+        }
+
+        let message = format!(
+            "{} has reported ${:.4}/hour for {} consecutive polls while connected; its API may be serving \
+             stuck data or the node may have stopped working",
+            protocol, earnings, config.flatline.polls
+        );
+        match record_alert_condition(
+            db_pool,
+            account_id,
+            &fingerprint,
+            Some(&protocol),
+            "EARNINGS_FLATLINE",
+            EARNINGS_FLATLINE_SEVERITY,
+            &message,
+            chrono::Duration::seconds(config.alert_cooldown_secs as i64),
+        )
+        .await
+        {
+            Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+                log::warn!("🚨 FLATLINE ALERT: {}", message);
+                let notification = Notification {
+                    alert_type: "EARNINGS_FLATLINE".to_string(),
+                    protocol: Some(protocol.clone()),
+                    severity: EARNINGS_FLATLINE_SEVERITY,
+                    message,
+                    earnings: Some(earnings),
+                    earnings_delta: None,
+                };
+                publish_alert(events, id, notification);
+            }
+            Ok(AlertOutcome::Repeated(_)) => log::debug!("{} earnings still flatlined", protocol),
+            Err(e) => log::error!("❌ Failed to store {} flatline alert: {}", protocol, e),
+        }
+    }
+}
+
+/// Raise an alert for each storage protocol whose disk is running out of free
+/// space, before the node fills it and gets disqualified, and resolve it once
+/// space is freed
+async fn process_disk_space_alerts(
+    db_pool: &SqlitePool,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    metrics: &AggregatedMetrics,
+    coordinator: &ProtocolCoordinator,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+    use crate::disk_quotas::disk_free_space;
+
+    let account_id = coordinator.account_id();
+    let threshold = config.disk_free_alert_percent;
+    for (protocol, details) in &metrics.protocol_details {
+        let Some((free_percent, free_gb)) = disk_free_space(details) else {
+            continue;
+        };
+        let fingerprint = format!("disk_space_low:{}", protocol);
+
+        if free_percent >= threshold {
+            match resolve_alert_condition(db_pool, account_id, &fingerprint).await {
+                Ok(true) => log::info!("✅ {} disk back to {:.1}% free, alert resolved", protocol, free_percent),
+                Ok(false) => {}
+                Err(e) => log::error!("❌ Failed to resolve {} disk alert: {}", protocol, e),
+            }
+            continue;
+        }
+
+        let severity = if free_percent < threshold / 2.0 {
+            DISK_SPACE_CRITICAL_SEVERITY
+        } else {
+            DISK_SPACE_LOW_SEVERITY
+        };
+        let message = format!(
+            "{} disk has {:.1} GB ({:.1}%) free, below the {:.1}% alert threshold",
+            protocol, free_gb, free_percent, threshold
+        );
+        match record_alert_condition(
+            db_pool,
+            account_id,
+            &fingerprint,
+            Some(protocol),
+            "DISK_SPACE_LOW",
+            severity,
+            &message,
+            chrono::Duration::seconds(config.alert_cooldown_secs as i64),
+        )
+        .await
+        {
+            Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+                log::warn!("🚨 DISK SPACE ALERT: {}", message);
+                let notification = Notification {
+                    alert_type: "DISK_SPACE_LOW".to_string(),
+                    protocol: Some(protocol.clone()),
+                    severity,
+                    message,
+                    earnings: metrics.earnings_by_protocol.get(protocol).copied(),
+                    earnings_delta: None,
+                };
+                publish_alert(events, id, notification);
+            }
+            Ok(AlertOutcome::Repeated(_)) => {
+                log::debug!("{} disk still at {:.1}% free", protocol, free_percent);
+            }
+            Err(e) => log::error!("❌ Failed to store {} disk alert: {}", protocol, e),
+        }
+    }
+}
+
+/// Raise an alert for each satellite score (Storj audit, suspension, and
+/// online scores) close to or past the level that gets the node disqualified
+/// or suspended, and resolve it once the score recovers
+async fn process_satellite_score_alerts(
+    db_pool: &SqlitePool,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    metrics: &AggregatedMetrics,
+    coordinator: &ProtocolCoordinator,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+    use crate::protocols::storj::satellite_scores;
+
+    let account_id = coordinator.account_id();
+    for (protocol, details) in &metrics.protocol_details {
+        for score in satellite_scores(details) {
+            let threshold = &score.threshold;
+            let fingerprint = format!("satellite_score:{}:{}:{}", protocol, score.satellite, threshold.metric);
 
-            if let Err(e) = store_alert_to_db(
-                &db_pool,
-                "HIGH_MEMORY_USAGE",
+            if !score.at_risk() {
+                match resolve_alert_condition(db_pool, account_id, &fingerprint).await {
+                    Ok(true) => log::info!(
+                        "✅ {} {} on {} back to {:.1}%, alert resolved",
+                        protocol,
+                        threshold.label,
+                        score.satellite,
+                        score.value * 100.0
+                    ),
+                    Ok(false) => {}
+                    Err(e) => log::error!("❌ Failed to resolve {} {} alert: {}", protocol, threshold.label, e),
+                }
+                continue;
+            }
+
+            let (severity, message) = if score.breached() {
+                (
+                    SATELLITE_SCORE_BREACHED_SEVERITY,
+                    format!(
+                        "{} {} on {} is {:.1}%, below the {:.0}% {} threshold",
+                        protocol,
+                        threshold.label,
+                        score.satellite,
+                        score.value * 100.0,
+                        threshold.limit * 100.0,
+                        threshold.consequence
+                    ),
+                )
+            } else {
+                (
+                    SATELLITE_SCORE_AT_RISK_SEVERITY,
+                    format!(
+                        "{} {} on {} is {:.1}%, close to the {:.0}% {} threshold",
+                        protocol,
+                        threshold.label,
+                        score.satellite,
+                        score.value * 100.0,
+                        threshold.limit * 100.0,
+                        threshold.consequence
+                    ),
+                )
+            };
+            match record_alert_condition(
+                db_pool,
+                account_id,
+                &fingerprint,
+                Some(protocol),
+                "SATELLITE_SCORE_AT_RISK",
                 severity,
-                &format!(
-                    "Memory usage at {:.1}% (threshold: {:.1}%)",
-                    metrics.resource_utilization.memory_percent, config.memory_alert_threshold
-                ),
+                &message,
+                chrono::Duration::seconds(config.alert_cooldown_secs as i64),
             )
             .await
             {
-                log::error!("❌ Failed to store memory alert: {}", e);
-            } else {
-                log::warn!(
-                    "🚨 HIGH MEMORY USAGE ALERT: {:.1}% (severity: {:.1})",
-                    metrics.resource_utilization.memory_percent,
-                    severity
-                );
+                Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+                    log::warn!("🚨 SATELLITE SCORE ALERT: {}", message);
+                    let notification = Notification {
+                        alert_type: "SATELLITE_SCORE_AT_RISK".to_string(),
+                        protocol: Some(protocol.clone()),
+                        severity,
+                        message,
+                        earnings: metrics.earnings_by_protocol.get(protocol).copied(),
+                        earnings_delta: None,
+                    };
+                    publish_alert(events, id, notification);
+                }
+                Ok(AlertOutcome::Repeated(_)) => {
+                    log::debug!("{} {} on {} still at {:.3}", protocol, threshold.label, score.satellite, score.value);
+                }
+                Err(e) => log::error!("❌ Failed to store {} {} alert: {}", protocol, threshold.label, e),
             }
         }
     }
 }
 
+/// Raise, repeat, or resolve a threshold alert identified by `fingerprint`
+///
+/// Only newly raised or reopened alerts are logged as warnings and sent to
+/// notification channels; repeats of an open alert just bump its occurrence
+/// count.
+#[allow(clippy::too_many_arguments)]
+async fn process_threshold_alert(
+    db_pool: &SqlitePool,
+    account_id: i64,
+    config: &SchedulerConfig,
+    events: &EventBus,
+    metrics: &AggregatedMetrics,
+    fingerprint: &str,
+    alert_type: &str,
+    resource: &str,
+    value: f64,
+    threshold: f64,
+) {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+
+    if value <= threshold {
+        match resolve_alert_condition(db_pool, account_id, fingerprint).await {
+            Ok(true) => log::info!("✅ {} usage back to {:.1}%, alert resolved", resource, value),
+            Ok(false) => {}
+            Err(e) => log::error!("❌ Failed to resolve {} alert: {}", resource, e),
+        }
+        return;
+    }
+
+    let severity = calculate_severity(value, threshold);
+    let message = format!(
+        "{} usage at {:.1}% (threshold: {:.1}%)",
+        resource, value, threshold
+    );
+
+    match record_alert_condition(
+        db_pool,
+        account_id,
+        fingerprint,
+        None,
+        alert_type,
+        severity,
+        &message,
+        chrono::Duration::seconds(config.alert_cooldown_secs as i64),
+    )
+    .await
+    {
+        Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+            log::warn!(
+                "🚨 HIGH {} USAGE ALERT: {:.1}% (severity: {:.1})",
+                resource.to_uppercase(),
+                value,
+                severity
+            );
+            let notification = Notification {
+                alert_type: alert_type.to_string(),
+                protocol: None,
+                severity,
+                message,
+                earnings: Some(metrics.total_earnings_per_hour),
+                earnings_delta: None,
+            };
+            publish_alert(events, id, notification);
+        }
+        Ok(AlertOutcome::Repeated(_)) => {
+            log::debug!("{} alert still open at {:.1}%", resource, value);
+        }
+        Err(e) => log::error!("❌ Failed to store {} alert: {}", resource, e),
+    }
+}
+
+/// Publish a raised alert; the notifier and WebSocket hub deliver it
+fn publish_alert(events: &EventBus, id: i64, notification: Notification) {
+    events.publish(OrchestratorEvent::AlertRaised(RaisedAlert {
+        id: Some(id),
+        timestamp: Utc::now(),
+        notification,
+    }));
+}
+
+/// Metrics flush task
+///
+/// Writes queued metrics snapshots every `metrics_flush_interval` seconds,
+/// and once more on shutdown so nothing queued is lost
+async fn metrics_flush_task(
+    buffer: Arc<MetricsWriteBuffer>,
+    mut config_rx: watch::Receiver<SchedulerConfig>,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "metrics_flush";
+    let mut interval = interval(Duration::from_secs(config_rx.borrow().metrics_flush_interval));
+
+    loop {
+        let stopping = tokio::select! {
+            _ = next_tick(&mut interval, &mut config_rx, |c| c.metrics_flush_interval, &status, TASK) => false,
+            _ = shutdown.cancelled() => true,
+        };
+        status.heartbeat(TASK, config_rx.borrow().metrics_flush_interval);
+
+        match buffer.flush().await {
+            Ok(0) => {}
+            Ok(rows) => log::debug!("💾 Wrote {} queued metrics snapshots", rows),
+            Err(e) => {
+                log::error!("❌ Failed to flush queued metrics ({} pending): {}", buffer.len(), e);
+                status.record_error(TASK, &e);
+            }
+        }
+
+        if stopping {
+            break;
+        }
+    }
+}
+
 /// Cleanup task
 ///
 /// Runs once per day to:
-/// 1. Remove old metrics records beyond retention period
-/// 2. Archive acknowledged alerts
-async fn cleanup_task(db_pool: SqlitePool, config: SchedulerConfig) {
+/// 1. Archive expiring metrics when an archive target is configured
+/// 2. Remove rows older than each table's retention period
+///
+/// Cutoffs fall on midnight in `tz`, so a table keeps whole days.
+async fn cleanup_task(
+    db_pool: SqlitePool,
+    config_rx: watch::Receiver<SchedulerConfig>,
+    tz: ReportingTimezone,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "cleanup";
     // Run once per day
-    let mut interval = interval(Duration::from_secs(86400)); // 24 hours
+    let mut interval = interval(Duration::from_secs(CLEANUP_INTERVAL_SECS));
 
     log::info!("🧹 Cleanup task started (runs daily)");
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        status.heartbeat(TASK, CLEANUP_INTERVAL_SECS);
 
         log::info!("🧹 Running daily cleanup task");
 
-        // Calculate cutoff date
-        let cutoff_date = Utc::now() - chrono::Duration::days(config.metrics_retention_days);
-        let cutoff_str = cutoff_date.to_rfc3339();
-
-        // Delete old metrics
-        let result = sqlx::query(
-            "DELETE FROM metrics WHERE created_at < ?1"
-        )
-        .bind(&cutoff_str)
-        .execute(&db_pool)
-        .await;
+        let config = config_rx.borrow().clone();
+        for table in RETAINED_TABLES {
+            let Some(days) = config.retention.retention_days(table.name, config.metrics_retention_days)
+            else {
+                continue;
+            };
+            let cutoff = tz.start_of_day(tz.date_of(Utc::now()) - chrono::Duration::days(days));
 
-        match result {
-            Ok(result) => {
-                let rows_deleted = result.rows_affected();
-                log::info!("✅ Deleted {} old metrics records", rows_deleted);
+            // Keep expiring metrics until they are safely archived
+            if table.name == "metrics" && config.retention.archive.enabled {
+                match archive_metrics(&db_pool, &config.retention.archive, cutoff).await {
+                    Ok(rows) => log::info!("📦 Archived {} metrics records", rows),
+                    Err(e) => {
+                        log::error!("❌ Failed to archive metrics, keeping them: {}", e);
+                        status.record_error(TASK, &e);
+                        continue;
+                    }
+                }
             }
-            Err(e) => {
-                log::error!("❌ Failed to delete old metrics: {}", e);
+
+            match table.expire(&db_pool, cutoff).await {
+                Ok(rows) => log::info!("✅ Deleted {} old {} records", rows, table.name),
+                Err(e) => {
+                    log::error!("❌ Failed to delete old {}: {}", table.name, e);
+                    status.record_error(TASK, &e);
+                }
             }
         }
 
-        // Delete old acknowledged alerts (keep for 7 days)
-        let alert_cutoff = Utc::now() - chrono::Duration::days(7);
-        let alert_cutoff_str = alert_cutoff.to_rfc3339();
+        log::info!("✅ Cleanup task completed");
+    }
+}
 
-        let result = sqlx::query(
-            "DELETE FROM alerts WHERE acknowledged = 1 AND created_at < ?1"
-        )
-        .bind(&alert_cutoff_str)
-        .execute(&db_pool)
-        .await;
+/// Backup task
+///
+/// Takes a backup every `interval_hours`, the first one a full interval
+/// after startup
+async fn backup_task(
+    config: Arc<BackupConfig>,
+    db_pool: SqlitePool,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "backup";
+    let period = Duration::from_secs(config.interval_hours * 3600);
+    let mut interval = interval_at(Instant::now() + period, period);
 
-        match result {
-            Ok(result) => {
-                let rows_deleted = result.rows_affected();
-                log::info!("✅ Deleted {} old acknowledged alerts", rows_deleted);
-            }
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        status.heartbeat(TASK, period.as_secs());
+
+        match crate::backup::create_backup(&db_pool, &config).await {
+            Ok(backup) => log::info!("💾 Backup {} written ({} bytes)", backup.name, backup.size_bytes),
             Err(e) => {
-                log::error!("❌ Failed to delete old alerts: {}", e);
+                log::error!("❌ Backup failed: {}", e);
+                status.record_error(TASK, &e);
             }
         }
+    }
+}
 
-        log::info!("✅ Cleanup task completed");
+/// Public IP check task
+///
+/// Checks at startup and then every `interval_secs`
+async fn ip_check_task(
+    config: Arc<IpIntelConfig>,
+    http: Arc<HttpClientFactory>,
+    coordinator: Arc<ProtocolCoordinator>,
+    db_pool: SqlitePool,
+    events: Arc<EventBus>,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "ip_check";
+    let mut interval = interval(Duration::from_secs(config.interval_secs));
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        status.heartbeat(TASK, config.interval_secs);
+
+        for protocol in config.protocols.iter().filter(|p| coordinator.adapters().contains_key(*p)) {
+            if let Err(e) = process_ip_alert(&db_pool, &config, &http, &events, &coordinator, protocol).await {
+                log::warn!("⚠️  Could not check the public IP of {}: {}", protocol, e);
+                status.record_error(TASK, e);
+            }
+        }
     }
 }
 
-/// Helper: Store metrics to database
-async fn store_metrics_to_db(
+/// Check `protocol`'s public IP, raising an alert while it would hurt the
+/// protocol's earnings and resolving it once it would not
+async fn process_ip_alert(
     db_pool: &SqlitePool,
-    metrics: &crate::orchestration::AggregatedMetrics,
-) -> Result<(), sqlx::Error> {
-    use crate::db::queries::{store_metrics, store_protocol_metrics};
-    use chrono::Utc;
+    config: &IpIntelConfig,
+    http: &HttpClientFactory,
+    events: &EventBus,
+    coordinator: &ProtocolCoordinator,
+    protocol: &str,
+) -> Result<(), String> {
+    use crate::db::models::AlertOutcome;
+    use crate::db::queries::{record_alert_condition, resolve_alert_condition};
+
+    let client = http.client(protocol).map_err(|e| e.to_string())?;
+    let report = check_ip(&client, config).await?;
+    let previous = coordinator.public_ips().get(protocol).map(|r| r.ip).filter(|ip| *ip != report.ip);
+    if let Some(previous) = previous {
+        log::info!("🌐 {} public IP changed from {} to {}", protocol, previous, report.ip);
+    }
+    let issues = report.issues(config);
+    let ip = report.ip;
+    coordinator.set_public_ip(protocol, report);
+
+    let account_id = coordinator.account_id();
+    let fingerprint = format!("ip_reputation:{}", protocol);
+    if issues.is_empty() {
+        match resolve_alert_condition(db_pool, account_id, &fingerprint).await {
+            Ok(true) => log::info!("✅ {} public IP {} looks clean, alert resolved", protocol, ip),
+            Ok(false) => {}
+            Err(e) => log::error!("❌ Failed to resolve {} IP alert: {}", protocol, e),
+        }
+        return Ok(());
+    }
 
-    let metrics_id = store_metrics(
+    let (severity, message) = match previous {
+        Some(previous) => (
+            IP_CHANGED_SEVERITY,
+            format!(
+                "{} public IP changed from {} to {}, which is {}; expect its earnings to drop",
+                protocol,
+                previous,
+                ip,
+                issues.join(" and ")
+            ),
+        ),
+        None => (
+            IP_REPUTATION_SEVERITY,
+            format!("{} public IP {} is {}, which limits its earnings", protocol, ip, issues.join(" and ")),
+        ),
+    };
+    // A check that fails again after its alert was resolved reopens the alert
+    let cooldown = chrono::Duration::seconds(config.interval_secs as i64 * 2);
+    match record_alert_condition(
         db_pool,
-        Utc::now(),
-        metrics.total_earnings_per_hour,
-        metrics.resource_utilization.cpu_percent,
-        metrics.resource_utilization.memory_percent,
-        metrics.resource_utilization.bandwidth_percent,
-        metrics.resource_utilization.storage_percent,
+        account_id,
+        &fingerprint,
+        Some(protocol),
+        "IP_REPUTATION",
+        severity,
+        &message,
+        cooldown,
     )
-    .await?;
+    .await
+    {
+        Ok(AlertOutcome::Raised(id) | AlertOutcome::Reopened(id)) => {
+            log::warn!("🚨 IP ALERT: {}", message);
+            let metrics = coordinator.get_current_metrics().await.ok().flatten();
+            let notification = Notification {
+                alert_type: "IP_REPUTATION".to_string(),
+                protocol: Some(protocol.to_string()),
+                severity,
+                message,
+                earnings: metrics.as_ref().and_then(|m| m.earnings_by_protocol.get(protocol).copied()),
+                earnings_delta: None,
+            };
+            publish_alert(events, id, notification);
+        }
+        Ok(AlertOutcome::Repeated(_)) => log::debug!("{} public IP {} still flagged", protocol, ip),
+        Err(e) => log::error!("❌ Failed to store {} IP alert: {}", protocol, e),
+    }
+    Ok(())
+}
 
-    // Store per-protocol metrics
-    for (protocol, earnings) in &metrics.earnings_by_protocol {
-        let allocation = metrics.allocation_by_protocol.get(protocol).copied().unwrap_or(0.0);
-        let connected = metrics.connection_status.get(protocol).copied().unwrap_or(false);
+/// Network probe task
+///
+/// Measures latency and throughput, stores the result, and hands the
+/// measured upstream to the coordinator (which reports bandwidth use against
+/// it) and the reallocation engine (which gives it to adapters)
+async fn network_probe_task(
+    config: Arc<NetworkProbeConfig>,
+    client: reqwest::Client,
+    coordinator: Arc<ProtocolCoordinator>,
+    reallocation: Arc<ReallocationEngine>,
+    db_pool: SqlitePool,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "network_probe";
+    let mut interval = interval(Duration::from_secs(config.interval_secs));
 
-        store_protocol_metrics(
-            db_pool,
-            metrics_id,
-            protocol.clone(),
-            *earnings,
-            allocation,
-            connected,
-        )
-        .await?;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        status.heartbeat(TASK, config.interval_secs);
+
+        let probe = match run_probe(&client, &config).await {
+            Ok(probe) => probe,
+            Err(e) => {
+                log::warn!("⚠️  Network probe failed: {}", e);
+                status.record_error(TASK, e);
+                continue;
+            }
+        };
+        log::info!(
+            "📶 Network probe: {:.0}ms latency, {:.1} Mbps down, {:.1} Mbps up",
+            probe.latency_ms,
+            probe.download_mbps,
+            probe.upload_mbps
+        );
+        if let Err(e) = crate::db::queries::store_network_probe(&db_pool, coordinator.account_id(), &probe).await {
+            log::error!("❌ Failed to store network probe: {}", e);
+            status.record_error(TASK, &e);
+        }
+        reallocation.set_upstream_mbps(probe.upload_mbps);
+        coordinator.set_network_probe(probe);
     }
+}
 
-    Ok(())
+/// Earnings digest task
+///
+/// Sleeps until the next configured send time, then summarizes the previous
+/// day or week and sends it to every notification channel
+async fn digest_task(
+    notifications: Arc<NotificationDispatcher>,
+    db_pool: SqlitePool,
+    account_id: i64,
+    tz: ReportingTimezone,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "digest";
+    let config = notifications.config().digest.clone();
+    let interval_secs = config.frequency.days() as u64 * 86400;
+
+    while let Some(at) = config.next_send(Utc::now(), tz) {
+        let wait = (at - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = shutdown.cancelled() => break,
+        }
+        status.heartbeat(TASK, interval_secs);
+
+        match digest::build_digest(&db_pool, account_id, config.frequency, at, tz).await {
+            Ok(digest) => {
+                let results = notifications.send_text(&digest.render()).await;
+                let delivered = results.iter().filter(|r| r.delivered).count();
+                log::info!("📊 Earnings digest sent to {}/{} channel(s)", delivered, results.len());
+            }
+            Err(e) => {
+                log::error!("❌ Failed to build earnings digest: {}", e);
+                status.record_error(TASK, &e);
+            }
+        }
+    }
+}
+
+/// Wallet balance task
+///
+/// Polls every configured payout address and stores the balances
+async fn wallet_balance_task(
+    tracker: Arc<WalletTracker>,
+    db_pool: SqlitePool,
+    account_id: i64,
+    status: Arc<SchedulerStatus>,
+    shutdown: ShutdownToken,
+) {
+    const TASK: &str = "wallet_balance";
+    let poll_interval_secs = tracker.config().poll_interval_secs;
+    let mut interval = interval(Duration::from_secs(poll_interval_secs));
+
+    log::info!("👛 Wallet balance task started");
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        status.heartbeat(TASK, poll_interval_secs);
+
+        for result in tracker.poll_all().await {
+            match result {
+                Ok(balance) => {
+                    if let Err(e) = crate::db::queries::store_wallet_balance(&db_pool, account_id, &balance).await {
+                        log::error!("❌ Failed to store balance for {}: {}", balance.label, e);
+                        status.record_error(TASK, &e);
+                    } else {
+                        log::debug!(
+                            "👛 {} ({}): {:.6} {}",
+                            balance.label,
+                            balance.chain,
+                            balance.balance,
+                            balance.symbol
+                        );
+                    }
+                }
+                Err((label, e)) => {
+                    log::warn!("⚠️  Failed to poll wallet {}: {}", label, e);
+                }
+            }
+        }
+    }
+}
+
+/// Wait for the next tick of `ticker`
+///
+/// When the configured period (selected by `period_secs`) changes while
+/// waiting, the interval is rebuilt so the new period applies immediately
+/// instead of after one more tick at the old rate, and task `name` is
+/// re-registered with the watchdog at the new period.
+async fn next_tick(
+    ticker: &mut Interval,
+    config_rx: &mut watch::Receiver<SchedulerConfig>,
+    period_secs: fn(&SchedulerConfig) -> u64,
+    status: &SchedulerStatus,
+    name: &str,
+) {
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => return,
+            changed = config_rx.changed() => {
+                if changed.is_err() {
+                    // Sender dropped: keep the current period
+                    ticker.tick().await;
+                    return;
+                }
+                let period = Duration::from_secs(period_secs(&config_rx.borrow()).max(1));
+                if period != ticker.period() {
+                    log::info!("🕐 {} interval changed to {}s", name, period.as_secs());
+                    *ticker = interval_at(Instant::now() + period, period);
+                    status.set_interval(name, period.as_secs());
+                }
+            }
+        }
+    }
 }
 
 /// Helper: Store reallocation to database
+#[allow(clippy::too_many_arguments)]
 async fn store_reallocation_to_db(
     db_pool: &SqlitePool,
+    account_id: i64,
     protocol: &str,
     old_allocation: f64,
     new_allocation: f64,
     earnings_impact: Option<f64>,
     reason: Option<&str>,
+    decision_id: Option<i64>,
 ) -> Result<(), sqlx::Error> {
     use crate::db::queries::store_reallocation;
     use chrono::Utc;
 
     store_reallocation(
         db_pool,
+        account_id,
         Utc::now(),
         protocol.to_string(),
         old_allocation,
         new_allocation,
         earnings_impact,
         reason.map(|s| s.to_string()),
+        decision_id,
     )
     .await
 }
 
-/// Helper: Store alert to database
-async fn store_alert_to_db(
+/// Helper: Store an optimizer decision, returning its ID unless that failed
+async fn record_decision(
     db_pool: &SqlitePool,
-    alert_type: &str,
-    severity: f64,
-    message: &str,
-) -> Result<(), sqlx::Error> {
-    use crate::db::queries::store_alert;
-    use chrono::Utc;
-
-    store_alert(
-        db_pool,
-        Utc::now(),
-        alert_type.to_string(),
-        severity,
-        message.to_string(),
-    )
-    .await
+    account_id: i64,
+    explanation: &DecisionExplanation,
+    outcome: DecisionOutcome,
+    reason: Option<String>,
+    pending_reallocation_id: Option<i64>,
+) -> Option<i64> {
+    let stored = match OptimizerDecisionRecord::new(explanation, outcome, reason) {
+        Ok(record) => {
+            let record = OptimizerDecisionRecord { pending_reallocation_id, ..record };
+            crate::db::queries::store_optimizer_decision(db_pool, account_id, &record)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => Err(e.to_string()),
+    };
+    stored
+        .map_err(|e| log::error!("❌ Failed to store optimizer decision: {}", e))
+        .ok()
 }
 
 /// Calculate alert severity based on threshold exceedance
@@ -413,7 +2119,7 @@ fn calculate_severity(current_value: f64, threshold: f64) -> f64 {
     let severity_base = (exceedance / threshold) * 100.0;
 
     // Cap at 100.0
-    severity_base.min(100.0).max(0.0)
+    severity_base.clamp(0.0, 100.0)
 }
 
 #[cfg(test)]
@@ -423,10 +2129,53 @@ mod tests {
     #[test]
     fn test_scheduler_config_defaults() {
         let config = SchedulerConfig::default();
+        assert_eq!(config.poll_interval, 60);
         assert_eq!(config.optimization_interval, 300);
         assert_eq!(config.metrics_retention_days, 30);
         assert_eq!(config.alert_processing_interval, 60);
         assert_eq!(config.min_reallocation_threshold, 5.0);
+        assert_eq!(config.alert_cooldown_secs, 900);
+    }
+
+    #[test]
+    fn test_scheduler_config_validation() {
+        assert!(SchedulerConfig::default().validate().is_ok());
+
+        let config = SchedulerConfig {
+            optimization_interval: 0,
+            ..SchedulerConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = SchedulerConfig {
+            poll_interval: 0,
+            ..SchedulerConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = SchedulerConfig {
+            min_reallocation_threshold: -1.0,
+            ..SchedulerConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_next_tick_follows_interval_updates() {
+        let (tx, mut rx) = watch::channel(SchedulerConfig::default());
+        let status = SchedulerStatus::new();
+        let mut ticker = interval(Duration::from_secs(300));
+        ticker.tick().await; // first tick completes immediately
+
+        tx.send_modify(|c| c.optimization_interval = 1);
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            next_tick(&mut ticker, &mut rx, |c| c.optimization_interval, &status, "optimization"),
+        )
+        .await
+        .expect("tick should follow the shortened interval");
+
+        assert_eq!(ticker.period(), Duration::from_secs(1));
     }
 
     #[test]
@@ -445,21 +2194,4 @@ mod tests {
         // Way over threshold = capped at 100%
         assert_eq!(calculate_severity(300.0, 90.0), 100.0);
     }
-
-    #[test]
-    fn test_config_from_env() {
-        std::env::set_var("OPTIMIZATION_INTERVAL", "600");
-        std::env::set_var("METRICS_RETENTION_DAYS", "60");
-        std::env::set_var("MIN_REALLOCATION_THRESHOLD", "10.0");
-
-        let config = SchedulerConfig::from_env();
-        assert_eq!(config.optimization_interval, 600);
-        assert_eq!(config.metrics_retention_days, 60);
-        assert_eq!(config.min_reallocation_threshold, 10.0);
-
-        // Cleanup
-        std::env::remove_var("OPTIMIZATION_INTERVAL");
-        std::env::remove_var("METRICS_retention_DAYS");
-        std::env::remove_var("MIN_REALLOCATION_THRESHOLD");
-    }
 }
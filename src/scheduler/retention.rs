@@ -0,0 +1,339 @@
+/// Data Retention and Archival
+///
+/// The cleanup task deletes rows older than each table's retention period.
+/// Expiring `metrics` and `protocol_metrics` rows can first be archived as
+/// gzip-compressed CSV to a local directory or, with the `s3-storage`
+/// feature, to an S3-compatible bucket, so long-term history isn't lost.
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use crate::api::export::csv_field;
+use crate::db::models::{MetricsRecord, ProtocolMetricsRecord};
+use crate::db::queries;
+use crate::storage::{S3Config, StorageError};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Retention settings (`[scheduler.retention]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Days to keep rows, by table (`[scheduler.retention.tables]`)
+    ///
+    /// `metrics` falls back to `metrics_retention_days`; other tables not
+    /// listed are kept forever.
+    pub tables: BTreeMap<String, i64>,
+    /// Export expiring metrics before they are deleted
+    pub archive: ArchiveConfig,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            tables: BTreeMap::from([
+                ("alerts".to_string(), 7),
+                ("idempotency_keys".to_string(), 1),
+                ("protocol_uptime".to_string(), 31),
+                ("optimizer_decisions".to_string(), 30),
+                ("request_log".to_string(), 14),
+                ("network_probes".to_string(), 90),
+            ]),
+            archive: ArchiveConfig::default(),
+        }
+    }
+}
+
+/// Archive settings (`[scheduler.retention.archive]` section)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    pub enabled: bool,
+    /// Directory archives are written to
+    pub directory: Option<PathBuf>,
+    /// Bucket archives are uploaded to (requires the `s3-storage` feature)
+    pub s3: Option<S3Config>,
+}
+
+impl RetentionConfig {
+    /// Validate table names, periods and the archive target
+    pub fn validate(&self) -> Result<(), String> {
+        for (table, days) in &self.tables {
+            if RetainedTable::find(table).is_none() {
+                let known: Vec<_> = RETAINED_TABLES.iter().map(|t| t.name).collect();
+                return Err(format!(
+                    "retention.tables.{} is not a retained table (expected one of {})",
+                    table,
+                    known.join(", ")
+                ));
+            }
+            if *days < 1 {
+                return Err(format!("retention.tables.{} must be at least 1 day", table));
+            }
+        }
+
+        let archive = &self.archive;
+        if archive.enabled && archive.directory.is_none() && archive.s3.is_none() {
+            return Err("retention.archive needs a directory or an s3 bucket when enabled".to_string());
+        }
+        if let Some(s3) = &archive.s3 {
+            s3.check_supported().map_err(|e| format!("retention.archive.s3: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Days to keep rows of `table`, or `None` to keep them forever
+    pub fn retention_days(&self, table: &str, metrics_retention_days: i64) -> Option<i64> {
+        match self.tables.get(table) {
+            Some(days) => Some(*days),
+            None if table == "metrics" => Some(metrics_retention_days),
+            None => None,
+        }
+    }
+}
+
+// ============================================================================
+// RETAINED TABLES
+// ============================================================================
+
+/// A table the cleanup task prunes
+#[derive(Debug)]
+pub struct RetainedTable {
+    pub name: &'static str,
+    /// Column holding the row's RFC 3339 timestamp
+    column: &'static str,
+    /// Condition rows must also meet to be deleted
+    filter: Option<&'static str>,
+}
+
+/// Tables with a retention period; `protocol_metrics` and
+/// `protocol_metric_values` rows go with their metrics
+pub const RETAINED_TABLES: &[RetainedTable] = &[
+    RetainedTable { name: "metrics", column: "timestamp", filter: None },
+    RetainedTable { name: "alerts", column: "timestamp", filter: Some("acknowledged = 1") },
+    RetainedTable { name: "reallocations", column: "timestamp", filter: None },
+    RetainedTable { name: "pending_reallocations", column: "created_at", filter: Some("status != 'pending'") },
+    RetainedTable { name: "wallet_balances", column: "polled_at", filter: None },
+    RetainedTable { name: "network_probes", column: "measured_at", filter: None },
+    RetainedTable { name: "reports", column: "generated_at", filter: None },
+    RetainedTable { name: "idempotency_keys", column: "created_at", filter: None },
+    RetainedTable { name: "protocol_uptime", column: "ended_at", filter: None },
+    RetainedTable { name: "optimizer_decisions", column: "created_at", filter: None },
+    RetainedTable { name: "request_log", column: "timestamp", filter: None },
+];
+
+impl RetainedTable {
+    /// Table named `name`
+    pub fn find(name: &str) -> Option<&'static RetainedTable> {
+        RETAINED_TABLES.iter().find(|t| t.name == name)
+    }
+
+    /// Delete rows older than `cutoff`; returns the number deleted
+    pub async fn expire(&self, pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        if self.name == "metrics" {
+            return queries::delete_metrics_before(pool, cutoff).await;
+        }
+
+        let sql = match self.filter {
+            Some(filter) => format!("DELETE FROM {} WHERE {} AND {} < ?", self.name, filter, self.column),
+            None => format!("DELETE FROM {} WHERE {} < ?", self.name, self.column),
+        };
+        Ok(sqlx::query(&sql)
+            .bind(cutoff.to_rfc3339())
+            .execute(pool)
+            .await?
+            .rows_affected())
+    }
+}
+
+// ============================================================================
+// ARCHIVAL
+// ============================================================================
+
+/// Errors while archiving expiring rows
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Archive I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Archive upload error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+/// Export metrics recorded before `cutoff` to the configured targets
+///
+/// Writes `metrics-<run>.csv.gz` and `protocol_metrics-<run>.csv.gz`.
+/// Returns the number of metrics rows archived; nothing is written when
+/// no rows have expired.
+pub async fn archive_metrics(
+    pool: &SqlitePool,
+    config: &ArchiveConfig,
+    cutoff: DateTime<Utc>,
+) -> Result<usize, ArchiveError> {
+    let metrics = queries::get_metrics_before(pool, cutoff).await?;
+    if metrics.is_empty() {
+        return Ok(0);
+    }
+    let protocol_metrics = queries::get_protocol_metrics_before(pool, cutoff).await?;
+
+    let run = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let files = [
+        (format!("metrics-{}.csv.gz", run), gzip(&metrics_csv(&metrics))?),
+        (
+            format!("protocol_metrics-{}.csv.gz", run),
+            gzip(&protocol_metrics_csv(&protocol_metrics))?,
+        ),
+    ];
+
+    for (name, contents) in &files {
+        if let Some(directory) = &config.directory {
+            tokio::fs::create_dir_all(directory).await?;
+            tokio::fs::write(directory.join(name), contents).await?;
+        }
+        if let Some(s3) = &config.s3 {
+            s3.upload(name, contents.clone()).await?;
+        }
+    }
+
+    Ok(metrics.len())
+}
+
+/// Gzip-compress `text`
+fn gzip(text: &str) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes())?;
+    encoder.finish()
+}
+
+/// Render metrics rows as CSV
+fn metrics_csv(rows: &[MetricsRecord]) -> String {
+    let mut out = String::from(
+        "id,timestamp,total_earnings_per_hour,cpu_percent,memory_percent,bandwidth_percent,storage_percent\n",
+    );
+    let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            row.id.map(|id| id.to_string()).unwrap_or_default(),
+            csv_field(&row.timestamp),
+            row.total_earnings_per_hour,
+            optional(row.cpu_percent),
+            optional(row.memory_percent),
+            optional(row.bandwidth_percent),
+            optional(row.storage_percent)
+        );
+    }
+
+    out
+}
+
+/// Render per-protocol metrics rows as CSV
+fn protocol_metrics_csv(rows: &[ProtocolMetricsRecord]) -> String {
+    let mut out =
+        String::from("id,metrics_id,protocol_name,earnings_per_hour,allocation_percent,connected\n");
+
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            row.id.map(|id| id.to_string()).unwrap_or_default(),
+            row.metrics_id,
+            csv_field(&row.protocol_name),
+            row.earnings_per_hour,
+            row.allocation_percent,
+            row.connected.map(|c| c.to_string()).unwrap_or_default()
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::collections::HashMap;
+    use std::io::Read;
+
+    #[test]
+    fn test_retention_config_validation() {
+        let config = RetentionConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.retention_days("metrics", 30), Some(30));
+        assert_eq!(config.retention_days("alerts", 30), Some(7));
+        assert_eq!(config.retention_days("reports", 30), None);
+
+        let unknown = RetentionConfig {
+            tables: BTreeMap::from([("api_keys".to_string(), 30)]),
+            ..RetentionConfig::default()
+        };
+        assert!(unknown.validate().is_err());
+
+        let no_target = RetentionConfig {
+            archive: ArchiveConfig {
+                enabled: true,
+                ..ArchiveConfig::default()
+            },
+            ..RetentionConfig::default()
+        };
+        assert!(no_target.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_archive_then_expire_metrics() {
+        let pool = crate::db::test_pool().await;
+
+        let now = Utc::now();
+        let earnings = HashMap::from([("storj".to_string(), 0.25)]);
+        for days_ago in [40, 35, 1] {
+            let timestamp = now - chrono::Duration::days(days_ago);
+            queries::store_historical_metrics(&pool, 1, timestamp, &earnings, &HashMap::new())
+                .await
+                .unwrap();
+        }
+
+        let directory = std::env::temp_dir().join(format!("orcha-archive-{}", uuid::Uuid::new_v4()));
+        let config = ArchiveConfig {
+            enabled: true,
+            directory: Some(directory.clone()),
+            s3: None,
+        };
+        let cutoff = now - chrono::Duration::days(30);
+        assert_eq!(archive_metrics(&pool, &config, cutoff).await.unwrap(), 2);
+
+        let mut names: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+        assert!(names[0].starts_with("metrics-"));
+
+        let mut csv = String::new();
+        GzDecoder::new(std::fs::File::open(directory.join(&names[1])).unwrap())
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(1).unwrap().contains(",storj,0.25,"));
+
+        let metrics = RetainedTable::find("metrics").unwrap();
+        assert_eq!(metrics.expire(&pool, cutoff).await.unwrap(), 2);
+        assert_eq!(queries::get_metrics_history(&pool, 1, 10).await.unwrap().len(), 1);
+        assert!(queries::get_protocol_metrics_before(&pool, now).await.unwrap().len() == 1);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
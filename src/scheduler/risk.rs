@@ -0,0 +1,98 @@
+/// Protocol Earnings Risk
+///
+/// The optimization task periodically computes how volatile each protocol's
+/// hourly earnings were over the trailing week — standard deviation, max
+/// drawdown and a combined 0-1 risk score — and stores them in the
+/// `protocol_risk` table. The scores are attached to optimization
+/// opportunities, so a higher-yielding but erratic protocol can be weighed
+/// against a steadier one.
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::db::models::{MetricsAggregation, MetricsInterval};
+use crate::db::queries;
+use crate::orchestration::ProtocolRisk;
+
+/// Trailing hours of earnings the risk is computed over
+pub const RISK_WINDOW_HOURS: i64 = 7 * 24;
+
+/// Recompute every protocol's earnings risk as of `now`, store it, and
+/// return it by protocol
+///
+/// Protocols with fewer than two hours of earnings in the window are left
+/// out (and keep whatever was stored for them before).
+pub async fn refresh_risk(
+    pool: &SqlitePool,
+    account_id: i64,
+    now: DateTime<Utc>,
+) -> Result<HashMap<String, ProtocolRisk>, sqlx::Error> {
+    let start = now - Duration::hours(RISK_WINDOW_HOURS);
+    let buckets =
+        queries::get_bucketed_protocol_metrics(pool, account_id, start, MetricsInterval::Hour, MetricsAggregation::Avg)
+            .await?;
+
+    // Buckets arrive oldest first, which drawdown depends on
+    let mut hourly: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for bucket in buckets {
+        hourly.entry(bucket.protocol_name).or_default().push(bucket.earnings_per_hour);
+    }
+
+    let mut risks = HashMap::new();
+    for (protocol, earnings) in hourly {
+        let Some(risk) = ProtocolRisk::from_hourly(&earnings) else {
+            continue;
+        };
+        queries::upsert_protocol_risk(pool, account_id, &protocol, &risk, RISK_WINDOW_HOURS, now).await?;
+        risks.insert(protocol, risk);
+    }
+    Ok(risks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_of_steady_and_falling_earnings() {
+        let steady = ProtocolRisk::from_hourly(&[2.0, 2.0, 2.0]).unwrap();
+        assert_eq!((steady.std_dev, steady.max_drawdown_percent, steady.risk_score), (0.0, 0.0, 0.0));
+
+        // Peaks at 4, falls to 1: a 75% drawdown
+        let falling = ProtocolRisk::from_hourly(&[2.0, 4.0, 1.0, 3.0]).unwrap();
+        assert!((falling.mean_earnings - 2.5).abs() < 1e-9);
+        assert!((falling.std_dev - 1.25f64.sqrt()).abs() < 1e-9);
+        assert!((falling.max_drawdown_percent - 75.0).abs() < 1e-9);
+        let variation = 1.25f64.sqrt() / 2.5;
+        assert!((falling.risk_score - (variation + 0.75) / 2.0).abs() < 1e-9);
+
+        assert!(ProtocolRisk::from_hourly(&[1.0]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_risk_stores_hourly_volatility() {
+        let pool = crate::db::test_pool().await;
+        let now = Utc::now();
+
+        // storj swings between two rates; grass was seen only once
+        for (hours, storj) in [(5, 1.0), (4, 3.0), (3, 1.0), (2, 3.0)] {
+            let mut earnings = HashMap::from([("storj".to_string(), storj)]);
+            if hours == 2 {
+                earnings.insert("grass".to_string(), 5.0);
+            }
+            queries::store_historical_metrics(&pool, 1, now - Duration::hours(hours), &earnings, &earnings)
+                .await
+                .unwrap();
+        }
+
+        let risks = refresh_risk(&pool, 1, now).await.unwrap();
+        assert_eq!(risks.keys().collect::<Vec<_>>(), ["storj"]);
+        let stored = queries::get_protocol_risk(&pool, 1, "storj").await.unwrap().unwrap();
+        assert_eq!((stored.samples, stored.window_hours), (4, RISK_WINDOW_HOURS));
+        assert!((stored.std_dev - 1.0).abs() < 1e-9);
+        assert!((stored.max_drawdown_percent - 100.0 * 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stored.risk_score, risks["storj"].risk_score);
+        assert!(queries::get_protocol_risk(&pool, 1, "grass").await.unwrap().is_none());
+        assert!(queries::get_protocol_risk(&pool, 2, "storj").await.unwrap().is_none());
+    }
+}
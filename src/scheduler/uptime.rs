@@ -0,0 +1,156 @@
+/// Protocol Uptime Tracking
+///
+/// The polling task records each protocol's connected state in the
+/// `protocol_uptime` table and refreshes rolling 24h/7d/30d uptime from it.
+/// Several networks stop paying or suspend nodes whose online time drops
+/// too low (Storj suspends below a 60% online score), so the alert task
+/// warns while there is still time to recover.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::db::queries;
+use crate::orchestration::ProtocolUptime;
+
+/// Polls a protocol can be missed before the gap counts as untracked time
+const MAX_MISSED_POLLS: i64 = 3;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Uptime alert settings (`[scheduler.uptime]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UptimeConfig {
+    /// 30-day uptime (%) a protocol needs to keep earning (default: 90.0)
+    pub min_percent: f64,
+    /// Alert this many points before uptime reaches the minimum (default: 5.0)
+    pub warning_margin: f64,
+    /// Per-protocol minimums overriding `min_percent` (`[scheduler.uptime.protocols]`)
+    pub protocols: BTreeMap<String, f64>,
+}
+
+impl Default for UptimeConfig {
+    fn default() -> Self {
+        Self {
+            min_percent: 90.0,
+            warning_margin: 5.0,
+            protocols: BTreeMap::from([("storj".to_string(), 60.0)]),
+        }
+    }
+}
+
+impl UptimeConfig {
+    /// Validate percentages and the margin
+    pub fn validate(&self) -> Result<(), String> {
+        let percent = |name: &str, value: f64| {
+            if (0.0..=100.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(format!("uptime.{} must be between 0 and 100", name))
+            }
+        };
+        percent("min_percent", self.min_percent)?;
+        for (protocol, value) in &self.protocols {
+            percent(&format!("protocols.{}", protocol), *value)?;
+        }
+        if self.warning_margin < 0.0 {
+            return Err("uptime.warning_margin must not be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Minimum 30-day uptime (%) for `protocol`
+    pub fn min_percent_for(&self, protocol: &str) -> f64 {
+        self.protocols.get(protocol).copied().unwrap_or(self.min_percent)
+    }
+}
+
+// ============================================================================
+// TRACKING
+// ============================================================================
+
+/// Record the connected state of each protocol polled at `at` and return
+/// every protocol's rolling uptime
+///
+/// A protocol unseen for more than a few `poll_interval_secs` starts a new
+/// window, so time the orchestrator was down is not counted either way.
+pub async fn record_connectivity(
+    pool: &SqlitePool,
+    account_id: i64,
+    connection_status: &HashMap<String, bool>,
+    at: DateTime<Utc>,
+    poll_interval_secs: u64,
+) -> Result<HashMap<String, ProtocolUptime>, sqlx::Error> {
+    let max_gap = chrono::Duration::seconds(poll_interval_secs as i64 * MAX_MISSED_POLLS);
+    for (protocol, connected) in connection_status {
+        if queries::record_protocol_connectivity(pool, account_id, protocol, *connected, at, max_gap).await? {
+            log::info!(
+                "🔌 {} {}",
+                protocol,
+                if *connected { "reconnected" } else { "disconnected" }
+            );
+        }
+    }
+
+    let windows = queries::get_uptime_windows(pool, account_id, at - chrono::Duration::days(30)).await?;
+    let mut by_protocol: HashMap<String, Vec<_>> = HashMap::new();
+    for window in windows {
+        by_protocol.entry(window.protocol_name.clone()).or_default().push(window);
+    }
+
+    Ok(by_protocol
+        .into_iter()
+        .map(|(protocol, windows)| (protocol, ProtocolUptime::from_windows(&windows, at)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime_config_validation() {
+        let config = UptimeConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.min_percent_for("storj"), 60.0);
+        assert_eq!(config.min_percent_for("grass"), 90.0);
+
+        let config = UptimeConfig {
+            protocols: BTreeMap::from([("golem".to_string(), 120.0)]),
+            ..UptimeConfig::default()
+        };
+        assert!(config.validate().unwrap_err().contains("protocols.golem"));
+    }
+
+    #[tokio::test]
+    async fn test_connectivity_transitions_and_gaps() {
+        let pool = crate::db::test_pool().await;
+        let start = Utc::now() - chrono::Duration::hours(3);
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        let status = |connected| HashMap::from([("storj".to_string(), connected)]);
+
+        // Up for an hour, down for 30 minutes, then the orchestrator stops
+        // for an hour and comes back to a connected protocol
+        for minute in (0..=60).step_by(10) {
+            record_connectivity(&pool, 1, &status(true), at(minute), 600).await.unwrap();
+        }
+        for minute in (70..=90).step_by(10) {
+            record_connectivity(&pool, 1, &status(false), at(minute), 600).await.unwrap();
+        }
+        record_connectivity(&pool, 1, &status(true), at(150), 600).await.unwrap();
+        let uptime = record_connectivity(&pool, 1, &status(true), at(180), 600).await.unwrap()["storj"];
+
+        let windows = queries::get_uptime_windows(&pool, 1, start).await.unwrap();
+        let states: Vec<_> = windows.iter().map(|w| w.connected).collect();
+        assert_eq!(states, [true, false, true]);
+        // Down from the last connected poll; the hour-long gap is untracked
+        assert_eq!(windows[1].started_at, at(60).to_rfc3339());
+        assert_eq!(windows[2].started_at, at(150).to_rfc3339());
+        assert!((uptime.last_24h.unwrap() - 100.0 * 90.0 / 120.0).abs() < 1e-9);
+        assert_eq!(uptime.last_30d, uptime.last_24h);
+        assert!(queries::get_uptime_windows(&pool, 2, start).await.unwrap().is_empty());
+    }
+}
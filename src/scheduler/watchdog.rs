@@ -0,0 +1,305 @@
+/// Scheduler Liveness Tracking
+///
+/// Each background task records a heartbeat per tick in the shared
+/// `SchedulerStatus`. The `Watchdog` respawns tasks that exit (usually a
+/// panic) or stop ticking for several intervals.
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use crate::shutdown::ShutdownToken;
+
+/// Missed intervals after which a task is considered stalled
+const STALL_INTERVALS: i64 = 3;
+
+/// Extra slack before declaring a task stalled, covering slow runs
+const STALL_GRACE_SECS: i64 = 60;
+
+/// Liveness of one scheduler task
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub name: String,
+    /// Expected seconds between ticks
+    pub interval_secs: u64,
+    pub started_at: DateTime<Utc>,
+    /// Start of the most recent run
+    pub last_run: Option<DateTime<Utc>>,
+    pub error_count: u64,
+    pub last_error: Option<String>,
+    pub restart_count: u64,
+    /// No tick within the expected window
+    pub stalled: bool,
+}
+
+impl TaskStatus {
+    fn new(name: &str, interval_secs: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            interval_secs,
+            started_at: Utc::now(),
+            last_run: None,
+            error_count: 0,
+            last_error: None,
+            restart_count: 0,
+            stalled: false,
+        }
+    }
+
+    /// Whether the task has gone too long without a tick at `now`
+    fn is_stalled(&self, now: DateTime<Utc>) -> bool {
+        let last = self.last_run.unwrap_or(self.started_at).max(self.started_at);
+        let allowed = chrono::Duration::seconds(
+            (self.interval_secs as i64).saturating_mul(STALL_INTERVALS) + STALL_GRACE_SECS,
+        );
+        now - last > allowed
+    }
+}
+
+/// Shared liveness registry for scheduler tasks
+#[derive(Debug, Default)]
+pub struct SchedulerStatus {
+    tasks: Mutex<BTreeMap<String, TaskStatus>>,
+}
+
+impl SchedulerStatus {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a tick of `name`, whose ticks are `interval_secs` apart
+    pub fn heartbeat(&self, name: &str, interval_secs: u64) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let task = tasks
+            .entry(name.to_string())
+            .or_insert_with(|| TaskStatus::new(name, interval_secs));
+        task.interval_secs = interval_secs;
+        task.last_run = Some(Utc::now());
+    }
+
+    /// Update the expected tick period of `name` without recording a run
+    pub fn set_interval(&self, name: &str, interval_secs: u64) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = tasks.get_mut(name) {
+            task.interval_secs = interval_secs;
+        }
+    }
+
+    /// Count a failed run of `name`
+    pub fn record_error(&self, name: &str, error: impl std::fmt::Display) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = tasks.get_mut(name) {
+            task.error_count += 1;
+            task.last_error = Some(error.to_string());
+        }
+    }
+
+    /// Status of every task, ordered by name
+    pub fn snapshot(&self) -> Vec<TaskStatus> {
+        let now = Utc::now();
+        let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks
+            .values()
+            .map(|task| TaskStatus {
+                stalled: task.is_stalled(now),
+                ..task.clone()
+            })
+            .collect()
+    }
+
+    /// Start (or restart) tracking `name`
+    fn register(&self, name: &str, interval_secs: u64) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks
+            .entry(name.to_string())
+            .and_modify(|task| {
+                task.started_at = Utc::now();
+                task.restart_count += 1;
+            })
+            .or_insert_with(|| TaskStatus::new(name, interval_secs));
+    }
+
+    fn is_stalled(&self, name: &str, now: DateTime<Utc>) -> bool {
+        let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.get(name).is_some_and(|task| task.is_stalled(now))
+    }
+}
+
+type TaskFactory = Box<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
+
+struct SupervisedTask {
+    name: &'static str,
+    interval_secs: u64,
+    spawn: TaskFactory,
+    handle: JoinHandle<()>,
+}
+
+/// Spawns scheduler tasks and restarts them when they die or stall
+pub struct Watchdog {
+    status: Arc<SchedulerStatus>,
+    tasks: Vec<SupervisedTask>,
+}
+
+impl Watchdog {
+    /// Create a watchdog reporting into `status`
+    pub fn new(status: Arc<SchedulerStatus>) -> Self {
+        Self {
+            status,
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Spawn a task now, and again from `spawn` whenever it needs restarting
+    ///
+    /// `interval_secs` is the initial expected tick period; heartbeats keep
+    /// it current afterwards.
+    pub fn supervise<F, Fut>(&mut self, name: &'static str, interval_secs: u64, spawn: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let spawn: TaskFactory = Box::new(move || Box::pin(spawn()));
+        self.status.register(name, interval_secs);
+        let handle = tokio::spawn(spawn());
+        self.tasks.push(SupervisedTask {
+            name,
+            interval_secs,
+            spawn,
+            handle,
+        });
+    }
+
+    /// Check tasks every `check_interval` until `shutdown`
+    ///
+    /// After shutdown no task is restarted; the returned handle completes
+    /// once every supervised task has exited.
+    pub fn start(mut self, check_interval: Duration, shutdown: ShutdownToken) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => self.check().await,
+                    _ = shutdown.cancelled() => break,
+                }
+            }
+
+            for task in &mut self.tasks {
+                if let Err(e) = (&mut task.handle).await {
+                    if e.is_panic() {
+                        log::error!("🐕 Scheduler task '{}' panicked during shutdown", task.name);
+                    }
+                }
+            }
+            log::info!("🐕 Scheduler tasks stopped");
+        })
+    }
+
+    /// Restart every task that exited or stalled
+    async fn check(&mut self) {
+        let now = Utc::now();
+        for task in &mut self.tasks {
+            let reason = if task.handle.is_finished() {
+                match (&mut task.handle).await {
+                    Err(e) if e.is_panic() => "panicked",
+                    Err(_) => "was cancelled",
+                    Ok(()) => "exited",
+                }
+            } else if self.status.is_stalled(task.name, now) {
+                task.handle.abort();
+                "stopped ticking"
+            } else {
+                continue;
+            };
+
+            log::error!("🐕 Scheduler task '{}' {}, restarting", task.name, reason);
+            self.status.record_error(task.name, format!("task {}", reason));
+            self.status.register(task.name, task.interval_secs);
+            task.handle = tokio::spawn((task.spawn)());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_heartbeat_and_errors_are_tracked() {
+        let status = SchedulerStatus::new();
+        status.register("cleanup", 86400);
+        status.heartbeat("cleanup", 86400);
+        status.record_error("cleanup", "disk full");
+
+        let tasks = status.snapshot();
+        assert_eq!(tasks.len(), 1);
+        assert!(tasks[0].last_run.is_some());
+        assert_eq!(tasks[0].error_count, 1);
+        assert_eq!(tasks[0].last_error.as_deref(), Some("disk full"));
+        assert!(!tasks[0].stalled);
+    }
+
+    #[test]
+    fn test_stall_detection() {
+        let mut task = TaskStatus::new("optimization", 10);
+        let now = Utc::now();
+        task.started_at = now - chrono::Duration::seconds(300);
+        task.last_run = Some(now - chrono::Duration::seconds(60));
+        assert!(!task.is_stalled(now));
+        task.last_run = Some(now - chrono::Duration::seconds(120));
+        assert!(task.is_stalled(now));
+        // A restart resets the clock
+        task.started_at = now;
+        assert!(!task.is_stalled(now));
+    }
+
+    #[tokio::test]
+    async fn test_panicked_task_is_restarted() {
+        let status = Arc::new(SchedulerStatus::new());
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let mut watchdog = Watchdog::new(status.clone());
+        let counter = runs.clone();
+        watchdog.supervise("flaky", 60, move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first run fails");
+                }
+                std::future::pending::<()>().await;
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        watchdog.check().await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        let tasks = status.snapshot();
+        assert_eq!(tasks[0].restart_count, 1);
+        assert_eq!(tasks[0].error_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_start_completes_after_shutdown() {
+        let shutdown = crate::shutdown::Shutdown::new();
+        let mut watchdog = Watchdog::new(Arc::new(SchedulerStatus::new()));
+        let token = shutdown.token();
+        watchdog.supervise("looping", 60, move || {
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+
+        let handle = watchdog.start(Duration::from_secs(30), shutdown.token());
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("watchdog should stop with its tasks")
+            .unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+/// Metrics Write Batching
+///
+/// The optimization task queues each metrics snapshot here instead of
+/// writing it directly. Queued snapshots are written together in a single
+/// transaction of multi-row INSERTs when the flush task ticks, when the
+/// buffer fills, and on shutdown, which keeps SQLite write amplification down
+/// for fleets running many protocols.
+///
+/// Protocol-specific metrics are stored with the unit and aggregation their
+/// adapter's metric schema declares; metrics a schema does not declare are
+/// not stored.
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::db::models::{MetricsRecord, MetricsSample, ProtocolMetricValueRecord, ProtocolMetricsRecord};
+use crate::db::queries;
+use crate::orchestration::AggregatedMetrics;
+use crate::protocols::{find_metric_schema, MetricSchema};
+
+/// Queue of metrics snapshots waiting to be written
+#[derive(Debug)]
+pub struct MetricsWriteBuffer {
+    db_pool: SqlitePool,
+    /// Queue length that triggers an immediate flush
+    max_pending: usize,
+    pending: Mutex<Vec<MetricsSample>>,
+    /// Declared protocol-specific metrics, keyed by protocol name
+    metric_schemas: HashMap<String, Vec<MetricSchema>>,
+}
+
+impl MetricsWriteBuffer {
+    /// Create a buffer that flushes on its own once `max_pending` snapshots are queued
+    pub fn new(db_pool: SqlitePool, max_pending: usize) -> Self {
+        Self {
+            db_pool,
+            max_pending: max_pending.max(1),
+            pending: Mutex::new(Vec::new()),
+            metric_schemas: HashMap::new(),
+        }
+    }
+
+    /// Store protocol-specific metrics declared in `metric_schemas`
+    pub fn with_metric_schemas(mut self, metric_schemas: HashMap<String, Vec<MetricSchema>>) -> Self {
+        self.metric_schemas = metric_schemas;
+        self
+    }
+
+    /// Queue `metrics` as recorded at `timestamp` for `account_id`
+    ///
+    /// Flushes the buffer when this fills it; a failed flush keeps the
+    /// snapshots queued.
+    pub async fn push(
+        &self,
+        account_id: i64,
+        timestamp: DateTime<Utc>,
+        metrics: &AggregatedMetrics,
+    ) -> Result<(), sqlx::Error> {
+        let full = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.push(sample(account_id, timestamp, metrics, &self.metric_schemas));
+            pending.len() >= self.max_pending
+        };
+        if full {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Write every queued snapshot in one transaction; returns how many were written
+    ///
+    /// On failure the snapshots are queued again, ahead of any pushed since.
+    pub async fn flush(&self) -> Result<usize, sqlx::Error> {
+        let samples = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        if samples.is_empty() {
+            return Ok(0);
+        }
+
+        match queries::store_metrics_batch(&self.db_pool, &samples).await {
+            Ok(()) => Ok(samples.len()),
+            Err(e) => {
+                let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                let newer = std::mem::replace(&mut *pending, samples);
+                pending.extend(newer);
+                Err(e)
+            }
+        }
+    }
+
+    /// Number of snapshots waiting to be written
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether nothing is waiting to be written
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Rows to store for one metrics snapshot
+fn sample(
+    account_id: i64,
+    timestamp: DateTime<Utc>,
+    metrics: &AggregatedMetrics,
+    metric_schemas: &HashMap<String, Vec<MetricSchema>>,
+) -> MetricsSample {
+    let resources = &metrics.resource_utilization;
+    let protocols = metrics
+        .earnings_by_protocol
+        .iter()
+        .map(|(protocol, earnings)| {
+            ProtocolMetricsRecord::new(
+                0,
+                protocol.clone(),
+                *earnings,
+                metrics.allocation_by_protocol.get(protocol).copied().unwrap_or(0.0),
+                metrics.connection_status.get(protocol).copied().unwrap_or(false),
+            )
+        })
+        .collect();
+    let values = metrics
+        .protocol_details
+        .iter()
+        .flat_map(|(protocol, details)| {
+            let schema = metric_schemas.get(protocol).map_or(&[][..], Vec::as_slice);
+            details.iter().filter_map(move |(name, value)| {
+                let declared = find_metric_schema(schema, name)?;
+                Some(ProtocolMetricValueRecord::new(
+                    0,
+                    protocol.clone(),
+                    name.clone(),
+                    *value,
+                    declared.unit.clone(),
+                    declared.aggregation.as_str().to_string(),
+                ))
+            })
+        })
+        .collect();
+
+    MetricsSample {
+        account_id,
+        metrics: MetricsRecord::new(
+            timestamp,
+            metrics.total_earnings_per_hour,
+            resources.cpu_percent,
+            resources.memory_percent,
+            resources.bandwidth_percent,
+            resources.storage_percent,
+        ),
+        protocols,
+        values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::ResourceUtilization;
+    use crate::protocols::MetricAggregation;
+
+    fn metrics(earnings: &[(&str, f64)]) -> AggregatedMetrics {
+        AggregatedMetrics {
+            timestamp: Utc::now(),
+            total_earnings_per_hour: earnings.iter().map(|(_, e)| e).sum(),
+            earnings_by_protocol: earnings.iter().map(|(p, e)| (p.to_string(), *e)).collect(),
+            allocation_by_protocol: earnings.iter().map(|(p, _)| (p.to_string(), 50.0)).collect(),
+            connection_status: earnings.iter().map(|(p, _)| (p.to_string(), true)).collect(),
+            resource_utilization: ResourceUtilization {
+                cpu_percent: 40.0,
+                memory_percent: 30.0,
+                bandwidth_percent: 20.0,
+                storage_percent: 10.0,
+            },
+            resource_profiles: HashMap::new(),
+            protocol_types: HashMap::new(),
+            efficiency_by_protocol: HashMap::new(),
+            protocol_details: HashMap::new(),
+            upstream_mbps: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_are_written_on_flush_and_when_full() {
+        let pool = crate::db::test_pool().await;
+        let schemas = HashMap::from([(
+            "golem".to_string(),
+            vec![MetricSchema::new("tasks_completed", "count", MetricAggregation::Last, "Tasks completed")],
+        )]);
+        let buffer = MetricsWriteBuffer::new(pool.clone(), 3).with_metric_schemas(schemas);
+        let start = Utc::now() - chrono::Duration::minutes(10);
+
+        for minute in 0..2 {
+            let timestamp = start + chrono::Duration::minutes(minute);
+            buffer
+                .push(1, timestamp, &metrics(&[("storj", 0.5), ("grass", 0.25)]))
+                .await
+                .unwrap();
+        }
+        assert_eq!(buffer.len(), 2);
+        assert!(queries::get_latest_metrics(&pool, 1).await.unwrap().is_none());
+
+        assert_eq!(buffer.flush().await.unwrap(), 2);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.flush().await.unwrap(), 0);
+
+        // The third queued snapshot fills the buffer
+        for minute in 2..5 {
+            let timestamp = start + chrono::Duration::minutes(minute);
+            let mut snapshot = metrics(&[("golem", 1.0)]);
+            let details = [("tasks_completed".to_string(), minute as f64), ("undeclared".to_string(), 1.0)];
+            snapshot.protocol_details.insert("golem".to_string(), HashMap::from(details));
+            buffer.push(2, timestamp, &snapshot).await.unwrap();
+        }
+        assert!(buffer.is_empty());
+
+        let end = Utc::now();
+        let history = queries::get_metrics_history(&pool, 1, 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].cpu_percent, Some(40.0));
+        let protocols = queries::get_protocol_metrics_by_range(&pool, 1, start, end).await.unwrap();
+        assert_eq!(protocols.len(), 4);
+        for record in &history {
+            let rows: Vec<_> = protocols.iter().filter(|p| Some(p.metrics_id) == record.id).collect();
+            assert_eq!(rows.len(), 2);
+        }
+        let golem = queries::get_protocol_metrics_by_range(&pool, 2, start, end).await.unwrap();
+        assert_eq!(golem.len(), 3);
+        assert!(golem.iter().all(|p| p.protocol_name == "golem" && p.earnings_per_hour == 1.0));
+        let values = queries::get_protocol_metric_values_by_range(&pool, 2, "golem", start, end).await.unwrap();
+        let completed: Vec<_> = values.iter().map(|v| (v.name.as_str(), v.value)).collect();
+        assert_eq!(completed, [("tasks_completed", 2.0), ("tasks_completed", 3.0), ("tasks_completed", 4.0)]);
+        assert!(values.iter().all(|v| v.unit == "count" && v.aggregation == "last"));
+    }
+}
@@ -0,0 +1,288 @@
+/// Protocol Credentials Vault
+///
+/// Keeps adapter credentials (private keys, auth tokens, API keys) out of the
+/// config file. Credentials are encrypted with AES-256-GCM under a master key
+/// and stored in the `protocol_credentials` table; they are only decrypted
+/// when adapters are instantiated.
+///
+/// The master key is 32 bytes, hex-encoded, read from `ORCHA_MASTER_KEY` or
+/// from the file named by `ORCHA_MASTER_KEY_FILE`.
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+use crate::db::queries;
+
+/// Environment variable holding the hex-encoded master key
+pub const MASTER_KEY_ENV: &str = "ORCHA_MASTER_KEY";
+
+/// Environment variable naming a file that holds the hex-encoded master key
+pub const MASTER_KEY_FILE_ENV: &str = "ORCHA_MASTER_KEY_FILE";
+
+/// Adapter config fields treated as credentials
+pub const SECRET_FIELDS: &[&str] = &["private_key", "auth_token", "api_key", "api_secret", "password"];
+
+/// Credential values for one protocol, keyed by adapter config field
+pub type Credentials = BTreeMap<String, String>;
+
+/// Vault errors
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    #[error("Invalid master key: {0}")]
+    MasterKey(String),
+
+    #[error("Failed to encrypt credentials for {0}")]
+    Encryption(String),
+
+    #[error("Failed to decrypt credentials for {0} (wrong master key or tampered data)")]
+    Decryption(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Encrypts and decrypts protocol credentials with the master key
+pub struct SecretsVault {
+    cipher: Aes256Gcm,
+}
+
+impl SecretsVault {
+    /// Create a vault from a raw 32-byte key
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// Create a vault from a hex-encoded 32-byte key
+    pub fn from_hex(key: &str) -> Result<Self, SecretsError> {
+        let bytes = hex::decode(key.trim()).map_err(|e| SecretsError::MasterKey(e.to_string()))?;
+        let key: [u8; 32] = bytes.try_into().map_err(|b: Vec<u8>| {
+            SecretsError::MasterKey(format!("expected 32 bytes, got {}", b.len()))
+        })?;
+        Ok(Self::new(&key))
+    }
+
+    /// Load the master key from `ORCHA_MASTER_KEY` or `ORCHA_MASTER_KEY_FILE`
+    ///
+    /// Returns `Ok(None)` when neither is set, leaving the vault disabled.
+    pub fn from_env() -> Result<Option<Self>, SecretsError> {
+        if let Ok(key) = std::env::var(MASTER_KEY_ENV) {
+            return Self::from_hex(&key).map(Some);
+        }
+        if let Ok(path) = std::env::var(MASTER_KEY_FILE_ENV) {
+            let key = std::fs::read_to_string(&path)
+                .map_err(|e| SecretsError::MasterKey(format!("cannot read {}: {}", path, e)))?;
+            return Self::from_hex(&key).map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Encrypt credentials, returning `(nonce, ciphertext)`
+    ///
+    /// The protocol name is bound as associated data, so a row copied to a
+    /// different protocol fails to decrypt.
+    pub fn encrypt(
+        &self,
+        protocol: &str,
+        credentials: &Credentials,
+    ) -> Result<(Vec<u8>, Vec<u8>), SecretsError> {
+        let plaintext = serde_json::to_vec(credentials)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: &plaintext, aad: protocol.as_bytes() })
+            .map_err(|_| SecretsError::Encryption(protocol.to_string()))?;
+        Ok((nonce.to_vec(), ciphertext))
+    }
+
+    /// Decrypt credentials produced by [`encrypt`](Self::encrypt)
+    pub fn decrypt(
+        &self,
+        protocol: &str,
+        nonce: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Credentials, SecretsError> {
+        if nonce.len() != 12 {
+            return Err(SecretsError::Decryption(protocol.to_string()));
+        }
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload { msg: ciphertext, aad: protocol.as_bytes() },
+            )
+            .map_err(|_| SecretsError::Decryption(protocol.to_string()))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    /// Encrypt and store an account's credentials, replacing any existing ones
+    pub async fn store(
+        &self,
+        pool: &SqlitePool,
+        account_id: i64,
+        protocol: &str,
+        credentials: &Credentials,
+    ) -> Result<(), SecretsError> {
+        let (nonce, ciphertext) =
+            self.encrypt(&associated_data(account_id, protocol), credentials)?;
+        let fields: Vec<&String> = credentials.keys().collect();
+        let fields_json = serde_json::to_string(&fields)?;
+        queries::upsert_protocol_credentials(
+            pool,
+            account_id,
+            protocol,
+            &fields_json,
+            &nonce,
+            &ciphertext,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Load and decrypt the credentials of every protocol of an account
+    pub async fn load_all(
+        &self,
+        pool: &SqlitePool,
+        account_id: i64,
+    ) -> Result<HashMap<String, Credentials>, SecretsError> {
+        queries::list_protocol_credentials(pool, account_id)
+            .await?
+            .into_iter()
+            .map(|r| {
+                let credentials = self.decrypt(
+                    &associated_data(account_id, &r.protocol),
+                    &r.nonce,
+                    &r.ciphertext,
+                )?;
+                Ok((r.protocol, credentials))
+            })
+            .collect()
+    }
+}
+
+/// Associated data binding stored credentials to their account and protocol
+///
+/// The default account uses the bare protocol name so credentials stored
+/// before accounts existed still decrypt.
+fn associated_data(account_id: i64, protocol: &str) -> String {
+    if account_id == crate::db::DEFAULT_ACCOUNT_ID {
+        protocol.to_string()
+    } else {
+        format!("{}/{}", account_id, protocol)
+    }
+}
+
+/// Merge decrypted credentials into `[protocols.<name>]` config sections
+///
+/// Vault values take precedence over values in the config file. Credentials
+/// for protocols without a config section are ignored.
+pub fn apply_credentials(protocols: &mut toml::Table, credentials: &HashMap<String, Credentials>) {
+    for (protocol, values) in credentials {
+        let Some(section) = protocols.get_mut(protocol).and_then(|s| s.as_table_mut()) else {
+            continue;
+        };
+        for (field, value) in values {
+            section.insert(field.clone(), toml::Value::String(value.clone()));
+        }
+    }
+}
+
+/// Credential fields set in plaintext in the config file, as `protocol.field`
+pub fn plaintext_secret_fields(protocols: &toml::Table) -> Vec<String> {
+    let mut found = Vec::new();
+    for (protocol, section) in protocols {
+        let Some(table) = section.as_table() else {
+            continue;
+        };
+        for field in SECRET_FIELDS {
+            if table.get(*field).and_then(|v| v.as_str()).is_some_and(|v| !v.is_empty()) {
+                found.push(format!("{}.{}", protocol, field));
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(token: &str) -> Credentials {
+        Credentials::from([("auth_token".to_string(), token.to_string())])
+    }
+
+    #[test]
+    fn test_encrypt_roundtrip_is_bound_to_protocol() {
+        let vault = SecretsVault::new(&[7u8; 32]);
+        let (nonce, ciphertext) = vault.encrypt("grass", &credentials("tok")).unwrap();
+
+        assert!(!ciphertext.windows(3).any(|w| w == b"tok"));
+        assert_eq!(vault.decrypt("grass", &nonce, &ciphertext).unwrap(), credentials("tok"));
+        assert!(vault.decrypt("streamr", &nonce, &ciphertext).is_err());
+        assert!(SecretsVault::new(&[8u8; 32]).decrypt("grass", &nonce, &ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_master_key_must_be_32_hex_bytes() {
+        assert!(SecretsVault::from_hex(&"ab".repeat(32)).is_ok());
+        assert!(SecretsVault::from_hex("abcd").is_err());
+        assert!(SecretsVault::from_hex("not hex").is_err());
+    }
+
+    #[test]
+    fn test_apply_credentials_and_plaintext_detection() {
+        let mut protocols: toml::Table = toml::from_str(
+            r#"
+            [grass]
+            auth_token = "plaintext"
+            [storj]
+            api_endpoint = "http://localhost:14002"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(plaintext_secret_fields(&protocols), vec!["grass.auth_token"]);
+
+        let vaulted = HashMap::from([
+            ("grass".to_string(), credentials("from-vault")),
+            ("unknown".to_string(), credentials("ignored")),
+        ]);
+        apply_credentials(&mut protocols, &vaulted);
+
+        assert_eq!(protocols["grass"]["auth_token"].as_str(), Some("from-vault"));
+        assert!(!protocols.contains_key("unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load_all() {
+        let pool = crate::db::test_pool().await;
+
+        let vault = SecretsVault::new(&[1u8; 32]);
+        vault.store(&pool, 1, "grass", &credentials("old")).await.unwrap();
+        vault.store(&pool, 1, "grass", &credentials("new")).await.unwrap();
+
+        let loaded = vault.load_all(&pool, 1).await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded["grass"], credentials("new"));
+
+        // Accounts only see their own credentials
+        let other = queries::create_account(&pool, "tenant", "free").await.unwrap();
+        assert!(vault.load_all(&pool, other).await.unwrap().is_empty());
+        vault.store(&pool, other, "grass", &credentials("theirs")).await.unwrap();
+        assert_eq!(vault.load_all(&pool, other).await.unwrap()["grass"], credentials("theirs"));
+        assert_eq!(vault.load_all(&pool, 1).await.unwrap()["grass"], credentials("new"));
+
+        // A row moved to another account no longer decrypts
+        sqlx::query("UPDATE protocol_credentials SET account_id = ? WHERE account_id = 1")
+            .bind(other + 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(vault.load_all(&pool, other + 1).await.is_err());
+    }
+}
@@ -0,0 +1,487 @@
+/// Protocol Services
+///
+/// On bare-metal hosts, protocol daemons run as systemd units. Protocols
+/// mapped to a unit in `[services]` have it started when their adapter
+/// connects and stopped when it disconnects, can be started, stopped, and
+/// restarted through the API by operator keys, and report the unit's state in
+/// their health. Controlling system units needs root or polkit rights; set
+/// `sudo` to run `systemctl` through passwordless `sudo`, or `user_units` for
+/// units of the orchestrator's own user. With `dry_run`, the commands that
+/// would change a unit are logged instead of run.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::process::Command;
+
+use crate::protocols::http::HttpClientFactory;
+use crate::protocols::{
+    AllocationStrategy, ConnectionStatus, CredentialStatus, EarningsData, HealthStatus, MetricSchema, ProtocolAdapter,
+    ProtocolError, ProtocolResult, ResourceMetrics, ResourceProfile,
+};
+
+/// Unit properties read for a unit's status
+const STATUS_PROPERTIES: &str = "ActiveState,SubState,MainPID,NRestarts,ActiveEnterTimestamp";
+
+/// `systemctl`/`sudo` errors meaning the orchestrator lacks the rights to act
+const PERMISSION_ERRORS: [&str; 4] = [
+    "access denied",
+    "interactive authentication required",
+    "a password is required",
+    "permission denied",
+];
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// systemd settings (`[services]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceConfig {
+    /// Control the units below
+    pub enabled: bool,
+    /// Log commands that would start, stop, or restart a unit instead of running them
+    pub dry_run: bool,
+    /// Run `systemctl` through `sudo -n`
+    pub sudo: bool,
+    /// Control units of the orchestrator's user (`systemctl --user`)
+    pub user_units: bool,
+    /// `systemctl` binary
+    pub systemctl: String,
+    /// Unit name by protocol (`[services.units]`)
+    pub units: BTreeMap<String, String>,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dry_run: false,
+            sudo: false,
+            user_units: false,
+            systemctl: "systemctl".to_string(),
+            units: BTreeMap::new(),
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// Validate the section
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.systemctl.trim().is_empty() {
+            return Err("systemctl must name the systemctl binary".to_string());
+        }
+        if self.sudo && self.user_units {
+            return Err("sudo and user_units cannot both be set".to_string());
+        }
+        if let Some((protocol, _)) = self.units.iter().find(|(_, unit)| !is_unit_name(unit)) {
+            return Err(format!("units.{} must name a systemd unit", protocol));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `unit` is a plain unit name, which keeps it from being read as an option
+fn is_unit_name(unit: &str) -> bool {
+    !unit.is_empty()
+        && !unit.starts_with('-')
+        && unit.chars().all(|c| c.is_ascii_alphanumeric() || ":-_.@\\".contains(c))
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// systemd control errors
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    #[error("Protocol {0} does not run as a managed systemd unit")]
+    NotManaged(String),
+
+    #[error("Not permitted to run `{command}`: {message}")]
+    PermissionDenied { command: String, message: String },
+
+    #[error("`{command}` failed: {message}")]
+    CommandFailed { command: String, message: String },
+
+    #[error("Failed to run systemctl: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Lifecycle operation on a protocol's unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl ServiceAction {
+    /// `systemctl` verb
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+        }
+    }
+}
+
+impl FromStr for ServiceAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Self::Start),
+            "stop" => Ok(Self::Stop),
+            "restart" => Ok(Self::Restart),
+            other => Err(format!("Unknown service action {:?} (expected start, stop, or restart)", other)),
+        }
+    }
+}
+
+/// Result of a start, stop, or restart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceOutcome {
+    pub unit: String,
+    pub action: ServiceAction,
+    /// Command line that was (or, in dry-run mode, would have been) run
+    pub command: String,
+    /// False in dry-run mode
+    pub executed: bool,
+    pub status: ServiceStatus,
+}
+
+/// State of a protocol's unit as reported by systemd
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub unit: String,
+    /// active, inactive, failed, activating, ...
+    pub active_state: String,
+    /// running, dead, exited, auto-restart, ...
+    pub sub_state: String,
+    pub main_pid: Option<u32>,
+    pub restarts: Option<u32>,
+    pub active_since: Option<String>,
+}
+
+impl ServiceStatus {
+    /// Parse `systemctl show --property=...` output
+    pub fn parse(unit: &str, output: &str) -> Self {
+        let mut status = Self {
+            unit: unit.to_string(),
+            ..Self::default()
+        };
+        for (key, value) in output.lines().filter_map(|line| line.split_once('=')) {
+            let value = value.trim();
+            match key {
+                "ActiveState" => status.active_state = value.to_string(),
+                "SubState" => status.sub_state = value.to_string(),
+                "MainPID" => status.main_pid = value.parse().ok().filter(|pid| *pid > 0),
+                "NRestarts" => status.restarts = value.parse().ok(),
+                "ActiveEnterTimestamp" => status.active_since = Some(value.to_string()).filter(|v| !v.is_empty()),
+                _ => {}
+            }
+        }
+        status
+    }
+
+    /// Whether the unit is active
+    pub fn is_healthy(&self) -> bool {
+        self.active_state == "active"
+    }
+
+    /// Report this unit in an adapter's health
+    ///
+    /// An inactive unit makes the adapter unhealthy.
+    pub fn apply_to(&self, status: &mut HealthStatus) {
+        if !self.is_healthy() {
+            status.is_healthy = false;
+            if status.error_message.is_none() {
+                let state = format!("{} ({})", self.active_state, self.sub_state);
+                status.error_message = Some(format!("Unit {} is {}", self.unit, state));
+            }
+        }
+        if let Ok(value) = serde_json::to_value(self) {
+            status.metrics.insert("service".to_string(), value);
+        }
+    }
+}
+
+// ============================================================================
+// SERVICE MANAGER
+// ============================================================================
+
+/// `systemctl` driver for the units protocol daemons run as
+pub struct ServiceManager {
+    config: ServiceConfig,
+}
+
+impl ServiceManager {
+    /// Drive the units mapped in `config`
+    pub fn new(config: ServiceConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether `protocol` runs as a managed unit
+    pub fn manages(&self, protocol: &str) -> bool {
+        self.config.units.contains_key(protocol)
+    }
+
+    /// Unit `protocol` runs as
+    pub fn unit_for(&self, protocol: &str) -> Result<&str, ServiceError> {
+        self.config
+            .units
+            .get(protocol)
+            .map(String::as_str)
+            .ok_or_else(|| ServiceError::NotManaged(protocol.to_string()))
+    }
+
+    /// Start, stop, or restart `protocol`'s unit
+    pub async fn run(&self, protocol: &str, action: ServiceAction) -> Result<ServiceOutcome, ServiceError> {
+        let unit = self.unit_for(protocol)?;
+        let command = self.command_line(&[action.as_str(), unit]);
+        let executed = !self.config.dry_run;
+        if executed {
+            self.systemctl(&command).await?;
+            tracing::info!("Unit {} ({}): {}", unit, protocol, action.as_str());
+        } else {
+            tracing::info!("Dry run, not running: {}", command.join(" "));
+        }
+        Ok(ServiceOutcome {
+            unit: unit.to_string(),
+            action,
+            command: command.join(" "),
+            executed,
+            status: self.status(protocol).await?,
+        })
+    }
+
+    /// Current state of `protocol`'s unit
+    pub async fn status(&self, protocol: &str) -> Result<ServiceStatus, ServiceError> {
+        let unit = self.unit_for(protocol)?;
+        let property = format!("--property={}", STATUS_PROPERTIES);
+        let output = self.systemctl(&self.command_line(&["show", unit, &property])).await?;
+        Ok(ServiceStatus::parse(unit, &output))
+    }
+
+    /// `systemctl` invocation with `args`, through `sudo` or for user units as configured
+    fn command_line(&self, args: &[&str]) -> Vec<String> {
+        let mut command = Vec::new();
+        if self.config.sudo {
+            command.extend(["sudo".to_string(), "-n".to_string()]);
+        }
+        command.push(self.config.systemctl.clone());
+        if self.config.user_units {
+            command.push("--user".to_string());
+        }
+        command.push("--no-pager".to_string());
+        command.extend(args.iter().map(|arg| arg.to_string()));
+        command
+    }
+
+    /// Run `command`, returning its stdout
+    async fn systemctl(&self, command: &[String]) -> Result<String, ServiceError> {
+        let output = Command::new(&command[0]).args(&command[1..]).output().await?;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+        Err(command_error(command.join(" "), String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Error for `command` failing with `stderr`
+fn command_error(command: String, stderr: &str) -> ServiceError {
+    let message = stderr.to_string();
+    let lower = stderr.to_lowercase();
+    if PERMISSION_ERRORS.iter().any(|pattern| lower.contains(pattern)) {
+        ServiceError::PermissionDenied { command, message }
+    } else {
+        ServiceError::CommandFailed { command, message }
+    }
+}
+
+// ============================================================================
+// SERVICE ADAPTER
+// ============================================================================
+
+/// Adapter for a protocol whose daemon runs as a managed systemd unit
+///
+/// Starts the unit before connecting the wrapped adapter and stops it after
+/// disconnecting, and reports the unit's state in its health.
+pub struct ServiceAdapter {
+    inner: Box<dyn ProtocolAdapter>,
+    services: Arc<ServiceManager>,
+    /// Protocol instance the adapter is registered as, keying its `services` entry
+    instance: String,
+}
+
+impl ServiceAdapter {
+    /// Wrap `inner`, whose daemon runs as a unit managed by `services`, as
+    /// protocol instance `instance`
+    pub fn new(
+        inner: Box<dyn ProtocolAdapter>,
+        services: Arc<ServiceManager>,
+        instance: impl Into<String>,
+    ) -> Self {
+        Self { inner, services, instance: instance.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProtocolAdapter for ServiceAdapter {
+    fn protocol_name(&self) -> &str {
+        self.inner.protocol_name()
+    }
+
+    async fn connect(&mut self) -> ProtocolResult<()> {
+        self.services
+            .run(&self.instance, ServiceAction::Start)
+            .await
+            .map_err(|e| ProtocolError::ConnectionError(e.to_string()))?;
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> ProtocolResult<()> {
+        self.inner.disconnect().await?;
+        self.services
+            .run(&self.instance, ServiceAction::Stop)
+            .await
+            .map_err(|e| ProtocolError::ConnectionError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        self.inner.connection_status()
+    }
+
+    async fn get_current_earnings(&self) -> ProtocolResult<EarningsData> {
+        self.inner.get_current_earnings().await
+    }
+
+    async fn get_historical_earnings(&self, hours: u32) -> ProtocolResult<Vec<EarningsData>> {
+        self.inner.get_historical_earnings(hours).await
+    }
+
+    async fn get_resource_usage(&self) -> ProtocolResult<ResourceMetrics> {
+        self.inner.get_resource_usage().await
+    }
+
+    async fn apply_allocation(&mut self, strategy: AllocationStrategy) -> ProtocolResult<()> {
+        self.inner.apply_allocation(strategy).await
+    }
+
+    async fn get_current_allocation(&self) -> ProtocolResult<AllocationStrategy> {
+        self.inner.get_current_allocation().await
+    }
+
+    async fn health_check(&self) -> ProtocolResult<HealthStatus> {
+        let mut status = self.inner.health_check().await?;
+        match self.services.status(&self.instance).await {
+            Ok(service) => service.apply_to(&mut status),
+            Err(e) => {
+                tracing::warn!("Failed to read unit status of {}: {}", self.instance, e);
+                status.metrics.insert("service_error".to_string(), e.to_string().into());
+            }
+        }
+        Ok(status)
+    }
+
+    async fn check_credentials(&self) -> ProtocolResult<CredentialStatus> {
+        self.inner.check_credentials().await
+    }
+
+    fn configure_http(&mut self, http: &HttpClientFactory, protocol: &str) -> ProtocolResult<()> {
+        self.inner.configure_http(http, protocol)
+    }
+
+    fn get_config(&self) -> serde_json::Value {
+        self.inner.get_config()
+    }
+
+    fn resource_profile(&self) -> ResourceProfile {
+        self.inner.resource_profile()
+    }
+
+    fn metric_schema(&self) -> Vec<MetricSchema> {
+        self.inner.metric_schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::basic_health_status;
+
+    fn config(toml: &str) -> ServiceConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_status_parsed_into_adapter_health() {
+        let output = "ActiveState=failed\nSubState=failed\nMainPID=0\nNRestarts=4\nActiveEnterTimestamp=\n";
+        let service = ServiceStatus::parse("storagenode.service", output);
+        assert_eq!(service.main_pid, None);
+        assert_eq!(service.restarts, Some(4));
+        assert_eq!(service.active_since, None);
+
+        let mut status = basic_health_status(true, ConnectionStatus::Connected, None);
+        service.apply_to(&mut status);
+        assert!(!status.is_healthy);
+        assert_eq!(status.error_message.as_deref(), Some("Unit storagenode.service is failed (failed)"));
+        assert_eq!(status.metrics["service"]["restarts"], 4);
+
+        let running = ServiceStatus::parse("golem.service", "ActiveState=active\nSubState=running\nMainPID=812\n");
+        assert!(running.is_healthy());
+        assert_eq!(running.main_pid, Some(812));
+    }
+
+    #[test]
+    fn test_command_line_and_permission_errors() {
+        let units = "[units]\nstorj = \"storagenode.service\"\n";
+        let sudo = ServiceManager::new(config(&format!("sudo = true\n{}", units)));
+        assert_eq!(
+            sudo.command_line(&["restart", "storagenode.service"]).join(" "),
+            "sudo -n systemctl --no-pager restart storagenode.service"
+        );
+        let user = ServiceManager::new(config(&format!("user_units = true\n{}", units)));
+        assert_eq!(user.command_line(&["stop", "x"]).join(" "), "systemctl --user --no-pager stop x");
+        assert!(matches!(user.unit_for("golem"), Err(ServiceError::NotManaged(_))));
+
+        let denied = command_error("systemctl stop x".to_string(), "Failed to stop x: Access denied");
+        assert!(matches!(denied, ServiceError::PermissionDenied { .. }));
+        let sudo_denied = command_error("sudo -n systemctl stop x".to_string(), "sudo: a password is required");
+        assert!(matches!(sudo_denied, ServiceError::PermissionDenied { .. }));
+        let failed = command_error("systemctl start x".to_string(), "Unit x.service not found.");
+        assert!(matches!(failed, ServiceError::CommandFailed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_only_reads_status() {
+        // `true` accepts any arguments and prints nothing, standing in for systemctl
+        let manager = ServiceManager::new(config(
+            "enabled = true\ndry_run = true\nsystemctl = \"true\"\n[units]\nstorj = \"storagenode.service\"\n",
+        ));
+        let outcome = manager.run("storj", ServiceAction::Restart).await.unwrap();
+        assert!(!outcome.executed);
+        assert_eq!(outcome.command, "true --no-pager restart storagenode.service");
+        assert_eq!(outcome.status.unit, "storagenode.service");
+    }
+
+    #[test]
+    fn test_service_config_validation() {
+        assert!(ServiceConfig::default().validate().is_ok());
+        let valid = config("enabled = true\n[units]\nstorj = \"storagenode.service\"\ngolem = \"golem@1\"\n");
+        assert!(valid.validate().is_ok());
+        let option = config("enabled = true\n[units]\nstorj = \"--now\"\n");
+        assert!(option.validate().unwrap_err().contains("units.storj"));
+        let both = config("enabled = true\nsudo = true\nuser_units = true\n");
+        assert!(both.validate().is_err());
+    }
+}
@@ -0,0 +1,143 @@
+/// Graceful Shutdown Coordination
+///
+/// `Shutdown` broadcasts a one-shot stop signal to every background loop
+/// through cloneable `ShutdownToken`s, and tracks the spawned tasks so the
+/// binary can wait for them to finish their current work — bounded by a
+/// timeout — before exiting.
+use std::sync::Mutex;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+/// Receiving side of the shutdown signal
+#[derive(Debug, Clone)]
+pub struct ShutdownToken {
+    /// `None` for a token that is never triggered
+    rx: Option<watch::Receiver<bool>>,
+}
+
+impl ShutdownToken {
+    /// A token that is never triggered
+    pub fn never() -> Self {
+        Self { rx: None }
+    }
+
+    /// Whether shutdown has started
+    pub fn is_triggered(&self) -> bool {
+        self.rx.as_ref().is_some_and(|rx| *rx.borrow())
+    }
+
+    /// Resolve once shutdown starts
+    pub async fn cancelled(&self) {
+        match &self.rx {
+            Some(rx) => {
+                // An error means the `Shutdown` was dropped, which also ends the run
+                let _ = rx.clone().wait_for(|triggered| *triggered).await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// Owner of the shutdown signal and the tasks that honour it
+#[derive(Debug)]
+pub struct Shutdown {
+    tx: watch::Sender<bool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    /// Create an untriggered shutdown signal
+    pub fn new() -> Self {
+        Self {
+            tx: watch::channel(false).0,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Token for a task that must stop on shutdown
+    pub fn token(&self) -> ShutdownToken {
+        ShutdownToken {
+            rx: Some(self.tx.subscribe()),
+        }
+    }
+
+    /// Wait for `handle` during [`drain`](Self::drain)
+    pub fn track(&self, handle: JoinHandle<()>) {
+        self.tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(handle);
+    }
+
+    /// Signal every token
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    /// Signal shutdown and wait up to `timeout` for tracked tasks to finish
+    ///
+    /// Tasks still running at the deadline are aborted. Returns `false` if
+    /// any had to be aborted.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.trigger();
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap_or_else(|e| e.into_inner()));
+
+        let all_done = tokio::time::timeout(timeout, async {
+            for task in tasks.iter_mut() {
+                let _ = task.await;
+            }
+        })
+        .await
+        .is_ok();
+
+        if !all_done {
+            for task in &tasks {
+                task.abort();
+            }
+        }
+        all_done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_tracked_tasks() {
+        let shutdown = Shutdown::new();
+        let token = shutdown.token();
+        assert!(!token.is_triggered());
+
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        shutdown.track(tokio::spawn(async move {
+            token.cancelled().await;
+            let _ = done_tx.send(());
+        }));
+
+        assert!(shutdown.drain(Duration::from_secs(1)).await);
+        assert!(done_rx.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_drain_aborts_tasks_past_the_timeout() {
+        let shutdown = Shutdown::new();
+        shutdown.track(tokio::spawn(std::future::pending::<()>()));
+
+        assert!(!shutdown.drain(Duration::from_millis(20)).await);
+    }
+
+    #[tokio::test]
+    async fn test_never_token_stays_untriggered() {
+        let token = ShutdownToken::never();
+        let waited = tokio::time::timeout(Duration::from_millis(20), token.cancelled()).await;
+        assert!(waited.is_err());
+    }
+}
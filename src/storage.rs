@@ -0,0 +1,118 @@
+/// Object Storage
+///
+/// Uploads and downloads files to an S3-compatible bucket (AWS S3, MinIO,
+/// R2, ...) for metrics archives and database backups. The client is only
+/// built with the `s3-storage` feature; without it every call fails with
+/// [`StorageError::Disabled`] and configurations naming a bucket are rejected
+/// at startup.
+use serde::{Deserialize, Serialize};
+
+/// S3-compatible bucket; credentials come from the standard `AWS_*` variables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    pub bucket: String,
+    /// Endpoint URL for non-AWS stores (MinIO, R2, ...)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Key prefix for stored objects
+    #[serde(default)]
+    pub prefix: String,
+}
+
+/// Object storage errors
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("S3 storage requires the s3-storage feature")]
+    Disabled,
+
+    #[cfg(feature = "s3-storage")]
+    #[error("Object storage error: {0}")]
+    Store(#[from] object_store::Error),
+}
+
+impl S3Config {
+    /// Fail unless this build can talk to S3
+    pub fn check_supported(&self) -> Result<(), StorageError> {
+        if cfg!(feature = "s3-storage") {
+            Ok(())
+        } else {
+            Err(StorageError::Disabled)
+        }
+    }
+
+    /// Full object key of `name`
+    pub fn key(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    /// Store `contents` as `name` under the prefix
+    pub async fn upload(&self, name: &str, contents: Vec<u8>) -> Result<(), StorageError> {
+        #[cfg(feature = "s3-storage")]
+        {
+            use object_store::ObjectStore;
+
+            let key = object_store::path::Path::from(self.key(name));
+            self.store()?.put(&key, contents.into()).await?;
+            Ok(())
+        }
+        #[cfg(not(feature = "s3-storage"))]
+        {
+            let _ = (name, contents);
+            Err(StorageError::Disabled)
+        }
+    }
+
+    /// Fetch object `name` from under the prefix
+    pub async fn download(&self, name: &str) -> Result<Vec<u8>, StorageError> {
+        #[cfg(feature = "s3-storage")]
+        {
+            use object_store::ObjectStore;
+
+            let key = object_store::path::Path::from(self.key(name));
+            let bytes = self.store()?.get(&key).await?.bytes().await?;
+            Ok(bytes.to_vec())
+        }
+        #[cfg(not(feature = "s3-storage"))]
+        {
+            let _ = name;
+            Err(StorageError::Disabled)
+        }
+    }
+
+    #[cfg(feature = "s3-storage")]
+    fn store(&self) -> Result<object_store::aws::AmazonS3, StorageError> {
+        use object_store::aws::AmazonS3Builder;
+
+        let mut builder = AmazonS3Builder::from_env().with_bucket_name(&self.bucket);
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"));
+        }
+        if let Some(region) = &self.region {
+            builder = builder.with_region(region);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_uses_prefix() {
+        let config: S3Config = toml::from_str(
+            r#"
+            bucket = "orcha"
+            prefix = "node-1/"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.key("backup.db"), "node-1/backup.db");
+        assert!(config.endpoint.is_none());
+        assert_eq!(config.check_supported().is_ok(), cfg!(feature = "s3-storage"));
+    }
+}
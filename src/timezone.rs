@@ -0,0 +1,220 @@
+/// Reporting Time Zone
+///
+/// Daily and weekly digests, daily earnings exports, report timestamps and
+/// retention cutoffs follow calendar days in the zone configured with
+/// `[reporting] timezone`, so "yesterday" ends at the operator's midnight
+/// rather than UTC's. Days are resolved through the zone's rules, so across
+/// a DST change a day is 23 or 25 hours long.
+///
+/// Named zones such as `"Europe/Berlin"` use the IANA rules bundled through
+/// chrono-tz; `"local"` uses the zone of the process, which follows the `TZ`
+/// environment variable or else `/etc/localtime`.
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Reporting settings (`[reporting]` section)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportingConfig {
+    /// Zone calendar days are counted in (default: UTC)
+    pub timezone: ReportingTimezone,
+}
+
+/// Zone reporting days are counted in
+///
+/// Written as `"UTC"`, an IANA name such as `"America/New_York"`, `"local"`,
+/// or a fixed offset such as `"+05:30"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ReportingTimezone {
+    #[default]
+    Utc,
+    /// IANA zone, with its DST rules
+    Named(Tz),
+    /// Zone of the host, with its DST rules
+    Local,
+    /// Fixed offset from UTC, without DST
+    Fixed(FixedOffset),
+}
+
+impl FromStr for ReportingTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            z if z.eq_ignore_ascii_case("utc") || z == "Z" => Ok(ReportingTimezone::Utc),
+            z if z.eq_ignore_ascii_case("local") => Ok(ReportingTimezone::Local),
+            z => {
+                if let Ok(tz) = z.parse::<Tz>() {
+                    return Ok(ReportingTimezone::Named(tz));
+                }
+                z.parse::<FixedOffset>().map(ReportingTimezone::Fixed).map_err(|_| {
+                    format!(
+                        "unknown time zone {:?}: use \"UTC\", an IANA name such as \"Europe/Berlin\", \"local\" \
+                         or an offset such as \"+02:00\"",
+                        z
+                    )
+                })
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for ReportingTimezone {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ReportingTimezone> for String {
+    fn from(tz: ReportingTimezone) -> Self {
+        tz.to_string()
+    }
+}
+
+impl fmt::Display for ReportingTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportingTimezone::Utc => f.write_str("UTC"),
+            ReportingTimezone::Named(tz) => f.write_str(tz.name()),
+            ReportingTimezone::Local => f.write_str("local"),
+            ReportingTimezone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+// ============================================================================
+// DAYS
+// ============================================================================
+
+impl ReportingTimezone {
+    /// Calendar day `at` falls on
+    pub fn date_of(&self, at: DateTime<Utc>) -> NaiveDate {
+        match self {
+            ReportingTimezone::Utc => at.date_naive(),
+            ReportingTimezone::Named(tz) => at.with_timezone(tz).date_naive(),
+            ReportingTimezone::Local => at.with_timezone(&Local).date_naive(),
+            ReportingTimezone::Fixed(offset) => at.with_timezone(offset).date_naive(),
+        }
+    }
+
+    /// Instant the wall clock shows `time` on `day`
+    ///
+    /// A time skipped by a DST change resolves to the first valid time after
+    /// it, and a repeated one to its first occurrence.
+    pub fn at(&self, day: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+        let local = day.and_time(time);
+        match self {
+            ReportingTimezone::Utc => local.and_utc(),
+            ReportingTimezone::Named(tz) => resolve(tz, local),
+            ReportingTimezone::Local => resolve(&Local, local),
+            ReportingTimezone::Fixed(offset) => resolve(offset, local),
+        }
+    }
+
+    /// Midnight starting `day`
+    pub fn start_of_day(&self, day: NaiveDate) -> DateTime<Utc> {
+        self.at(day, NaiveTime::MIN)
+    }
+
+    /// `[start, end)` of the days `from` through `to`
+    pub fn days(&self, from: NaiveDate, to: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.start_of_day(from), self.start_of_day(to + Duration::days(1)))
+    }
+
+    /// Length of `day` in hours: 24, or 23 or 25 across a DST change
+    pub fn hours_in_day(&self, day: NaiveDate) -> f64 {
+        let (start, end) = self.days(day, day);
+        (end - start).num_seconds() as f64 / 3600.0
+    }
+
+    /// `at` as `YYYY-MM-DD HH:MM` followed by the zone
+    pub fn format(&self, at: &DateTime<Utc>) -> String {
+        match self {
+            ReportingTimezone::Utc => at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            ReportingTimezone::Named(tz) => at.with_timezone(tz).format("%Y-%m-%d %H:%M %Z").to_string(),
+            ReportingTimezone::Local => at.with_timezone(&Local).format("%Y-%m-%d %H:%M %:z").to_string(),
+            ReportingTimezone::Fixed(offset) => at.with_timezone(offset).format("%Y-%m-%d %H:%M %:z").to_string(),
+        }
+    }
+}
+
+/// Map a wall clock time in `tz` to UTC, moving past DST gaps
+fn resolve<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    // Gaps are at most a couple of hours; step through them in 15 minutes
+    (0..=12)
+        .find_map(|step| tz.from_local_datetime(&(local + Duration::minutes(15 * step))).earliest())
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("UTC".parse::<ReportingTimezone>().unwrap(), ReportingTimezone::Utc);
+        assert_eq!("local".parse::<ReportingTimezone>().unwrap(), ReportingTimezone::Local);
+        let tz: ReportingTimezone = "+05:30".parse().unwrap();
+        assert_eq!(tz, ReportingTimezone::Fixed(FixedOffset::east_opt(5 * 3600 + 1800).unwrap()));
+        assert_eq!(tz.to_string(), "+05:30");
+        let berlin: ReportingTimezone = "Europe/Berlin".parse().unwrap();
+        assert_eq!(berlin, ReportingTimezone::Named(chrono_tz::Europe::Berlin));
+        assert_eq!(berlin.to_string(), "Europe/Berlin");
+        assert!("Mars/Olympus".parse::<ReportingTimezone>().unwrap_err().contains("IANA name"));
+
+        let config: ReportingConfig = toml::from_str("timezone = \"-08:00\"").unwrap();
+        assert_eq!(config.timezone.to_string(), "-08:00");
+        assert!(toml::from_str::<ReportingConfig>("timezone = \"Mars\"").is_err());
+    }
+
+    #[test]
+    fn test_days_follow_the_zone() {
+        let utc = ReportingTimezone::Utc;
+        assert_eq!(utc.start_of_day(day(14)), Utc.with_ymd_and_hms(2026, 10, 14, 0, 0, 0).unwrap());
+
+        let tokyo: ReportingTimezone = "+09:00".parse().unwrap();
+        let (start, end) = tokyo.days(day(14), day(15));
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 10, 13, 15, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2026, 10, 15, 15, 0, 0).unwrap());
+        // 20:00 UTC is already the next day in Tokyo
+        assert_eq!(tokyo.date_of(Utc.with_ymd_and_hms(2026, 10, 14, 20, 0, 0).unwrap()), day(15));
+        assert_eq!(tokyo.hours_in_day(day(14)), 24.0);
+        assert_eq!(tokyo.format(&start), "2026-10-14 00:00 +09:00");
+
+        // Whatever the host zone, its days start on the day they belong to
+        let local = ReportingTimezone::Local;
+        assert_eq!(local.date_of(local.start_of_day(day(14))), day(14));
+    }
+
+    #[test]
+    fn test_days_across_dst() {
+        let berlin: ReportingTimezone = "Europe/Berlin".parse().unwrap();
+
+        // Clocks go back on 2026-10-25: the day is 25 hours long
+        assert_eq!(berlin.hours_in_day(day(25)), 25.0);
+        assert_eq!(berlin.start_of_day(day(25)), Utc.with_ymd_and_hms(2026, 10, 24, 22, 0, 0).unwrap());
+        assert_eq!(berlin.start_of_day(day(26)), Utc.with_ymd_and_hms(2026, 10, 25, 23, 0, 0).unwrap());
+
+        // Clocks go forward on 2026-03-29: 02:30 does not exist and moves to 03:00
+        let march = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
+        assert_eq!(berlin.hours_in_day(march), 23.0);
+        let skipped = berlin.at(march, NaiveTime::from_hms_opt(2, 30, 0).unwrap());
+        assert_eq!(skipped, Utc.with_ymd_and_hms(2026, 3, 29, 1, 0, 0).unwrap());
+        assert_eq!(berlin.format(&skipped), "2026-03-29 03:00 CEST");
+    }
+}
@@ -0,0 +1,604 @@
+/// Wallet Balance Tracking
+///
+/// Tracks the payout addresses that DePIN protocols pay rewards into and polls
+/// their on-chain balances over JSON-RPC. Stored balances let operators
+/// cross-check the orchestrator's earnings estimates against funds actually
+/// received.
+///
+/// Supported chains:
+/// - Ethereum and Polygon (`eth_getBalance`, ERC-20 `balanceOf`)
+/// - Solana (`getBalance`, SPL `getTokenAccountsByOwner`)
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+/// ERC-20 `balanceOf(address)` function selector
+const ERC20_BALANCE_OF_SELECTOR: &str = "70a08231";
+
+/// Characters of the base58 alphabet used by Solana addresses
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// ============================================================================
+// CHAINS
+// ============================================================================
+
+/// Blockchain a payout address lives on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Chain {
+    Ethereum,
+    Solana,
+    Polygon,
+}
+
+impl Chain {
+    /// Native token symbol
+    pub fn native_symbol(&self) -> &'static str {
+        match self {
+            Chain::Ethereum => "ETH",
+            Chain::Solana => "SOL",
+            Chain::Polygon => "POL",
+        }
+    }
+
+    /// Decimals of the native token
+    pub fn native_decimals(&self) -> u32 {
+        match self {
+            Chain::Ethereum | Chain::Polygon => 18,
+            Chain::Solana => 9,
+        }
+    }
+
+    /// Whether the chain speaks the Ethereum JSON-RPC dialect
+    pub fn is_evm(&self) -> bool {
+        matches!(self, Chain::Ethereum | Chain::Polygon)
+    }
+
+    /// Whether `address` is well-formed for this chain: `0x` and 40 hex
+    /// digits on EVM chains, 32-44 base58 characters on Solana
+    pub fn is_valid_address(&self, address: &str) -> bool {
+        if self.is_evm() {
+            return address
+                .strip_prefix("0x")
+                .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+        (32..=44).contains(&address.len()) && address.chars().all(|c| BASE58_ALPHABET.contains(c))
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chain::Ethereum => write!(f, "ethereum"),
+            Chain::Solana => write!(f, "solana"),
+            Chain::Polygon => write!(f, "polygon"),
+        }
+    }
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// A tracked payout address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletConfig {
+    /// Human-readable label
+    pub label: String,
+    /// Chain the address lives on
+    pub chain: Chain,
+    /// Address (hex for EVM chains, base58 for Solana)
+    pub address: String,
+    /// Protocol paying into this address, if any
+    #[serde(default)]
+    pub protocol: Option<String>,
+    /// Token contract (ERC-20) or mint (SPL); native balance when unset
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Token symbol reported alongside the balance
+    #[serde(default)]
+    pub token_symbol: Option<String>,
+    /// Token decimals (ERC-20 only; SPL decimals come from the RPC response)
+    #[serde(default)]
+    pub token_decimals: Option<u32>,
+}
+
+impl WalletConfig {
+    /// Symbol of the tracked asset
+    pub fn symbol(&self) -> String {
+        match (&self.token, &self.token_symbol) {
+            (_, Some(symbol)) => symbol.clone(),
+            (Some(token), None) => token.clone(),
+            (None, None) => self.chain.native_symbol().to_string(),
+        }
+    }
+}
+
+/// Wallet tracking configuration (`[wallets]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletsConfig {
+    /// Ethereum JSON-RPC endpoint
+    pub ethereum_rpc_url: String,
+    /// Solana JSON-RPC endpoint
+    pub solana_rpc_url: String,
+    /// Polygon JSON-RPC endpoint
+    pub polygon_rpc_url: String,
+    /// Balance polling interval in seconds
+    pub poll_interval_secs: u64,
+    /// RPC request timeout in seconds
+    pub request_timeout_secs: u64,
+    /// Asset prices used for USD conversion, keyed by symbol
+    #[serde(default)]
+    pub prices_usd: HashMap<String, f64>,
+    /// Tracked payout addresses
+    #[serde(default)]
+    pub addresses: Vec<WalletConfig>,
+}
+
+impl Default for WalletsConfig {
+    fn default() -> Self {
+        Self {
+            ethereum_rpc_url: "https://cloudflare-eth.com".to_string(),
+            solana_rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+            polygon_rpc_url: "https://polygon-rpc.com".to_string(),
+            poll_interval_secs: 3600,
+            request_timeout_secs: 15,
+            prices_usd: HashMap::new(),
+            addresses: Vec::new(),
+        }
+    }
+}
+
+impl WalletsConfig {
+    /// Load from the `[wallets]` section of the config file, falling back to defaults
+    pub fn from_table(config: &toml::Table) -> Result<Self, WalletError> {
+        let Some(section) = config.get("wallets") else {
+            return Ok(Self::default());
+        };
+
+        let mut merged = toml::Value::try_from(Self::default())
+            .map_err(|e| WalletError::Config(e.to_string()))?;
+        if let (Some(base), Some(overrides)) = (merged.as_table_mut(), section.as_table()) {
+            for (key, value) in overrides {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+
+        let config: Self = merged
+            .try_into()
+            .map_err(|e: toml::de::Error| WalletError::Config(e.to_string()))?;
+        if let Some(wallet) = config.addresses.iter().find(|w| !w.chain.is_valid_address(&w.address)) {
+            return Err(WalletError::Config(format!(
+                "{} address {:?} ({}) is not a valid {} address",
+                wallet.chain, wallet.address, wallet.label, wallet.chain
+            )));
+        }
+        Ok(config)
+    }
+
+    /// JSON-RPC endpoint for a chain
+    pub fn rpc_url(&self, chain: Chain) -> &str {
+        match chain {
+            Chain::Ethereum => &self.ethereum_rpc_url,
+            Chain::Solana => &self.solana_rpc_url,
+            Chain::Polygon => &self.polygon_rpc_url,
+        }
+    }
+}
+
+// ============================================================================
+// ERRORS AND RESULTS
+// ============================================================================
+
+/// Wallet tracking errors
+#[derive(Error, Debug)]
+pub enum WalletError {
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("RPC request failed: {0}")]
+    Network(String),
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("Failed to parse RPC response: {0}")]
+    Parse(String),
+}
+
+/// Balance observed for a tracked address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletBalance {
+    pub label: String,
+    pub chain: Chain,
+    pub address: String,
+    pub protocol: Option<String>,
+    pub symbol: String,
+    pub balance: f64,
+    pub balance_usd: Option<f64>,
+    pub polled_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// TRACKER
+// ============================================================================
+
+/// Polls configured payout addresses for their balances
+pub struct WalletTracker {
+    config: WalletsConfig,
+    client: reqwest::Client,
+}
+
+impl WalletTracker {
+    /// Create tracker
+    pub fn new(config: WalletsConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self { config, client }
+    }
+
+    /// Tracker configuration
+    pub fn config(&self) -> &WalletsConfig {
+        &self.config
+    }
+
+    /// Whether any addresses are configured
+    pub fn is_empty(&self) -> bool {
+        self.config.addresses.is_empty()
+    }
+
+    /// Poll every configured address, returning balances that could be fetched
+    pub async fn poll_all(&self) -> Vec<Result<WalletBalance, (String, WalletError)>> {
+        let mut results = Vec::with_capacity(self.config.addresses.len());
+        for wallet in &self.config.addresses {
+            results.push(
+                self.poll_wallet(wallet)
+                    .await
+                    .map_err(|e| (wallet.label.clone(), e)),
+            );
+        }
+        results
+    }
+
+    /// Poll a single address
+    pub async fn poll_wallet(&self, wallet: &WalletConfig) -> Result<WalletBalance, WalletError> {
+        let balance = self.fetch_balance(wallet).await?;
+        let symbol = wallet.symbol();
+        let balance_usd = self.config.prices_usd.get(&symbol).map(|price| balance * price);
+
+        Ok(WalletBalance {
+            label: wallet.label.clone(),
+            chain: wallet.chain,
+            address: wallet.address.clone(),
+            protocol: wallet.protocol.clone(),
+            symbol,
+            balance,
+            balance_usd,
+            polled_at: Utc::now(),
+        })
+    }
+
+    /// Fetch the balance of a tracked asset in whole tokens
+    async fn fetch_balance(&self, wallet: &WalletConfig) -> Result<f64, WalletError> {
+        let url = self.config.rpc_url(wallet.chain);
+
+        match (wallet.chain.is_evm(), &wallet.token) {
+            (true, None) => {
+                let result = self
+                    .rpc(url, "eth_getBalance", json!([wallet.address, "latest"]))
+                    .await?;
+                let raw = parse_hex_quantity(&result)?;
+                Ok(scale(raw, wallet.chain.native_decimals()))
+            }
+            (true, Some(contract)) => {
+                let data = format!(
+                    "0x{}{:0>64}",
+                    ERC20_BALANCE_OF_SELECTOR,
+                    wallet.address.trim_start_matches("0x").to_lowercase()
+                );
+                let result = self
+                    .rpc(
+                        url,
+                        "eth_call",
+                        json!([{ "to": contract, "data": data }, "latest"]),
+                    )
+                    .await?;
+                let raw = parse_hex_quantity(&result)?;
+                Ok(scale(raw, wallet.token_decimals.unwrap_or(18)))
+            }
+            (false, None) => {
+                let result = self.rpc(url, "getBalance", json!([wallet.address])).await?;
+                let lamports = result
+                    .get("value")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| WalletError::Parse("missing balance value".to_string()))?;
+                Ok(scale(lamports as u128, wallet.chain.native_decimals()))
+            }
+            (false, Some(mint)) => {
+                let result = self
+                    .rpc(
+                        url,
+                        "getTokenAccountsByOwner",
+                        json!([wallet.address, { "mint": mint }, { "encoding": "jsonParsed" }]),
+                    )
+                    .await?;
+                let accounts = result
+                    .get("value")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| WalletError::Parse("missing token accounts".to_string()))?;
+
+                Ok(accounts
+                    .iter()
+                    .filter_map(|account| {
+                        account
+                            .pointer("/account/data/parsed/info/tokenAmount/uiAmount")
+                            .and_then(Value::as_f64)
+                    })
+                    .sum())
+            }
+        }
+    }
+
+    /// Issue a JSON-RPC call and return its `result`
+    async fn rpc(&self, url: &str, method: &str, params: Value) -> Result<Value, WalletError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response = self
+            .client
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| WalletError::Network(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(WalletError::Network(format!(
+                "{} returned HTTP {}",
+                method,
+                response.status()
+            )));
+        }
+
+        let mut payload: Value = response
+            .json()
+            .await
+            .map_err(|e| WalletError::Parse(e.to_string()))?;
+
+        if let Some(error) = payload.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            return Err(WalletError::Rpc(format!("{}: {}", method, message)));
+        }
+
+        payload
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| WalletError::Parse(format!("{} response has no result", method)))
+    }
+}
+
+/// Parse an Ethereum hex quantity (`"0x1bc16d674ec80000"`)
+fn parse_hex_quantity(value: &Value) -> Result<u128, WalletError> {
+    let hex = value
+        .as_str()
+        .ok_or_else(|| WalletError::Parse("expected hex string".to_string()))?
+        .trim_start_matches("0x");
+    // eth_call returns 32-byte words; strip leading zeros so u128 can hold them
+    let trimmed = hex.trim_start_matches('0');
+    if trimmed.is_empty() {
+        return Ok(0);
+    }
+    u128::from_str_radix(trimmed, 16).map_err(|e| WalletError::Parse(e.to_string()))
+}
+
+/// Convert a raw integer amount into whole tokens
+fn scale(raw: u128, decimals: u32) -> f64 {
+    raw as f64 / 10f64.powi(decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn wallet(chain: Chain, token: Option<&str>) -> WalletConfig {
+        WalletConfig {
+            label: "payout".to_string(),
+            chain,
+            address: "0x00000000000000000000000000000000000000aa".to_string(),
+            protocol: Some("grass".to_string()),
+            token: token.map(str::to_string),
+            token_symbol: None,
+            token_decimals: None,
+        }
+    }
+
+    fn tracker(url: &str, addresses: Vec<WalletConfig>) -> WalletTracker {
+        let mut prices_usd = HashMap::new();
+        prices_usd.insert("ETH".to_string(), 2000.0);
+        WalletTracker::new(WalletsConfig {
+            ethereum_rpc_url: url.to_string(),
+            solana_rpc_url: url.to_string(),
+            polygon_rpc_url: url.to_string(),
+            prices_usd,
+            addresses,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_parse_hex_quantity() {
+        assert_eq!(parse_hex_quantity(&json!("0x0")).unwrap(), 0);
+        assert_eq!(parse_hex_quantity(&json!("0x1bc16d674ec80000")).unwrap(), 2_000_000_000_000_000_000);
+        assert_eq!(
+            parse_hex_quantity(&json!(format!("0x{:0>64}", "ff"))).unwrap(),
+            255
+        );
+        assert!(parse_hex_quantity(&json!(12)).is_err());
+    }
+
+    #[test]
+    fn test_config_from_table() {
+        let table: toml::Table = toml::from_str(
+            r#"
+            [wallets]
+            poll_interval_secs = 600
+            prices_usd = { SOL = 150.0 }
+
+            [[wallets.addresses]]
+            label = "helium"
+            chain = "solana"
+            address = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"
+            "#,
+        )
+        .unwrap();
+
+        let config = WalletsConfig::from_table(&table).unwrap();
+        assert_eq!(config.poll_interval_secs, 600);
+        assert_eq!(config.request_timeout_secs, 15);
+        assert_eq!(config.addresses.len(), 1);
+        assert_eq!(config.addresses[0].chain, Chain::Solana);
+        assert_eq!(config.addresses[0].symbol(), "SOL");
+
+        let empty = WalletsConfig::from_table(&toml::Table::new()).unwrap();
+        assert!(empty.addresses.is_empty());
+
+        let mistyped: toml::Table = toml::from_str(
+            r#"
+            [[wallets.addresses]]
+            label = "payout"
+            chain = "ethereum"
+            address = "0x00000000000000000000000000000000000000a"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(WalletsConfig::from_table(&mistyped), Err(WalletError::Config(_))));
+    }
+
+    #[test]
+    fn test_address_formats() {
+        assert!(Chain::Polygon.is_valid_address("0x00000000000000000000000000000000000000aA"));
+        assert!(!Chain::Ethereum.is_valid_address("00000000000000000000000000000000000000aa"));
+        assert!(!Chain::Ethereum.is_valid_address("0x00000000000000000000000000000000000000zz"));
+        assert!(Chain::Solana.is_valid_address("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"));
+        // 0, O, I and l are not base58
+        assert!(!Chain::Solana.is_valid_address("0xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"));
+        assert!(!Chain::Solana.is_valid_address("9xQeWvG8"));
+    }
+
+    #[tokio::test]
+    async fn test_poll_evm_native_balance() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(json!({ "method": "eth_getBalance" })))
+            .with_status(200)
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":"0x1bc16d674ec80000"}"#)
+            .create_async()
+            .await;
+
+        let tracker = tracker(&server.url(), vec![wallet(Chain::Ethereum, None)]);
+        let balance = tracker.poll_wallet(&tracker.config().addresses[0]).await.unwrap();
+
+        assert_eq!(balance.symbol, "ETH");
+        assert!((balance.balance - 2.0).abs() < 1e-9);
+        assert_eq!(balance.balance_usd, Some(4000.0));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_poll_erc20_balance() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(json!({ "method": "eth_call" })))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":"0x{:0>64}"}}"#,
+                "f4240"
+            ))
+            .create_async()
+            .await;
+
+        let mut usdc = wallet(Chain::Polygon, Some("0x3c499c542cef5e3811e1192ce70d8cc03d5c3359"));
+        usdc.token_symbol = Some("USDC".to_string());
+        usdc.token_decimals = Some(6);
+
+        let tracker = tracker(&server.url(), vec![usdc]);
+        let balance = tracker.poll_wallet(&tracker.config().addresses[0]).await.unwrap();
+
+        assert_eq!(balance.symbol, "USDC");
+        assert!((balance.balance - 1.0).abs() < 1e-9);
+        assert_eq!(balance.balance_usd, None);
+    }
+
+    #[tokio::test]
+    async fn test_poll_solana_balances() {
+        let mut server = mockito::Server::new_async().await;
+        let _native = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(json!({ "method": "getBalance" })))
+            .with_status(200)
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":1},"value":2500000000}}"#)
+            .create_async()
+            .await;
+        let _spl = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(json!({ "method": "getTokenAccountsByOwner" })))
+            .with_status(200)
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"value":[
+                    {"account":{"data":{"parsed":{"info":{"tokenAmount":{"uiAmount":12.5}}}}}},
+                    {"account":{"data":{"parsed":{"info":{"tokenAmount":{"uiAmount":7.5}}}}}}
+                ]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let tracker = tracker(
+            &server.url(),
+            vec![wallet(Chain::Solana, None), wallet(Chain::Solana, Some("hntyVP6YFm1Hg25TN9WGLqM12b8TQmcknKrdu1oxWux"))],
+        );
+        let results = tracker.poll_all().await;
+
+        let native = results[0].as_ref().unwrap();
+        assert!((native.balance - 2.5).abs() < 1e-9);
+        let spl = results[1].as_ref().unwrap();
+        assert!((spl.balance - 20.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_error_is_reported() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid address"}}"#)
+            .create_async()
+            .await;
+
+        let tracker = tracker(&server.url(), vec![wallet(Chain::Ethereum, None)]);
+        let results = tracker.poll_all().await;
+
+        match &results[0] {
+            Err((label, WalletError::Rpc(message))) => {
+                assert_eq!(label, "payout");
+                assert!(message.contains("invalid address"));
+            }
+            other => panic!("expected RPC error, got {:?}", other.as_ref().map(|b| b.balance)),
+        }
+    }
+}